@@ -0,0 +1,110 @@
+//! Alerts of consensus-critical anomalies
+//!
+//! Alerts are posted to the configured webhooks as JSON:
+//!
+//! ```json
+//! {"kind": "bad_block", "severity": "critical", "message": "...", "timestamp": 1620000000000}
+//! ```
+//!
+//! A webhook only receives alerts at least as severe as its `min_severity`,
+//! delivery errors are logged and the alert is dropped.
+//!
+//! Alerts are addressed to the node operator, they are not the `Event`s of the gw-events crate
+//! pushed to the WebSocket subscriptions.
+
+use crate::runtime;
+use gw_config::{AlertConfig, AlertSeverity, AlertWebhookConfig};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// a synced block is invalid
+    BadBlock,
+    /// the local state diverges from the global state on L1
+    StateRootMismatch,
+    /// the rollup is halted by a challenge
+    RollupHalted,
+    /// the indexer is behind the CKB tip
+    IndexerLag,
+    /// failed to submit a block to L1
+    SubmissionFailure,
+    /// a produced block is replayed to a different state
+    ReplayMismatch,
+}
+
+impl AlertKind {
+    pub fn severity(self) -> AlertSeverity {
+        match self {
+            AlertKind::BadBlock
+            | AlertKind::StateRootMismatch
+            | AlertKind::RollupHalted
+            | AlertKind::ReplayMismatch => AlertSeverity::Critical,
+            AlertKind::IndexerLag | AlertKind::SubmissionFailure => AlertSeverity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Alert {
+    kind: AlertKind,
+    severity: AlertSeverity,
+    message: String,
+    timestamp: u64,
+}
+
+/// Send alerts to the webhooks, sends nothing if no webhook is configured
+#[derive(Clone, Default)]
+pub struct AlertSink {
+    client: reqwest::Client,
+    webhooks: Arc<Vec<AlertWebhookConfig>>,
+}
+
+impl AlertSink {
+    pub fn new(config: &AlertConfig) -> Self {
+        AlertSink {
+            client: reqwest::Client::new(),
+            webhooks: Arc::new(config.webhooks.clone()),
+        }
+    }
+
+    /// Log the alert and post it to the webhooks in background
+    pub fn send(&self, kind: AlertKind, message: String) {
+        let severity = kind.severity();
+        eprintln!("[alert] {:?} {:?}: {}", severity, kind, message);
+        let urls: Vec<String> = self
+            .webhooks
+            .iter()
+            .filter(|webhook| severity >= webhook.min_severity)
+            .map(|webhook| webhook.url.clone())
+            .collect();
+        if urls.is_empty() {
+            return;
+        }
+        let alert = Alert {
+            kind,
+            severity,
+            message,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+        };
+        let client = self.client.clone();
+        runtime::spawn("alert", async move {
+            for url in urls {
+                let result = client
+                    .post(&url)
+                    .json(&alert)
+                    .send()
+                    .await
+                    .and_then(|resp| resp.error_for_status());
+                if let Err(err) = result {
+                    eprintln!("[alert] post to {} error: {}", url, err);
+                }
+            }
+        });
+    }
+}
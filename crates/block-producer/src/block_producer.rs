@@ -1,11 +1,13 @@
 #![allow(clippy::clippy::mutable_key_type)]
 
+use crate::alert::{AlertKind, AlertSink};
 use crate::rpc_client::{DepositInfo, RPCClient};
 use crate::transaction_skeleton::TransactionSkeleton;
 use crate::utils::{fill_tx_fee, CKBGenesisInfo};
 use crate::wallet::Wallet;
 use crate::{
-    produce_block::{produce_block, ProduceBlockParam, ProduceBlockResult},
+    produce_block::{check_block_replay, produce_block, ProduceBlockParam, ProduceBlockResult},
+    runtime,
     types::{CellInfo, InputCellInfo},
 };
 use anyhow::{anyhow, Context, Result};
@@ -145,7 +147,9 @@ async fn complete_tx_skeleton(
     block: L2Block,
     global_state: GlobalState,
 ) -> Result<Transaction> {
-    let rollup_cell_info = smol::block_on(rpc_client.query_rollup_cell())?
+    let rollup_cell_info = rpc_client
+        .query_rollup_cell()
+        .await?
         .ok_or_else(|| anyhow!("can't find rollup cell"))?;
     let mut tx_skeleton = TransactionSkeleton::default();
     // rollup cell
@@ -274,6 +278,7 @@ pub struct BlockProducer {
     config: BlockProducerConfig,
     rpc_client: RPCClient,
     ckb_genesis_info: CKBGenesisInfo,
+    alert_sink: AlertSink,
 }
 
 impl BlockProducer {
@@ -287,6 +292,7 @@ impl BlockProducer {
         rpc_client: RPCClient,
         ckb_genesis_info: CKBGenesisInfo,
         config: BlockProducerConfig,
+        alert_sink: AlertSink,
     ) -> Result<Self> {
         let wallet = Wallet::from_config(&config.wallet_config).with_context(|| "init wallet")?;
 
@@ -300,13 +306,14 @@ impl BlockProducer {
             wallet,
             ckb_genesis_info,
             config,
+            alert_sink,
         };
         Ok(block_producer)
     }
 
     pub async fn poll_loop(&self) -> Result<()> {
         loop {
-            async_std::task::sleep(std::time::Duration::from_secs(45)).await;
+            runtime::sleep(std::time::Duration::from_secs(45)).await;
             self.produce_next_block().await?;
         }
     }
@@ -314,25 +321,33 @@ impl BlockProducer {
     pub async fn produce_next_block(&self) -> Result<()> {
         // TODO fix the default value
         let block_producer_id = 0;
-        let timestamp = 0;
 
         // get deposit cells
         let deposit_cells = self.rpc_client.query_deposit_cells().await?;
 
-        // get txs & withdrawal requests from mem pool
-        let mut txs = Vec::new();
-        let mut withdrawal_requests = Vec::new();
-        {
-            let mem_pool = self.mem_pool.lock();
-            for entry in mem_pool.pending().values() {
-                if let Some(withdrawal) = entry.withdrawals.first() {
-                    withdrawal_requests.push(withdrawal.clone());
-                } else {
-                    txs.extend(entry.txs.iter().cloned());
-                }
-            }
+        // get txs & withdrawal requests from mem pool,
+        // synthetic deposits of the dev mode follow the deposit cells
+        let (txs, withdrawal_requests, dev_deposits) = {
+            let mut mem_pool = self.mem_pool.lock();
+            let dev_deposits = mem_pool
+                .dev_deposits()
+                .map(|dev_deposits| dev_deposits.take_queued())
+                .unwrap_or_default();
+            let (txs, withdrawal_requests) = mem_pool.package();
+            (txs, withdrawal_requests, dev_deposits)
         };
+        let deposition_requests: Vec<_> = deposit_cells
+            .iter()
+            .map(|d| &d.request)
+            .cloned()
+            .chain(dev_deposits.iter().cloned())
+            .collect();
         let parent_block = self.chain.lock().local_state().tip().clone();
+        // read from the clock of the mem pool, which is controllable in dev mode
+        let timestamp = self
+            .mem_pool
+            .lock()
+            .next_block_timestamp(parent_block.raw().timestamp().unpack());
         let max_withdrawal_capacity = std::u128::MAX;
         // produce block
         let param = ProduceBlockParam {
@@ -341,7 +356,7 @@ impl BlockProducer {
             block_producer_id,
             timestamp,
             txs,
-            deposition_requests: deposit_cells.iter().map(|d| &d.request).cloned().collect(),
+            deposition_requests: deposition_requests.clone(),
             withdrawal_requests,
             parent_block: &parent_block,
             rollup_config_hash: &self.rollup_config_hash,
@@ -353,8 +368,30 @@ impl BlockProducer {
             global_state,
             unused_transactions,
             unused_withdrawal_requests,
+            access_lists,
         } = block_result;
         let number: u64 = block.raw().number().unpack();
+        // keep the access lists for replaying the block in parallel
+        {
+            let db = self.store.begin_transaction();
+            db.insert_block_access_lists(&block.hash().into(), &access_lists)?;
+            db.commit()?;
+        }
+        // the chain updater applies them again when the block is synced
+        if let Some(produced) = self.mem_pool.lock().dev_deposits() {
+            produced.insert_produced(block.hash().into(), dev_deposits);
+        }
+        if self.config.check_block_replay {
+            let db = self.store.begin_transaction();
+            if let Err(err) = check_block_replay(&db, &self.generator, &block, deposition_requests)
+            {
+                self.alert_sink.send(
+                    AlertKind::ReplayMismatch,
+                    format!("produced block #{} replay error: {}", number, err),
+                );
+                return Err(err);
+            }
+        }
         println!(
             "produce new block #{} (txs: {}, unused txs: {}, unused withdrawals: {})",
             number,
@@ -378,7 +415,13 @@ impl BlockProducer {
         .await?;
 
         // send transaction
-        self.rpc_client.send_transaction(tx).await?;
+        if let Err(err) = self.rpc_client.send_transaction(tx).await {
+            self.alert_sink.send(
+                AlertKind::SubmissionFailure,
+                format!("submit block #{} error: {}", number, err),
+            );
+            return Err(err);
+        }
         Ok(())
     }
 }
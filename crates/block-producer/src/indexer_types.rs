@@ -60,6 +60,12 @@ pub enum IOType {
     Output,
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct Tip {
+    pub block_hash: H256,
+    pub block_number: BlockNumber,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Pagination<T> {
     pub objects: Vec<T>,
@@ -1,8 +1,11 @@
+pub mod alert;
 pub mod block_producer;
 pub mod indexer_types;
 pub mod poller;
 pub mod produce_block;
 pub mod rpc_client;
+pub mod runtime;
+pub mod startup_check;
 pub mod transaction_skeleton;
 pub mod types;
 pub mod utils;
@@ -2,25 +2,47 @@ use anyhow::{anyhow, Context, Result};
 use async_jsonrpc_client::HttpClient;
 use futures::{select, FutureExt};
 use gw_block_producer::{
-    block_producer::BlockProducer, poller::ChainUpdater, rpc_client::RPCClient,
+    alert::{AlertKind, AlertSink},
+    block_producer::BlockProducer,
+    poller::ChainUpdater,
+    rpc_client::RPCClient,
+    runtime,
+    startup_check::StartupCheck,
     utils::CKBGenesisInfo,
 };
-use gw_chain::chain::Chain;
+use gw_chain::{chain::Chain, sync_progress::SyncProgress};
 use gw_config::Config;
 use gw_generator::{
-    account_lock_manage::AccountLockManage, backend_manage::BackendManage, genesis::init_genesis,
+    account_lock_manage::AccountLockManage,
+    backend_manage::BackendManage,
+    genesis::{init_genesis, load_builtin_accounts},
+    script_args::ScriptArgsRegistry,
     Generator, RollupContext,
 };
-use gw_mem_pool::pool::MemPool;
-use gw_rpc_server::{registry::Registry, server::start_jsonrpc_server};
-use gw_store::Store;
+use gw_mem_pool::{policy::DenyList, pool::MemPool};
+use gw_rpc_server::{
+    registry::Registry,
+    server::{resolve_listen_addrs, start_jsonrpc_server},
+};
+use gw_store::{
+    cold::{open_cold_db, ColdStoreMover},
+    migration::migrate,
+    mirror::{open_tmp_with_mirror, StoreMirrorChecker},
+    smt_gc::{SMTGCConfig, SMTGarbageCollector},
+    Store,
+};
 use gw_types::{
     packed::{RollupConfig, Script},
     prelude::*,
 };
 use parking_lot::Mutex;
-use std::net::{SocketAddr, ToSocketAddrs};
-use std::{fs, path::Path, process::exit, sync::Arc};
+use std::{
+    fs,
+    path::Path,
+    process::exit,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 fn read_config<P: AsRef<Path>>(path: P) -> Result<Config> {
     let content = fs::read(&path)
@@ -29,41 +51,87 @@ fn read_config<P: AsRef<Path>>(path: P) -> Result<Config> {
     Ok(config)
 }
 
-fn run() -> Result<()> {
+fn run(dev_mode: bool) -> Result<()> {
     let config_path = "./config.toml";
     // read config
     let config = read_config(&config_path)?;
     let rollup_config: RollupConfig = config.genesis.rollup_config.clone().into();
     // TODO: use persistent store later
-    let store = Store::open_tmp().with_context(|| "init store")?;
+    let store = match config.store.mirror {
+        Some(ref mirror_config) => open_tmp_with_mirror(mirror_config),
+        None => Store::open_tmp().with_context(|| "init store")?,
+    };
+    let store = match config.store.cold {
+        Some(ref cold_config) => store.with_cold(open_cold_db(cold_config)),
+        None => store,
+    };
+    migrate(&store).with_context(|| "migrate store")?;
     init_genesis(
         &store,
         &config.genesis,
         config.chain.genesis_committed_info.clone().into(),
     )
     .with_context(|| "init genesis")?;
+    // check dependencies before start, all failures are reported together
+    let mut startup_check = StartupCheck::default();
+    startup_check
+        .check_store(&store, &config)
+        .with_context(|| "check store")?;
+    startup_check.check_backends(&config.backends, &config);
+    // reclaim stale SMT nodes in background
+    if let Some(ref pruning_config) = config.store.smt_pruning {
+        let finality_blocks: u64 = rollup_config.finality_blocks().unpack();
+        if pruning_config.pruning_depth < finality_blocks {
+            return Err(anyhow!(
+                "SMT pruning depth {} is less than the finality blocks {}",
+                pruning_config.pruning_depth,
+                finality_blocks
+            ));
+        }
+        SMTGarbageCollector::new(store.clone(), SMTGCConfig::from(pruning_config)).start();
+    }
+    // move old block bodies to the cold DB in background
+    if let Some(ref cold_config) = config.store.cold {
+        ColdStoreMover::new(store.clone(), cold_config).start();
+    }
     let rollup_context = RollupContext {
         rollup_config: rollup_config.clone(),
         rollup_script_hash: {
             let rollup_script_hash: [u8; 32] = config.genesis.rollup_type_hash.clone().into();
             rollup_script_hash.into()
         },
+        builtin_accounts: load_builtin_accounts(&store, &config.genesis)
+            .with_context(|| "load builtin accounts")?,
+        script_args: ScriptArgsRegistry::from_config(&config.chain.account_script_args),
+        deposit_limits: config.chain.deposit_limits.clone(),
+        withdrawal_fee: config.chain.withdrawal_fee.clone(),
     };
 
     let rollup_config_hash = rollup_config.hash().into();
     let generator = {
         let backend_manage = BackendManage::from_config(config.backends.clone())
             .with_context(|| "config backends")?;
-        let account_lock_manage = AccountLockManage::default();
+        let account_lock_manage = AccountLockManage::from_config(&config.chain.account_locks);
         Arc::new(Generator::new(
             backend_manage,
             account_lock_manage,
             rollup_context.clone(),
+            config.chain.block_limits.clone(),
+            config.chain.account_creation_fee,
         ))
     };
-    let mem_pool = Arc::new(Mutex::new(
-        MemPool::create(store.clone(), generator.clone()).with_context(|| "create mem-pool")?,
-    ));
+    let mem_pool = {
+        let mut mem_pool =
+            MemPool::create(store.clone(), generator.clone()).with_context(|| "create mem-pool")?;
+        let deny_list = DenyList::from_config(&config.mem_pool.deny_list);
+        mem_pool.set_admission_policy(Box::new(deny_list));
+        mem_pool.set_limits(config.mem_pool.limits.clone());
+        mem_pool.set_storage_limits(config.mem_pool.storage_limits.clone());
+        if dev_mode {
+            mem_pool.enable_dev_mode();
+        }
+        Arc::new(Mutex::new(mem_pool))
+    };
     let chain = Arc::new(Mutex::new(
         Chain::create(
             &rollup_config,
@@ -90,7 +158,17 @@ fn run() -> Result<()> {
     };
 
     // RPC registry
-    let rpc_registry = Registry::new(mem_pool.clone(), store.clone());
+    let sync_progress = Arc::new(SyncProgress::default());
+    let rpc_registry = Registry::new(
+        mem_pool.clone(),
+        store.clone(),
+        config.rpc_server.clone(),
+        rollup_context.builtin_accounts,
+        Arc::clone(&sync_progress),
+    )
+    .with_backends_loader(move || Ok(read_config(config_path)?.backends));
+
+    let alert_sink = AlertSink::new(&config.alert);
 
     // create chain updater
     let mut chain_updater = ChainUpdater::new(
@@ -98,10 +176,25 @@ fn run() -> Result<()> {
         rpc_client.clone(),
         rollup_context,
         rollup_type_script,
+        alert_sink.clone(),
+        config.alert.indexer_lag_threshold,
+        Arc::clone(&sync_progress),
     );
 
+    let rt = runtime::build()?;
+    if let Some(ref mirror_config) = config.store.mirror {
+        let alert_sink = alert_sink.clone();
+        let handle = rt.handle().clone();
+        StoreMirrorChecker::new(store.clone(), mirror_config).start(move |message| {
+            let _guard = handle.enter();
+            alert_sink.send(AlertKind::StateRootMismatch, message);
+        });
+    }
+    rt.block_on(startup_check.check_ckb_chain(&rpc_client, &config.chain.genesis_committed_info));
+    startup_check.finish()?;
+
     let ckb_genesis_info = {
-        let ckb_genesis = smol::block_on(async { rpc_client.get_block_by_number(0).await })?;
+        let ckb_genesis = rt.block_on(rpc_client.get_block_by_number(0))?;
         CKBGenesisInfo::from_block(&ckb_genesis)?
     };
 
@@ -117,6 +210,7 @@ fn run() -> Result<()> {
         config
             .block_producer
             .ok_or_else(|| anyhow!("not set block producer"))?,
+        alert_sink,
     )
     .with_context(|| "init block producer")?;
 
@@ -126,28 +220,30 @@ fn run() -> Result<()> {
     };
     ctrlc::set_handler(handle).unwrap();
 
-    let rpc_address: SocketAddr = {
-        let mut addrs: Vec<_> = config.rpc_server.listen.to_socket_addrs()?.collect();
-        if addrs.len() != 1 {
-            return Err(anyhow!(
-                "Invalid RPC listen address `{}`",
-                &config.rpc_server.listen
-            ));
+    let rpc_addrs = {
+        let mut listens = vec![config.rpc_server.listen.clone()];
+        listens.extend(config.rpc_server.extra_listen.iter().cloned());
+        resolve_listen_addrs(&listens)?
+    };
+    let serve_within_l1_blocks = config.rpc_server.serve_within_l1_blocks;
+    let jsonrpc_server = async move {
+        if let Some(blocks) = serve_within_l1_blocks {
+            wait_for_catch_up(&sync_progress, blocks).await;
         }
-        addrs.remove(0)
+        start_jsonrpc_server(rpc_addrs, rpc_registry).await
     };
 
-    smol::block_on(async {
+    rt.block_on(async {
         select! {
             _ = ctrl_c.recv().fuse() => println!("Exiting..."),
-            e = chain_updater.poll_loop().fuse() => {
+            e = runtime::instrument("chain_updater", chain_updater.poll_loop()).fuse() => {
                 eprintln!("Error occurs polling blocks: {:?}", e);
                 exit(1);
             },
-            e = block_producer.poll_loop().fuse() => {
+            e = runtime::instrument("block_producer", block_producer.poll_loop()).fuse() => {
                 eprintln!("Error occurs produce block: {:?}", e);
             }
-            e = start_jsonrpc_server(rpc_address, rpc_registry).fuse() => {
+            e = runtime::instrument("jsonrpc_server", jsonrpc_server).fuse() => {
                 eprintln!("Error running JSONRPC server: {:?}", e);
                 exit(1);
             },
@@ -157,6 +253,30 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+/// Wait until the synced L1 block is within `blocks` of the L1 tip, the progress is reported
+/// every minute
+async fn wait_for_catch_up(sync_progress: &SyncProgress, blocks: u64) {
+    let mut last_report = Instant::now();
+    loop {
+        let status = sync_progress.status();
+        if status.is_within(blocks) {
+            println!("Caught up L1 block #{}", status.synced_block);
+            return;
+        }
+        if last_report.elapsed() >= Duration::from_secs(60) {
+            println!(
+                "Catching up L1 blocks: #{} / #{}, {}%, ETA {:?}",
+                status.synced_block,
+                status.tip_block.unwrap_or_default(),
+                status.percent,
+                status.eta
+            );
+            last_report = Instant::now();
+        }
+        runtime::sleep(Duration::from_secs(1)).await;
+    }
+}
+
 fn generate_example_config<P: AsRef<Path>>(path: P) -> Result<()> {
     let mut config = Config::default();
     config.backends.push(Default::default());
@@ -169,5 +289,10 @@ fn generate_example_config<P: AsRef<Path>>(path: P) -> Result<()> {
 /// Block producer
 fn main() {
     generate_example_config("./config.example.toml").expect("default config");
-    run().expect("run");
+    // dev mode accepts synthetic deposits, it must not be used with a public rollup
+    let dev_mode = std::env::args().skip(1).any(|arg| arg == "--dev");
+    if dev_mode {
+        println!("Running in dev mode");
+    }
+    run(dev_mode).expect("run");
 }
@@ -1,26 +1,44 @@
-use crate::utils::to_result;
+use crate::alert::{AlertKind, AlertSink};
+use crate::utils::{deposition_call, to_result};
 use crate::{
     indexer_types::{Order, Pagination, ScriptType, SearchKey, SearchKeyFilter, Tx},
     rpc_client::RPCClient,
+    runtime,
 };
 use anyhow::Result;
 use async_jsonrpc_client::{Params as ClientParams, Transport};
 use ckb_fixed_hash::H256;
-use gw_chain::chain::{Chain, L1Action, L1ActionContext, SyncParam};
+use futures::future::try_join_all;
+use gw_chain::chain::{
+    extract_global_state, extract_l2_block, Chain, L1Action, L1ActionContext, SyncEvent, SyncParam,
+};
+use gw_chain::sync_progress::SyncProgress;
 use gw_generator::RollupContext;
 use gw_jsonrpc_types::ckb_jsonrpc_types::{BlockNumber, HeaderView, TransactionWithStatus, Uint32};
 use gw_types::{
     bytes::Bytes,
-    core::ScriptHashType,
+    core::{ScriptHashType, Status},
     packed::{
-        Byte32, CellOutput, DepositionLockArgs, DepositionRequest, L2BlockCommittedInfo, Script,
-        Transaction,
+        Byte32, CellOutput, ChallengeLockArgs, ChallengeTarget, DepositionLockArgs,
+        DepositionRequest, GlobalState, L2BlockCommittedInfo, Script, Transaction,
+        VerifyTransactionWitness,
     },
     prelude::*,
 };
 use parking_lot::Mutex;
 use serde_json::json;
-use std::sync::Arc;
+use std::{cmp, convert::TryFrom, sync::Arc};
+
+/// L1 transactions fetched at the same time, they are still applied in order
+const FETCH_CONCURRENCY: usize = 8;
+
+/// A rollup L1 transaction and the header of the block which committed it
+struct L1Transaction {
+    tx_hash: H256,
+    tx: Transaction,
+    block_hash: H256,
+    header_view: HeaderView,
+}
 
 pub struct ChainUpdater {
     chain: Arc<Mutex<Chain>>,
@@ -28,6 +46,11 @@ pub struct ChainUpdater {
     last_tx_hash: Option<H256>,
     rollup_context: RollupContext,
     rollup_type_script: ckb_types::packed::Script,
+    alert_sink: AlertSink,
+    indexer_lag_threshold: u64,
+    // alert once when the indexer starts lagging
+    indexer_lagging: bool,
+    sync_progress: Arc<SyncProgress>,
 }
 
 impl ChainUpdater {
@@ -36,6 +59,9 @@ impl ChainUpdater {
         rpc_client: RPCClient,
         rollup_context: RollupContext,
         rollup_type_script: Script,
+        alert_sink: AlertSink,
+        indexer_lag_threshold: u64,
+        sync_progress: Arc<SyncProgress>,
     ) -> ChainUpdater {
         let rollup_type_script =
             ckb_types::packed::Script::new_unchecked(rollup_type_script.as_bytes());
@@ -45,6 +71,10 @@ impl ChainUpdater {
             rollup_context,
             rollup_type_script,
             last_tx_hash: None,
+            alert_sink,
+            indexer_lag_threshold,
+            indexer_lagging: false,
+            sync_progress,
         }
     }
 
@@ -56,7 +86,17 @@ impl ChainUpdater {
         //     .connect(&sql_address)
         //     .await?;
         let rollup_type_script = self.rollup_type_script.clone();
+        // the blocks missed during a downtime are caught up from here
+        let last_synced = self.chain.lock().local_state().last_synced().number();
+        self.sync_progress.set_synced_block(last_synced.unpack());
         loop {
+            let indexer_tip = match self.check_indexer_lag().await {
+                Ok(indexer_tip) => indexer_tip,
+                Err(err) => {
+                    eprintln!("Check indexer lag error: {}", err);
+                    None
+                }
+            };
             let tip_l1_block = self.chain.lock().local_state().last_synced().number();
             let search_key = SearchKey {
                 script: rollup_type_script.clone().into(),
@@ -103,26 +143,46 @@ impl ChainUpdater {
                 println!("Poll transactions: {}", txs.objects.len());
                 self.update(&txs.objects).await?;
             }
+            // the blocks up to the indexer tip have no more rollup txs
+            if let Some(indexer_tip) = indexer_tip {
+                let last_synced = self.chain.lock().local_state().last_synced().number();
+                let synced_block = cmp::max(indexer_tip, last_synced.unpack());
+                self.sync_progress.set_synced_block(synced_block);
+            }
 
-            async_std::task::sleep(std::time::Duration::from_secs(3)).await;
+            runtime::sleep(std::time::Duration::from_secs(3)).await;
         }
     }
 
-    pub async fn update(&mut self, txs: &[Tx]) -> anyhow::Result<()> {
-        for tx in txs {
-            self.update_single(&tx.tx_hash).await?;
+    /// Returns the indexer tip, the L1 tip is recorded in the sync progress
+    async fn check_indexer_lag(&mut self) -> anyhow::Result<Option<u64>> {
+        let tip_number = self.rpc_client.get_tip_block_number().await?;
+        self.sync_progress.set_tip_block(tip_number);
+        let indexer_tip_number = self.rpc_client.get_indexer_tip_number().await?;
+        let lag = tip_number.saturating_sub(indexer_tip_number.unwrap_or_default());
+        let lagging = lag > self.indexer_lag_threshold;
+        if lagging && !self.indexer_lagging {
+            self.alert_sink.send(
+                AlertKind::IndexerLag,
+                format!("indexer is {} blocks behind CKB tip #{}", lag, tip_number),
+            );
         }
-        Ok(())
+        self.indexer_lagging = lagging;
+        Ok(indexer_tip_number)
     }
 
-    async fn update_single(&mut self, tx_hash: &H256) -> anyhow::Result<()> {
-        if let Some(last_tx_hash) = &self.last_tx_hash {
-            if last_tx_hash == tx_hash {
-                return Ok(());
+    pub async fn update(&mut self, txs: &[Tx]) -> anyhow::Result<()> {
+        for txs in txs.chunks(FETCH_CONCURRENCY) {
+            let l1_txs =
+                try_join_all(txs.iter().map(|tx| self.fetch_l1_transaction(&tx.tx_hash))).await?;
+            for l1_tx in l1_txs {
+                self.update_single(l1_tx).await?;
             }
         }
-        self.last_tx_hash = Some(tx_hash.clone());
+        Ok(())
+    }
 
+    async fn fetch_l1_transaction(&self, tx_hash: &H256) -> anyhow::Result<L1Transaction> {
         let tx: Option<TransactionWithStatus> = to_result(
             self.rpc_client
                 .ckb_client
@@ -152,10 +212,42 @@ impl ChainUpdater {
         )?;
         let header_view =
             header_view.ok_or_else(|| anyhow::anyhow!("Cannot locate block: {:x}", block_hash))?;
-        let requests = self.extract_deposition_requests(&tx).await?;
-        let context = L1ActionContext::SubmitTxs {
-            deposition_requests: requests,
-        };
+        Ok(L1Transaction {
+            tx_hash: tx_hash.clone(),
+            tx,
+            block_hash,
+            header_view,
+        })
+    }
+
+    async fn update_single(&mut self, l1_tx: L1Transaction) -> anyhow::Result<()> {
+        let L1Transaction {
+            tx_hash,
+            tx,
+            block_hash,
+            header_view,
+        } = l1_tx;
+        if let Some(last_tx_hash) = &self.last_tx_hash {
+            if last_tx_hash == &tx_hash {
+                return Ok(());
+            }
+        }
+        self.last_tx_hash = Some(tx_hash.clone());
+
+        let context = self.extract_l1_action_context(&tx).await?;
+        if let L1ActionContext::Challenge {
+            context: ref target,
+        } = context
+        {
+            self.alert_sink.send(
+                AlertKind::RollupHalted,
+                format!(
+                    "rollup halted by challenge {} in L1 tx {:x}",
+                    target, tx_hash
+                ),
+            );
+        }
+        let is_submission = matches!(context, L1ActionContext::SubmitTxs { .. });
         let l2block_committed_info = L2BlockCommittedInfo::new_builder()
             .number(header_view.inner.number.value().pack())
             .block_hash(block_hash.0.pack())
@@ -171,7 +263,16 @@ impl ChainUpdater {
             reverts: vec![],
             updates: vec![update],
         };
-        self.chain.lock().sync(sync_param)?;
+        let event = self.chain.lock().sync(sync_param)?;
+        match event {
+            SyncEvent::BadBlock(ref challenge_context) => self.alert_sink.send(
+                AlertKind::BadBlock,
+                format!("bad block {} in L1 tx {:x}", challenge_context, tx_hash),
+            ),
+            SyncEvent::Success if is_submission => self.check_state_root(&tx_hash),
+            _ => {}
+        }
+        self.sync_progress.set_synced_block(header_view.inner.number.value());
         // self.insert_to_sql(&tx).await?;
         Ok(())
     }
@@ -205,6 +306,109 @@ impl ChainUpdater {
     //     Ok(())
     // }
 
+    /// Compare the local tip and the roots computed by this node with the global state on L1
+    fn check_state_root(&self, tx_hash: &H256) {
+        let chain = self.chain.lock();
+        let local_state = chain.local_state();
+        let tip = local_state.tip();
+        let global_state = local_state.last_global_state();
+        let tip_number: u64 = tip.raw().number().unpack();
+        let local_post_state = chain
+            .store()
+            .begin_transaction()
+            .get_block_local_post_state(&tip.hash().into());
+        let is_diverged = match local_post_state {
+            Ok(Some((account, block))) => {
+                global_state.tip_block_hash().as_slice() != tip.hash()
+                    || global_state.account().as_slice() != account.as_slice()
+                    || global_state.block().as_slice() != block.as_slice()
+            }
+            Ok(None) => global_state.tip_block_hash().as_slice() != tip.hash(),
+            Err(err) => {
+                eprintln!("Load local post state of #{} error: {}", tip_number, err);
+                return;
+            }
+        };
+        if is_diverged {
+            self.alert_sink.send(
+                AlertKind::StateRootMismatch,
+                format!(
+                    "local tip #{} diverges from the global state in L1 tx {:x}",
+                    tip_number, tx_hash
+                ),
+            );
+        }
+    }
+
+    /// Recognize the rollup action of a L1 transaction by the status transition of the
+    /// rollup cell, actions which don't change the status submit blocks
+    async fn extract_l1_action_context(&self, tx: &Transaction) -> anyhow::Result<L1ActionContext> {
+        let rollup_id: [u8; 32] = self.rollup_context.rollup_script_hash.into();
+        let global_state = extract_global_state(tx, &rollup_id)?;
+        let (prev_global_state, last_rollup_action) = {
+            let chain = self.chain.lock();
+            let db = chain.store().begin_transaction();
+            (
+                chain.local_state().last_global_state().clone(),
+                db.get_last_rollup_action(),
+            )
+        };
+        let prev_status = chain_status(&prev_global_state)?;
+        let status = chain_status(&global_state)?;
+        let context = match (prev_status, status) {
+            (Status::Running, Status::Halting) => {
+                let target = tx
+                    .raw()
+                    .outputs()
+                    .into_iter()
+                    .find_map(|output| try_parse_challenge_target(&output, &self.rollup_context))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("No challenge cell in L1 tx {:x}", H256(tx.hash()))
+                    })?;
+                L1ActionContext::Challenge { context: target }
+            }
+            (Status::Halting, Status::Running) => {
+                // a revert removes the challenged block and the blocks after it
+                let prev_count: u64 = prev_global_state.block().count().unpack();
+                let count: u64 = global_state.block().count().unpack();
+                if count < prev_count {
+                    let target = last_rollup_action
+                        .map(|record| record.challenge_target())
+                        .unwrap_or_default();
+                    L1ActionContext::Revert { context: target }
+                } else {
+                    L1ActionContext::CancelChallenge {
+                        context: VerifyTransactionWitness::default(),
+                    }
+                }
+            }
+            _ => {
+                let mut deposition_requests = self.extract_deposition_requests(tx).await?;
+                deposition_requests.extend(self.take_dev_deposits(tx, &rollup_id)?);
+                L1ActionContext::SubmitTxs {
+                    deposition_requests,
+                }
+            }
+        };
+        Ok(context)
+    }
+
+    /// Synthetic deposits of a block produced in dev mode, the layer1 tx doesn't carry them
+    fn take_dev_deposits(
+        &self,
+        tx: &Transaction,
+        rollup_id: &[u8; 32],
+    ) -> anyhow::Result<Vec<DepositionRequest>> {
+        let chain = self.chain.lock();
+        let mut mem_pool = chain.mem_pool().lock();
+        let dev_deposits = match mem_pool.dev_deposits() {
+            Some(dev_deposits) => dev_deposits,
+            None => return Ok(Vec::new()),
+        };
+        let block_hash = extract_l2_block(tx, rollup_id)?.hash();
+        Ok(dev_deposits.take_produced(&block_hash.into()))
+    }
+
     async fn extract_deposition_requests(
         &self,
         tx: &Transaction,
@@ -253,6 +457,34 @@ impl ChainUpdater {
     }
 }
 
+fn chain_status(global_state: &GlobalState) -> anyhow::Result<Status> {
+    let status: u8 = global_state.status().into();
+    Status::try_from(status).map_err(|status| anyhow::anyhow!("Invalid rollup status {}", status))
+}
+
+fn try_parse_challenge_target(
+    cell_output: &CellOutput,
+    rollup_context: &RollupContext,
+) -> Option<ChallengeTarget> {
+    if cell_output.lock().code_hash() != rollup_context.rollup_config.challenge_script_type_hash()
+        || cell_output.lock().hash_type() != ScriptHashType::Type.into()
+    {
+        return None;
+    }
+    let args = cell_output.lock().args().raw_data();
+    if args.len() < 32 {
+        return None;
+    }
+    let rollup_type_script_hash: [u8; 32] = rollup_context.rollup_script_hash.into();
+    if args.slice(0..32) != rollup_type_script_hash[..] {
+        return None;
+    }
+    match ChallengeLockArgs::from_slice(&args.slice(32..)) {
+        Ok(lock_args) => Some(lock_args.target()),
+        Err(_) => None,
+    }
+}
+
 fn try_parse_deposition_request(
     cell_output: &CellOutput,
     cell_data: &Bytes,
@@ -310,6 +542,7 @@ fn try_parse_deposition_request(
         .amount(amount.pack())
         .sudt_script_hash(sudt_script_hash.pack())
         .script(lock_args.layer2_lock())
+        .call(deposition_call(cell_output, cell_data).pack())
         .build();
     Some(deposition_request)
 }
@@ -2,6 +2,7 @@
 //! Block producer assemble serveral Godwoken components into a single executor.
 //! A block producer can act without the ability of produce block.
 
+use crate::utils::deposition_call_txs;
 use anyhow::{anyhow, Result};
 use gw_common::{
     h256_ext::H256Ext,
@@ -10,14 +11,17 @@ use gw_common::{
     state::State,
     H256,
 };
-use gw_generator::{traits::StateExt, Generator};
+use gw_generator::{generator::StateTransitionArgs, traits::StateExt, Generator};
 use gw_store::{
+    block_reader::{BlockReader, DEFAULT_READ_AHEAD},
     chain_view::ChainView,
     state_db::{StateDBTransaction, StateDBVersion},
     transaction::StoreTransaction,
+    Store,
 };
 use gw_types::{
     core::Status,
+    offchain::TxAccessList,
     packed::{
         AccountMerkleState, BlockInfo, BlockMerkleState, DepositionRequest, GlobalState, L2Block,
         L2Transaction, RawL2Block, SubmitTransactions, SubmitWithdrawals, TxReceipt,
@@ -25,12 +29,23 @@ use gw_types::{
     },
     prelude::*,
 };
+use std::{
+    cmp::{max, min},
+    collections::HashSet,
+};
+
+/// Reserved bytes for the block header and the block proof
+const BLOCK_SIZE_RESERVED: usize = 4096;
+/// Estimated bytes of a touched key, a KV pair and its share of the merkle proof
+const TOUCHED_KEY_SIZE: usize = 64 + 64;
 
 pub struct ProduceBlockResult {
     pub block: L2Block,
     pub global_state: GlobalState,
     pub unused_transactions: Vec<L2Transaction>,
     pub unused_withdrawal_requests: Vec<WithdrawalRequest>,
+    /// access lists of the txs of the block, in the order of txs
+    pub access_lists: Vec<TxAccessList>,
 }
 
 pub struct ProduceBlockParam<'a> {
@@ -50,24 +65,68 @@ pub struct ProduceBlockParam<'a> {
 /// this method take txs & withdrawal requests from tx pool and produce a new block
 /// the package method should packs the items in order:
 /// withdrawals, then deposits, finally the txs. Thus, the state-validator can verify this correctly
-pub fn produce_block(param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult> {
-    let ProduceBlockParam {
-        db,
-        generator,
-        block_producer_id,
-        timestamp,
-        txs,
-        deposition_requests,
-        withdrawal_requests,
-        parent_block,
-        rollup_config_hash,
-        max_withdrawal_capacity,
-    } = param;
+///
+/// Once the block is full, the rest txs are returned in `unused_transactions`,
+/// they are left in the pool for the next block
+///
+/// The calls carried by the deposits are signed txs, they are packaged as the leading txs and
+/// verified like the others. A call which isn't packaged is dropped, the deposit is still credited
+pub fn produce_block(mut param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult> {
+    let generator = param.generator;
+    let max_block_size = generator.block_limits().max_block_size;
+    let mut txs = deposition_call_txs(&param.deposition_requests);
+    let call_hashes: HashSet<[u8; 32]> = txs.iter().map(|tx| tx.hash()).collect();
+    txs.extend(std::mem::take(&mut param.txs));
+    let mut postponed_txs = Vec::new();
+    loop {
+        let mut result = build_block(&param, txs)?;
+        // the estimation may be off, never submit a block which can't be accepted
+        let err = match generator.verify_block_limits(&result.block) {
+            Ok(()) => {
+                result.unused_transactions.extend(postponed_txs);
+                result
+                    .unused_transactions
+                    .retain(|tx| !call_hashes.contains(&tx.hash()));
+                return Ok(result);
+            }
+            Err(err) => err,
+        };
+        let mut used_txs: Vec<L2Transaction> = result.block.transactions().into_iter().collect();
+        if used_txs.is_empty() {
+            return Err(anyhow!("produced block exceeds limits: {}", err));
+        }
+        // retry with the leading txs which are estimated to fit in the block
+        let block_size = result.block.as_slice().len();
+        let keep = min(
+            used_txs.len() - 1,
+            used_txs.len() * max_block_size / block_size,
+        );
+        let mut dropped_txs = used_txs.split_off(keep);
+        dropped_txs.extend(result.unused_transactions);
+        dropped_txs.extend(postponed_txs);
+        postponed_txs = dropped_txs;
+        txs = used_txs;
+    }
+}
+
+fn build_block(
+    param: &ProduceBlockParam<'_>,
+    txs: Vec<L2Transaction>,
+) -> Result<ProduceBlockResult> {
+    let db = &param.db;
+    let generator = param.generator;
+    let block_producer_id = param.block_producer_id;
+    let timestamp = param.timestamp;
+    let deposition_requests = &param.deposition_requests;
+    let withdrawal_requests = &param.withdrawal_requests;
+    let parent_block = param.parent_block;
+    let rollup_config_hash = param.rollup_config_hash;
+    let max_withdrawal_capacity = param.max_withdrawal_capacity;
     let rollup_context = generator.rollup_context();
     // create overlay storage
     let state_db = {
         let tip_block_hash = db.get_tip_block_hash()?;
-        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_block_hash))?
+        StateDBTransaction::from_version(db, StateDBVersion::from_block_hash(tip_block_hash))?
     };
     let mut state = state_db.account_state_tree()?;
     // track state changes
@@ -78,7 +137,7 @@ pub fn produce_block(param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult>
     let mut used_withdrawal_requests = Vec::with_capacity(withdrawal_requests.len());
     let mut unused_withdrawal_requests = Vec::with_capacity(withdrawal_requests.len());
     let mut total_withdrawal_capacity: u128 = 0;
-    for request in withdrawal_requests {
+    for request in withdrawal_requests.iter().cloned() {
         // check withdrawal request
         if generator
             .check_withdrawal_request_signature(&state, &request)
@@ -105,7 +164,7 @@ pub fn produce_block(param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult>
         }
         total_withdrawal_capacity = new_total_withdrwal_capacity;
         // update the state
-        match state.apply_withdrawal_request(rollup_context, &request) {
+        match state.apply_withdrawal_request(rollup_context, block_producer_id, &request) {
             Ok(_) => {
                 used_withdrawal_requests.push(request);
             }
@@ -114,14 +173,6 @@ pub fn produce_block(param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult>
             }
         }
     }
-    // update deposits
-    state.apply_deposition_requests(rollup_context, &deposition_requests)?;
-    // calculate state after withdrawals & deposits
-    let compacted_prev_root_hash = state.calculate_compacted_account_root()?;
-    // execute txs
-    let mut tx_receipts = Vec::with_capacity(txs.len());
-    let mut used_transactions = Vec::with_capacity(txs.len());
-    let mut unused_transactions = Vec::with_capacity(txs.len());
     // build block info
     let parent_block_number: u64 = parent_block.raw().number().unpack();
     let parent_block_hash = parent_block.hash();
@@ -131,8 +182,42 @@ pub fn produce_block(param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult>
         .timestamp(timestamp.pack())
         .block_producer_id(block_producer_id.pack())
         .build();
-    let chain_view = ChainView::new(&db, parent_block_hash.into());
-    for tx in txs {
+    let chain_view = ChainView::new(db, parent_block_hash.into());
+    // update deposits
+    state.apply_deposition_requests(rollup_context, deposition_requests)?;
+    // calculate state after withdrawals & deposits
+    let compacted_prev_root_hash = state.calculate_compacted_account_root()?;
+    // execute txs
+    let mut tx_receipts = Vec::with_capacity(txs.len());
+    let mut access_lists = Vec::with_capacity(txs.len());
+    let mut used_transactions = Vec::with_capacity(txs.len());
+    let mut unused_transactions = Vec::with_capacity(txs.len());
+    // txs and withdrawals are the bulk of the block,
+    // the touched keys also add kv pairs and proofs to the block
+    let block_limits = generator.block_limits();
+    let mut block_cycles: u64 = 0;
+    let mut block_size = BLOCK_SIZE_RESERVED
+        + used_withdrawal_requests
+            .iter()
+            .map(|request| request.as_slice().len())
+            .sum::<usize>();
+    let mut txs = txs.into_iter();
+    while let Some(tx) = txs.next() {
+        // 0. check block limits
+        let touched_keys_size = state
+            .tracker_mut()
+            .touched_keys()
+            .expect("track touched keys")
+            .lock()
+            .len()
+            * TOUCHED_KEY_SIZE;
+        let tx_size = tx.as_slice().len();
+        if used_transactions.len() >= block_limits.max_tx_count as usize
+            || block_size + touched_keys_size + tx_size > block_limits.max_block_size
+        {
+            unused_transactions.push(tx);
+            continue;
+        }
         // 1. verify tx
         if generator.check_transaction_signature(&state, &tx).is_err() {
             unused_transactions.push(tx);
@@ -147,13 +232,27 @@ pub fn produce_block(param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult>
         let run_result =
             match generator.execute_transaction(&chain_view, &state, &block_info, &raw_tx) {
                 Ok(run_result) => run_result,
+                // a failed tx is still packaged, the sender pays for the gas limit
+                Err(err) if err.is_execution_failure() => {
+                    generator.failed_run_result(&state, &raw_tx, &err)?
+                }
                 Err(_) => {
                     unused_transactions.push(tx);
                     continue;
                 }
             };
-        // 3. apply tx state
+        // the block is full, leave this tx and the followings to the next block
+        let new_block_cycles = block_cycles.saturating_add(run_result.used_cycles);
+        if new_block_cycles > block_limits.max_block_cycles {
+            unused_transactions.push(tx);
+            unused_transactions.extend(txs);
+            break;
+        }
+        block_cycles = new_block_cycles;
+        let access_list = TxAccessList::from_run_result(&run_result);
+        // 3. apply tx state and settle fee
         state.apply_run_result(&run_result)?;
+        let fee = generator.settle_fee(&mut state, &block_info, &raw_tx, &run_result)?;
         // 4. build tx receipt
         let tx_witness_hash = tx.witness_hash();
         let tx_post_state = {
@@ -176,16 +275,22 @@ pub fn produce_block(param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult>
                     .pack(),
             )
             .logs(run_result.logs.pack())
+            .used_cycles(fee.used_cycles.pack())
+            .paid_fee(fee.paid_fee.pack())
+            .refunded_fee(fee.refunded_fee.pack())
+            .return_data(run_result.return_data.pack())
             .build();
+        block_size += tx_size;
         used_transactions.push(tx);
         tx_receipts.push(receipt);
+        access_lists.push(access_list);
     }
     assert_eq!(used_transactions.len(), tx_receipts.len());
     let touched_keys: Vec<H256> = state
         .tracker_mut()
         .touched_keys()
         .expect("track touched keys")
-        .borrow()
+        .lock()
         .clone()
         .into_iter()
         .collect();
@@ -319,5 +424,105 @@ pub fn produce_block(param: ProduceBlockParam<'_>) -> Result<ProduceBlockResult>
         global_state,
         unused_transactions,
         unused_withdrawal_requests,
+        access_lists,
     })
 }
+
+/// Replay the block through the sync path like a validator does,
+/// returns an error if the replayed states diverge from the block.
+/// The txs are executed in parallel if the access lists of the block are stored.
+///
+/// `db` must not contain the state changes of producing the block
+pub fn check_block_replay(
+    db: &StoreTransaction,
+    generator: &Generator,
+    block: &L2Block,
+    deposition_requests: Vec<DepositionRequest>,
+) -> Result<()> {
+    let raw_block = block.raw();
+    let parent_block_hash: [u8; 32] = raw_block.parent_block_hash().unpack();
+    let state_db = StateDBTransaction::from_version(
+        db,
+        StateDBVersion::from_block_hash(parent_block_hash.into()),
+    )?;
+    let mut state = state_db.account_state_tree()?;
+    let chain_view = ChainView::new(db, parent_block_hash.into());
+    let args = StateTransitionArgs {
+        l2block: block.clone(),
+        deposition_requests,
+    };
+    let result = match db.get_block_access_lists(&block.hash().into())? {
+        Some(access_lists) => {
+            generator.apply_state_transition_parallel(&chain_view, &mut state, args, &access_lists)
+        }
+        None => generator.apply_state_transition(&chain_view, &mut state, args),
+    }
+    .map_err(|err| anyhow!("replay block error: {:?}", err))?;
+
+    // check the post state of each tx
+    let expected_roots: Vec<[u8; 32]> = raw_block
+        .submit_transactions()
+        .compacted_post_root_list()
+        .unpack();
+    let replayed_roots: Vec<[u8; 32]> = result
+        .receipts
+        .iter()
+        .map(|receipt| {
+            let post_state = receipt.post_state();
+            calculate_compacted_account_root(
+                &post_state.merkle_root().unpack(),
+                post_state.count().unpack(),
+            )
+        })
+        .collect();
+    let tx_count = max(expected_roots.len(), replayed_roots.len());
+    if let Some(tx_index) = (0..tx_count).find(|&i| expected_roots.get(i) != replayed_roots.get(i))
+    {
+        return Err(anyhow!("replayed post state diverges at tx {}", tx_index));
+    }
+
+    // check the post state of the block
+    let post_account = AccountMerkleState::new_builder()
+        .merkle_root(state.calculate_root()?.pack())
+        .count(state.get_account_count()?.pack())
+        .build();
+    if post_account.as_slice() != raw_block.post_account().as_slice() {
+        return Err(anyhow!(
+            "replayed post account state diverges, block: {}, replayed: {}",
+            raw_block.post_account(),
+            post_account
+        ));
+    }
+    Ok(())
+}
+
+/// Replay the stored main chain blocks in `[from_block, to_block]`, the genesis block is skipped.
+/// The next blocks are read ahead while a block is replayed.
+///
+/// Returns the number of the replayed blocks, or an error at the first diverged block
+pub fn replay_blocks(
+    store: &Store,
+    generator: &Generator,
+    from_block: u64,
+    to_block: u64,
+) -> Result<u64> {
+    let reader = BlockReader::new(
+        store.clone(),
+        max(from_block, 1),
+        to_block,
+        DEFAULT_READ_AHEAD,
+    );
+    let mut replayed = 0;
+    for block in reader {
+        let (number, block) = block?;
+        // the replayed state changes are dropped with the db transaction
+        let db = store.begin_transaction();
+        let deposition_requests = db
+            .get_block_deposition_requests(&block.hash().into())?
+            .unwrap_or_default();
+        check_block_replay(&db, generator, &block, deposition_requests)
+            .map_err(|err| anyhow!("block #{}: {}", number, err))?;
+        replayed += 1;
+    }
+    Ok(replayed)
+}
@@ -1,5 +1,6 @@
-use crate::indexer_types::{Cell, Order, Pagination, ScriptType, SearchKey, SearchKeyFilter};
+use crate::indexer_types::{Cell, Order, Pagination, ScriptType, SearchKey, SearchKeyFilter, Tip};
 use crate::types::CellInfo;
+use crate::utils::deposition_call;
 use anyhow::Result;
 use async_jsonrpc_client::{HttpClient, Output, Params as ClientParams, Transport};
 use ckb_types::prelude::{Entity, Unpack as CKBUnpack};
@@ -60,6 +61,7 @@ fn parse_deposit_request(
         .capacity(capacity)
         .amount(amount.pack())
         .sudt_script_hash(sudt_script_hash.pack())
+        .call(deposition_call(output, output_data).pack())
         .build();
     Some(request)
 }
@@ -224,7 +226,12 @@ impl RPCClient {
         Ok(cell_info)
     }
 
-    async fn get_tip_block_number(&self) -> Result<u64> {
+    pub async fn get_indexer_tip_number(&self) -> Result<Option<u64>> {
+        let tip: Option<Tip> = to_result(self.indexer_client.request("get_tip", None).await?)?;
+        Ok(tip.map(|tip| tip.block_number.value()))
+    }
+
+    pub async fn get_tip_block_number(&self) -> Result<u64> {
         let number: BlockNumber = to_result(
             self.ckb_client
                 .request("get_tip_block_number", None)
@@ -233,6 +240,22 @@ impl RPCClient {
         Ok(number.value())
     }
 
+    pub async fn get_block_hash(&self, number: u64) -> Result<Option<H256>> {
+        let block_number = BlockNumber::from(number);
+        let block_hash: Option<ckb_types::H256> = to_result(
+            self.ckb_client
+                .request(
+                    "get_block_hash",
+                    Some(ClientParams::Array(vec![json!(block_number)])),
+                )
+                .await?,
+        )?;
+        Ok(block_hash.map(|hash| {
+            let hash: [u8; 32] = hash.into();
+            hash.into()
+        }))
+    }
+
     pub async fn get_block_by_number(&self, number: u64) -> Result<Block> {
         let block_number = BlockNumber::from(number);
         let block: ckb_jsonrpc_types::BlockView = to_result(
@@ -336,6 +359,19 @@ impl RPCClient {
                                 continue;
                             }
                         };
+                    // the deposit can't create an account or is dust, it's left to be cancelled
+                    let rollup_context = &self.rollup_context;
+                    if let Err(err) = rollup_context
+                        .script_args
+                        .verify(&rollup_context.rollup_script_hash, &request.script())
+                    {
+                        eprintln!("invalid deposit cell: {}\n{:?}", err, cell);
+                        continue;
+                    }
+                    if let Err(err) = rollup_context.verify_deposit_amounts(&request) {
+                        eprintln!("skip deposit cell: {}\n{:?}", err, cell);
+                        continue;
+                    }
                     let info = DepositInfo { cell, request };
                     deposit_infos.push(info);
                 }
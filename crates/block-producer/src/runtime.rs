@@ -0,0 +1,46 @@
+//! Async runtime
+//!
+//! Godwoken drives every async task on a single tokio runtime, modules should
+//! use the helpers in here instead of depending on a specific executor.
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Builder, Runtime};
+
+pub use tokio::task::JoinHandle;
+
+/// Build the multi-thread runtime used by the node
+pub fn build() -> Result<Runtime> {
+    let runtime = Builder::new_multi_thread()
+        .thread_name("godwoken")
+        .enable_all()
+        .build()?;
+    Ok(runtime)
+}
+
+/// Run a future and log when it exits
+pub async fn instrument<F: Future>(name: &'static str, fut: F) -> F::Output {
+    let started_at = Instant::now();
+    println!("[runtime] task {} started", name);
+    let output = fut.await;
+    println!(
+        "[runtime] task {} exited after {:?}",
+        name,
+        started_at.elapsed()
+    );
+    output
+}
+
+/// Spawn an instrumented task on the current runtime
+pub fn spawn<F>(name: &'static str, fut: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(instrument(name, fut))
+}
+
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
@@ -0,0 +1,166 @@
+//! Dependency checks run on startup
+//!
+//! All checks run before syncing, failures are collected and reported in a single
+//! diagnostic, the node refuses to start instead of failing later mid-sync.
+
+use crate::rpc_client::RPCClient;
+use anyhow::{anyhow, Result};
+use gw_config::{BackendConfig, Config};
+use gw_jsonrpc_types::godwoken::L2BlockCommittedInfo;
+use gw_store::Store;
+use gw_types::{packed::RollupConfig, prelude::*};
+use std::{collections::HashSet, fmt, fs};
+
+fn to_hex(hash: [u8; 32]) -> ckb_fixed_hash::H256 {
+    hash.into()
+}
+
+pub struct CheckFailure {
+    pub name: &'static str,
+    pub reason: String,
+}
+
+impl fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.name, self.reason)
+    }
+}
+
+#[derive(Default)]
+pub struct StartupCheck {
+    failures: Vec<CheckFailure>,
+}
+
+impl StartupCheck {
+    fn fail(&mut self, name: &'static str, reason: String) {
+        self.failures.push(CheckFailure { name, reason });
+    }
+
+    /// The store must be initialized by the same rollup
+    pub fn check_store(&mut self, store: &Store, config: &Config) -> Result<()> {
+        if !store.has_genesis()? {
+            self.fail("store", "genesis is not initialized".to_string());
+            return Ok(());
+        }
+        let rollup_type_hash: [u8; 32] = config.genesis.rollup_type_hash.clone().into();
+        let chain_id = store.get_chain_id()?;
+        if chain_id != rollup_type_hash.into() {
+            self.fail(
+                "store",
+                format!(
+                    "store chain id {:#x} mismatches rollup type hash {:#x}",
+                    to_hex(chain_id.into()),
+                    config.genesis.rollup_type_hash
+                ),
+            );
+        }
+
+        let rollup_config: RollupConfig = config.genesis.rollup_config.clone().into();
+        let db = store.begin_transaction();
+        let genesis_hash = db
+            .get_block_hash_by_number(0)?
+            .ok_or_else(|| anyhow!("can't find genesis hash"))?;
+        let store_rollup_config_hash: [u8; 32] = db
+            .get_block_post_global_state(&genesis_hash)?
+            .ok_or_else(|| anyhow!("can't find genesis global state"))?
+            .rollup_config_hash()
+            .unpack();
+        if store_rollup_config_hash != rollup_config.hash() {
+            self.fail(
+                "store",
+                format!(
+                    "rollup config hash {:#x} mismatches store genesis {:#x}",
+                    to_hex(rollup_config.hash()),
+                    to_hex(store_rollup_config_hash),
+                ),
+            );
+        }
+        Ok(())
+    }
+
+    /// Backend binaries must be loadable and registered by the rollup config
+    pub fn check_backends(&mut self, backends: &[BackendConfig], config: &Config) {
+        let rollup_config: RollupConfig = config.genesis.rollup_config.clone().into();
+        let mut allowed_type_hashes: HashSet<[u8; 32]> = rollup_config
+            .allowed_contract_type_hashes()
+            .into_iter()
+            .map(|hash| hash.unpack())
+            .collect();
+        allowed_type_hashes.insert(rollup_config.l2_sudt_validator_script_type_hash().unpack());
+        let meta_contract_type_hash = config.genesis.meta_contract_validator_type_hash.clone();
+        allowed_type_hashes.insert(meta_contract_type_hash.into());
+
+        let mut registered = HashSet::new();
+        for backend in backends {
+            for path in &[&backend.validator_path, &backend.generator_path] {
+                match fs::metadata(path) {
+                    Ok(metadata) if metadata.len() > 0 => {}
+                    Ok(_) => self.fail("backend", format!("{} is empty", path.display())),
+                    Err(err) => self.fail("backend", format!("{}: {}", path.display(), err)),
+                }
+            }
+            let type_hash: [u8; 32] = backend.validator_script_type_hash.clone().into();
+            if !registered.insert(type_hash) {
+                self.fail(
+                    "backend",
+                    format!(
+                        "duplicated validator script type hash {:#x}",
+                        backend.validator_script_type_hash
+                    ),
+                );
+            }
+            if !allowed_type_hashes.contains(&type_hash) {
+                self.fail(
+                    "backend",
+                    format!(
+                        "validator script type hash {:#x} of {} isn't allowed by rollup config",
+                        backend.validator_script_type_hash,
+                        backend.validator_path.display()
+                    ),
+                );
+            }
+        }
+    }
+
+    /// The CKB node must be on the chain the rollup genesis is committed to
+    pub async fn check_ckb_chain(
+        &mut self,
+        rpc_client: &RPCClient,
+        committed_info: &L2BlockCommittedInfo,
+    ) {
+        let number = committed_info.number.value();
+        let expected: [u8; 32] = committed_info.block_hash.clone().into();
+        match rpc_client.get_block_hash(number).await {
+            Ok(Some(block_hash)) if block_hash == expected.into() => {}
+            Ok(Some(block_hash)) => self.fail(
+                "ckb",
+                format!(
+                    "block {} is {:#x}, expected {:#x}, the node is on another chain",
+                    number,
+                    to_hex(block_hash.into()),
+                    committed_info.block_hash
+                ),
+            ),
+            Ok(None) => self.fail(
+                "ckb",
+                format!("the node hasn't synced rollup genesis block {}", number),
+            ),
+            Err(err) => self.fail("ckb", format!("request CKB node: {}", err)),
+        }
+    }
+
+    /// Print the consolidated diagnostic, returns error if any check failed
+    pub fn finish(self) -> Result<()> {
+        if self.failures.is_empty() {
+            return Ok(());
+        }
+        eprintln!("Startup checks failed:");
+        for failure in &self.failures {
+            eprintln!("  {}", failure);
+        }
+        Err(anyhow!(
+            "{} startup check(s) failed, refuse to start",
+            self.failures.len()
+        ))
+    }
+}
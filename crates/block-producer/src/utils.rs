@@ -4,8 +4,12 @@ use anyhow::{anyhow, Result};
 use async_jsonrpc_client::Output;
 use gw_common::{blake2b::new_blake2b, H256};
 use gw_types::{
+    bytes::Bytes,
     core::DepType,
-    packed::{Block, CellDep, CellInput, Header, OutPoint, Script},
+    packed::{
+        Block, CellDep, CellInput, CellOutput, DepositionRequest, Header, L2Transaction, OutPoint,
+        Script,
+    },
     prelude::*,
 };
 use serde::de::DeserializeOwned;
@@ -19,6 +23,35 @@ pub fn to_result<T: DeserializeOwned>(output: Output) -> Result<T> {
     }
 }
 
+/// Returns the call carried by a deposition cell
+/// the call follows the sUDT amount in the cell data, or takes the whole data of a CKB deposition
+pub fn deposition_call(output: &CellOutput, output_data: &Bytes) -> Bytes {
+    let start = if output.type_().to_opt().is_some() {
+        16
+    } else {
+        0
+    };
+    if output_data.len() > start {
+        output_data.slice(start..)
+    } else {
+        Bytes::new()
+    }
+}
+
+/// Returns the signed L2 txs carried by the deposition requests, malformed calls are skipped
+pub fn deposition_call_txs(deposition_requests: &[DepositionRequest]) -> Vec<L2Transaction> {
+    deposition_requests
+        .iter()
+        .filter_map(|request| {
+            let call = request.call().raw_data();
+            if call.is_empty() {
+                return None;
+            }
+            L2Transaction::from_slice(&call).ok()
+        })
+        .collect()
+}
+
 /// Calculate tx fee
 fn calculate_required_tx_fee(tx_size: usize) -> u64 {
     // tx_size * KB / MIN_FEE_RATE
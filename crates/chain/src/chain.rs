@@ -12,11 +12,12 @@ use gw_store::{
 };
 use gw_types::{
     bytes::Bytes,
-    core::Status,
+    core::{RollupActionType, Status},
     packed::{
-        ChallengeTarget, ChallengeWitness, DepositionRequest, GlobalState, L2Block,
-        L2BlockCommittedInfo, L2BlockReader, RollupConfig, Script, Transaction, TxReceipt,
-        VerifyTransactionWitness, WitnessArgs, WitnessArgsReader,
+        AccountMerkleState, BlockMerkleState, ChallengeTarget, ChallengeWitness, DepositionRequest,
+        GlobalState, L2Block, L2BlockCommittedInfo, L2BlockReader, RollupActionRecord,
+        RollupConfig, Script, Transaction, TxReceipt, VerifyTransactionWitness, WitnessArgs,
+        WitnessArgsReader,
     },
     prelude::{
         Builder as GWBuilder, Entity as GWEntity, Pack as GWPack, Reader as GWReader,
@@ -25,6 +26,7 @@ use gw_types::{
 };
 use parking_lot::Mutex;
 use std::{convert::TryFrom, sync::Arc};
+use thiserror::Error;
 
 /// sync params
 pub struct SyncParam {
@@ -178,7 +180,7 @@ impl Chain {
         &self.store
     }
 
-    pub fn mem_pool(&self) -> &Mutex<MemPool> {
+    pub fn mem_pool(&self) -> &Arc<Mutex<MemPool>> {
         &self.mem_pool
     }
 
@@ -197,7 +199,7 @@ impl Chain {
             l2block_committed_info,
             context,
         } = action;
-        let global_state = parse_global_state(&transaction, &self.rollup_type_script_hash)?;
+        let global_state = extract_global_state(&transaction, &self.rollup_type_script_hash)?;
         assert!(
             {
                 let number: u64 = l2block_committed_info.number().unpack();
@@ -212,6 +214,11 @@ impl Chain {
             let status: u8 = self.local_state.last_global_state.status().into();
             Status::try_from(status).expect("invalid status")
         };
+        if let Some(record) =
+            self.build_rollup_action_record(db, &context, &global_state, &l2block_committed_info)
+        {
+            db.insert_rollup_action(&record)?;
+        }
         let event = match (status, context) {
             (
                 Status::Running,
@@ -221,8 +228,9 @@ impl Chain {
             ) => {
                 // Submit transactions
                 // parse layer2 block
-                let l2block = parse_l2block(&transaction, &self.rollup_type_script_hash)?;
+                let l2block = extract_l2_block(&transaction, &self.rollup_type_script_hash)?;
                 let number: u64 = l2block.raw().number().unpack();
+                let block_hash: H256 = l2block.hash().into();
                 if let Some(challenge_context) = self.process_block(
                     db,
                     l2block,
@@ -234,6 +242,10 @@ impl Chain {
                     self.bad_block_context = Some(challenge_context.target.clone());
                     SyncEvent::BadBlock(challenge_context)
                 } else {
+                    let output_index =
+                        find_rollup_cell(&transaction, &self.rollup_type_script_hash)
+                            .map_err(|err| err.with_tx(&transaction))?;
+                    db.insert_l2block_committed_output_index(&block_hash, output_index as u32)?;
                     println!("sync new block #{} success", number);
                     SyncEvent::Success
                 }
@@ -251,13 +263,13 @@ impl Chain {
                 } else {
                     // now, either we haven't found a bad block or the challenge is challenge a validate block
                     // in both cases the challenge is bad
-                    // TODO: implement this
-                    let _witness = VerifyTransactionWitness::default();
-                    unimplemented!();
+                    // TODO: implement this, wait for the challenge to be cancelled for now
                     // SyncEvent::BadChallenge {
                     //     witness,
                     //     tx_receipt,
                     // }
+                    eprintln!("challenge of a valid block: {}", context);
+                    SyncEvent::WaitChallenge
                 }
             }
             (Status::Halting, L1ActionContext::CancelChallenge { context: _ }) => {
@@ -291,6 +303,41 @@ impl Chain {
         Ok(event)
     }
 
+    /// Record of a non-block rollup action, returns None for a block submission
+    fn build_rollup_action_record(
+        &self,
+        db: &StoreTransaction,
+        context: &L1ActionContext,
+        global_state: &GlobalState,
+        committed_info: &L2BlockCommittedInfo,
+    ) -> Option<RollupActionRecord> {
+        let (action_type, challenge_target) = match context {
+            L1ActionContext::SubmitTxs { .. } => return None,
+            L1ActionContext::Challenge { context } => {
+                (RollupActionType::EnterChallenge, context.clone())
+            }
+            L1ActionContext::CancelChallenge { .. } => {
+                // target of the challenge being cancelled
+                let target = db
+                    .get_last_rollup_action()
+                    .map(|record| record.challenge_target())
+                    .unwrap_or_default();
+                (RollupActionType::CancelChallenge, target)
+            }
+            L1ActionContext::Revert { context } => (RollupActionType::Revert, context.clone()),
+        };
+        let record = RollupActionRecord::new_builder()
+            .action_type(action_type.into())
+            .prev_status(self.local_state.last_global_state.status())
+            .post_status(global_state.status())
+            .challenge_target(challenge_target)
+            .l1_block_number(committed_info.number())
+            .l1_block_hash(committed_info.block_hash())
+            .l1_tx_hash(committed_info.transaction_hash())
+            .build();
+        Some(record)
+    }
+
     /// revert a layer1 action
     fn revert_l1action(&mut self, db: &StoreTransaction, action: RevertedL1Action) -> Result<()> {
         let RevertedL1Action {
@@ -309,13 +356,12 @@ impl Chain {
             },
             "must be smaller than or equalled to last synced number"
         );
-        #[allow(clippy::single_match)]
         match context {
             L1ActionContext::SubmitTxs {
                 deposition_requests: _,
             } => {
                 // parse layer2 block
-                let l2block = parse_l2block(&transaction, &self.rollup_type_script_hash)?;
+                let l2block = extract_l2_block(&transaction, &self.rollup_type_script_hash)?;
                 assert_eq!(
                     l2block.hash(),
                     self.local_state.tip.hash(),
@@ -324,7 +370,10 @@ impl Chain {
                 db.detach_block(&l2block)?;
             }
             _ => {
-                // do nothing
+                db.delete_rollup_action(
+                    l2block_committed_info.number().unpack(),
+                    &l2block_committed_info.transaction_hash(),
+                )?;
             }
         };
 
@@ -357,10 +406,8 @@ impl Chain {
             }
         }
         db.commit()?;
-        // update mem pool state
-        self.mem_pool
-            .lock()
-            .notify_new_tip(self.local_state.tip.hash().into())?;
+        // update mem pool state, the pending state is recomputed without the mem-pool lock
+        MemPool::notify_new_tip_with_lock(&self.mem_pool, self.local_state.tip.hash().into())?;
         // check consistency of account SMT
         {
             // check account SMT, should be able to calculate account state root
@@ -459,64 +506,142 @@ impl Chain {
             result.receipts,
             deposition_requests,
         )?;
+        db.insert_block_storage_usage(&l2block.hash().into(), &result.storage_usage)?;
         db.attach_block(l2block.clone())?;
+        // record the roots computed by this node, they are compared with the global state on L1
+        let local_post_account = AccountMerkleState::new_builder()
+            .merkle_root(tree.calculate_root()?.pack())
+            .count(tree.get_account_count()?.pack())
+            .build();
+        let block_number: u64 = l2block.raw().number().unpack();
+        let local_post_block = BlockMerkleState::new_builder()
+            .merkle_root(db.get_block_smt_root()?.pack())
+            .count((block_number + 1).pack())
+            .build();
+        db.insert_block_local_post_state(
+            &l2block.hash().into(),
+            &local_post_account,
+            &local_post_block,
+        )?;
         tree.submit_tree()?;
         self.local_state.tip = l2block;
         Ok(None)
     }
 }
 
-fn parse_global_state(tx: &Transaction, rollup_id: &[u8; 32]) -> Result<GlobalState> {
-    // find rollup state cell from outputs
-    let (i, _) = tx
-        .raw()
-        .outputs()
-        .into_iter()
-        .enumerate()
-        .find(|(_i, output)| {
-            output.type_().to_opt().map(|type_| type_.hash()).as_ref() == Some(rollup_id)
-        })
-        .ok_or_else(|| anyhow!("no rollup cell found"))?;
+/// Errors of extracting the rollup action of a L1 transaction
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ExtractError {
+    #[error("no rollup cell in outputs")]
+    NoRollupCell,
+    #[error("multiple rollup cells in outputs {0:?}")]
+    MultipleRollupCells(Vec<usize>),
+    #[error("invalid global state in output data {0}")]
+    InvalidGlobalState(usize),
+    #[error("no rollup action in witnesses")]
+    NoRollupAction,
+    #[error("multiple rollup actions in witnesses {0:?}")]
+    MultipleRollupActions(Vec<usize>),
+    #[error("invalid l2block in witness {0}")]
+    InvalidL2Block(usize),
+}
 
-    let output_data: Bytes = tx
-        .raw()
-        .outputs_data()
-        .get(i)
-        .ok_or_else(|| anyhow!("no output data"))?
-        .unpack();
-    GlobalState::from_slice(&output_data).map_err(|_| anyhow!("global state unpacking error"))
+/// Extract error with the L1 transaction
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+#[error("extract rollup action from L1 tx {tx_hash:#x}: {error}")]
+pub struct ExtractErrorWithTx {
+    pub tx_hash: ckb_fixed_hash::H256,
+    pub error: ExtractError,
 }
 
-fn parse_l2block(tx: &Transaction, rollup_id: &[u8; 32]) -> Result<L2Block> {
-    // find rollup state cell from outputs
-    let (i, _) = tx
+impl ExtractError {
+    fn with_tx(self, tx: &Transaction) -> ExtractErrorWithTx {
+        ExtractErrorWithTx {
+            tx_hash: ckb_fixed_hash::H256(tx.hash()),
+            error: self,
+        }
+    }
+}
+
+/// Index of the rollup cell in outputs
+fn find_rollup_cell(tx: &Transaction, rollup_id: &[u8; 32]) -> Result<usize, ExtractError> {
+    let indexes: Vec<usize> = tx
         .raw()
         .outputs()
         .into_iter()
         .enumerate()
-        .find(|(_i, output)| {
+        .filter(|(_i, output)| {
             output.type_().to_opt().map(|type_| type_.hash()).as_ref() == Some(rollup_id)
         })
-        .ok_or_else(|| anyhow!("no rollup cell found"))?;
-
-    let witness: Bytes = tx
-        .witnesses()
-        .get(i)
-        .ok_or_else(|| anyhow!("no witness"))?
-        .unpack();
-    let witness_args = match WitnessArgsReader::verify(&witness, false) {
-        Ok(_) => WitnessArgs::new_unchecked(witness),
-        Err(_) => {
-            return Err(anyhow!("invalid witness"));
-        }
+        .map(|(i, _)| i)
+        .collect();
+    match indexes.as_slice() {
+        [] => Err(ExtractError::NoRollupCell),
+        [i] => Ok(*i),
+        _ => Err(ExtractError::MultipleRollupCells(indexes)),
+    }
+}
+
+pub fn extract_global_state(
+    tx: &Transaction,
+    rollup_id: &[u8; 32],
+) -> Result<GlobalState, ExtractErrorWithTx> {
+    let extract = || {
+        let i = find_rollup_cell(tx, rollup_id)?;
+        let output_data: Bytes = tx
+            .raw()
+            .outputs_data()
+            .get(i)
+            .ok_or(ExtractError::InvalidGlobalState(i))?
+            .unpack();
+        GlobalState::from_slice(&output_data).map_err(|_| ExtractError::InvalidGlobalState(i))
     };
-    let output_type: Bytes = witness_args
+    extract().map_err(|err| err.with_tx(tx))
+}
+
+// The rollup action of a witness, the action is in the output_type field
+fn witness_rollup_action(witness: &Bytes) -> Option<Bytes> {
+    if WitnessArgsReader::verify(witness, false).is_err() {
+        return None;
+    }
+    WitnessArgs::new_unchecked(witness.clone())
         .output_type()
         .to_opt()
-        .ok_or_else(|| anyhow!("output_type field is none"))?
-        .unpack();
-    match L2BlockReader::verify(&output_type, false) {
-        Ok(_) => Ok(L2Block::new_unchecked(output_type)),
-        Err(_) => Err(anyhow!("invalid l2block")),
-    }
+        .map(|output_type| output_type.unpack())
+}
+
+/// Extract the submitted l2block of the rollup cell.
+///
+/// The action is usually in the witness of the same index as the rollup cell,
+/// other witnesses are searched if it isn't there, a transaction must carry exactly
+/// one rollup action.
+pub fn extract_l2_block(
+    tx: &Transaction,
+    rollup_id: &[u8; 32],
+) -> Result<L2Block, ExtractErrorWithTx> {
+    let extract = || {
+        let rollup_index = find_rollup_cell(tx, rollup_id)?;
+        let witnesses: Vec<Bytes> = tx.witnesses().into_iter().map(|w| w.unpack()).collect();
+        let actions: Vec<(usize, Bytes)> =
+            match witnesses.get(rollup_index).and_then(witness_rollup_action) {
+                Some(action) => vec![(rollup_index, action)],
+                None => witnesses
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, witness)| witness_rollup_action(witness).map(|a| (i, a)))
+                    .filter(|(_i, action)| L2BlockReader::verify(action, false).is_ok())
+                    .collect(),
+            };
+        match actions.as_slice() {
+            [] => Err(ExtractError::NoRollupAction),
+            [(i, action)] => match L2BlockReader::verify(action, false) {
+                Ok(_) => Ok(L2Block::new_unchecked(action.clone())),
+                Err(_) => Err(ExtractError::InvalidL2Block(*i)),
+            },
+            _ => Err(ExtractError::MultipleRollupActions(
+                actions.iter().map(|(i, _)| *i).collect(),
+            )),
+        }
+    };
+    extract().map_err(|err| err.with_tx(tx))
 }
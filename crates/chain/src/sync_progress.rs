@@ -0,0 +1,99 @@
+//! Progress of syncing layer1 blocks
+//!
+//! The chain updater records the L1 tip and the L1 block it has synced to, so the missed
+//! blocks after a downtime can be reported as a percentage and an ETA while catching up.
+
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncStatus {
+    /// synced L1 block when the node started
+    pub start_block: u64,
+    pub synced_block: u64,
+    /// L1 tip, `None` until it's first read
+    pub tip_block: Option<u64>,
+    /// 0 to 100
+    pub percent: u8,
+    /// estimated from the speed since the start, `None` until a block is synced
+    pub eta: Option<Duration>,
+}
+
+impl SyncStatus {
+    /// Returns true if the synced block is within `blocks` of the L1 tip
+    pub fn is_within(&self, blocks: u64) -> bool {
+        match self.tip_block {
+            Some(tip_block) => tip_block.saturating_sub(self.synced_block) <= blocks,
+            None => false,
+        }
+    }
+}
+
+struct Progress {
+    start_block: Option<u64>,
+    synced_block: u64,
+    tip_block: Option<u64>,
+    started_at: Instant,
+}
+
+pub struct SyncProgress {
+    progress: Mutex<Progress>,
+}
+
+impl Default for SyncProgress {
+    fn default() -> Self {
+        SyncProgress {
+            progress: Mutex::new(Progress {
+                start_block: None,
+                synced_block: 0,
+                tip_block: None,
+                started_at: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl SyncProgress {
+    pub fn set_tip_block(&self, tip_block: u64) {
+        self.progress.lock().tip_block = Some(tip_block);
+    }
+
+    /// The first synced block is the start of the catch-up
+    pub fn set_synced_block(&self, synced_block: u64) {
+        let mut progress = self.progress.lock();
+        if progress.start_block.is_none() {
+            progress.start_block = Some(synced_block);
+            progress.started_at = Instant::now();
+        }
+        progress.synced_block = synced_block;
+    }
+
+    pub fn status(&self) -> SyncStatus {
+        let progress = self.progress.lock();
+        let start_block = progress.start_block.unwrap_or(progress.synced_block);
+        let synced = progress.synced_block.saturating_sub(start_block);
+        let (percent, eta) = match progress.tip_block {
+            Some(tip_block) if tip_block > progress.synced_block => {
+                let total = tip_block.saturating_sub(start_block);
+                let remaining = tip_block - progress.synced_block;
+                let percent = (synced * 100 / total) as u8;
+                let eta = if synced > 0 {
+                    let elapsed = progress.started_at.elapsed().as_secs_f64();
+                    Some(Duration::from_secs_f64(elapsed * remaining as f64 / synced as f64))
+                } else {
+                    None
+                };
+                (percent, eta)
+            }
+            Some(_) => (100, Some(Duration::from_secs(0))),
+            None => (0, None),
+        };
+        SyncStatus {
+            start_block,
+            synced_block: progress.synced_block,
+            tip_block: progress.tip_block,
+            percent,
+            eta,
+        }
+    }
+}
@@ -1,4 +1,24 @@
 //! defines builtin accounts
 
+/// Ids of the builtin accounts created by the default genesis
 pub const RESERVED_ACCOUNT_ID: u32 = 0;
 pub const CKB_SUDT_ACCOUNT_ID: u32 = 1;
+
+/// Ids of the builtin accounts of a rollup, resolve them from the genesis state
+/// instead of assuming the default layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinAccounts {
+    /// the meta contract account, send a tx to it to create a contract account
+    pub reserved_id: u32,
+    /// the CKB simple UDT account
+    pub ckb_sudt_id: u32,
+}
+
+impl Default for BuiltinAccounts {
+    fn default() -> Self {
+        BuiltinAccounts {
+            reserved_id: RESERVED_ACCOUNT_ID,
+            ckb_sudt_id: CKB_SUDT_ACCOUNT_ID,
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Entropy of layer2 blocks
+//!
+//! Contracts read a weak random seed of the block by a syscall. The entropy isn't chosen by
+//! the block producer, it's derived from the parent block hash and the number in the block
+//! header, so the block producer, every node and the state validator derive the same entropy
+//! of a block. Layer1 data isn't used, the parent block may not be committed to layer1 when
+//! the block is produced.
+//! A block producer can still withhold a block to retry, the entropy must not secure high values.
+
+use crate::blake2b::new_blake2b;
+
+/// Derive the entropy of the block `number` from the hash of its parent block
+pub fn derive_block_entropy(parent_block_hash: &[u8; 32], number: u64) -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    let mut hasher = new_blake2b();
+    hasher.update(parent_block_hash);
+    hasher.update(&number.to_le_bytes());
+    hasher.finalize(&mut buf);
+    buf
+}
@@ -9,6 +9,8 @@ cfg_if::cfg_if! {
             SMT(SMTError),
             #[error("Amount overflow")]
             AmountOverflow,
+            #[error("Insufficient balance")]
+            InsufficientBalance,
             #[error("Merkle proof error")]
             MerkleProof,
             #[error("Missing key error")]
@@ -21,6 +23,7 @@ cfg_if::cfg_if! {
         pub enum Error {
             SMT(SMTError),
             AmountOverflow,
+            InsufficientBalance,
             MerkleProof,
             MissingKey,
             Store,
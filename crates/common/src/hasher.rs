@@ -0,0 +1,58 @@
+//! Hash functions of accounts
+//!
+//! Accounts are blake2b based, the lock algorithms of other ecosystems may derive addresses
+//! with keccak256. Script hashes are always blake2b, since layer1 scripts verify them that way.
+
+use crate::blake2b::{new_blake2b, Blake2b};
+use crate::H256;
+use sha3::{Digest, Keccak256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashType {
+    Blake2b,
+    Keccak256,
+}
+
+impl Default for HashType {
+    fn default() -> Self {
+        HashType::Blake2b
+    }
+}
+
+impl HashType {
+    pub fn new_hasher(self) -> Hasher {
+        match self {
+            HashType::Blake2b => Hasher::Blake2b(new_blake2b()),
+            HashType::Keccak256 => Hasher::Keccak256(Keccak256::new()),
+        }
+    }
+
+    pub fn hash(self, data: &[u8]) -> H256 {
+        let mut hasher = self.new_hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+pub enum Hasher {
+    Blake2b(Blake2b),
+    Keccak256(Keccak256),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Blake2b(hasher) => hasher.update(data),
+            Hasher::Keccak256(hasher) => hasher.update(data),
+        }
+    }
+
+    pub fn finalize(self) -> H256 {
+        let mut hash = [0u8; 32];
+        match self {
+            Hasher::Blake2b(hasher) => hasher.finalize(&mut hash),
+            Hasher::Keccak256(hasher) => hash.copy_from_slice(&hasher.finalize()[..]),
+        }
+        hash.into()
+    }
+}
@@ -0,0 +1,58 @@
+//! JSON H256
+//!
+//! The RPC uses `ckb_fixed_hash::H256` (0x prefixed hex) while the state and the store
+//! use the SMT `H256`. `JsonH256` is the single place that converts between them, the
+//! bytes are copied as they are, no conversion should reverse or reinterpret them.
+
+use crate::H256;
+use ckb_fixed_hash::H256 as FixedH256;
+use serde::{Deserialize, Serialize};
+
+/// H256 serialized as 0x prefixed hex
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(from = "FixedH256", into = "FixedH256")]
+pub struct JsonH256([u8; 32]);
+
+impl JsonH256 {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for JsonH256 {
+    fn from(bytes: [u8; 32]) -> Self {
+        JsonH256(bytes)
+    }
+}
+
+impl From<JsonH256> for [u8; 32] {
+    fn from(h: JsonH256) -> Self {
+        h.0
+    }
+}
+
+impl From<H256> for JsonH256 {
+    fn from(h: H256) -> Self {
+        JsonH256(h.into())
+    }
+}
+
+impl From<JsonH256> for H256 {
+    fn from(h: JsonH256) -> Self {
+        h.0.into()
+    }
+}
+
+impl From<FixedH256> for JsonH256 {
+    fn from(h: FixedH256) -> Self {
+        JsonH256(h.0)
+    }
+}
+
+impl From<JsonH256> for FixedH256 {
+    fn from(h: JsonH256) -> Self {
+        FixedH256(h.0)
+    }
+}
@@ -1,8 +1,12 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub mod builtins;
+pub mod entropy;
 pub mod error;
 pub mod h256_ext;
+pub mod hasher;
+#[cfg(feature = "std")]
+pub mod json_h256;
 pub mod merkle_utils;
 pub mod smt;
 pub mod state;
@@ -11,6 +15,8 @@ pub mod state;
 
 pub use gw_hash::blake2b;
 pub use h256_ext::H256;
+#[cfg(feature = "std")]
+pub use json_h256::JsonH256;
 pub use sparse_merkle_tree;
 
 /// constants
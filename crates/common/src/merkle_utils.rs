@@ -2,7 +2,8 @@ use crate::vec::Vec;
 use crate::{
     blake2b::new_blake2b,
     h256_ext::H256Ext,
-    smt::{default_store::DefaultStore, Error, H256, SMT},
+    smt::{default_store::DefaultStore, Blake2bHasher, CompiledMerkleProof, Error, H256, SMT},
+    state::build_account_key,
 };
 
 // Calculate compacted account root
@@ -26,3 +27,47 @@ pub fn calculate_merkle_root(leaves: Vec<[u8; 32]>) -> Result<[u8; 32], Error> {
     }
     Ok((*tree.root()).into())
 }
+
+/// Compute merkle proof of the leaf at `index`, the proof can be verified against
+/// the root returned by `calculate_merkle_root`
+pub fn calculate_merkle_proof(
+    leaves: Vec<[u8; 32]>,
+    index: u32,
+) -> Result<CompiledMerkleProof, Error> {
+    let mut tree = SMT::<DefaultStore<H256>>::default();
+    let mut leaf_opt = None;
+    for (i, leaf) in leaves.into_iter().enumerate() {
+        if i as u32 == index {
+            leaf_opt = Some(leaf);
+        }
+        tree.update(H256::from_u32(i as u32), leaf.into())?;
+    }
+    let leaf = leaf_opt.unwrap_or_default();
+    let key = H256::from_u32(index);
+    tree.merkle_proof(vec![key])?
+        .compile(vec![(key, leaf.into())])
+}
+
+/// SMT key of the balance of an account in a sUDT account
+pub fn build_sudt_balance_key(sudt_id: u32, account_id: u32) -> H256 {
+    build_account_key(sudt_id, H256::from_u32(account_id).as_slice())
+}
+
+/// Verify a compiled proof of sUDT balances against the account SMT root of a block,
+/// `balances` are (account_id, sudt_id, balance), a zero balance proves the key is absent
+pub fn verify_sudt_balances_proof(
+    account_root: &H256,
+    proof: Vec<u8>,
+    balances: &[(u32, u32, u128)],
+) -> Result<bool, Error> {
+    let leaves = balances
+        .iter()
+        .map(|(account_id, sudt_id, balance)| {
+            (
+                build_sudt_balance_key(*sudt_id, *account_id),
+                H256::from_u128(*balance),
+            )
+        })
+        .collect();
+    CompiledMerkleProof(proof).verify::<Blake2bHasher>(account_root, leaves)
+}
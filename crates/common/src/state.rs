@@ -25,9 +25,16 @@ use core::mem::size_of;
 pub const GW_ACCOUNT_KV: u8 = 0;
 pub const GW_ACCOUNT_NONCE: u8 = 1;
 pub const GW_ACCOUNT_SCRIPT_HASH: u8 = 2;
+pub const GW_ACCOUNT_SUDT_TOTAL_SUPPLY: u8 = 5;
 /* Non-account types */
 pub const GW_SCRIPT_HASH_TO_ID_PREFIX: [u8; 5] = [0, 0, 0, 0, 3];
 pub const GW_DATA_HASH_PREFIX: [u8; 5] = [0, 0, 0, 0, 4];
+pub const GW_L2_TO_L1_MESSAGE_PREFIX: [u8; 5] = [0, 0, 0, 0, 6];
+/* Log service flags */
+/// Logs with this flag are L2 to L1 messages, the flag is reserved for the SYS_SEND_MESSAGE syscall
+pub const GW_LOG_L2_TO_L1_MESSAGE: u8 = 0xff;
+/// Logs with this flag record the error of a failed tx, the flag is reserved for the generator
+pub const GW_LOG_TX_FAILED: u8 = 0xfe;
 
 /* Generate a SMT key
  * raw_key: blake2b(id | type | key)
@@ -51,6 +58,14 @@ pub fn build_account_field_key(id: u32, type_: u8) -> H256 {
     key.into()
 }
 
+/// Returns true if the key is a sUDT total supply key
+/// total supply is only changed by mint and burn, contracts are not allowed to write it
+pub fn is_sudt_total_supply_key(key: &H256) -> bool {
+    let key = key.as_slice();
+    key[size_of::<u32>()] == GW_ACCOUNT_SUDT_TOTAL_SUPPLY
+        && key[size_of::<u32>() + 1..].iter().all(|b| *b == 0)
+}
+
 pub fn build_script_hash_to_account_id_key(script_hash: &[u8]) -> H256 {
     let mut key: [u8; 32] = H256::zero().into();
     let mut hasher = new_blake2b();
@@ -69,6 +84,27 @@ pub fn build_data_hash_key(data_hash: &[u8]) -> H256 {
     key.into()
 }
 
+/// L2 to L1 message hash
+/// hash: blake2b(GW_L2_TO_L1_MESSAGE_PREFIX | from_id(4 bytes) | payload)
+pub fn build_l2_to_l1_message_hash(from_id: u32, payload: &[u8]) -> H256 {
+    let mut hash = [0u8; 32];
+    let mut hasher = new_blake2b();
+    hasher.update(&GW_L2_TO_L1_MESSAGE_PREFIX);
+    hasher.update(&from_id.to_le_bytes());
+    hasher.update(payload);
+    hasher.finalize(&mut hash);
+    hash.into()
+}
+
+pub fn build_l2_to_l1_message_key(message_hash: &[u8]) -> H256 {
+    let mut key: [u8; 32] = H256::zero().into();
+    let mut hasher = new_blake2b();
+    hasher.update(&GW_L2_TO_L1_MESSAGE_PREFIX);
+    hasher.update(message_hash);
+    hasher.finalize(&mut key);
+    key.into()
+}
+
 pub struct PrepareWithdrawalRecord {
     pub withdrawal_lock_hash: H256,
     pub amount: u128,
@@ -158,26 +194,106 @@ pub trait State {
         Ok(v == H256::one())
     }
 
+    /// Register a L2 to L1 message into the state,
+    /// L1 scripts consume the message by proving the key against a finalized account root
+    fn store_l2_to_l1_message_hash(&mut self, message_hash: H256) -> Result<(), Error> {
+        let key = build_l2_to_l1_message_key(message_hash.as_slice());
+        self.update_raw(key, H256::one())?;
+        Ok(())
+    }
+
+    fn is_l2_to_l1_message_exist(&self, message_hash: &H256) -> Result<bool, Error> {
+        let key = build_l2_to_l1_message_key(message_hash.as_slice());
+        let v = self.get_raw(&key)?;
+        Ok(v == H256::one())
+    }
+
     /// Mint SUDT token on layer2
+    /// returns `AmountOverflow` if the balance exceeds u128::MAX
     fn mint_sudt(&mut self, sudt_id: u32, id: u32, amount: u128) -> Result<(), Error> {
-        let raw_key = build_account_key(sudt_id, &H256::from_u32(id).as_slice());
-        // calculate balance
-        let mut balance = self.get_raw(&raw_key)?.to_u128();
-        balance = balance.checked_add(amount).ok_or(Error::AmountOverflow)?;
-        self.update_raw(raw_key, H256::from_u128(balance))?;
-        Ok(())
+        let balance = self.get_sudt_balance(sudt_id, id)?;
+        let new_balance = balance.checked_add(amount).ok_or(Error::AmountOverflow)?;
+        let total_supply = self.get_sudt_total_supply(sudt_id)?;
+        let new_total_supply = total_supply
+            .checked_add(amount)
+            .ok_or(Error::AmountOverflow)?;
+        self.set_sudt_balance(sudt_id, id, new_balance)?;
+        self.set_sudt_total_supply(sudt_id, new_total_supply)
     }
 
     /// burn SUDT
+    /// returns `InsufficientBalance` if the balance is lower than amount
     fn burn_sudt(&mut self, sudt_id: u32, id: u32, amount: u128) -> Result<(), Error> {
-        let raw_key = build_account_key(sudt_id, &H256::from_u32(id).as_slice());
-        // calculate balance
-        let mut balance = self.get_raw(&raw_key)?.to_u128();
-        balance = balance.checked_sub(amount).ok_or(Error::AmountOverflow)?;
-        self.update_raw(raw_key, H256::from_u128(balance))?;
+        let balance = self.get_sudt_balance(sudt_id, id)?;
+        let new_balance = balance
+            .checked_sub(amount)
+            .ok_or(Error::InsufficientBalance)?;
+        // total supply is never lower than a balance
+        let total_supply = self.get_sudt_total_supply(sudt_id)?;
+        let new_total_supply = total_supply
+            .checked_sub(amount)
+            .ok_or(Error::InsufficientBalance)?;
+        self.set_sudt_balance(sudt_id, id, new_balance)?;
+        self.set_sudt_total_supply(sudt_id, new_total_supply)
+    }
+
+    /// Transfer SUDT between two accounts
+    /// the state is untouched if any of the checks failed
+    fn transfer_sudt(
+        &mut self,
+        sudt_id: u32,
+        from_id: u32,
+        to_id: u32,
+        amount: u128,
+    ) -> Result<(), Error> {
+        let from_balance = self.get_sudt_balance(sudt_id, from_id)?;
+        let new_from_balance = from_balance
+            .checked_sub(amount)
+            .ok_or(Error::InsufficientBalance)?;
+        if from_id == to_id {
+            return Ok(());
+        }
+        let to_balance = self.get_sudt_balance(sudt_id, to_id)?;
+        let new_to_balance = to_balance
+            .checked_add(amount)
+            .ok_or(Error::AmountOverflow)?;
+        self.set_sudt_balance(sudt_id, from_id, new_from_balance)?;
+        self.set_sudt_balance(sudt_id, to_id, new_to_balance)?;
         Ok(())
     }
 
+    /// Pay fee to the block producer
+    fn pay_fee(
+        &mut self,
+        sudt_id: u32,
+        payer_id: u32,
+        block_producer_id: u32,
+        amount: u128,
+    ) -> Result<(), Error> {
+        self.transfer_sudt(sudt_id, payer_id, block_producer_id, amount)
+    }
+
+    fn set_sudt_balance(&mut self, sudt_id: u32, id: u32, balance: u128) -> Result<(), Error> {
+        let raw_key = build_account_key(sudt_id, &H256::from_u32(id).as_slice());
+        self.update_raw(raw_key, H256::from_u128(balance))
+    }
+
+    /// Total supply of a SUDT on layer2
+    fn get_sudt_total_supply(&self, sudt_id: u32) -> Result<u128, Error> {
+        let value = self.get_raw(&build_account_field_key(
+            sudt_id,
+            GW_ACCOUNT_SUDT_TOTAL_SUPPLY,
+        ))?;
+        Ok(value.to_u128())
+    }
+
+    fn set_sudt_total_supply(&mut self, sudt_id: u32, total_supply: u128) -> Result<(), Error> {
+        self.update_raw(
+            build_account_field_key(sudt_id, GW_ACCOUNT_SUDT_TOTAL_SUPPLY),
+            H256::from_u128(total_supply),
+        )
+    }
+
     /// calculate compacted account root
     fn calculate_compacted_account_root(&self) -> Result<H256, Error> {
         let account_root = self.calculate_root()?;
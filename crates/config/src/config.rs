@@ -8,6 +8,7 @@ use std::path::PathBuf;
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Config {
+    /// read again at runtime by `gw_admin_reload_backends`
     pub backends: Vec<BackendConfig>,
     pub store: StoreConfig,
     pub genesis: GenesisConfig,
@@ -15,11 +16,231 @@ pub struct Config {
     pub rpc_client: RPCClientConfig,
     pub rpc_server: RPCServerConfig,
     pub block_producer: Option<BlockProducerConfig>,
+    #[serde(default)]
+    pub mem_pool: MemPoolConfig,
+    #[serde(default)]
+    pub alert: AlertConfig,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemPoolConfig {
+    #[serde(default)]
+    pub deny_list: DenyListConfig,
+    #[serde(default)]
+    pub limits: MemPoolLimitsConfig,
+    #[serde(default)]
+    pub storage_limits: StorageLimitsConfig,
+}
+
+/// Limits of the txs in the mem-pool, once the pool is full the txs paying the lowest
+/// gas price are evicted
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemPoolLimitsConfig {
+    pub max_txs: usize,
+    /// max total bytes of the serialized txs
+    pub max_tx_bytes: usize,
+    pub max_txs_per_account: usize,
+    /// txs are dropped if they stay in the pool longer than this, 0 means no limit
+    pub tx_ttl_secs: u64,
+}
+
+impl Default for MemPoolLimitsConfig {
+    fn default() -> Self {
+        MemPoolLimitsConfig {
+            max_txs: 6000,
+            max_tx_bytes: 64 * 1024 * 1024,
+            max_txs_per_account: 64,
+            tx_ttl_secs: 3 * 60 * 60,
+        }
+    }
+}
+
+/// Limits of the storage growth, txs exceeding them are refused by the mem-pool.
+/// They aren't consensus rules, the blocks produced by other nodes are not checked.
+/// A tx which frees storage is always accepted
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StorageLimitsConfig {
+    /// max storage slots owned by an account
+    #[serde(default)]
+    pub max_account_slots: Option<u64>,
+    /// max data bytes owned by an account
+    #[serde(default)]
+    pub max_account_data_bytes: Option<u64>,
+    /// max storage slots created by a tx
+    #[serde(default)]
+    pub max_tx_new_slots: Option<u64>,
+    /// max data bytes stored by a tx
+    #[serde(default)]
+    pub max_tx_data_bytes: Option<u64>,
+}
+
+/// Txs sent from or to the listed accounts are refused by the mem-pool
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DenyListConfig {
+    #[serde(default)]
+    pub from_ids: Vec<u32>,
+    #[serde(default)]
+    pub to_ids: Vec<u32>,
+    /// account script hashes, matches both the sender and the receiver
+    #[serde(default)]
+    pub script_hashes: Vec<H256>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RPCServerConfig {
     pub listen: String,
+    /// more addresses to listen on, e.g. an IPv6 address besides an IPv4 one
+    #[serde(default)]
+    pub extra_listen: Vec<String>,
+    /// deadline of a read-only `execute_l2transaction` call,
+    /// clients can only ask for a shorter one
+    #[serde(default = "default_execute_timeout_ms")]
+    pub execute_timeout_ms: u64,
+    /// max syscalls recorded by a `trace_transaction` call, the tracing fails once it's reached
+    #[serde(default = "default_max_trace_steps")]
+    pub max_trace_steps: usize,
+    /// append state-mutating calls to this file if set
+    #[serde(default)]
+    pub audit_log: Option<PathBuf>,
+    /// state RPCs answer from the last finalized block unless a request asks for the tip
+    #[serde(default)]
+    pub serve_finalized_state: bool,
+    /// connections accepted beyond this are closed at once
+    #[serde(default = "default_max_connections")]
+    pub max_connections: usize,
+    /// requests of a connection served at the same time
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+    /// close a connection if a pending read doesn't complete in time,
+    /// idle keep-alive connections are closed after it too
+    #[serde(default = "default_io_timeout_ms")]
+    pub read_timeout_ms: u64,
+    /// close a connection if a pending write doesn't complete in time
+    #[serde(default = "default_io_timeout_ms")]
+    pub write_timeout_ms: u64,
+    /// max requests in a JSONRPC batch, larger batches are rejected as a whole
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+    /// max size of a request body, counted after decompression
+    #[serde(default = "default_max_request_body_size")]
+    pub max_request_body_size: usize,
+    /// calls per second accepted from a client IP, each call of a batch and each WebSocket
+    /// message is counted. Bursts up to the same number of calls are allowed. 0 means no limit
+    #[serde(default)]
+    pub max_requests_per_second_per_ip: u32,
+    /// methods answered as if they don't exist, a pattern ending with `*` matches a prefix,
+    /// e.g. `debug_*`
+    #[serde(default)]
+    pub disabled_methods: Vec<String>,
+    /// start in maintenance mode, `submit_*` methods are rejected until it's turned off by
+    /// `gw_admin_set_maintenance_mode` on the `admin_listen` address
+    #[serde(default)]
+    pub maintenance_mode: bool,
+    /// open the JSONRPC ports once the synced L1 block is within this many blocks of the L1 tip,
+    /// so a node catching up after a downtime doesn't serve a stale state. Open at once if not set
+    #[serde(default)]
+    pub serve_within_l1_blocks: Option<u64>,
+    /// the `gw_admin_*` methods are only served on this address, which must not be reachable
+    /// from the public network, e.g. `localhost:8120`. Admin methods are unavailable if not set
+    #[serde(default)]
+    pub admin_listen: Option<String>,
+}
+
+fn default_execute_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_max_trace_steps() -> usize {
+    10_000
+}
+
+fn default_max_connections() -> usize {
+    1024
+}
+
+fn default_max_concurrent_requests() -> usize {
+    16
+}
+
+fn default_io_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_max_batch_size() -> usize {
+    100
+}
+
+fn default_max_request_body_size() -> usize {
+    10 * 1024 * 1024
+}
+
+impl Default for RPCServerConfig {
+    fn default() -> Self {
+        RPCServerConfig {
+            listen: String::new(),
+            extra_listen: Vec::new(),
+            execute_timeout_ms: default_execute_timeout_ms(),
+            max_trace_steps: default_max_trace_steps(),
+            audit_log: None,
+            serve_finalized_state: false,
+            max_connections: default_max_connections(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+            read_timeout_ms: default_io_timeout_ms(),
+            write_timeout_ms: default_io_timeout_ms(),
+            max_batch_size: default_max_batch_size(),
+            max_request_body_size: default_max_request_body_size(),
+            max_requests_per_second_per_ip: 0,
+            disabled_methods: Vec::new(),
+            maintenance_mode: false,
+            serve_within_l1_blocks: None,
+            admin_listen: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Default for AlertSeverity {
+    fn default() -> Self {
+        AlertSeverity::Warning
+    }
+}
+
+/// Alerts are posted as JSON to the webhooks
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AlertConfig {
+    #[serde(default)]
+    pub webhooks: Vec<AlertWebhookConfig>,
+    /// alert if the indexer is behind the CKB tip by more blocks
+    #[serde(default = "default_indexer_lag_threshold")]
+    pub indexer_lag_threshold: u64,
+}
+
+fn default_indexer_lag_threshold() -> u64 {
+    100
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        AlertConfig {
+            webhooks: Vec::new(),
+            indexer_lag_threshold: default_indexer_lag_threshold(),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AlertWebhookConfig {
+    pub url: String,
+    /// alerts less severe than this are not sent to the webhook
+    #[serde(default)]
+    pub min_severity: AlertSeverity,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
@@ -33,6 +254,139 @@ pub struct RPCClientConfig {
 pub struct ChainConfig {
     pub genesis_committed_info: L2BlockCommittedInfo,
     pub rollup_type_script: Script,
+    #[serde(default)]
+    pub block_limits: BlockLimitsConfig,
+    /// min gas fee in CKB of a layer2 tx for each account it creates, it prevents filling the
+    /// state with empty accounts. It's only checked by the mem-pool, the fee goes to the block
+    /// producer as the gas fee
+    #[serde(default)]
+    pub account_creation_fee: u64,
+    /// format of the account script args. The mem-pool rejects txs creating accounts with
+    /// malformed args and the block producer skips such deposits, blocks with them are still valid
+    #[serde(default)]
+    pub account_script_args: Vec<ScriptArgsConfig>,
+    /// signature algorithm of the account locks, txs and withdrawals of the accounts with other
+    /// locks are rejected. The block producer and validators must use the same locks
+    #[serde(default)]
+    pub account_locks: Vec<AccountLockConfig>,
+    #[serde(default)]
+    pub deposit_limits: DepositLimitsConfig,
+    #[serde(default)]
+    pub withdrawal_fee: WithdrawalFeeConfig,
+}
+
+/// Min fee of a withdrawal accepted by the mem-pool. The fee is signed by the withdrawer in
+/// the request, it's charged from the layer2 balances on top of the withdrawn assets when
+/// the withdrawal is packaged, and credited to the block producer. Validators charge the
+/// signed fee, so nodes may use different minimums
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WithdrawalFeeConfig {
+    /// min CKB fee of every withdrawal, in shannons
+    #[serde(default)]
+    pub ckb_fee: u64,
+    /// min sUDT fee of withdrawals of the sUDTs, sUDTs not listed have no min fee
+    #[serde(default)]
+    pub sudt_fees: Vec<SUDTWithdrawalFeeConfig>,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SUDTWithdrawalFeeConfig {
+    /// layer1 sUDT script hash, same as the `sudt_script_hash` of the withdrawals
+    pub sudt_script_hash: H256,
+    /// min fee in the sUDT
+    pub fee: u64,
+}
+
+/// Deposits below the minimums are not collected, so dust can't make the rollup create
+/// uneconomical custodian cells, the owner cancels such a deposit after the cancel timeout.
+/// It's a policy of the block producer, blocks with smaller deposits are still valid
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DepositLimitsConfig {
+    /// min capacity of a deposit cell, in shannons
+    #[serde(default)]
+    pub min_capacity: u64,
+    /// min sUDT amount of a deposit cell with a sUDT type script
+    #[serde(default)]
+    pub min_sudt_amount: u64,
+}
+
+/// Format of the args of the account scripts with the code hash
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptArgsConfig {
+    pub code_hash: H256,
+    /// args start with the rollup script hash
+    #[serde(default)]
+    pub rollup_prefix: bool,
+    /// length of the args after the rollup prefix
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+/// Signature algorithm of the account locks with the code hash
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountLockConfig {
+    pub code_hash: H256,
+    pub algorithm: LockAlgorithmType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockAlgorithmType {
+    /// signed with an ethereum key, the lock args are the 20 bytes address
+    Secp256k1Eth,
+    /// `Secp256k1Eth` which also accepts withdrawals signed as EIP-712 typed data,
+    /// only for a lock script which verifies the typed data signatures on-chain
+    Secp256k1EthEip712,
+    /// signed with a CKB secp256k1 key, the lock args are the blake160 hash of the pubkey
+    Secp256k1Ckb,
+}
+
+/// Limits keep a block fit in a layer1 transaction.
+/// They are node-local, so they are only enforced when the mem-pool and the block producer
+/// collect the txs, a validator doesn't reject a block by them
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockLimitsConfig {
+    /// max bytes of the serialized L2Block in the rollup cell witness
+    pub max_block_size: usize,
+    pub max_tx_count: u32,
+    pub max_tx_args_size: usize,
+    /// max total cycles of the txs packed into a block, the cycles aren't part of the block,
+    /// so only the block producer enforces it
+    #[serde(default = "default_max_block_cycles")]
+    pub max_block_cycles: u64,
+    /// max total gas limit of the txs packaged by the mem-pool
+    #[serde(default = "default_max_block_gas")]
+    pub max_block_gas: u64,
+    /// max cycles of a collected tx, whatever its gas limit is, it also bounds the read-only
+    /// executions of the RPC. The txs of a block are only bounded by their gas limits
+    #[serde(default = "default_max_tx_cycles")]
+    pub max_tx_cycles: u64,
+}
+
+fn default_max_block_cycles() -> u64 {
+    1_000_000_000
+}
+
+fn default_max_block_gas() -> u64 {
+    default_max_block_cycles()
+}
+
+fn default_max_tx_cycles() -> u64 {
+    default_max_block_cycles()
+}
+
+impl Default for BlockLimitsConfig {
+    fn default() -> Self {
+        // CKB limits a transaction to about 500KB
+        BlockLimitsConfig {
+            max_block_size: 400_000,
+            max_tx_count: 2000,
+            max_tx_args_size: 25_000,
+            max_block_cycles: default_max_block_cycles(),
+            max_block_gas: default_max_block_gas(),
+            max_tx_cycles: default_max_tx_cycles(),
+        }
+    }
 }
 
 /// Genesis config
@@ -58,11 +412,83 @@ pub struct BlockProducerConfig {
     pub rollup_cell_type_dep: CellDep,
     pub deposit_cell_lock_dep: CellDep,
     pub wallet_config: WalletConfig,
+    /// replay every produced block through the sync path and compare the state roots
+    /// before submitting it, a divergence would be challenged by validators
+    #[serde(default)]
+    pub check_block_replay: bool,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct StoreConfig {
     pub path: PathBuf,
+    /// mirror the writes to a secondary DB, for validating store changes
+    #[serde(default)]
+    pub mirror: Option<StoreMirrorConfig>,
+    /// move the bodies and receipts of the old blocks to a secondary DB
+    #[serde(default)]
+    pub cold: Option<ColdStoreConfig>,
+    /// delete the superseded SMT nodes of the old blocks, all states are kept if not set
+    #[serde(default)]
+    pub smt_pruning: Option<SMTPruningConfig>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SMTPruningConfig {
+    /// states of the latest blocks are kept, it must be at least the finality blocks of the
+    /// rollup so a challenged block can be replayed
+    pub pruning_depth: u64,
+    /// interval between two pruning rounds
+    #[serde(default = "default_smt_pruning_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_smt_pruning_interval_secs() -> u64 {
+    600
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoreMirrorConfig {
+    /// path of the secondary DB, a temporary directory is used if it's not set
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// interval between two root comparisons
+    #[serde(default = "default_mirror_check_interval_secs")]
+    pub check_interval_secs: u64,
+}
+
+fn default_mirror_check_interval_secs() -> u64 {
+    60
+}
+
+impl Default for StoreMirrorConfig {
+    fn default() -> Self {
+        StoreMirrorConfig {
+            path: None,
+            check_interval_secs: default_mirror_check_interval_secs(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ColdStoreConfig {
+    /// path of the cold DB, it must be persistent since the number of the next block to move
+    /// is stored in the primary DB
+    pub path: PathBuf,
+    /// bodies of the latest blocks are kept in the primary DB, it should be larger than the
+    /// finality so the moved blocks aren't reverted
+    #[serde(default = "default_cold_store_hot_blocks")]
+    pub hot_blocks: u64,
+    /// interval between two moves
+    #[serde(default = "default_cold_store_move_interval_secs")]
+    pub move_interval_secs: u64,
+}
+
+fn default_cold_store_hot_blocks() -> u64 {
+    10_000
+}
+
+fn default_cold_store_move_interval_secs() -> u64 {
+    60
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
@@ -5,11 +5,11 @@ use crate::transaction::RocksDBTransaction;
 use crate::write_batch::RocksDBWriteBatch;
 use crate::{internal_error, Result};
 use rocksdb::ops::{
-    CreateCF, DropCF, GetColumnFamilys, GetPinned, GetPinnedCF, IterateCF, OpenCF, Put, SetOptions,
-    WriteOps,
+    CompactRangeCF, CreateCF, DropCF, GetColumnFamilys, GetPinned, GetPinnedCF, GetPropertyCF,
+    IterateCF, OpenCF, Put, SetOptions, WriteOps,
 };
 use rocksdb::{
-    ffi, ColumnFamily, ColumnFamilyDescriptor, DBPinnableSlice, FullOptions, IteratorMode,
+    ffi, ColumnFamily, ColumnFamilyDescriptor, DBPinnableSlice, Env, FullOptions, IteratorMode,
     OptimisticTransactionDB, OptimisticTransactionOptions, Options, WriteBatch, WriteOptions,
 };
 use std::sync::Arc;
@@ -20,6 +20,8 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct RocksDB {
     pub(crate) inner: Arc<OptimisticTransactionDB>,
+    // the env of an in-memory DB, it must outlive the DB
+    _env: Option<Arc<Env>>,
 }
 
 impl RocksDB {
@@ -110,6 +112,7 @@ impl RocksDB {
 
         Ok(RocksDB {
             inner: Arc::new(db),
+            _env: None,
         })
     }
 
@@ -126,6 +129,26 @@ impl RocksDB {
         Self::open_with_check(&config, columns).unwrap_or_else(|err| panic!("{}", err))
     }
 
+    /// Open a DB which keeps all data in memory, the data is dropped with the DB
+    #[cfg(feature = "in-memory")]
+    pub fn open_in_memory(columns: u32) -> Self {
+        let env = Env::mem_env().unwrap_or_else(|err| panic!("failed to create mem env: {}", err));
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        opts.set_env(&env);
+        let cf_descriptors: Vec<_> = (0..columns)
+            .map(|c| ColumnFamilyDescriptor::new(c.to_string(), Options::default()))
+            .collect();
+        // the path is only a name in the mem env
+        let db = OptimisticTransactionDB::open_cf_descriptors(&opts, "/godwoken", cf_descriptors)
+            .unwrap_or_else(|err| panic!("failed to open the in-memory database: {}", err));
+        RocksDB {
+            inner: Arc::new(db),
+            _env: Some(Arc::new(env)),
+        }
+    }
+
     pub fn get_pinned(&self, col: Col, key: &[u8]) -> Result<Option<DBPinnableSlice>> {
         let cf = cf_handle(&self.inner, col)?;
         self.inner.get_pinned_cf(cf, &key).map_err(internal_error)
@@ -188,6 +211,22 @@ impl RocksDB {
         }
     }
 
+    /// Compact the underlying storage for the key range [start, end]
+    /// a `None` bound means the range is unbounded on that side
+    pub fn compact_range(&self, col: Col, start: Option<&[u8]>, end: Option<&[u8]>) -> Result<()> {
+        let cf = cf_handle(&self.inner, col)?;
+        self.inner.compact_range_cf(cf, start, end);
+        Ok(())
+    }
+
+    /// Return an integer rocksdb property of the column, e.g. "rocksdb.total-sst-files-size"
+    pub fn property_int_value(&self, col: Col, name: &str) -> Result<Option<u64>> {
+        let cf = cf_handle(&self.inner, col)?;
+        self.inner
+            .property_int_value_cf(cf, name)
+            .map_err(internal_error)
+    }
+
     pub fn inner(&self) -> Arc<OptimisticTransactionDB> {
         Arc::clone(&self.inner)
     }
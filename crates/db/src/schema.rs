@@ -3,7 +3,7 @@
 /// Column families alias type
 pub type Col = u8;
 /// Total column number
-pub const COLUMNS: u32 = 19;
+pub const COLUMNS: u32 = 27;
 /// Column store meta data
 pub const COLUMN_META: Col = 0;
 /// Column store chain index
@@ -42,6 +42,22 @@ pub const COLUMN_BLOCK_DEPOSITION_REQUESTS: Col = 16;
 pub const COLUMN_CUSTODIAN_ASSETS: Col = 17;
 /// Column block state record
 pub const COLUMN_BLOCK_STATE_RECORD: Col = 18;
+/// Column block economics
+pub const COLUMN_BLOCK_ECONOMICS: Col = 19;
+/// Column non-block rollup actions
+pub const COLUMN_ROLLUP_ACTION: Col = 20;
+/// Column index of the rollup cell in the L1 transaction which committed a block
+pub const COLUMN_L2BLOCK_COMMITTED_OUTPUT_INDEX: Col = 21;
+/// Column index of the main chain txs sent from or to an account
+pub const COLUMN_ACCOUNT_TRANSACTION: Col = 22;
+/// Column index of the access lists of the txs of a produced block
+pub const COLUMN_BLOCK_ACCESS_LIST: Col = 23;
+/// Column storage changes of the accounts called by the txs of a block
+pub const COLUMN_BLOCK_STORAGE_USAGE: Col = 24;
+/// Column storage usage of an account on the main chain, signed totals of the block changes
+pub const COLUMN_ACCOUNT_STORAGE_USAGE: Col = 25;
+/// Column post account state and block SMT state of a block computed by this node
+pub const COLUMN_BLOCK_LOCAL_POST_STATE: Col = 26;
 
 /// chain id
 pub const META_CHAIN_ID_KEY: &[u8] = b"CHAIN_ID";
@@ -53,8 +69,12 @@ pub const META_BLOCK_SMT_ROOT_KEY: &[u8] = b"BLOCK_SMT_ROOT_KEY";
 pub const META_ACCOUNT_SMT_ROOT_KEY: &[u8] = b"ACCOUNT_SMT_ROOT_KEY";
 /// account SMT count
 pub const META_ACCOUNT_SMT_COUNT_KEY: &[u8] = b"ACCOUNT_SMT_COUNT_KEY";
+/// number of the next block to move to the cold DB
+pub const META_COLD_BLOCK_NUMBER_KEY: &[u8] = b"COLD_BLOCK_NUMBER_KEY";
+/// block number of the SMT pruning horizon, states of the blocks below it are incomplete
+pub const META_SMT_PRUNING_HORIZON_KEY: &[u8] = b"SMT_PRUNING_HORIZON_KEY";
 
 /// CHAIN_SPEC_HASH_KEY tracks the hash of chain spec which created current database
 pub const CHAIN_SPEC_HASH_KEY: &[u8] = b"chain-spec-hash";
-/// CHAIN_SPEC_HASH_KEY tracks the current database version.
+/// MIGRATION_VERSION_KEY tracks the current database version.
 pub const MIGRATION_VERSION_KEY: &[u8] = b"db-version";
@@ -0,0 +1,90 @@
+//! Events published by a godwoken node
+//!
+//! The events are the JSON shapes pushed to the WebSocket subscriptions. Downstream Rust
+//! consumers depend on this crate instead of the internal crates of the node, so the
+//! structs only use the JSON types of ckb-jsonrpc-types, and fields are only added.
+//!
+//! An [`Event`] is serialized with its type:
+//!
+//! ```json
+//! {"type": "new_block", "payload": {"block_number": "0x1", "block_hash": "0x..."}}
+//! ```
+//!
+//! It's the result of the `new_blocks`, `included_transactions`, `dropped_withdrawals`,
+//! `finalized_withdrawals` and `challenges` subscriptions. The webhook alerts of the node
+//! operator are not events and use their own payloads.
+
+pub use ckb_fixed_hash::H256;
+pub use ckb_jsonrpc_types::{Uint128, Uint32, Uint64};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum Event {
+    NewBlock(NewBlock),
+    TxIncluded(TxIncluded),
+    WithdrawalFinalized(WithdrawalFinalized),
+    ChallengeStarted(ChallengeStarted),
+    WithdrawalDropped(WithdrawalDropped),
+}
+
+/// A layer2 block is added to the chain
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct NewBlock {
+    pub block_number: Uint64,
+    pub block_hash: H256,
+}
+
+/// A layer2 tx is included in a block
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct TxIncluded {
+    pub tx_hash: H256,
+    pub block_number: Uint64,
+    pub block_hash: H256,
+    /// index of the tx in the block
+    pub tx_index: Uint32,
+}
+
+/// The block of a withdrawal is finalized, the withdrawn assets can be unlocked on layer1
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct WithdrawalFinalized {
+    pub withdrawal_hash: H256,
+    pub block_number: Uint64,
+    pub block_hash: H256,
+    pub account_script_hash: H256,
+    pub capacity: Uint64,
+    /// zero hash for CKB only withdrawals
+    pub sudt_script_hash: H256,
+    pub amount: Uint128,
+}
+
+/// A withdrawal request is dropped from the mem-pool, e.g. the account can't afford it anymore
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct WithdrawalDropped {
+    pub withdrawal_hash: H256,
+    pub reason: String,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeTargetType {
+    Transaction,
+    Withdrawal,
+}
+
+/// A block is challenged on layer1, the rollup is halted until the challenge is resolved
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ChallengeStarted {
+    pub block_hash: H256,
+    pub target_type: ChallengeTargetType,
+    /// index of the challenged tx or withdrawal in the block
+    pub target_index: Uint32,
+    /// the layer1 tx which starts the challenge
+    pub l1_transaction_hash: H256,
+}
@@ -0,0 +1,69 @@
+//! EIP-712 typed data of withdrawal requests
+//!
+//! Wallets like MetaMask show typed data to users in a readable form,
+//! see https://eips.ethereum.org/EIPS/eip-712.
+//! The domain is bound to the rollup by using the rollup type hash as the salt,
+//! a chain id is not used since wallets require it to match the connected L1 network.
+
+use gw_common::{hasher::HashType, H256};
+use gw_types::{packed::RawWithdrawalRequest, prelude::*};
+
+pub const DOMAIN_NAME: &str = "Godwoken";
+pub const DOMAIN_VERSION: &str = "1";
+pub const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,bytes32 salt)";
+pub const WITHDRAWAL_TYPE: &str = "Withdrawal(uint32 nonce,uint64 capacity,uint128 amount,\
+uint128 sellAmount,uint64 sellCapacity,bytes32 sudtScriptHash,bytes32 accountScriptHash,\
+bytes32 ownerLockHash,bytes32 paymentLockHash,uint64 fee,uint128 sudtFee)";
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    HashType::Keccak256.hash(data).into()
+}
+
+/// Encode an uint as a big-endian 32 bytes word
+fn encode_uint(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+pub fn domain_separator(rollup_type_hash: &H256) -> [u8; 32] {
+    let mut data = Vec::with_capacity(32 * 4);
+    data.extend_from_slice(&keccak256(DOMAIN_TYPE.as_bytes()));
+    data.extend_from_slice(&keccak256(DOMAIN_NAME.as_bytes()));
+    data.extend_from_slice(&keccak256(DOMAIN_VERSION.as_bytes()));
+    data.extend_from_slice(rollup_type_hash.as_slice());
+    keccak256(&data)
+}
+
+fn hash_withdrawal(raw: &RawWithdrawalRequest) -> [u8; 32] {
+    let nonce: u32 = raw.nonce().unpack();
+    let capacity: u64 = raw.capacity().unpack();
+    let amount: u128 = raw.amount().unpack();
+    let sell_amount: u128 = raw.sell_amount().unpack();
+    let sell_capacity: u64 = raw.sell_capacity().unpack();
+    let fee: u64 = raw.fee().unpack();
+    let sudt_fee: u128 = raw.sudt_fee().unpack();
+    let mut data = Vec::with_capacity(32 * 12);
+    data.extend_from_slice(&keccak256(WITHDRAWAL_TYPE.as_bytes()));
+    data.extend_from_slice(&encode_uint(nonce.into()));
+    data.extend_from_slice(&encode_uint(capacity.into()));
+    data.extend_from_slice(&encode_uint(amount));
+    data.extend_from_slice(&encode_uint(sell_amount));
+    data.extend_from_slice(&encode_uint(sell_capacity.into()));
+    data.extend_from_slice(raw.sudt_script_hash().as_slice());
+    data.extend_from_slice(raw.account_script_hash().as_slice());
+    data.extend_from_slice(raw.owner_lock_hash().as_slice());
+    data.extend_from_slice(raw.payment_lock_hash().as_slice());
+    data.extend_from_slice(&encode_uint(fee.into()));
+    data.extend_from_slice(&encode_uint(sudt_fee));
+    keccak256(&data)
+}
+
+/// Digest signed by `eth_signTypedData_v4`
+pub fn withdrawal_digest(raw: &RawWithdrawalRequest, rollup_type_hash: &H256) -> H256 {
+    let mut data = Vec::with_capacity(2 + 32 * 2);
+    data.extend_from_slice(b"\x19\x01");
+    data.extend_from_slice(&domain_separator(rollup_type_hash));
+    data.extend_from_slice(&hash_withdrawal(raw));
+    keccak256(&data).into()
+}
@@ -1,13 +1,19 @@
 use std::collections::HashMap;
 
 use gw_common::H256;
-use gw_types::{bytes::Bytes, packed::Signature};
+use gw_config::{AccountLockConfig, LockAlgorithmType};
+use gw_types::{
+    bytes::Bytes,
+    packed::{Signature, WithdrawalRequest},
+};
 
 #[cfg(debug_assertions)]
 pub mod always_success;
+pub mod eip712;
 pub mod secp256k1;
 
 use crate::error::LockAlgorithmError;
+use self::secp256k1::{Secp256k1, Secp256k1Eth, Secp256k1EthEip712};
 
 pub trait LockAlgorithm {
     fn verify_signature(
@@ -16,6 +22,18 @@ pub trait LockAlgorithm {
         signature: Signature,
         message: H256,
     ) -> Result<bool, LockAlgorithmError>;
+
+    /// Verify the signature of a withdrawal request,
+    /// a lock may accept signing formats other than the withdrawal message
+    fn verify_withdrawal_signature(
+        &self,
+        lock_args: Bytes,
+        withdrawal_request: &WithdrawalRequest,
+        rollup_type_hash: &H256,
+    ) -> Result<bool, LockAlgorithmError> {
+        let message = withdrawal_request.raw().calc_message(rollup_type_hash);
+        self.verify_signature(lock_args, withdrawal_request.signature(), message)
+    }
 }
 
 pub struct AccountLockManage {
@@ -31,6 +49,20 @@ impl Default for AccountLockManage {
 }
 
 impl AccountLockManage {
+    pub fn from_config(configs: &[AccountLockConfig]) -> Self {
+        let mut manage = AccountLockManage::default();
+        for config in configs {
+            let code_hash: [u8; 32] = config.code_hash.clone().into();
+            let lock_algo: Box<dyn LockAlgorithm + Send + Sync> = match config.algorithm {
+                LockAlgorithmType::Secp256k1Eth => Box::new(Secp256k1Eth),
+                LockAlgorithmType::Secp256k1EthEip712 => Box::new(Secp256k1EthEip712),
+                LockAlgorithmType::Secp256k1Ckb => Box::new(Secp256k1),
+            };
+            manage.register_lock_algorithm(code_hash.into(), lock_algo);
+        }
+        manage
+    }
+
     pub fn register_lock_algorithm(
         &mut self,
         code_hash: H256,
@@ -1,12 +1,14 @@
-use super::LockAlgorithm;
+use super::{eip712, LockAlgorithm};
 use crate::error::LockAlgorithmError;
-use gw_common::blake2b::new_blake2b;
+use gw_common::hasher::HashType;
 use gw_common::H256;
 use gw_types::prelude::*;
-use gw_types::{bytes::Bytes, packed::Signature};
+use gw_types::{
+    bytes::Bytes,
+    packed::{Signature, WithdrawalRequest},
+};
 use lazy_static::lazy_static;
 use secp256k1::recovery::{RecoverableSignature, RecoveryId};
-use sha3::{Digest, Keccak256};
 
 lazy_static! {
     pub static ref SECP256K1: secp256k1::Secp256k1<secp256k1::All> = secp256k1::Secp256k1::new();
@@ -45,12 +47,9 @@ impl LockAlgorithm for Secp256k1 {
             .recover(&msg, &signature)
             .map_err(|_| LockAlgorithmError::InvalidSignature)?;
         let pubkey_hash = {
-            let mut buf = [0u8; 32];
-            let mut hasher = new_blake2b();
-            hasher.update(&pubkey.serialize());
-            hasher.finalize(&mut buf);
+            let hash = HashType::Blake2b.hash(&pubkey.serialize());
             let mut pubkey_hash = [0u8; 20];
-            pubkey_hash.copy_from_slice(&buf[..20]);
+            pubkey_hash.copy_from_slice(&hash.as_slice()[..20]);
             pubkey_hash
         };
         if pubkey_hash != expected_pubkey_hash {
@@ -63,6 +62,32 @@ impl LockAlgorithm for Secp256k1 {
 #[derive(Debug, Default)]
 pub struct Secp256k1Eth;
 
+impl Secp256k1Eth {
+    /// Recover the ethereum address which signed the message
+    fn recover_address(
+        &self,
+        signature: [u8; 65],
+        signing_message: H256,
+    ) -> Result<[u8; 20], LockAlgorithmError> {
+        let signature: RecoverableSignature = {
+            let recid = RecoveryId::from_i32(signature[64] as i32)
+                .map_err(|_| LockAlgorithmError::InvalidSignature)?;
+            let data = &signature[..64];
+            RecoverableSignature::from_compact(data, recid)
+                .map_err(|_| LockAlgorithmError::InvalidSignature)?
+        };
+        let msg = secp256k1::Message::from_slice(signing_message.as_slice())
+            .map_err(|_| LockAlgorithmError::InvalidSignature)?;
+        let pubkey = SECP256K1
+            .recover(&msg, &signature)
+            .map_err(|_| LockAlgorithmError::InvalidSignature)?;
+        let hash = HashType::Keccak256.hash(&pubkey.serialize_uncompressed()[1..]);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash.as_slice()[12..]);
+        Ok(address)
+    }
+}
+
 /// Usage
 /// register AlwaysSuccess to AccountLockManage
 ///
@@ -77,41 +102,57 @@ impl LockAlgorithm for Secp256k1Eth {
         if lock_args.len() != 20 {
             return Err(LockAlgorithmError::InvalidLockArgs);
         }
-        let mut hasher = Keccak256::new();
-        hasher.update("\x19Ethereum Signed Message:\n32");
+        let mut hasher = HashType::Keccak256.new_hasher();
+        hasher.update(b"\x19Ethereum Signed Message:\n32");
         hasher.update(message.as_slice());
-        let buf = hasher.finalize();
-        let mut signing_message = [0u8; 32];
-        signing_message.copy_from_slice(&buf[..]);
-        let signing_message = H256::from(signing_message);
+        let signing_message = hasher.finalize();
 
-        let mut expected_pubkey_hash = [0u8; 20];
-        expected_pubkey_hash.copy_from_slice(&lock_args);
-        let signature: RecoverableSignature = {
-            let signature: [u8; 65] = signature.unpack();
-            let recid = RecoveryId::from_i32(signature[64] as i32)
-                .map_err(|_| LockAlgorithmError::InvalidSignature)?;
-            let data = &signature[..64];
-            RecoverableSignature::from_compact(data, recid)
-                .map_err(|_| LockAlgorithmError::InvalidSignature)?
-        };
-        let msg = secp256k1::Message::from_slice(signing_message.as_slice())
-            .map_err(|_| LockAlgorithmError::InvalidSignature)?;
-        let pubkey = SECP256K1
-            .recover(&msg, &signature)
-            .map_err(|_| LockAlgorithmError::InvalidSignature)?;
-        let pubkey_hash = {
-            let mut hasher = Keccak256::new();
-            hasher.update(&pubkey.serialize_uncompressed()[1..]);
-            let buf = hasher.finalize();
-            let mut pubkey_hash = [0u8; 20];
-            pubkey_hash.copy_from_slice(&buf[12..]);
-            pubkey_hash
-        };
-        if pubkey_hash != expected_pubkey_hash {
-            return Ok(false);
+        let address = self.recover_address(signature.unpack(), signing_message)?;
+        Ok(address[..] == lock_args[..])
+    }
+}
+
+/// The ethereum lock which also accepts withdrawals signed as EIP-712 typed data.
+///
+/// Only register it for a lock script which verifies the EIP-712 signatures on-chain,
+/// or the withdrawals accepted by the node are rejected on layer1
+#[derive(Debug, Default)]
+pub struct Secp256k1EthEip712;
+
+impl LockAlgorithm for Secp256k1EthEip712 {
+    fn verify_signature(
+        &self,
+        lock_args: Bytes,
+        signature: Signature,
+        message: H256,
+    ) -> Result<bool, LockAlgorithmError> {
+        Secp256k1Eth.verify_signature(lock_args, signature, message)
+    }
+
+    /// Accept both the personal signed withdrawal message and the EIP-712 typed data
+    fn verify_withdrawal_signature(
+        &self,
+        lock_args: Bytes,
+        withdrawal_request: &WithdrawalRequest,
+        rollup_type_hash: &H256,
+    ) -> Result<bool, LockAlgorithmError> {
+        let raw = withdrawal_request.raw();
+        let message = raw.calc_message(rollup_type_hash);
+        let signature = withdrawal_request.signature();
+        // an unrecoverable signature may still be a typed data signature
+        match self.verify_signature(lock_args.clone(), signature.clone(), message) {
+            Ok(true) => return Ok(true),
+            Ok(false) | Err(LockAlgorithmError::InvalidSignature) => {}
+            Err(err) => return Err(err),
         }
-        Ok(true)
+        // wallets set the recovery id of typed data signatures to 27 or 28
+        let mut signature: [u8; 65] = signature.unpack();
+        if signature[64] >= 27 {
+            signature[64] -= 27;
+        }
+        let digest = eip712::withdrawal_digest(&raw, rollup_type_hash);
+        let address = Secp256k1Eth.recover_address(signature, digest)?;
+        Ok(address[..] == lock_args[..])
     }
 }
 
@@ -129,3 +170,58 @@ fn test_secp256k1_eth() {
         .expect("verify signature");
     assert!(result);
 }
+
+#[test]
+fn test_secp256k1_eth_eip712_withdrawal() {
+    let secret_key = secp256k1::SecretKey::from_slice(&[42u8; 32]).expect("secret key");
+    let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, &secret_key);
+    let address = {
+        let hash = HashType::Keccak256.hash(&pubkey.serialize_uncompressed()[1..]);
+        Bytes::from(hash.as_slice()[12..].to_vec())
+    };
+    let rollup_type_hash = H256::from([1u8; 32]);
+    let raw = gw_types::packed::RawWithdrawalRequest::new_builder()
+        .nonce(1u32.pack())
+        .capacity(500_00000000u64.pack())
+        .account_script_hash([2u8; 32].pack())
+        .build();
+    let sign = |digest: H256| {
+        let msg = secp256k1::Message::from_slice(digest.as_slice()).expect("message");
+        let (recid, data) = SECP256K1
+            .sign_recoverable(&msg, &secret_key)
+            .serialize_compact();
+        let mut signature = [0u8; 65];
+        signature[..64].copy_from_slice(&data);
+        // as signed by wallets
+        signature[64] = recid.to_i32() as u8 + 27;
+        signature
+    };
+    let eth = Secp256k1EthEip712 {};
+
+    let digest = eip712::withdrawal_digest(&raw, &rollup_type_hash);
+    let withdrawal = WithdrawalRequest::new_builder()
+        .raw(raw.clone())
+        .signature(sign(digest).pack())
+        .build();
+    let result = eth
+        .verify_withdrawal_signature(address.clone(), &withdrawal, &rollup_type_hash)
+        .expect("verify signature");
+    assert!(result);
+
+    // the plain ethereum lock doesn't accept typed data
+    let result = Secp256k1Eth {}
+        .verify_withdrawal_signature(address.clone(), &withdrawal, &rollup_type_hash)
+        .unwrap_or(false);
+    assert!(!result);
+
+    // signed for another rollup
+    let digest = eip712::withdrawal_digest(&raw, &H256::from([3u8; 32]));
+    let withdrawal = WithdrawalRequest::new_builder()
+        .raw(raw)
+        .signature(sign(digest).pack())
+        .build();
+    let result = eth
+        .verify_withdrawal_signature(address, &withdrawal, &rollup_type_hash)
+        .expect("verify signature");
+    assert!(!result);
+}
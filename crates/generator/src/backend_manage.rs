@@ -1,26 +1,66 @@
 use anyhow::Result;
-use gw_common::H256;
+use gw_common::{blake2b::new_blake2b, H256};
 use gw_config::BackendConfig;
 use gw_types::bytes::Bytes;
-use std::{collections::HashMap, fs};
+use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex};
 
 #[derive(Clone)]
 pub struct Backend {
     pub validator: Bytes,
     pub generator: Bytes,
     pub validator_script_type_hash: H256,
+    /// blake2b hash of the generator binary
+    pub generator_hash: H256,
 }
 
-#[derive(Clone)]
+impl Backend {
+    /// Read the binaries of a backend
+    pub fn from_config(config: &BackendConfig) -> Result<Self> {
+        let validator = fs::read(&config.validator_path)?.into();
+        let generator: Bytes = fs::read(&config.generator_path)?.into();
+        let generator_hash = binary_hash(&generator);
+        let validator_script_type_hash = {
+            let hash: [u8; 32] = config.validator_script_type_hash.clone().into();
+            hash.into()
+        };
+        Ok(Backend {
+            validator,
+            generator,
+            validator_script_type_hash,
+            generator_hash,
+        })
+    }
+}
+
+/// A registered backend, binaries are identified by their blake2b hash
+#[derive(Clone, Debug)]
+pub struct BackendStatus {
+    pub validator_script_type_hash: H256,
+    /// not set if the backend wasn't loaded from files
+    pub validator_path: Option<PathBuf>,
+    pub generator_path: Option<PathBuf>,
+    pub validator_hash: H256,
+    pub generator_hash: H256,
+}
+
+fn binary_hash(binary: &[u8]) -> H256 {
+    let mut hasher = new_blake2b();
+    hasher.update(binary);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash.into()
+}
+
+#[derive(Clone, Default)]
 pub struct BackendManage {
     backends: HashMap<H256, Backend>,
+    /// configs of the backends loaded from files, kept to reload them
+    configs: HashMap<H256, BackendConfig>,
 }
 
 impl BackendManage {
     pub fn from_config(configs: Vec<BackendConfig>) -> Result<Self> {
-        let mut backend_manage = BackendManage {
-            backends: Default::default(),
-        };
+        let mut backend_manage = BackendManage::default();
 
         for config in configs {
             backend_manage.register_backend_config(config)?;
@@ -30,22 +70,8 @@ impl BackendManage {
     }
 
     pub fn register_backend_config(&mut self, config: BackendConfig) -> Result<()> {
-        let BackendConfig {
-            validator_path,
-            generator_path,
-            validator_script_type_hash,
-        } = config;
-        let validator = fs::read(validator_path)?.into();
-        let generator = fs::read(generator_path)?.into();
-        let validator_script_type_hash = {
-            let hash: [u8; 32] = validator_script_type_hash.into();
-            hash.into()
-        };
-        let backend = Backend {
-            validator,
-            generator,
-            validator_script_type_hash,
-        };
+        let backend = Backend::from_config(&config)?;
+        self.configs.insert(backend.validator_script_type_hash, config);
         self.register_backend(backend);
         Ok(())
     }
@@ -55,7 +81,69 @@ impl BackendManage {
             .insert(backend.validator_script_type_hash, backend);
     }
 
+    /// Load the backends of the configs, a backend replaces the one of the same validator
+    /// script type hash. Nothing is changed if any of them fails to load
+    pub fn reload(&mut self, configs: &[BackendConfig]) -> Result<()> {
+        let backends = configs
+            .iter()
+            .map(Backend::from_config)
+            .collect::<Result<Vec<_>>>()?;
+        for (backend, config) in backends.into_iter().zip(configs) {
+            self.configs
+                .insert(backend.validator_script_type_hash, config.clone());
+            self.register_backend(backend);
+        }
+        Ok(())
+    }
+
     pub fn get_backend(&self, code_hash: &H256) -> Option<&Backend> {
         self.backends.get(code_hash)
     }
+
+    /// Registered backends ordered by the validator script type hash
+    pub fn list(&self) -> Vec<BackendStatus> {
+        let mut backends: Vec<_> = self
+            .backends
+            .values()
+            .map(|backend| {
+                let config = self.configs.get(&backend.validator_script_type_hash);
+                BackendStatus {
+                    validator_script_type_hash: backend.validator_script_type_hash,
+                    validator_path: config.map(|config| config.validator_path.clone()),
+                    generator_path: config.map(|config| config.generator_path.clone()),
+                    validator_hash: binary_hash(&backend.validator),
+                    generator_hash: backend.generator_hash,
+                }
+            })
+            .collect();
+        backends.sort_unstable_by_key(|backend| backend.validator_script_type_hash);
+        backends
+    }
+}
+
+/// Backends of the accounts called by the txs of a block.
+///
+/// The script of an account never changes, so repeated calls to the same contract skip the
+/// script hash and script lookups. Only found backends are cached, an account which doesn't
+/// exist yet may be created by a later tx.
+#[derive(Default)]
+pub(crate) struct BlockBackendCache {
+    backends: Mutex<HashMap<u32, (H256, Backend)>>,
+}
+
+impl BlockBackendCache {
+    pub fn get(&self, account_id: u32) -> Option<(H256, Backend)> {
+        self.backends
+            .lock()
+            .expect("backend cache")
+            .get(&account_id)
+            .cloned()
+    }
+
+    pub fn insert(&self, account_id: u32, script_hash: H256, backend: Backend) {
+        self.backends
+            .lock()
+            .expect("backend cache")
+            .insert(account_id, (script_hash, backend));
+    }
 }
@@ -14,15 +14,23 @@ pub enum Error {
     Account(AccountError),
     #[error("Unlock error {0}")]
     Unlock(LockAlgorithmError),
+    #[error("Backend error {0}")]
+    Backend(TransactionError),
     #[error("Deposition error {0}")]
     Deposition(DepositionError),
     #[error("Withdrawal error {0}")]
     Withdrawal(WithdrawalError),
+    #[error("Block error {0}")]
+    Block(BlockError),
 }
 
 impl From<StateError> for Error {
     fn from(err: StateError) -> Self {
-        Error::State(err)
+        match err {
+            StateError::AmountOverflow => AccountError::AmountOverflow.into(),
+            StateError::InsufficientBalance => AccountError::InsufficientBalance.into(),
+            err => Error::State(err),
+        }
     }
 }
 
@@ -46,6 +54,10 @@ impl From<LockAlgorithmError> for Error {
 pub enum DepositionError {
     #[error("Deposit Faked CKB")]
     DepositFakedCKB,
+    #[error("Deposit capacity {capacity} is less than the min capacity {min_capacity}")]
+    InsufficientCapacity { min_capacity: u64, capacity: u64 },
+    #[error("Deposit sUDT amount {amount} is less than the min amount {min_amount}")]
+    InsufficientSUDTAmount { min_amount: u128, amount: u128 },
 }
 
 impl From<DepositionError> for Error {
@@ -64,6 +76,10 @@ pub enum WithdrawalError {
     WithdrawFakedCKB,
     #[error("Non positive sudt amount")]
     NonPositiveSUDTAmount,
+    #[error("Withdrawal fee {fee} is less than the min fee {min_fee}")]
+    InsufficientFee { min_fee: u64, fee: u64 },
+    #[error("Withdrawal sUDT fee {sudt_fee} is less than the min sUDT fee {min_sudt_fee}")]
+    InsufficientSUDTFee { min_sudt_fee: u128, sudt_fee: u128 },
 }
 
 impl From<WithdrawalError> for Error {
@@ -72,6 +88,26 @@ impl From<WithdrawalError> for Error {
     }
 }
 
+#[derive(Error, Debug, PartialEq, Clone, Eq)]
+pub enum BlockError {
+    #[error("Exceeded maximum block size: max bytes {max_bytes}, block bytes {used_bytes}")]
+    ExceededMaxBlockSize { max_bytes: usize, used_bytes: usize },
+    #[error("Exceeded maximum tx count: max {max_count}, actual {count}")]
+    ExceededMaxTxCount { max_count: u32, count: u32 },
+    #[error("Exceeded maximum tx args size at tx {tx_index}: max bytes {max_bytes}, args bytes {used_bytes}")]
+    ExceededMaxTxArgsSize {
+        tx_index: u32,
+        max_bytes: usize,
+        used_bytes: usize,
+    },
+}
+
+impl From<BlockError> for Error {
+    fn from(err: BlockError) -> Self {
+        Error::Block(err)
+    }
+}
+
 #[derive(Error, Debug, PartialEq, Clone, Eq)]
 pub enum AccountError {
     #[error("Insufficient capacity expected {expected} actual {actual}")]
@@ -84,10 +120,16 @@ pub enum AccountError {
     UnknownAccount,
     #[error("Unknown script")]
     UnknownScript,
+    #[error("Invalid script args")]
+    InvalidScriptArgs,
     #[error("Nonce Overflow")]
     NonceOverflow,
     #[error("can't find script for account {account_id}")]
     ScriptNotFound { account_id: u32 },
+    #[error("Amount overflow")]
+    AmountOverflow,
+    #[error("Insufficient balance")]
+    InsufficientBalance,
 }
 
 impl From<AccountError> for Error {
@@ -121,6 +163,54 @@ pub enum TransactionError {
     ExceededMaxReadData { max_bytes: usize, used_bytes: usize },
     #[error("Exceeded maximum write data: max bytes {max_bytes}, writen bytes {used_bytes}")]
     ExceededMaxWriteData { max_bytes: usize, used_bytes: usize },
+    #[error("Contract can't modify sUDT total supply, key {key:?}")]
+    ModifySUDTTotalSupply { key: H256 },
+    #[error("Exceeded maximum args size: max bytes {max_bytes}, args bytes {used_bytes}")]
+    ExceededMaxArgsSize { max_bytes: usize, used_bytes: usize },
+    #[error("backend of script_hash {script_hash:?} crashed: {reason}")]
+    BackendCrashed { script_hash: H256, reason: String },
+    #[error("insufficient CKB balance {balance} to pay max fee {max_fee}")]
+    InsufficientFee { max_fee: u128, balance: u128 },
+    #[error("gas fee {gas_fee} doesn't cover account creation fee {fee}")]
+    InsufficientAccountCreationFee { fee: u128, gas_fee: u128 },
+    #[error("execution timeout")]
+    Timeout,
+    #[error("Exceeded maximum cycles {max_cycles} of a tx")]
+    ExceededMaxCycles { max_cycles: u64 },
+    #[error("Exceeded maximum trace steps {max_steps}")]
+    ExceededMaxTraceSteps { max_steps: usize },
+    #[error("invalid meta contract args")]
+    InvalidMetaContractArgs,
+    #[error("account of script_hash {script_hash:?} already exists")]
+    DuplicatedScriptHash { script_hash: H256 },
+    #[error("script_hash {script_hash:?} is not allowed to create an account")]
+    InvalidScript { script_hash: H256 },
+    #[error("Unlock error {0}")]
+    Unlock(LockAlgorithmError),
+}
+
+impl TransactionError {
+    /// The tx failed in the execution, it's still valid in a block and pays for its gas limit.
+    /// Other errors make the tx invalid
+    pub fn is_execution_failure(&self) -> bool {
+        matches!(
+            self,
+            TransactionError::InvalidExitCode(_)
+                | TransactionError::VM(_)
+                | TransactionError::ExceededMaxReadData { .. }
+                | TransactionError::ExceededMaxWriteData { .. }
+                | TransactionError::ModifySUDTTotalSupply { .. }
+        )
+    }
+
+    /// The backend is missing from the config of this node or crashed, the tx is neither
+    /// failed nor invalid, another node may execute it successfully
+    pub fn is_backend_error(&self) -> bool {
+        matches!(
+            self,
+            TransactionError::BackendNotFound { .. } | TransactionError::BackendCrashed { .. }
+        )
+    }
 }
 
 impl From<VMError> for TransactionError {
@@ -129,6 +219,12 @@ impl From<VMError> for TransactionError {
     }
 }
 
+impl From<LockAlgorithmError> for TransactionError {
+    fn from(err: LockAlgorithmError) -> Self {
+        TransactionError::Unlock(err)
+    }
+}
+
 impl From<SMTError> for TransactionError {
     fn from(err: SMTError) -> Self {
         TransactionError::SMT(err)
@@ -188,6 +284,10 @@ impl From<LockAlgorithmError> for TransactionValidateError {
 
 impl From<StateError> for TransactionValidateError {
     fn from(err: StateError) -> Self {
-        Self::State(err)
+        match err {
+            StateError::AmountOverflow => AccountError::AmountOverflow.into(),
+            StateError::InsufficientBalance => AccountError::InsufficientBalance.into(),
+            err => Self::State(err),
+        }
     }
 }
@@ -1,43 +1,61 @@
 use crate::{
     account_lock_manage::AccountLockManage,
-    backend_manage::BackendManage,
-    error::{TransactionValidateError, WithdrawalError},
+    backend_manage::{BackendManage, BackendStatus, BlockBackendCache},
+    error::{BlockError, TransactionValidateError, WithdrawalError},
+    meta_contract::check_meta_contract_tx,
+    storage_usage::storage_delta,
     RollupContext,
 };
 use crate::{
     backend_manage::Backend,
     error::{Error, TransactionError, TransactionErrorWithContext},
     sudt::build_l2_sudt_script,
+    types::TxFee,
 };
 use crate::{error::AccountError, syscalls::L2Syscalls};
 use crate::{error::LockAlgorithmError, traits::StateExt};
 use gw_common::{
-    builtins::CKB_SUDT_ACCOUNT_ID,
     error::Error as StateError,
     h256_ext::H256Ext,
-    state::{build_account_field_key, State, GW_ACCOUNT_NONCE},
+    state::{
+        build_account_field_key, build_account_key, is_sudt_total_supply_key, State,
+        GW_ACCOUNT_NONCE, GW_LOG_TX_FAILED,
+    },
     H256,
 };
+use gw_config::{BackendConfig, BlockLimitsConfig};
 use gw_traits::{ChainStore, CodeStore};
 use gw_types::{
     core::{ChallengeTargetType, ScriptHashType},
-    offchain::RunResult,
+    offchain::{RunResult, StorageDelta, TraceStep, TxAccessList},
     packed::{
         AccountMerkleState, BlockInfo, ChallengeTarget, DepositionRequest, L2Block, L2Transaction,
-        RawL2Block, RawL2Transaction, TxReceipt, WithdrawalRequest,
+        LogItem, RawL2Block, RawL2Transaction, Script, TxReceipt, WithdrawalRequest,
     },
     prelude::*,
 };
 
 use ckb_vm::{
     machine::asm::{AsmCoreMachine, AsmMachine},
-    DefaultMachineBuilder,
+    snapshot::{make_snapshot, resume, Snapshot},
+    DefaultMachineBuilder, Error as VMError, SupportMachine,
+};
+use std::{
+    any::Any,
+    cmp,
+    collections::{HashMap, HashSet},
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, RwLock},
+    time::Instant,
 };
 
 // TODO ensure this value
 const MIN_WITHDRAWAL_CAPACITY: u64 = 100_00000000;
 // 25 KB
 const MAX_DATA_BYTES_LIMIT: usize = 25_000;
+/// Max cycles of a tx, a tx with gas limit 0 or above it uses this gas limit
+pub const MAX_TX_GAS_LIMIT: u64 = 1_000_000_000;
 
 pub struct StateTransitionArgs {
     pub l2block: L2Block,
@@ -46,12 +64,39 @@ pub struct StateTransitionArgs {
 
 pub struct StateTransitionResult {
     pub receipts: Vec<TxReceipt>,
+    /// storage changes of the called accounts, zero changes are omitted
+    pub storage_usage: HashMap<u32, StorageDelta>,
+}
+
+/// Environment of a tx execution
+struct ExecutionContext<'a, S, C> {
+    chain: &'a C,
+    state: &'a S,
+    block_info: &'a BlockInfo,
+    /// the node-local cap of the collected txs, the txs of a block are only bounded by
+    /// their gas limits
+    max_tx_cycles: Option<u64>,
+    /// the VM is stopped at the next syscall once the deadline passed
+    deadline: Option<Instant>,
+    /// the VM is stopped once the tracing mode recorded the max steps
+    max_trace_steps: Option<usize>,
+    /// caches the backends of the called accounts across the txs of a block
+    backends: Option<&'a BlockBackendCache>,
 }
 
 pub struct Generator {
-    backend_manage: BackendManage,
+    /// backends can be registered and reloaded at runtime by the admin RPCs
+    backend_manage: RwLock<BackendManage>,
+    /// machine snapshots taken right after loading the generator of a backend, keyed by the
+    /// hash of the generator binary, so the ELF is only parsed and loaded once. A snapshot of
+    /// a replaced binary is never restored, even if a running load inserts it after the
+    /// backend is replaced
+    program_snapshots: RwLock<HashMap<H256, Arc<Snapshot>>>,
     account_lock_manage: AccountLockManage,
     rollup_context: RollupContext,
+    block_limits: BlockLimitsConfig,
+    /// min gas fee of a tx for each account it creates, only checked by the mem-pool
+    account_creation_fee: u64,
 }
 
 impl Generator {
@@ -59,11 +104,16 @@ impl Generator {
         backend_manage: BackendManage,
         account_lock_manage: AccountLockManage,
         rollup_context: RollupContext,
+        block_limits: BlockLimitsConfig,
+        account_creation_fee: u64,
     ) -> Self {
         Generator {
-            backend_manage,
+            backend_manage: RwLock::new(backend_manage),
+            program_snapshots: Default::default(),
             account_lock_manage,
             rollup_context,
+            block_limits,
+            account_creation_fee,
         }
     }
 
@@ -71,6 +121,68 @@ impl Generator {
         &self.rollup_context
     }
 
+    pub fn block_limits(&self) -> &BlockLimitsConfig {
+        &self.block_limits
+    }
+
+    /// Min gas fee of a layer2 tx for each account it creates
+    pub fn account_creation_fee(&self) -> u64 {
+        self.account_creation_fee
+    }
+
+    /// Register a backend or replace the one of the same validator script type hash.
+    /// The validator must be deployed on layer1 too, or the blocks using it can be challenged
+    pub fn register_backend(&self, config: BackendConfig) -> anyhow::Result<()> {
+        let mut backend_manage = self.backend_manage.write().expect("backend manage");
+        backend_manage.register_backend_config(config)?;
+        self.program_snapshots.write().expect("program snapshots").clear();
+        Ok(())
+    }
+
+    /// Load the backends of the configs, see `BackendManage::reload`
+    pub fn reload_backends(&self, configs: &[BackendConfig]) -> anyhow::Result<()> {
+        let mut backend_manage = self.backend_manage.write().expect("backend manage");
+        backend_manage.reload(configs)?;
+        self.program_snapshots.write().expect("program snapshots").clear();
+        Ok(())
+    }
+
+    pub fn backends(&self) -> Vec<BackendStatus> {
+        self.backend_manage.read().expect("backend manage").list()
+    }
+
+    /// Verify the block fits in a layer1 transaction.
+    /// The limits are node-local, so they are only checked when the block producer collects
+    /// the block, a validator never rejects a block by its own limits
+    pub fn verify_block_limits(&self, block: &L2Block) -> Result<(), BlockError> {
+        let limits = &self.block_limits;
+        let block_size = block.as_slice().len();
+        if block_size > limits.max_block_size {
+            return Err(BlockError::ExceededMaxBlockSize {
+                max_bytes: limits.max_block_size,
+                used_bytes: block_size,
+            });
+        }
+        let tx_count = block.transactions().len() as u32;
+        if tx_count > limits.max_tx_count {
+            return Err(BlockError::ExceededMaxTxCount {
+                max_count: limits.max_tx_count,
+                count: tx_count,
+            });
+        }
+        for (tx_index, tx) in block.transactions().into_iter().enumerate() {
+            let args_size = tx.raw().args().len();
+            if args_size > limits.max_tx_args_size {
+                return Err(BlockError::ExceededMaxTxArgsSize {
+                    tx_index: tx_index as u32,
+                    max_bytes: limits.max_tx_args_size,
+                    used_bytes: args_size,
+                });
+            }
+        }
+        Ok(())
+    }
+
     pub fn account_lock_manage(&self) -> &AccountLockManage {
         &self.account_lock_manage
     }
@@ -87,6 +199,8 @@ impl Generator {
         let sudt_script_hash: H256 = raw.sudt_script_hash().unpack();
         let amount: u128 = raw.amount().unpack();
         let capacity: u64 = raw.capacity().unpack();
+        let ckb_fee: u64 = raw.fee().unpack();
+        let sudt_fee: u128 = raw.sudt_fee().unpack();
 
         // check capacity
         if capacity < MIN_WITHDRAWAL_CAPACITY {
@@ -103,8 +217,10 @@ impl Generator {
             .ok_or(AccountError::UnknownAccount)?; // find Simple UDT account
 
         // check CKB balance
-        let ckb_balance = state.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, id)?;
-        if capacity as u128 > ckb_balance {
+        let ckb_sudt_id = self.rollup_context.builtin_accounts.ckb_sudt_id;
+        let ckb_balance = state.get_sudt_balance(ckb_sudt_id, id)?;
+        // the fee is charged on top of the withdrawn assets
+        if capacity as u128 + ckb_fee as u128 > ckb_balance {
             return Err(WithdrawalError::Overdraft.into());
         }
         let l2_sudt_script_hash =
@@ -112,17 +228,20 @@ impl Generator {
         let sudt_id = state
             .get_account_id_by_script_hash(&l2_sudt_script_hash.into())?
             .ok_or(AccountError::UnknownSUDT)?;
-        if sudt_id != CKB_SUDT_ACCOUNT_ID {
+        if sudt_id != ckb_sudt_id {
             // check SUDT balance
             // user can't withdrawal 0 SUDT when non-CKB sudt_id exists
             if amount == 0 {
                 return Err(WithdrawalError::NonPositiveSUDTAmount.into());
             }
             let balance = state.get_sudt_balance(sudt_id, id)?;
-            if amount > balance {
+            if amount
+                .checked_add(sudt_fee)
+                .map_or(true, |total| total > balance)
+            {
                 return Err(WithdrawalError::Overdraft.into());
             }
-        } else if amount != 0 {
+        } else if amount != 0 || sudt_fee != 0 {
             // user can't withdrawal CKB token via SUDT fields
             return Err(WithdrawalError::WithdrawFakedCKB.into());
         }
@@ -159,11 +278,10 @@ impl Generator {
             .get_lock_algorithm(&lock_code_hash.into())
             .ok_or(LockAlgorithmError::UnknownAccountLock)?;
 
-        let message = raw.calc_message(&self.rollup_context.rollup_script_hash);
-        let valid_signature = lock_algo.verify_signature(
+        let valid_signature = lock_algo.verify_withdrawal_signature(
             account_script.args().unpack(),
-            withdrawal_request.signature(),
-            message,
+            withdrawal_request,
+            &self.rollup_context.rollup_script_hash,
         )?;
 
         if !valid_signature {
@@ -183,6 +301,16 @@ impl Generator {
         let raw_tx = tx.raw();
         let sender_id: u32 = raw_tx.from_id().unpack();
 
+        // verify args size
+        let args_size = raw_tx.args().len();
+        if args_size > self.block_limits.max_tx_args_size {
+            return Err(TransactionError::ExceededMaxArgsSize {
+                max_bytes: self.block_limits.max_tx_args_size,
+                used_bytes: args_size,
+            }
+            .into());
+        }
+
         // verify nonce
         let account_nonce: u32 = state.get_nonce(sender_id)?;
         let nonce: u32 = raw_tx.nonce().unpack();
@@ -194,6 +322,17 @@ impl Generator {
             .into());
         }
 
+        // verify the sender can pay the max fee
+        let ckb_sudt_id = self.rollup_context.builtin_accounts.ckb_sudt_id;
+        let balance = state.get_sudt_balance(ckb_sudt_id, sender_id)?;
+        check_max_fee(&raw_tx, balance)?;
+
+        // the meta contract backend executes the accepted txs
+        let receiver_id: u32 = raw_tx.to_id().unpack();
+        if receiver_id == self.rollup_context.builtin_accounts.reserved_id {
+            check_meta_contract_tx(state, &self.rollup_context, &raw_tx)?;
+        }
+
         Ok(())
     }
 
@@ -245,90 +384,341 @@ impl Generator {
 
     /// Apply l2 state transition
     ///
-    /// Notice:
-    /// This function do not verify the block and transactions signature.
-    /// The caller is supposed to do the verification.
+    /// The signatures of the withdrawals and txs are verified by the registered lock
+    /// algorithms, a bad signature is a transaction error with the challenge target.
     pub fn apply_state_transition<S: State + CodeStore, C: ChainStore>(
         &self,
         chain: &C,
         state: &mut S,
         args: StateTransitionArgs,
     ) -> Result<StateTransitionResult, Error> {
+        let block_info = self.apply_block_requests(state, &args)?;
+
+        // handle transactions
+        let block_hash: H256 = args.l2block.raw().hash().into();
+        let backends = BlockBackendCache::default();
+        let mut receipts = Vec::with_capacity(args.l2block.transactions().len());
+        let mut storage_usage = HashMap::new();
+        for (tx_index, tx) in args.l2block.transactions().into_iter().enumerate() {
+            let context = (block_hash, tx_index as u32);
+            let run_result = self.execute_block_transaction(
+                chain,
+                state,
+                &block_info,
+                &tx,
+                context,
+                &backends,
+            )?;
+            let (receipt, delta) =
+                self.apply_block_transaction(state, &block_info, &tx, run_result)?;
+            receipts.push(receipt);
+            record_storage_delta(&mut storage_usage, &tx, delta);
+        }
+
+        let result = StateTransitionResult {
+            receipts,
+            storage_usage,
+        };
+
+        Ok(result)
+    }
+
+    /// Apply the withdrawals, the deposits and the txs before `tx_index` of a block, it replays
+    /// the state the tx at `tx_index` ran on. Returns the block info to execute the tx
+    pub fn replay_block_until<S: State + CodeStore, C: ChainStore>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        args: &StateTransitionArgs,
+        tx_index: u32,
+    ) -> Result<BlockInfo, Error> {
+        let block_info = self.apply_block_requests(state, args)?;
+
+        let block_hash: H256 = args.l2block.raw().hash().into();
+        let backends = BlockBackendCache::default();
+        let txs = args.l2block.transactions().into_iter();
+        for (index, tx) in txs.take(tx_index as usize).enumerate() {
+            let context = (block_hash, index as u32);
+            let run_result = self.execute_block_transaction(
+                chain,
+                state,
+                &block_info,
+                &tx,
+                context,
+                &backends,
+            )?;
+            self.apply_block_transaction(state, &block_info, &tx, run_result)?;
+        }
+        Ok(block_info)
+    }
+
+    /// Apply l2 state transition, txs which don't conflict are executed in parallel.
+    ///
+    /// The access lists recorded by the block producer split the txs into batches, the txs of
+    /// a batch are executed in parallel on the state before the batch, then applied in order.
+    /// The access lists aren't trusted, a tx is executed again on the current state if the
+    /// keys it accessed are changed by the previous txs of the batch, so the result is always
+    /// the same as `apply_state_transition`.
+    pub fn apply_state_transition_parallel<S, C>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        args: StateTransitionArgs,
+        access_lists: &[TxAccessList],
+    ) -> Result<StateTransitionResult, Error>
+    where
+        S: State + CodeStore + Sync,
+        C: ChainStore + Sync,
+    {
+        let txs: Vec<L2Transaction> = args.l2block.transactions().into_iter().collect();
+        if access_lists.len() != txs.len() {
+            return self.apply_state_transition(chain, state, args);
+        }
+        let block_info = self.apply_block_requests(state, &args)?;
+
+        let block_hash: H256 = args.l2block.raw().hash().into();
+        let backends = BlockBackendCache::default();
+        let ckb_sudt_id = self.rollup_context.builtin_accounts.ckb_sudt_id;
+        let block_producer_id: u32 = block_info.block_producer_id().unpack();
+        let producer_balance_key =
+            build_account_key(ckb_sudt_id, H256::from_u32(block_producer_id).as_slice());
+        let mut receipts = Vec::with_capacity(txs.len());
+        let mut storage_usage = HashMap::new();
+        for batch in split_parallel_batches(access_lists) {
+            let run_results: Vec<Result<RunResult, TransactionError>> = {
+                let ctx = &ExecutionContext {
+                    chain,
+                    state: &*state,
+                    block_info: &block_info,
+                    max_tx_cycles: None,
+                    deadline: None,
+                    max_trace_steps: None,
+                    backends: Some(&backends),
+                };
+                crossbeam_utils::thread::scope(|scope| {
+                    let handles: Vec<_> = txs[batch.clone()]
+                        .iter()
+                        .map(|tx| {
+                            scope.spawn(move |_| {
+                                let mut run_result = RunResult::default();
+                                self.execute_into(ctx, &tx.raw(), &mut run_result)
+                                    .map(|()| run_result)
+                            })
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().expect("join execution thread"))
+                        .collect()
+                })
+                .expect("execution threads")
+            };
+
+            // keys changed by the applied txs of the batch
+            let mut changed_keys: HashSet<H256> = HashSet::new();
+            let mut account_created = false;
+            for (tx_index, run_result) in batch.zip(run_results) {
+                let tx = &txs[tx_index];
+                let context = (block_hash, tx_index as u32);
+                let sender_id: u32 = tx.raw().from_id().unpack();
+                let sender_balance_key =
+                    build_account_key(ckb_sudt_id, H256::from_u32(sender_id).as_slice());
+                let run_result = match run_result {
+                    Ok(run_result)
+                        if !changed_keys.contains(&sender_balance_key)
+                            && !(account_created && run_result.account_count.is_some())
+                            && !run_result
+                                .read_values
+                                .keys()
+                                .chain(run_result.write_values.keys())
+                                .any(|key| changed_keys.contains(key)) =>
+                    {
+                        self.check_block_transaction(state, tx, context)?;
+                        run_result
+                    }
+                    // conflict or failure, fall back to the sequential execution
+                    _ => self.execute_block_transaction(
+                        chain,
+                        state,
+                        &block_info,
+                        tx,
+                        context,
+                        &backends,
+                    )?,
+                };
+                changed_keys.extend(run_result.write_values.keys().copied());
+                changed_keys.insert(sender_balance_key);
+                changed_keys.insert(producer_balance_key);
+                account_created |= run_result.account_count.is_some();
+                let (receipt, delta) =
+                    self.apply_block_transaction(state, &block_info, tx, run_result)?;
+                receipts.push(receipt);
+                record_storage_delta(&mut storage_usage, tx, delta);
+            }
+        }
+
+        Ok(StateTransitionResult {
+            receipts,
+            storage_usage,
+        })
+    }
+
+    /// Apply the withdrawals and the deposits of a block, returns the block info to execute the txs
+    fn apply_block_requests<S: State + CodeStore>(
+        &self,
+        state: &mut S,
+        args: &StateTransitionArgs,
+    ) -> Result<BlockInfo, Error> {
         let raw_block = args.l2block.raw();
         let withdrawal_requests: Vec<_> = args.l2block.withdrawals().into_iter().collect();
+        let block_info = get_block_info(&raw_block);
+        let block_producer_id: u32 = block_info.block_producer_id().unpack();
+        // check withdrawal signatures
+        let block_hash: H256 = raw_block.hash().into();
+        for (index, request) in withdrawal_requests.iter().enumerate() {
+            match self.check_withdrawal_request_signature(state, request) {
+                Ok(()) => {}
+                // an unknown lock is a config error of this node, not a bad block
+                Err(Error::Unlock(err @ LockAlgorithmError::InvalidSignature)) => {
+                    let target = build_challenge_target(
+                        block_hash,
+                        ChallengeTargetType::Withdrawal,
+                        index as u32,
+                    );
+                    return Err(TransactionErrorWithContext::new(target, err.into()).into());
+                }
+                Err(err) => return Err(err),
+            }
+        }
         // apply withdrawal to state
-        state.apply_withdrawal_requests(&self.rollup_context, &withdrawal_requests)?;
+        state.apply_withdrawal_requests(
+            &self.rollup_context,
+            block_producer_id,
+            &withdrawal_requests,
+        )?;
         // apply deposition to state
         state.apply_deposition_requests(&self.rollup_context, &args.deposition_requests)?;
+        Ok(block_info)
+    }
 
-        // handle transactions
-        let block_info = get_block_info(&raw_block);
-        let block_hash = raw_block.hash();
-        let mut receipts = Vec::with_capacity(args.l2block.transactions().len());
-        for (tx_index, tx) in args.l2block.transactions().into_iter().enumerate() {
-            let raw_tx = tx.raw();
-            // check nonce
-            let expected_nonce = state.get_nonce(raw_tx.from_id().unpack())?;
-            let actual_nonce: u32 = raw_tx.nonce().unpack();
-            if actual_nonce != expected_nonce {
-                return Err(TransactionErrorWithContext::new(
-                    build_challenge_target(
-                        block_hash.into(),
-                        ChallengeTargetType::Transaction,
-                        tx_index as u32,
-                    ),
-                    TransactionError::Nonce {
-                        expected: expected_nonce,
-                        actual: actual_nonce,
-                    },
-                )
-                .into());
-            }
-            // build call context
-            // NOTICE users only allowed to send HandleMessage CallType txs
-            let run_result = match self.execute_transaction(chain, state, &block_info, &raw_tx) {
-                Ok(run_result) => run_result,
-                Err(err) => {
-                    return Err(TransactionErrorWithContext::new(
-                        build_challenge_target(
-                            block_hash.into(),
-                            ChallengeTargetType::Transaction,
-                            tx_index as u32,
-                        ),
-                        err,
-                    )
-                    .into());
+    /// The nonce of a tx must be the sender's nonce and the signature must be valid,
+    /// `context` is (block hash, tx index)
+    fn check_block_transaction<S: State + CodeStore>(
+        &self,
+        state: &S,
+        tx: &L2Transaction,
+        context: (H256, u32),
+    ) -> Result<(), Error> {
+        let target = build_challenge_target(context.0, ChallengeTargetType::Transaction, context.1);
+        let raw_tx = tx.raw();
+        let expected_nonce = state.get_nonce(raw_tx.from_id().unpack())?;
+        let actual_nonce: u32 = raw_tx.nonce().unpack();
+        if actual_nonce != expected_nonce {
+            return Err(TransactionErrorWithContext::new(
+                target,
+                TransactionError::Nonce {
+                    expected: expected_nonce,
+                    actual: actual_nonce,
+                },
+            )
+            .into());
+        }
+        if let Err(err) = self.check_transaction_signature(state, tx) {
+            let err = match err {
+                TransactionValidateError::Transaction(err) => err,
+                TransactionValidateError::Unlock(err @ LockAlgorithmError::InvalidSignature) => {
+                    err.into()
                 }
+                // an unknown lock is a config error of this node, not a bad block
+                TransactionValidateError::Unlock(err) => return Err(err.into()),
+                TransactionValidateError::State(err) => return Err(err.into()),
+                TransactionValidateError::Account(err) => return Err(err.into()),
             };
-            state.apply_run_result(&run_result)?;
-
-            let post_state = {
-                let account_root = state.calculate_root()?;
-                let account_count = state.get_account_count()?;
-                AccountMerkleState::new_builder()
-                    .merkle_root(account_root.pack())
-                    .count(account_count.pack())
-                    .build()
-            };
-            let tx_receipt = TxReceipt::new_builder()
-                .tx_witness_hash(tx.witness_hash().pack())
-                .post_state(post_state)
-                .read_data_hashes(
-                    run_result
-                        .read_data
-                        .into_iter()
-                        .map(|(hash, _)| hash.pack())
-                        .collect::<Vec<_>>()
-                        .pack(),
-                )
-                .logs(run_result.logs.pack())
-                .build();
-            receipts.push(tx_receipt);
+            return Err(TransactionErrorWithContext::new(target, err).into());
         }
+        Ok(())
+    }
 
-        let result = StateTransitionResult { receipts };
+    /// Check the nonce and the signature, then execute a tx of a block,
+    /// `context` is (block hash, tx index)
+    fn execute_block_transaction<S: State + CodeStore, C: ChainStore>(
+        &self,
+        chain: &C,
+        state: &S,
+        block_info: &BlockInfo,
+        tx: &L2Transaction,
+        context: (H256, u32),
+        backends: &BlockBackendCache,
+    ) -> Result<RunResult, Error> {
+        self.check_block_transaction(state, tx, context)?;
+        // build call context
+        // NOTICE users only allowed to send HandleMessage CallType txs
+        let raw_tx = tx.raw();
+        let mut run_result = RunResult::default();
+        let ctx = ExecutionContext {
+            chain,
+            state,
+            block_info,
+            max_tx_cycles: None,
+            deadline: None,
+            max_trace_steps: None,
+            backends: Some(backends),
+        };
+        let result = match self.execute_into(&ctx, &raw_tx, &mut run_result) {
+            Ok(()) => Ok(run_result),
+            Err(err) if err.is_execution_failure() => self.failed_run_result(state, &raw_tx, &err),
+            // an error of this node, not a bad block
+            Err(err) if err.is_backend_error() => return Err(Error::Backend(err)),
+            Err(err) => Err(err),
+        };
+        let run_result = result.map_err(|err| {
+            TransactionErrorWithContext::new(
+                build_challenge_target(context.0, ChallengeTargetType::Transaction, context.1),
+                err,
+            )
+        })?;
+        Ok(run_result)
+    }
 
-        Ok(result)
+    /// Apply the result of a tx of a block and settle the fee, returns the receipt and the
+    /// storage change of the called account
+    fn apply_block_transaction<S: State + CodeStore>(
+        &self,
+        state: &mut S,
+        block_info: &BlockInfo,
+        tx: &L2Transaction,
+        run_result: RunResult,
+    ) -> Result<(TxReceipt, StorageDelta), Error> {
+        let storage_delta = storage_delta(state, &tx.raw(), &run_result)?;
+        state.apply_run_result(&run_result)?;
+        let fee = self.settle_fee(state, block_info, &tx.raw(), &run_result)?;
+
+        let post_state = {
+            let account_root = state.calculate_root()?;
+            let account_count = state.get_account_count()?;
+            AccountMerkleState::new_builder()
+                .merkle_root(account_root.pack())
+                .count(account_count.pack())
+                .build()
+        };
+        let tx_receipt = TxReceipt::new_builder()
+            .tx_witness_hash(tx.witness_hash().pack())
+            .post_state(post_state)
+            .read_data_hashes(
+                run_result
+                    .read_data
+                    .into_iter()
+                    .map(|(hash, _)| hash.pack())
+                    .collect::<Vec<_>>()
+                    .pack(),
+            )
+            .logs(run_result.logs.pack())
+            .used_cycles(fee.used_cycles.pack())
+            .paid_fee(fee.paid_fee.pack())
+            .refunded_fee(fee.refunded_fee.pack())
+            .return_data(run_result.return_data.pack())
+            .build();
+        Ok((tx_receipt, storage_delta))
     }
 
     fn load_backend<S: State + CodeStore>(&self, state: &S, script_hash: &H256) -> Option<Backend> {
@@ -338,7 +728,8 @@ impl Generator {
                 // only accept type script hash type for now
                 if script.hash_type() == ScriptHashType::Type.into() {
                     let code_hash: [u8; 32] = script.code_hash().unpack();
-                    self.backend_manage.get_backend(&code_hash.into())
+                    let backend_manage = self.backend_manage.read().expect("backend manage");
+                    backend_manage.get_backend(&code_hash.into()).cloned()
                 } else {
                     eprintln!(
                         "Found a invalid account script which hash_type is data: {:?}",
@@ -347,10 +738,151 @@ impl Generator {
                     None
                 }
             })
-            .cloned()
     }
 
-    /// execute a layer2 tx
+    /// Load the generator of a backend into a fresh machine, restored from the snapshot taken
+    /// the first time it was loaded. A machine is still created for each tx because the
+    /// syscalls borrow the state of the tx, only the ELF loading is skipped
+    pub(crate) fn load_generator(
+        &self,
+        machine: &mut AsmMachine<'_>,
+        backend: &Backend,
+    ) -> Result<(), VMError> {
+        let snapshot = self
+            .program_snapshots
+            .read()
+            .expect("program snapshots")
+            .get(&backend.generator_hash)
+            .cloned();
+        if let Some(snapshot) = snapshot {
+            return resume(&mut machine.machine, &snapshot);
+        }
+        machine.load_program(&backend.generator, &[])?;
+        let snapshot = make_snapshot(&mut machine.machine)?;
+        self.program_snapshots
+            .write()
+            .expect("program snapshots")
+            .insert(backend.generator_hash, Arc::new(snapshot));
+        Ok(())
+    }
+
+    /// Settle the fee of an applied layer2 tx in CKB
+    ///
+    /// The max fee `gas_limit * gas_price` (see `tx_gas_limit`) is deducted from the sender, the fee of unused cycles
+    /// is refunded to the sender, and the fee of used cycles is credited to the block producer.
+    /// `execute_transaction` ensures the sender can still pay the max fee after the execution.
+    pub fn settle_fee<S: State>(
+        &self,
+        state: &mut S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        run_result: &RunResult,
+    ) -> Result<TxFee, Error> {
+        let sender_id: u32 = raw_tx.from_id().unpack();
+        let gas_price: u128 = raw_tx.gas_price().unpack();
+        let max_fee = calculate_max_fee(raw_tx).ok_or(StateError::AmountOverflow)?;
+        if max_fee == 0 {
+            return Ok(TxFee {
+                used_cycles: run_result.used_cycles,
+                ..Default::default()
+            });
+        }
+        // used cycles never exceed the gas limit, so the gas fee never exceeds the max fee
+        let paid_fee = u128::from(run_result.used_cycles) * gas_price;
+        let refunded_fee = max_fee - paid_fee;
+        let ckb_sudt_id = self.rollup_context.builtin_accounts.ckb_sudt_id;
+        let balance = state.get_sudt_balance(ckb_sudt_id, sender_id)?;
+        let new_balance = balance
+            .checked_sub(max_fee)
+            .ok_or(StateError::InsufficientBalance)?
+            + refunded_fee;
+        state.set_sudt_balance(ckb_sudt_id, sender_id, new_balance)?;
+        let block_producer_id: u32 = block_info.block_producer_id().unpack();
+        let balance = state.get_sudt_balance(ckb_sudt_id, block_producer_id)?;
+        let new_balance = balance
+            .checked_add(paid_fee)
+            .ok_or(StateError::AmountOverflow)?;
+        state.set_sudt_balance(ckb_sudt_id, block_producer_id, new_balance)?;
+        Ok(TxFee {
+            used_cycles: run_result.used_cycles,
+            paid_fee,
+            refunded_fee,
+        })
+    }
+
+    /// Result of a tx which failed in the execution, see `TransactionError::is_execution_failure`.
+    ///
+    /// Only the nonce of the sender is increased, the sender pays for the whole gas limit,
+    /// and the error is recorded by a `GW_LOG_TX_FAILED` log
+    pub fn failed_run_result<S: State>(
+        &self,
+        state: &S,
+        raw_tx: &RawL2Transaction,
+        err: &TransactionError,
+    ) -> Result<RunResult, TransactionError> {
+        let sender_id: u32 = raw_tx.from_id().unpack();
+        let nonce = state.get_nonce(sender_id)?;
+        let nonce_raw_key = build_account_field_key(sender_id, GW_ACCOUNT_NONCE);
+        let mut run_result = RunResult {
+            used_cycles: tx_gas_limit(raw_tx),
+            ..Default::default()
+        };
+        run_result
+            .read_values
+            .insert(nonce_raw_key, H256::from_u32(nonce));
+        run_result
+            .write_values
+            .insert(nonce_raw_key, H256::from_u32(nonce + 1));
+        let log = LogItem::new_builder()
+            .account_id(raw_tx.to_id())
+            .service_flag(GW_LOG_TX_FAILED.into())
+            .data(err.to_string().pack())
+            .build();
+        run_result.logs.push(log);
+        Ok(run_result)
+    }
+
+    /// Check the gas fee of an executed tx covers `account_creation_fee` for each account it
+    /// creates, the created accounts are the new scripts.
+    ///
+    /// It's a policy of the mem-pool against filling the state with empty accounts. It isn't a
+    /// part of the state transition, a block with a tx paying less is still valid
+    pub fn check_account_creation_fee(
+        &self,
+        raw_tx: &RawL2Transaction,
+        run_result: &RunResult,
+    ) -> Result<(), TransactionError> {
+        let fee = u128::from(self.account_creation_fee) * run_result.new_scripts.len() as u128;
+        let gas_price: u128 = raw_tx.gas_price().unpack();
+        let gas_fee = u128::from(run_result.used_cycles) * gas_price;
+        if gas_fee < fee {
+            return Err(TransactionError::InsufficientAccountCreationFee { fee, gas_fee });
+        }
+        Ok(())
+    }
+
+    /// Check the args of the accounts created by an executed tx by the script args registry.
+    ///
+    /// The registry is a config of the node, so it's a policy of the mem-pool like the account
+    /// creation fee, a block with a tx creating such an account is still valid
+    pub fn check_new_script_args(&self, run_result: &RunResult) -> Result<(), TransactionError> {
+        let rollup_context = &self.rollup_context;
+        for (script_hash, script) in &run_result.new_scripts {
+            let script = Script::from_slice(&script).expect("script");
+            if rollup_context
+                .script_args
+                .verify(&rollup_context.rollup_script_hash, &script)
+                .is_err()
+            {
+                return Err(TransactionError::InvalidScript {
+                    script_hash: *script_hash,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// execute a layer2 tx, it's bounded by the max tx cycles
     pub fn execute_transaction<S: State + CodeStore, C: ChainStore>(
         &self,
         chain: &C,
@@ -358,33 +890,97 @@ impl Generator {
         block_info: &BlockInfo,
         raw_tx: &RawL2Transaction,
     ) -> Result<RunResult, TransactionError> {
+        self.execute_transaction_with_deadline(chain, state, block_info, raw_tx, None)
+    }
+
+    /// Execute a layer2 tx, the VM is stopped at the next syscall once the deadline passed.
+    ///
+    /// A backend that never calls a syscall is only bounded by the gas limit of the tx.
+    pub fn execute_transaction_with_deadline<S: State + CodeStore, C: ChainStore>(
+        &self,
+        chain: &C,
+        state: &S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        deadline: Option<Instant>,
+    ) -> Result<RunResult, TransactionError> {
+        let ctx = ExecutionContext {
+            chain,
+            state,
+            block_info,
+            max_tx_cycles: Some(self.block_limits.max_tx_cycles),
+            deadline,
+            max_trace_steps: None,
+            backends: None,
+        };
         let mut run_result = RunResult::default();
+        self.execute_into(&ctx, raw_tx, &mut run_result)?;
+        Ok(run_result)
+    }
+
+    /// Execute a layer2 tx in the tracing mode, the syscalls are recorded into `RunResult::trace`.
+    ///
+    /// The recorded syscalls are returned even if the tx fails. Calls between contracts of a
+    /// backend run inside the VM, they are only seen by the syscalls they make. At most
+    /// `max_steps` syscalls are recorded, the tx fails with the recorded ones once it's reached.
+    pub fn trace_transaction<S: State + CodeStore, C: ChainStore>(
+        &self,
+        chain: &C,
+        state: &S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        deadline: Option<Instant>,
+        max_steps: usize,
+    ) -> (Result<RunResult, TransactionError>, Vec<TraceStep>) {
+        let mut run_result = RunResult {
+            trace: Some(Vec::new()),
+            ..Default::default()
+        };
+        let ctx = ExecutionContext {
+            chain,
+            state,
+            block_info,
+            max_tx_cycles: Some(self.block_limits.max_tx_cycles),
+            deadline,
+            max_trace_steps: Some(max_steps),
+            backends: None,
+        };
+        let result = self.execute_into(&ctx, raw_tx, &mut run_result);
+        let trace = run_result.trace.clone().unwrap_or_default();
+        (result.map(|()| run_result), trace)
+    }
+
+    fn execute_into<S: State + CodeStore, C: ChainStore>(
+        &self,
+        ctx: &ExecutionContext<S, C>,
+        raw_tx: &RawL2Transaction,
+        run_result: &mut RunResult,
+    ) -> Result<(), TransactionError> {
+        let state = ctx.state;
+        let sender_id: u32 = raw_tx.from_id().unpack();
+        let ckb_sudt_id = self.rollup_context.builtin_accounts.ckb_sudt_id;
+        let balance = state.get_sudt_balance(ckb_sudt_id, sender_id)?;
+        let max_fee = check_max_fee(raw_tx, balance)?;
+        let account_id: u32 = raw_tx.to_id().unpack();
+        run_result.used_cycles = self.run_backend(ctx, raw_tx, account_id, run_result)?;
+        // sUDT total supply is only changed by deposits and withdrawals
+        if let Some(key) = run_result
+            .write_values
+            .keys()
+            .find(|key| is_sudt_total_supply_key(key))
         {
-            let core_machine = Box::<AsmCoreMachine>::default();
-            let machine_builder =
-                DefaultMachineBuilder::new(core_machine).syscall(Box::new(L2Syscalls {
-                    chain,
-                    state,
-                    block_info,
-                    raw_tx,
-                    rollup_context: &self.rollup_context,
-                    result: &mut run_result,
-                    code_store: state,
-                }));
-            let mut machine = AsmMachine::new(machine_builder.build(), None);
-            let account_id = raw_tx.to_id().unpack();
-            let script_hash = state.get_script_hash(account_id)?;
-            let backend = self
-                .load_backend(state, &script_hash)
-                .ok_or(TransactionError::BackendNotFound { script_hash })?;
-            machine.load_program(&backend.generator, &[])?;
-            let code = machine.run()?;
-            if code != 0 {
-                return Err(TransactionError::InvalidExitCode(code));
-            }
+            return Err(TransactionError::ModifySUDTTotalSupply { key: *key });
+        }
+        // the sender must still be able to pay the max fee after the execution
+        if max_fee > 0 {
+            let balance_key = build_account_key(ckb_sudt_id, H256::from_u32(sender_id).as_slice());
+            let balance = match run_result.write_values.get(&balance_key) {
+                Some(value) => value.to_u128(),
+                None => balance,
+            };
+            check_max_fee(raw_tx, balance)?;
         }
         // set nonce
-        let sender_id: u32 = raw_tx.from_id().unpack();
         let nonce = state.get_nonce(sender_id)?;
         let nonce_raw_key = build_account_field_key(sender_id, GW_ACCOUNT_NONCE);
         if run_result.read_values.get(&nonce_raw_key).is_none() {
@@ -414,7 +1010,163 @@ impl Generator {
             });
         }
 
-        Ok(run_result)
+        Ok(())
+    }
+
+    /// Run the generator of the backend of the called account, returns the used cycles
+    fn run_backend<S: State + CodeStore, C: ChainStore>(
+        &self,
+        ctx: &ExecutionContext<S, C>,
+        raw_tx: &RawL2Transaction,
+        account_id: u32,
+        run_result: &mut RunResult,
+    ) -> Result<u64, TransactionError> {
+        let ExecutionContext {
+            chain,
+            state,
+            block_info,
+            max_tx_cycles,
+            deadline,
+            max_trace_steps,
+            backends,
+        } = *ctx;
+        let (script_hash, backend) = match backends.and_then(|backends| backends.get(account_id)) {
+            Some(cached) => cached,
+            None => {
+                let script_hash = state.get_script_hash(account_id)?;
+                let backend = self
+                    .load_backend(state, &script_hash)
+                    .ok_or(TransactionError::BackendNotFound { script_hash })?;
+                if let Some(backends) = backends {
+                    backends.insert(account_id, script_hash, backend.clone());
+                }
+                (script_hash, backend)
+            }
+        };
+        // every instruction costs 1 cycle,
+        // and a collected tx never runs longer than the max tx cycles
+        let gas_limit = tx_gas_limit(raw_tx);
+        let max_cycles = match max_tx_cycles {
+            Some(max_tx_cycles) => cmp::min(gas_limit, max_tx_cycles),
+            None => gas_limit,
+        };
+        // the VM memory is fixed-size, but a backend may still trigger a panic in the VM
+        // or in the syscalls handler, contain it so it can't take down the whole process.
+        // the state is only read during the execution, so it's safe to keep using it.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let core_machine = AsmCoreMachine::new_with_max_cycles(max_cycles);
+            let machine_builder = DefaultMachineBuilder::new(core_machine)
+                .instruction_cycle_func(Box::new(|_| 1))
+                .syscall(Box::new(L2Syscalls {
+                    chain,
+                    state,
+                    block_info,
+                    raw_tx,
+                    rollup_context: &self.rollup_context,
+                    result: &mut *run_result,
+                    code_store: state,
+                    deadline,
+                    max_trace_steps,
+                }));
+            let mut machine = AsmMachine::new(machine_builder.build(), None);
+            self.load_generator(&mut machine, &backend)?;
+            let code = machine.run()?;
+            Ok::<_, VMError>((code, machine.machine.cycles()))
+        }))
+        .map_err(|err| TransactionError::BackendCrashed {
+            script_hash,
+            reason: panic_reason(err),
+        })?;
+        // the syscalls stop the VM once the trace is full
+        if let (Err(_), Some(max_steps), Some(trace)) =
+            (&result, max_trace_steps, run_result.trace.as_ref())
+        {
+            if trace.len() >= max_steps {
+                return Err(TransactionError::ExceededMaxTraceSteps { max_steps });
+            }
+        }
+        let (code, used_cycles) = match result {
+            Ok(result) => result,
+            Err(_) if deadline.map(|d| Instant::now() >= d).unwrap_or(false) => {
+                return Err(TransactionError::Timeout);
+            }
+            Err(VMError::InvalidCycles) if Some(max_cycles) == max_tx_cycles => {
+                return Err(TransactionError::ExceededMaxCycles { max_cycles });
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if code != 0 {
+            return Err(TransactionError::InvalidExitCode(code));
+        }
+        Ok(used_cycles)
+    }
+}
+
+/// Split the txs into batches of consecutive txs which don't conflict with each other
+pub(crate) fn split_parallel_batches(access_lists: &[TxAccessList]) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    for (index, access_list) in access_lists.iter().enumerate() {
+        if access_lists[start..index]
+            .iter()
+            .any(|prev| prev.conflicts_with(access_list))
+        {
+            batches.push(start..index);
+            start = index;
+        }
+    }
+    if start < access_lists.len() {
+        batches.push(start..access_lists.len());
+    }
+    batches
+}
+
+fn record_storage_delta(
+    storage_usage: &mut HashMap<u32, StorageDelta>,
+    tx: &L2Transaction,
+    delta: StorageDelta,
+) {
+    if delta == StorageDelta::default() {
+        return;
+    }
+    let account_id: u32 = tx.raw().to_id().unpack();
+    storage_usage.entry(account_id).or_default().merge(delta);
+}
+
+/// Gas limit of a tx, a gas limit 0 or above `MAX_TX_GAS_LIMIT` means `MAX_TX_GAS_LIMIT`
+pub fn tx_gas_limit(raw_tx: &RawL2Transaction) -> u64 {
+    let gas_limit: u64 = raw_tx.gas_limit().unpack();
+    if gas_limit == 0 {
+        MAX_TX_GAS_LIMIT
+    } else {
+        cmp::min(gas_limit, MAX_TX_GAS_LIMIT)
+    }
+}
+
+/// Max fee of a tx is `gas_limit * gas_price`, returns None if overflow
+fn calculate_max_fee(raw_tx: &RawL2Transaction) -> Option<u128> {
+    let gas_price: u128 = raw_tx.gas_price().unpack();
+    u128::from(tx_gas_limit(raw_tx)).checked_mul(gas_price)
+}
+
+/// The sender must be able to pay the max fee of the tx
+fn check_max_fee(raw_tx: &RawL2Transaction, balance: u128) -> Result<u128, TransactionError> {
+    match calculate_max_fee(raw_tx) {
+        Some(max_fee) if max_fee <= balance => Ok(max_fee),
+        max_fee => Err(TransactionError::InsufficientFee {
+            max_fee: max_fee.unwrap_or_else(u128::max_value),
+            balance,
+        }),
+    }
+}
+
+fn panic_reason(err: Box<dyn Any + Send>) -> String {
+    if let Some(reason) = err.downcast_ref::<&str>() {
+        reason.to_string()
+    } else if let Some(reason) = err.downcast_ref::<String>() {
+        reason.clone()
+    } else {
+        "unknown panic".to_string()
     }
 }
 
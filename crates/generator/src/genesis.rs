@@ -1,8 +1,8 @@
-use crate::{traits::StateExt, RollupContext};
-use anyhow::Result;
+use crate::{sudt::build_l2_sudt_script, traits::StateExt, RollupContext};
+use anyhow::{anyhow, Result};
 use gw_common::{
     blake2b::new_blake2b,
-    builtins::{CKB_SUDT_ACCOUNT_ID, RESERVED_ACCOUNT_ID},
+    builtins::BuiltinAccounts,
     smt::{default_store::DefaultStore, H256, SMT},
     state::State,
     CKB_SUDT_SCRIPT_ARGS,
@@ -35,19 +35,42 @@ pub struct GenesisWithGlobalState {
     pub global_state: GlobalState,
 }
 
+fn genesis_rollup_context(config: &GenesisConfig) -> RollupContext {
+    RollupContext {
+        rollup_script_hash: {
+            let rollup_script_hash: [u8; 32] = config.rollup_type_hash.clone().into();
+            rollup_script_hash.into()
+        },
+        rollup_config: config.rollup_config.clone().into(),
+        builtin_accounts: Default::default(),
+        script_args: Default::default(),
+        deposit_limits: Default::default(),
+        withdrawal_fee: Default::default(),
+    }
+}
+
+/// Script of the reserved account, the account is reserved for special use
+fn build_reserved_account_script(config: &GenesisConfig, rollup_script_hash: &H256) -> Script {
+    Script::new_builder()
+        .code_hash({
+            let code_hash: [u8; 32] = config.meta_contract_validator_type_hash.clone().into();
+            code_hash.pack()
+        })
+        .hash_type(ScriptHashType::Type.into())
+        .args({
+            let rollup_script_hash: [u8; 32] = (*rollup_script_hash).into();
+            Bytes::from(rollup_script_hash.to_vec()).pack()
+        })
+        .build()
+}
+
 /// build genesis from store
 /// This function initialize db to genesis state
 pub fn build_genesis_from_store(
     db: StoreTransaction,
     config: &GenesisConfig,
 ) -> Result<(StoreTransaction, GenesisWithGlobalState)> {
-    let rollup_context = RollupContext {
-        rollup_script_hash: {
-            let rollup_script_hash: [u8; 32] = config.rollup_type_hash.clone().into();
-            rollup_script_hash.into()
-        },
-        rollup_config: config.rollup_config.clone().into(),
-    };
+    let rollup_context = genesis_rollup_context(config);
     // initialize store
     db.set_account_smt_root(H256::zero())?;
     db.set_block_smt_root(H256::zero())?;
@@ -58,30 +81,22 @@ pub fn build_genesis_from_store(
     // create a reserved account
     // this account is reserved for special use
     // for example: send a tx to reserved account to create a new contract account
-    let reserved_id = tree.create_account_from_script(
-        Script::new_builder()
-            .code_hash({
-                let code_hash: [u8; 32] = config.meta_contract_validator_type_hash.clone().into();
-                code_hash.pack()
-            })
-            .hash_type(ScriptHashType::Type.into())
-            .args({
-                let rollup_script_hash: [u8; 32] = rollup_context.rollup_script_hash.into();
-                Bytes::from(rollup_script_hash.to_vec()).pack()
-            })
-            .build(),
-    )?;
+    // the genesis creates builtin accounts in the default layout
+    let builtin_accounts = &rollup_context.builtin_accounts;
+    let reserved_id = tree.create_account_from_script(build_reserved_account_script(
+        config,
+        &rollup_context.rollup_script_hash,
+    ))?;
     assert_eq!(
-        reserved_id, RESERVED_ACCOUNT_ID,
-        "reserved account id must be zero"
+        reserved_id, builtin_accounts.reserved_id,
+        "reserved account id"
     );
 
     // setup CKB simple UDT contract
-    let ckb_sudt_script =
-        crate::sudt::build_l2_sudt_script(&rollup_context, &CKB_SUDT_SCRIPT_ARGS.into());
+    let ckb_sudt_script = build_l2_sudt_script(&rollup_context, &CKB_SUDT_SCRIPT_ARGS.into());
     let ckb_sudt_id = tree.create_account_from_script(ckb_sudt_script)?;
     assert_eq!(
-        ckb_sudt_id, CKB_SUDT_ACCOUNT_ID,
+        ckb_sudt_id, builtin_accounts.ckb_sudt_id,
         "ckb simple UDT account id"
     );
 
@@ -175,6 +190,12 @@ pub fn init_genesis(
             global_state,
         },
     ) = build_genesis_from_store(db, config)?;
+    // the genesis global state is computed locally
+    db.insert_block_local_post_state(
+        &genesis.hash().into(),
+        &global_state.account(),
+        &global_state.block(),
+    )?;
     db.insert_block(
         genesis.clone(),
         genesis_committed_info,
@@ -186,3 +207,25 @@ pub fn init_genesis(
     db.commit()?;
     Ok(())
 }
+
+/// Resolve the builtin account ids from the genesis state of the store
+pub fn load_builtin_accounts(store: &Store, config: &GenesisConfig) -> Result<BuiltinAccounts> {
+    let db = store.begin_transaction();
+    let genesis_hash = db
+        .get_block_hash_by_number(0)?
+        .ok_or_else(|| anyhow!("the store isn't initialized"))?;
+    let state_db =
+        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(genesis_hash))?;
+    let tree = state_db.account_state_tree()?;
+    let find_account_id = |script: Script, name: &str| -> Result<u32> {
+        tree.get_account_id_by_script_hash(&script.hash().into())?
+            .ok_or_else(|| anyhow!("can't find the {} account in genesis", name))
+    };
+    let rollup_context = genesis_rollup_context(config);
+    let reserved_script = build_reserved_account_script(config, &rollup_context.rollup_script_hash);
+    let ckb_sudt_script = build_l2_sudt_script(&rollup_context, &CKB_SUDT_SCRIPT_ARGS.into());
+    Ok(BuiltinAccounts {
+        reserved_id: find_account_id(reserved_script, "reserved")?,
+        ckb_sudt_id: find_account_id(ckb_sudt_script, "CKB sUDT")?,
+    })
+}
@@ -7,6 +7,9 @@ pub mod dummy_state;
 pub mod error;
 pub mod generator;
 pub mod genesis;
+pub mod meta_contract;
+pub mod script_args;
+pub mod storage_usage;
 pub mod sudt;
 pub mod syscalls;
 pub mod traits;
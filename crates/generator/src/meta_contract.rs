@@ -0,0 +1,68 @@
+//! Built-in meta contract
+//!
+//! Txs sent to the meta contract account are executed by the meta contract backend, the same
+//! as in an on-chain challenge. The mem-pool checks a `CreateAccount` tx before executing it,
+//! so a tx which can't create its account is rejected instead of failing in a block. The
+//! checks are those of the create syscall called by the backend.
+
+use gw_common::{state::State, H256};
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{MetaContractArgs, MetaContractArgsUnion, RawL2Transaction, Script},
+    prelude::*,
+};
+
+use crate::{error::TransactionError, RollupContext};
+
+/// Check a tx sent to the meta contract, it's only checked by the mem-pool
+pub fn check_meta_contract_tx<S: State>(
+    state: &S,
+    rollup_context: &RollupContext,
+    raw_tx: &RawL2Transaction,
+) -> Result<(), TransactionError> {
+    let args: Bytes = raw_tx.args().unpack();
+    let args = MetaContractArgs::from_slice(&args)
+        .map_err(|_| TransactionError::InvalidMetaContractArgs)?;
+    match args.to_enum() {
+        MetaContractArgsUnion::CreateAccount(create_account) => {
+            check_create_account(state, rollup_context, &create_account.script())
+        }
+    }
+}
+
+/// Same checks as the create syscall, but rejects the tx instead of returning an error code
+fn check_create_account<S: State>(
+    state: &S,
+    rollup_context: &RollupContext,
+    script: &Script,
+) -> Result<(), TransactionError> {
+    let script_hash: H256 = script.hash().into();
+    if state.get_account_id_by_script_hash(&script_hash)?.is_some() {
+        return Err(TransactionError::DuplicatedScriptHash { script_hash });
+    }
+
+    if script.hash_type() != ScriptHashType::Type.into() {
+        return Err(TransactionError::InvalidScript { script_hash });
+    }
+    let rollup_config = &rollup_context.rollup_config;
+    let is_eoa_account = rollup_config
+        .allowed_eoa_type_hashes()
+        .into_iter()
+        .any(|type_hash| type_hash == script.code_hash());
+    if !is_eoa_account {
+        let is_contract_account = rollup_config
+            .allowed_contract_type_hashes()
+            .into_iter()
+            .any(|type_hash| type_hash == script.code_hash());
+        // contract scripts are prefixed with the rollup script hash
+        let args: Bytes = script.args().unpack();
+        if !is_contract_account
+            || args.len() < 32
+            || !args.starts_with(rollup_context.rollup_script_hash.as_slice())
+        {
+            return Err(TransactionError::InvalidScript { script_hash });
+        }
+    }
+    Ok(())
+}
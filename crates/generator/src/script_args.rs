@@ -0,0 +1,78 @@
+//! Format of account script args
+//!
+//! Accounts are identified by the hash of their script, the args of an account script are
+//! interpreted by the backend or the lock of the code hash, e.g. an Ethereum address is
+//! derived from them. A script with malformed args creates an account nobody can use.
+//!
+//! The registry is a config of the node, not of the rollup, so it isn't a part of the state
+//! transition. The mem-pool rejects txs creating accounts with such scripts and the block
+//! producer skips such deposits, but blocks including them are valid.
+
+use crate::error::AccountError;
+use gw_common::H256;
+use gw_config::ScriptArgsConfig;
+use gw_types::{packed::Script, prelude::*};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptArgsRule {
+    /// args start with the rollup script hash
+    pub rollup_prefix: bool,
+    /// length of the args after the rollup prefix
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+/// Rules of the script args by code hash, scripts of other code hashes are not checked
+#[derive(Debug, Clone, Default)]
+pub struct ScriptArgsRegistry {
+    rules: HashMap<H256, ScriptArgsRule>,
+}
+
+impl ScriptArgsRegistry {
+    pub fn from_config(configs: &[ScriptArgsConfig]) -> Self {
+        let mut registry = ScriptArgsRegistry::default();
+        for config in configs {
+            let code_hash: [u8; 32] = config.code_hash.clone().into();
+            registry.register(
+                code_hash.into(),
+                ScriptArgsRule {
+                    rollup_prefix: config.rollup_prefix,
+                    min_len: config.min_len,
+                    max_len: config.max_len,
+                },
+            );
+        }
+        registry
+    }
+
+    pub fn register(&mut self, code_hash: H256, rule: ScriptArgsRule) {
+        self.rules.insert(code_hash, rule);
+    }
+
+    pub fn get_rule(&self, code_hash: &H256) -> Option<&ScriptArgsRule> {
+        self.rules.get(code_hash)
+    }
+
+    /// Verify the args of an account script
+    pub fn verify(&self, rollup_script_hash: &H256, script: &Script) -> Result<(), AccountError> {
+        let code_hash: H256 = script.code_hash().unpack();
+        let rule = match self.get_rule(&code_hash) {
+            Some(rule) => rule,
+            None => return Ok(()),
+        };
+        let args = script.args().raw_data();
+        let args = if rule.rollup_prefix {
+            if args.len() < 32 || &args[..32] != rollup_script_hash.as_slice() {
+                return Err(AccountError::InvalidScriptArgs);
+            }
+            &args[32..]
+        } else {
+            &args[..]
+        };
+        if args.len() < rule.min_len || args.len() > rule.max_len {
+            return Err(AccountError::InvalidScriptArgs);
+        }
+        Ok(())
+    }
+}
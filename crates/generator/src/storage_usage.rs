@@ -0,0 +1,45 @@
+//! Storage usage accounting
+//!
+//! The slots and data written by a tx are owned by the called account. Calls between contracts
+//! of a backend run inside the VM, so their writes can't be told apart. A slot is used while
+//! its value is non-zero, data is never removed.
+//!
+//! Only txs are accounted, the balances changed by deposits and withdrawals are not,
+//! neither is the nonce of the sender.
+
+use gw_common::{
+    error::Error,
+    state::{build_account_field_key, State, GW_ACCOUNT_NONCE},
+};
+use gw_types::{
+    offchain::{RunResult, StorageDelta},
+    packed::RawL2Transaction,
+    prelude::*,
+};
+
+/// Storage change of the called account made by a tx, `state` is the state before the tx
+pub fn storage_delta<S: State>(
+    state: &S,
+    raw_tx: &RawL2Transaction,
+    run_result: &RunResult,
+) -> Result<StorageDelta, Error> {
+    let sender_id: u32 = raw_tx.from_id().unpack();
+    let sender_nonce_key = build_account_field_key(sender_id, GW_ACCOUNT_NONCE);
+    let mut delta = StorageDelta::default();
+    for (key, value) in &run_result.write_values {
+        if key == &sender_nonce_key {
+            continue;
+        }
+        match (state.get_raw(key)?.is_zero(), value.is_zero()) {
+            (true, false) => delta.slots += 1,
+            (false, true) => delta.slots -= 1,
+            _ => {}
+        }
+    }
+    for (data_hash, data) in &run_result.write_data {
+        if !state.is_data_hash_exist(data_hash)? {
+            delta.data_bytes += data.len() as i64;
+        }
+    }
+    Ok(delta)
+}
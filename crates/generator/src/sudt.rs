@@ -1,7 +1,12 @@
 use gw_common::H256;
-use gw_types::{bytes::Bytes, core::ScriptHashType, packed::Script, prelude::*};
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{SUDTArgs, SUDTArgsUnion, Script},
+    prelude::*,
+};
 
-use crate::RollupContext;
+use crate::{error::AccountError, RollupContext};
 
 pub fn build_l2_sudt_script(rollup_context: &RollupContext, l1_sudt_script_hash: &H256) -> Script {
     let args = {
@@ -20,3 +25,31 @@ pub fn build_l2_sudt_script(rollup_context: &RollupContext, l1_sudt_script_hash:
         .hash_type(ScriptHashType::Type.into())
         .build()
 }
+
+/// A SUDT transfer performed by a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SUDTTransferRecord {
+    pub from_id: u32,
+    pub to_id: u32,
+    pub amount: u128,
+}
+
+/// Extract transfers from SUDT args, indexers use it to track SUDT movements
+/// fees are not included.
+///
+/// Queries don't move tokens, their result is recorded as the return data of the tx receipt.
+pub fn extract_sudt_transfers(
+    sender_id: u32,
+    args: &[u8],
+) -> Result<Vec<SUDTTransferRecord>, AccountError> {
+    let args = SUDTArgs::from_slice(args).map_err(|_| AccountError::InvalidSUDTOperation)?;
+    let transfers = match args.to_enum() {
+        SUDTArgsUnion::SUDTTransfer(transfer) => vec![SUDTTransferRecord {
+            from_id: sender_id,
+            to_id: transfer.to().unpack(),
+            amount: transfer.amount().unpack(),
+        }],
+        SUDTArgsUnion::SUDTQuery(_) => Vec::new(),
+    };
+    Ok(transfers)
+}
@@ -6,10 +6,12 @@ use ckb_vm::{
 };
 use gw_common::{
     blake2b::new_blake2b,
+    entropy::derive_block_entropy,
     h256_ext::H256Ext,
     state::{
         build_account_field_key, build_script_hash_to_account_id_key, State, GW_ACCOUNT_NONCE,
-        GW_ACCOUNT_SCRIPT_HASH,
+        GW_ACCOUNT_SCRIPT_HASH, GW_ACCOUNT_SUDT_TOTAL_SUPPLY, GW_LOG_L2_TO_L1_MESSAGE,
+        GW_LOG_TX_FAILED,
     },
     H256,
 };
@@ -17,15 +19,18 @@ use gw_traits::{ChainStore, CodeStore};
 use gw_types::{
     bytes::Bytes,
     core::ScriptHashType,
-    offchain::RunResult,
+    offchain::{RunResult, TraceEvent, TraceStep},
     packed::{BlockInfo, LogItem, RawL2Transaction, Script},
     prelude::*,
 };
-use std::{cmp, convert::TryInto};
+use std::{cmp, convert::TryInto, time::Instant};
 
 /* Constants */
 // 24KB is max ethereum contract code size
 const MAX_SET_RETURN_DATA_SIZE: u64 = 1024 * 24;
+// L2 to L1 message payloads are committed in the state, keep them small
+const MAX_L2_TO_L1_MESSAGE_SIZE: u32 = 1024 * 4;
+const BLOCK_METADATA_SIZE: usize = 8 + 4 + 32 + 8 + 32 + 8;
 
 /* Syscall numbers */
 const SYS_STORE: u64 = 3051;
@@ -41,7 +46,11 @@ const SYS_LOAD_ACCOUNT_SCRIPT: u64 = 4055;
 const SYS_STORE_DATA: u64 = 4056;
 const SYS_LOAD_DATA: u64 = 4057;
 const SYS_GET_BLOCK_HASH: u64 = 4058;
+const SYS_LOAD_BLOCK_METADATA: u64 = 4059;
+const SYS_LOAD_SUDT_TOTAL_SUPPLY: u64 = 4060;
 const SYS_LOG: u64 = 4061;
+const SYS_SEND_MESSAGE: u64 = 4062;
+const SYS_LOAD_BLOCK_ENTROPY: u64 = 4063;
 /* CKB compatible syscalls */
 const DEBUG_PRINT_SYSCALL_NUMBER: u64 = 2177;
 
@@ -59,6 +68,10 @@ pub(crate) struct L2Syscalls<'a, S, C> {
     pub(crate) raw_tx: &'a RawL2Transaction,
     pub(crate) code_store: &'a dyn CodeStore,
     pub(crate) result: &'a mut RunResult,
+    /// stop the VM at the next syscall once passed
+    pub(crate) deadline: Option<Instant>,
+    /// stop the VM once the trace has the max steps
+    pub(crate) max_trace_steps: Option<usize>,
 }
 
 fn load_data_u32<Mac: SupportMachine>(machine: &mut Mac, addr: u64) -> Result<u32, VMError> {
@@ -123,13 +136,27 @@ impl<'a, S: State, C: ChainStore, Mac: SupportMachine> Syscalls<Mac> for L2Sysca
     }
 
     fn ecall(&mut self, machine: &mut Mac) -> Result<bool, VMError> {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(VMError::Unexpected);
+            }
+        }
         let code = machine.registers()[A7].to_u64();
+        let cycles = machine.cycles();
+        // the syscalls changing the state are traced with their arguments
+        if !matches!(
+            code,
+            SYS_STORE | SYS_LOAD | SYS_SET_RETURN_DATA | SYS_CREATE | SYS_LOG | SYS_SEND_MESSAGE
+        ) {
+            self.trace(cycles, || TraceEvent::Syscall(code))?;
+        }
         match code {
             SYS_STORE => {
                 let key_addr = machine.registers()[A0].to_u64();
                 let key = load_data_h256(machine, key_addr)?;
                 let value_addr = machine.registers()[A1].to_u64();
                 let value = load_data_h256(machine, value_addr)?;
+                self.trace(cycles, || TraceEvent::Store { key, value })?;
                 self.result.write_values.insert(key, value);
                 machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
                 Ok(true)
@@ -139,6 +166,7 @@ impl<'a, S: State, C: ChainStore, Mac: SupportMachine> Syscalls<Mac> for L2Sysca
                 let key = load_data_h256(machine, key_addr)?;
                 let value_addr = machine.registers()[A1].to_u64();
                 let value = self.get_raw(&key)?;
+                self.trace(cycles, || TraceEvent::Load { key, value })?;
                 machine
                     .memory_mut()
                     .store_bytes(value_addr, &value.as_slice())?;
@@ -152,6 +180,7 @@ impl<'a, S: State, C: ChainStore, Mac: SupportMachine> Syscalls<Mac> for L2Sysca
                     return Err(VMError::Unexpected);
                 }
                 let data = load_bytes(machine, data_addr, len as usize)?;
+                self.trace(cycles, || TraceEvent::SetReturnData(data.clone()))?;
                 self.result.return_data = data;
                 machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
                 Ok(true)
@@ -229,6 +258,10 @@ impl<'a, S: State, C: ChainStore, Mac: SupportMachine> Syscalls<Mac> for L2Sysca
                     .new_scripts
                     .insert(script_hash.into(), script.as_slice().to_vec());
                 self.set_account_count(id + 1);
+                self.trace(cycles, || TraceEvent::CreateAccount {
+                    account_id: id,
+                    script_hash: script_hash.into(),
+                })?;
                 machine
                     .memory_mut()
                     .store32(&account_id_addr, &Mac::REG::from_u32(id))?;
@@ -387,20 +420,81 @@ impl<'a, S: State, C: ChainStore, Mac: SupportMachine> Syscalls<Mac> for L2Sysca
                 }
                 Ok(true)
             }
+            SYS_LOAD_BLOCK_METADATA => {
+                let data = self.build_block_metadata()?;
+                store_data(machine, &data)?;
+                machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
+                Ok(true)
+            }
+            SYS_LOAD_BLOCK_ENTROPY => {
+                let entropy_addr = machine.registers()[A0].to_u64();
+                let entropy = self.build_block_entropy();
+                machine.memory_mut().store_bytes(entropy_addr, &entropy)?;
+                machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
+                Ok(true)
+            }
+            SYS_LOAD_SUDT_TOTAL_SUPPLY => {
+                let sudt_id = machine.registers()[A0].to_u32();
+                let total_supply_addr = machine.registers()[A1].to_u64();
+                let total_supply = self
+                    .get_raw(&build_account_field_key(
+                        sudt_id,
+                        GW_ACCOUNT_SUDT_TOTAL_SUPPLY,
+                    ))?
+                    .to_u128();
+                machine
+                    .memory_mut()
+                    .store_bytes(total_supply_addr, &total_supply.to_le_bytes())?;
+                machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
+                Ok(true)
+            }
             SYS_LOG => {
                 let account_id = machine.registers()[A0].to_u32();
                 let service_flag = machine.registers()[A1].to_u8();
                 let data_len = machine.registers()[A2].to_u32();
                 let data_addr = machine.registers()[A3].to_u64();
 
+                if service_flag == GW_LOG_L2_TO_L1_MESSAGE || service_flag == GW_LOG_TX_FAILED {
+                    eprintln!(
+                        "syscall error: log service flag {} is reserved",
+                        service_flag
+                    );
+                    return Err(VMError::Unexpected);
+                }
+
                 let data = load_bytes(machine, data_addr, data_len as usize)?;
-                self.result.logs.push(
-                    LogItem::new_builder()
-                        .account_id(account_id.pack())
-                        .service_flag(service_flag.into())
-                        .data(Bytes::from(data).pack())
-                        .build(),
-                );
+                let log = LogItem::new_builder()
+                    .account_id(account_id.pack())
+                    .service_flag(service_flag.into())
+                    .data(Bytes::from(data).pack())
+                    .build();
+                self.trace(cycles, || TraceEvent::Log(log.clone()))?;
+                self.result.logs.push(log);
+                machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
+                Ok(true)
+            }
+            SYS_SEND_MESSAGE => {
+                let payload_len = machine.registers()[A0].to_u32();
+                let payload_addr = machine.registers()[A1].to_u64();
+
+                if payload_len > MAX_L2_TO_L1_MESSAGE_SIZE {
+                    eprintln!(
+                        "syscall error: message payload too large: {} > {}",
+                        payload_len, MAX_L2_TO_L1_MESSAGE_SIZE
+                    );
+                    return Err(VMError::Unexpected);
+                }
+
+                let payload = load_bytes(machine, payload_addr, payload_len as usize)?;
+                // the message is always sent from the account being executed
+                let from_id: u32 = self.raw_tx.to_id().unpack();
+                let log = LogItem::new_builder()
+                    .account_id(from_id.pack())
+                    .service_flag(GW_LOG_L2_TO_L1_MESSAGE.into())
+                    .data(Bytes::from(payload).pack())
+                    .build();
+                self.trace(cycles, || TraceEvent::Log(log.clone()))?;
+                self.result.logs.push(log);
                 machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
                 Ok(true)
             }
@@ -414,6 +508,20 @@ impl<'a, S: State, C: ChainStore, Mac: SupportMachine> Syscalls<Mac> for L2Sysca
 }
 
 impl<'a, S: State, C: ChainStore> L2Syscalls<'a, S, C> {
+    /// Record a syscall if the tracing is on, it fails once the trace has the max steps
+    fn trace<F: FnOnce() -> TraceEvent>(&mut self, cycles: u64, event: F) -> Result<(), VMError> {
+        if let Some(trace) = self.result.trace.as_mut() {
+            if self.max_trace_steps.map_or(false, |max| trace.len() >= max) {
+                return Err(VMError::Unexpected);
+            }
+            trace.push(TraceStep {
+                cycles,
+                event: event(),
+            });
+        }
+        Ok(())
+    }
+
     fn get_raw(&mut self, key: &H256) -> Result<H256, VMError> {
         let value = match self.result.write_values.get(&key) {
             Some(value) => *value,
@@ -478,6 +586,40 @@ impl<'a, S: State, C: ChainStore> L2Syscalls<'a, S, C> {
         Ok(Some(id))
     }
 
+    /// Block metadata returned by SYS_LOAD_BLOCK_METADATA, integers are little endian:
+    ///
+    /// | number: u64 | block_producer_id: u32 | block_producer_script_hash: [u8; 32]
+    /// | timestamp: u64 | parent_block_hash: [u8; 32] | last_finalized_block_number: u64 |
+    ///
+    /// All fields come from the block header, so the block producer and the validators load the
+    /// same metadata. Layer1 data isn't returned, the parent block may not be committed yet.
+    pub(crate) fn build_block_metadata(&mut self) -> Result<Vec<u8>, VMError> {
+        let number: u64 = self.block_info.number().unpack();
+        let block_producer_id: u32 = self.block_info.block_producer_id().unpack();
+        let block_producer_script_hash = self.get_script_hash(block_producer_id)?;
+        let timestamp: u64 = self.block_info.timestamp().unpack();
+        let parent_block_hash = self.chain.get_tip_block_hash();
+        // the last finalized block number of the post global state of the block
+        let finality_blocks: u64 = self.rollup_context.rollup_config.finality_blocks().unpack();
+        let last_finalized_block_number = number.saturating_sub(finality_blocks);
+
+        let mut data = Vec::with_capacity(BLOCK_METADATA_SIZE);
+        data.extend_from_slice(&number.to_le_bytes());
+        data.extend_from_slice(&block_producer_id.to_le_bytes());
+        data.extend_from_slice(block_producer_script_hash.as_slice());
+        data.extend_from_slice(&timestamp.to_le_bytes());
+        data.extend_from_slice(parent_block_hash.as_slice());
+        data.extend_from_slice(&last_finalized_block_number.to_le_bytes());
+        Ok(data)
+    }
+
+    /// Entropy of the block returned by SYS_LOAD_BLOCK_ENTROPY, see `gw_common::entropy`
+    pub(crate) fn build_block_entropy(&self) -> [u8; 32] {
+        let number: u64 = self.block_info.number().unpack();
+        let parent_block_hash = self.chain.get_tip_block_hash();
+        derive_block_entropy(&parent_block_hash.into(), number)
+    }
+
     fn output_debug<Mac: SupportMachine>(&self, machine: &mut Mac) -> Result<(), VMError> {
         let mut addr = machine.registers()[A0].to_u64();
         let mut buffer = Vec::new();
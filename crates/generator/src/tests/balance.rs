@@ -0,0 +1,70 @@
+use crate::{dummy_state::DummyState, error::AccountError, Error};
+use gw_common::{error::Error as StateError, state::State};
+
+const SUDT_ID: u32 = 1;
+
+#[test]
+fn test_mint_overflow() {
+    let mut state = DummyState::default();
+    state.mint_sudt(SUDT_ID, 2, u128::MAX).unwrap();
+    let err = state.mint_sudt(SUDT_ID, 2, 1).unwrap_err();
+    assert_eq!(err, StateError::AmountOverflow);
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 2).unwrap(), u128::MAX);
+    let err: Error = err.into();
+    assert_eq!(err, Error::Account(AccountError::AmountOverflow));
+}
+
+#[test]
+fn test_burn_insufficient_balance() {
+    let mut state = DummyState::default();
+    state.mint_sudt(SUDT_ID, 2, 42).unwrap();
+    let err = state.burn_sudt(SUDT_ID, 2, 43).unwrap_err();
+    assert_eq!(err, StateError::InsufficientBalance);
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 2).unwrap(), 42);
+    let err: Error = err.into();
+    assert_eq!(err, Error::Account(AccountError::InsufficientBalance));
+}
+
+#[test]
+fn test_transfer_and_pay_fee() {
+    let mut state = DummyState::default();
+    state.mint_sudt(SUDT_ID, 2, 100).unwrap();
+    state.transfer_sudt(SUDT_ID, 2, 3, 60).unwrap();
+    state.pay_fee(SUDT_ID, 2, 4, 10).unwrap();
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 2).unwrap(), 30);
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 3).unwrap(), 60);
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 4).unwrap(), 10);
+
+    // failed transfer must not touch the state
+    let err = state.transfer_sudt(SUDT_ID, 2, 3, 31).unwrap_err();
+    assert_eq!(err, StateError::InsufficientBalance);
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 2).unwrap(), 30);
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 3).unwrap(), 60);
+
+    // minting would overflow the total supply, set the balance directly
+    state.set_sudt_balance(SUDT_ID, 3, u128::MAX).unwrap();
+    let err = state.transfer_sudt(SUDT_ID, 2, 3, 1).unwrap_err();
+    assert_eq!(err, StateError::AmountOverflow);
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 2).unwrap(), 30);
+}
+
+#[test]
+fn test_total_supply() {
+    let mut state = DummyState::default();
+    state.mint_sudt(SUDT_ID, 2, 100).unwrap();
+    state.mint_sudt(SUDT_ID, 3, 50).unwrap();
+    assert_eq!(state.get_sudt_total_supply(SUDT_ID).unwrap(), 150);
+
+    // transfer doesn't change total supply
+    state.transfer_sudt(SUDT_ID, 2, 3, 30).unwrap();
+    assert_eq!(state.get_sudt_total_supply(SUDT_ID).unwrap(), 150);
+
+    state.burn_sudt(SUDT_ID, 3, 80).unwrap();
+    assert_eq!(state.get_sudt_total_supply(SUDT_ID).unwrap(), 70);
+
+    // total supply overflow
+    let err = state.mint_sudt(SUDT_ID, 4, u128::MAX).unwrap_err();
+    assert_eq!(err, StateError::AmountOverflow);
+    assert_eq!(state.get_sudt_balance(SUDT_ID, 4).unwrap(), 0);
+    assert_eq!(state.get_sudt_total_supply(SUDT_ID).unwrap(), 70);
+}
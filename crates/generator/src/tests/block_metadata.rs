@@ -0,0 +1,90 @@
+use crate::{
+    dummy_state::DummyState, genesis::init_genesis, syscalls::L2Syscalls,
+    tests::new_rollup_context, RollupContext,
+};
+use gw_common::{entropy::derive_block_entropy, state::State, H256};
+use gw_config::GenesisConfig;
+use gw_store::{chain_view::ChainView, Store};
+use gw_types::{
+    offchain::RunResult,
+    packed::{BlockInfo, L2BlockCommittedInfo, RawL2Transaction, RollupConfig},
+    prelude::*,
+};
+
+const FINALITY_BLOCKS: u64 = 3;
+
+fn build_rollup_context() -> RollupContext {
+    let rollup_config = RollupConfig::new_builder()
+        .finality_blocks(FINALITY_BLOCKS.pack())
+        .build();
+    new_rollup_context([42u8; 32].into(), rollup_config)
+}
+
+/// (metadata, entropy) of the block after the genesis
+fn load_block_metadata(
+    store: &Store,
+    state: &DummyState,
+    block_info: &BlockInfo,
+) -> (Vec<u8>, [u8; 32]) {
+    let rollup_context = build_rollup_context();
+    let db = store.begin_transaction();
+    let chain_view = ChainView::new(&db, db.get_tip_block_hash().unwrap());
+    let raw_tx = RawL2Transaction::default();
+    let mut run_result = RunResult::default();
+    let mut syscalls = L2Syscalls {
+        chain: &chain_view,
+        state,
+        rollup_context: &rollup_context,
+        block_info,
+        raw_tx: &raw_tx,
+        code_store: state,
+        result: &mut run_result,
+        deadline: None,
+        max_trace_steps: None,
+    };
+    let metadata = syscalls.build_block_metadata().unwrap();
+    (metadata, syscalls.build_block_entropy())
+}
+
+#[test]
+fn test_block_metadata_of_producer_and_validator() {
+    let config = GenesisConfig {
+        timestamp: 42,
+        meta_contract_validator_type_hash: [1u8; 32].into(),
+        rollup_config: RollupConfig::default().into(),
+        rollup_type_hash: [42u8; 32].into(),
+    };
+    // the parent block isn't committed to layer1 yet when the block producer executes the
+    // block, it's committed when a validator replays the block
+    let producer_store = Store::open_tmp().unwrap();
+    init_genesis(&producer_store, &config, L2BlockCommittedInfo::default()).unwrap();
+    let validator_store = Store::open_tmp().unwrap();
+    let committed_info = L2BlockCommittedInfo::new_builder()
+        .number(100u64.pack())
+        .block_hash([7u8; 32].pack())
+        .transaction_hash([8u8; 32].pack())
+        .build();
+    init_genesis(&validator_store, &config, committed_info).unwrap();
+
+    let mut state = DummyState::default();
+    let block_producer_id = state.create_account(H256::from_u32(1)).unwrap();
+    let block_info = BlockInfo::new_builder()
+        .block_producer_id(block_producer_id.pack())
+        .number(5u64.pack())
+        .timestamp(1000u64.pack())
+        .build();
+    let (metadata, entropy) = load_block_metadata(&producer_store, &state, &block_info);
+    assert_eq!(
+        load_block_metadata(&validator_store, &state, &block_info),
+        (metadata.clone(), entropy)
+    );
+
+    let parent_block_hash = producer_store.get_tip_block_hash().unwrap();
+    assert_eq!(entropy, derive_block_entropy(&parent_block_hash.into(), 5));
+    assert_eq!(&metadata[0..8], &5u64.to_le_bytes());
+    assert_eq!(&metadata[8..12], &block_producer_id.to_le_bytes());
+    assert_eq!(&metadata[12..44], H256::from_u32(1).as_slice());
+    assert_eq!(&metadata[44..52], &1000u64.to_le_bytes());
+    assert_eq!(&metadata[52..84], parent_block_hash.as_slice());
+    assert_eq!(&metadata[84..92], &(5 - FINALITY_BLOCKS).to_le_bytes());
+}
@@ -0,0 +1,83 @@
+use crate::{
+    dummy_state::DummyState, error::DepositionError, tests::new_rollup_context, traits::StateExt,
+    RollupContext,
+};
+use gw_common::{state::State, H256};
+use gw_config::DepositLimitsConfig;
+use gw_types::{
+    core::ScriptHashType,
+    packed::{DepositionRequest, RollupConfig, Script},
+    prelude::*,
+};
+
+fn build_rollup_context() -> RollupContext {
+    RollupContext {
+        deposit_limits: DepositLimitsConfig {
+            min_capacity: 400_00000000,
+            min_sudt_amount: 100,
+        },
+        ..new_rollup_context(H256::zero(), RollupConfig::default())
+    }
+}
+
+fn build_deposit(capacity: u64, sudt_script_hash: [u8; 32], amount: u128) -> DepositionRequest {
+    let script = Script::new_builder()
+        .code_hash([1u8; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .build();
+    DepositionRequest::new_builder()
+        .script(script)
+        .capacity(capacity.pack())
+        .sudt_script_hash(sudt_script_hash.pack())
+        .amount(amount.pack())
+        .build()
+}
+
+#[test]
+fn test_min_deposit_amounts() {
+    let ctx = build_rollup_context();
+    let sudt_script_hash = [2u8; 32];
+
+    let err = ctx
+        .verify_deposit_amounts(&build_deposit(399_00000000, [0u8; 32], 0))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        DepositionError::InsufficientCapacity {
+            min_capacity: 400_00000000,
+            capacity: 399_00000000,
+        }
+    );
+    let err = ctx
+        .verify_deposit_amounts(&build_deposit(400_00000000, sudt_script_hash, 99))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        DepositionError::InsufficientSUDTAmount {
+            min_amount: 100,
+            amount: 99,
+        }
+    );
+    ctx.verify_deposit_amounts(&build_deposit(400_00000000, [0u8; 32], 0))
+        .unwrap();
+    ctx.verify_deposit_amounts(&build_deposit(400_00000000, sudt_script_hash, 100))
+        .unwrap();
+}
+
+#[test]
+fn test_deposit_below_min_amounts_is_applied() {
+    // the limits aren't a part of the state transition, a block with the deposit is valid
+    let ctx = build_rollup_context();
+    let mut state = DummyState::default();
+    let request = build_deposit(399_00000000, [0u8; 32], 0);
+    state.apply_deposition_request(&ctx, &request).unwrap();
+    let account_id = state
+        .get_account_id_by_script_hash(&request.script().hash().into())
+        .unwrap()
+        .expect("account");
+    let ckb_sudt_id = ctx.builtin_accounts.ckb_sudt_id;
+    assert_eq!(
+        state.get_sudt_balance(ckb_sudt_id, account_id).unwrap(),
+        399_00000000
+    );
+}
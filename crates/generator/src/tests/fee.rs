@@ -0,0 +1,95 @@
+use crate::{
+    account_lock_manage::AccountLockManage,
+    dummy_state::DummyState,
+    error::TransactionError,
+    tests::{new_generator_with, new_rollup_context},
+    Generator,
+};
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State, H256};
+use gw_types::{
+    offchain::RunResult,
+    packed::{BlockInfo, RawL2Transaction, RollupConfig},
+    prelude::*,
+};
+
+const ACCOUNT_CREATION_FEE: u64 = 100;
+
+fn build_generator(account_creation_fee: u64) -> Generator {
+    let rollup_context = new_rollup_context(H256::zero(), RollupConfig::default());
+    new_generator_with(
+        AccountLockManage::default(),
+        rollup_context,
+        account_creation_fee,
+    )
+}
+
+#[test]
+fn test_account_creation_fee() {
+    let mut state = DummyState::default();
+    for i in 0..=3u32 {
+        state.create_account(H256::from_u32(i + 1)).unwrap();
+    }
+    let (sender_id, block_producer_id) = (2, 3);
+    state
+        .mint_sudt(CKB_SUDT_ACCOUNT_ID, sender_id, 250)
+        .unwrap();
+    let raw_tx = RawL2Transaction::new_builder()
+        .from_id(sender_id.pack())
+        .gas_limit(10u64.pack())
+        .gas_price(25u128.pack())
+        .build();
+    let block_info = BlockInfo::new_builder()
+        .block_producer_id(block_producer_id.pack())
+        .build();
+    let mut run_result = RunResult {
+        used_cycles: 8,
+        ..Default::default()
+    };
+    for i in 0..2u32 {
+        run_result
+            .new_scripts
+            .insert(H256::from_u32(100 + i), Vec::new());
+    }
+
+    // the gas fee 8 * 25 covers 2 created accounts
+    assert!(build_generator(0)
+        .check_account_creation_fee(&raw_tx, &run_result)
+        .is_ok());
+    let generator = build_generator(ACCOUNT_CREATION_FEE);
+    assert!(generator
+        .check_account_creation_fee(&raw_tx, &run_result)
+        .is_ok());
+    // not a 3rd one
+    run_result
+        .new_scripts
+        .insert(H256::from_u32(102), Vec::new());
+    let err = generator
+        .check_account_creation_fee(&raw_tx, &run_result)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InsufficientAccountCreationFee {
+            fee: 3 * ACCOUNT_CREATION_FEE as u128,
+            gas_fee: 200,
+        }
+    );
+
+    // the created accounts are not charged in the state, only the gas fee is
+    let fee = generator
+        .settle_fee(&mut state, &block_info, &raw_tx, &run_result)
+        .unwrap();
+    assert_eq!(fee.paid_fee, 200);
+    assert_eq!(fee.refunded_fee, 50);
+    assert_eq!(
+        state
+            .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, sender_id)
+            .unwrap(),
+        50
+    );
+    assert_eq!(
+        state
+            .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, block_producer_id)
+            .unwrap(),
+        200
+    );
+}
@@ -53,3 +53,30 @@ fn test_init_genesis() {
     let code_hash: [u8; 32] = script.code_hash().unpack();
     assert_eq!(code_hash, meta_contract_code_hash);
 }
+
+#[test]
+fn test_init_genesis_in_memory() {
+    let config = GenesisConfig {
+        timestamp: 42,
+        meta_contract_validator_type_hash: [1u8; 32].into(),
+        rollup_config: RollupConfig::default().into(),
+        rollup_type_hash: [42u8; 32].into(),
+    };
+    // the in-memory store initializes the same genesis state as a temp store on disk
+    let stores = vec![Store::open_tmp().unwrap(), Store::open_in_memory()];
+    let roots: Vec<_> = stores
+        .iter()
+        .map(|store| {
+            init_genesis(store, &config, L2BlockCommittedInfo::default()).unwrap();
+            let db = store.begin_transaction();
+            (
+                db.get_tip_block_hash().unwrap(),
+                db.get_block_smt_root().unwrap(),
+                db.get_account_smt_root().unwrap(),
+                db.get_account_count().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(roots[0], roots[1]);
+    assert_eq!(roots[1].0, GENESIS_BLOCK_HASH.into());
+}
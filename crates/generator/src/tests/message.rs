@@ -0,0 +1,33 @@
+use crate::{dummy_state::DummyState, traits::StateExt};
+use gw_common::state::{build_l2_to_l1_message_hash, State, GW_LOG_L2_TO_L1_MESSAGE};
+use gw_types::{bytes::Bytes, offchain::RunResult, packed::LogItem, prelude::*};
+
+fn build_log(account_id: u32, service_flag: u8, data: &[u8]) -> LogItem {
+    LogItem::new_builder()
+        .account_id(account_id.pack())
+        .service_flag(service_flag.into())
+        .data(Bytes::from(data.to_vec()).pack())
+        .build()
+}
+
+#[test]
+fn test_apply_l2_to_l1_messages() {
+    let mut state = DummyState::default();
+    let run_result = RunResult {
+        logs: vec![
+            build_log(2, GW_LOG_L2_TO_L1_MESSAGE, b"withdraw nft 42"),
+            build_log(3, 0, b"ordinary log"),
+        ],
+        ..Default::default()
+    };
+    state.apply_run_result(&run_result).expect("apply");
+
+    let message_hash = build_l2_to_l1_message_hash(2, b"withdraw nft 42");
+    assert!(state.is_l2_to_l1_message_exist(&message_hash).unwrap());
+    // the sender is part of the message
+    let forged_hash = build_l2_to_l1_message_hash(3, b"withdraw nft 42");
+    assert!(!state.is_l2_to_l1_message_exist(&forged_hash).unwrap());
+    // ordinary logs are not committed
+    let log_hash = build_l2_to_l1_message_hash(3, b"ordinary log");
+    assert!(!state.is_l2_to_l1_message_exist(&log_hash).unwrap());
+}
@@ -0,0 +1,84 @@
+use crate::{
+    dummy_state::DummyState, error::TransactionError, meta_contract::check_meta_contract_tx,
+    tests::new_rollup_context, traits::StateExt, RollupContext,
+};
+use gw_common::{state::State, H256};
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{CreateAccount, MetaContractArgs, RawL2Transaction, RollupConfig, Script},
+    prelude::*,
+};
+
+const EOA_CODE_HASH: [u8; 32] = [1u8; 32];
+
+fn build_rollup_context() -> RollupContext {
+    let rollup_config = RollupConfig::new_builder()
+        .allowed_eoa_type_hashes(vec![EOA_CODE_HASH].pack())
+        .build();
+    new_rollup_context([42u8; 32].into(), rollup_config)
+}
+
+fn build_create_account_tx(script: Script) -> RawL2Transaction {
+    let args = MetaContractArgs::new_builder()
+        .set(CreateAccount::new_builder().script(script).build())
+        .build();
+    RawL2Transaction::new_builder()
+        .from_id(1u32.pack())
+        .to_id(0u32.pack())
+        .args(args.as_bytes().pack())
+        .build()
+}
+
+fn build_script(args: u8, hash_type: ScriptHashType) -> Script {
+    Script::new_builder()
+        .code_hash(EOA_CODE_HASH.pack())
+        .hash_type(hash_type.into())
+        .args(Bytes::from(vec![args]).pack())
+        .build()
+}
+
+#[test]
+fn test_check_create_account() {
+    let rollup_context = build_rollup_context();
+    let mut state = DummyState::default();
+    for i in 0..2u32 {
+        state.create_account(H256::from_u32(i + 1)).unwrap();
+    }
+
+    let script = build_script(1, ScriptHashType::Type);
+    let raw_tx = build_create_account_tx(script.clone());
+    assert_eq!(
+        check_meta_contract_tx(&state, &rollup_context, &raw_tx),
+        Ok(())
+    );
+
+    // the account exists
+    state.create_account_from_script(script.clone()).unwrap();
+    assert_eq!(
+        check_meta_contract_tx(&state, &rollup_context, &raw_tx),
+        Err(TransactionError::DuplicatedScriptHash {
+            script_hash: script.hash().into()
+        })
+    );
+
+    // only type hash type is allowed
+    let script = build_script(2, ScriptHashType::Data);
+    let raw_tx = build_create_account_tx(script.clone());
+    assert_eq!(
+        check_meta_contract_tx(&state, &rollup_context, &raw_tx),
+        Err(TransactionError::InvalidScript {
+            script_hash: script.hash().into()
+        })
+    );
+
+    // unknown args
+    let raw_tx = raw_tx
+        .as_builder()
+        .args(Bytes::from(vec![0u8; 4]).pack())
+        .build();
+    assert_eq!(
+        check_meta_contract_tx(&state, &rollup_context, &raw_tx),
+        Err(TransactionError::InvalidMetaContractArgs)
+    );
+}
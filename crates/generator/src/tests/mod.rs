@@ -1 +1,52 @@
+mod balance;
+mod block_metadata;
+mod deposit;
+mod fee;
 mod genesis;
+mod message;
+mod meta_contract;
+mod parallel;
+mod program_snapshots;
+mod script_args;
+mod signature;
+mod storage_usage;
+mod sudt;
+mod withdrawal_fee;
+
+use crate::{
+    account_lock_manage::AccountLockManage, backend_manage::BackendManage, Generator, RollupContext,
+};
+use gw_common::H256;
+use gw_types::packed::RollupConfig;
+
+/// Rollup context of the tests, the node-local configs are the defaults
+fn new_rollup_context(rollup_script_hash: H256, rollup_config: RollupConfig) -> RollupContext {
+    RollupContext {
+        rollup_script_hash,
+        rollup_config,
+        builtin_accounts: Default::default(),
+        script_args: Default::default(),
+        deposit_limits: Default::default(),
+        withdrawal_fee: Default::default(),
+    }
+}
+
+/// Generator of the tests, without backends and with the default locks
+fn new_generator(rollup_context: RollupContext) -> Generator {
+    new_generator_with(AccountLockManage::default(), rollup_context, 0)
+}
+
+/// Generator of the tests without backends
+fn new_generator_with(
+    account_lock_manage: AccountLockManage,
+    rollup_context: RollupContext,
+    account_creation_fee: u64,
+) -> Generator {
+    Generator::new(
+        BackendManage::from_config(Vec::new()).expect("backend manage"),
+        account_lock_manage,
+        rollup_context,
+        Default::default(),
+        account_creation_fee,
+    )
+}
@@ -0,0 +1,47 @@
+use crate::generator::split_parallel_batches;
+use gw_common::{h256_ext::H256Ext, H256};
+use gw_types::offchain::TxAccessList;
+
+fn access_list(reads: &[u32], writes: &[u32]) -> TxAccessList {
+    let keys = |ids: &[u32]| {
+        let mut keys: Vec<H256> = ids.iter().map(|id| H256::from_u32(*id)).collect();
+        keys.sort_unstable();
+        keys
+    };
+    TxAccessList {
+        reads: keys(reads),
+        writes: keys(writes),
+    }
+}
+
+#[test]
+fn test_access_list_conflicts() {
+    let a = access_list(&[1, 2], &[2]);
+    // read-read doesn't conflict
+    assert!(!a.conflicts_with(&access_list(&[1], &[3])));
+    // read-write
+    assert!(a.conflicts_with(&access_list(&[3], &[1])));
+    // write-read
+    assert!(a.conflicts_with(&access_list(&[2], &[3])));
+    assert!(access_list(&[2], &[3]).conflicts_with(&a));
+    // write-write
+    assert!(a.conflicts_with(&access_list(&[], &[2])));
+}
+
+#[test]
+fn test_split_parallel_batches() {
+    assert!(split_parallel_batches(&[]).is_empty());
+    let access_lists = vec![
+        access_list(&[1], &[1]),
+        access_list(&[2], &[2]),
+        // conflicts with the first tx
+        access_list(&[3], &[1]),
+        access_list(&[4], &[4]),
+        // conflicts with the previous tx
+        access_list(&[4], &[5]),
+    ];
+    assert_eq!(
+        split_parallel_batches(&access_lists),
+        vec![0..2, 2..4, 4..5]
+    );
+}
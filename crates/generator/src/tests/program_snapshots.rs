@@ -0,0 +1,62 @@
+use crate::{
+    backend_manage::Backend,
+    tests::{new_generator, new_rollup_context},
+    Generator,
+};
+use ckb_vm::{
+    machine::asm::{AsmCoreMachine, AsmMachine},
+    snapshot::make_snapshot,
+    DefaultMachineBuilder,
+};
+use gw_common::H256;
+use gw_config::BackendConfig;
+use gw_types::packed::RollupConfig;
+
+const VALIDATOR_SCRIPT_TYPE_HASH: [u8; 32] = [1u8; 32];
+const META_VALIDATOR_PATH: &str = "../../godwoken-scripts/c/build/meta-contract-validator";
+const META_GENERATOR_PATH: &str = "../../godwoken-scripts/c/build/meta-contract-generator";
+const SUDT_VALIDATOR_PATH: &str = "../../godwoken-scripts/c/build/sudt-validator";
+const SUDT_GENERATOR_PATH: &str = "../../godwoken-scripts/c/build/sudt-generator";
+
+fn build_generator() -> Generator {
+    let rollup_context = new_rollup_context(H256::zero(), RollupConfig::default());
+    new_generator(rollup_context)
+}
+
+fn backend_config(validator_path: &str, generator_path: &str) -> BackendConfig {
+    BackendConfig {
+        validator_path: validator_path.into(),
+        generator_path: generator_path.into(),
+        validator_script_type_hash: VALIDATOR_SCRIPT_TYPE_HASH.into(),
+    }
+}
+
+/// Memory pages of a machine right after loading the generator of the backend
+fn load_pages(generator: &Generator, backend: &Backend) -> Vec<Vec<u8>> {
+    let core_machine = AsmCoreMachine::new_with_max_cycles(u64::max_value());
+    let mut machine = AsmMachine::new(DefaultMachineBuilder::new(core_machine).build(), None);
+    generator.load_generator(&mut machine, backend).unwrap();
+    make_snapshot(&mut machine.machine).unwrap().pages
+}
+
+#[test]
+fn test_stale_program_snapshot() {
+    let generator = build_generator();
+    let sudt_config = backend_config(SUDT_VALIDATOR_PATH, SUDT_GENERATOR_PATH);
+    generator.register_backend(sudt_config.clone()).unwrap();
+    let stale_backend = Backend::from_config(&sudt_config).unwrap();
+    let sudt_pages = load_pages(&generator, &stale_backend);
+
+    // replace the generator of the validator script type hash, while a tx which is
+    // still running the replaced one inserts its snapshot after the cache is cleared
+    let meta_config = backend_config(META_VALIDATOR_PATH, META_GENERATOR_PATH);
+    generator.register_backend(meta_config.clone()).unwrap();
+    assert_eq!(load_pages(&generator, &stale_backend), sudt_pages);
+
+    let backend = Backend::from_config(&meta_config).unwrap();
+    let meta_pages = load_pages(&build_generator(), &backend);
+    assert_ne!(meta_pages, sudt_pages);
+    // loaded from the binary, then restored from its own snapshot
+    assert_eq!(load_pages(&generator, &backend), meta_pages);
+    assert_eq!(load_pages(&generator, &backend), meta_pages);
+}
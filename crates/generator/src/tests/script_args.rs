@@ -0,0 +1,119 @@
+use crate::{
+    dummy_state::DummyState,
+    error::{AccountError, TransactionError},
+    script_args::{ScriptArgsRegistry, ScriptArgsRule},
+    tests::{new_generator, new_rollup_context},
+    traits::StateExt,
+    RollupContext,
+};
+use gw_common::state::State;
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    offchain::RunResult,
+    packed::{DepositionRequest, RollupConfig, Script},
+    prelude::*,
+};
+
+const ETH_CODE_HASH: [u8; 32] = [1u8; 32];
+
+fn build_script(args: Vec<u8>) -> Script {
+    Script::new_builder()
+        .code_hash(ETH_CODE_HASH.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(args).pack())
+        .build()
+}
+
+/// Args of `len` bytes after the rollup prefix
+fn with_prefix(len: usize) -> Vec<u8> {
+    let mut args = vec![42u8; 32];
+    args.extend(vec![0u8; len]);
+    args
+}
+
+fn build_rollup_context() -> RollupContext {
+    let mut script_args = ScriptArgsRegistry::default();
+    script_args.register(
+        ETH_CODE_HASH.into(),
+        ScriptArgsRule {
+            rollup_prefix: true,
+            min_len: 20,
+            max_len: 20,
+        },
+    );
+    RollupContext {
+        script_args,
+        ..new_rollup_context([42u8; 32].into(), RollupConfig::default())
+    }
+}
+
+#[test]
+fn test_verify_script_args() {
+    let ctx = build_rollup_context();
+    let verify = |args: Vec<u8>| {
+        ctx.script_args
+            .verify(&ctx.rollup_script_hash, &build_script(args))
+    };
+    assert_eq!(verify(with_prefix(20)), Ok(()));
+    assert_eq!(
+        verify(with_prefix(19)),
+        Err(AccountError::InvalidScriptArgs)
+    );
+    assert_eq!(
+        verify(with_prefix(21)),
+        Err(AccountError::InvalidScriptArgs)
+    );
+    assert_eq!(verify(vec![0u8; 52]), Err(AccountError::InvalidScriptArgs));
+
+    // scripts of other code hashes are not checked
+    let script = build_script(Vec::new())
+        .as_builder()
+        .code_hash([2u8; 32].pack())
+        .build();
+    assert_eq!(
+        ctx.script_args.verify(&ctx.rollup_script_hash, &script),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_check_new_script_args() {
+    let generator = new_generator(build_rollup_context());
+    let mut run_result = RunResult::default();
+    let script = build_script(with_prefix(20));
+    run_result
+        .new_scripts
+        .insert(script.hash().into(), script.as_slice().to_vec());
+    assert_eq!(generator.check_new_script_args(&run_result), Ok(()));
+
+    let script = build_script(with_prefix(19));
+    run_result
+        .new_scripts
+        .insert(script.hash().into(), script.as_slice().to_vec());
+    assert_eq!(
+        generator.check_new_script_args(&run_result),
+        Err(TransactionError::InvalidScript {
+            script_hash: script.hash().into()
+        })
+    );
+}
+
+#[test]
+fn test_deposit_with_invalid_script_args_is_applied() {
+    // the registry isn't a part of the state transition, a block with the deposit is valid
+    let ctx = build_rollup_context();
+    let mut state = DummyState::default();
+    let request = DepositionRequest::new_builder()
+        .script(build_script(vec![42u8; 40]))
+        .capacity(100u64.pack())
+        .build();
+    state.apply_deposition_request(&ctx, &request).unwrap();
+    assert_eq!(state.get_account_count().unwrap(), 1);
+    assert_eq!(
+        state
+            .get_account_id_by_script_hash(&request.script().hash().into())
+            .unwrap(),
+        Some(0)
+    );
+}
@@ -0,0 +1,96 @@
+use crate::{
+    account_lock_manage::{secp256k1::Secp256k1Eth, AccountLockManage},
+    dummy_state::DummyState,
+    tests::{new_generator_with, new_rollup_context},
+    traits::StateExt,
+    Generator,
+};
+use gw_common::{state::State, H256};
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{L2Transaction, RawL2Transaction, RollupConfig, Script},
+    prelude::*,
+};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use sha3::{Digest, Keccak256};
+
+const ETH_LOCK_CODE_HASH: [u8; 32] = [3u8; 32];
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize()[..]);
+    hash
+}
+
+fn build_generator(rollup_script_hash: H256) -> Generator {
+    let mut account_lock_manage = AccountLockManage::default();
+    account_lock_manage.register_lock_algorithm(ETH_LOCK_CODE_HASH.into(), Box::new(Secp256k1Eth));
+    let rollup_context = new_rollup_context(rollup_script_hash, RollupConfig::default());
+    new_generator_with(account_lock_manage, rollup_context, 0)
+}
+
+#[test]
+fn test_tx_signature_is_scoped_by_rollup() {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[42u8; 32]).expect("secret key");
+    let pubkey = PublicKey::from_secret_key(&secp, &secret_key);
+    let address = keccak256(&pubkey.serialize_uncompressed()[1..])[12..].to_vec();
+
+    let mut state = DummyState::default();
+    let sender_script = Script::new_builder()
+        .code_hash(ETH_LOCK_CODE_HASH.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(address).pack())
+        .build();
+    let receiver_script = Script::new_builder()
+        .code_hash([4u8; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .build();
+    let sender_id = state
+        .create_account_from_script(sender_script.clone())
+        .unwrap();
+    let receiver_id = state
+        .create_account_from_script(receiver_script.clone())
+        .unwrap();
+    assert_eq!(state.get_nonce(sender_id).unwrap(), 0);
+
+    // sign a tx for the testnet rollup
+    let testnet_rollup = H256::from([1u8; 32]);
+    let raw_tx = RawL2Transaction::new_builder()
+        .from_id(sender_id.pack())
+        .to_id(receiver_id.pack())
+        .build();
+    let message = raw_tx.calc_message(
+        &testnet_rollup,
+        &sender_script.hash().into(),
+        &receiver_script.hash().into(),
+    );
+    let signing_message = {
+        let mut data = b"\x19Ethereum Signed Message:\n32".to_vec();
+        data.extend_from_slice(message.as_slice());
+        keccak256(&data)
+    };
+    let msg = Message::from_slice(&signing_message).expect("message");
+    let (recid, data) = secp.sign_recoverable(&msg, &secret_key).serialize_compact();
+    let mut signature = [0u8; 65];
+    signature[..64].copy_from_slice(&data);
+    signature[64] = recid.to_i32() as u8;
+    let tx = L2Transaction::new_builder()
+        .raw(raw_tx)
+        .signature(signature.pack())
+        .build();
+
+    let testnet = build_generator(testnet_rollup);
+    testnet
+        .check_transaction_signature(&state, &tx)
+        .expect("valid on testnet");
+
+    // can't be replayed on mainnet
+    let mainnet = build_generator(H256::from([2u8; 32]));
+    mainnet
+        .check_transaction_signature(&state, &tx)
+        .expect_err("replay on mainnet");
+}
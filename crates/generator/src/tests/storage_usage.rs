@@ -0,0 +1,78 @@
+use crate::{dummy_state::DummyState, storage_usage::storage_delta};
+use gw_common::{
+    h256_ext::H256Ext,
+    state::{build_account_field_key, State, GW_ACCOUNT_NONCE},
+    H256,
+};
+use gw_types::{
+    offchain::{RunResult, StorageDelta, StorageUsage},
+    packed::RawL2Transaction,
+    prelude::*,
+};
+
+#[test]
+fn test_storage_delta() {
+    let mut state = DummyState::default();
+    let sender_id = 2u32;
+    let (freed, new, updated) = (H256::from_u32(1), H256::from_u32(2), H256::from_u32(3));
+    state.update_raw(freed, H256::from_u32(1)).unwrap();
+    state.update_raw(updated, H256::from_u32(1)).unwrap();
+    let stored_data_hash = H256::from_u32(100);
+    state.store_data_hash(stored_data_hash).unwrap();
+
+    let raw_tx = RawL2Transaction::new_builder()
+        .from_id(sender_id.pack())
+        .build();
+    let mut run_result = RunResult::default();
+    run_result.write_values.insert(freed, H256::zero());
+    run_result.write_values.insert(new, H256::from_u32(1));
+    run_result.write_values.insert(updated, H256::from_u32(2));
+    // the nonce of the sender isn't owned by the called account
+    run_result.write_values.insert(
+        build_account_field_key(sender_id, GW_ACCOUNT_NONCE),
+        H256::from_u32(1),
+    );
+    run_result
+        .write_data
+        .insert(stored_data_hash, vec![0u8; 5]);
+    run_result
+        .write_data
+        .insert(H256::from_u32(101), vec![0u8; 10]);
+
+    let delta = storage_delta(&state, &raw_tx, &run_result).unwrap();
+    assert_eq!(
+        delta,
+        StorageDelta {
+            slots: 0,
+            data_bytes: 10
+        }
+    );
+
+    run_result.write_values.remove(&freed);
+    let delta = storage_delta(&state, &raw_tx, &run_result).unwrap();
+    assert_eq!(delta.slots, 1);
+
+    // usage never goes below zero
+    let usage = StorageUsage {
+        slots: 1,
+        data_bytes: 10,
+    };
+    assert_eq!(
+        usage.apply(delta),
+        StorageUsage {
+            slots: 2,
+            data_bytes: 20
+        }
+    );
+    assert_eq!(
+        usage.apply(StorageDelta {
+            slots: -2,
+            data_bytes: 0
+        }),
+        StorageUsage {
+            slots: 0,
+            data_bytes: 10
+        }
+    );
+    assert_eq!(usage.apply(delta).apply(delta.revert()), usage);
+}
@@ -0,0 +1,34 @@
+use crate::sudt::{extract_sudt_transfers, SUDTTransferRecord};
+use gw_types::{
+    packed::{SUDTArgs, SUDTQuery, SUDTTransfer},
+    prelude::*,
+};
+
+#[test]
+fn test_extract_query_and_transfer() {
+    // a query doesn't move tokens
+    let args = SUDTArgs::new_builder()
+        .set(SUDTQuery::new_builder().account_id(1u32.pack()).build())
+        .build();
+    let transfers = extract_sudt_transfers(2, args.as_slice()).unwrap();
+    assert!(transfers.is_empty());
+
+    let args = SUDTArgs::new_builder()
+        .set(
+            SUDTTransfer::new_builder()
+                .to(1u32.pack())
+                .amount(30u128.pack())
+                .fee(5u128.pack())
+                .build(),
+        )
+        .build();
+    let transfers = extract_sudt_transfers(2, args.as_slice()).unwrap();
+    assert_eq!(
+        transfers,
+        vec![SUDTTransferRecord {
+            from_id: 2,
+            to_id: 1,
+            amount: 30,
+        }]
+    );
+}
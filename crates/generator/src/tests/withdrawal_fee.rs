@@ -0,0 +1,210 @@
+use crate::{
+    dummy_state::DummyState,
+    error::WithdrawalError,
+    sudt::build_l2_sudt_script,
+    tests::{new_generator, new_rollup_context},
+    traits::StateExt,
+    Error, Generator, RollupContext,
+};
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State, CKB_SUDT_SCRIPT_ARGS, H256};
+use gw_config::{SUDTWithdrawalFeeConfig, WithdrawalFeeConfig};
+use gw_types::{
+    core::ScriptHashType,
+    packed::{RawWithdrawalRequest, RollupConfig, Script, WithdrawalRequest},
+    prelude::*,
+};
+
+const CKB_FEE: u64 = 1_00000000;
+const SUDT_FEE: u64 = 10;
+const SUDT_SCRIPT_HASH: [u8; 32] = [2u8; 32];
+
+fn build_generator() -> Generator {
+    let rollup_context = RollupContext {
+        withdrawal_fee: WithdrawalFeeConfig {
+            ckb_fee: CKB_FEE,
+            sudt_fees: vec![SUDTWithdrawalFeeConfig {
+                sudt_script_hash: SUDT_SCRIPT_HASH.into(),
+                fee: SUDT_FEE,
+            }],
+        },
+        ..new_rollup_context(H256::zero(), RollupConfig::default())
+    };
+    new_generator(rollup_context)
+}
+
+fn build_withdrawal(
+    account_script_hash: [u8; 32],
+    capacity: u64,
+    sudt_script_hash: [u8; 32],
+    amount: u128,
+    (fee, sudt_fee): (u64, u128),
+) -> WithdrawalRequest {
+    let raw = RawWithdrawalRequest::new_builder()
+        .account_script_hash(account_script_hash.pack())
+        .capacity(capacity.pack())
+        .sudt_script_hash(sudt_script_hash.pack())
+        .amount(amount.pack())
+        .fee(fee.pack())
+        .sudt_fee(sudt_fee.pack())
+        .build();
+    WithdrawalRequest::new_builder().raw(raw).build()
+}
+
+#[test]
+fn test_check_withdrawal_fee() {
+    let generator = build_generator();
+    let ctx = generator.rollup_context();
+    assert_eq!(
+        ctx.min_withdrawal_fee(&CKB_SUDT_SCRIPT_ARGS.into()),
+        (CKB_FEE, 0)
+    );
+    assert_eq!(
+        ctx.min_withdrawal_fee(&SUDT_SCRIPT_HASH.into()),
+        (CKB_FEE, SUDT_FEE as u128)
+    );
+    assert_eq!(ctx.min_withdrawal_fee(&[3u8; 32].into()), (CKB_FEE, 0));
+
+    let check = |sudt_script_hash, fees| {
+        let withdrawal = build_withdrawal([1u8; 32], 500_00000000, sudt_script_hash, 1, fees);
+        ctx.check_withdrawal_fee(&withdrawal)
+    };
+    assert_eq!(
+        check(CKB_SUDT_SCRIPT_ARGS, (CKB_FEE - 1, 0)),
+        Err(WithdrawalError::InsufficientFee {
+            min_fee: CKB_FEE,
+            fee: CKB_FEE - 1
+        })
+    );
+    assert_eq!(check(CKB_SUDT_SCRIPT_ARGS, (CKB_FEE, 0)), Ok(()));
+    assert_eq!(
+        check(SUDT_SCRIPT_HASH, (CKB_FEE, SUDT_FEE as u128 - 1)),
+        Err(WithdrawalError::InsufficientSUDTFee {
+            min_sudt_fee: SUDT_FEE as u128,
+            sudt_fee: SUDT_FEE as u128 - 1
+        })
+    );
+    assert_eq!(check(SUDT_SCRIPT_HASH, (CKB_FEE * 2, 20)), Ok(()));
+    assert_eq!(check([3u8; 32], (CKB_FEE, 0)), Ok(()));
+}
+
+#[test]
+fn test_withdrawal_fee() {
+    let generator = build_generator();
+    let ctx = generator.rollup_context();
+    let mut state = DummyState::default();
+    state.create_account(H256::from_u32(1)).unwrap();
+    let ckb_sudt_script = build_l2_sudt_script(ctx, &CKB_SUDT_SCRIPT_ARGS.into());
+    let ckb_sudt_id = state.create_account_from_script(ckb_sudt_script).unwrap();
+    assert_eq!(ckb_sudt_id, CKB_SUDT_ACCOUNT_ID);
+    let block_producer_id = state.create_account(H256::from_u32(2)).unwrap();
+    let user_script = Script::new_builder()
+        .code_hash([1u8; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .build();
+    let user_script_hash = user_script.hash();
+    let user_id = state.create_account_from_script(user_script).unwrap();
+    let sudt_script = build_l2_sudt_script(ctx, &SUDT_SCRIPT_HASH.into());
+    let sudt_id = state.create_account_from_script(sudt_script).unwrap();
+    state
+        .mint_sudt(ckb_sudt_id, user_id, 1000_00000000)
+        .unwrap();
+    state.mint_sudt(sudt_id, user_id, 100).unwrap();
+
+    // the fees are charged on top of the withdrawn assets
+    let fees = (2 * CKB_FEE, 20);
+    let withdrawal = build_withdrawal(
+        user_script_hash,
+        1000_00000000 - CKB_FEE,
+        CKB_SUDT_SCRIPT_ARGS,
+        0,
+        (2 * CKB_FEE, 0),
+    );
+    let err = generator
+        .verify_withdrawal_request(&state, &withdrawal)
+        .unwrap_err();
+    assert_eq!(err, Error::Withdrawal(WithdrawalError::Overdraft));
+    let withdrawal = build_withdrawal(user_script_hash, 500_00000000, SUDT_SCRIPT_HASH, 81, fees);
+    let err = generator
+        .verify_withdrawal_request(&state, &withdrawal)
+        .unwrap_err();
+    assert_eq!(err, Error::Withdrawal(WithdrawalError::Overdraft));
+
+    // the fees signed in the request are charged rather than the min fees of the node
+    let withdrawal = build_withdrawal(user_script_hash, 500_00000000, SUDT_SCRIPT_HASH, 80, fees);
+    generator
+        .verify_withdrawal_request(&state, &withdrawal)
+        .unwrap();
+    state
+        .apply_withdrawal_request(ctx, block_producer_id, &withdrawal)
+        .unwrap();
+    assert_eq!(
+        state.get_sudt_balance(ckb_sudt_id, user_id).unwrap(),
+        (1000_00000000 - 500_00000000 - 2 * CKB_FEE) as u128
+    );
+    assert_eq!(state.get_sudt_balance(sudt_id, user_id).unwrap(), 0);
+    assert_eq!(
+        state
+            .get_sudt_balance(ckb_sudt_id, block_producer_id)
+            .unwrap(),
+        2 * CKB_FEE as u128
+    );
+    assert_eq!(
+        state.get_sudt_balance(sudt_id, block_producer_id).unwrap(),
+        20
+    );
+    // only the withdrawn assets are burnt
+    assert_eq!(state.get_sudt_total_supply(sudt_id).unwrap(), 20);
+
+    // a fee under the min of the node is still valid in a block
+    let withdrawal = build_withdrawal(
+        user_script_hash,
+        400_00000000,
+        CKB_SUDT_SCRIPT_ARGS,
+        0,
+        (0, 0),
+    );
+    state
+        .apply_withdrawal_request(ctx, block_producer_id, &withdrawal)
+        .unwrap();
+    assert_eq!(
+        state.get_sudt_balance(ckb_sudt_id, user_id).unwrap(),
+        (100_00000000 - 2 * CKB_FEE) as u128
+    );
+}
+
+#[test]
+fn test_withdraw_faked_sudt_fee() {
+    let generator = build_generator();
+    let ctx = generator.rollup_context();
+    let mut state = DummyState::default();
+    state.create_account(H256::from_u32(1)).unwrap();
+    let ckb_sudt_script = build_l2_sudt_script(ctx, &CKB_SUDT_SCRIPT_ARGS.into());
+    let ckb_sudt_id = state.create_account_from_script(ckb_sudt_script).unwrap();
+    let block_producer_id = state.create_account(H256::from_u32(2)).unwrap();
+    let user_script = Script::new_builder()
+        .code_hash([1u8; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .build();
+    let user_script_hash = user_script.hash();
+    let user_id = state.create_account_from_script(user_script).unwrap();
+    state
+        .mint_sudt(ckb_sudt_id, user_id, 1000_00000000)
+        .unwrap();
+
+    // a CKB withdrawal can't pay a sUDT fee
+    let withdrawal = build_withdrawal(
+        user_script_hash,
+        500_00000000,
+        CKB_SUDT_SCRIPT_ARGS,
+        0,
+        (CKB_FEE, 1),
+    );
+    let err = generator
+        .verify_withdrawal_request(&state, &withdrawal)
+        .unwrap_err();
+    assert_eq!(err, Error::Withdrawal(WithdrawalError::WithdrawFakedCKB));
+    let err = state
+        .apply_withdrawal_request(ctx, block_producer_id, &withdrawal)
+        .unwrap_err();
+    assert_eq!(err, Error::Withdrawal(WithdrawalError::WithdrawFakedCKB));
+}
@@ -3,7 +3,10 @@ use crate::{
     error::{AccountError, DepositionError, Error, WithdrawalError},
     RollupContext,
 };
-use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State, CKB_SUDT_SCRIPT_ARGS};
+use gw_common::{
+    state::{build_l2_to_l1_message_hash, State, GW_LOG_L2_TO_L1_MESSAGE},
+    CKB_SUDT_SCRIPT_ARGS, H256,
+};
 use gw_traits::CodeStore;
 use gw_types::{
     bytes::Bytes,
@@ -22,9 +25,11 @@ pub trait StateExt {
         deposition_request: &DepositionRequest,
     ) -> Result<(), Error>;
 
+    /// Burn the withdrawn assets and charge the withdrawal fee to the block producer
     fn apply_withdrawal_request(
         &mut self,
         ctx: &RollupContext,
+        block_producer_id: u32,
         withdrawal_request: &WithdrawalRequest,
     ) -> Result<(), Error>;
 
@@ -42,10 +47,11 @@ pub trait StateExt {
     fn apply_withdrawal_requests(
         &mut self,
         ctx: &RollupContext,
+        block_producer_id: u32,
         withdrawal_requests: &[WithdrawalRequest],
     ) -> Result<(), Error> {
         for request in withdrawal_requests {
-            self.apply_withdrawal_request(ctx, request)?;
+            self.apply_withdrawal_request(ctx, block_producer_id, request)?;
         }
 
         Ok(())
@@ -79,6 +85,14 @@ impl<S: State + CodeStore> StateExt for S {
             self.store_data_hash(*data_hash)?;
             self.insert_data(*data_hash, Bytes::from(data.clone()));
         }
+        for log in &run_result.logs {
+            let service_flag: u8 = log.service_flag().into();
+            if service_flag == GW_LOG_L2_TO_L1_MESSAGE {
+                let from_id: u32 = log.account_id().unpack();
+                let payload: Bytes = log.data().unpack();
+                self.store_l2_to_l1_message_hash(build_l2_to_l1_message_hash(from_id, &payload))?;
+            }
+        }
         Ok(())
     }
 
@@ -97,8 +111,9 @@ impl<S: State + CodeStore> StateExt for S {
             }
         };
         // mint CKB
+        let ckb_sudt_id = ctx.builtin_accounts.ckb_sudt_id;
         let capacity: u64 = request.capacity().unpack();
-        self.mint_sudt(CKB_SUDT_ACCOUNT_ID, id, capacity.into())?;
+        self.mint_sudt(ckb_sudt_id, id, capacity.into())?;
         let sudt_script_hash = request.sudt_script_hash().unpack();
         let amount = request.amount().unpack();
         if sudt_script_hash != CKB_SUDT_SCRIPT_ARGS.into() {
@@ -113,7 +128,7 @@ impl<S: State + CodeStore> StateExt for S {
                 }
             };
             // prevent fake CKB SUDT, the caller should filter these invalid depositions
-            if sudt_id == CKB_SUDT_ACCOUNT_ID {
+            if sudt_id == ckb_sudt_id {
                 return Err(AccountError::InvalidSUDTOperation.into());
             }
             // mint SUDT
@@ -128,29 +143,41 @@ impl<S: State + CodeStore> StateExt for S {
     fn apply_withdrawal_request(
         &mut self,
         ctx: &RollupContext,
+        block_producer_id: u32,
         request: &WithdrawalRequest,
     ) -> Result<(), Error> {
         let raw = request.raw();
         let account_script_hash: [u8; 32] = raw.account_script_hash().unpack();
-        let l2_sudt_script_hash: [u8; 32] =
-            build_l2_sudt_script(&ctx, &raw.sudt_script_hash().unpack()).hash();
+        let sudt_script_hash: H256 = raw.sudt_script_hash().unpack();
+        let l2_sudt_script_hash: [u8; 32] = build_l2_sudt_script(&ctx, &sudt_script_hash).hash();
         let amount: u128 = raw.amount().unpack();
+        // the fees are signed by the user in the request
+        let ckb_fee: u64 = raw.fee().unpack();
+        let sudt_fee: u128 = raw.sudt_fee().unpack();
         // find user account
         let id = self
             .get_account_id_by_script_hash(&account_script_hash.into())?
             .ok_or(AccountError::UnknownAccount)?; // find Simple UDT account
         let capacity: u64 = raw.capacity().unpack();
         // burn CKB
-        self.burn_sudt(CKB_SUDT_ACCOUNT_ID, id, capacity.into())?;
+        let ckb_sudt_id = ctx.builtin_accounts.ckb_sudt_id;
+        self.burn_sudt(ckb_sudt_id, id, capacity.into())?;
         let sudt_id = self
             .get_account_id_by_script_hash(&l2_sudt_script_hash.into())?
             .ok_or(AccountError::UnknownSUDT)?;
-        if sudt_id != CKB_SUDT_ACCOUNT_ID {
+        if sudt_id != ckb_sudt_id {
             // burn sudt
             self.burn_sudt(sudt_id, id, amount)?;
-        } else if amount != 0 {
+        } else if amount != 0 || sudt_fee != 0 {
             return Err(WithdrawalError::WithdrawFakedCKB.into());
         }
+        // charge fee
+        if ckb_fee != 0 {
+            self.transfer_sudt(ckb_sudt_id, id, block_producer_id, ckb_fee.into())?;
+        }
+        if sudt_fee != 0 {
+            self.transfer_sudt(sudt_id, id, block_producer_id, sudt_fee)?;
+        }
         // increase nonce
         let nonce = self.get_nonce(id)?;
         let new_nonce = nonce.checked_add(1).ok_or(AccountError::NonceOverflow)?;
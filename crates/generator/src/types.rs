@@ -1,11 +1,94 @@
-use gw_common::H256;
-use gw_types::packed::{ChallengeTarget, ChallengeWitness, RollupConfig};
+use crate::{
+    error::{DepositionError, WithdrawalError},
+    script_args::ScriptArgsRegistry,
+};
+use gw_common::{builtins::BuiltinAccounts, CKB_SUDT_SCRIPT_ARGS, H256};
+use gw_config::{DepositLimitsConfig, WithdrawalFeeConfig};
+use gw_types::{
+    packed::{
+        ChallengeTarget, ChallengeWitness, DepositionRequest, RollupConfig, WithdrawalRequest,
+    },
+    prelude::*,
+};
 use std::fmt::{self, Display};
 
 #[derive(Clone)]
 pub struct RollupContext {
     pub rollup_script_hash: H256,
     pub rollup_config: RollupConfig,
+    pub builtin_accounts: BuiltinAccounts,
+    pub script_args: ScriptArgsRegistry,
+    pub deposit_limits: DepositLimitsConfig,
+    pub withdrawal_fee: WithdrawalFeeConfig,
+}
+
+impl RollupContext {
+    /// Check the deposit is not dust, only the block producer checks it when collecting
+    /// deposits. It isn't a part of the state transition
+    pub fn verify_deposit_amounts(
+        &self,
+        request: &DepositionRequest,
+    ) -> Result<(), DepositionError> {
+        let limits = &self.deposit_limits;
+        let capacity: u64 = request.capacity().unpack();
+        if capacity < limits.min_capacity {
+            return Err(DepositionError::InsufficientCapacity {
+                min_capacity: limits.min_capacity,
+                capacity,
+            });
+        }
+        let sudt_script_hash: [u8; 32] = request.sudt_script_hash().unpack();
+        let amount: u128 = request.amount().unpack();
+        let min_amount = u128::from(limits.min_sudt_amount);
+        if sudt_script_hash != CKB_SUDT_SCRIPT_ARGS && amount < min_amount {
+            return Err(DepositionError::InsufficientSUDTAmount { min_amount, amount });
+        }
+        Ok(())
+    }
+
+    /// Min fee (CKB, sUDT) of a withdrawal of the layer1 sUDT accepted by the mem-pool,
+    /// the min sUDT fee of a CKB withdrawal is 0
+    pub fn min_withdrawal_fee(&self, sudt_script_hash: &H256) -> (u64, u128) {
+        let fee = &self.withdrawal_fee;
+        if sudt_script_hash.as_slice() == CKB_SUDT_SCRIPT_ARGS {
+            return (fee.ckb_fee, 0);
+        }
+        let sudt_fee = fee
+            .sudt_fees
+            .iter()
+            .find(|sudt_fee| sudt_fee.sudt_script_hash.as_bytes() == sudt_script_hash.as_slice())
+            .map(|sudt_fee| u128::from(sudt_fee.fee))
+            .unwrap_or(0);
+        (fee.ckb_fee, sudt_fee)
+    }
+
+    /// Check the fees signed in the withdrawal request are not less than the min fees,
+    /// only the mem-pool checks it. It isn't a part of the state transition
+    pub fn check_withdrawal_fee(&self, request: &WithdrawalRequest) -> Result<(), WithdrawalError> {
+        let raw = request.raw();
+        let sudt_script_hash: H256 = raw.sudt_script_hash().unpack();
+        let (min_fee, min_sudt_fee) = self.min_withdrawal_fee(&sudt_script_hash);
+        let fee: u64 = raw.fee().unpack();
+        if fee < min_fee {
+            return Err(WithdrawalError::InsufficientFee { min_fee, fee });
+        }
+        let sudt_fee: u128 = raw.sudt_fee().unpack();
+        if sudt_fee < min_sudt_fee {
+            return Err(WithdrawalError::InsufficientSUDTFee {
+                min_sudt_fee,
+                sudt_fee,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Fee settled for a layer2 tx, in CKB
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct TxFee {
+    pub used_cycles: u64,
+    pub paid_fee: u128,
+    pub refunded_fee: u128,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
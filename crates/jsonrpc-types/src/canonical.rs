@@ -0,0 +1,65 @@
+//! Canonical serialization of user-facing payloads
+//!
+//! The same JSON payload can be written in many ways (key order, hex case, omitted defaults),
+//! so signing messages are never computed from JSON text. A payload is converted to its
+//! molecule serialization first, off-chain signers and the node get identical digests.
+
+use crate::godwoken::{RawL2Transaction, RawWithdrawalRequest};
+use ckb_fixed_hash::H256;
+use ckb_jsonrpc_types::JsonBytes;
+use gw_types::{packed, prelude::*};
+
+fn to_h256(hash: &H256) -> gw_common::H256 {
+    let hash: [u8; 32] = hash.clone().into();
+    hash.into()
+}
+
+fn to_json_h256(hash: gw_common::H256) -> H256 {
+    let hash: [u8; 32] = hash.into();
+    hash.into()
+}
+
+pub trait Canonical {
+    /// Molecule serialization of the payload
+    fn canonical_bytes(&self) -> JsonBytes;
+}
+
+impl Canonical for RawL2Transaction {
+    fn canonical_bytes(&self) -> JsonBytes {
+        let raw_tx: packed::RawL2Transaction = self.clone().into();
+        JsonBytes::from_bytes(raw_tx.as_bytes())
+    }
+}
+
+impl Canonical for RawWithdrawalRequest {
+    fn canonical_bytes(&self) -> JsonBytes {
+        let raw_request: packed::RawWithdrawalRequest = self.clone().into();
+        JsonBytes::from_bytes(raw_request.as_bytes())
+    }
+}
+
+/// Signing message of a layer2 tx,
+/// the sender and receiver script hashes are the script hashes of `from_id` and `to_id`
+pub fn raw_l2_transaction_signing_message(
+    raw_tx: &RawL2Transaction,
+    rollup_type_hash: &H256,
+    sender_script_hash: &H256,
+    receiver_script_hash: &H256,
+) -> H256 {
+    let raw_tx: packed::RawL2Transaction = raw_tx.clone().into();
+    let message = raw_tx.calc_message(
+        &to_h256(rollup_type_hash),
+        &to_h256(sender_script_hash),
+        &to_h256(receiver_script_hash),
+    );
+    to_json_h256(message)
+}
+
+/// Signing message of a withdrawal request
+pub fn raw_withdrawal_request_signing_message(
+    raw_request: &RawWithdrawalRequest,
+    rollup_type_hash: &H256,
+) -> H256 {
+    let raw_request: packed::RawWithdrawalRequest = raw_request.clone().into();
+    to_json_h256(raw_request.calc_message(&to_h256(rollup_type_hash)))
+}
@@ -7,13 +7,20 @@ use gw_types::{bytes::Bytes, offchain, packed, prelude::*};
 use serde::{Deserialize, Serialize};
 use std::convert::{TryFrom, TryInto};
 
+// unknown fields are rejected, e.g. a misspelled `gas_price` would otherwise be dropped
+// silently and the tx would be signed with a zero gas price
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
-#[serde(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct RawL2Transaction {
     pub from_id: Uint32,
     pub to_id: Uint32,
     pub nonce: Uint32,
     pub args: JsonBytes,
+    // 0 means the max gas limit of the rollup
+    #[serde(default)]
+    pub gas_limit: Uint64,
+    #[serde(default)]
+    pub gas_price: Uint128,
 }
 
 impl From<RawL2Transaction> for packed::RawL2Transaction {
@@ -23,6 +30,8 @@ impl From<RawL2Transaction> for packed::RawL2Transaction {
             to_id,
             nonce,
             args,
+            gas_limit,
+            gas_price,
         } = tx;
         let args: Bytes = args.into_bytes();
         packed::RawL2Transaction::new_builder()
@@ -30,6 +39,8 @@ impl From<RawL2Transaction> for packed::RawL2Transaction {
             .to_id(u32::from(to_id).pack())
             .nonce(u32::from(nonce).pack())
             .args(args.pack())
+            .gas_limit(u64::from(gas_limit).pack())
+            .gas_price(u128::from(gas_price).pack())
             .build()
     }
 }
@@ -39,11 +50,15 @@ impl From<packed::RawL2Transaction> for RawL2Transaction {
         let from_id: u32 = raw_l2_transaction.from_id().unpack();
         let to_id: u32 = raw_l2_transaction.to_id().unpack();
         let nonce: u32 = raw_l2_transaction.nonce().unpack();
+        let gas_limit: u64 = raw_l2_transaction.gas_limit().unpack();
+        let gas_price: u128 = raw_l2_transaction.gas_price().unpack();
         Self {
             from_id: from_id.into(),
             to_id: to_id.into(),
             nonce: nonce.into(),
             args: JsonBytes::from_bytes(raw_l2_transaction.args().unpack()),
+            gas_limit: gas_limit.into(),
+            gas_price: gas_price.into(),
         }
     }
 }
@@ -85,7 +100,7 @@ pub struct L2TransactionView {
 
 impl From<packed::L2Transaction> for L2TransactionView {
     fn from(l2_tx: packed::L2Transaction) -> L2TransactionView {
-        let hash = H256::from(l2_tx.raw().hash());
+        let hash = H256::from(l2_tx.hash());
         let inner = L2Transaction::from(l2_tx);
         L2TransactionView { inner, hash }
     }
@@ -135,6 +150,16 @@ pub struct TxReceipt {
     pub post_state: AccountMerkleState,
     pub read_data_hashes: Vec<H256>,
     pub logs: Vec<LogItem>,
+    #[serde(default)]
+    pub used_cycles: Uint64,
+    // CKB paid to the block producer
+    #[serde(default)]
+    pub paid_fee: Uint128,
+    // CKB refunded to the sender for the unused gas
+    #[serde(default)]
+    pub refunded_fee: Uint128,
+    #[serde(default)]
+    pub return_data: JsonBytes,
 }
 
 impl From<TxReceipt> for packed::TxReceipt {
@@ -144,6 +169,10 @@ impl From<TxReceipt> for packed::TxReceipt {
             post_state,
             read_data_hashes,
             logs,
+            used_cycles,
+            paid_fee,
+            refunded_fee,
+            return_data,
         } = json;
         let tx_witness_hash: [u8; 32] = tx_witness_hash.into();
         let read_data_hashes: Vec<_> = read_data_hashes
@@ -159,6 +188,10 @@ impl From<TxReceipt> for packed::TxReceipt {
             .post_state(post_state.into())
             .read_data_hashes(read_data_hashes.pack())
             .logs(logs.pack())
+            .used_cycles(u64::from(used_cycles).pack())
+            .paid_fee(u128::from(paid_fee).pack())
+            .refunded_fee(u128::from(refunded_fee).pack())
+            .return_data(return_data.into_bytes().pack())
             .build()
     }
 }
@@ -176,11 +209,19 @@ impl From<packed::TxReceipt> for TxReceipt {
             })
             .collect();
         let logs: Vec<LogItem> = data.logs().into_iter().map(|item| item.into()).collect();
+        let used_cycles: u64 = data.used_cycles().unpack();
+        let paid_fee: u128 = data.paid_fee().unpack();
+        let refunded_fee: u128 = data.refunded_fee().unpack();
+        let return_data: Bytes = data.return_data().unpack();
         TxReceipt {
             tx_witness_hash: tx_witness_hash.into(),
             post_state,
             read_data_hashes,
             logs,
+            used_cycles: used_cycles.into(),
+            paid_fee: paid_fee.into(),
+            refunded_fee: refunded_fee.into(),
+            return_data: JsonBytes::from_bytes(return_data),
         }
     }
 }
@@ -478,8 +519,10 @@ pub struct L2BlockView {
     pub kv_state_proof: JsonBytes,
     pub transactions: Vec<L2TransactionView>,
     pub block_proof: JsonBytes,
-    pub withdrawal_requests: Vec<WithdrawalRequest>,
+    pub withdrawal_requests: Vec<WithdrawalRequestView>,
     pub hash: H256,
+    /// None if the block isn't committed on L1
+    pub l1_commitment: Option<L1Commitment>,
 }
 
 impl From<packed::L2Block> for L2BlockView {
@@ -500,16 +543,135 @@ impl From<packed::L2Block> for L2BlockView {
                 .into_iter()
                 .map(|w| w.into())
                 .collect(),
+            l1_commitment: None,
+        }
+    }
+}
+
+/// A L2 block without the transactions, withdrawals and proofs
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct L2BlockHeaderView {
+    pub raw: RawL2Block,
+    pub hash: H256,
+    /// None if the block isn't committed on L1
+    pub l1_commitment: Option<L1Commitment>,
+}
+
+impl From<packed::L2Block> for L2BlockHeaderView {
+    fn from(l2_block: packed::L2Block) -> L2BlockHeaderView {
+        let raw = l2_block.raw();
+        Self {
+            hash: H256::from(raw.hash()),
+            raw: raw.into(),
+            l1_commitment: None,
+        }
+    }
+}
+
+/// Block headers or full blocks
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(untagged)]
+pub enum L2BlocksView {
+    Headers(Vec<L2BlockHeaderView>),
+    Blocks(Vec<L2BlockView>),
+}
+
+/// Parts of a block returned by `get_block` and `get_block_by_number`
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockVerbosity {
+    /// the header only
+    Header,
+    /// the header with the hashes of the txs and withdrawals
+    TxHashes,
+    /// the full block with the receipts of the txs
+    Full,
+}
+
+/// A L2 block with the hashes of the txs and withdrawals
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct L2BlockTxHashesView {
+    #[serde(flatten)]
+    pub header: L2BlockHeaderView,
+    pub transactions: Vec<H256>,
+    pub withdrawal_requests: Vec<H256>,
+}
+
+/// A L2 block with the receipts of the txs, in the order of the txs
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct L2BlockWithReceiptsView {
+    #[serde(flatten)]
+    pub block: L2BlockView,
+    pub receipts: Vec<TxReceipt>,
+}
+
+/// A L2 block of the requested verbosity, the full block without receipts if none
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(untagged)]
+pub enum L2BlockVerboseView {
+    // variants with more fields go first, so they are tried first when deserializing
+    Full(L2BlockWithReceiptsView),
+    Block(L2BlockView),
+    TxHashes(L2BlockTxHashesView),
+    Header(L2BlockHeaderView),
+}
+
+/// The L1 transaction which committed a L2 block
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct L1Commitment {
+    pub l1_block_number: Uint64,
+    pub l1_block_hash: H256,
+    pub l1_transaction_hash: H256,
+    /// index of the rollup cell in the outputs, None for the genesis block
+    pub output_index: Option<Uint32>,
+}
+
+/// Post state roots of a L2 block computed by the node and the L1 transaction which committed
+/// the block, verifiers compare them with the global state on L1
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct BlockStateRoot {
+    pub block_number: Uint64,
+    pub block_hash: H256,
+    pub account_root: H256,
+    pub block_root: H256,
+    pub l1_transaction_hash: Option<H256>,
+}
+
+impl L1Commitment {
+    pub fn new(committed_info: packed::L2BlockCommittedInfo, output_index: Option<u32>) -> Self {
+        let l1_block_number: u64 = committed_info.number().unpack();
+        L1Commitment {
+            l1_block_number: l1_block_number.into(),
+            l1_block_hash: committed_info.block_hash().unpack(),
+            l1_transaction_hash: committed_info.transaction_hash().unpack(),
+            output_index: output_index.map(Into::into),
         }
     }
 }
 
+// Transactions & withdrawals the producer would package next, they are not executed
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingBlockView {
+    pub number: Uint64,
+    pub parent_block_hash: H256,
+    pub transactions: Vec<L2TransactionView>,
+    pub withdrawal_requests: Vec<WithdrawalRequestView>,
+}
+
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct SubmitTransactions {
     pub tx_witness_root: H256,
     pub tx_count: Uint32,
-    // hash(account_root | account_count) before each transaction
+    // hash(account_root | account_count) before apply all txs
+    pub compacted_prev_root_hash: H256,
+    // hash(account_root | account_count) of each transaction
     pub compacted_post_root_list: Vec<H256>,
 }
 
@@ -518,6 +680,7 @@ impl From<SubmitTransactions> for packed::SubmitTransactions {
         let SubmitTransactions {
             tx_witness_root,
             tx_count,
+            compacted_prev_root_hash,
             compacted_post_root_list,
         } = json;
         let compacted_post_root_list_vec: Vec<packed::Byte32> = compacted_post_root_list
@@ -527,6 +690,7 @@ impl From<SubmitTransactions> for packed::SubmitTransactions {
         packed::SubmitTransactions::new_builder()
             .tx_witness_root(tx_witness_root.pack())
             .tx_count(u32::from(tx_count).pack())
+            .compacted_prev_root_hash(compacted_prev_root_hash.pack())
             .compacted_post_root_list(
                 packed::Byte32Vec::new_builder()
                     .set(compacted_post_root_list_vec)
@@ -542,6 +706,7 @@ impl From<packed::SubmitTransactions> for SubmitTransactions {
         Self {
             tx_witness_root: submit_transactions.tx_witness_root().unpack(),
             tx_count: tx_count.into(),
+            compacted_prev_root_hash: submit_transactions.compacted_prev_root_hash().unpack(),
             compacted_post_root_list: submit_transactions
                 .compacted_post_root_list()
                 .into_iter()
@@ -664,28 +829,35 @@ impl From<packed::KVPair> for KVPair {
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct GlobalState {
+    pub rollup_config_hash: H256,
     pub account: AccountMerkleState,
     pub block: BlockMerkleState,
     pub reverted_block_root: H256,
+    pub tip_block_hash: H256,
     pub last_finalized_block_number: Uint64,
+    // The actual type is `u8`, 0: running, 1: halting
     pub status: Uint32,
 }
 
 impl From<GlobalState> for packed::GlobalState {
     fn from(json: GlobalState) -> packed::GlobalState {
         let GlobalState {
+            rollup_config_hash,
             account,
             block,
             reverted_block_root,
+            tip_block_hash,
             last_finalized_block_number,
             status,
         } = json;
         let last_finalized_block_number: u64 = last_finalized_block_number.into();
         let status: u32 = status.into();
         packed::GlobalState::new_builder()
+            .rollup_config_hash(rollup_config_hash.pack())
             .account(account.into())
             .block(block.into())
             .reverted_block_root(reverted_block_root.pack())
+            .tip_block_hash(tip_block_hash.pack())
             .last_finalized_block_number(last_finalized_block_number.pack())
             .status((status as u8).into())
             .build()
@@ -696,9 +868,11 @@ impl From<packed::GlobalState> for GlobalState {
         let last_finalized_block_number: u64 = global_state.last_finalized_block_number().unpack();
         let status: u8 = global_state.status().into();
         Self {
+            rollup_config_hash: global_state.rollup_config_hash().unpack(),
             account: global_state.account().into(),
             block: global_state.block().into(),
             reverted_block_root: global_state.reverted_block_root().unpack(),
+            tip_block_hash: global_state.tip_block_hash().unpack(),
             last_finalized_block_number: last_finalized_block_number.into(),
             status: (status as u32).into(),
         }
@@ -712,6 +886,9 @@ pub struct DepositionRequest {
     pub sudt_script_hash: H256,
     pub amount: Uint128,
     pub capacity: Uint64,
+    // serialized L2Transaction
+    #[serde(default)]
+    pub call: JsonBytes,
 }
 
 impl From<DepositionRequest> for packed::DepositionRequest {
@@ -721,12 +898,14 @@ impl From<DepositionRequest> for packed::DepositionRequest {
             sudt_script_hash,
             amount,
             capacity,
+            call,
         } = json;
         packed::DepositionRequest::new_builder()
             .script(script.into())
             .sudt_script_hash(sudt_script_hash.pack())
             .amount(u128::from(amount).pack())
             .capacity(u64::from(capacity).pack())
+            .call(call.into_bytes().pack())
             .build()
     }
 }
@@ -740,6 +919,7 @@ impl From<packed::DepositionRequest> for DepositionRequest {
             sudt_script_hash: deposition_request.sudt_script_hash().unpack(),
             amount: amount.into(),
             capacity: capacity.into(),
+            call: JsonBytes::from_bytes(deposition_request.call().unpack()),
         }
     }
 }
@@ -770,8 +950,26 @@ impl From<packed::WithdrawalRequest> for WithdrawalRequest {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "snake_case")]
+pub struct WithdrawalRequestView {
+    #[serde(flatten)]
+    pub inner: WithdrawalRequest,
+    pub hash: H256,
+}
+
+impl From<packed::WithdrawalRequest> for WithdrawalRequestView {
+    fn from(withdrawal: packed::WithdrawalRequest) -> WithdrawalRequestView {
+        let hash = H256::from(withdrawal.hash());
+        let inner = WithdrawalRequest::from(withdrawal);
+        WithdrawalRequestView { inner, hash }
+    }
+}
+
+// unknown fields are rejected, a wallet must not show a withdrawal field which the signed
+// request doesn't contain
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct RawWithdrawalRequest {
     pub nonce: Uint32,
     pub capacity: Uint64,
@@ -785,6 +983,11 @@ pub struct RawWithdrawalRequest {
     pub owner_lock_hash: H256,
     // layer1 lock to receive the payment, must exists on the chain
     pub payment_lock_hash: H256,
+    // paid to the block producer on top of the withdrawn assets
+    #[serde(default)]
+    pub fee: Uint64,
+    #[serde(default)]
+    pub sudt_fee: Uint128,
 }
 
 impl From<RawWithdrawalRequest> for packed::RawWithdrawalRequest {
@@ -799,6 +1002,8 @@ impl From<RawWithdrawalRequest> for packed::RawWithdrawalRequest {
             account_script_hash,
             owner_lock_hash,
             payment_lock_hash,
+            fee,
+            sudt_fee,
         } = json;
         packed::RawWithdrawalRequest::new_builder()
             .nonce(u32::from(nonce).pack())
@@ -810,6 +1015,8 @@ impl From<RawWithdrawalRequest> for packed::RawWithdrawalRequest {
             .account_script_hash(account_script_hash.pack())
             .owner_lock_hash(owner_lock_hash.pack())
             .payment_lock_hash(payment_lock_hash.pack())
+            .fee(u64::from(fee).pack())
+            .sudt_fee(u128::from(sudt_fee).pack())
             .build()
     }
 }
@@ -821,6 +1028,8 @@ impl From<packed::RawWithdrawalRequest> for RawWithdrawalRequest {
         let amount: u128 = raw_withdrawal_request.amount().unpack();
         let sell_capacity: u64 = raw_withdrawal_request.sell_capacity().unpack();
         let sell_amount: u128 = raw_withdrawal_request.sell_amount().unpack();
+        let fee: u64 = raw_withdrawal_request.fee().unpack();
+        let sudt_fee: u128 = raw_withdrawal_request.sudt_fee().unpack();
         Self {
             nonce: nonce.into(),
             capacity: capacity.into(),
@@ -831,6 +1040,8 @@ impl From<packed::RawWithdrawalRequest> for RawWithdrawalRequest {
             account_script_hash: raw_withdrawal_request.account_script_hash().unpack(),
             owner_lock_hash: raw_withdrawal_request.owner_lock_hash().unpack(),
             payment_lock_hash: raw_withdrawal_request.payment_lock_hash().unpack(),
+            fee: fee.into(),
+            sudt_fee: sudt_fee.into(),
         }
     }
 }
@@ -858,6 +1069,17 @@ impl From<L2BlockCommittedInfo> for packed::L2BlockCommittedInfo {
     }
 }
 
+impl From<packed::L2BlockCommittedInfo> for L2BlockCommittedInfo {
+    fn from(data: packed::L2BlockCommittedInfo) -> L2BlockCommittedInfo {
+        let number: u64 = data.number().unpack();
+        Self {
+            number: number.into(),
+            block_hash: data.block_hash().unpack(),
+            transaction_hash: data.transaction_hash().unpack(),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct RollupConfig {
@@ -943,16 +1165,535 @@ pub struct RunResult {
     pub return_data: JsonBytes,
     // log data
     pub logs: Vec<LogItem>,
+    pub used_cycles: Uint64,
 }
 
 impl From<offchain::RunResult> for RunResult {
     fn from(data: offchain::RunResult) -> RunResult {
         let offchain::RunResult {
-            return_data, logs, ..
+            return_data,
+            logs,
+            used_cycles,
+            ..
         } = data;
         RunResult {
             return_data: JsonBytes::from_vec(return_data),
             logs: logs.into_iter().map(Into::into).collect(),
+            used_cycles: used_cycles.into(),
+        }
+    }
+}
+
+/// A syscall recorded by `trace_transaction`
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceEvent {
+    Load { key: H256, value: H256 },
+    Store { key: H256, value: H256 },
+    CreateAccount { account_id: Uint32, script_hash: H256 },
+    Log { log: LogItem },
+    SetReturnData { data: JsonBytes },
+    // other syscalls
+    Syscall { number: Uint64 },
+}
+
+fn to_json_h256(hash: gw_common::H256) -> H256 {
+    let hash: [u8; 32] = hash.into();
+    hash.into()
+}
+
+impl From<offchain::TraceEvent> for TraceEvent {
+    fn from(event: offchain::TraceEvent) -> TraceEvent {
+        match event {
+            offchain::TraceEvent::Load { key, value } => TraceEvent::Load {
+                key: to_json_h256(key),
+                value: to_json_h256(value),
+            },
+            offchain::TraceEvent::Store { key, value } => TraceEvent::Store {
+                key: to_json_h256(key),
+                value: to_json_h256(value),
+            },
+            offchain::TraceEvent::CreateAccount {
+                account_id,
+                script_hash,
+            } => TraceEvent::CreateAccount {
+                account_id: account_id.into(),
+                script_hash: to_json_h256(script_hash),
+            },
+            offchain::TraceEvent::Log(log) => TraceEvent::Log { log: log.into() },
+            offchain::TraceEvent::SetReturnData(data) => TraceEvent::SetReturnData {
+                data: JsonBytes::from_vec(data),
+            },
+            offchain::TraceEvent::Syscall(number) => TraceEvent::Syscall {
+                number: number.into(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct TraceStep {
+    // cycles used before the syscall
+    pub cycles: Uint64,
+    #[serde(flatten)]
+    pub event: TraceEvent,
+}
+
+impl From<offchain::TraceStep> for TraceStep {
+    fn from(step: offchain::TraceStep) -> TraceStep {
+        TraceStep {
+            cycles: step.cycles.into(),
+            event: step.event.into(),
         }
     }
 }
+
+/// Result of `trace_transaction`, the steps are recorded even if the tx fails
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct TransactionTrace {
+    pub steps: Vec<TraceStep>,
+    pub run_result: Option<RunResult>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct StoreColumnUsage {
+    pub column: Uint32,
+    pub total_sst_files_size: Uint64,
+    pub live_data_size: Uint64,
+    // estimated bytes can be reclaimed by compaction
+    pub reclaimable_size: Uint64,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct BackendInfo {
+    pub validator_script_type_hash: H256,
+    // blake2b hashes of the binaries
+    pub validator_hash: H256,
+    pub generator_hash: H256,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct NodeStatus {
+    pub tip_block_number: Uint64,
+    // L1 block synced when the node started, the catch-up starts from it
+    pub l1_start_block_number: Uint64,
+    pub l1_synced_block_number: Uint64,
+    // not set until the L1 tip is read
+    pub l1_tip_block_number: Option<Uint64>,
+    // catch-up progress, 0 to 100
+    pub sync_percent: Uint32,
+    pub sync_eta_secs: Option<Uint64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ConnectionStats {
+    pub active_connections: Uint64,
+    pub accepted_connections: Uint64,
+    // closed at once because of the connection limit
+    pub rejected_connections: Uint64,
+    // rejected because of the concurrent request limit of a connection
+    pub rejected_requests: Uint64,
+    // rejected because of the request rate limit of a client IP
+    pub rate_limited_requests: Uint64,
+    pub read_timeouts: Uint64,
+    pub write_timeouts: Uint64,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct L2ToL1Message {
+    pub block_hash: H256,
+    pub block_number: Uint64,
+    // index of the message in the block
+    pub index: Uint32,
+    pub tx_hash: H256,
+    pub from_id: Uint32,
+    pub payload: JsonBytes,
+    pub message_hash: H256,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct L2ToL1MessageProof {
+    pub message: L2ToL1Message,
+    // account SMT root of the block, which is committed in the block header
+    pub account_root: H256,
+    // compiled SMT proof of the message key, the leaf value is H256::one()
+    pub proof: JsonBytes,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SUDTBalanceKey {
+    pub account_id: Uint32,
+    pub sudt_id: Uint32,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SUDTBalance {
+    pub account_id: Uint32,
+    pub sudt_id: Uint32,
+    pub balance: Uint128,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SUDTBalancesProof {
+    pub block_hash: H256,
+    pub block_number: Uint64,
+    // account SMT root of the block, which is committed in the block header
+    pub account_root: H256,
+    pub balances: Vec<SUDTBalance>,
+    // compiled SMT proof of all balances, see `gw_common::merkle_utils::verify_sudt_balances_proof`
+    pub proof: JsonBytes,
+}
+
+/// A main chain tx sent from or to an account
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountTransaction {
+    pub tx_hash: H256,
+    pub block_number: Uint64,
+    pub tx_index: Uint32,
+}
+
+/// A page of the txs of an account
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountTransactions {
+    pub transactions: Vec<AccountTransaction>,
+    // pass it to get the next page, it's null if there are no more txs
+    pub cursor: Option<JsonBytes>,
+}
+
+/// A transaction and the block including it
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct L2TransactionWithBlockInfo {
+    pub transaction: L2TransactionView,
+    pub block_hash: H256,
+    pub block_number: Uint64,
+    pub tx_index: Uint32,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct TxInclusionProof {
+    pub tx_hash: H256,
+    pub tx_witness_hash: H256,
+    pub tx_index: Uint32,
+    pub block_hash: H256,
+    // the tx_witness_root in submit_transactions is the root of the proof
+    pub raw_l2block: RawL2Block,
+    // compiled SMT proof of the tx witness hash, the leaf key is the tx index
+    pub proof: JsonBytes,
+    // L1 transaction which commits the block, the block is in its witness
+    pub l2block_committed_info: L2BlockCommittedInfo,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ProducerBlockStats {
+    pub producer_id: Uint32,
+    pub block_count: Uint64,
+    pub tx_count: Uint64,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ProducerStats {
+    pub from_block: Uint64,
+    pub to_block: Uint64,
+    pub block_count: Uint64,
+    pub tx_count: Uint64,
+    // in the unit of block timestamp
+    pub average_interval: Uint64,
+    pub average_tx_count: Uint64,
+    // estimated from the intervals longer than the median interval
+    pub missed_slots: Uint64,
+    pub producers: Vec<ProducerBlockStats>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct MemPoolRejection {
+    pub reason: String,
+    pub count: Uint64,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct MemPoolStats {
+    pub pending_accounts: Uint32,
+    pub txs: Uint32,
+    // total bytes of the serialized txs
+    pub tx_bytes: Uint64,
+    pub withdrawals: Uint32,
+    // rejected txs since the node started, by reason
+    pub rejections: Vec<MemPoolRejection>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SUDTBalanceChange {
+    pub sudt_id: Uint32,
+    pub old_balance: Uint128,
+    pub new_balance: Uint128,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountChanges {
+    pub block_number: Uint64,
+    pub block_hash: H256,
+    pub account_id: Uint32,
+    // None if the nonce isn't changed in the block
+    pub nonce: Option<Uint32>,
+    pub balances: Vec<SUDTBalanceChange>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum SigningPayload {
+    RawL2Transaction(RawL2Transaction),
+    RawWithdrawalRequest(RawWithdrawalRequest),
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SigningMessage {
+    pub message: H256,
+    // molecule serialization of the payload, the message is computed from it
+    pub canonical_bytes: JsonBytes,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalStatus {
+    /// the withdrawal can be packaged into the next block
+    NextBlock,
+    /// the withdrawal is valid but waits for the custodian liquidity or the previous
+    /// withdrawals of the account
+    Queued,
+    /// the withdrawal is rejected by the mem-pool
+    Rejected,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct WithdrawalFeasibility {
+    pub status: WithdrawalStatus,
+    /// None if the status is `next_block`
+    pub reason: Option<String>,
+    /// finalized custodian capacity minus the withdrawals in the mem-pool
+    pub available_capacity: Uint128,
+    /// finalized custodian sUDT amount minus the withdrawals in the mem-pool
+    pub available_amount: Uint128,
+    /// CKB fee signed in the request, charged on top of the withdrawn capacity
+    pub fee_capacity: Uint64,
+    /// sUDT fee signed in the request, charged on top of the withdrawn amount
+    pub fee_amount: Uint128,
+    /// withdrawn capacity plus the fee, deducted from the layer2 CKB balance
+    pub charged_capacity: Uint128,
+    /// withdrawn amount plus the fee, deducted from the layer2 sUDT balance
+    pub charged_amount: Uint128,
+}
+
+/// Min fees accepted by the mem-pool on top of the gas fee of txs
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct FeeConfig {
+    /// min gas fee in CKB of a layer2 tx for each account it creates, checked by the mem-pool
+    pub account_creation_fee: Uint64,
+    /// min CKB fee of every withdrawal accepted by the mem-pool
+    pub withdrawal_ckb_fee: Uint64,
+    /// min sUDT fee of withdrawals of the sUDTs accepted by the mem-pool, sUDTs not listed
+    /// have no min fee
+    pub withdrawal_sudt_fees: Vec<SUDTWithdrawalFee>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SUDTWithdrawalFee {
+    /// layer1 sUDT script hash
+    pub sudt_script_hash: H256,
+    pub fee: Uint128,
+}
+
+/// State served by state RPCs
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockTag {
+    /// the optimistic tip block
+    Latest,
+    /// the last finalized block, it can't be reverted by a challenge
+    Finalized,
+    /// the tip block with the txs & withdrawals of the mem-pool applied,
+    /// only served by `get_nonce` and `get_balance`
+    Pending,
+}
+
+impl Default for BlockTag {
+    fn default() -> Self {
+        Self::Latest
+    }
+}
+
+/// Block of the state served by state RPCs, either a tag or a block
+/// of the main chain, e.g. `"finalized"`, `{"block_number": "0x10"}`
+/// or `{"block_hash": "0x..."}`
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(untagged)]
+pub enum BlockParam {
+    Tag(BlockTag),
+    Number { block_number: Uint64 },
+    Hash { block_hash: H256 },
+}
+
+impl Default for BlockParam {
+    fn default() -> Self {
+        Self::Tag(BlockTag::default())
+    }
+}
+
+impl From<BlockTag> for BlockParam {
+    fn from(tag: BlockTag) -> Self {
+        Self::Tag(tag)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct NewAccount {
+    pub account_id: Uint32,
+    pub script_hash: H256,
+}
+
+/// Storage owned by an account on the main chain
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountStorageUsage {
+    pub account_id: Uint32,
+    // storage slots with non-zero values
+    pub slots: Uint64,
+    pub data_bytes: Uint64,
+}
+
+/// Fees of a block, credited to the block producer
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct BlockEconomics {
+    pub block_producer_id: Uint32,
+    pub tx_count: Uint32,
+    pub withdrawal_count: Uint32,
+    pub used_cycles: Uint64,
+    // CKB paid by txs
+    pub paid_fees: Uint128,
+    // CKB refunded to senders for the unused gas
+    pub refunded_fees: Uint128,
+}
+
+impl From<packed::BlockEconomics> for BlockEconomics {
+    fn from(data: packed::BlockEconomics) -> BlockEconomics {
+        let block_producer_id: u32 = data.block_producer_id().unpack();
+        let tx_count: u32 = data.tx_count().unpack();
+        let withdrawal_count: u32 = data.withdrawal_count().unpack();
+        let used_cycles: u64 = data.used_cycles().unpack();
+        let paid_fees: u128 = data.paid_fees().unpack();
+        let refunded_fees: u128 = data.refunded_fees().unpack();
+        BlockEconomics {
+            block_producer_id: block_producer_id.into(),
+            tx_count: tx_count.into(),
+            withdrawal_count: withdrawal_count.into(),
+            used_cycles: used_cycles.into(),
+            paid_fees: paid_fees.into(),
+            refunded_fees: refunded_fees.into(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupActionType {
+    EnterChallenge,
+    CancelChallenge,
+    Revert,
+}
+
+impl Default for RollupActionType {
+    fn default() -> Self {
+        Self::EnterChallenge
+    }
+}
+
+impl TryFrom<packed::Byte> for RollupActionType {
+    type Error = JsonError;
+
+    fn try_from(v: packed::Byte) -> Result<RollupActionType, Self::Error> {
+        match u8::from(v) {
+            0 => Ok(RollupActionType::EnterChallenge),
+            1 => Ok(RollupActionType::CancelChallenge),
+            2 => Ok(RollupActionType::Revert),
+            _ => Err(anyhow!("Invalid rollup action type {}", v)),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct RollupAction {
+    pub action_type: RollupActionType,
+    // The actual type is `u8`, 0: running, 1: halting
+    pub prev_status: Uint32,
+    pub post_status: Uint32,
+    pub challenge_target: ChallengeTarget,
+    pub l1_block_number: Uint64,
+    pub l1_block_hash: H256,
+    pub l1_tx_hash: H256,
+}
+
+impl From<packed::RollupActionRecord> for RollupAction {
+    fn from(data: packed::RollupActionRecord) -> RollupAction {
+        let prev_status: u8 = data.prev_status().into();
+        let post_status: u8 = data.post_status().into();
+        let l1_block_number: u64 = data.l1_block_number().unpack();
+        RollupAction {
+            action_type: data.action_type().try_into().expect("invalid action type"),
+            prev_status: (prev_status as u32).into(),
+            post_status: (post_status as u32).into(),
+            challenge_target: data.challenge_target().into(),
+            l1_block_number: l1_block_number.into(),
+            l1_block_hash: data.l1_block_hash().unpack(),
+            l1_tx_hash: data.l1_tx_hash().unpack(),
+        }
+    }
+}
+
+/// State override of an account, applied before an execution
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountOverride {
+    pub account_id: Uint32,
+    #[serde(default)]
+    pub balances: Vec<BalanceOverride>,
+    #[serde(default)]
+    pub storage: Vec<KVPair>,
+    // replace the script of the account, the backend is selected by its code hash
+    #[serde(default)]
+    pub script: Option<Script>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct BalanceOverride {
+    pub sudt_id: Uint32,
+    pub balance: Uint128,
+}
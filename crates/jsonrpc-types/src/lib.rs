@@ -1,4 +1,5 @@
 pub mod blockchain;
+pub mod canonical;
 pub mod fixed_bytes;
 pub mod godwoken;
 // re-exports
@@ -0,0 +1,62 @@
+//! Clock
+//!
+//! Block timestamps and the TTL of txs are read from a `Clock`, so the dev mode can
+//! control the time and contracts with time-dependent logic can be tested deterministically.
+
+use parking_lot::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub trait Clock: Send + Sync {
+    /// Duration since the unix epoch
+    fn now(&self) -> Duration;
+
+    /// Milliseconds since the unix epoch, the unit of block timestamps
+    fn now_millis(&self) -> u64 {
+        self.now().as_millis() as u64
+    }
+}
+
+/// The system time
+#[derive(Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before unix epoch")
+    }
+}
+
+/// A clock controlled by the `dev_setTime` and `dev_increaseTime` RPCs, only in dev mode.
+/// The time is frozen until it's set or increased
+pub struct DevClock {
+    now: Mutex<Duration>,
+}
+
+impl Default for DevClock {
+    fn default() -> Self {
+        DevClock {
+            now: Mutex::new(SystemClock.now()),
+        }
+    }
+}
+
+impl DevClock {
+    pub fn set_time(&self, now: Duration) {
+        *self.now.lock() = now;
+    }
+
+    /// Returns the new time
+    pub fn increase_time(&self, duration: Duration) -> Duration {
+        let mut now = self.now.lock();
+        *now += duration;
+        *now
+    }
+}
+
+impl Clock for DevClock {
+    fn now(&self) -> Duration {
+        *self.now.lock()
+    }
+}
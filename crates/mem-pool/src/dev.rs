@@ -0,0 +1,50 @@
+//! Dev mode deposits
+//!
+//! In dev mode synthetic deposits can be injected into the next block without deposit cells
+//! on layer1, so tests can fund accounts without constructing layer1 transactions.
+//! The block producer takes the queued deposits when it produces a block, and the chain
+//! updater applies them again when the block is synced from layer1, since the layer1
+//! transaction doesn't carry them.
+
+use gw_common::H256;
+use gw_types::packed::DepositionRequest;
+use std::collections::HashMap;
+
+/// MAX produced blocks whose deposits are kept until the blocks are synced
+const MAX_PRODUCED_BLOCKS: usize = 256;
+
+#[derive(Debug, Default)]
+pub struct DevDeposits {
+    /// deposits of the next block
+    queued: Vec<DepositionRequest>,
+    /// deposits of the produced blocks which are not synced yet
+    produced: HashMap<H256, Vec<DepositionRequest>>,
+}
+
+impl DevDeposits {
+    pub fn push(&mut self, request: DepositionRequest) {
+        self.queued.push(request);
+    }
+
+    /// Take the deposits of the next block
+    pub fn take_queued(&mut self) -> Vec<DepositionRequest> {
+        self.queued.drain(..).collect()
+    }
+
+    /// Keep the deposits of a produced block until it is synced,
+    /// deposits of blocks never synced are dropped once too many are kept
+    pub fn insert_produced(&mut self, block_hash: H256, requests: Vec<DepositionRequest>) {
+        if requests.is_empty() {
+            return;
+        }
+        if self.produced.len() >= MAX_PRODUCED_BLOCKS {
+            self.produced.clear();
+        }
+        self.produced.insert(block_hash, requests);
+    }
+
+    /// Take the deposits of a synced block
+    pub fn take_produced(&mut self, block_hash: &H256) -> Vec<DepositionRequest> {
+        self.produced.remove(block_hash).unwrap_or_default()
+    }
+}
@@ -3,4 +3,7 @@
 //! MemPool only do basic verification on l2transactions & withdrawal requests,
 //! the block producer need to verify the fully verification itself.
 
+pub mod clock;
+pub mod dev;
+pub mod policy;
 pub mod pool;
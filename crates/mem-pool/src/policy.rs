@@ -0,0 +1,72 @@
+//! Admission policy
+//!
+//! Operators of hosted RPC may need to refuse txs of some accounts,
+//! the policy is evaluated before a tx is pushed into the pool.
+
+use gw_common::H256;
+use gw_config::DenyListConfig;
+use std::collections::HashSet;
+
+/// Accounts involved in a layer2 tx
+pub struct AdmissionContext {
+    pub tx_hash: H256,
+    pub from_id: u32,
+    pub to_id: u32,
+    pub from_script_hash: H256,
+    pub to_script_hash: H256,
+}
+
+pub trait AdmissionPolicy: Send + Sync {
+    /// Returns the reason if the tx is denied
+    fn check(&self, ctx: &AdmissionContext) -> Result<(), String>;
+}
+
+/// Deny txs sent from or to listed accounts
+#[derive(Default)]
+pub struct DenyList {
+    from_ids: HashSet<u32>,
+    to_ids: HashSet<u32>,
+    script_hashes: HashSet<H256>,
+}
+
+impl DenyList {
+    pub fn from_config(config: &DenyListConfig) -> Self {
+        let script_hashes = config
+            .script_hashes
+            .iter()
+            .map(|hash| {
+                let hash: [u8; 32] = hash.clone().into();
+                hash.into()
+            })
+            .collect();
+        DenyList {
+            from_ids: config.from_ids.iter().cloned().collect(),
+            to_ids: config.to_ids.iter().cloned().collect(),
+            script_hashes,
+        }
+    }
+}
+
+impl AdmissionPolicy for DenyList {
+    fn check(&self, ctx: &AdmissionContext) -> Result<(), String> {
+        if self.from_ids.contains(&ctx.from_id) {
+            return Err(format!("sender {} is denied", ctx.from_id));
+        }
+        if self.to_ids.contains(&ctx.to_id) {
+            return Err(format!("receiver {} is denied", ctx.to_id));
+        }
+        if self.script_hashes.contains(&ctx.from_script_hash) {
+            return Err(format!(
+                "sender script {:?} is denied",
+                ctx.from_script_hash
+            ));
+        }
+        if self.script_hashes.contains(&ctx.to_script_hash) {
+            return Err(format!(
+                "receiver script {:?} is denied",
+                ctx.to_script_hash
+            ));
+        }
+        Ok(())
+    }
+}
@@ -11,35 +11,391 @@
 //! The design of Godwoken MemPool is highly inspired by the Geth TxPool.
 //! We maintain a pending list which contains executable txs & withdrawals (executable means can be packaged into the next block),
 //! we also maintain a queue list which contains non-executable txs & withdrawals (these objects may become executable in the future).
+//!
+//! Accepted txs & withdrawals are applied to a pending state on top of the tip, so the
+//! next tx of an account is verified against the nonce and balance left by its pending txs.
+//! The pending state is recomputed on new tips, in the order of account id. The writes of
+//! each tx & withdrawal are kept, so removing one only re-executes the ones applied after it.
+//!
+//! `push_transaction_with_lock` and `notify_new_tip_with_lock` execute without holding the
+//! mem-pool lock, the executions on the pending state are serialized by a separate lock.
 
+use crate::{
+    clock::{Clock, DevClock, SystemClock},
+    dev::DevDeposits,
+    policy::{AdmissionContext, AdmissionPolicy, DenyList},
+};
 use anyhow::{anyhow, Result};
-use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State, H256};
-use gw_generator::Generator;
+use gw_common::{blake2b::new_blake2b, state::State, CKB_SUDT_SCRIPT_ARGS, H256};
+use gw_config::{BackendConfig, MemPoolLimitsConfig, StorageLimitsConfig};
+use gw_generator::{
+    error::TransactionError,
+    generator::{tx_gas_limit, StateTransitionArgs},
+    storage_usage::storage_delta,
+    traits::StateExt,
+    Generator,
+};
 use gw_store::{
     chain_view::ChainView,
-    state_db::{StateDBTransaction, StateDBVersion},
+    overlay_state::{AccountOverride, OverlayChanges, OverlayState},
+    state_db::{StateDBTransaction, StateDBVersion, StateTree},
     transaction::StoreTransaction,
     Store,
 };
 use gw_types::{
-    offchain::RunResult,
-    packed::{BlockInfo, L2Transaction, WithdrawalRequest},
-    prelude::{Entity, Unpack},
+    offchain::{RunResult, StorageDelta, TraceStep},
+    packed::{BlockInfo, DepositionRequest, L2Transaction, RawL2Transaction, WithdrawalRequest},
+    prelude::{Builder, Entity, Pack, Unpack},
 };
+use lru::LruCache;
+use parking_lot::Mutex;
 use std::{
-    cmp::{max, min},
-    collections::{HashMap, HashSet},
+    cmp::{max, min, Reverse},
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
-/// MAX mem pool txs
-const MAX_IN_POOL_TXS: usize = 6000;
 /// MAX mem pool withdrawal requests
 const MAX_IN_POOL_WITHDRAWAL: usize = 3000;
+/// Min gas price bump in percent for a tx to replace a pending tx with the same nonce
+const MIN_REPLACEMENT_PRICE_BUMP: u128 = 10;
 /// MAX tx size
 const MAX_TX_SIZE: usize = 50_000;
 /// MAX withdrawal size
 const MAX_WITHDRAWAL_SIZE: usize = 50_000;
+/// MAX cached execution results
+const MAX_EXECUTE_CACHE_SIZE: usize = 1024;
+/// MAX execution time of a tx on the pending state, later pushes wait for the execution
+const MAX_PENDING_EXECUTE_TIME: Duration = Duration::from_secs(1);
+/// MAX events kept until they are drained, the oldest ones are discarded
+const MAX_EVENTS: usize = 1024;
+
+type ExecuteCache = LruCache<(H256, H256), RunResult>;
+
+/// Reasons of rejected txs, see `MemPoolStats::rejections`
+const REJECT_DUPLICATED: &str = "duplicated";
+const REJECT_DENIED: &str = "denied";
+const REJECT_UNDERPRICED: &str = "underpriced";
+const REJECT_ACCOUNT_LIMIT: &str = "account_limit";
+const REJECT_INVALID: &str = "invalid";
+const REJECT_POOL_FULL: &str = "pool_full";
+
+/// Read-only view of the mem-pool state
+#[derive(Clone)]
+pub struct MemPoolSnapshot {
+    store: Store,
+    generator: Arc<Generator>,
+    state_db_version: StateDBVersion,
+    execute_cache: Arc<Mutex<ExecuteCache>>,
+    /// block of the state, None for the tip
+    block_hash: Option<H256>,
+    /// (block hash, tx index) of a child block of `block_hash`, the block is replayed on the
+    /// state until the tx
+    replay: Option<(H256, u32)>,
+}
+
+impl MemPoolSnapshot {
+    /// Snapshot of the state of a historical block, txs are executed as the next tx of the block.
+    /// The caller must check the state of the block is not pruned
+    pub fn at_block(mut self, block_hash: H256) -> Self {
+        self.state_db_version = StateDBVersion::from_block_hash(block_hash);
+        self.block_hash = Some(block_hash);
+        self.replay = None;
+        self
+    }
+
+    /// Snapshot of the state a tx of a historical block ran on, the withdrawals, the deposits
+    /// and the txs before `tx_index` of the block are replayed on the state of the parent block.
+    /// The caller must check the state of the parent block is not pruned
+    pub fn at_transaction(self, block_hash: H256, tx_index: u32) -> Result<Self> {
+        let block = self
+            .store
+            .get_block(&block_hash)?
+            .ok_or_else(|| anyhow!("block {:?} not found", block_hash))?;
+        if tx_index as usize >= block.transactions().len() {
+            return Err(anyhow!(
+                "block {:?} doesn't have the tx {}",
+                block_hash,
+                tx_index
+            ));
+        }
+        let mut snapshot = self.at_block(block.raw().parent_block_hash().unpack());
+        snapshot.replay = Some((block_hash, tx_index));
+        Ok(snapshot)
+    }
+
+    /// Changes of the replayed part of the block on top of the state, empty if the snapshot
+    /// isn't at a tx
+    fn replay_changes(
+        &self,
+        db: &StoreTransaction,
+        chain_view: &ChainView,
+        tree: &StateTree,
+    ) -> Result<OverlayChanges> {
+        let (block_hash, tx_index) = match self.replay {
+            Some(replay) => replay,
+            None => return Ok(Default::default()),
+        };
+        let l2block = db
+            .get_block(&block_hash)?
+            .ok_or_else(|| anyhow!("block {:?} not found", block_hash))?;
+        let deposition_requests = db
+            .get_block_deposition_requests(&block_hash)?
+            .unwrap_or_default();
+        let args = StateTransitionArgs {
+            l2block,
+            deposition_requests,
+        };
+        let mut state = OverlayState::new(tree, Default::default());
+        self.generator
+            .replay_block_until(chain_view, &mut state, &args, tx_index)?;
+        Ok(state.into_changes())
+    }
+
+    /// The chain view of the txs ends at the block of the state
+    fn chain_tip(&self) -> Result<H256> {
+        match self.block_hash {
+            Some(block_hash) => Ok(block_hash),
+            None => Ok(self.store.get_tip_block_hash()?),
+        }
+    }
+
+    /// Execute tx without push it into pool
+    pub fn execute_transaction(
+        &self,
+        tx: L2Transaction,
+        block_info: &BlockInfo,
+    ) -> Result<RunResult> {
+        self.execute_transaction_with_deadline(tx, block_info, None)
+    }
+
+    /// Execute tx without push it into pool, returns a timeout error once the deadline passed
+    pub fn execute_transaction_with_deadline(
+        &self,
+        tx: L2Transaction,
+        block_info: &BlockInfo,
+        deadline: Option<Instant>,
+    ) -> Result<RunResult> {
+        let db = self.store.begin_transaction();
+        let state_db = StateDBTransaction::from_version(&db, self.state_db_version.clone())
+            .map_err(|err| anyhow!("err: {}", err))?;
+        let tree = state_db.account_state_tree()?;
+        let tip_block_hash = self.chain_tip()?;
+        let chain_view = ChainView::new(&db, tip_block_hash);
+        let state = OverlayState::new(&tree, self.replay_changes(&db, &chain_view, &tree)?);
+        // verify tx signature
+        self.generator.check_transaction_signature(&state, &tx)?;
+        // tx basic verification
+        self.generator.verify_transaction(&state, &tx)?;
+        // dapps usually poll the same view calls, return the cached result if any
+        let raw_tx = tx.raw();
+        let cache_key = (
+            state.calculate_root()?,
+            build_call_hash(block_info, &raw_tx),
+        );
+        if let Some(run_result) = self.execute_cache.lock().get(&cache_key) {
+            return Ok(run_result.clone());
+        }
+        // execute tx, the cache isn't locked during the execution
+        let run_result = self.generator.execute_transaction_with_deadline(
+            &chain_view,
+            &state,
+            &block_info,
+            &raw_tx,
+            deadline,
+        )?;
+        self.execute_cache.lock().put(cache_key, run_result.clone());
+        Ok(run_result)
+    }
+
+    /// Estimate the gas limit of a tx, it's the lowest gas limit the tx succeeds with.
+    ///
+    /// The tx is executed with its gas limit, or the max block gas if it's 0.
+    /// The used gas is usually enough, but a backend may need more gas than it uses, e.g. it
+    /// checks the remaining gas, the gas limit is binary searched above the used gas then.
+    pub fn estimate_gas(
+        &self,
+        tx: L2Transaction,
+        block_info: &BlockInfo,
+        deadline: Option<Instant>,
+    ) -> Result<u64> {
+        let db = self.store.begin_transaction();
+        let state_db = StateDBTransaction::from_version(&db, self.state_db_version.clone())
+            .map_err(|err| anyhow!("err: {}", err))?;
+        let tree = state_db.account_state_tree()?;
+        let tip_block_hash = self.chain_tip()?;
+        let chain_view = ChainView::new(&db, tip_block_hash);
+        let state = OverlayState::new(&tree, self.replay_changes(&db, &chain_view, &tree)?);
+        self.generator.check_transaction_signature(&state, &tx)?;
+        self.generator.verify_transaction(&state, &tx)?;
+        let raw_tx = tx.raw();
+        let execute = |gas_limit: u64| {
+            let raw_tx = raw_tx
+                .clone()
+                .as_builder()
+                .gas_limit(gas_limit.pack())
+                .build();
+            self.generator.execute_transaction_with_deadline(
+                &chain_view,
+                &state,
+                block_info,
+                &raw_tx,
+                deadline,
+            )
+        };
+        let gas_limit: u64 = raw_tx.gas_limit().unpack();
+        // the tx succeeds with `hi` and fails with `lo`
+        let mut hi = match gas_limit {
+            0 => self.generator.block_limits().max_block_gas,
+            gas_limit => gas_limit,
+        };
+        let used_gas = execute(hi)?.used_cycles;
+        // gas limit 0 isn't an estimation, so the estimation is at least 1
+        let mut lo = max(used_gas, 1);
+        if lo >= hi {
+            return Ok(hi);
+        }
+        match execute(lo) {
+            Ok(_) => return Ok(lo),
+            Err(TransactionError::Timeout) => return Err(TransactionError::Timeout.into()),
+            Err(_) => {}
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            match execute(mid) {
+                Ok(_) => hi = mid,
+                Err(TransactionError::Timeout) => return Err(TransactionError::Timeout.into()),
+                Err(_) => lo = mid,
+            }
+        }
+        Ok(hi)
+    }
+
+    /// Execute the raw tx on the state with the overrides applied,
+    /// the signature and the nonce are not verified and the result isn't cached
+    pub fn execute_with_state_overrides(
+        &self,
+        raw_tx: &RawL2Transaction,
+        block_info: &BlockInfo,
+        overrides: &[AccountOverride],
+        deadline: Option<Instant>,
+    ) -> Result<RunResult> {
+        let db = self.store.begin_transaction();
+        let state_db = StateDBTransaction::from_version(&db, self.state_db_version.clone())
+            .map_err(|err| anyhow!("err: {}", err))?;
+        let tree = state_db.account_state_tree()?;
+        let tip_block_hash = self.chain_tip()?;
+        let chain_view = ChainView::new(&db, tip_block_hash);
+        let mut state = OverlayState::new(&tree, self.replay_changes(&db, &chain_view, &tree)?);
+        let account_count = state.get_account_count()?;
+        for account_override in overrides {
+            if account_override.account_id >= account_count {
+                return Err(anyhow!(
+                    "can't override account {}, it doesn't exist",
+                    account_override.account_id
+                ));
+            }
+            state.apply_override(account_override)?;
+        }
+        let run_result = self.generator.execute_transaction_with_deadline(
+            &chain_view,
+            &state,
+            block_info,
+            raw_tx,
+            deadline,
+        )?;
+        Ok(run_result)
+    }
+
+    /// Execute tx in the tracing mode without push it into pool, the result isn't cached.
+    /// The recorded syscalls are returned with the result of the tx, even if it fails.
+    /// The tx fails once `max_steps` syscalls are recorded
+    pub fn trace_transaction(
+        &self,
+        tx: L2Transaction,
+        block_info: &BlockInfo,
+        deadline: Option<Instant>,
+        max_steps: usize,
+    ) -> Result<(Result<RunResult, TransactionError>, Vec<TraceStep>)> {
+        let db = self.store.begin_transaction();
+        let state_db = StateDBTransaction::from_version(&db, self.state_db_version.clone())
+            .map_err(|err| anyhow!("err: {}", err))?;
+        let tree = state_db.account_state_tree()?;
+        let tip_block_hash = self.chain_tip()?;
+        let chain_view = ChainView::new(&db, tip_block_hash);
+        let state = OverlayState::new(&tree, self.replay_changes(&db, &chain_view, &tree)?);
+        self.generator.check_transaction_signature(&state, &tx)?;
+        self.generator.verify_transaction(&state, &tx)?;
+        Ok(self.generator.trace_transaction(
+            &chain_view,
+            &state,
+            block_info,
+            &tx.raw(),
+            deadline,
+            max_steps,
+        ))
+    }
+}
+
+/// Events of the mem-pool, see `MemPool::drain_events`
+#[derive(Debug, Clone)]
+pub enum MemPoolEvent {
+    /// a pending withdrawal is evicted since it's invalid on the new tip,
+    /// e.g. the balance is spent by a tx
+    WithdrawalDropped {
+        withdrawal_hash: H256,
+        reason: String,
+    },
+    /// a pending tx is replaced by a tx with the same nonce which pays a higher gas price
+    TransactionReplaced {
+        tx_hash: H256,
+        replaced_tx_hash: H256,
+    },
+    /// a pending tx is evicted since it's invalid after a previous tx is replaced
+    TransactionDropped { tx_hash: H256, reason: String },
+}
+
+/// Counts of the mem-pool, see `MemPool::stats`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemPoolStats {
+    pub pending_accounts: usize,
+    pub txs: usize,
+    /// total bytes of the serialized txs
+    pub tx_bytes: usize,
+    pub withdrawals: usize,
+    /// reason => count of the rejected txs since the mem-pool is created
+    pub rejections: BTreeMap<&'static str, u64>,
+}
+
+/// Whether a withdrawal can be packaged into the next block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WithdrawalStatus {
+    NextBlock,
+    /// valid but waits for the liquidity or the previous withdrawals of the account
+    Queued,
+    /// the pool will reject the withdrawal
+    Rejected,
+}
+
+#[derive(Debug, Clone)]
+pub struct WithdrawalFeasibility {
+    pub status: WithdrawalStatus,
+    /// None if the status is `NextBlock`
+    pub reason: Option<String>,
+    /// finalized custodian capacity minus the pending withdrawals
+    pub available_capacity: u128,
+    /// finalized custodian sUDT amount minus the pending withdrawals
+    pub available_amount: u128,
+    /// CKB fee charged on top of the withdrawn capacity
+    pub fee_capacity: u64,
+    /// sUDT fee charged on top of the withdrawn amount
+    pub fee_amount: u128,
+    /// withdrawn capacity plus the fee, deducted from the layer2 CKB balance
+    pub charged_capacity: u128,
+    /// withdrawn amount plus the fee, deducted from the layer2 sUDT balance
+    pub charged_amount: u128,
+}
 
 #[derive(Default)]
 pub struct EntryList {
@@ -67,12 +423,8 @@ impl EntryList {
         removed
     }
 
-    // remove and return withdrawals which withdrawal.nonce is lower than nonce & have not enough balance
-    fn remove_lower_nonce_balance_withdrawals(
-        &mut self,
-        nonce: u32,
-        capacity: u128,
-    ) -> Vec<WithdrawalRequest> {
+    // remove and return withdrawals which withdrawal.nonce is lower than nonce
+    fn remove_lower_nonce_withdrawals(&mut self, nonce: u32) -> Vec<WithdrawalRequest> {
         let mut removed = Vec::default();
 
         // remove lower nonce withdrawals
@@ -84,20 +436,216 @@ impl EntryList {
             removed.push(self.withdrawals.remove(0));
         }
 
-        // remove lower balance withdrawals
-        if let Some(withdrawal) = self.withdrawals.get(0) {
-            let withdrawal_capacity: u64 = withdrawal.raw().capacity().unpack();
-            if (withdrawal_capacity as u128) > capacity {
-                // TODO instead of remove all withdrawals, put them into future queue
-                removed.extend_from_slice(&self.withdrawals);
-                self.withdrawals.clear();
-            }
+        removed
+    }
+}
+
+/// A tx or withdrawal applied to the pending state
+enum PendingItem {
+    Transaction(L2Transaction),
+    Withdrawal(WithdrawalRequest),
+}
+
+/// Writes of a pending item, the pending state is the writes of the items in order
+struct PendingEntry {
+    item: PendingItem,
+    changes: OverlayChanges,
+}
+
+/// Executes txs & withdrawals on top of a pending state,
+/// it doesn't borrow the mem-pool so the mem-pool lock can be released during the execution
+struct PendingExecutor {
+    store: Store,
+    generator: Arc<Generator>,
+    state_db_version: StateDBVersion,
+    tip_block_hash: H256,
+    block_info: BlockInfo,
+    storage_limits: StorageLimitsConfig,
+}
+
+impl PendingExecutor {
+    fn fetch_state_db<'a>(&self, db: &'a StoreTransaction) -> Result<StateDBTransaction<'a>> {
+        StateDBTransaction::from_version(db, self.state_db_version.clone())
+            .map_err(|err| anyhow!("err: {}", err))
+    }
+
+    /// Basic verification of the tx on top of `pending_state`
+    fn verify_transaction(&self, pending_state: &OverlayChanges, tx: &L2Transaction) -> Result<()> {
+        // check tx size
+        if tx.as_slice().len() > MAX_TX_SIZE {
+            return Err(anyhow!("tx over size"));
         }
 
-        removed
+        let db = self.store.begin_transaction();
+        let state_db = self.fetch_state_db(&db)?;
+
+        // TODO
+        // we should introduce queue manchanism and only remove tx when tx.nonce is lower
+        // reject tx if nonce is not equals the pending nonce of the account
+        let tree = state_db.account_state_tree()?;
+        let state = OverlayState::new(&tree, pending_state.clone());
+        let account_id: u32 = tx.raw().from_id().unpack();
+        let nonce = state.get_nonce(account_id)?;
+        let tx_nonce: u32 = tx.raw().nonce().unpack();
+        if nonce != tx_nonce {
+            return Err(anyhow!(
+                "tx's nonce is incorrect, expected: {} got: {}",
+                nonce,
+                tx_nonce,
+            ));
+        }
+
+        // verify signature, the signing message commits to the rollup script hash
+        // so txs signed for another rollup deployment are rejected
+        self.generator.check_transaction_signature(&state, &tx)?;
+
+        // verify args and the sender can pay the max fee
+        self.generator.verify_transaction(&state, &tx)?;
+
+        Ok(())
+    }
+
+    /// Execute the tx on top of `pending_state`, returns the writes of the tx and its fee
+    fn execute_transaction(
+        &self,
+        pending_state: &OverlayChanges,
+        tx: &L2Transaction,
+    ) -> Result<OverlayChanges> {
+        let db = self.store.begin_transaction();
+        let state_db = self.fetch_state_db(&db)?;
+        let tree = state_db.account_state_tree()?;
+        let pending = OverlayState::new(&tree, pending_state.clone());
+        let mut state = OverlayState::new(&pending, Default::default());
+        let chain_view = ChainView::new(&db, self.tip_block_hash);
+        let raw_tx = tx.raw();
+        let deadline = Instant::now() + MAX_PENDING_EXECUTE_TIME;
+        let run_result = self.generator.execute_transaction_with_deadline(
+            &chain_view,
+            &state,
+            &self.block_info,
+            &raw_tx,
+            Some(deadline),
+        )?;
+        self.generator
+            .check_account_creation_fee(&raw_tx, &run_result)?;
+        self.generator.check_new_script_args(&run_result)?;
+        let delta = storage_delta(&state, &raw_tx, &run_result)?;
+        self.check_storage_limits(&db, raw_tx.to_id().unpack(), delta)?;
+        state.apply_run_result(&run_result)?;
+        self.generator
+            .settle_fee(&mut state, &self.block_info, &raw_tx, &run_result)?;
+        Ok(state.into_changes())
+    }
+
+    /// The storage owned by the called account after the tx is checked against the usage
+    /// on the tip, the other pending txs calling the account are not counted
+    fn check_storage_limits(
+        &self,
+        db: &StoreTransaction,
+        account_id: u32,
+        delta: StorageDelta,
+    ) -> Result<()> {
+        let limits = &self.storage_limits;
+        let exceeds = |value: u64, limit: Option<u64>| limit.map_or(false, |limit| value > limit);
+        if delta.slots > 0 && exceeds(delta.slots as u64, limits.max_tx_new_slots) {
+            return Err(anyhow!(
+                "tx creates {} storage slots, max: {:?}",
+                delta.slots,
+                limits.max_tx_new_slots
+            ));
+        }
+        if delta.data_bytes > 0 && exceeds(delta.data_bytes as u64, limits.max_tx_data_bytes) {
+            return Err(anyhow!(
+                "tx stores {} data bytes, max: {:?}",
+                delta.data_bytes,
+                limits.max_tx_data_bytes
+            ));
+        }
+        if limits.max_account_slots.is_none() && limits.max_account_data_bytes.is_none() {
+            return Ok(());
+        }
+        let usage = db.get_account_storage_usage(account_id)?.apply(delta);
+        if delta.slots > 0 && exceeds(usage.slots, limits.max_account_slots) {
+            return Err(anyhow!(
+                "account {} would own {} storage slots, max: {:?}",
+                account_id,
+                usage.slots,
+                limits.max_account_slots
+            ));
+        }
+        if delta.data_bytes > 0 && exceeds(usage.data_bytes, limits.max_account_data_bytes) {
+            return Err(anyhow!(
+                "account {} would own {} data bytes, max: {:?}",
+                account_id,
+                usage.data_bytes,
+                limits.max_account_data_bytes
+            ));
+        }
+        Ok(())
+    }
+
+    /// Apply the withdrawal on top of `pending_state`, returns the writes of the withdrawal
+    fn execute_withdrawal(
+        &self,
+        pending_state: &OverlayChanges,
+        withdrawal: &WithdrawalRequest,
+    ) -> Result<OverlayChanges> {
+        let db = self.store.begin_transaction();
+        let state_db = self.fetch_state_db(&db)?;
+        let tree = state_db.account_state_tree()?;
+        let pending = OverlayState::new(&tree, pending_state.clone());
+        let mut state = OverlayState::new(&pending, Default::default());
+        let block_producer_id: u32 = self.block_info.block_producer_id().unpack();
+        state.apply_withdrawal_request(
+            self.generator.rollup_context(),
+            block_producer_id,
+            withdrawal,
+        )?;
+        Ok(state.into_changes())
+    }
+
+    /// Apply the items in order to the pending state,
+    /// txs & withdrawals which fail are skipped, they are left to `demote_unexecutables`
+    fn apply_items(
+        &self,
+        pending_state: &mut OverlayChanges,
+        pending_log: &mut Vec<PendingEntry>,
+        items: Vec<PendingItem>,
+    ) {
+        for item in items {
+            let result = match &item {
+                PendingItem::Transaction(tx) => self.execute_transaction(pending_state, tx),
+                PendingItem::Withdrawal(withdrawal) => {
+                    self.execute_withdrawal(pending_state, withdrawal)
+                }
+            };
+            match result {
+                Ok(changes) => {
+                    pending_state.merge(changes.clone());
+                    pending_log.push(PendingEntry { item, changes });
+                }
+                Err(err) => match item {
+                    PendingItem::Transaction(tx) => {
+                        eprintln!("MemPool: skip tx {:?} in pending state: {}", tx.hash(), err)
+                    }
+                    PendingItem::Withdrawal(withdrawal) => eprintln!(
+                        "MemPool: skip withdrawal {:?} in pending state: {}",
+                        withdrawal.hash(),
+                        err
+                    ),
+                },
+            }
+        }
     }
 }
 
+/// A tx checked under the mem-pool lock, it's executed after the lock is released
+struct PreparedTransaction {
+    executor: PendingExecutor,
+    pending_state: OverlayChanges,
+    pending_version: u64,
+}
+
 pub struct MemPool {
     /// current state db version
     state_db_version: StateDBVersion,
@@ -112,8 +660,38 @@ pub struct MemPool {
     pending: HashMap<u32, EntryList>,
     /// all transactions in the pool
     all_txs: HashMap<H256, L2Transaction>,
+    /// when the txs entered the pool, read from the clock
+    tx_arrivals: HashMap<H256, Duration>,
     /// all withdrawals in the pool
     all_withdrawals: HashMap<H256, WithdrawalRequest>,
+    /// cached results of read-only executions, keyed by (state root, call hash)
+    /// the state is fixed within a block window, so the cache is cleared on new tips
+    execute_cache: Arc<Mutex<ExecuteCache>>,
+    /// admission policy of txs
+    policy: Box<dyn AdmissionPolicy>,
+    /// changes of the pending txs & withdrawals on top of the current state
+    pending_state: OverlayChanges,
+    /// the txs & withdrawals applied to the pending state in order, with their writes
+    pending_log: Vec<PendingEntry>,
+    /// bumped on every change of the pending state, a tx executed without the mem-pool lock
+    /// is added only if the pending state didn't change during the execution
+    pending_version: u64,
+    /// the pending state isn't recomputed on the new tip yet
+    pending_outdated: bool,
+    /// serializes the executions on the pending state done without the mem-pool lock
+    execution_lock: Arc<Mutex<()>>,
+    /// events not drained yet
+    events: VecDeque<MemPoolEvent>,
+    limits: MemPoolLimitsConfig,
+    storage_limits: StorageLimitsConfig,
+    /// reason => count of the rejected txs
+    rejections: BTreeMap<&'static str, u64>,
+    /// synthetic deposits, only in dev mode
+    dev_deposits: Option<DevDeposits>,
+    /// time of block timestamps and the TTL of txs
+    clock: Arc<dyn Clock>,
+    /// controllable clock, only in dev mode
+    dev_clock: Option<Arc<DevClock>>,
 }
 
 impl MemPool {
@@ -122,6 +700,18 @@ impl MemPool {
         let all_txs = Default::default();
         let all_withdrawals = Default::default();
 
+        // signatures are verified against the generator's rollup,
+        // which must be the rollup of the store, or txs signed for another deployment are accepted
+        let chain_id = store.get_chain_id()?;
+        let rollup_script_hash = generator.rollup_context().rollup_script_hash;
+        if chain_id != rollup_script_hash {
+            return Err(anyhow!(
+                "rollup script hash {:?} mismatches store chain id {:?}",
+                rollup_script_hash,
+                chain_id
+            ));
+        }
+
         let tip = store.get_tip_block_hash()?;
 
         let state_db_version = StateDBVersion::from_block_hash(tip);
@@ -133,7 +723,22 @@ impl MemPool {
             generator,
             pending,
             all_txs,
+            tx_arrivals: Default::default(),
             all_withdrawals,
+            execute_cache: Arc::new(Mutex::new(LruCache::new(MAX_EXECUTE_CACHE_SIZE))),
+            policy: Box::new(DenyList::default()),
+            pending_state: Default::default(),
+            pending_log: Default::default(),
+            pending_version: 0,
+            pending_outdated: false,
+            execution_lock: Default::default(),
+            events: Default::default(),
+            limits: Default::default(),
+            storage_limits: Default::default(),
+            rejections: Default::default(),
+            dev_deposits: None,
+            clock: Arc::new(SystemClock),
+            dev_clock: None,
         };
 
         // set tip
@@ -146,100 +751,505 @@ impl MemPool {
             .map_err(|err| anyhow!("err: {}", err))
     }
 
+    /// Changes of the pending txs & withdrawals,
+    /// they are on top of the state of `state_db_version`.
+    /// Right after a new tip, they may not be recomputed on the new tip yet
+    pub fn pending_state(&self) -> (StateDBVersion, OverlayChanges) {
+        (self.state_db_version.clone(), self.pending_state.clone())
+    }
+
+    /// Take the events emitted since the last call
+    pub fn drain_events(&mut self) -> Vec<MemPoolEvent> {
+        self.events.drain(..).collect()
+    }
+
+    pub fn set_admission_policy(&mut self, policy: Box<dyn AdmissionPolicy>) {
+        self.policy = policy;
+    }
+
+    pub fn generator(&self) -> &Generator {
+        &self.generator
+    }
+
+    pub fn set_limits(&mut self, limits: MemPoolLimitsConfig) {
+        self.limits = limits;
+    }
+
+    pub fn set_storage_limits(&mut self, storage_limits: StorageLimitsConfig) {
+        self.storage_limits = storage_limits;
+    }
+
+    /// Accept synthetic deposits and control the time, see `crate::dev` and `crate::clock`
+    pub fn enable_dev_mode(&mut self) {
+        self.dev_deposits.get_or_insert_with(Default::default);
+        if self.dev_clock.is_none() {
+            let dev_clock = Arc::new(DevClock::default());
+            self.clock = dev_clock.clone();
+            self.dev_clock = Some(dev_clock);
+        }
+    }
+
+    pub fn is_dev_mode(&self) -> bool {
+        self.dev_deposits.is_some()
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Timestamp of the next block in milliseconds, read from the clock.
+    /// It doesn't go backwards if the clock is behind the parent block
+    pub fn next_block_timestamp(&self, parent_timestamp: u64) -> u64 {
+        max(self.clock.now_millis(), parent_timestamp)
+    }
+
+    /// None if the dev mode isn't enabled
+    pub fn dev_clock(&self) -> Option<&Arc<DevClock>> {
+        self.dev_clock.as_ref()
+    }
+
+    /// None if the dev mode isn't enabled
+    pub fn dev_deposits(&mut self) -> Option<&mut DevDeposits> {
+        self.dev_deposits.as_mut()
+    }
+
+    /// Queue a synthetic deposit into the next block, only in dev mode
+    pub fn push_dev_deposit(&mut self, request: DepositionRequest) -> Result<()> {
+        let rollup_context = self.generator.rollup_context();
+        rollup_context.verify_deposit_amounts(&request)?;
+        rollup_context
+            .script_args
+            .verify(&rollup_context.rollup_script_hash, &request.script())?;
+        let dev_deposits = self
+            .dev_deposits
+            .as_mut()
+            .ok_or_else(|| anyhow!("dev mode isn't enabled"))?;
+        dev_deposits.push(request);
+        Ok(())
+    }
+
+    pub fn stats(&self) -> MemPoolStats {
+        MemPoolStats {
+            pending_accounts: self.pending.len(),
+            txs: self.all_txs.len(),
+            tx_bytes: self.all_txs.values().map(|tx| tx.as_slice().len()).sum(),
+            withdrawals: self.all_withdrawals.len(),
+            rejections: self.rejections.clone(),
+        }
+    }
+
     /// Push a layer2 tx into pool
+    ///
+    /// A tx with the nonce of a pending tx replaces it if the gas price is at least
+    /// `MIN_REPLACEMENT_PRICE_BUMP` percent higher, the later txs of the account are pushed again
+    /// on top of the new tx.
+    ///
+    /// Once the pool exceeds the limits, the txs paying the lowest gas price are evicted,
+    /// the tx is rejected if it's the one to evict. A tx which would be evicted first is
+    /// rejected before it's executed.
     pub fn push_transaction(&mut self, tx: L2Transaction) -> Result<()> {
-        // check duplication
-        let tx_hash: H256 = tx.raw().hash().into();
+        self.refresh_pending_state()?;
+        self.check_new_transaction(&tx)?;
+
+        // Check replace-by-fee
+        let tx_hash: H256 = tx.hash().into();
+        let replaced_txs = match self.take_replaced_txs(&tx) {
+            Ok(replaced_txs) => replaced_txs,
+            Err(err) => return Err(self.reject(REJECT_UNDERPRICED, err)),
+        };
+        let is_replacement = !replaced_txs.is_empty();
+        if is_replacement {
+            self.replace_transaction(tx, replaced_txs)?;
+        } else {
+            self.check_account_limit(&tx)?;
+            self.check_pool_capacity(&tx)?;
+            if let Err(err) = self.push_executable_transaction(tx) {
+                return Err(self.reject(REJECT_INVALID, err));
+            }
+        }
+        self.evict_for_new_transaction(tx_hash, is_replacement)
+    }
+
+    /// Push a tx like `push_transaction`, but the tx is verified and executed without
+    /// holding the mem-pool lock, so a slow tx doesn't block the block producer and the RPC.
+    /// A tx replacing a pending tx is pushed under the lock
+    pub fn push_transaction_with_lock(mem_pool: &Mutex<MemPool>, tx: L2Transaction) -> Result<()> {
+        let execution_lock = Arc::clone(&mem_pool.lock().execution_lock);
+        let _execution_guard = execution_lock.lock();
+        let prepared = mem_pool.lock().prepare_transaction(&tx)?;
+        let prepared = match prepared {
+            Some(prepared) => prepared,
+            None => return mem_pool.lock().push_transaction(tx),
+        };
+        let executor = &prepared.executor;
+        let result = executor
+            .verify_transaction(&prepared.pending_state, &tx)
+            .and_then(|_| executor.execute_transaction(&prepared.pending_state, &tx));
+
+        let mut mem_pool = mem_pool.lock();
+        if mem_pool.pending_version != prepared.pending_version {
+            // the pending state is changed by a caller without the execution lock
+            return mem_pool.push_transaction(tx);
+        }
+        let changes = match result {
+            Ok(changes) => changes,
+            Err(err) => return Err(mem_pool.reject(REJECT_INVALID, err)),
+        };
+        let tx_hash: H256 = tx.hash().into();
+        mem_pool.push_pending_entry(PendingItem::Transaction(tx.clone()), changes);
+        mem_pool.insert_pending_transaction(tx);
+        mem_pool.evict_for_new_transaction(tx_hash, false)
+    }
+
+    /// Check the tx before it's executed without the mem-pool lock,
+    /// returns `None` if the tx replaces a pending tx
+    fn prepare_transaction(&mut self, tx: &L2Transaction) -> Result<Option<PreparedTransaction>> {
+        self.refresh_pending_state()?;
+        self.check_new_transaction(tx)?;
+        let account_id: u32 = tx.raw().from_id().unpack();
+        let tx_nonce: u32 = tx.raw().nonce().unpack();
+        let is_replacement = self.pending.get(&account_id).map_or(false, |entry_list| {
+            entry_list.txs.iter().any(|pending_tx| {
+                let nonce: u32 = pending_tx.raw().nonce().unpack();
+                nonce == tx_nonce
+            })
+        });
+        if is_replacement {
+            return Ok(None);
+        }
+        self.check_account_limit(tx)?;
+        self.check_pool_capacity(tx)?;
+        Ok(Some(PreparedTransaction {
+            executor: self.pending_executor()?,
+            pending_state: self.pending_state.clone(),
+            pending_version: self.pending_version,
+        }))
+    }
+
+    /// Check duplication and the admission policy
+    fn check_new_transaction(&mut self, tx: &L2Transaction) -> Result<()> {
+        let tx_hash: H256 = tx.hash().into();
         if self.all_txs.contains_key(&tx_hash) {
-            return Err(anyhow!("duplicated tx"));
+            return Err(self.reject(REJECT_DUPLICATED, anyhow!("duplicated tx")));
+        }
+        if let Err(err) = self.check_admission_policy(tx) {
+            return Err(self.reject(REJECT_DENIED, err));
         }
+        Ok(())
+    }
+
+    fn check_account_limit(&mut self, tx: &L2Transaction) -> Result<()> {
+        let account_id: u32 = tx.raw().from_id().unpack();
+        let account_tx_count = self.pending.get(&account_id).map_or(0, |e| e.txs.len());
+        if account_tx_count >= self.limits.max_txs_per_account {
+            let err = anyhow!(
+                "too many txs of the account in the pool, max: {}",
+                self.limits.max_txs_per_account
+            );
+            return Err(self.reject(REJECT_ACCOUNT_LIMIT, err));
+        }
+        Ok(())
+    }
 
+    /// Reject a new tx without executing it if the pool is full and the tx pays a lower gas
+    /// price than every evictable tx, it would be the first to evict
+    fn check_pool_capacity(&mut self, tx: &L2Transaction) -> Result<()> {
+        let tx_bytes: usize = self.all_txs.values().map(|tx| tx.as_slice().len()).sum();
+        if self.all_txs.len() < self.limits.max_txs
+            && tx_bytes + tx.as_slice().len() <= self.limits.max_tx_bytes
+        {
+            return Ok(());
+        }
+        // the tx would be the last tx of its account, the previous one isn't evictable
+        let account_id: u32 = tx.raw().from_id().unpack();
+        let lowest_price = self
+            .pending
+            .iter()
+            .filter(|(&pending_account_id, _)| pending_account_id != account_id)
+            .filter_map(|(_, entry)| entry.txs.last())
+            .map(tx_gas_price)
+            .min();
+        match lowest_price {
+            Some(lowest_price) if tx_gas_price(tx) >= lowest_price => Ok(()),
+            _ => {
+                let err = anyhow!("the mem-pool is full, the gas price is too low");
+                Err(self.reject(REJECT_POOL_FULL, err))
+            }
+        }
+    }
+
+    /// Evict the txs paying the lowest gas price if the pool is full,
+    /// the new tx is rejected if it's evicted.
+    /// A replacement tx is never evicted, the tx it replaced is already dropped
+    fn evict_for_new_transaction(&mut self, tx_hash: H256, is_replacement: bool) -> Result<()> {
+        let protected_tx_hash = if is_replacement { Some(tx_hash) } else { None };
+        let mut is_evicted = false;
+        for evicted_tx_hash in self.evict_over_limits(protected_tx_hash)? {
+            if evicted_tx_hash == tx_hash {
+                is_evicted = true;
+                continue;
+            }
+            self.push_event(MemPoolEvent::TransactionDropped {
+                tx_hash: evicted_tx_hash,
+                reason: "evicted from the full mem-pool".to_string(),
+            });
+        }
+        if is_evicted {
+            let err = anyhow!("the mem-pool is full, the gas price is too low");
+            return Err(self.reject(REJECT_POOL_FULL, err));
+        }
+        Ok(())
+    }
+
+    /// Push `tx` which replaces the first of `replaced_txs`, the replaced txs are restored
+    /// if `tx` is rejected
+    fn replace_transaction(
+        &mut self,
+        tx: L2Transaction,
+        replaced_txs: Vec<L2Transaction>,
+    ) -> Result<()> {
+        let tx_hash: H256 = tx.hash().into();
+        let replaced_tx_hash: H256 = replaced_txs[0].hash().into();
+        if let Err(err) = self.push_executable_transaction(tx.clone()) {
+            // restore the replaced txs
+            let account_id: u32 = tx.raw().from_id().unpack();
+            for replaced_tx in &replaced_txs {
+                self.all_txs
+                    .insert(replaced_tx.hash().into(), replaced_tx.clone());
+            }
+            let entry_list = self.pending.entry(account_id).or_default();
+            entry_list.txs.extend(replaced_txs.clone());
+            let executor = self.pending_executor()?;
+            let items = replaced_txs
+                .into_iter()
+                .map(PendingItem::Transaction)
+                .collect();
+            self.apply_pending_items(&executor, items);
+            return Err(self.reject(REJECT_INVALID, err));
+        }
+        self.tx_arrivals.remove(&replaced_tx_hash);
+        let mut later_txs = replaced_txs.into_iter().skip(1);
+        while let Some(later_tx) = later_txs.next() {
+            if let Err(err) = self.push_executable_transaction(later_tx.clone()) {
+                // the rest txs are non-executable without this one
+                for dropped_tx in std::iter::once(later_tx).chain(later_txs.by_ref()) {
+                    let dropped_tx_hash: H256 = dropped_tx.hash().into();
+                    self.tx_arrivals.remove(&dropped_tx_hash);
+                    self.push_event(MemPoolEvent::TransactionDropped {
+                        tx_hash: dropped_tx_hash,
+                        reason: format!("a previous tx is replaced: {}", err),
+                    });
+                }
+            }
+        }
+        self.push_event(MemPoolEvent::TransactionReplaced {
+            tx_hash,
+            replaced_tx_hash,
+        });
+        Ok(())
+    }
+
+    /// Count the rejected tx
+    fn reject(&mut self, reason: &'static str, err: anyhow::Error) -> anyhow::Error {
+        *self.rejections.entry(reason).or_default() += 1;
+        err
+    }
+
+    /// Evict txs until the pool fits in the limits, returns the evicted txs.
+    /// Only the last tx of an account can be evicted, the previous txs don't depend on it.
+    /// The tx paying the lowest gas price is evicted first, the older one on a tie.
+    /// The protected tx and the previous txs of its account are not evicted
+    fn evict_over_limits(&mut self, protected_tx_hash: Option<H256>) -> Result<Vec<H256>> {
+        let mut evicted = Vec::new();
+        let mut tx_bytes: usize = self.all_txs.values().map(|tx| tx.as_slice().len()).sum();
+        while self.all_txs.len() > self.limits.max_txs || tx_bytes > self.limits.max_tx_bytes {
+            let tx_arrivals = &self.tx_arrivals;
+            let account_id = self
+                .pending
+                .iter()
+                .filter_map(|(&account_id, entry)| entry.txs.last().map(|tx| (account_id, tx)))
+                .filter(|(_, tx)| Some(H256::from(tx.hash())) != protected_tx_hash)
+                .min_by_key(|(_, tx)| {
+                    let tx_hash: H256 = tx.hash().into();
+                    (tx_gas_price(tx), tx_arrivals.get(&tx_hash).copied())
+                })
+                .map(|(account_id, _)| account_id);
+            let account_id = match account_id {
+                Some(account_id) => account_id,
+                None => break,
+            };
+            let entry_list = self.pending.get_mut(&account_id).expect("pending entry");
+            let tx = entry_list.txs.pop().expect("pending tx");
+            if entry_list.is_empty() {
+                self.pending.remove(&account_id);
+            }
+            let tx_hash: H256 = tx.hash().into();
+            self.all_txs.remove(&tx_hash);
+            self.tx_arrivals.remove(&tx_hash);
+            tx_bytes -= tx.as_slice().len();
+            evicted.push(tx_hash);
+        }
+        if !evicted.is_empty() {
+            self.rollback_pending_state()?;
+        }
+        Ok(evicted)
+    }
+
+    /// Drop the txs which stay in the pool longer than the TTL,
+    /// the later txs of the account are dropped too
+    fn expire_transactions(&mut self) {
+        if self.limits.tx_ttl_secs == 0 {
+            return;
+        }
+        let ttl = Duration::from_secs(self.limits.tx_ttl_secs);
+        let now = self.clock.now();
+        let mut expired_txs = Vec::new();
+        for entry_list in self.pending.values_mut() {
+            let tx_arrivals = &self.tx_arrivals;
+            let index = entry_list.txs.iter().position(|tx| {
+                let tx_hash: H256 = tx.hash().into();
+                tx_arrivals
+                    .get(&tx_hash)
+                    .map_or(false, |&arrival| now.saturating_sub(arrival) >= ttl)
+            });
+            if let Some(index) = index {
+                expired_txs.extend(entry_list.txs.split_off(index));
+            }
+        }
+        self.pending.retain(|_, entry_list| !entry_list.is_empty());
+        for tx in expired_txs {
+            let tx_hash: H256 = tx.hash().into();
+            self.all_txs.remove(&tx_hash);
+            self.tx_arrivals.remove(&tx_hash);
+            self.push_event(MemPoolEvent::TransactionDropped {
+                tx_hash,
+                reason: "expired".to_string(),
+            });
+        }
+    }
+
+    /// Verify & execute the tx on the pending state, then add it to the pool
+    fn push_executable_transaction(&mut self, tx: L2Transaction) -> Result<()> {
         // basic verification
         self.basic_verify_tx(&tx)?;
 
-        // remove under price tx if pool is full
-        if self.all_txs.len() >= MAX_IN_POOL_TXS {
-            //TODO
-            return Err(anyhow!(
-                "Too many txs in the pool! MAX_IN_POOL_TXS: {}",
-                MAX_IN_POOL_TXS
-            ));
-        }
-        // Check replace-by-fee
-        // TODO
+        // execute on the pending state, the tx is rejected if it fails
+        self.apply_pending_transaction(&tx)?;
 
-        // Add to pool
-        // TODO check nonce conflict
+        self.insert_pending_transaction(tx);
+        Ok(())
+    }
+
+    /// Add the tx applied to the pending state to the pool
+    fn insert_pending_transaction(&mut self, tx: L2Transaction) {
+        let tx_hash: H256 = tx.hash().into();
         self.all_txs.insert(tx_hash, tx.clone());
+        let now = self.clock.now();
+        self.tx_arrivals.entry(tx_hash).or_insert(now);
         let account_id: u32 = tx.raw().from_id().unpack();
         let entry_list = self.pending.entry(account_id).or_default();
         entry_list.txs.push(tx);
-        Ok(())
     }
 
-    /// Basic verification for tx
-    fn basic_verify_tx(&self, tx: &L2Transaction) -> Result<()> {
-        // check tx size
-        if tx.as_slice().len() > MAX_TX_SIZE {
-            return Err(anyhow!("tx over size"));
-        }
-
-        let db = self.store.begin_transaction();
-        let state_db = self.fetch_state_db(&db)?;
-
-        // TODO
-        // we should introduce queue manchanism and only remove tx when tx.nonce is lower
-        // reject tx if nonce is not equals account.nonce
-        let state = state_db.account_state_tree()?;
+    /// Remove the pending tx which has the same nonce as `tx` and the later txs of the account,
+    /// they are removed from the pending state.
+    /// returns an empty list if `tx` doesn't replace a pending tx,
+    /// an error if it pays less than the replacement price
+    fn take_replaced_txs(&mut self, tx: &L2Transaction) -> Result<Vec<L2Transaction>> {
         let account_id: u32 = tx.raw().from_id().unpack();
-        let nonce = state.get_nonce(account_id)?;
         let tx_nonce: u32 = tx.raw().nonce().unpack();
-        if nonce != tx_nonce {
+        let entry_list = match self.pending.get_mut(&account_id) {
+            Some(entry_list) => entry_list,
+            None => return Ok(Vec::new()),
+        };
+        let index = match entry_list.txs.iter().position(|pending_tx| {
+            let nonce: u32 = pending_tx.raw().nonce().unpack();
+            nonce == tx_nonce
+        }) {
+            Some(index) => index,
+            None => return Ok(Vec::new()),
+        };
+        let old_price = tx_gas_price(&entry_list.txs[index]);
+        let new_price = tx_gas_price(tx);
+        let min_price = old_price.saturating_mul(100 + MIN_REPLACEMENT_PRICE_BUMP) / 100;
+        if new_price <= old_price || new_price < min_price {
             return Err(anyhow!(
-                "tx's nonce is incorrect, expected: {} got: {}",
-                nonce,
-                tx_nonce,
+                "replacement tx underpriced, pending gas price: {}, min replacement gas price: {}",
+                old_price,
+                min_price.max(old_price.saturating_add(1)),
             ));
         }
+        let replaced_txs = entry_list.txs.split_off(index);
+        for replaced_tx in &replaced_txs {
+            self.all_txs.remove(&H256::from(replaced_tx.hash()));
+        }
+        self.rollback_pending_state()?;
+        Ok(replaced_txs)
+    }
 
-        // verify signature
-        self.generator.check_transaction_signature(&state, &tx)?;
-
+    /// Denied txs are logged for auditing
+    fn check_admission_policy(&self, tx: &L2Transaction) -> Result<()> {
+        let db = self.store.begin_transaction();
+        let state_db = self.fetch_state_db(&db)?;
+        let state = state_db.account_state_tree()?;
+        let from_id: u32 = tx.raw().from_id().unpack();
+        let to_id: u32 = tx.raw().to_id().unpack();
+        let ctx = AdmissionContext {
+            tx_hash: tx.hash().into(),
+            from_id,
+            to_id,
+            from_script_hash: state.get_script_hash(from_id)?,
+            to_script_hash: state.get_script_hash(to_id)?,
+        };
+        if let Err(reason) = self.policy.check(&ctx) {
+            eprintln!(
+                "MemPool: deny tx {:?} from {} to {}: {}",
+                ctx.tx_hash, from_id, to_id, reason
+            );
+            return Err(anyhow!("tx is denied: {}", reason));
+        }
         Ok(())
     }
 
+    /// Basic verification for tx
+    fn basic_verify_tx(&self, tx: &L2Transaction) -> Result<()> {
+        self.pending_executor()?
+            .verify_transaction(&self.pending_state, tx)
+    }
+
     /// Execute tx without push it into pool
     pub fn execute_transaction(
         &self,
         tx: L2Transaction,
         block_info: &BlockInfo,
     ) -> Result<RunResult> {
-        let db = self.store.begin_transaction();
-        let state_db = self.fetch_state_db(&db)?;
-        let state = state_db.account_state_tree()?;
-        let tip_block_hash = self.store.get_tip_block_hash()?;
-        let chain_view = ChainView::new(&db, tip_block_hash);
-        // verify tx signature
-        self.generator.check_transaction_signature(&state, &tx)?;
-        // tx basic verification
-        self.generator.verify_transaction(&state, &tx)?;
-        // execute tx
-        let raw_tx = tx.raw();
-        let run_result =
-            self.generator
-                .execute_transaction(&chain_view, &state, &block_info, &raw_tx)?;
-        Ok(run_result)
+        self.snapshot().execute_transaction(tx, block_info)
+    }
+
+    /// Snapshot of current state,
+    /// callers should release the mem-pool lock before using it
+    pub fn snapshot(&self) -> MemPoolSnapshot {
+        MemPoolSnapshot {
+            store: self.store.clone(),
+            generator: Arc::clone(&self.generator),
+            state_db_version: self.state_db_version.clone(),
+            execute_cache: Arc::clone(&self.execute_cache),
+            block_hash: None,
+            replay: None,
+        }
     }
 
     /// Push a withdrawal request into pool
     pub fn push_withdrawal_request(&mut self, withdrawal: WithdrawalRequest) -> Result<()> {
+        self.refresh_pending_state()?;
+
         // check withdrawal size
         if withdrawal.as_slice().len() > MAX_WITHDRAWAL_SIZE {
             return Err(anyhow!("withdrawal over size"));
         }
 
         // check duplication
-        let withdrawal_hash: H256 = withdrawal.raw().hash().into();
+        let withdrawal_hash: H256 = withdrawal.hash().into();
         if self.all_withdrawals.contains_key(&withdrawal_hash) {
             return Err(anyhow!("duplicated withdrawal"));
         }
@@ -269,6 +1279,7 @@ impl MemPool {
         let account_id = state
             .get_account_id_by_script_hash(&account_script_hash)?
             .expect("get account_id");
+        self.apply_pending_withdrawal(&withdrawal)?;
         let entry_list = self.pending.entry(account_id).or_default();
         entry_list.withdrawals.push(withdrawal);
         Ok(())
@@ -284,8 +1295,156 @@ impl MemPool {
             .check_withdrawal_request_signature(&state, withdrawal_request)?;
         // withdrawal basic verification
         self.generator
-            .verify_withdrawal_request(&state, withdrawal_request)
-            .map_err(Into::into)
+            .verify_withdrawal_request(&state, withdrawal_request)?;
+        // the min fee is a policy of the node, it isn't checked by the state transition
+        self.generator
+            .rollup_context()
+            .check_withdrawal_fee(withdrawal_request)
+            .map_err(gw_generator::Error::from)?;
+        Ok(())
+    }
+
+    /// Check whether the withdrawal can be packaged into the next block without pushing it,
+    /// the signature isn't checked so a withdrawal can be checked before signing.
+    ///
+    /// Custodian cells of unfinalized blocks can't be spent, the liquidity is the custodian
+    /// assets of the tip minus the deposits of the last `finality_blocks` blocks and the
+    /// withdrawals already in the pool.
+    pub fn withdrawal_feasibility(
+        &self,
+        withdrawal: &WithdrawalRequest,
+    ) -> Result<WithdrawalFeasibility> {
+        let raw = withdrawal.raw();
+        let capacity: u64 = raw.capacity().unpack();
+        let amount: u128 = raw.amount().unpack();
+        let sudt_script_hash: H256 = raw.sudt_script_hash().unpack();
+        let (available_capacity, available_amount) = self.available_custodian(&sudt_script_hash)?;
+        let fee_capacity: u64 = raw.fee().unpack();
+        let fee_amount: u128 = raw.sudt_fee().unpack();
+        let feasibility = |status, reason: Option<String>| WithdrawalFeasibility {
+            status,
+            reason,
+            available_capacity,
+            available_amount,
+            fee_capacity,
+            fee_amount,
+            charged_capacity: u128::from(capacity) + u128::from(fee_capacity),
+            charged_amount: amount.saturating_add(fee_amount),
+        };
+
+        if withdrawal.as_slice().len() > MAX_WITHDRAWAL_SIZE {
+            return Ok(feasibility(
+                WithdrawalStatus::Rejected,
+                Some("withdrawal over size".to_string()),
+            ));
+        }
+        if self.all_withdrawals.len() >= MAX_IN_POOL_WITHDRAWAL {
+            return Ok(feasibility(
+                WithdrawalStatus::Rejected,
+                Some(format!(
+                    "too many withdrawals in the pool, max: {}",
+                    MAX_IN_POOL_WITHDRAWAL
+                )),
+            ));
+        }
+
+        // verify against the pending state, so the nonce and balances left by the pending
+        // txs & withdrawals of the account are used
+        let db = self.store.begin_transaction();
+        let state_db = self.fetch_state_db(&db)?;
+        let tree = state_db.account_state_tree()?;
+        let state = OverlayState::new(&tree, self.pending_state.clone());
+        let verify_result = self
+            .generator
+            .verify_withdrawal_request(&state, withdrawal)
+            .and_then(|_| {
+                let rollup_context = self.generator.rollup_context();
+                rollup_context
+                    .check_withdrawal_fee(withdrawal)
+                    .map_err(Into::into)
+            });
+        if let Err(err) = verify_result {
+            return Ok(feasibility(
+                WithdrawalStatus::Rejected,
+                Some(format!("{}", err)),
+            ));
+        }
+
+        // a block takes at most one withdrawal of an account
+        let account_script_hash: H256 = raw.account_script_hash().unpack();
+        let account_id = state.get_account_id_by_script_hash(&account_script_hash)?;
+        let has_pending_withdrawals = account_id
+            .and_then(|id| self.pending.get(&id))
+            .map(|entry_list| !entry_list.withdrawals.is_empty())
+            .unwrap_or(false);
+        if has_pending_withdrawals {
+            return Ok(feasibility(
+                WithdrawalStatus::Queued,
+                Some("the account has pending withdrawals".to_string()),
+            ));
+        }
+        if capacity as u128 > available_capacity {
+            return Ok(feasibility(
+                WithdrawalStatus::Queued,
+                Some("insufficient finalized custodian capacity".to_string()),
+            ));
+        }
+        if amount > available_amount {
+            return Ok(feasibility(
+                WithdrawalStatus::Queued,
+                Some("insufficient finalized custodian sUDT".to_string()),
+            ));
+        }
+        Ok(feasibility(WithdrawalStatus::NextBlock, None))
+    }
+
+    /// Finalized custodian (capacity, sUDT amount) which isn't claimed by the pool
+    fn available_custodian(&self, sudt_script_hash: &H256) -> Result<(u128, u128)> {
+        let db = self.store.begin_transaction();
+        let mut capacity = db.get_custodian_asset(CKB_SUDT_SCRIPT_ARGS.into())?;
+        let mut amount = db.get_custodian_asset(*sudt_script_hash)?;
+        let is_sudt = *sudt_script_hash != CKB_SUDT_SCRIPT_ARGS.into();
+
+        let finality_blocks: u64 = self
+            .generator
+            .rollup_context()
+            .rollup_config
+            .finality_blocks()
+            .unpack();
+        let tip_number: u64 = db.get_tip_block()?.raw().number().unpack();
+        let finalized_number = tip_number.saturating_sub(finality_blocks);
+        for number in (finalized_number + 1)..=tip_number {
+            let block_hash = match db.get_block_hash_by_number(number)? {
+                Some(block_hash) => block_hash,
+                None => continue,
+            };
+            let deposits = db
+                .get_block_deposition_requests(&block_hash)?
+                .unwrap_or_default();
+            for deposit in deposits {
+                let deposit_capacity: u64 = deposit.capacity().unpack();
+                capacity = capacity.saturating_sub(deposit_capacity as u128);
+                if is_sudt && deposit.sudt_script_hash().as_slice() == sudt_script_hash.as_slice() {
+                    let deposit_amount: u128 = deposit.amount().unpack();
+                    amount = amount.saturating_sub(deposit_amount);
+                }
+            }
+        }
+
+        for withdrawal in self.all_withdrawals.values() {
+            let raw = withdrawal.raw();
+            let withdrawal_capacity: u64 = raw.capacity().unpack();
+            capacity = capacity.saturating_sub(withdrawal_capacity as u128);
+            if is_sudt && raw.sudt_script_hash().as_slice() == sudt_script_hash.as_slice() {
+                let withdrawal_amount: u128 = raw.amount().unpack();
+                amount = amount.saturating_sub(withdrawal_amount);
+            }
+        }
+        if !is_sudt {
+            // CKB is withdrawn by the capacity field
+            amount = 0;
+        }
+        Ok((capacity, amount))
     }
 
     /// Return pending contents
@@ -293,9 +1452,220 @@ impl MemPool {
         &self.pending
     }
 
+    /// Txs & withdrawals to package into the next block.
+    /// either txs or withdrawals are taken from an entry to avoid nonce conflict,
+    /// withdrawals are in the order of account id.
+    ///
+    /// Txs are taken in the order of gas price, the txs of an account are kept in the order
+    /// of nonce, so an account's next tx only competes with others once its previous one is taken.
+    /// Txs with the same gas price are taken in the order of account id.
+    pub fn package(&self) -> (Vec<L2Transaction>, Vec<WithdrawalRequest>) {
+        let block_limits = self.generator.block_limits();
+        let mut withdrawals = Vec::new();
+        // (gas price, account id, index of the tx in the entry)
+        let mut queue = BinaryHeap::new();
+        let mut account_ids: Vec<u32> = self.pending.keys().copied().collect();
+        account_ids.sort_unstable();
+        for account_id in account_ids {
+            let entry = &self.pending[&account_id];
+            if let Some(withdrawal) = entry.withdrawals.first() {
+                withdrawals.push(withdrawal.clone());
+            } else if let Some(tx) = entry.txs.first() {
+                queue.push((tx_gas_price(tx), Reverse(account_id), 0));
+            }
+        }
+
+        let mut txs = Vec::new();
+        let mut block_gas = 0u64;
+        while let Some((_, Reverse(account_id), index)) = queue.pop() {
+            if txs.len() >= block_limits.max_tx_count as usize {
+                break;
+            }
+            let entry_txs = &self.pending[&account_id].txs;
+            let tx = &entry_txs[index];
+            // the later txs of the account are skipped too, they depend on this one's nonce
+            match block_gas.checked_add(tx_gas_limit(&tx.raw())) {
+                Some(gas) if gas <= block_limits.max_block_gas => block_gas = gas,
+                _ => continue,
+            }
+            txs.push(tx.clone());
+            if let Some(next_tx) = entry_txs.get(index + 1) {
+                queue.push((tx_gas_price(next_tx), Reverse(account_id), index + 1));
+            }
+        }
+        (txs, withdrawals)
+    }
+
+    /// Block info of the next block, it's used to execute txs on the pending state
+    fn next_block_info(&self) -> Result<BlockInfo> {
+        let raw_block = self.store.get_tip_block()?.raw();
+        let number: u64 = raw_block.number().unpack();
+        let timestamp = self.next_block_timestamp(raw_block.timestamp().unpack());
+        let block_info = BlockInfo::new_builder()
+            .block_producer_id(raw_block.block_producer_id())
+            .timestamp(timestamp.pack())
+            .number(number.saturating_add(1).pack())
+            .build();
+        Ok(block_info)
+    }
+
+    /// Executor of txs & withdrawals on the pending state
+    fn pending_executor(&self) -> Result<PendingExecutor> {
+        Ok(PendingExecutor {
+            store: self.store.clone(),
+            generator: Arc::clone(&self.generator),
+            state_db_version: self.state_db_version.clone(),
+            tip_block_hash: self.store.get_tip_block_hash()?,
+            block_info: self.next_block_info()?,
+            storage_limits: self.storage_limits.clone(),
+        })
+    }
+
+    /// Execute the tx on the pending state and apply the result and the fee
+    fn apply_pending_transaction(&mut self, tx: &L2Transaction) -> Result<()> {
+        let changes = self
+            .pending_executor()?
+            .execute_transaction(&self.pending_state, tx)?;
+        self.push_pending_entry(PendingItem::Transaction(tx.clone()), changes);
+        Ok(())
+    }
+
+    /// Apply the withdrawal to the pending state,
+    /// the pending state is kept if the withdrawal can't be applied
+    fn apply_pending_withdrawal(&mut self, withdrawal: &WithdrawalRequest) -> Result<()> {
+        let executor = self.pending_executor()?;
+        self.apply_pending_items(&executor, vec![PendingItem::Withdrawal(withdrawal.clone())]);
+        Ok(())
+    }
+
+    fn apply_pending_items(&mut self, executor: &PendingExecutor, items: Vec<PendingItem>) {
+        executor.apply_items(&mut self.pending_state, &mut self.pending_log, items);
+        self.pending_version += 1;
+    }
+
+    fn push_pending_entry(&mut self, item: PendingItem, changes: OverlayChanges) {
+        self.pending_state.merge(changes.clone());
+        self.pending_log.push(PendingEntry { item, changes });
+        self.pending_version += 1;
+    }
+
+    fn set_pending_state(&mut self, pending_state: OverlayChanges, pending_log: Vec<PendingEntry>) {
+        self.pending_state = pending_state;
+        self.pending_log = pending_log;
+        self.pending_version += 1;
+        self.pending_outdated = false;
+    }
+
+    /// Remove the txs & withdrawals which left the pool from the pending state,
+    /// only the ones applied after the first removed one are executed again
+    fn rollback_pending_state(&mut self) -> Result<()> {
+        let index = self
+            .pending_log
+            .iter()
+            .position(|entry| !self.contains_item(&entry.item));
+        let index = match index {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+        let later_entries = self.pending_log.split_off(index);
+        let mut pending_state = OverlayChanges::default();
+        for entry in &self.pending_log {
+            pending_state.merge(entry.changes.clone());
+        }
+        self.pending_state = pending_state;
+        let items = later_entries
+            .into_iter()
+            .map(|entry| entry.item)
+            .filter(|item| self.contains_item(item))
+            .collect();
+        let executor = self.pending_executor()?;
+        self.apply_pending_items(&executor, items);
+        Ok(())
+    }
+
+    fn contains_item(&self, item: &PendingItem) -> bool {
+        match item {
+            PendingItem::Transaction(tx) => self.all_txs.contains_key(&H256::from(tx.hash())),
+            PendingItem::Withdrawal(withdrawal) => self
+                .all_withdrawals
+                .contains_key(&H256::from(withdrawal.hash())),
+        }
+    }
+
+    /// Txs & withdrawals in the pool, in the order of account id
+    fn pending_items(&self) -> Vec<PendingItem> {
+        let mut account_ids: Vec<u32> = self.pending.keys().copied().collect();
+        account_ids.sort_unstable();
+        let mut items = Vec::new();
+        for account_id in account_ids {
+            let entry = &self.pending[&account_id];
+            let withdrawals = entry.withdrawals.iter().cloned();
+            items.extend(withdrawals.map(PendingItem::Withdrawal));
+            items.extend(entry.txs.iter().cloned().map(PendingItem::Transaction));
+        }
+        items
+    }
+
+    /// Recompute the pending state on top of the current state,
+    /// txs & withdrawals which fail are skipped, they are left to `demote_unexecutables`
+    fn recompute_pending_state(&mut self) -> Result<()> {
+        let executor = self.pending_executor()?;
+        let mut pending_state = OverlayChanges::default();
+        let mut pending_log = Vec::new();
+        executor.apply_items(&mut pending_state, &mut pending_log, self.pending_items());
+        self.set_pending_state(pending_state, pending_log);
+        Ok(())
+    }
+
+    /// Recompute the pending state if it isn't recomputed on the new tip yet
+    fn refresh_pending_state(&mut self) -> Result<()> {
+        if self.pending_outdated {
+            self.recompute_pending_state()?;
+        }
+        Ok(())
+    }
+
+    /// Reload the backends of the generator, the cached results and the pending state of the
+    /// old backends are discarded
+    pub fn reload_backends(&mut self, configs: &[BackendConfig]) -> Result<()> {
+        self.generator.reload_backends(configs)?;
+        self.execute_cache.lock().clear();
+        self.recompute_pending_state()
+    }
+
     /// Notify new tip
     /// this method update current state of mem pool
     pub fn notify_new_tip(&mut self, new_tip: H256) -> Result<()> {
+        self.update_tip(new_tip)?;
+        self.recompute_pending_state()
+    }
+
+    /// Notify new tip like `notify_new_tip`, but the pending state is recomputed without
+    /// holding the mem-pool lock. Txs pushed with `push_transaction_with_lock` wait for it,
+    /// the other writers recompute it under the lock instead
+    pub fn notify_new_tip_with_lock(mem_pool: &Mutex<MemPool>, new_tip: H256) -> Result<()> {
+        let execution_lock = Arc::clone(&mem_pool.lock().execution_lock);
+        let _execution_guard = execution_lock.lock();
+        let (executor, items, pending_version) = {
+            let mut mem_pool = mem_pool.lock();
+            mem_pool.update_tip(new_tip)?;
+            let executor = mem_pool.pending_executor()?;
+            (executor, mem_pool.pending_items(), mem_pool.pending_version)
+        };
+        let mut pending_state = OverlayChanges::default();
+        let mut pending_log = Vec::new();
+        executor.apply_items(&mut pending_state, &mut pending_log, items);
+
+        let mut mem_pool = mem_pool.lock();
+        // otherwise the pending state is already recomputed by another writer
+        if mem_pool.pending_version == pending_version {
+            mem_pool.set_pending_state(pending_state, pending_log);
+        }
+        Ok(())
+    }
+
+    /// Reset the pool on the new tip, the pending state is recomputed later
+    fn update_tip(&mut self, new_tip: H256) -> Result<()> {
         // reset pool state
         self.reset(self.current_tip, Some(new_tip))?;
         self.current_tip = Some(new_tip);
@@ -303,6 +1673,9 @@ impl MemPool {
         self.promote_executables(self.pending.iter())?;
         // try demote unexecutables, this function also discards objects that already in the chain
         self.demote_unexecutables()?;
+        self.expire_transactions();
+        self.pending_outdated = true;
+        self.pending_version += 1;
         Ok(())
     }
 
@@ -319,6 +1692,9 @@ impl MemPool {
         let state_db = self.fetch_state_db(&db)?;
         let state = state_db.account_state_tree()?;
         let mut remove_list = Vec::default();
+        let mut dropped_withdrawals = Vec::default();
+        let generator = &self.generator;
+        let block_producer_id: u32 = self.next_block_info()?.block_producer_id().unpack();
         // iter pending accounts and demote any non-executable objects
         for (&account_id, list) in &mut self.pending {
             let nonce = state.get_nonce(account_id)?;
@@ -328,15 +1704,43 @@ impl MemPool {
             for tx in deprecated_txs {
                 let tx_hash = tx.hash().into();
                 self.all_txs.remove(&tx_hash);
+                self.tx_arrivals.remove(&tx_hash);
             }
-            // Drop all withdrawals that are have no enough balance
-            let capacity = state.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, account_id)?;
-            let deprecated_withdrawals =
-                list.remove_lower_nonce_balance_withdrawals(nonce, capacity);
+            // drop withdrawals if withdrawal.nonce lower than nonce
+            let deprecated_withdrawals = list.remove_lower_nonce_withdrawals(nonce);
             for withdrawal in deprecated_withdrawals {
                 let withdrawal_hash: H256 = withdrawal.hash().into();
                 self.all_withdrawals.remove(&withdrawal_hash);
             }
+            // revalidate the rest withdrawals in order against the new tip,
+            // the first invalid withdrawal and the followings are dropped
+            let mut account_state = OverlayState::new(&state, Default::default());
+            let mut invalid = None;
+            for (i, withdrawal) in list.withdrawals.iter().enumerate() {
+                let result = generator
+                    .verify_withdrawal_request(&account_state, withdrawal)
+                    .map_err(|err| format!("{:?}", err))
+                    .and_then(|_| {
+                        account_state
+                            .apply_withdrawal_request(
+                                generator.rollup_context(),
+                                block_producer_id,
+                                withdrawal,
+                            )
+                            .map_err(|err| format!("{:?}", err))
+                    });
+                if let Err(reason) = result {
+                    invalid = Some((i, reason));
+                    break;
+                }
+            }
+            if let Some((index, reason)) = invalid {
+                for withdrawal in list.withdrawals.split_off(index) {
+                    let withdrawal_hash: H256 = withdrawal.hash().into();
+                    self.all_withdrawals.remove(&withdrawal_hash);
+                    dropped_withdrawals.push((withdrawal_hash, reason.clone()));
+                }
+            }
             // Delete empty entry
             if list.is_empty() {
                 remove_list.push(account_id);
@@ -345,9 +1749,23 @@ impl MemPool {
         for account_id in remove_list {
             self.pending.remove(&account_id);
         }
+        for (withdrawal_hash, reason) in dropped_withdrawals {
+            eprintln!("MemPool: drop withdrawal {:?}: {}", withdrawal_hash, reason);
+            self.push_event(MemPoolEvent::WithdrawalDropped {
+                withdrawal_hash,
+                reason,
+            });
+        }
         Ok(())
     }
 
+    fn push_event(&mut self, event: MemPoolEvent) {
+        if self.events.len() >= MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
     /// Reset
     /// this method reset the current state of the mem pool
     /// discarded txs & withdrawals will be reinject to pool
@@ -423,6 +1841,8 @@ impl MemPool {
         // update current state
         let tip_block_hash = new_tip_block.hash().into();
         self.state_db_version = StateDBVersion::from_block_hash(tip_block_hash);
+        self.execute_cache.lock().clear();
+        self.set_pending_state(Default::default(), Vec::new());
 
         // re-inject txs
         for tx in reinject_txs {
@@ -439,3 +1859,21 @@ impl MemPool {
         Ok(())
     }
 }
+
+fn tx_gas_price(tx: &L2Transaction) -> u128 {
+    tx.raw().gas_price().unpack()
+}
+
+/// Hash of a call, the nonce is excluded since it's verified before the execution
+fn build_call_hash(block_info: &BlockInfo, raw_tx: &RawL2Transaction) -> H256 {
+    let mut hasher = new_blake2b();
+    hasher.update(block_info.as_slice());
+    hasher.update(raw_tx.from_id().as_slice());
+    hasher.update(raw_tx.to_id().as_slice());
+    hasher.update(raw_tx.args().as_slice());
+    // the execution fails if it exceeds the gas limit
+    hasher.update(raw_tx.gas_limit().as_slice());
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash.into()
+}
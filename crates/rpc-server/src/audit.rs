@@ -0,0 +1,94 @@
+//! Audit log of state-mutating RPC calls
+//!
+//! Each audited request is appended to the log file as a JSON line:
+//!
+//! ```json
+//! {"timestamp": 1620000000000, "source": "127.0.0.1:53412", "method": "submit_l2transaction", "id": 1, "params_hash": "0x..."}
+//! ```
+//!
+//! `timestamp` is in milliseconds, `params_hash` is the blake2b hash of the params JSON text.
+//! `source` is the peer address, followed by the `X-Forwarded-For` header if any.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use gw_common::blake2b::new_blake2b;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+
+/// Methods that submit objects to the mem-pool
+const AUDITED_METHODS: &[&str] = &["submit_l2transaction", "submit_withdrawal_request"];
+/// Prefix of admin methods, all of them are audited
+const ADMIN_METHOD_PREFIX: &str = "gw_admin_";
+
+fn is_audited(method: &str) -> bool {
+    AUDITED_METHODS.contains(&method) || method.starts_with(ADMIN_METHOD_PREFIX)
+}
+
+fn params_hash(params: &Value) -> String {
+    let mut hasher = new_blake2b();
+    hasher.update(params.to_string().as_bytes());
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    format!("0x{}", faster_hex::hex_string(&hash).expect("hex"))
+}
+
+/// Append-only audit log, records nothing if disabled
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    file: Option<Arc<Mutex<File>>>,
+}
+
+impl AuditLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(AuditLog {
+            file: Some(Arc::new(Mutex::new(file))),
+        })
+    }
+
+    /// Record the audited requests of a single or batch JSONRPC request body,
+    /// unparsable bodies are rejected by the JSONRPC server so they are skipped here
+    pub fn record(&self, source: &str, body: &[u8]) {
+        let file = match self.file {
+            Some(ref file) => file,
+            None => return,
+        };
+        let requests = match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Array(requests)) => requests,
+            Ok(request) => vec![request],
+            Err(_) => return,
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let mut lines = String::new();
+        for request in requests {
+            let method = match request.get("method").and_then(Value::as_str) {
+                Some(method) if is_audited(method) => method,
+                _ => continue,
+            };
+            let params = request.get("params").cloned().unwrap_or(Value::Null);
+            let entry = json!({
+                "timestamp": timestamp,
+                "source": source,
+                "method": method,
+                "id": request.get("id").cloned().unwrap_or(Value::Null),
+                "params_hash": params_hash(&params),
+            });
+            lines.push_str(&entry.to_string());
+            lines.push('\n');
+        }
+        if lines.is_empty() {
+            return;
+        }
+        if let Err(err) = file.lock().write_all(lines.as_bytes()) {
+            eprintln!("Audit log write error: {}", err);
+        }
+    }
+}
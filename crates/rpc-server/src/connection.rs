@@ -0,0 +1,393 @@
+//! Connection limits of the JSONRPC server
+//!
+//! Accepted sockets are wrapped in `LimitedStream`, a read or write which stays pending
+//! longer than the configured timeout fails the connection, so slow clients can't hold
+//! connections forever. The number of connections and the concurrent requests of each
+//! connection are limited too.
+//!
+//! Calls of a client IP are rate limited by a token bucket shared by all its connections,
+//! each call of a batch and each WebSocket message takes a token.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use gw_config::RPCServerConfig;
+use gw_jsonrpc_types::godwoken::ConnectionStats;
+use hyper::server::accept::{self, Accept};
+use hyper::server::conn::{AddrIncoming, AddrStream};
+use parking_lot::Mutex;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::{sleep, Sleep};
+
+#[derive(Debug, Clone)]
+pub struct ConnectionLimits {
+    pub max_connections: usize,
+    pub max_concurrent_requests: usize,
+    pub read_timeout: Duration,
+    pub write_timeout: Duration,
+    pub max_batch_size: usize,
+    pub max_request_body_size: usize,
+    pub max_requests_per_second_per_ip: u32,
+}
+
+impl From<&RPCServerConfig> for ConnectionLimits {
+    fn from(config: &RPCServerConfig) -> Self {
+        ConnectionLimits {
+            max_connections: config.max_connections,
+            max_concurrent_requests: config.max_concurrent_requests,
+            read_timeout: Duration::from_millis(config.read_timeout_ms),
+            write_timeout: Duration::from_millis(config.write_timeout_ms),
+            max_batch_size: config.max_batch_size,
+            max_request_body_size: config.max_request_body_size,
+            max_requests_per_second_per_ip: config.max_requests_per_second_per_ip,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ConnectionMetrics {
+    active_connections: AtomicUsize,
+    accepted_connections: AtomicU64,
+    rejected_connections: AtomicU64,
+    rejected_requests: AtomicU64,
+    rate_limited_requests: AtomicU64,
+    read_timeouts: AtomicU64,
+    write_timeouts: AtomicU64,
+}
+
+impl ConnectionMetrics {
+    pub fn stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            active_connections: (self.active_connections.load(Ordering::Relaxed) as u64).into(),
+            accepted_connections: self.accepted_connections.load(Ordering::Relaxed).into(),
+            rejected_connections: self.rejected_connections.load(Ordering::Relaxed).into(),
+            rejected_requests: self.rejected_requests.load(Ordering::Relaxed).into(),
+            rate_limited_requests: self.rate_limited_requests.load(Ordering::Relaxed).into(),
+            read_timeouts: self.read_timeouts.load(Ordering::Relaxed).into(),
+            write_timeouts: self.write_timeouts.load(Ordering::Relaxed).into(),
+        }
+    }
+}
+
+/// Accept connections on the address, connections beyond the limit are closed at once
+pub fn bind(
+    listen_addr: &SocketAddr,
+    limits: ConnectionLimits,
+    metrics: Arc<ConnectionMetrics>,
+) -> Result<impl Accept<Conn = LimitedStream, Error = io::Error>> {
+    let mut incoming = AddrIncoming::bind(listen_addr)
+        .map_err(|err| anyhow!("bind JSONRPC listen address {}: {}", listen_addr, err))?;
+    let connections = futures::stream::poll_fn(move |cx| loop {
+        let stream = match Pin::new(&mut incoming).poll_accept(cx) {
+            Poll::Ready(Some(Ok(stream))) => stream,
+            Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Pending => return Poll::Pending,
+        };
+        let active = metrics.active_connections.fetch_add(1, Ordering::Relaxed);
+        if active >= limits.max_connections {
+            metrics.active_connections.fetch_sub(1, Ordering::Relaxed);
+            metrics.rejected_connections.fetch_add(1, Ordering::Relaxed);
+            // drop the stream to close the connection
+            continue;
+        }
+        metrics.accepted_connections.fetch_add(1, Ordering::Relaxed);
+        let stream = LimitedStream::new(stream, &limits, Arc::clone(&metrics));
+        return Poll::Ready(Some(Ok(stream)));
+    });
+    Ok(accept::from_stream(connections))
+}
+
+// Buckets of idle clients are full, a few buckets are checked and dropped if they are full
+// on each call, so the idle clients are expired without scanning all of them
+const EXPIRED_BUCKETS_PER_CHECK: usize = 2;
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+#[derive(Default)]
+struct Buckets {
+    buckets: HashMap<IpAddr, TokenBucket>,
+    /// the tracked clients in the order of the expiry checks
+    expiry_queue: VecDeque<IpAddr>,
+}
+
+/// Per client IP call rate limiter, shared by all connections of a server
+#[derive(Clone)]
+pub struct RateLimiter {
+    requests_per_second: u32,
+    buckets: Arc<Mutex<Buckets>>,
+    metrics: Arc<ConnectionMetrics>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: &ConnectionLimits, metrics: Arc<ConnectionMetrics>) -> Self {
+        RateLimiter {
+            requests_per_second: limits.max_requests_per_second_per_ip,
+            buckets: Default::default(),
+            metrics,
+        }
+    }
+
+    /// Take a token for each of the `calls`, returns false if the client calls too fast.
+    /// A batch of more calls than the per second limit is always refused
+    pub fn check(&self, ip: IpAddr, calls: usize) -> bool {
+        if self.requests_per_second == 0 || calls == 0 {
+            return true;
+        }
+        let rate = f64::from(self.requests_per_second);
+        let now = Instant::now();
+        let refill = |bucket: &TokenBucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated_at);
+            (bucket.tokens + elapsed.as_secs_f64() * rate).min(rate)
+        };
+        let mut guard = self.buckets.lock();
+        let Buckets {
+            buckets,
+            expiry_queue,
+        } = &mut *guard;
+        for _ in 0..EXPIRED_BUCKETS_PER_CHECK {
+            let client = match expiry_queue.pop_front() {
+                Some(client) => client,
+                None => break,
+            };
+            // a full bucket is the same as a new one
+            let is_full = buckets
+                .get(&client)
+                .map_or(true, |bucket| refill(bucket) >= rate);
+            if is_full {
+                buckets.remove(&client);
+            } else {
+                expiry_queue.push_back(client);
+            }
+        }
+        let bucket = buckets.entry(ip).or_insert_with(|| {
+            expiry_queue.push_back(ip);
+            TokenBucket {
+                tokens: rate,
+                updated_at: now,
+            }
+        });
+        bucket.tokens = refill(bucket);
+        bucket.updated_at = now;
+        let calls_tokens = calls as f64;
+        if bucket.tokens < calls_tokens {
+            self.metrics
+                .rate_limited_requests
+                .fetch_add(calls as u64, Ordering::Relaxed);
+            return false;
+        }
+        bucket.tokens -= calls_tokens;
+        true
+    }
+
+    /// Number of the tracked client IPs
+    pub fn tracked_clients(&self) -> usize {
+        self.buckets.lock().buckets.len()
+    }
+}
+
+/// Counts the requests of a connection being served
+#[derive(Clone)]
+pub struct RequestLimiter {
+    in_flight: Arc<AtomicUsize>,
+    max_concurrent_requests: usize,
+    metrics: Arc<ConnectionMetrics>,
+}
+
+impl RequestLimiter {
+    /// Returns None if the connection has too many requests being served
+    pub fn try_acquire(&self) -> Option<RequestPermit> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::Relaxed);
+        let permit = RequestPermit(Arc::clone(&self.in_flight));
+        if in_flight >= self.max_concurrent_requests {
+            self.metrics
+                .rejected_requests
+                .fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Some(permit)
+    }
+}
+
+pub struct RequestPermit(Arc<AtomicUsize>);
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// TCP stream with read and write timeouts
+pub struct LimitedStream {
+    inner: AddrStream,
+    read_timeout: Duration,
+    write_timeout: Duration,
+    read_deadline: Option<Pin<Box<Sleep>>>,
+    write_deadline: Option<Pin<Box<Sleep>>>,
+    // WebSocket connections stay idle between messages, timeouts are disabled for them
+    timeouts_enabled: Arc<AtomicBool>,
+    requests: RequestLimiter,
+    metrics: Arc<ConnectionMetrics>,
+}
+
+impl LimitedStream {
+    fn new(inner: AddrStream, limits: &ConnectionLimits, metrics: Arc<ConnectionMetrics>) -> Self {
+        let requests = RequestLimiter {
+            in_flight: Default::default(),
+            max_concurrent_requests: limits.max_concurrent_requests,
+            metrics: Arc::clone(&metrics),
+        };
+        LimitedStream {
+            inner,
+            read_timeout: limits.read_timeout,
+            write_timeout: limits.write_timeout,
+            read_deadline: None,
+            write_deadline: None,
+            timeouts_enabled: Arc::new(AtomicBool::new(true)),
+            requests,
+            metrics,
+        }
+    }
+
+    pub fn remote_addr(&self) -> SocketAddr {
+        self.inner.remote_addr()
+    }
+
+    pub fn requests(&self) -> RequestLimiter {
+        self.requests.clone()
+    }
+
+    /// Set it to false to disable the timeouts of the connection
+    pub fn timeouts_enabled(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.timeouts_enabled)
+    }
+}
+
+impl Drop for LimitedStream {
+    fn drop(&mut self) {
+        self.metrics
+            .active_connections
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// Poll the deadline of a pending IO, the deadline is reset once the IO completes
+fn poll_deadline<T>(
+    result: Poll<io::Result<T>>,
+    deadline: &mut Option<Pin<Box<Sleep>>>,
+    timeout: Duration,
+    enabled: bool,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<T>> {
+    if result.is_ready() || !enabled {
+        *deadline = None;
+        return result;
+    }
+    let timer = deadline.get_or_insert_with(|| Box::pin(sleep(timeout)));
+    match timer.as_mut().poll(cx) {
+        Poll::Ready(()) => {
+            *deadline = None;
+            Poll::Ready(Err(io::ErrorKind::TimedOut.into()))
+        }
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+impl LimitedStream {
+    fn poll_read_deadline<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        result: Poll<io::Result<T>>,
+    ) -> Poll<io::Result<T>> {
+        let enabled = self.timeouts_enabled.load(Ordering::Relaxed);
+        let result = poll_deadline(
+            result,
+            &mut self.read_deadline,
+            self.read_timeout,
+            enabled,
+            cx,
+        );
+        if let Poll::Ready(Err(ref err)) = result {
+            if err.kind() == io::ErrorKind::TimedOut {
+                self.metrics.read_timeouts.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        result
+    }
+
+    fn poll_write_deadline<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        result: Poll<io::Result<T>>,
+    ) -> Poll<io::Result<T>> {
+        let enabled = self.timeouts_enabled.load(Ordering::Relaxed);
+        let result = poll_deadline(
+            result,
+            &mut self.write_deadline,
+            self.write_timeout,
+            enabled,
+            cx,
+        );
+        if let Poll::Ready(Err(ref err)) = result {
+            if err.kind() == io::ErrorKind::TimedOut {
+                self.metrics.write_timeouts.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        result
+    }
+}
+
+impl AsyncRead for LimitedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        self.poll_read_deadline(cx, result)
+    }
+}
+
+impl AsyncWrite for LimitedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.poll_write_deadline(cx, result)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let result = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        self.poll_write_deadline(cx, result)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let result = Pin::new(&mut self.inner).poll_flush(cx);
+        self.poll_write_deadline(cx, result)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,147 @@
+//! Errors of the RPC handlers
+//!
+//! Each error maps to a JSON-RPC error code, so clients can branch on the code instead of
+//! parsing the message. Codes of the JSON-RPC spec are used where one fits, the others are in
+//! the server error range:
+//!
+//! | code   | error                                             |
+//! |--------|---------------------------------------------------|
+//! | -32602 | invalid params                                    |
+//! | -32603 | internal error                                    |
+//! | -32000 | tx or withdrawal rejected by the mem-pool         |
+//! | -32001 | invalid nonce                                     |
+//! | -32002 | backend of the account not found                  |
+//! | -32003 | execution timeout                                 |
+//! | -32004 | the requested object doesn't exist or isn't ready |
+//! | -32005 | write method rejected in maintenance mode         |
+//! | -32006 | the client calls faster than the rate limit       |
+
+use gw_common::H256;
+use gw_generator::error::{
+    Error as GeneratorError, TransactionError, TransactionErrorWithContext,
+    TransactionValidateError,
+};
+
+pub const INVALID_PARAMS_CODE: i64 = -32602;
+pub const INTERNAL_ERROR_CODE: i64 = -32603;
+pub const TX_REJECTED_CODE: i64 = -32000;
+pub const INVALID_NONCE_CODE: i64 = -32001;
+pub const BACKEND_NOT_FOUND_CODE: i64 = -32002;
+pub const EXECUTION_TIMEOUT_CODE: i64 = -32003;
+pub const NOT_FOUND_CODE: i64 = -32004;
+pub const MAINTENANCE_MODE_CODE: i64 = -32005;
+pub const RATE_LIMITED_CODE: i64 = -32006;
+
+pub type RpcResult<T> = std::result::Result<T, RpcError>;
+
+/// Other errors are converted into `RpcError::Internal`, unless they are transaction errors
+/// with a code of their own.
+///
+/// It doesn't implement `Display`, every `Display` type is converted into an internal error
+/// by `jsonrpc_v2`.
+#[derive(Debug)]
+pub enum RpcError {
+    InvalidParams(String),
+    NotFound(String),
+    TxRejected(String),
+    InvalidNonce { expected: u32, actual: u32 },
+    BackendNotFound { script_hash: H256 },
+    ExecutionTimeout,
+    Internal(anyhow::Error),
+}
+
+impl RpcError {
+    pub fn invalid_params<T: ToString>(msg: T) -> Self {
+        RpcError::InvalidParams(msg.to_string())
+    }
+
+    /// Error of a tx or withdrawal refused by the mem-pool
+    pub fn rejected(err: anyhow::Error) -> Self {
+        match from_transaction_error(&err) {
+            Some(err) => err,
+            None => RpcError::TxRejected(err.to_string()),
+        }
+    }
+
+    pub fn code(&self) -> i64 {
+        match self {
+            RpcError::InvalidParams(_) => INVALID_PARAMS_CODE,
+            RpcError::NotFound(_) => NOT_FOUND_CODE,
+            RpcError::TxRejected(_) => TX_REJECTED_CODE,
+            RpcError::InvalidNonce { .. } => INVALID_NONCE_CODE,
+            RpcError::BackendNotFound { .. } => BACKEND_NOT_FOUND_CODE,
+            RpcError::ExecutionTimeout => EXECUTION_TIMEOUT_CODE,
+            RpcError::Internal(_) => INTERNAL_ERROR_CODE,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            RpcError::InvalidParams(msg) => format!("invalid params: {}", msg),
+            RpcError::NotFound(msg) => msg.clone(),
+            RpcError::TxRejected(msg) => msg.clone(),
+            RpcError::InvalidNonce { expected, actual } => {
+                format!("invalid nonce expected {}, actual {}", expected, actual)
+            }
+            RpcError::BackendNotFound { script_hash } => {
+                format!("can't find backend for script_hash {:?}", script_hash)
+            }
+            RpcError::ExecutionTimeout => "execution timeout".to_string(),
+            RpcError::Internal(err) => err.to_string(),
+        }
+    }
+}
+
+// Transaction errors are returned wrapped in other errors of the generator
+fn from_transaction_error(err: &anyhow::Error) -> Option<RpcError> {
+    let tx_err = if let Some(tx_err) = err.downcast_ref::<TransactionError>() {
+        tx_err
+    } else if let Some(err) = err.downcast_ref::<TransactionErrorWithContext>() {
+        &err.error
+    } else if let Some(GeneratorError::Transaction(err)) = err.downcast_ref::<GeneratorError>() {
+        &err.error
+    } else if let Some(TransactionValidateError::Transaction(tx_err)) =
+        err.downcast_ref::<TransactionValidateError>()
+    {
+        tx_err
+    } else {
+        return None;
+    };
+    match tx_err {
+        TransactionError::Nonce { expected, actual } => Some(RpcError::InvalidNonce {
+            expected: *expected,
+            actual: *actual,
+        }),
+        TransactionError::BackendNotFound { script_hash } => Some(RpcError::BackendNotFound {
+            script_hash: *script_hash,
+        }),
+        TransactionError::Timeout => Some(RpcError::ExecutionTimeout),
+        TransactionError::ExceededMaxCycles { .. }
+        | TransactionError::ExceededMaxTraceSteps { .. }
+        | TransactionError::InvalidMetaContractArgs
+        | TransactionError::DuplicatedScriptHash { .. }
+        | TransactionError::InvalidScript { .. }
+        | TransactionError::Unlock(_) => Some(RpcError::TxRejected(tx_err.to_string())),
+        _ => None,
+    }
+}
+
+impl<E: Into<anyhow::Error>> From<E> for RpcError {
+    fn from(err: E) -> Self {
+        let err = err.into();
+        match from_transaction_error(&err) {
+            Some(err) => err,
+            None => RpcError::Internal(err),
+        }
+    }
+}
+
+impl From<RpcError> for jsonrpc_v2::Error {
+    fn from(err: RpcError) -> Self {
+        jsonrpc_v2::Error::Full {
+            code: err.code(),
+            message: err.message(),
+            data: None,
+        }
+    }
+}
@@ -1,2 +1,8 @@
+pub mod audit;
+pub mod connection;
+pub mod error;
+pub mod method_filter;
+pub mod openrpc;
 pub mod registry;
 pub mod server;
+pub mod subscription;
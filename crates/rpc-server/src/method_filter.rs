@@ -0,0 +1,124 @@
+//! Methods refused before they reach the JSONRPC server
+//!
+//! Methods matching `RPCServerConfig::disabled_methods` are answered as if they don't exist,
+//! a pattern ending with `*` matches a prefix, e.g. `debug_*`.
+//!
+//! In maintenance mode the `submit_*` methods are rejected while the reads stay available.
+//! The mode is toggled at runtime by `gw_admin_set_maintenance_mode` on the admin listener.
+//!
+//! A batch calling a refused method is rejected as a whole, like a batch over the size limit.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use crate::error::MAINTENANCE_MODE_CODE;
+
+const METHOD_NOT_FOUND_CODE: i64 = -32601;
+/// Prefix of the methods which submit objects to the mem-pool
+const WRITE_METHOD_PREFIX: &str = "submit_";
+
+/// Returns true if the method matches one of the patterns
+pub fn is_disabled(patterns: &[String], method: &str) -> bool {
+    patterns.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => method.starts_with(prefix),
+        None => pattern == method,
+    })
+}
+
+/// Runtime switch of the maintenance mode, shared by the servers and the admin RPC
+#[derive(Clone, Default)]
+pub struct MaintenanceMode(Arc<AtomicBool>);
+
+impl MaintenanceMode {
+    pub fn new(enabled: bool) -> Self {
+        MaintenanceMode(Arc::new(AtomicBool::new(enabled)))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::SeqCst);
+    }
+}
+
+#[derive(Clone)]
+pub struct MethodFilter {
+    disabled_methods: Arc<Vec<String>>,
+    maintenance_mode: MaintenanceMode,
+}
+
+impl MethodFilter {
+    pub fn new(disabled_methods: Vec<String>, maintenance_mode: MaintenanceMode) -> Self {
+        MethodFilter {
+            disabled_methods: Arc::new(disabled_methods),
+            maintenance_mode,
+        }
+    }
+
+    /// Returns an error response if the request body calls a refused method
+    pub fn check(&self, body: &RequestBody) -> Option<Value> {
+        let maintenance = self.maintenance_mode.is_enabled();
+        if self.disabled_methods.is_empty() && !maintenance {
+            return None;
+        }
+        for request in &body.requests {
+            let method = match request.get("method").and_then(Value::as_str) {
+                Some(method) => method,
+                None => continue,
+            };
+            let (code, message) = if is_disabled(&self.disabled_methods, method) {
+                (METHOD_NOT_FOUND_CODE, format!("method {} is disabled", method))
+            } else if maintenance && method.starts_with(WRITE_METHOD_PREFIX) {
+                (
+                    MAINTENANCE_MODE_CODE,
+                    format!("{} is unavailable in maintenance mode", method),
+                )
+            } else {
+                continue;
+            };
+            let id = if body.is_batch {
+                Value::Null
+            } else {
+                request.get("id").cloned().unwrap_or(Value::Null)
+            };
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }));
+        }
+        None
+    }
+}
+
+/// A single or batch JSONRPC request body, parsed once for all the checks of the server
+pub struct RequestBody {
+    requests: Vec<Value>,
+    is_batch: bool,
+}
+
+impl RequestBody {
+    /// Returns None if the body is unparsable, it's reported by the JSONRPC server
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Array(requests)) => Some(RequestBody {
+                requests,
+                is_batch: true,
+            }),
+            Ok(request) => Some(RequestBody {
+                requests: vec![request],
+                is_batch: false,
+            }),
+            Err(_) => None,
+        }
+    }
+
+    /// Number of the calls
+    pub fn calls(&self) -> usize {
+        self.requests.len()
+    }
+}
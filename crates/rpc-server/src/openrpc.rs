@@ -0,0 +1,940 @@
+//! OpenRPC document of the RPC surface
+//!
+//! The document is served by the `rpc.discover` method, see https://spec.open-rpc.org.
+//! Every method registered in `Registry::build_rpc_server` must have an entry in `METHODS`,
+//! or in `DEV_METHODS` if it's only registered in dev mode. Disabled methods are left out.
+
+use serde_json::{json, Map, Value};
+
+use crate::method_filter::is_disabled;
+
+const OPENRPC_VERSION: &str = "1.2.6";
+
+/// Schema of a method param or result
+#[derive(Debug, Clone, Copy)]
+pub enum Schema {
+    Null,
+    Boolean,
+    String,
+    H256,
+    Uint32,
+    Uint64,
+    Uint128,
+    JsonBytes,
+    Optional(&'static Schema),
+    Array(&'static Schema),
+    Ref(&'static str),
+}
+
+pub struct MethodDescription {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub params: &'static [(&'static str, Schema)],
+    pub result: Schema,
+}
+
+/// Optional last param of state RPCs
+const BLOCK_PARAM: (&str, Schema) = ("block", Schema::Optional(&Schema::Ref("BlockParam")));
+const VERBOSITY_PARAM: (&str, Schema) = (
+    "verbosity",
+    Schema::Optional(&Schema::Ref("BlockVerbosity")),
+);
+
+pub const METHODS: &[MethodDescription] = &[
+    MethodDescription {
+        name: "ping",
+        summary: "Check the server is alive",
+        params: &[],
+        result: Schema::String,
+    },
+    MethodDescription {
+        name: "get_tip_block_hash",
+        summary: "Get hash of the tip block",
+        params: &[],
+        result: Schema::H256,
+    },
+    MethodDescription {
+        name: "get_block_hash",
+        summary: "Get block hash by block number",
+        params: &[("block_number", Schema::Uint64)],
+        result: Schema::Optional(&Schema::H256),
+    },
+    MethodDescription {
+        name: "get_block",
+        summary: "Get block by block hash, the verbosity selects the parts of the block",
+        params: &[("block_hash", Schema::H256), VERBOSITY_PARAM],
+        result: Schema::Optional(&Schema::Ref("L2BlockVerboseView")),
+    },
+    MethodDescription {
+        name: "get_block_header",
+        summary: "Get block header by block hash, without the transactions and withdrawals",
+        params: &[("block_hash", Schema::H256)],
+        result: Schema::Optional(&Schema::Ref("L2BlockHeaderView")),
+    },
+    MethodDescription {
+        name: "get_block_by_number",
+        summary: "Get block by block number, the verbosity selects the parts of the block",
+        params: &[("block_number", Schema::Uint64), VERBOSITY_PARAM],
+        result: Schema::Optional(&Schema::Ref("L2BlockVerboseView")),
+    },
+    MethodDescription {
+        name: "get_transaction",
+        summary: "Get a committed transaction and the block including it",
+        params: &[("tx_hash", Schema::H256)],
+        result: Schema::Optional(&Schema::Ref("L2TransactionWithBlockInfo")),
+    },
+    MethodDescription {
+        name: "get_transaction_receipt",
+        summary: "Get the receipt of a committed transaction",
+        params: &[("tx_hash", Schema::H256)],
+        result: Schema::Optional(&Schema::Ref("TxReceipt")),
+    },
+    MethodDescription {
+        name: "get_transactions_by_account",
+        summary: "Get txs sent from or to an account in a block range, at most 1000 txs a page",
+        params: &[
+            ("account_id", Schema::Uint32),
+            ("from_block", Schema::Uint64),
+            ("to_block", Schema::Uint64),
+            ("limit", Schema::Uint32),
+            ("cursor", Schema::Optional(&Schema::JsonBytes)),
+        ],
+        result: Schema::Ref("AccountTransactions"),
+    },
+    MethodDescription {
+        name: "get_blocks",
+        summary: "Get consecutive main chain blocks, or only their headers, at most 100 blocks",
+        params: &[
+            ("from_number", Schema::Uint64),
+            ("count", Schema::Uint32),
+            ("full", Schema::Boolean),
+        ],
+        result: Schema::Ref("L2BlocksView"),
+    },
+    MethodDescription {
+        name: "get_pending_block",
+        summary: "Get transactions and withdrawals to be packaged into the next block",
+        params: &[],
+        result: Schema::Ref("PendingBlockView"),
+    },
+    MethodDescription {
+        name: "get_balance",
+        summary: "Get sUDT balance of an account",
+        params: &[
+            ("account_id", Schema::Uint32),
+            ("sudt_id", Schema::Uint32),
+            BLOCK_PARAM,
+        ],
+        result: Schema::Uint128,
+    },
+    MethodDescription {
+        name: "get_sudt_total_supply",
+        summary: "Get layer2 total supply of a sUDT",
+        params: &[("sudt_id", Schema::Uint32), BLOCK_PARAM],
+        result: Schema::Uint128,
+    },
+    MethodDescription {
+        name: "get_storage_at",
+        summary: "Get a storage value of an account",
+        params: &[
+            ("account_id", Schema::Uint32),
+            ("key", Schema::H256),
+            BLOCK_PARAM,
+        ],
+        result: Schema::H256,
+    },
+    MethodDescription {
+        name: "get_account_storage_usage",
+        summary: "Get storage slots and data bytes owned by an account on the main chain",
+        params: &[("account_id", Schema::Uint32)],
+        result: Schema::Ref("AccountStorageUsage"),
+    },
+    MethodDescription {
+        name: "get_account_id_by_script_hash",
+        summary: "Get account id by script hash",
+        params: &[("script_hash", Schema::H256), BLOCK_PARAM],
+        result: Schema::Optional(&Schema::Uint32),
+    },
+    MethodDescription {
+        name: "get_nonce",
+        summary: "Get nonce of an account",
+        params: &[("account_id", Schema::Uint32), BLOCK_PARAM],
+        result: Schema::Uint32,
+    },
+    MethodDescription {
+        name: "get_script",
+        summary: "Get script by script hash",
+        params: &[("script_hash", Schema::H256), BLOCK_PARAM],
+        result: Schema::Optional(&Schema::Ref("Script")),
+    },
+    MethodDescription {
+        name: "get_script_hash",
+        summary: "Get script hash of an account",
+        params: &[("account_id", Schema::Uint32), BLOCK_PARAM],
+        result: Schema::H256,
+    },
+    MethodDescription {
+        name: "get_data",
+        summary: "Get data by data hash",
+        params: &[("data_hash", Schema::H256), BLOCK_PARAM],
+        result: Schema::Optional(&Schema::JsonBytes),
+    },
+    MethodDescription {
+        name: "get_block_l2_to_l1_messages",
+        summary: "Get L2 to L1 messages sent in a block",
+        params: &[("block_hash", Schema::H256)],
+        result: Schema::Array(&Schema::Ref("L2ToL1Message")),
+    },
+    MethodDescription {
+        name: "get_l2_to_l1_message_proof",
+        summary: "Get merkle proof of a L2 to L1 message in a finalized block",
+        params: &[("block_hash", Schema::H256), ("index", Schema::Uint32)],
+        result: Schema::Optional(&Schema::Ref("L2ToL1MessageProof")),
+    },
+    MethodDescription {
+        name: "get_sudt_balances_proof",
+        summary: "Get sUDT balances of accounts at a block with one merkle proof",
+        params: &[
+            ("block_hash", Schema::H256),
+            ("keys", Schema::Array(&Schema::Ref("SUDTBalanceKey"))),
+        ],
+        result: Schema::Optional(&Schema::Ref("SUDTBalancesProof")),
+    },
+    MethodDescription {
+        name: "get_new_accounts",
+        summary: "Get accounts created in a block",
+        params: &[("block_hash", Schema::H256)],
+        result: Schema::Array(&Schema::Ref("NewAccount")),
+    },
+    MethodDescription {
+        name: "get_block_economics",
+        summary: "Get fees of a block credited to the block producer",
+        params: &[("block_hash", Schema::H256)],
+        result: Schema::Optional(&Schema::Ref("BlockEconomics")),
+    },
+    MethodDescription {
+        name: "get_challenge_history",
+        summary: "Get challenges, cancellations and reverts on L1, at most 1000 actions",
+        params: &[
+            ("from_l1_block_number", Schema::Uint64),
+            ("limit", Schema::Uint32),
+        ],
+        result: Schema::Array(&Schema::Ref("RollupAction")),
+    },
+    MethodDescription {
+        name: "get_tx_inclusion_proof",
+        summary: "Get proof that a transaction is included in a block committed on L1",
+        params: &[("tx_hash", Schema::H256)],
+        result: Schema::Optional(&Schema::Ref("TxInclusionProof")),
+    },
+    MethodDescription {
+        name: "get_l1_commitment",
+        summary: "Get the L1 transaction which committed a block",
+        params: &[("block_hash", Schema::H256)],
+        result: Schema::Optional(&Schema::Ref("L1Commitment")),
+    },
+    MethodDescription {
+        name: "get_state_roots",
+        summary: "Get the locally computed state roots of main chain blocks, at most 1000 blocks",
+        params: &[("from", Schema::Uint64), ("to", Schema::Uint64)],
+        result: Schema::Array(&Schema::Ref("BlockStateRoot")),
+    },
+    MethodDescription {
+        name: "get_producer_stats",
+        summary: "Get block production statistics of a block range, at most 10000 blocks",
+        params: &[("from_block", Schema::Uint64), ("to_block", Schema::Uint64)],
+        result: Schema::Ref("ProducerStats"),
+    },
+    MethodDescription {
+        name: "get_mempool_stats",
+        summary: "Get counts of the mem-pool and the rejected transactions by reason",
+        params: &[],
+        result: Schema::Ref("MemPoolStats"),
+    },
+    MethodDescription {
+        name: "compute_signing_message",
+        summary: "Compute the message to sign of a raw transaction or withdrawal request",
+        params: &[("payload", Schema::Ref("SigningPayload"))],
+        result: Schema::Ref("SigningMessage"),
+    },
+    MethodDescription {
+        name: "execute_l2transaction",
+        summary: "Execute a serialized L2Transaction without committing it, at a block or at its position in the block",
+        params: &[
+            ("l2tx", Schema::JsonBytes),
+            ("timeout_ms", Schema::Optional(&Schema::Uint64)),
+            BLOCK_PARAM,
+        ],
+        result: Schema::Ref("RunResult"),
+    },
+    MethodDescription {
+        name: "execute_with_state_overrides",
+        summary: "Execute a serialized RawL2Transaction with account overrides, it isn't signed",
+        params: &[
+            ("raw_l2tx", Schema::JsonBytes),
+            ("overrides", Schema::Array(&Schema::Ref("AccountOverride"))),
+        ],
+        result: Schema::Ref("RunResult"),
+    },
+    MethodDescription {
+        name: "estimate_gas",
+        summary: "Estimate the lowest gas limit a serialized L2Transaction succeeds with",
+        params: &[("l2tx", Schema::JsonBytes)],
+        result: Schema::Uint64,
+    },
+    MethodDescription {
+        name: "trace_transaction",
+        summary: "Trace the syscalls of a serialized L2Transaction, the trace is kept if it fails",
+        params: &[("l2tx", Schema::JsonBytes), BLOCK_PARAM],
+        result: Schema::Ref("TransactionTrace"),
+    },
+    MethodDescription {
+        name: "submit_l2transaction",
+        summary: "Submit a serialized L2Transaction to the mem-pool",
+        params: &[("l2tx", Schema::JsonBytes)],
+        result: Schema::Null,
+    },
+    MethodDescription {
+        name: "submit_withdrawal_request",
+        summary: "Submit a serialized WithdrawalRequest to the mem-pool",
+        params: &[("withdrawal_request", Schema::JsonBytes)],
+        result: Schema::Null,
+    },
+    MethodDescription {
+        name: "can_fulfill_withdrawal",
+        summary: "Check whether a serialized WithdrawalRequest can be packaged into the next block",
+        params: &[("withdrawal_request", Schema::JsonBytes)],
+        result: Schema::Ref("WithdrawalFeasibility"),
+    },
+    MethodDescription {
+        name: "get_fee_config",
+        summary: "Get the min account creation fee and withdrawal fees accepted by the mem-pool",
+        params: &[],
+        result: Schema::Ref("FeeConfig"),
+    },
+    MethodDescription {
+        name: "get_backends",
+        summary: "List the registered backends",
+        params: &[],
+        result: Schema::Array(&Schema::Ref("BackendInfo")),
+    },
+    MethodDescription {
+        name: "get_store_usage",
+        summary: "Get disk usage of store columns",
+        params: &[],
+        result: Schema::Array(&Schema::Ref("StoreColumnUsage")),
+    },
+    MethodDescription {
+        name: "get_connection_stats",
+        summary: "Get connection counters of the JSONRPC server",
+        params: &[],
+        result: Schema::Ref("ConnectionStats"),
+    },
+    MethodDescription {
+        name: "get_node_status",
+        summary: "Get the L2 tip and the progress of catching up the L1 blocks",
+        params: &[],
+        result: Schema::Ref("NodeStatus"),
+    },
+];
+
+fn hex_string(pattern: &str) -> Value {
+    json!({ "type": "string", "pattern": pattern })
+}
+
+impl Schema {
+    pub fn to_json(self) -> Value {
+        match self {
+            Schema::Null => json!({ "type": "null" }),
+            Schema::Boolean => json!({ "type": "boolean" }),
+            Schema::String => json!({ "type": "string" }),
+            Schema::H256 => hex_string("^0x[0-9a-f]{64}$"),
+            Schema::Uint32 | Schema::Uint64 | Schema::Uint128 => {
+                hex_string("^0x(0|[1-9a-f][0-9a-f]*)$")
+            }
+            Schema::JsonBytes => hex_string("^0x([0-9a-f]{2})*$"),
+            Schema::Optional(inner) => json!({ "oneOf": [inner.to_json(), { "type": "null" }] }),
+            Schema::Array(inner) => json!({ "type": "array", "items": inner.to_json() }),
+            Schema::Ref(name) => json!({ "$ref": format!("#/components/schemas/{}", name) }),
+        }
+    }
+}
+
+fn object(properties: &[(&str, Value)]) -> Value {
+    let required: Vec<&str> = properties.iter().map(|(name, _)| *name).collect();
+    let properties: Map<String, Value> = properties
+        .iter()
+        .map(|(name, schema)| (name.to_string(), schema.clone()))
+        .collect();
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn component_schemas() -> Value {
+    let log_item = object(&[
+        ("account_id", Schema::Uint32.to_json()),
+        ("service_flag", Schema::Uint32.to_json()),
+        ("data", Schema::JsonBytes.to_json()),
+    ]);
+    let script = object(&[
+        ("code_hash", Schema::H256.to_json()),
+        (
+            "hash_type",
+            json!({ "type": "string", "enum": ["data", "type"] }),
+        ),
+        ("args", Schema::JsonBytes.to_json()),
+    ]);
+    let run_result = object(&[
+        ("return_data", Schema::JsonBytes.to_json()),
+        (
+            "logs",
+            json!({ "type": "array", "items": log_item.clone() }),
+        ),
+        ("used_cycles", Schema::Uint64.to_json()),
+    ]);
+    // the other fields of a step depend on the type
+    let trace_step = json!({
+        "type": "object",
+        "properties": {
+            "cycles": Schema::Uint64.to_json(),
+            "type": {
+                "type": "string",
+                "enum": ["load", "store", "create_account", "log", "set_return_data", "syscall"],
+            },
+            "key": Schema::H256.to_json(),
+            "value": Schema::H256.to_json(),
+            "account_id": Schema::Uint32.to_json(),
+            "script_hash": Schema::H256.to_json(),
+            "log": log_item.clone(),
+            "data": Schema::JsonBytes.to_json(),
+            "number": Schema::Uint64.to_json(),
+        },
+        "required": ["cycles", "type"],
+    });
+    let transaction_trace = object(&[
+        ("steps", Schema::Array(&Schema::Ref("TraceStep")).to_json()),
+        ("run_result", Schema::Optional(&Schema::Ref("RunResult")).to_json()),
+        ("error", Schema::Optional(&Schema::String).to_json()),
+    ]);
+    let backend_info = object(&[
+        ("validator_script_type_hash", Schema::H256.to_json()),
+        ("validator_hash", Schema::H256.to_json()),
+        ("generator_hash", Schema::H256.to_json()),
+    ]);
+    let store_column_usage = object(&[
+        ("column", Schema::Uint32.to_json()),
+        ("total_sst_files_size", Schema::Uint64.to_json()),
+        ("live_data_size", Schema::Uint64.to_json()),
+        ("reclaimable_size", Schema::Uint64.to_json()),
+    ]);
+    let l2_to_l1_message = object(&[
+        ("block_hash", Schema::H256.to_json()),
+        ("block_number", Schema::Uint64.to_json()),
+        ("index", Schema::Uint32.to_json()),
+        ("tx_hash", Schema::H256.to_json()),
+        ("from_id", Schema::Uint32.to_json()),
+        ("payload", Schema::JsonBytes.to_json()),
+        ("message_hash", Schema::H256.to_json()),
+    ]);
+    let l2_to_l1_message_proof = object(&[
+        ("message", Schema::Ref("L2ToL1Message").to_json()),
+        ("account_root", Schema::H256.to_json()),
+        ("proof", Schema::JsonBytes.to_json()),
+    ]);
+    let sudt_balance_key = object(&[
+        ("account_id", Schema::Uint32.to_json()),
+        ("sudt_id", Schema::Uint32.to_json()),
+    ]);
+    let sudt_balances_proof = object(&[
+        ("block_hash", Schema::H256.to_json()),
+        ("block_number", Schema::Uint64.to_json()),
+        ("account_root", Schema::H256.to_json()),
+        (
+            "balances",
+            json!({
+                "type": "array",
+                "items": object(&[
+                    ("account_id", Schema::Uint32.to_json()),
+                    ("sudt_id", Schema::Uint32.to_json()),
+                    ("balance", Schema::Uint128.to_json()),
+                ]),
+            }),
+        ),
+        ("proof", Schema::JsonBytes.to_json()),
+    ]);
+    let tx_inclusion_proof = object(&[
+        ("tx_hash", Schema::H256.to_json()),
+        ("tx_witness_hash", Schema::H256.to_json()),
+        ("tx_index", Schema::Uint32.to_json()),
+        ("block_hash", Schema::H256.to_json()),
+        ("raw_l2block", Schema::Ref("RawL2Block").to_json()),
+        ("proof", Schema::JsonBytes.to_json()),
+        (
+            "l2block_committed_info",
+            object(&[
+                ("number", Schema::Uint64.to_json()),
+                ("block_hash", Schema::H256.to_json()),
+                ("transaction_hash", Schema::H256.to_json()),
+            ]),
+        ),
+    ]);
+    let producer_block_stats = object(&[
+        ("producer_id", Schema::Uint32.to_json()),
+        ("block_count", Schema::Uint64.to_json()),
+        ("tx_count", Schema::Uint64.to_json()),
+    ]);
+    let producer_stats = object(&[
+        ("from_block", Schema::Uint64.to_json()),
+        ("to_block", Schema::Uint64.to_json()),
+        ("block_count", Schema::Uint64.to_json()),
+        ("tx_count", Schema::Uint64.to_json()),
+        ("average_interval", Schema::Uint64.to_json()),
+        ("average_tx_count", Schema::Uint64.to_json()),
+        ("missed_slots", Schema::Uint64.to_json()),
+        (
+            "producers",
+            json!({ "type": "array", "items": producer_block_stats }),
+        ),
+    ]);
+    let mem_pool_rejection = object(&[
+        ("reason", Schema::String.to_json()),
+        ("count", Schema::Uint64.to_json()),
+    ]);
+    let mem_pool_stats = object(&[
+        ("pending_accounts", Schema::Uint32.to_json()),
+        ("txs", Schema::Uint32.to_json()),
+        ("tx_bytes", Schema::Uint64.to_json()),
+        ("withdrawals", Schema::Uint32.to_json()),
+        (
+            "rejections",
+            json!({ "type": "array", "items": mem_pool_rejection }),
+        ),
+    ]);
+    let account_merkle_state = object(&[
+        ("merkle_root", Schema::H256.to_json()),
+        ("count", Schema::Uint32.to_json()),
+    ]);
+    let raw_l2_block = object(&[
+        ("number", Schema::Uint64.to_json()),
+        ("parent_block_hash", Schema::H256.to_json()),
+        ("block_producer_id", Schema::Uint32.to_json()),
+        ("stake_cell_owner_lock_hash", Schema::H256.to_json()),
+        ("timestamp", Schema::Uint64.to_json()),
+        ("prev_account", account_merkle_state.clone()),
+        ("post_account", account_merkle_state.clone()),
+        (
+            "submit_transactions",
+            object(&[
+                ("tx_witness_root", Schema::H256.to_json()),
+                ("tx_count", Schema::Uint32.to_json()),
+                ("compacted_prev_root_hash", Schema::H256.to_json()),
+                (
+                    "compacted_post_root_list",
+                    Schema::Array(&Schema::H256).to_json(),
+                ),
+            ]),
+        ),
+        (
+            "submit_withdrawals",
+            object(&[
+                ("withdrawal_witness_root", Schema::H256.to_json()),
+                ("withdrawal_count", Schema::Uint32.to_json()),
+            ]),
+        ),
+    ]);
+    let signature = hex_string("^0x[0-9a-f]{130}$");
+    let raw_l2_transaction = object(&[
+        ("from_id", Schema::Uint32.to_json()),
+        ("to_id", Schema::Uint32.to_json()),
+        ("nonce", Schema::Uint32.to_json()),
+        ("args", Schema::JsonBytes.to_json()),
+        ("gas_limit", Schema::Uint64.to_json()),
+        ("gas_price", Schema::Uint128.to_json()),
+    ]);
+    let raw_withdrawal_request = object(&[
+        ("nonce", Schema::Uint32.to_json()),
+        ("capacity", Schema::Uint64.to_json()),
+        ("amount", Schema::Uint128.to_json()),
+        ("sell_amount", Schema::Uint128.to_json()),
+        ("sell_capacity", Schema::Uint64.to_json()),
+        ("sudt_script_hash", Schema::H256.to_json()),
+        ("account_script_hash", Schema::H256.to_json()),
+        ("owner_lock_hash", Schema::H256.to_json()),
+        ("payment_lock_hash", Schema::H256.to_json()),
+        ("fee", Schema::Uint64.to_json()),
+        ("sudt_fee", Schema::Uint128.to_json()),
+    ]);
+    let l2_transaction_view = object(&[
+        ("raw", raw_l2_transaction.clone()),
+        ("signature", signature.clone()),
+        ("hash", Schema::H256.to_json()),
+    ]);
+    let account_transactions = object(&[
+        (
+            "transactions",
+            json!({
+                "type": "array",
+                "items": object(&[
+                    ("tx_hash", Schema::H256.to_json()),
+                    ("block_number", Schema::Uint64.to_json()),
+                    ("tx_index", Schema::Uint32.to_json()),
+                ]),
+            }),
+        ),
+        ("cursor", Schema::Optional(&Schema::JsonBytes).to_json()),
+    ]);
+    let l2_transaction_with_block_info = object(&[
+        ("transaction", l2_transaction_view.clone()),
+        ("block_hash", Schema::H256.to_json()),
+        ("block_number", Schema::Uint64.to_json()),
+        ("tx_index", Schema::Uint32.to_json()),
+    ]);
+    let withdrawal_request_view = object(&[
+        ("raw", raw_withdrawal_request.clone()),
+        ("signature", signature),
+        ("hash", Schema::H256.to_json()),
+    ]);
+    let signing_payload = json!({
+        "oneOf": [
+            object(&[
+                ("type", json!({ "type": "string", "enum": ["raw_l2_transaction"] })),
+                ("payload", raw_l2_transaction),
+            ]),
+            object(&[
+                ("type", json!({ "type": "string", "enum": ["raw_withdrawal_request"] })),
+                ("payload", raw_withdrawal_request),
+            ]),
+        ]
+    });
+    let signing_message = object(&[
+        ("message", Schema::H256.to_json()),
+        ("canonical_bytes", Schema::JsonBytes.to_json()),
+    ]);
+    let pending_block_view = object(&[
+        ("number", Schema::Uint64.to_json()),
+        ("parent_block_hash", Schema::H256.to_json()),
+        (
+            "transactions",
+            json!({ "type": "array", "items": l2_transaction_view.clone() }),
+        ),
+        (
+            "withdrawal_requests",
+            json!({ "type": "array", "items": withdrawal_request_view.clone() }),
+        ),
+    ]);
+    let kv_pair = object(&[("k", Schema::H256.to_json()), ("v", Schema::H256.to_json())]);
+    let balance_override = object(&[
+        ("sudt_id", Schema::Uint32.to_json()),
+        ("balance", Schema::Uint128.to_json()),
+    ]);
+    let mut account_override = object(&[
+        ("account_id", Schema::Uint32.to_json()),
+        (
+            "balances",
+            json!({ "type": "array", "items": balance_override }),
+        ),
+        (
+            "storage",
+            json!({ "type": "array", "items": kv_pair.clone() }),
+        ),
+        ("script", Schema::Optional(&Schema::Ref("Script")).to_json()),
+    ]);
+    // overrides are optional except the account id
+    account_override["required"] = json!(["account_id"]);
+    let l2_block_view = object(&[
+        ("raw", Schema::Ref("RawL2Block").to_json()),
+        ("kv_state", json!({ "type": "array", "items": kv_pair })),
+        ("kv_state_proof", Schema::JsonBytes.to_json()),
+        (
+            "transactions",
+            json!({ "type": "array", "items": l2_transaction_view }),
+        ),
+        ("block_proof", Schema::JsonBytes.to_json()),
+        (
+            "withdrawal_requests",
+            json!({ "type": "array", "items": withdrawal_request_view }),
+        ),
+        ("hash", Schema::H256.to_json()),
+        (
+            "l1_commitment",
+            Schema::Optional(&Schema::Ref("L1Commitment")).to_json(),
+        ),
+    ]);
+    let l2_block_header_view = object(&[
+        ("raw", Schema::Ref("RawL2Block").to_json()),
+        ("hash", Schema::H256.to_json()),
+        (
+            "l1_commitment",
+            Schema::Optional(&Schema::Ref("L1Commitment")).to_json(),
+        ),
+    ]);
+    let tx_receipt = object(&[
+        ("tx_witness_hash", Schema::H256.to_json()),
+        ("post_state", account_merkle_state),
+        ("read_data_hashes", Schema::Array(&Schema::H256).to_json()),
+        ("logs", json!({ "type": "array", "items": log_item })),
+        ("used_cycles", Schema::Uint64.to_json()),
+        ("paid_fee", Schema::Uint128.to_json()),
+        ("refunded_fee", Schema::Uint128.to_json()),
+        ("return_data", Schema::JsonBytes.to_json()),
+    ]);
+    let l2_block_tx_hashes_view = json!({
+        "allOf": [
+            Schema::Ref("L2BlockHeaderView").to_json(),
+            object(&[
+                ("transactions", Schema::Array(&Schema::H256).to_json()),
+                ("withdrawal_requests", Schema::Array(&Schema::H256).to_json()),
+            ]),
+        ]
+    });
+    let l2_block_with_receipts_view = json!({
+        "allOf": [
+            Schema::Ref("L2BlockView").to_json(),
+            object(&[("receipts", Schema::Array(&Schema::Ref("TxReceipt")).to_json())]),
+        ]
+    });
+    let l2_block_verbose_view = json!({
+        "oneOf": [
+            Schema::Ref("L2BlockHeaderView").to_json(),
+            l2_block_tx_hashes_view,
+            Schema::Ref("L2BlockView").to_json(),
+            l2_block_with_receipts_view,
+        ]
+    });
+    let block_verbosity = json!({ "type": "string", "enum": ["header", "tx_hashes", "full"] });
+    let l2_blocks_view = json!({
+        "oneOf": [
+            Schema::Array(&Schema::Ref("L2BlockHeaderView")).to_json(),
+            Schema::Array(&Schema::Ref("L2BlockView")).to_json(),
+        ]
+    });
+    let block_state_root = object(&[
+        ("block_number", Schema::Uint64.to_json()),
+        ("block_hash", Schema::H256.to_json()),
+        ("account_root", Schema::H256.to_json()),
+        ("block_root", Schema::H256.to_json()),
+        ("l1_transaction_hash", Schema::Optional(&Schema::H256).to_json()),
+    ]);
+    let l1_commitment = object(&[
+        ("l1_block_number", Schema::Uint64.to_json()),
+        ("l1_block_hash", Schema::H256.to_json()),
+        ("l1_transaction_hash", Schema::H256.to_json()),
+        ("output_index", Schema::Optional(&Schema::Uint32).to_json()),
+    ]);
+    let account_storage_usage = object(&[
+        ("account_id", Schema::Uint32.to_json()),
+        ("slots", Schema::Uint64.to_json()),
+        ("data_bytes", Schema::Uint64.to_json()),
+    ]);
+    let block_economics = object(&[
+        ("block_producer_id", Schema::Uint32.to_json()),
+        ("tx_count", Schema::Uint32.to_json()),
+        ("withdrawal_count", Schema::Uint32.to_json()),
+        ("used_cycles", Schema::Uint64.to_json()),
+        ("paid_fees", Schema::Uint128.to_json()),
+        ("refunded_fees", Schema::Uint128.to_json()),
+    ]);
+    let new_account = object(&[
+        ("account_id", Schema::Uint32.to_json()),
+        ("script_hash", Schema::H256.to_json()),
+    ]);
+    let node_status = object(&[
+        ("tip_block_number", Schema::Uint64.to_json()),
+        ("l1_start_block_number", Schema::Uint64.to_json()),
+        ("l1_synced_block_number", Schema::Uint64.to_json()),
+        ("l1_tip_block_number", Schema::Optional(&Schema::Uint64).to_json()),
+        ("sync_percent", Schema::Uint32.to_json()),
+        ("sync_eta_secs", Schema::Optional(&Schema::Uint64).to_json()),
+    ]);
+    let connection_stats = object(&[
+        ("active_connections", Schema::Uint64.to_json()),
+        ("accepted_connections", Schema::Uint64.to_json()),
+        ("rejected_connections", Schema::Uint64.to_json()),
+        ("rejected_requests", Schema::Uint64.to_json()),
+        ("rate_limited_requests", Schema::Uint64.to_json()),
+        ("read_timeouts", Schema::Uint64.to_json()),
+        ("write_timeouts", Schema::Uint64.to_json()),
+    ]);
+    let rollup_action = object(&[
+        (
+            "action_type",
+            json!({ "type": "string", "enum": ["enter_challenge", "cancel_challenge", "revert"] }),
+        ),
+        ("prev_status", Schema::Uint32.to_json()),
+        ("post_status", Schema::Uint32.to_json()),
+        (
+            "challenge_target",
+            object(&[
+                ("block_hash", Schema::H256.to_json()),
+                ("target_index", Schema::Uint32.to_json()),
+                (
+                    "target_type",
+                    json!({ "type": "string", "enum": ["transaction", "withdrawal"] }),
+                ),
+            ]),
+        ),
+        ("l1_block_number", Schema::Uint64.to_json()),
+        ("l1_block_hash", Schema::H256.to_json()),
+        ("l1_tx_hash", Schema::H256.to_json()),
+    ]);
+    let withdrawal_feasibility = object(&[
+        (
+            "status",
+            json!({ "type": "string", "enum": ["next_block", "queued", "rejected"] }),
+        ),
+        ("reason", Schema::Optional(&Schema::String).to_json()),
+        ("available_capacity", Schema::Uint128.to_json()),
+        ("available_amount", Schema::Uint128.to_json()),
+        ("fee_capacity", Schema::Uint64.to_json()),
+        ("fee_amount", Schema::Uint128.to_json()),
+        ("charged_capacity", Schema::Uint128.to_json()),
+        ("charged_amount", Schema::Uint128.to_json()),
+    ]);
+    let fee_config = object(&[
+        ("account_creation_fee", Schema::Uint64.to_json()),
+        ("withdrawal_ckb_fee", Schema::Uint64.to_json()),
+        (
+            "withdrawal_sudt_fees",
+            Schema::Array(&Schema::Ref("SUDTWithdrawalFee")).to_json(),
+        ),
+    ]);
+    let sudt_withdrawal_fee = object(&[
+        ("sudt_script_hash", Schema::H256.to_json()),
+        ("fee", Schema::Uint128.to_json()),
+    ]);
+    let block_tag = json!({ "type": "string", "enum": ["latest", "finalized", "pending"] });
+    let block_param = json!({
+        "oneOf": [
+            Schema::Ref("BlockTag").to_json(),
+            object(&[("block_number", Schema::Uint64.to_json())]),
+            object(&[("block_hash", Schema::H256.to_json())]),
+        ]
+    });
+    json!({
+        "AccountOverride": account_override,
+        "AccountStorageUsage": account_storage_usage,
+        "AccountTransactions": account_transactions,
+        "BackendInfo": backend_info,
+        "BlockEconomics": block_economics,
+        "BlockStateRoot": block_state_root,
+        "BlockParam": block_param,
+        "BlockTag": block_tag,
+        "BlockVerbosity": block_verbosity,
+        "ConnectionStats": connection_stats,
+        "FeeConfig": fee_config,
+        "L1Commitment": l1_commitment,
+        "L2BlockHeaderView": l2_block_header_view,
+        "L2BlockVerboseView": l2_block_verbose_view,
+        "L2BlockView": l2_block_view,
+        "L2BlocksView": l2_blocks_view,
+        "L2TransactionWithBlockInfo": l2_transaction_with_block_info,
+        "L2ToL1Message": l2_to_l1_message,
+        "L2ToL1MessageProof": l2_to_l1_message_proof,
+        "MemPoolStats": mem_pool_stats,
+        "NewAccount": new_account,
+        "NodeStatus": node_status,
+        "PendingBlockView": pending_block_view,
+        "ProducerStats": producer_stats,
+        "RawL2Block": raw_l2_block,
+        "RollupAction": rollup_action,
+        "RunResult": run_result,
+        "SUDTBalanceKey": sudt_balance_key,
+        "SUDTBalancesProof": sudt_balances_proof,
+        "SUDTWithdrawalFee": sudt_withdrawal_fee,
+        "Script": script,
+        "SigningMessage": signing_message,
+        "SigningPayload": signing_payload,
+        "StoreColumnUsage": store_column_usage,
+        "TraceStep": trace_step,
+        "TransactionTrace": transaction_trace,
+        "TxInclusionProof": tx_inclusion_proof,
+        "TxReceipt": tx_receipt,
+        "WithdrawalFeasibility": withdrawal_feasibility,
+    })
+}
+
+fn method_to_json(method: &MethodDescription) -> Value {
+    let params: Vec<Value> = method
+        .params
+        .iter()
+        .map(|(name, schema)| {
+            let required = !matches!(schema, Schema::Optional(_));
+            json!({ "name": name, "required": required, "schema": schema.to_json() })
+        })
+        .collect();
+    json!({
+        "name": method.name,
+        "summary": method.summary,
+        "params": params,
+        "result": { "name": format!("{}_result", method.name), "schema": method.result.to_json() },
+    })
+}
+
+/// Methods only registered in dev mode
+pub const DEV_METHODS: &[MethodDescription] = &[
+    MethodDescription {
+        name: "dev_deposit",
+        summary:
+            "Inject a synthetic deposit into the next block, the amount is CKB if no sUDT is set",
+        params: &[
+            ("script", Schema::Ref("Script")),
+            ("amount", Schema::Uint128),
+            ("sudt_script_hash", Schema::Optional(&Schema::H256)),
+        ],
+        result: Schema::Null,
+    },
+    MethodDescription {
+        name: "dev_setTime",
+        summary: "Set the dev clock in milliseconds, the time is frozen until it's changed",
+        params: &[("timestamp", Schema::Uint64)],
+        result: Schema::Null,
+    },
+    MethodDescription {
+        name: "dev_increaseTime",
+        summary: "Move the dev clock forward in milliseconds, returns the new time",
+        params: &[("duration", Schema::Uint64)],
+        result: Schema::Uint64,
+    },
+];
+
+/// Methods registered in `Registry::build_admin_rpc_server`, they are only served on the admin
+/// listener and are left out of the public document
+pub const ADMIN_METHODS: &[MethodDescription] = &[
+    MethodDescription {
+        name: "gw_admin_compact_store",
+        summary: "Compact a store column, or all columns if not set",
+        params: &[("column", Schema::Optional(&Schema::Uint32))],
+        result: Schema::Null,
+    },
+    MethodDescription {
+        name: "gw_admin_set_maintenance_mode",
+        summary: "Turn the maintenance mode on or off, submit methods are rejected while it's on",
+        params: &[("enabled", Schema::Boolean)],
+        result: Schema::Null,
+    },
+    MethodDescription {
+        name: "gw_admin_reload_backends",
+        summary: "Read the backends section of the config file again and load its backends",
+        params: &[],
+        result: Schema::Null,
+    },
+];
+
+/// Build the OpenRPC document
+pub fn document(dev_mode: bool, disabled_methods: &[String]) -> Value {
+    let dev_methods = if dev_mode { DEV_METHODS } else { &[] };
+    let methods: Vec<Value> = METHODS
+        .iter()
+        .chain(dev_methods)
+        .filter(|method| !is_disabled(disabled_methods, method.name))
+        .map(method_to_json)
+        .collect();
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "Godwoken JSONRPC",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": methods,
+        "components": { "schemas": component_schemas() },
+    })
+}
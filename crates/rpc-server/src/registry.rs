@@ -1,63 +1,390 @@
+use crate::connection::ConnectionMetrics;
+use crate::error::{RpcError, RpcResult};
+use crate::method_filter::MaintenanceMode;
+use crate::openrpc;
 use anyhow::Result;
 use ckb_types::prelude::{Builder, Entity};
-use gw_common::{state::State, H256};
+use gw_chain::sync_progress::SyncProgress;
+use gw_common::{
+    builtins::BuiltinAccounts,
+    h256_ext::H256Ext,
+    merkle_utils::{build_sudt_balance_key, calculate_merkle_proof},
+    state::{
+        build_l2_to_l1_message_hash, build_l2_to_l1_message_key, State, GW_LOG_L2_TO_L1_MESSAGE,
+    },
+    JsonH256, CKB_SUDT_SCRIPT_ARGS, H256,
+};
+use gw_config::{BackendConfig, RPCServerConfig};
 use gw_jsonrpc_types::{
     blockchain::Script,
-    ckb_jsonrpc_types::{JsonBytes, Uint128, Uint32},
-    godwoken::{L2BlockView, RunResult},
+    canonical::{
+        raw_l2_transaction_signing_message, raw_withdrawal_request_signing_message, Canonical,
+    },
+    ckb_jsonrpc_types::{JsonBytes, Uint128, Uint32, Uint64},
+    godwoken::{
+        AccountOverride, AccountStorageUsage, AccountTransaction, AccountTransactions, BackendInfo,
+        BlockEconomics, BlockParam, BlockStateRoot, BlockTag, BlockVerbosity, ConnectionStats,
+        FeeConfig, L1Commitment, L2BlockHeaderView, L2BlockTxHashesView, L2BlockVerboseView,
+        L2BlockView, L2BlockWithReceiptsView, L2BlocksView, L2ToL1Message, L2ToL1MessageProof,
+        L2TransactionWithBlockInfo, MemPoolRejection, MemPoolStats, NewAccount, NodeStatus,
+        PendingBlockView, ProducerBlockStats, ProducerStats, RollupAction, RunResult, SUDTBalance,
+        SUDTBalanceKey, SUDTBalancesProof, SUDTWithdrawalFee, SigningMessage, SigningPayload,
+        StoreColumnUsage, TransactionTrace, TxInclusionProof, TxReceipt, WithdrawalFeasibility,
+        WithdrawalStatus,
+    },
 };
+use gw_mem_pool::{clock::DevClock, pool::MemPoolSnapshot};
 use gw_store::{
+    block_reader::{BlockReader, DEFAULT_READ_AHEAD},
+    overlay_state::OverlayState,
+    smt_gc::get_pruning_horizon,
     state_db::{StateDBTransaction, StateDBVersion},
+    transaction::StoreTransaction,
     Store,
 };
 use gw_traits::CodeStore;
 use gw_types::{
-    packed::{self, BlockInfo},
+    packed::{self, BlockInfo, TransactionKey},
     prelude::*,
 };
 use jsonrpc_v2::{Data, MapRouter, Params, Server, Server as JsonrpcServer};
 use parking_lot::Mutex;
-use std::sync::Arc;
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize, Deserializer,
+};
+use std::{
+    cmp,
+    collections::BTreeMap,
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Max blocks scanned by a `get_producer_stats` request
+const MAX_PRODUCER_STATS_BLOCKS: u64 = 10_000;
+/// Max actions returned by a `get_challenge_history` request
+const MAX_CHALLENGE_HISTORY_LIMIT: u32 = 1000;
+/// Max balances proved by a `get_sudt_balances_proof` request
+const MAX_BALANCES_PROOF_KEYS: usize = 1000;
+/// Max txs returned by a `get_transactions_by_account` request
+const MAX_ACCOUNT_TRANSACTIONS_LIMIT: u32 = 1000;
+/// Max blocks returned by a `get_blocks` request
+const MAX_GET_BLOCKS_COUNT: u32 = 100;
+/// Max blocks of a `get_state_roots` request
+const MAX_STATE_ROOTS_COUNT: u64 = 1000;
+
+struct OpenRpcDocument(serde_json::Value);
+
+/// Max duration of an `execute_l2transaction` call
+struct ExecuteTimeout(Duration);
+
+/// Max syscalls recorded by a `trace_transaction` call
+struct MaxTraceSteps(usize);
+
+/// The timeout in milliseconds and the block of the state are optional
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExecuteL2TransactionParams {
+    Default((JsonBytes,)),
+    WithTimeout((JsonBytes, Uint64)),
+    AtBlock((JsonBytes, BlockParam)),
+    WithTimeoutAtBlock((JsonBytes, Uint64, BlockParam)),
+}
+
+/// Params of `get_block` and `get_block_by_number`, the verbosity is optional
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GetBlockParams<T> {
+    Default((T,)),
+    WithVerbosity((T, BlockVerbosity)),
+}
+
+impl<T> GetBlockParams<T> {
+    fn into_inner(self) -> (T, Option<BlockVerbosity>) {
+        match self {
+            GetBlockParams::Default((block,)) => (block, None),
+            GetBlockParams::WithVerbosity((block, verbosity)) => (block, Some(verbosity)),
+        }
+    }
+}
+
+/// Params of `dev_deposit`, a CKB deposit if the sUDT script hash isn't set
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DevDepositParams {
+    CKB((Script, Uint128)),
+    SUDT((Script, Uint128, JsonH256)),
+}
 
 // type alias
 type RPCServer = Arc<Server<MapRouter>>;
 type MemPool = Arc<Mutex<gw_mem_pool::pool::MemPool>>;
 type AccountID = Uint32;
-type JsonH256 = ckb_fixed_hash::H256;
 
-fn to_h256(v: JsonH256) -> H256 {
-    let h: [u8; 32] = v.into();
-    h.into()
+/// State of the tip block,
+/// the tip and the state are read in the same store transaction so they are consistent
+fn tip_state_db(db: &StoreTransaction) -> Result<StateDBTransaction<'_>> {
+    let tip_hash = db.get_tip_block_hash()?;
+    let state_db = StateDBTransaction::from_version(db, StateDBVersion::from_block_hash(tip_hash))?;
+    Ok(state_db)
 }
 
-fn to_jsonh256(v: H256) -> JsonH256 {
-    let h: [u8; 32] = v.into();
-    h.into()
+/// Hash of the last finalized block
+fn finalized_block_hash(db: &StoreTransaction) -> Result<H256> {
+    let tip_hash = db.get_tip_block_hash()?;
+    let global_state = db
+        .get_block_post_global_state(&tip_hash)?
+        .ok_or_else(|| anyhow::anyhow!("can't find global state of tip block {:?}", tip_hash))?;
+    let number: u64 = global_state.last_finalized_block_number().unpack();
+    let block_hash = db
+        .get_block_hash_by_number(number)?
+        .ok_or_else(|| anyhow::anyhow!("can't find finalized block {}", number))?;
+    Ok(block_hash)
 }
 
+/// Check the block is on the main chain,
+/// historical blocks are only served from the SMT pruning horizon
+fn check_block_state(db: &StoreTransaction, block_hash: H256) -> RpcResult<()> {
+    let not_found =
+        || RpcError::NotFound(format!("block {:?} isn't on the main chain", block_hash));
+    let number = db.get_block_number(&block_hash)?.ok_or_else(not_found)?;
+    if db.get_block_hash_by_number(number)? != Some(block_hash) {
+        return Err(not_found());
+    }
+    let horizon = get_pruning_horizon(db)?;
+    if number < horizon {
+        return Err(RpcError::NotFound(format!(
+            "state of block {} is pruned, only the states from block {} are served",
+            number, horizon
+        )));
+    }
+    Ok(())
+}
+
+/// State of a main chain block
+fn block_state_db(db: &StoreTransaction, block_hash: H256) -> RpcResult<StateDBTransaction<'_>> {
+    check_block_state(db, block_hash)?;
+    let state_db =
+        StateDBTransaction::from_version(db, StateDBVersion::from_block_hash(block_hash))?;
+    Ok(state_db)
+}
+
+/// Hash of a main chain block whose state is served
+fn block_hash_of_param(db: &StoreTransaction, block: BlockParam) -> RpcResult<H256> {
+    match block {
+        BlockParam::Tag(BlockTag::Latest) => Ok(db.get_tip_block_hash()?),
+        BlockParam::Tag(BlockTag::Finalized) => {
+            let block_hash = finalized_block_hash(db)?;
+            check_block_state(db, block_hash)?;
+            Ok(block_hash)
+        }
+        BlockParam::Tag(BlockTag::Pending) => Err(RpcError::invalid_params(
+            "pending state is only served by get_nonce and get_balance",
+        )),
+        BlockParam::Number { block_number } => {
+            let number = block_number.value();
+            let block_hash = db
+                .get_block_hash_by_number(number)?
+                .ok_or_else(|| RpcError::NotFound(format!("block {} not found", number)))?;
+            check_block_state(db, block_hash)?;
+            Ok(block_hash)
+        }
+        BlockParam::Hash { block_hash } => {
+            let block_hash = JsonH256::from(block_hash).into();
+            check_block_state(db, block_hash)?;
+            Ok(block_hash)
+        }
+    }
+}
+
+fn state_db(db: &StoreTransaction, block: BlockParam) -> RpcResult<StateDBTransaction<'_>> {
+    match block {
+        BlockParam::Tag(BlockTag::Latest) => Ok(tip_state_db(db)?),
+        BlockParam::Tag(BlockTag::Finalized) => block_state_db(db, finalized_block_hash(db)?),
+        block => {
+            let block_hash = block_hash_of_param(db, block)?;
+            let state_db =
+                StateDBTransaction::from_version(db, StateDBVersion::from_block_hash(block_hash))?;
+            Ok(state_db)
+        }
+    }
+}
+
+/// Run `f` on the pending state of the mem-pool, or on the state of the block
+fn with_state<T, F>(
+    db: &StoreTransaction,
+    block: BlockParam,
+    mem_pool: &MemPool,
+    f: F,
+) -> RpcResult<T>
+where
+    F: FnOnce(&dyn State) -> RpcResult<T>,
+{
+    if block != BlockParam::Tag(BlockTag::Pending) {
+        let state_db = state_db(db, block)?;
+        let tree = state_db.account_state_tree()?;
+        return f(&tree);
+    }
+    // only hold the mem-pool lock to clone the pending state
+    let (version, changes) = mem_pool.lock().pending_state();
+    let state_db = StateDBTransaction::from_version(db, version)?;
+    let tree = state_db.account_state_tree()?;
+    f(&OverlayState::new(&tree, changes))
+}
+
+/// Block tag of state RPCs which don't set one
+struct DefaultBlockTag(BlockTag);
+
+impl DefaultBlockTag {
+    fn or(&self, block: Option<BlockParam>) -> BlockParam {
+        block.unwrap_or_else(|| self.0.into())
+    }
+}
+
+/// Params of state RPCs, a block tag, number or hash can be appended as the last param
+struct StateParams<T>(T, Option<BlockParam>);
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for StateParams<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut params = Vec::<serde_json::Value>::deserialize(deserializer)?;
+        let block = match params.last().map(BlockParam::deserialize) {
+            Some(Ok(block)) => {
+                params.pop();
+                Some(block)
+            }
+            _ => None,
+        };
+        // a single param isn't wrapped in a tuple
+        let inner = if params.len() == 1 {
+            serde_json::from_value(params[0].clone())
+                .or_else(|_| serde_json::from_value(serde_json::Value::Array(params)))
+        } else {
+            serde_json::from_value(serde_json::Value::Array(params))
+        };
+        inner
+            .map(|inner| StateParams(inner, block))
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Reads the backends section of the node's config file
+type BackendsLoader = Arc<dyn Fn() -> Result<Vec<BackendConfig>> + Send + Sync>;
+
+struct ConfiguredBackends(Option<BackendsLoader>);
+
 pub struct Registry {
     mem_pool: MemPool,
     store: Store,
+    config: RPCServerConfig,
+    builtin_accounts: BuiltinAccounts,
+    connection_metrics: Arc<ConnectionMetrics>,
+    maintenance_mode: MaintenanceMode,
+    sync_progress: Arc<SyncProgress>,
+    backends_loader: Option<BackendsLoader>,
 }
 
 impl Registry {
-    pub fn new(mem_pool: MemPool, store: Store) -> Self {
-        Self { mem_pool, store }
+    pub fn new(
+        mem_pool: MemPool,
+        store: Store,
+        config: RPCServerConfig,
+        builtin_accounts: BuiltinAccounts,
+        sync_progress: Arc<SyncProgress>,
+    ) -> Self {
+        let maintenance_mode = MaintenanceMode::new(config.maintenance_mode);
+        Self {
+            mem_pool,
+            store,
+            config,
+            builtin_accounts,
+            connection_metrics: Default::default(),
+            maintenance_mode,
+            sync_progress,
+            backends_loader: None,
+        }
+    }
+
+    /// Set how `gw_admin_reload_backends` reads the backends section of the node's config file
+    pub fn with_backends_loader<F>(mut self, loader: F) -> Self
+    where
+        F: Fn() -> Result<Vec<BackendConfig>> + Send + Sync + 'static,
+    {
+        self.backends_loader = Some(Arc::new(loader));
+        self
+    }
+
+    pub fn mem_pool(&self) -> &MemPool {
+        &self.mem_pool
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    pub fn config(&self) -> &RPCServerConfig {
+        &self.config
+    }
+
+    pub fn builtin_accounts(&self) -> &BuiltinAccounts {
+        &self.builtin_accounts
+    }
+
+    pub fn connection_metrics(&self) -> &Arc<ConnectionMetrics> {
+        &self.connection_metrics
+    }
+
+    pub fn maintenance_mode(&self) -> &MaintenanceMode {
+        &self.maintenance_mode
+    }
+
+    /// Build the server of the `gw_admin_*` methods, it's only served on the admin listener
+    pub fn build_admin_rpc_server(&self) -> Result<RPCServer> {
+        let server = JsonrpcServer::new()
+            .with_data(Data::new(self.store.clone()))
+            .with_method("gw_admin_compact_store", compact_store)
+            .with_data(Data::new(self.maintenance_mode.clone()))
+            .with_method("gw_admin_set_maintenance_mode", set_maintenance_mode)
+            .with_data(Data(self.mem_pool.clone()))
+            .with_data(Data::new(ConfiguredBackends(self.backends_loader.clone())))
+            .with_method("gw_admin_reload_backends", reload_backends);
+        Ok(server.finish())
     }
 
     pub fn build_rpc_server(self) -> Result<RPCServer> {
         let mut server = JsonrpcServer::new();
+        let default_block_tag = if self.config.serve_finalized_state {
+            BlockTag::Finalized
+        } else {
+            BlockTag::Latest
+        };
+
+        let dev_mode = self.mem_pool.lock().is_dev_mode();
+        let document = openrpc::document(dev_mode, &self.config.disabled_methods);
 
         server = server
             .with_data(Data(self.mem_pool.clone()))
             .with_data(Data::new(self.store))
+            .with_data(Data::new(DefaultBlockTag(default_block_tag)))
+            .with_data(Data::new(ExecuteTimeout(Duration::from_millis(
+                self.config.execute_timeout_ms,
+            ))))
+            .with_data(Data::new(MaxTraceSteps(self.config.max_trace_steps)))
             .with_method("ping", ping)
             .with_method("get_tip_block_hash", get_tip_block_hash)
             .with_method("get_block_hash", get_block_hash)
             .with_method("get_block", get_block)
+            .with_method("get_block_header", get_block_header)
             .with_method("get_block_by_number", get_block_by_number)
+            .with_method("get_transaction", get_transaction)
+            .with_method("get_transaction_receipt", get_transaction_receipt)
+            .with_method("get_transactions_by_account", get_transactions_by_account)
+            .with_method("get_blocks", get_blocks)
+            .with_method("get_pending_block", get_pending_block)
             .with_method("get_balance", get_balance)
+            .with_method("get_sudt_total_supply", get_sudt_total_supply)
             .with_method("get_storage_at", get_storage_at)
+            .with_method("get_account_storage_usage", get_account_storage_usage)
             .with_method(
                 "get_account_id_by_script_hash",
                 get_account_id_by_script_hash,
@@ -66,72 +393,333 @@ impl Registry {
             .with_method("get_script", get_script)
             .with_method("get_script_hash", get_script_hash)
             .with_method("get_data", get_data)
+            .with_method("get_block_l2_to_l1_messages", get_block_l2_to_l1_messages)
+            .with_method("get_l2_to_l1_message_proof", get_l2_to_l1_message_proof)
+            .with_method("get_sudt_balances_proof", get_sudt_balances_proof)
+            .with_method("get_new_accounts", get_new_accounts)
+            .with_method("get_block_economics", get_block_economics)
+            .with_method("get_challenge_history", get_challenge_history)
+            .with_method("get_tx_inclusion_proof", get_tx_inclusion_proof)
+            .with_method("get_l1_commitment", get_l1_commitment)
+            .with_method("get_state_roots", get_state_roots)
+            .with_method("get_producer_stats", get_producer_stats)
+            .with_method("get_mempool_stats", get_mempool_stats)
+            .with_method("compute_signing_message", compute_signing_message)
             .with_method("execute_l2transaction", execute_l2transaction)
+            .with_method("execute_with_state_overrides", execute_with_state_overrides)
+            .with_method("estimate_gas", estimate_gas)
+            .with_method("trace_transaction", trace_transaction)
             .with_method("submit_l2transaction", submit_l2transaction)
-            .with_method("submit_withdrawal_request", submit_withdrawal_request);
+            .with_method("submit_withdrawal_request", submit_withdrawal_request)
+            .with_method("can_fulfill_withdrawal", can_fulfill_withdrawal)
+            .with_method("get_fee_config", get_fee_config)
+            .with_method("get_backends", get_backends)
+            .with_method("get_store_usage", get_store_usage)
+            .with_data(Data(self.connection_metrics))
+            .with_method("get_connection_stats", get_connection_stats)
+            .with_data(Data(self.sync_progress))
+            .with_method("get_node_status", get_node_status)
+            .with_data(Data::new(OpenRpcDocument(document)))
+            .with_method("rpc.discover", discover);
+        if dev_mode {
+            server = server
+                .with_method("dev_deposit", dev_deposit)
+                .with_method("dev_setTime", dev_set_time)
+                .with_method("dev_increaseTime", dev_increase_time);
+        }
 
         Ok(server.finish())
     }
 }
 
-async fn ping() -> Result<String> {
+async fn discover(document: Data<OpenRpcDocument>) -> RpcResult<serde_json::Value> {
+    Ok(document.0.clone())
+}
+
+async fn ping() -> RpcResult<String> {
     Ok("pong".to_string())
 }
 
 async fn get_block(
+    Params(params): Params<GetBlockParams<JsonH256>>,
+    store: Data<Store>,
+) -> RpcResult<Option<L2BlockVerboseView>> {
+    let (block_hash, verbosity) = params.into_inner();
+    let block_hash = H256::from(block_hash);
+    let db = store.begin_transaction();
+    Ok(load_block_view(&db, &block_hash, verbosity)?)
+}
+
+/// Load the block with the parts of the verbosity
+fn load_block_view(
+    db: &StoreTransaction,
+    block_hash: &H256,
+    verbosity: Option<BlockVerbosity>,
+) -> Result<Option<L2BlockVerboseView>> {
+    let block = match db.get_block(block_hash)? {
+        Some(block) => block,
+        None => return Ok(None),
+    };
+    let l1_commitment = load_l1_commitment(db, block_hash)?;
+    let block_view = match verbosity {
+        None => {
+            let mut block_view: L2BlockView = block.into();
+            block_view.l1_commitment = l1_commitment;
+            L2BlockVerboseView::Block(block_view)
+        }
+        Some(BlockVerbosity::Header) => {
+            let mut header_view: L2BlockHeaderView = block.into();
+            header_view.l1_commitment = l1_commitment;
+            L2BlockVerboseView::Header(header_view)
+        }
+        Some(BlockVerbosity::TxHashes) => {
+            let transactions = block
+                .transactions()
+                .into_iter()
+                .map(|tx| JsonH256::from(tx.hash()).into())
+                .collect();
+            let withdrawal_requests = block
+                .withdrawals()
+                .into_iter()
+                .map(|withdrawal| JsonH256::from(withdrawal.hash()).into())
+                .collect();
+            let mut header_view: L2BlockHeaderView = block.into();
+            header_view.l1_commitment = l1_commitment;
+            L2BlockVerboseView::TxHashes(L2BlockTxHashesView {
+                header: header_view,
+                transactions,
+                withdrawal_requests,
+            })
+        }
+        Some(BlockVerbosity::Full) => {
+            let mut receipts = Vec::with_capacity(block.transactions().len());
+            for tx in block.transactions() {
+                let tx_hash = tx.hash();
+                let receipt = db
+                    .get_transaction_receipt(&tx_hash.into())?
+                    .ok_or_else(|| anyhow::anyhow!("can't find receipt of tx {:?}", tx_hash))?;
+                receipts.push(receipt.into());
+            }
+            let mut block_view: L2BlockView = block.into();
+            block_view.l1_commitment = l1_commitment;
+            L2BlockVerboseView::Full(L2BlockWithReceiptsView {
+                block: block_view,
+                receipts,
+            })
+        }
+    };
+    Ok(Some(block_view))
+}
+
+async fn get_block_header(
     Params(params): Params<JsonH256>,
     store: Data<Store>,
-) -> Result<Option<L2BlockView>> {
-    let block_hash = to_h256(params);
+) -> RpcResult<Option<L2BlockHeaderView>> {
+    let block_hash = H256::from(params);
     let db = store.begin_transaction();
-    let block_opt = db.get_block(&block_hash)?.map(|block| {
-        let block_view: L2BlockView = block.into();
-        block_view
-    });
-    Ok(block_opt)
+    let header_opt = match db.get_block(&block_hash)? {
+        Some(block) => {
+            let mut header_view: L2BlockHeaderView = block.into();
+            header_view.l1_commitment = load_l1_commitment(&db, &block_hash)?;
+            Some(header_view)
+        }
+        None => None,
+    };
+    Ok(header_opt)
 }
 
 async fn get_block_by_number(
-    Params(params): Params<gw_jsonrpc_types::ckb_jsonrpc_types::Uint64>,
+    Params(params): Params<GetBlockParams<Uint64>>,
     store: Data<Store>,
-) -> Result<Option<L2BlockView>> {
-    let block_number = params.value();
+) -> RpcResult<Option<L2BlockVerboseView>> {
+    let (block_number, verbosity) = params.into_inner();
     let db = store.begin_transaction();
-    let block_hash = match db.get_block_hash_by_number(block_number)? {
+    let block_hash = match db.get_block_hash_by_number(block_number.value())? {
         Some(hash) => hash,
         None => return Ok(None),
     };
-    let block_opt = db.get_block(&block_hash)?.map(|block| {
-        let block_view: L2BlockView = block.into();
-        block_view
-    });
-    Ok(block_opt)
+    Ok(load_block_view(&db, &block_hash, verbosity)?)
+}
+
+fn load_l1_commitment(db: &StoreTransaction, block_hash: &H256) -> Result<Option<L1Commitment>> {
+    let committed_info = match db.get_l2block_committed_info(block_hash)? {
+        Some(committed_info) => committed_info,
+        None => return Ok(None),
+    };
+    let output_index = db.get_l2block_committed_output_index(block_hash)?;
+    Ok(Some(L1Commitment::new(committed_info, output_index)))
+}
+
+/// Consecutive main chain blocks from the number, stops at the tip
+async fn get_blocks(
+    Params((from_number, count, full)): Params<(Uint64, Uint32, bool)>,
+    store: Data<Store>,
+) -> RpcResult<L2BlocksView> {
+    let count = cmp::min(count.value(), MAX_GET_BLOCKS_COUNT) as u64;
+    let from_number = from_number.value();
+    let db = store.begin_transaction();
+    let mut blocks = Vec::new();
+    for number in from_number..from_number.saturating_add(count) {
+        let block_hash = match db.get_block_hash_by_number(number)? {
+            Some(hash) => hash,
+            None => break,
+        };
+        let block = db
+            .get_block(&block_hash)?
+            .ok_or_else(|| anyhow::anyhow!("can't find block {:?}", block_hash))?;
+        let l1_commitment = load_l1_commitment(&db, &block_hash)?;
+        blocks.push((block, l1_commitment));
+    }
+    let blocks_view = if full {
+        let blocks = blocks
+            .into_iter()
+            .map(|(block, l1_commitment)| {
+                let mut block_view: L2BlockView = block.into();
+                block_view.l1_commitment = l1_commitment;
+                block_view
+            })
+            .collect();
+        L2BlocksView::Blocks(blocks)
+    } else {
+        let headers = blocks
+            .into_iter()
+            .map(|(block, l1_commitment)| {
+                let mut header_view: L2BlockHeaderView = block.into();
+                header_view.l1_commitment = l1_commitment;
+                header_view
+            })
+            .collect();
+        L2BlocksView::Headers(headers)
+    };
+    Ok(blocks_view)
+}
+
+/// Returns the L1 transaction which committed the block
+async fn get_l1_commitment(
+    Params(params): Params<JsonH256>,
+    store: Data<Store>,
+) -> RpcResult<Option<L1Commitment>> {
+    let block_hash = H256::from(params);
+    let db = store.begin_transaction();
+    Ok(load_l1_commitment(&db, &block_hash)?)
+}
+
+/// State roots of the main chain blocks from `from` to `to` inclusive computed by this node,
+/// stops at the tip
+async fn get_state_roots(
+    Params((from, to)): Params<(Uint64, Uint64)>,
+    store: Data<Store>,
+) -> RpcResult<Vec<BlockStateRoot>> {
+    let (from, to) = (from.value(), to.value());
+    if from > to {
+        return Err(RpcError::InvalidParams(format!("from {} is greater than to {}", from, to)));
+    }
+    if to - from >= MAX_STATE_ROOTS_COUNT {
+        return Err(RpcError::InvalidParams(format!(
+            "at most {} blocks in a request",
+            MAX_STATE_ROOTS_COUNT
+        )));
+    }
+    let db = store.begin_transaction();
+    let mut state_roots = Vec::new();
+    for number in from..=to {
+        let block_hash = match db.get_block_hash_by_number(number)? {
+            Some(hash) => hash,
+            None => break,
+        };
+        let (post_account, post_block) = db
+            .get_block_local_post_state(&block_hash)?
+            .ok_or_else(|| anyhow::anyhow!("can't find local post state of {:?}", block_hash))?;
+        let l1_transaction_hash = db
+            .get_l2block_committed_info(&block_hash)?
+            .map(|committed_info| {
+                let tx_hash: H256 = committed_info.transaction_hash().unpack();
+                JsonH256::from(tx_hash).into()
+            });
+        let account_root: H256 = post_account.merkle_root().unpack();
+        let block_root: H256 = post_block.merkle_root().unpack();
+        state_roots.push(BlockStateRoot {
+            block_number: number.into(),
+            block_hash: JsonH256::from(block_hash).into(),
+            account_root: JsonH256::from(account_root).into(),
+            block_root: JsonH256::from(block_root).into(),
+            l1_transaction_hash,
+        });
+    }
+    Ok(state_roots)
+}
+
+async fn get_pending_block(
+    mem_pool: Data<MemPool>,
+    store: Data<Store>,
+) -> RpcResult<PendingBlockView> {
+    let parent_block = store.get_tip_block()?;
+    let (txs, withdrawals) = mem_pool.lock().package();
+    let number: u64 = parent_block.raw().number().unpack();
+    Ok(PendingBlockView {
+        number: (number + 1).into(),
+        parent_block_hash: parent_block.hash().into(),
+        transactions: txs.into_iter().map(Into::into).collect(),
+        withdrawal_requests: withdrawals.into_iter().map(Into::into).collect(),
+    })
 }
 
 async fn get_block_hash(
-    Params(params): Params<gw_jsonrpc_types::ckb_jsonrpc_types::Uint64>,
+    Params(params): Params<Uint64>,
     store: Data<Store>,
-) -> Result<Option<JsonH256>> {
+) -> RpcResult<Option<JsonH256>> {
     let block_number = params.value();
     let db = store.begin_transaction();
-    let hash_opt = db.get_block_hash_by_number(block_number)?.map(to_jsonh256);
+    let hash_opt = db
+        .get_block_hash_by_number(block_number)?
+        .map(JsonH256::from);
     Ok(hash_opt)
 }
 
-async fn get_tip_block_hash(store: Data<Store>) -> Result<JsonH256> {
+async fn get_tip_block_hash(store: Data<Store>) -> RpcResult<JsonH256> {
     let tip_block_hash = store.get_tip_block_hash()?;
-    Ok(to_jsonh256(tip_block_hash))
+    Ok(JsonH256::from(tip_block_hash))
 }
 
-async fn execute_l2transaction(
-    Params(params): Params<JsonBytes>,
-    mem_pool: Data<MemPool>,
+async fn compute_signing_message(
+    Params(payload): Params<SigningPayload>,
     store: Data<Store>,
-) -> Result<RunResult> {
-    let l2tx_bytes = params.into_bytes();
-    let tx = packed::L2Transaction::from_slice(&l2tx_bytes)?;
+) -> RpcResult<SigningMessage> {
+    let rollup_type_hash: ckb_fixed_hash::H256 = JsonH256::from(store.get_chain_id()?).into();
+    let signing_message = match payload {
+        SigningPayload::RawL2Transaction(raw_tx) => {
+            let db = store.begin_transaction();
+            let state_db = tip_state_db(&db)?;
+            let tree = state_db.account_state_tree()?;
+            let sender_script_hash = tree.get_script_hash(raw_tx.from_id.into())?;
+            let receiver_script_hash = tree.get_script_hash(raw_tx.to_id.into())?;
+            let message = raw_l2_transaction_signing_message(
+                &raw_tx,
+                &rollup_type_hash,
+                &JsonH256::from(sender_script_hash).into(),
+                &JsonH256::from(receiver_script_hash).into(),
+            );
+            SigningMessage {
+                message,
+                canonical_bytes: raw_tx.canonical_bytes(),
+            }
+        }
+        SigningPayload::RawWithdrawalRequest(raw_request) => SigningMessage {
+            message: raw_withdrawal_request_signing_message(&raw_request, &rollup_type_hash),
+            canonical_bytes: raw_request.canonical_bytes(),
+        },
+    };
+    Ok(signing_message)
+}
 
+/// Block info of the next block, it's used to execute txs on the tip state
+fn next_block_info(store: &Store) -> Result<BlockInfo> {
     let raw_block = store.get_tip_block()?.raw();
+    Ok(block_info_after(&raw_block))
+}
+
+/// Block info of the child block, it's used to execute txs on the state of the block
+fn block_info_after(raw_block: &packed::RawL2Block) -> BlockInfo {
     let block_producer_id = raw_block.block_producer_id();
     let timestamp = raw_block.timestamp();
     let number = {
@@ -144,75 +732,405 @@ async fn execute_l2transaction(
         .timestamp(timestamp)
         .number(number.pack())
         .build();
+    block_info
+}
+
+/// Block info of the block, it's used to execute a tx of the block at its position
+fn block_info_of(raw_block: &packed::RawL2Block) -> BlockInfo {
+    BlockInfo::new_builder()
+        .block_producer_id(raw_block.block_producer_id())
+        .timestamp(raw_block.timestamp())
+        .number(raw_block.number())
+        .build()
+}
 
-    let run_result: RunResult = mem_pool.lock().execute_transaction(tx, &block_info)?.into();
-    Ok(run_result)
+/// Move the snapshot to the state of the block if it's set. A tx of the block is executed at
+/// its position inside the block to replay it, other txs are executed as the next tx of the block
+fn snapshot_at_block(
+    snapshot: MemPoolSnapshot,
+    store: &Store,
+    block: Option<BlockParam>,
+    tx: &packed::L2Transaction,
+) -> RpcResult<(MemPoolSnapshot, BlockInfo)> {
+    let block = match block {
+        Some(block) => block,
+        None => return Ok((snapshot, next_block_info(store)?)),
+    };
+    let db = store.begin_transaction();
+    let block_hash = block_hash_of_param(&db, block)?;
+    let raw_block = db
+        .get_block(&block_hash)?
+        .ok_or_else(|| RpcError::NotFound(format!("block {:?} not found", block_hash)))?
+        .raw();
+    let tx_key = db
+        .get_transaction_info(&tx.hash().into())?
+        .map(|info| info.key());
+    match tx_key.map(|tx_key| split_transaction_key(&tx_key)) {
+        Some((tx_block_hash, tx_index)) if tx_block_hash == block_hash => {
+            // the block is replayed on the state of the parent block
+            check_block_state(&db, raw_block.parent_block_hash().unpack())?;
+            let snapshot = snapshot.at_transaction(block_hash, tx_index)?;
+            Ok((snapshot, block_info_of(&raw_block)))
+        }
+        _ => Ok((snapshot.at_block(block_hash), block_info_after(&raw_block))),
+    }
+}
+
+async fn execute_l2transaction(
+    Params(params): Params<ExecuteL2TransactionParams>,
+    mem_pool: Data<MemPool>,
+    store: Data<Store>,
+    max_timeout: Data<ExecuteTimeout>,
+) -> RpcResult<RunResult> {
+    let (l2tx, timeout_ms, block) = match params {
+        ExecuteL2TransactionParams::Default((l2tx,)) => (l2tx, None, None),
+        ExecuteL2TransactionParams::WithTimeout((l2tx, timeout_ms)) => {
+            (l2tx, Some(timeout_ms), None)
+        }
+        ExecuteL2TransactionParams::AtBlock((l2tx, block)) => (l2tx, None, Some(block)),
+        ExecuteL2TransactionParams::WithTimeoutAtBlock((l2tx, timeout_ms, block)) => {
+            (l2tx, Some(timeout_ms), Some(block))
+        }
+    };
+    let timeout = match timeout_ms {
+        Some(timeout_ms) => {
+            let timeout_ms: u64 = timeout_ms.into();
+            cmp::min(Duration::from_millis(timeout_ms), max_timeout.0)
+        }
+        None => max_timeout.0,
+    };
+    let l2tx_bytes = l2tx.into_bytes();
+    let tx = packed::L2Transaction::from_slice(&l2tx_bytes).map_err(RpcError::invalid_params)?;
+
+    // only hold the mem-pool lock to take a snapshot, the execution may be slow
+    let snapshot = mem_pool.lock().snapshot();
+    let (snapshot, block_info) = snapshot_at_block(snapshot, &store, block, &tx)?;
+    // execute in a blocking thread so the RPC worker is freed on timeout,
+    // the deadline stops the VM at its next syscall
+    let deadline = Instant::now() + timeout;
+    let execution = tokio::task::spawn_blocking(move || {
+        snapshot.execute_transaction_with_deadline(tx, &block_info, Some(deadline))
+    });
+    let run_result = match tokio::time::timeout(timeout, execution).await {
+        Ok(result) => result??,
+        Err(_) => return Err(RpcError::ExecutionTimeout),
+    };
+    Ok(run_result.into())
+}
+
+/// Trace the syscalls of a serialized L2Transaction, on the tip state or the state of a block.
+/// The trace is returned even if the tx fails
+async fn trace_transaction(
+    Params(StateParams(l2tx, block)): Params<StateParams<JsonBytes>>,
+    mem_pool: Data<MemPool>,
+    store: Data<Store>,
+    timeout: Data<ExecuteTimeout>,
+    max_steps: Data<MaxTraceSteps>,
+) -> RpcResult<TransactionTrace> {
+    let l2tx_bytes = l2tx.into_bytes();
+    let tx = packed::L2Transaction::from_slice(&l2tx_bytes).map_err(RpcError::invalid_params)?;
+
+    let snapshot = mem_pool.lock().snapshot();
+    let (snapshot, block_info) = snapshot_at_block(snapshot, &store, block, &tx)?;
+    let timeout = timeout.0;
+    let deadline = Instant::now() + timeout;
+    let max_steps = max_steps.0;
+    let execution = tokio::task::spawn_blocking(move || {
+        snapshot.trace_transaction(tx, &block_info, Some(deadline), max_steps)
+    });
+    let (result, steps) = match tokio::time::timeout(timeout, execution).await {
+        Ok(result) => result??,
+        Err(_) => return Err(RpcError::ExecutionTimeout),
+    };
+    let (run_result, error) = match result {
+        Ok(run_result) => (Some(run_result.into()), None),
+        Err(err) => (None, Some(err.to_string())),
+    };
+    Ok(TransactionTrace {
+        steps: steps.into_iter().map(Into::into).collect(),
+        run_result,
+        error,
+    })
+}
+
+/// Estimate the gas limit of a serialized L2Transaction on the pending state
+async fn estimate_gas(
+    Params((l2tx,)): Params<(JsonBytes,)>,
+    mem_pool: Data<MemPool>,
+    store: Data<Store>,
+    max_timeout: Data<ExecuteTimeout>,
+) -> RpcResult<Uint64> {
+    let l2tx_bytes = l2tx.into_bytes();
+    let tx = packed::L2Transaction::from_slice(&l2tx_bytes).map_err(RpcError::invalid_params)?;
+    let block_info = next_block_info(&store)?;
+
+    // the estimation executes the tx several times, run it like `execute_l2transaction`
+    let snapshot = mem_pool.lock().snapshot();
+    let timeout = max_timeout.0;
+    let deadline = Instant::now() + timeout;
+    let estimation =
+        tokio::task::spawn_blocking(move || snapshot.estimate_gas(tx, &block_info, Some(deadline)));
+    let gas = match tokio::time::timeout(timeout, estimation).await {
+        Ok(result) => result?.map_err(RpcError::rejected)?,
+        Err(_) => return Err(RpcError::ExecutionTimeout),
+    };
+    Ok(gas.into())
+}
+
+/// Execute a serialized RawL2Transaction on the tip state with the account overrides,
+/// the signature and the nonce are not verified
+async fn execute_with_state_overrides(
+    Params((raw_l2tx, overrides)): Params<(JsonBytes, Vec<AccountOverride>)>,
+    mem_pool: Data<MemPool>,
+    store: Data<Store>,
+    timeout: Data<ExecuteTimeout>,
+) -> RpcResult<RunResult> {
+    let raw_tx_bytes = raw_l2tx.into_bytes();
+    let raw_tx =
+        packed::RawL2Transaction::from_slice(&raw_tx_bytes).map_err(RpcError::invalid_params)?;
+    let overrides: Vec<gw_store::overlay_state::AccountOverride> =
+        overrides.into_iter().map(to_account_override).collect();
+    let block_info = next_block_info(&store)?;
+
+    let snapshot = mem_pool.lock().snapshot();
+    let timeout = timeout.0;
+    let deadline = Instant::now() + timeout;
+    let execution = tokio::task::spawn_blocking(move || {
+        snapshot.execute_with_state_overrides(&raw_tx, &block_info, &overrides, Some(deadline))
+    });
+    let run_result = match tokio::time::timeout(timeout, execution).await {
+        Ok(result) => result??,
+        Err(_) => return Err(RpcError::ExecutionTimeout),
+    };
+    Ok(run_result.into())
+}
+
+fn to_account_override(json: AccountOverride) -> gw_store::overlay_state::AccountOverride {
+    gw_store::overlay_state::AccountOverride {
+        account_id: json.account_id.value(),
+        balances: json
+            .balances
+            .into_iter()
+            .map(|b| (b.sudt_id.value(), b.balance.value()))
+            .collect(),
+        storage: json
+            .storage
+            .into_iter()
+            .map(|kv| (JsonH256::from(kv.k).into(), JsonH256::from(kv.v).into()))
+            .collect(),
+        script: json.script.map(Into::into),
+    }
 }
 
 async fn submit_l2transaction(
     Params(params): Params<JsonBytes>,
     mem_pool: Data<MemPool>,
-) -> Result<()> {
+) -> RpcResult<()> {
     let l2tx_bytes = params.into_bytes();
-    let tx = packed::L2Transaction::from_slice(&l2tx_bytes)?;
-    mem_pool.lock().push_transaction(tx)?;
+    let tx = packed::L2Transaction::from_slice(&l2tx_bytes).map_err(RpcError::invalid_params)?;
+    gw_mem_pool::pool::MemPool::push_transaction_with_lock(&mem_pool, tx)
+        .map_err(RpcError::rejected)?;
     Ok(())
 }
 
 async fn submit_withdrawal_request(
     Params(params): Params<JsonBytes>,
     mem_pool: Data<MemPool>,
-) -> Result<()> {
+) -> RpcResult<()> {
     let withdrawal_bytes = params.into_bytes();
-    let withdrawal = packed::WithdrawalRequest::from_slice(&withdrawal_bytes)?;
+    let withdrawal = packed::WithdrawalRequest::from_slice(&withdrawal_bytes)
+        .map_err(RpcError::invalid_params)?;
 
-    mem_pool.lock().push_withdrawal_request(withdrawal)?;
+    mem_pool
+        .lock()
+        .push_withdrawal_request(withdrawal)
+        .map_err(RpcError::rejected)?;
     Ok(())
 }
 
+async fn can_fulfill_withdrawal(
+    Params(params): Params<JsonBytes>,
+    mem_pool: Data<MemPool>,
+) -> RpcResult<WithdrawalFeasibility> {
+    let withdrawal_bytes = params.into_bytes();
+    let withdrawal = packed::WithdrawalRequest::from_slice(&withdrawal_bytes)
+        .map_err(RpcError::invalid_params)?;
+
+    let feasibility = mem_pool.lock().withdrawal_feasibility(&withdrawal)?;
+    let status = match feasibility.status {
+        gw_mem_pool::pool::WithdrawalStatus::NextBlock => WithdrawalStatus::NextBlock,
+        gw_mem_pool::pool::WithdrawalStatus::Queued => WithdrawalStatus::Queued,
+        gw_mem_pool::pool::WithdrawalStatus::Rejected => WithdrawalStatus::Rejected,
+    };
+    Ok(WithdrawalFeasibility {
+        status,
+        reason: feasibility.reason,
+        available_capacity: feasibility.available_capacity.into(),
+        available_amount: feasibility.available_amount.into(),
+        fee_capacity: feasibility.fee_capacity.into(),
+        fee_amount: feasibility.fee_amount.into(),
+        charged_capacity: feasibility.charged_capacity.into(),
+        charged_amount: feasibility.charged_amount.into(),
+    })
+}
+
+/// Queue a synthetic deposit into the next block, only registered in dev mode.
+/// A sUDT deposit carries the min deposit capacity
+async fn dev_deposit(
+    Params(params): Params<DevDepositParams>,
+    mem_pool: Data<MemPool>,
+) -> RpcResult<()> {
+    let (script, amount, sudt_script_hash) = match params {
+        DevDepositParams::CKB((script, amount)) => (script, amount, None),
+        DevDepositParams::SUDT((script, amount, sudt_script_hash)) => {
+            (script, amount, Some(sudt_script_hash))
+        }
+    };
+    let amount: u128 = amount.into();
+    let mut mem_pool = mem_pool.lock();
+    let (capacity, sudt_script_hash, amount) = match sudt_script_hash {
+        Some(sudt_script_hash) => {
+            let min_capacity = mem_pool
+                .generator()
+                .rollup_context()
+                .deposit_limits
+                .min_capacity;
+            (min_capacity, sudt_script_hash.into(), amount)
+        }
+        None => {
+            let capacity = u64::try_from(amount)
+                .map_err(|_| RpcError::invalid_params("CKB amount overflows u64"))?;
+            (capacity, CKB_SUDT_SCRIPT_ARGS, 0)
+        }
+    };
+    let request = packed::DepositionRequest::new_builder()
+        .script(script.into())
+        .capacity(capacity.pack())
+        .sudt_script_hash(sudt_script_hash.pack())
+        .amount(amount.pack())
+        .build();
+    mem_pool
+        .push_dev_deposit(request)
+        .map_err(RpcError::rejected)?;
+    Ok(())
+}
+
+fn dev_clock(mem_pool: &MemPool) -> RpcResult<Arc<DevClock>> {
+    mem_pool
+        .lock()
+        .dev_clock()
+        .cloned()
+        .ok_or_else(|| RpcError::invalid_params("dev mode isn't enabled"))
+}
+
+/// Set the time of the dev clock in milliseconds, only registered in dev mode
+async fn dev_set_time(
+    Params((timestamp,)): Params<(Uint64,)>,
+    mem_pool: Data<MemPool>,
+) -> RpcResult<()> {
+    let timestamp: u64 = timestamp.into();
+    dev_clock(&mem_pool)?.set_time(Duration::from_millis(timestamp));
+    Ok(())
+}
+
+/// Move the dev clock forward in milliseconds, only registered in dev mode
+async fn dev_increase_time(
+    Params((duration,)): Params<(Uint64,)>,
+    mem_pool: Data<MemPool>,
+) -> RpcResult<Uint64> {
+    let duration: u64 = duration.into();
+    let now = dev_clock(&mem_pool)?.increase_time(Duration::from_millis(duration));
+    Ok((now.as_millis() as u64).into())
+}
+
+async fn get_fee_config(mem_pool: Data<MemPool>) -> RpcResult<FeeConfig> {
+    let mem_pool = mem_pool.lock();
+    let generator = mem_pool.generator();
+    let withdrawal_fee = &generator.rollup_context().withdrawal_fee;
+    let withdrawal_sudt_fees = withdrawal_fee
+        .sudt_fees
+        .iter()
+        .map(|sudt_fee| SUDTWithdrawalFee {
+            sudt_script_hash: sudt_fee.sudt_script_hash.clone(),
+            fee: u128::from(sudt_fee.fee).into(),
+        })
+        .collect();
+    Ok(FeeConfig {
+        account_creation_fee: generator.account_creation_fee().into(),
+        withdrawal_ckb_fee: withdrawal_fee.ckb_fee.into(),
+        withdrawal_sudt_fees,
+    })
+}
+
 async fn get_balance(
-    Params((account_id, sudt_id)): Params<(AccountID, AccountID)>,
+    Params(StateParams((account_id, sudt_id), block)): Params<StateParams<(AccountID, AccountID)>>,
     store: Data<Store>,
-) -> Result<Uint128> {
+    mem_pool: Data<MemPool>,
+    default_tag: Data<DefaultBlockTag>,
+) -> RpcResult<Uint128> {
     let db = store.begin_transaction();
-    let tip_hash = db.get_tip_block_hash()?;
-    let state_db =
-        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_hash))?;
+    let block = default_tag.or(block);
+    let balance = with_state(&db, block, &mem_pool, |state| {
+        Ok(state.get_sudt_balance(sudt_id.into(), account_id.into())?)
+    })?;
+
+    Ok(balance.into())
+}
+
+async fn get_sudt_total_supply(
+    Params(StateParams(sudt_id, block)): Params<StateParams<AccountID>>,
+    store: Data<Store>,
+    default_tag: Data<DefaultBlockTag>,
+) -> RpcResult<Uint128> {
+    let db = store.begin_transaction();
+    let state_db = state_db(&db, default_tag.or(block))?;
 
     let tree = state_db.account_state_tree()?;
-    let balance = tree.get_sudt_balance(sudt_id.into(), account_id.into())?;
+    let total_supply = tree.get_sudt_total_supply(sudt_id.into())?;
 
-    Ok(balance.into())
+    Ok(total_supply.into())
 }
 
 async fn get_storage_at(
-    Params((account_id, key)): Params<(AccountID, JsonH256)>,
+    Params(StateParams((account_id, key), block)): Params<StateParams<(AccountID, JsonH256)>>,
     store: Data<Store>,
-) -> Result<JsonH256> {
+    default_tag: Data<DefaultBlockTag>,
+) -> RpcResult<JsonH256> {
     let db = store.begin_transaction();
-    let tip_hash = db.get_tip_block_hash()?;
-    let state_db =
-        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_hash))?;
+    let state_db = state_db(&db, default_tag.or(block))?;
 
     let tree = state_db.account_state_tree()?;
-    let key: H256 = to_h256(key);
+    let key = H256::from(key);
     let value = tree.get_value(account_id.into(), &key)?;
 
-    let json_value = to_jsonh256(value);
+    let json_value = JsonH256::from(value);
     Ok(json_value)
 }
 
+/// Storage usage is accounted by the chain, it isn't a part of the state
+async fn get_account_storage_usage(
+    Params(account_id): Params<AccountID>,
+    store: Data<Store>,
+) -> RpcResult<AccountStorageUsage> {
+    let db = store.begin_transaction();
+    let usage = db.get_account_storage_usage(account_id.into())?;
+    Ok(AccountStorageUsage {
+        account_id,
+        slots: usage.slots.into(),
+        data_bytes: usage.data_bytes.into(),
+    })
+}
+
 async fn get_account_id_by_script_hash(
-    Params(params): Params<JsonH256>,
+    Params(StateParams(params, block)): Params<StateParams<JsonH256>>,
     store: Data<Store>,
-) -> Result<Option<AccountID>> {
+    default_tag: Data<DefaultBlockTag>,
+) -> RpcResult<Option<AccountID>> {
     let db = store.begin_transaction();
-    let tip_hash = db.get_tip_block_hash()?;
-    let state_db =
-        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_hash))?;
+    let state_db = state_db(&db, default_tag.or(block))?;
     let tree = state_db.account_state_tree()?;
 
-    let script_hash = to_h256(params);
+    let script_hash = H256::from(params);
 
     let account_id_opt = tree
         .get_account_id_by_script_hash(&script_hash)?
@@ -221,61 +1139,618 @@ async fn get_account_id_by_script_hash(
     Ok(account_id_opt)
 }
 
-async fn get_nonce(Params(account_id): Params<AccountID>, store: Data<Store>) -> Result<Uint32> {
+async fn get_nonce(
+    Params(StateParams(account_id, block)): Params<StateParams<AccountID>>,
+    store: Data<Store>,
+    mem_pool: Data<MemPool>,
+    default_tag: Data<DefaultBlockTag>,
+) -> RpcResult<Uint32> {
     let db = store.begin_transaction();
-    let tip_hash = db.get_tip_block_hash()?;
-    let state_db =
-        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_hash))?;
-    let tree = state_db.account_state_tree()?;
-
-    let nonce = tree.get_nonce(account_id.into())?;
+    let block = default_tag.or(block);
+    let nonce = with_state(&db, block, &mem_pool, |state| {
+        Ok(state.get_nonce(account_id.into())?)
+    })?;
 
     Ok(nonce.into())
 }
 
 async fn get_script(
-    Params(params): Params<JsonH256>,
+    Params(StateParams(params, block)): Params<StateParams<JsonH256>>,
     store: Data<Store>,
-) -> Result<Option<Script>> {
+    default_tag: Data<DefaultBlockTag>,
+) -> RpcResult<Option<Script>> {
     let db = store.begin_transaction();
-    let tip_hash = db.get_tip_block_hash()?;
-    let state_db =
-        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_hash))?;
+    let state_db = state_db(&db, default_tag.or(block))?;
     let tree = state_db.account_state_tree()?;
 
-    let script_hash = to_h256(params);
+    let script_hash = H256::from(params);
     let script_opt = tree.get_script(&script_hash).map(Into::into);
 
     Ok(script_opt)
 }
 
 async fn get_script_hash(
-    Params(account_id): Params<AccountID>,
+    Params(StateParams(account_id, block)): Params<StateParams<AccountID>>,
     store: Data<Store>,
-) -> Result<JsonH256> {
+    default_tag: Data<DefaultBlockTag>,
+) -> RpcResult<JsonH256> {
     let db = store.begin_transaction();
-    let tip_hash = db.get_tip_block_hash()?;
-    let state_db =
-        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_hash))?;
+    let state_db = state_db(&db, default_tag.or(block))?;
     let tree = state_db.account_state_tree()?;
 
     let script_hash = tree.get_script_hash(account_id.into())?;
-    Ok(to_jsonh256(script_hash))
+    Ok(JsonH256::from(script_hash))
 }
 
 async fn get_data(
-    Params(data_hash): Params<JsonH256>,
+    Params(StateParams(data_hash, block)): Params<StateParams<JsonH256>>,
     store: Data<Store>,
-) -> Result<Option<JsonBytes>> {
+    default_tag: Data<DefaultBlockTag>,
+) -> RpcResult<Option<JsonBytes>> {
     let db = store.begin_transaction();
-    let tip_hash = db.get_tip_block_hash()?;
-    let state_db =
-        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_hash))?;
+    let state_db = state_db(&db, default_tag.or(block))?;
     let tree = state_db.account_state_tree()?;
 
     let data_opt = tree
-        .get_data(&to_h256(data_hash))
+        .get_data(&H256::from(data_hash))
         .map(JsonBytes::from_bytes);
 
     Ok(data_opt)
 }
+
+fn block_l2_to_l1_messages(
+    db: &StoreTransaction,
+    block: &packed::L2Block,
+) -> Result<Vec<L2ToL1Message>> {
+    let block_hash = block.hash();
+    let block_number: u64 = block.raw().number().unpack();
+    let mut messages = Vec::new();
+    for tx in block.transactions() {
+        let tx_hash = tx.hash();
+        let receipt = db
+            .get_transaction_receipt(&tx_hash.into())?
+            .ok_or_else(|| anyhow::anyhow!("can't find receipt of tx {:?}", tx_hash))?;
+        for log in receipt.logs() {
+            let service_flag: u8 = log.service_flag().into();
+            if service_flag != GW_LOG_L2_TO_L1_MESSAGE {
+                continue;
+            }
+            let from_id: u32 = log.account_id().unpack();
+            let payload: gw_types::bytes::Bytes = log.data().unpack();
+            let message_hash = build_l2_to_l1_message_hash(from_id, &payload);
+            messages.push(L2ToL1Message {
+                block_hash: block_hash.into(),
+                block_number: block_number.into(),
+                index: (messages.len() as u32).into(),
+                tx_hash: tx_hash.into(),
+                from_id: from_id.into(),
+                payload: JsonBytes::from_bytes(payload),
+                message_hash: JsonH256::from(message_hash).into(),
+            });
+        }
+    }
+    Ok(messages)
+}
+
+async fn get_block_l2_to_l1_messages(
+    Params(block_hash): Params<JsonH256>,
+    store: Data<Store>,
+) -> RpcResult<Vec<L2ToL1Message>> {
+    let db = store.begin_transaction();
+    let block = match db.get_block(&H256::from(block_hash))? {
+        Some(block) => block,
+        None => return Ok(Vec::new()),
+    };
+    Ok(block_l2_to_l1_messages(&db, &block)?)
+}
+
+async fn get_block_economics(
+    Params(block_hash): Params<JsonH256>,
+    store: Data<Store>,
+) -> RpcResult<Option<BlockEconomics>> {
+    let db = store.begin_transaction();
+    let economics = db.get_block_economics(&H256::from(block_hash))?;
+    Ok(economics.map(Into::into))
+}
+
+/// Challenges, cancellations and reverts on L1 from the L1 block number
+async fn get_challenge_history(
+    Params((from_l1_block_number, limit)): Params<(Uint64, Uint32)>,
+    store: Data<Store>,
+) -> RpcResult<Vec<RollupAction>> {
+    let limit = cmp::min(limit.value(), MAX_CHALLENGE_HISTORY_LIMIT);
+    let db = store.begin_transaction();
+    let actions = db
+        .get_rollup_actions(from_l1_block_number.value(), limit as usize)
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Ok(actions)
+}
+
+/// Accounts created in a block, ids are allocated in order so they are the range of
+/// the account count delta
+async fn get_new_accounts(
+    Params(block_hash): Params<JsonH256>,
+    store: Data<Store>,
+) -> RpcResult<Vec<NewAccount>> {
+    let db = store.begin_transaction();
+    let block_hash = H256::from(block_hash);
+    let raw_block = match db.get_block(&block_hash)? {
+        Some(block) => block.raw(),
+        None => return Ok(Vec::new()),
+    };
+    let prev_count: u32 = raw_block.prev_account().count().unpack();
+    let post_count: u32 = raw_block.post_account().count().unpack();
+    let state_db = block_state_db(&db, block_hash)?;
+    let tree = state_db.account_state_tree()?;
+    (prev_count..post_count)
+        .map(|account_id| -> RpcResult<NewAccount> {
+            let script_hash = tree.get_script_hash(account_id)?;
+            Ok(NewAccount {
+                account_id: account_id.into(),
+                script_hash: JsonH256::from(script_hash).into(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the proof of a L2 to L1 message
+/// the proof is only available after the block is finalized, since L1 scripts can't
+/// consume messages from a block that may be reverted by a challenge
+async fn get_l2_to_l1_message_proof(
+    Params((block_hash, index)): Params<(JsonH256, Uint32)>,
+    store: Data<Store>,
+) -> RpcResult<Option<L2ToL1MessageProof>> {
+    let db = store.begin_transaction();
+    let block_hash = H256::from(block_hash);
+    let block = match db.get_block(&block_hash)? {
+        Some(block) => block,
+        None => return Ok(None),
+    };
+    let message = match block_l2_to_l1_messages(&db, &block)?
+        .into_iter()
+        .nth(index.value() as usize)
+    {
+        Some(message) => message,
+        None => return Ok(None),
+    };
+
+    let tip_hash = db.get_tip_block_hash()?;
+    let last_finalized_block_number: u64 = db
+        .get_block_post_global_state(&tip_hash)?
+        .ok_or_else(|| anyhow::anyhow!("can't find tip global state"))?
+        .last_finalized_block_number()
+        .unpack();
+    let block_number: u64 = block.raw().number().unpack();
+    if block_number > last_finalized_block_number {
+        return Err(RpcError::NotFound(format!(
+            "block {} is not finalized, last finalized block is {}",
+            block_number, last_finalized_block_number
+        )));
+    }
+
+    let state_db = block_state_db(&db, block_hash)?;
+    let message_hash: H256 = JsonH256::from(message.message_hash.clone()).into();
+    let key = build_l2_to_l1_message_key(message_hash.as_slice());
+    let proof = state_db
+        .account_smt()?
+        .merkle_proof(vec![key])
+        .map_err(|err| anyhow::anyhow!("merkle proof error: {:?}", err))?
+        .compile(vec![(key, H256::one())])?;
+    let account_root: [u8; 32] = block.raw().post_account().merkle_root().unpack();
+
+    Ok(Some(L2ToL1MessageProof {
+        message,
+        account_root: account_root.into(),
+        proof: JsonBytes::from_vec(proof.0),
+    }))
+}
+
+/// Returns the sUDT balances of the accounts at a block with one compiled SMT proof,
+/// the proof is verified against the account root in the block header by
+/// `gw_common::merkle_utils::verify_sudt_balances_proof`
+async fn get_sudt_balances_proof(
+    Params((block_hash, keys)): Params<(JsonH256, Vec<SUDTBalanceKey>)>,
+    store: Data<Store>,
+) -> RpcResult<Option<SUDTBalancesProof>> {
+    let mut keys: Vec<(u32, u32)> = keys
+        .into_iter()
+        .map(|key| (key.account_id.value(), key.sudt_id.value()))
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    if keys.is_empty() || keys.len() > MAX_BALANCES_PROOF_KEYS {
+        return Err(RpcError::InvalidParams(format!(
+            "the number of keys must be in 1..={}",
+            MAX_BALANCES_PROOF_KEYS
+        )));
+    }
+
+    let db = store.begin_transaction();
+    let block_hash = H256::from(block_hash);
+    let block = match db.get_block(&block_hash)? {
+        Some(block) => block,
+        None => return Ok(None),
+    };
+    let state_db = block_state_db(&db, block_hash)?;
+    let tree = state_db.account_state_tree()?;
+    let mut balances = Vec::with_capacity(keys.len());
+    let mut leaves = Vec::with_capacity(keys.len());
+    for (account_id, sudt_id) in keys {
+        let balance = tree.get_sudt_balance(sudt_id, account_id)?;
+        balances.push(SUDTBalance {
+            account_id: account_id.into(),
+            sudt_id: sudt_id.into(),
+            balance: balance.into(),
+        });
+        leaves.push((
+            build_sudt_balance_key(sudt_id, account_id),
+            H256::from_u128(balance),
+        ));
+    }
+    let proof = state_db
+        .account_smt()?
+        .merkle_proof(leaves.iter().map(|(key, _)| *key).collect())
+        .map_err(|err| anyhow::anyhow!("merkle proof error: {:?}", err))?
+        .compile(leaves)?;
+    let raw_block = block.raw();
+    let block_number: u64 = raw_block.number().unpack();
+    let account_root: [u8; 32] = raw_block.post_account().merkle_root().unpack();
+
+    Ok(Some(SUDTBalancesProof {
+        block_hash: JsonH256::from(block_hash).into(),
+        block_number: block_number.into(),
+        account_root: account_root.into(),
+        balances,
+        proof: JsonBytes::from_vec(proof.0),
+    }))
+}
+
+// transaction key is block_hash | index(BE)
+fn split_transaction_key(tx_key: &TransactionKey) -> (H256, u32) {
+    let block_hash = {
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&tx_key.as_slice()[..32]);
+        H256::from(buf)
+    };
+    let tx_index = {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(&tx_key.as_slice()[32..]);
+        u32::from_be_bytes(buf)
+    };
+    (block_hash, tx_index)
+}
+
+async fn get_transaction(
+    Params(tx_hash): Params<JsonH256>,
+    store: Data<Store>,
+) -> RpcResult<Option<L2TransactionWithBlockInfo>> {
+    let db = store.begin_transaction();
+    let tx_hash = H256::from(tx_hash);
+    let tx_info = match db.get_transaction_info(&tx_hash)? {
+        Some(tx_info) => tx_info,
+        None => return Ok(None),
+    };
+    let tx_key = tx_info.key();
+    let tx = db
+        .get_transaction_by_key(&tx_key)?
+        .ok_or_else(|| anyhow::anyhow!("can't find transaction {:?}", tx_hash))?;
+    let (block_hash, tx_index) = split_transaction_key(&tx_key);
+    let block_number: u64 = tx_info.block_number().unpack();
+    Ok(Some(L2TransactionWithBlockInfo {
+        transaction: tx.into(),
+        block_hash: JsonH256::from(block_hash).into(),
+        block_number: block_number.into(),
+        tx_index: tx_index.into(),
+    }))
+}
+
+async fn get_transaction_receipt(
+    Params(tx_hash): Params<JsonH256>,
+    store: Data<Store>,
+) -> RpcResult<Option<TxReceipt>> {
+    let db = store.begin_transaction();
+    let receipt = db.get_transaction_receipt(&H256::from(tx_hash))?;
+    Ok(receipt.map(Into::into))
+}
+
+/// Main chain txs of an account in the block range, the cursor of the next page is the
+/// position of the last tx, i.e. `block_number(8 bytes, BE) | tx_index(4 bytes, BE)`
+async fn get_transactions_by_account(
+    Params((account_id, from_block, to_block, limit, cursor)): Params<(
+        Uint32,
+        Uint64,
+        Uint64,
+        Uint32,
+        Option<JsonBytes>,
+    )>,
+    store: Data<Store>,
+) -> RpcResult<AccountTransactions> {
+    let limit = cmp::min(limit.value(), MAX_ACCOUNT_TRANSACTIONS_LIMIT) as usize;
+    let after = match cursor {
+        Some(cursor) => {
+            let cursor = cursor.as_bytes();
+            if cursor.len() != 12 {
+                return Err(RpcError::invalid_params("invalid cursor, expect 12 bytes"));
+            }
+            let mut block_number = [0u8; 8];
+            block_number.copy_from_slice(&cursor[..8]);
+            let mut tx_index = [0u8; 4];
+            tx_index.copy_from_slice(&cursor[8..]);
+            Some((
+                u64::from_be_bytes(block_number),
+                u32::from_be_bytes(tx_index),
+            ))
+        }
+        None => None,
+    };
+    let db = store.begin_transaction();
+    let txs = db.get_account_transactions(
+        account_id.value(),
+        from_block.value(),
+        to_block.value(),
+        after,
+        limit,
+    );
+    // a full page may be followed by more txs
+    let cursor = match txs.last() {
+        Some((block_number, tx_index, _)) if txs.len() == limit => {
+            let mut cursor = block_number.to_be_bytes().to_vec();
+            cursor.extend_from_slice(&tx_index.to_be_bytes());
+            Some(JsonBytes::from_vec(cursor))
+        }
+        _ => None,
+    };
+    let transactions = txs
+        .into_iter()
+        .map(|(block_number, tx_index, tx_hash)| AccountTransaction {
+            tx_hash: JsonH256::from(tx_hash).into(),
+            block_number: block_number.into(),
+            tx_index: tx_index.into(),
+        })
+        .collect();
+    Ok(AccountTransactions {
+        transactions,
+        cursor,
+    })
+}
+
+/// Returns the inclusion proof of a transaction
+/// the proof can be verified with CKB data alone: the L1 transaction in
+/// `l2block_committed_info` carries the block in its witness, the block hash is
+/// the hash of `raw_l2block`, and the proof is verified against its tx_witness_root
+async fn get_tx_inclusion_proof(
+    Params(tx_hash): Params<JsonH256>,
+    store: Data<Store>,
+) -> RpcResult<Option<TxInclusionProof>> {
+    let db = store.begin_transaction();
+    let tx_hash = H256::from(tx_hash);
+    let tx_info = match db.get_transaction_info(&tx_hash)? {
+        Some(tx_info) => tx_info,
+        None => return Ok(None),
+    };
+    let (block_hash, tx_index) = split_transaction_key(&tx_info.key());
+    let block = db
+        .get_block(&block_hash)?
+        .ok_or_else(|| anyhow::anyhow!("can't find block {:?}", block_hash))?;
+    let committed_info = db.get_l2block_committed_info(&block_hash)?.ok_or_else(|| {
+        RpcError::NotFound(format!("block {:?} isn't committed on L1", block_hash))
+    })?;
+
+    let leaves: Vec<[u8; 32]> = block
+        .transactions()
+        .into_iter()
+        .map(|tx| tx.witness_hash())
+        .collect();
+    let tx_witness_hash = *leaves
+        .get(tx_index as usize)
+        .ok_or_else(|| RpcError::NotFound(format!("tx index {} out of bound", tx_index)))?;
+    let proof = calculate_merkle_proof(leaves, tx_index)
+        .map_err(|err| anyhow::anyhow!("merkle proof error: {:?}", err))?;
+
+    Ok(Some(TxInclusionProof {
+        tx_hash: JsonH256::from(tx_hash).into(),
+        tx_witness_hash: tx_witness_hash.into(),
+        tx_index: tx_index.into(),
+        block_hash: JsonH256::from(block_hash).into(),
+        raw_l2block: block.raw().into(),
+        proof: JsonBytes::from_vec(proof.0),
+        l2block_committed_info: committed_info.into(),
+    }))
+}
+
+async fn get_mempool_stats(mem_pool: Data<MemPool>) -> RpcResult<MemPoolStats> {
+    let stats = mem_pool.lock().stats();
+    Ok(MemPoolStats {
+        pending_accounts: (stats.pending_accounts as u32).into(),
+        txs: (stats.txs as u32).into(),
+        tx_bytes: (stats.tx_bytes as u64).into(),
+        withdrawals: (stats.withdrawals as u32).into(),
+        rejections: stats
+            .rejections
+            .into_iter()
+            .map(|(reason, count)| MemPoolRejection {
+                reason: reason.to_string(),
+                count: count.into(),
+            })
+            .collect(),
+    })
+}
+
+/// Returns block production statistics of the blocks in [from_block, to_block]
+async fn get_producer_stats(
+    Params((from_block, to_block)): Params<(Uint64, Uint64)>,
+    store: Data<Store>,
+) -> RpcResult<ProducerStats> {
+    let db = store.begin_transaction();
+    let tip_number: u64 = db.get_tip_block()?.raw().number().unpack();
+    let from_block = from_block.value();
+    let to_block = to_block.value().min(tip_number);
+    if from_block > to_block {
+        return Err(RpcError::InvalidParams(format!(
+            "invalid range [{}, {}], tip block is {}",
+            from_block, to_block, tip_number
+        )));
+    }
+    let block_count = to_block - from_block + 1;
+    if block_count > MAX_PRODUCER_STATS_BLOCKS {
+        return Err(RpcError::InvalidParams(format!(
+            "range exceeds max {} blocks",
+            MAX_PRODUCER_STATS_BLOCKS
+        )));
+    }
+
+    // read the blocks on the blocking pool, the handler only awaits the aggregation
+    let store = Store::clone(&store);
+    let aggregation = tokio::task::spawn_blocking(move || -> Result<_> {
+        // producer_id => (block_count, tx_count)
+        let mut producers: BTreeMap<u32, (u64, u64)> = BTreeMap::new();
+        let mut timestamps: Vec<u64> = Vec::with_capacity(block_count as usize);
+        let mut tx_count = 0u64;
+        let reader =
+            BlockReader::with_spawner(store, from_block, to_block, DEFAULT_READ_AHEAD, |task| {
+                tokio::task::spawn_blocking(task);
+            });
+        for block in reader {
+            let (_number, block) = block?;
+            let raw = block.raw();
+            let producer_id: u32 = raw.block_producer_id().unpack();
+            let block_tx_count: u32 = raw.submit_transactions().tx_count().unpack();
+            let entry = producers.entry(producer_id).or_default();
+            entry.0 += 1;
+            entry.1 += block_tx_count as u64;
+            tx_count += block_tx_count as u64;
+            timestamps.push(raw.timestamp().unpack());
+        }
+        Ok((producers, timestamps, tx_count))
+    });
+    let (producers, timestamps, tx_count) = aggregation.await??;
+
+    let mut intervals: Vec<u64> = timestamps
+        .windows(2)
+        .map(|w| w[1].saturating_sub(w[0]))
+        .collect();
+    let average_interval = if intervals.is_empty() {
+        0
+    } else {
+        intervals.iter().sum::<u64>() / intervals.len() as u64
+    };
+    // take the median interval as the slot, a longer interval means missed slots
+    intervals.sort_unstable();
+    let missed_slots = match intervals.get(intervals.len() / 2) {
+        Some(&slot) if slot > 0 => intervals
+            .iter()
+            .map(|interval| (interval / slot).saturating_sub(1))
+            .sum(),
+        _ => 0,
+    };
+
+    Ok(ProducerStats {
+        from_block: from_block.into(),
+        to_block: to_block.into(),
+        block_count: block_count.into(),
+        tx_count: tx_count.into(),
+        average_interval: average_interval.into(),
+        average_tx_count: (tx_count / block_count).into(),
+        missed_slots: missed_slots.into(),
+        producers: producers
+            .into_iter()
+            .map(
+                |(producer_id, (block_count, tx_count))| ProducerBlockStats {
+                    producer_id: producer_id.into(),
+                    block_count: block_count.into(),
+                    tx_count: tx_count.into(),
+                },
+            )
+            .collect(),
+    })
+}
+
+async fn compact_store(
+    Params(column): Params<Option<Uint32>>,
+    store: Data<Store>,
+) -> RpcResult<()> {
+    let column = match column {
+        Some(column) => {
+            let column: u32 = column.into();
+            let column = u8::try_from(column)
+                .map_err(|_| RpcError::InvalidParams(format!("invalid column {}", column)))?;
+            Some(column)
+        }
+        None => None,
+    };
+    store.compact(column)?;
+    Ok(())
+}
+
+/// Toggle the maintenance mode, the `submit_*` methods are rejected while it's on
+async fn set_maintenance_mode(
+    Params((enabled,)): Params<(bool,)>,
+    maintenance_mode: Data<MaintenanceMode>,
+) -> RpcResult<()> {
+    maintenance_mode.set(enabled);
+    Ok(())
+}
+
+/// Read the backends section of the config file again, a backend replaces the one of the same
+/// validator script type hash. The backends are only loaded from the paths of the config file
+async fn reload_backends(
+    mem_pool: Data<MemPool>,
+    backends: Data<ConfiguredBackends>,
+) -> RpcResult<()> {
+    let load = backends
+        .0
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("backends aren't loaded from a config file"))?;
+    let configs = load()?;
+    mem_pool.lock().reload_backends(&configs)?;
+    Ok(())
+}
+
+async fn get_backends(mem_pool: Data<MemPool>) -> RpcResult<Vec<BackendInfo>> {
+    let backends = mem_pool.lock().generator().backends();
+    let backends = backends
+        .into_iter()
+        .map(|backend| BackendInfo {
+            validator_script_type_hash: JsonH256::from(backend.validator_script_type_hash).into(),
+            validator_hash: JsonH256::from(backend.validator_hash).into(),
+            generator_hash: JsonH256::from(backend.generator_hash).into(),
+        })
+        .collect();
+    Ok(backends)
+}
+
+async fn get_store_usage(store: Data<Store>) -> RpcResult<Vec<StoreColumnUsage>> {
+    let usage = store
+        .get_usage()?
+        .into_iter()
+        .map(|usage| StoreColumnUsage {
+            column: (usage.column as u32).into(),
+            total_sst_files_size: usage.total_sst_files_size.into(),
+            live_data_size: usage.live_data_size.into(),
+            reclaimable_size: usage.reclaimable_size.into(),
+        })
+        .collect();
+    Ok(usage)
+}
+
+async fn get_connection_stats(metrics: Data<ConnectionMetrics>) -> RpcResult<ConnectionStats> {
+    Ok(metrics.stats())
+}
+
+async fn get_node_status(
+    sync_progress: Data<SyncProgress>,
+    store: Data<Store>,
+) -> RpcResult<NodeStatus> {
+    let tip_block_number: u64 = store.get_tip_block()?.raw().number().unpack();
+    let status = sync_progress.status();
+    Ok(NodeStatus {
+        tip_block_number: tip_block_number.into(),
+        l1_start_block_number: status.start_block.into(),
+        l1_synced_block_number: status.synced_block.into(),
+        l1_tip_block_number: status.tip_block.map(Into::into),
+        sync_percent: u32::from(status.percent).into(),
+        sync_eta_secs: status.eta.map(|eta| eta.as_secs().into()),
+    })
+}
@@ -1,172 +1,544 @@
-// Taken and adapted from https://github.com/smol-rs/smol/blob/ad0839e1b3700dd33abb9bf23c1efd3c83b5bb2d/examples/hyper-server.rs
-use std::net::SocketAddr;
-use std::net::{Shutdown, TcpListener, TcpStream};
-use std::pin::Pin;
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::task::{Context, Poll};
 
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
+use flate2::{
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+use futures::future::{try_join, try_join_all, Future, TryFutureExt};
+use hyper::server::conn::{AddrIncoming, AddrStream};
 use hyper::service::{make_service_fn, service_fn};
-use hyper::{body::HttpBody, Body, Request, Response, Server};
-use smol::{io, prelude::*, Async};
+use hyper::{body::HttpBody, header, Body, Request, Response, Server, StatusCode};
 
 use jsonrpc_v2::{RequestKind, ResponseObjects, Router, Server as JsonrpcServer};
+use serde_json::{json, Value};
 
+use crate::audit::AuditLog;
+use crate::connection::{self, ConnectionLimits, LimitedStream, RateLimiter};
+use crate::error::RATE_LIMITED_CODE;
+use crate::method_filter::{MethodFilter, RequestBody};
 use crate::registry::Registry;
+use crate::subscription::{self, Notifier};
+use gw_common::builtins::BuiltinAccounts;
+use gw_store::Store;
 
-pub async fn start_jsonrpc_server(listen_addr: SocketAddr, registry: Registry) -> Result<()> {
+/// Resolve listen addresses, a host name may resolve to both an IPv4 and an IPv6 address.
+///
+/// IPv6 addresses are written in brackets, e.g. `[::1]:8119`. On most systems `[::]:8119`
+/// is a dual-stack listener which also accepts IPv4 connections.
+pub fn resolve_listen_addrs(listens: &[String]) -> Result<Vec<SocketAddr>> {
+    let mut addrs = Vec::new();
+    for listen in listens {
+        let resolved = listen
+            .to_socket_addrs()
+            .map_err(|err| anyhow!("invalid JSONRPC listen address `{}`: {}", listen, err))?;
+        for addr in resolved {
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+    }
+    if addrs.is_empty() {
+        return Err(anyhow!("no JSONRPC listen address"));
+    }
+    Ok(addrs)
+}
+
+pub async fn start_jsonrpc_server(listen_addrs: Vec<SocketAddr>, registry: Registry) -> Result<()> {
+    let store = registry.store().clone();
+    let audit_log = match registry.config().audit_log {
+        Some(ref path) => AuditLog::open(path)?,
+        None => AuditLog::default(),
+    };
+    let limits = ConnectionLimits::from(registry.config());
+    let max_body_size = limits.max_request_body_size;
+    let metrics = Arc::clone(registry.connection_metrics());
+    let rate_limiter = RateLimiter::new(&limits, Arc::clone(&metrics));
+    let builtin_accounts = *registry.builtin_accounts();
+    let mem_pool = Arc::clone(registry.mem_pool());
+    let method_filter = MethodFilter::new(
+        registry.config().disabled_methods.clone(),
+        registry.maintenance_mode().clone(),
+    );
+    let call_checks = CallChecks::new(rate_limiter.clone(), method_filter, limits.max_batch_size);
+    let admin_server = match registry.config().admin_listen {
+        Some(ref listen) => Some(bind_admin_server(
+            resolve_listen_addrs(&[listen.clone()])?,
+            registry.build_admin_rpc_server()?,
+            audit_log.clone(),
+            max_body_size,
+        )?),
+        None => None,
+    };
     let rpc_server = registry.build_rpc_server()?;
-    let listener = Async::<TcpListener>::bind(listen_addr)?;
+    let notifier = Notifier::start(store.clone(), mem_pool);
 
-    // Format the full address.
-    let url = format!("http://{}", listener.get_ref().local_addr()?);
-    println!("JSONRPC server listening on {}", url);
+    // bind all addresses before serving, so a bad address fails the startup
+    let mut servers = Vec::with_capacity(listen_addrs.len());
+    for listen_addr in listen_addrs {
+        // hyper server runs on the tokio runtime, see gw_block_producer::runtime
+        let incoming = connection::bind(&listen_addr, limits.clone(), Arc::clone(&metrics))?;
+        println!("JSONRPC server listening on http://{}", listen_addr);
+        servers.push(Server::builder(incoming));
+    }
 
-    // Start a hyper server.
-    Server::builder(SmolListener::new(&listener))
-        .executor(SmolExecutor)
-        .serve(make_service_fn(move |_| {
+    // Start hyper servers.
+    let servers = servers.into_iter().map(|server| {
+        let rpc_server = Arc::clone(&rpc_server);
+        let store = store.clone();
+        let notifier = notifier.clone();
+        let audit_log = audit_log.clone();
+        let rate_limiter = rate_limiter.clone();
+        let call_checks = call_checks.clone();
+        server.serve(make_service_fn(move |conn: &LimitedStream| {
             let rpc_server = Arc::clone(&rpc_server);
-            async { Ok::<_, Error>(service_fn(move |req| serve(Arc::clone(&rpc_server), req))) }
+            let store = store.clone();
+            let notifier = notifier.clone();
+            let audit_log = audit_log.clone();
+            let rate_limiter = rate_limiter.clone();
+            let call_checks = call_checks.clone();
+            let remote_addr = conn.remote_addr();
+            let requests = conn.requests();
+            let timeouts_enabled = conn.timeouts_enabled();
+            async move {
+                Ok::<_, Error>(service_fn(move |req| {
+                    // the first call of the request, the other calls of a batch are counted
+                    // once the body is read
+                    let is_rate_limited = !rate_limiter.check(remote_addr.ip(), 1);
+                    let permit = requests.try_acquire();
+                    let rpc_server = Arc::clone(&rpc_server);
+                    let store = store.clone();
+                    let notifier = notifier.clone();
+                    let audit_log = audit_log.clone();
+                    let call_checks = call_checks.clone();
+                    let timeouts_enabled = Arc::clone(&timeouts_enabled);
+                    async move {
+                        if is_rate_limited {
+                            return error_response(
+                                StatusCode::TOO_MANY_REQUESTS,
+                                "too many requests",
+                            );
+                        }
+                        let _permit = match permit {
+                            Some(permit) => permit,
+                            None => {
+                                return error_response(
+                                    StatusCode::SERVICE_UNAVAILABLE,
+                                    "too many concurrent requests",
+                                )
+                            }
+                        };
+                        if subscription::is_upgrade_request(&req) {
+                            // WebSocket connections stay idle between messages
+                            timeouts_enabled.store(false, Ordering::Relaxed);
+                        }
+                        serve(
+                            rpc_server,
+                            store,
+                            notifier,
+                            builtin_accounts,
+                            audit_log,
+                            call_checks,
+                            max_body_size,
+                            remote_addr,
+                            req,
+                        )
+                        .await
+                    }
+                }))
+            }
         }))
-        .await?;
+    });
+    let admin_server = async move {
+        match admin_server {
+            Some(admin_server) => admin_server.await,
+            None => Ok(()),
+        }
+    };
+    try_join(try_join_all(servers).map_err(Error::from), admin_server).await?;
 
     Ok(())
 }
 
-// Serves a request and returns a response.
-async fn serve<R: Router + 'static>(
-    rpc: Arc<JsonrpcServer<R>>,
-    req: Request<Body>,
-) -> Result<Response<Body>> {
-    // Handler here is adapted from https://github.com/kardeiz/jsonrpc-v2/blob/1acf0b911c698413950d0b101ec4255cabd0d4ec/src/lib.rs#L1302
-    let mut buf = if let Some(content_length) = req
-        .headers()
-        .get(hyper::header::CONTENT_LENGTH)
-        .and_then(|x| x.to_str().ok())
-        .and_then(|x| x.parse().ok())
-    {
-        bytes_v10::BytesMut::with_capacity(content_length)
-    } else {
-        bytes_v10::BytesMut::default()
-    };
+/// Bind the admin listeners, the returned future serves the `gw_admin_*` methods on them.
+///
+/// The admin listeners are meant for the operator only, so requests are neither rate limited
+/// nor filtered, but they are still recorded in the audit log.
+fn bind_admin_server<R: Router + Send + Sync + 'static>(
+    listen_addrs: Vec<SocketAddr>,
+    rpc_server: Arc<JsonrpcServer<R>>,
+    audit_log: AuditLog,
+    max_body_size: usize,
+) -> Result<impl Future<Output = Result<()>>> {
+    let mut servers = Vec::with_capacity(listen_addrs.len());
+    for listen_addr in listen_addrs {
+        let incoming = AddrIncoming::bind(&listen_addr)
+            .map_err(|err| anyhow!("bind admin JSONRPC listen address {}: {}", listen_addr, err))?;
+        println!("Admin JSONRPC server listening on http://{}", listen_addr);
+        servers.push(Server::builder(incoming));
+    }
+    let servers = servers.into_iter().map(|server| {
+        let rpc_server = Arc::clone(&rpc_server);
+        let audit_log = audit_log.clone();
+        server.serve(make_service_fn(move |conn: &AddrStream| {
+            let rpc_server = Arc::clone(&rpc_server);
+            let audit_log = audit_log.clone();
+            let remote_addr = conn.remote_addr();
+            async move {
+                Ok::<_, Error>(service_fn(move |req| {
+                    serve_admin(
+                        Arc::clone(&rpc_server),
+                        audit_log.clone(),
+                        max_body_size,
+                        remote_addr,
+                        req,
+                    )
+                }))
+            }
+        }))
+    });
+    Ok(try_join_all(servers).map_ok(|_| ()).map_err(Error::from))
+}
 
-    let mut body = req.into_body();
+// Responses smaller than this are sent uncompressed.
+const MIN_COMPRESS_SIZE: usize = 1024;
 
-    while let Some(chunk) = body.data().await {
-        buf.extend(chunk?);
-    }
+const INVALID_REQUEST_CODE: i64 = -32600;
 
-    match rpc.handle(RequestKind::Bytes(buf.freeze())).await {
-        ResponseObjects::Empty => hyper::Response::builder()
-            .status(hyper::StatusCode::NO_CONTENT)
-            .body(hyper::Body::from(Vec::<u8>::new()))
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>),
-        json => serde_json::to_vec(&json)
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
-            .and_then(|json| {
-                hyper::Response::builder()
-                    .status(hyper::StatusCode::OK)
-                    .header("Content-Type", "application/json")
-                    .body(hyper::Body::from(json))
-                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
-            }),
-    }
-    .map_err(|e| anyhow::anyhow!("JSONRPC Request error: {:?}", e))
+/// Checks of the calls of a client, in the HTTP requests and the WebSocket messages
+#[derive(Clone)]
+pub(crate) struct CallChecks {
+    rate_limiter: RateLimiter,
+    method_filter: MethodFilter,
+    max_batch_size: usize,
 }
 
-// Spawns futures.
-#[derive(Clone)]
-struct SmolExecutor;
+impl CallChecks {
+    pub(crate) fn new(
+        rate_limiter: RateLimiter,
+        method_filter: MethodFilter,
+        max_batch_size: usize,
+    ) -> Self {
+        CallChecks {
+            rate_limiter,
+            method_filter,
+            max_batch_size,
+        }
+    }
 
-impl<F: Future + Send + 'static> hyper::rt::Executor<F> for SmolExecutor {
-    fn execute(&self, fut: F) {
-        smol::spawn(async { drop(fut.await) }).detach();
+    /// Returns an error response if the single or batch request body is refused.
+    /// Each call takes a token of the rate limiter, except the `taken_calls` which already did
+    pub(crate) fn check(&self, ip: IpAddr, body: &[u8], taken_calls: usize) -> Option<Value> {
+        let body = RequestBody::parse(body);
+        let calls = body.as_ref().map_or(1, RequestBody::calls);
+        let untaken_calls = calls.saturating_sub(taken_calls);
+        check_batch_size(calls, self.max_batch_size)
+            .or_else(|| {
+                body.as_ref()
+                    .and_then(|body| self.method_filter.check(body))
+            })
+            .or_else(|| {
+                if self.rate_limiter.check(ip, untaken_calls) {
+                    return None;
+                }
+                Some(json!({
+                    "jsonrpc": "2.0",
+                    "id": Value::Null,
+                    "error": { "code": RATE_LIMITED_CODE, "message": "too many requests" },
+                }))
+            })
     }
 }
 
-// Listens for incoming connections.
-struct SmolListener<'a> {
-    incoming: Pin<Box<dyn Stream<Item = io::Result<Async<TcpStream>>> + Send + 'a>>,
+/// Number of the calls of a single or batch request body, an unparsable body is one call
+pub fn count_calls(body: &[u8]) -> usize {
+    // an unparsable batch is reported by the JSONRPC server
+    RequestBody::parse(body).map_or(1, |body| body.calls())
 }
 
-impl<'a> SmolListener<'a> {
-    fn new(listener: &'a Async<TcpListener>) -> Self {
-        Self {
-            incoming: Box::pin(listener.incoming()),
-        }
+/// Returns an error response if a batch has more than `max_batch_size` calls,
+/// the calls of an accepted batch are served concurrently by the JSONRPC server
+fn check_batch_size(calls: usize, max_batch_size: usize) -> Option<Value> {
+    if calls <= max_batch_size {
+        return None;
     }
+    Some(json!({
+        "jsonrpc": "2.0",
+        "id": Value::Null,
+        "error": {
+            "code": INVALID_REQUEST_CODE,
+            "message": format!("batch size {} exceeds the limit {}", calls, max_batch_size),
+        },
+    }))
 }
 
-impl hyper::server::accept::Accept for SmolListener<'_> {
-    type Conn = SmolStream;
-    type Error = Error;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Identity,
+    Gzip,
+    Deflate,
+}
 
-    fn poll_accept(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context,
-    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
-        let stream = smol::ready!(self.incoming.as_mut().poll_next(cx)).unwrap()?;
+impl Encoding {
+    fn from_content_encoding(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "identity" => Some(Encoding::Identity),
+            "gzip" | "x-gzip" => Some(Encoding::Gzip),
+            "deflate" => Some(Encoding::Deflate),
+            _ => None,
+        }
+    }
 
-        let stream = SmolStream::Plain(stream);
+    // Pick the response encoding from an Accept-Encoding header, prefer gzip.
+    fn from_accept_encoding(value: &str) -> Self {
+        let accepted: Vec<String> = value
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let name = parts.next()?.trim().to_ascii_lowercase();
+                let quality = parts
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .filter_map(|q| q.parse::<f32>().ok())
+                    .next()
+                    .unwrap_or(1.0);
+                if quality > 0.0 {
+                    Some(name)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let accepts = |name: &str| accepted.iter().any(|a| a == name || a == "*");
+        if accepts("gzip") {
+            Encoding::Gzip
+        } else if accepts("deflate") {
+            Encoding::Deflate
+        } else {
+            Encoding::Identity
+        }
+    }
 
-        Poll::Ready(Some(Ok(stream)))
+    fn header_value(self) -> Option<&'static str> {
+        match self {
+            Encoding::Identity => None,
+            Encoding::Gzip => Some("gzip"),
+            Encoding::Deflate => Some("deflate"),
+        }
     }
 }
 
-// A TCP or TCP+TLS connection.
-enum SmolStream {
-    // A plain TCP connection.
-    Plain(Async<TcpStream>),
+fn decode(encoding: Encoding, data: &[u8], max_body_size: usize) -> std::io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(data.len());
+    // read one byte past the limit so oversized bodies can be detected
+    let limit = (max_body_size + 1) as u64;
+    match encoding {
+        Encoding::Identity => buf.extend_from_slice(data),
+        Encoding::Gzip => {
+            GzDecoder::new(data).take(limit).read_to_end(&mut buf)?;
+        }
+        Encoding::Deflate => {
+            ZlibDecoder::new(data).take(limit).read_to_end(&mut buf)?;
+        }
+    }
+    Ok(buf)
 }
 
-impl hyper::client::connect::Connection for SmolStream {
-    fn connected(&self) -> hyper::client::connect::Connected {
-        hyper::client::connect::Connected::new()
+fn encode(encoding: Encoding, data: &[u8]) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Identity => Ok(data.to_vec()),
+        Encoding::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
+        Encoding::Deflate => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
     }
 }
 
-impl tokio::io::AsyncRead for SmolStream {
-    fn poll_read(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &mut tokio::io::ReadBuf<'_>,
-    ) -> Poll<io::Result<()>> {
-        match &mut *self {
-            SmolStream::Plain(s) => {
-                return Pin::new(s)
-                    .poll_read(cx, buf.initialize_unfilled())
-                    .map_ok(|size| {
-                        buf.advance(size);
-                    });
-            }
-        }
+// Peer address of the request, followed by the addresses forwarded by proxies
+fn request_source(remote_addr: SocketAddr, req: &Request<Body>) -> String {
+    match req
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|x| x.to_str().ok())
+    {
+        Some(forwarded_for) => format!("{}, {}", remote_addr, forwarded_for),
+        None => remote_addr.to_string(),
     }
 }
 
-impl tokio::io::AsyncWrite for SmolStream {
-    fn poll_write(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &[u8],
-    ) -> Poll<io::Result<usize>> {
-        match &mut *self {
-            SmolStream::Plain(s) => Pin::new(s).poll_write(cx, buf),
-        }
+fn error_response(status: StatusCode, msg: &str) -> Result<Response<Body>> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(msg.to_string()))
+        .map_err(|e| anyhow::anyhow!("JSONRPC Request error: {:?}", e))
+}
+
+// Serves a request and returns a response.
+#[allow(clippy::too_many_arguments)]
+async fn serve<R: Router + Send + Sync + 'static>(
+    rpc: Arc<JsonrpcServer<R>>,
+    store: Store,
+    notifier: Notifier,
+    builtin_accounts: BuiltinAccounts,
+    audit_log: AuditLog,
+    call_checks: CallChecks,
+    max_body_size: usize,
+    remote_addr: SocketAddr,
+    req: Request<Body>,
+) -> Result<Response<Body>> {
+    let source = request_source(remote_addr, &req);
+    if subscription::is_upgrade_request(&req) {
+        return subscription::upgrade(
+            rpc,
+            store,
+            notifier,
+            builtin_accounts,
+            audit_log,
+            call_checks,
+            max_body_size,
+            remote_addr.ip(),
+            source,
+            req,
+        );
     }
+    // Handler here is adapted from https://github.com/kardeiz/jsonrpc-v2/blob/1acf0b911c698413950d0b101ec4255cabd0d4ec/src/lib.rs#L1302
+    let content_length: Option<usize> = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|x| x.to_str().ok())
+        .and_then(|x| x.parse().ok());
+    if content_length.unwrap_or(0) > max_body_size {
+        return error_response(StatusCode::PAYLOAD_TOO_LARGE, "request body too large");
+    }
+    let request_encoding = match req
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .map(|x| x.to_str().ok().and_then(Encoding::from_content_encoding))
+    {
+        None => Encoding::Identity,
+        Some(Some(encoding)) => encoding,
+        Some(None) => {
+            return error_response(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported content encoding",
+            )
+        }
+    };
+    let response_encoding = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|x| x.to_str().ok())
+        .map(Encoding::from_accept_encoding)
+        .unwrap_or(Encoding::Identity);
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        match &mut *self {
-            SmolStream::Plain(s) => Pin::new(s).poll_flush(cx),
+    let mut buf = bytes_v10::BytesMut::with_capacity(content_length.unwrap_or_default());
+
+    // read the body chunk by chunk, stop as soon as the limit is exceeded
+    let mut body = req.into_body();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if buf.len() + chunk.len() > max_body_size {
+            return error_response(StatusCode::PAYLOAD_TOO_LARGE, "request body too large");
         }
+        buf.extend(chunk);
     }
 
-    fn poll_shutdown(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        match &mut *self {
-            SmolStream::Plain(s) => {
-                s.get_ref().shutdown(Shutdown::Write)?;
-                Poll::Ready(Ok(()))
+    let request_body = if request_encoding == Encoding::Identity {
+        buf.freeze()
+    } else {
+        let decoded = match decode(request_encoding, &buf, max_body_size) {
+            Ok(decoded) => decoded,
+            Err(_) => {
+                return error_response(StatusCode::BAD_REQUEST, "invalid compressed request body")
             }
+        };
+        if decoded.len() > max_body_size {
+            return error_response(StatusCode::PAYLOAD_TOO_LARGE, "request body too large");
+        }
+        decoded.into()
+    };
+
+    // the first call took a token when the request arrived
+    if let Some(error) = call_checks.check(remote_addr.ip(), &request_body, 1) {
+        return Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(error.to_string()))
+            .map_err(|e| anyhow::anyhow!("JSONRPC Request error: {:?}", e));
+    }
+
+    audit_log.record(&source, &request_body);
+
+    match rpc.handle(RequestKind::Bytes(request_body)).await {
+        ResponseObjects::Empty => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::from(Vec::<u8>::new()))
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>),
+        json => serde_json::to_vec(&json)
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+            .and_then(|json| {
+                let encoding = if json.len() >= MIN_COMPRESS_SIZE {
+                    response_encoding
+                } else {
+                    Encoding::Identity
+                };
+                let json = encode(encoding, &json)
+                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+                let mut builder = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(header::CONTENT_TYPE, "application/json");
+                if let Some(value) = encoding.header_value() {
+                    builder = builder
+                        .header(header::CONTENT_ENCODING, value)
+                        .header(header::VARY, "Accept-Encoding");
+                }
+                builder
+                    .body(Body::from(json))
+                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+            }),
+    }
+    .map_err(|e| anyhow::anyhow!("JSONRPC Request error: {:?}", e))
+}
+
+// Serves a request of the admin listeners, plain JSON bodies only.
+async fn serve_admin<R: Router + Send + Sync + 'static>(
+    rpc: Arc<JsonrpcServer<R>>,
+    audit_log: AuditLog,
+    max_body_size: usize,
+    remote_addr: SocketAddr,
+    req: Request<Body>,
+) -> Result<Response<Body>> {
+    let source = request_source(remote_addr, &req);
+    let mut buf = bytes_v10::BytesMut::new();
+    let mut body = req.into_body();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if buf.len() + chunk.len() > max_body_size {
+            return error_response(StatusCode::PAYLOAD_TOO_LARGE, "request body too large");
         }
+        buf.extend(chunk);
+    }
+    let request_body = buf.freeze();
+
+    audit_log.record(&source, &request_body);
+
+    match rpc.handle(RequestKind::Bytes(request_body)).await {
+        ResponseObjects::Empty => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::from(Vec::<u8>::new())),
+        json => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&json)?)),
     }
+    .map_err(|e| anyhow::anyhow!("JSONRPC Request error: {:?}", e))
 }
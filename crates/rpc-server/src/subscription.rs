@@ -0,0 +1,882 @@
+//! WebSocket subscriptions
+//!
+//! A WebSocket connection is opened by upgrading a request to the JSONRPC listen address.
+//! Besides the registered JSONRPC methods, a connection serves:
+//!
+//! - `subscribe(topic, ...)` returns a subscription id
+//! - `unsubscribe(subscription_id)` returns whether the subscription existed
+//!
+//! Topics:
+//!
+//! - `subscribe("new_blocks")` pushes the number and hash of every new block
+//! - `subscribe("included_transactions")` pushes the hash and position of every tx included
+//!   in a new block
+//! - `subscribe("new_pending_transactions")` pushes the hash of every tx entering the mem-pool
+//! - `subscribe("account_changes", account_ids)` pushes each subscribed account whose nonce
+//!   or sUDT balances are changed by a new block
+//! - `subscribe("dropped_withdrawals")` pushes the hash and the reason of every withdrawal
+//!   request dropped from the mem-pool
+//! - `subscribe("finalized_withdrawals")` pushes every withdrawal of a block once the block
+//!   is finalized
+//! - `subscribe("challenges")` pushes every challenge started on layer1
+//!
+//! Notifications are pushed as:
+//!
+//! ```json
+//! {"jsonrpc": "2.0", "method": "subscription", "params": {"subscription": id, "result": result}}
+//! ```
+//!
+//! Except for `new_pending_transactions` and `account_changes`, the results are the `Event`s of
+//! the gw-events crate, serialized with their type, e.g.
+//! `{"type": "new_block", "payload": {"block_number": "0x1", "block_hash": "0x..."}}`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use futures::{SinkExt, StreamExt};
+use gw_common::{
+    builtins::BuiltinAccounts,
+    h256_ext::H256Ext,
+    state::{build_account_field_key, build_account_key, State, GW_ACCOUNT_NONCE},
+    JsonH256, H256,
+};
+use gw_events::{
+    ChallengeStarted, ChallengeTargetType, Event, NewBlock, TxIncluded, WithdrawalDropped,
+    WithdrawalFinalized,
+};
+use gw_jsonrpc_types::{
+    ckb_jsonrpc_types::{Uint32, Uint64},
+    godwoken::{AccountChanges, SUDTBalanceChange},
+};
+use gw_mem_pool::pool::{MemPool, MemPoolEvent};
+use gw_store::{
+    state_db::{StateDBTransaction, StateDBVersion},
+    transaction::StoreTransaction,
+    Store,
+};
+use gw_traits::CodeStore;
+use gw_types::{
+    core::{ChallengeTargetType as PackedChallengeTargetType, RollupActionType},
+    packed::{L2Block, RollupActionRecord},
+    prelude::*,
+};
+use hyper::{header, upgrade::Upgraded, Body, Request, Response, StatusCode};
+use jsonrpc_v2::{RequestKind, ResponseObjects, Router, Server as JsonrpcServer};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use tokio_tungstenite::{
+    tungstenite::{
+        handshake::derive_accept_key,
+        protocol::{Role, WebSocketConfig},
+        Message,
+    },
+    WebSocketStream,
+};
+
+use crate::audit::AuditLog;
+use crate::server::CallChecks;
+
+const NOTIFIER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const NEW_BLOCK_CHANNEL_SIZE: usize = 64;
+const NEW_TX_CHANNEL_SIZE: usize = 1024;
+const EVENT_CHANNEL_SIZE: usize = 1024;
+// Max subscribed accounts of a connection
+const MAX_SUBSCRIBED_ACCOUNTS: usize = 10_000;
+
+const INVALID_PARAMS_CODE: i64 = -32602;
+
+/// Broadcast (number, hash) of new blocks, hashes of new mem-pool txs, and the dropped
+/// withdrawals, finalized withdrawals and started challenges as events
+#[derive(Clone)]
+pub struct Notifier {
+    blocks: broadcast::Sender<(u64, H256)>,
+    txs: broadcast::Sender<H256>,
+    events: broadcast::Sender<Event>,
+}
+
+impl Notifier {
+    /// Start polling the store tip and the mem-pool in background
+    pub fn start(store: Store, mem_pool: Arc<Mutex<MemPool>>) -> Self {
+        let (blocks, _) = broadcast::channel(NEW_BLOCK_CHANNEL_SIZE);
+        let (txs, _) = broadcast::channel(NEW_TX_CHANNEL_SIZE);
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_SIZE);
+        let notifier = Notifier {
+            blocks: blocks.clone(),
+            txs: txs.clone(),
+            events: events.clone(),
+        };
+        let finality_blocks: u64 = mem_pool
+            .lock()
+            .generator()
+            .rollup_context()
+            .rollup_config
+            .finality_blocks()
+            .unpack();
+        tokio::spawn(async move {
+            let mut last_tip = None;
+            let mut last_txs = None;
+            let mut last_rollup_action = None;
+            let mut interval = tokio::time::interval(NOTIFIER_POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                let new_blocks = NewBlockSenders {
+                    blocks: &blocks,
+                    events: &events,
+                    finality_blocks,
+                };
+                if let Err(err) = notify_new_blocks(&store, &new_blocks, &mut last_tip) {
+                    eprintln!("block notifier error: {}", err);
+                }
+                notify_new_txs(&mem_pool, &txs, &mut last_txs);
+                notify_dropped_withdrawals(&mem_pool, &events);
+                notify_challenges(&store, &events, &mut last_rollup_action);
+            }
+        });
+        notifier
+    }
+
+    pub fn subscribe_blocks(&self) -> broadcast::Receiver<(u64, H256)> {
+        self.blocks.subscribe()
+    }
+
+    pub fn subscribe_txs(&self) -> broadcast::Receiver<H256> {
+        self.txs.subscribe()
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+}
+
+struct NewBlockSenders<'a> {
+    blocks: &'a broadcast::Sender<(u64, H256)>,
+    events: &'a broadcast::Sender<Event>,
+    finality_blocks: u64,
+}
+
+fn notify_new_blocks(
+    store: &Store,
+    senders: &NewBlockSenders,
+    last_tip: &mut Option<(u64, H256)>,
+) -> Result<()> {
+    let db = store.begin_transaction();
+    let tip_hash = db.get_tip_block_hash()?;
+    let tip_number: u64 = db.get_tip_block()?.raw().number().unpack();
+    let start = match *last_tip {
+        Some((_, hash)) if hash == tip_hash => return Ok(()),
+        Some((number, _)) if number < tip_number => number + 1,
+        // the tip is reverted, only notify the new tip
+        Some(_) => tip_number,
+        // don't replay history blocks
+        None => {
+            *last_tip = Some((tip_number, tip_hash));
+            return Ok(());
+        }
+    };
+    for number in start..=tip_number {
+        if let Some(block_hash) = db.get_block_hash_by_number(number)? {
+            // it's fine that no connection is listening
+            let _ = senders.blocks.send((number, block_hash));
+        }
+        // a block is finalized once it's `finality_blocks` behind the tip
+        let finalized_number = match number.checked_sub(senders.finality_blocks) {
+            Some(finalized_number) if senders.events.receiver_count() > 0 => finalized_number,
+            _ => continue,
+        };
+        if let Some(block_hash) = db.get_block_hash_by_number(finalized_number)? {
+            if let Some(block) = db.get_block(&block_hash)? {
+                for event in finalized_withdrawals(&block) {
+                    let _ = senders.events.send(event);
+                }
+            }
+        }
+    }
+    *last_tip = Some((tip_number, tip_hash));
+    Ok(())
+}
+
+/// `WithdrawalFinalized` events of the withdrawals of a finalized block
+pub fn finalized_withdrawals(block: &L2Block) -> Vec<Event> {
+    let block_number: u64 = block.raw().number().unpack();
+    let block_hash: [u8; 32] = block.hash();
+    block
+        .withdrawals()
+        .into_iter()
+        .map(|withdrawal| {
+            let raw = withdrawal.raw();
+            let account_script_hash: [u8; 32] = raw.account_script_hash().unpack();
+            let sudt_script_hash: [u8; 32] = raw.sudt_script_hash().unpack();
+            let capacity: u64 = raw.capacity().unpack();
+            let amount: u128 = raw.amount().unpack();
+            Event::WithdrawalFinalized(WithdrawalFinalized {
+                withdrawal_hash: withdrawal.hash().into(),
+                block_number: block_number.into(),
+                block_hash: block_hash.into(),
+                account_script_hash: account_script_hash.into(),
+                capacity: capacity.into(),
+                sudt_script_hash: sudt_script_hash.into(),
+                amount: amount.into(),
+            })
+        })
+        .collect()
+}
+
+/// `ChallengeStarted` event of a rollup action, None if the action doesn't start a challenge
+pub fn challenge_started(record: &RollupActionRecord) -> Option<Event> {
+    match RollupActionType::try_from(record.action_type()) {
+        Ok(RollupActionType::EnterChallenge) => {}
+        _ => return None,
+    }
+    let target = record.challenge_target();
+    let target_type = match PackedChallengeTargetType::try_from(target.target_type()).ok()? {
+        PackedChallengeTargetType::Transaction => ChallengeTargetType::Transaction,
+        PackedChallengeTargetType::Withdrawal => ChallengeTargetType::Withdrawal,
+    };
+    let block_hash: [u8; 32] = target.block_hash().unpack();
+    let target_index: u32 = target.target_index().unpack();
+    let l1_transaction_hash: [u8; 32] = record.l1_tx_hash().unpack();
+    Some(Event::ChallengeStarted(ChallengeStarted {
+        block_hash: block_hash.into(),
+        target_type,
+        target_index: target_index.into(),
+        l1_transaction_hash: l1_transaction_hash.into(),
+    }))
+}
+
+/// The rollup actions are polled after the last notified one, the history is not replayed
+fn notify_challenges(
+    store: &Store,
+    sender: &broadcast::Sender<Event>,
+    last_action: &mut Option<(u64, [u8; 32])>,
+) {
+    let db = store.begin_transaction();
+    let from_l1_block_number = last_action.map_or(0, |(number, _)| number);
+    let is_first_poll = last_action.is_none();
+    for record in db.get_rollup_actions(from_l1_block_number, usize::MAX) {
+        let l1_block_number: u64 = record.l1_block_number().unpack();
+        let l1_tx_hash: [u8; 32] = record.l1_tx_hash().unpack();
+        let key = (l1_block_number, l1_tx_hash);
+        if last_action.map_or(false, |last| key <= last) {
+            continue;
+        }
+        *last_action = Some(key);
+        if is_first_poll {
+            continue;
+        }
+        if let Some(event) = challenge_started(&record) {
+            let _ = sender.send(event);
+        }
+    }
+    if last_action.is_none() {
+        // no action yet, later actions are all new
+        *last_action = Some((0, [0u8; 32]));
+    }
+}
+
+/// The mem-pool is diffed against the previous poll, txs entered and left between two
+/// polls are missed
+fn notify_new_txs(
+    mem_pool: &Mutex<MemPool>,
+    sender: &broadcast::Sender<H256>,
+    last_txs: &mut Option<HashSet<H256>>,
+) {
+    if sender.receiver_count() == 0 {
+        *last_txs = None;
+        return;
+    }
+    let txs: HashSet<H256> = mem_pool
+        .lock()
+        .pending()
+        .values()
+        .flat_map(|entry_list| entry_list.txs.iter().map(|tx| tx.hash().into()))
+        .collect();
+    // the first poll after a receiver is subscribed only records the txs
+    if let Some(last_txs) = last_txs {
+        for tx_hash in txs.difference(last_txs) {
+            let _ = sender.send(*tx_hash);
+        }
+    }
+    *last_txs = Some(txs);
+}
+
+/// The mem-pool events are drained on every poll, the other events are discarded
+fn notify_dropped_withdrawals(mem_pool: &Mutex<MemPool>, sender: &broadcast::Sender<Event>) {
+    for event in mem_pool.lock().drain_events() {
+        if let MemPoolEvent::WithdrawalDropped {
+            withdrawal_hash,
+            reason,
+        } = event
+        {
+            let _ = sender.send(Event::WithdrawalDropped(WithdrawalDropped {
+                withdrawal_hash: JsonH256::from(withdrawal_hash).into(),
+                reason,
+            }));
+        }
+    }
+}
+
+/// sUDT accounts found in the state, scanned incrementally as accounts are created
+struct SUDTAccounts {
+    ckb_sudt_id: u32,
+    ids: Vec<u32>,
+    scanned_count: u32,
+}
+
+impl SUDTAccounts {
+    fn new(ckb_sudt_id: u32) -> Self {
+        SUDTAccounts {
+            ckb_sudt_id,
+            ids: Vec::new(),
+            scanned_count: 0,
+        }
+    }
+
+    /// Returns true if new sUDT accounts are found
+    fn scan<S: State + CodeStore>(&mut self, state: &S) -> Result<bool> {
+        let account_count = state.get_account_count()?;
+        if account_count <= self.scanned_count {
+            return Ok(false);
+        }
+        // all sUDT accounts share the script code hash of the CKB sUDT account
+        let sudt_code_hash = match state.get_script(&state.get_script_hash(self.ckb_sudt_id)?) {
+            Some(script) => script.code_hash(),
+            None => return Ok(false),
+        };
+        let mut found = false;
+        for id in self.scanned_count..account_count {
+            let script_hash = state.get_script_hash(id)?;
+            let is_sudt = state
+                .get_script(&script_hash)
+                .map(|script| script.code_hash().as_slice() == sudt_code_hash.as_slice())
+                .unwrap_or(false);
+            if is_sudt {
+                self.ids.push(id);
+                found = true;
+            }
+        }
+        self.scanned_count = account_count;
+        Ok(found)
+    }
+}
+
+struct AccountChangesSubscription {
+    account_ids: Vec<u32>,
+    // state key -> (account id, sUDT id), the nonce key has no sUDT id
+    keys: HashMap<H256, (u32, Option<u32>)>,
+}
+
+impl AccountChangesSubscription {
+    fn new(account_ids: Vec<u32>, sudt_accounts: &SUDTAccounts) -> Self {
+        let mut subscription = AccountChangesSubscription {
+            account_ids,
+            keys: HashMap::new(),
+        };
+        subscription.build_keys(sudt_accounts);
+        subscription
+    }
+
+    fn build_keys(&mut self, sudt_accounts: &SUDTAccounts) {
+        self.keys.clear();
+        for &account_id in &self.account_ids {
+            let nonce_key = build_account_field_key(account_id, GW_ACCOUNT_NONCE);
+            self.keys.insert(nonce_key, (account_id, None));
+            for &sudt_id in &sudt_accounts.ids {
+                let balance_key = build_account_key(sudt_id, H256::from_u32(account_id).as_slice());
+                self.keys.insert(balance_key, (account_id, Some(sudt_id)));
+            }
+        }
+    }
+}
+
+/// Collect changes of the subscribed accounts from the block's touched keys
+fn account_changes<S: State>(
+    subscription: &AccountChangesSubscription,
+    block: &L2Block,
+    prev_state: &S,
+    post_state: &S,
+) -> Result<Vec<AccountChanges>> {
+    let block_hash: [u8; 32] = block.hash();
+    let block_number: u64 = block.raw().number().unpack();
+    let mut changes: BTreeMap<u32, AccountChanges> = BTreeMap::new();
+    for kv in block.kv_state() {
+        let (key, _): ([u8; 32], [u8; 32]) = kv.unpack();
+        let key: H256 = key.into();
+        let (account_id, sudt_id) = match subscription.keys.get(&key) {
+            Some(field) => *field,
+            None => continue,
+        };
+        let old_value = prev_state.get_raw(&key)?;
+        let new_value = post_state.get_raw(&key)?;
+        if old_value == new_value {
+            continue;
+        }
+        let entry = changes.entry(account_id).or_insert_with(|| AccountChanges {
+            block_number: block_number.into(),
+            block_hash: block_hash.into(),
+            account_id: account_id.into(),
+            nonce: None,
+            balances: Vec::new(),
+        });
+        match sudt_id {
+            None => entry.nonce = Some(new_value.to_u32().into()),
+            Some(sudt_id) => entry.balances.push(SUDTBalanceChange {
+                sudt_id: sudt_id.into(),
+                old_balance: old_value.to_u128().into(),
+                new_balance: new_value.to_u128().into(),
+            }),
+        }
+    }
+    Ok(changes.into_iter().map(|(_, changes)| changes).collect())
+}
+
+enum Subscription {
+    NewBlocks,
+    IncludedTransactions,
+    NewPendingTransactions,
+    AccountChanges(AccountChangesSubscription),
+    DroppedWithdrawals,
+    FinalizedWithdrawals,
+    Challenges,
+}
+
+impl Subscription {
+    /// Returns true if the event is pushed to the subscription
+    fn matches(&self, event: &Event) -> bool {
+        match event {
+            Event::WithdrawalDropped(_) => matches!(self, Subscription::DroppedWithdrawals),
+            Event::WithdrawalFinalized(_) => matches!(self, Subscription::FinalizedWithdrawals),
+            Event::ChallengeStarted(_) => matches!(self, Subscription::Challenges),
+            // new blocks and included txs are pushed along with the block
+            Event::NewBlock(_) | Event::TxIncluded(_) => false,
+        }
+    }
+}
+
+fn notification<T: serde::Serialize>(id: u64, result: T) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "subscription",
+        "params": { "subscription": Uint64::from(id), "result": result },
+    })
+}
+
+struct Connection {
+    call_checks: CallChecks,
+    ip: IpAddr,
+    sudt_accounts: SUDTAccounts,
+    subscriptions: HashMap<u64, Subscription>,
+    next_subscription_id: u64,
+}
+
+impl Connection {
+    fn new(builtin_accounts: &BuiltinAccounts, call_checks: CallChecks, ip: IpAddr) -> Self {
+        Connection {
+            call_checks,
+            ip,
+            sudt_accounts: SUDTAccounts::new(builtin_accounts.ckb_sudt_id),
+            subscriptions: HashMap::new(),
+            next_subscription_id: 0,
+        }
+    }
+
+    fn subscribed_accounts(&self) -> usize {
+        self.subscriptions
+            .values()
+            .map(|subscription| match subscription {
+                Subscription::AccountChanges(subscription) => subscription.account_ids.len(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Returns true if the connection should receive the new mem-pool txs
+    fn subscribes_pending_transactions(&self) -> bool {
+        self.subscriptions
+            .values()
+            .any(|subscription| matches!(subscription, Subscription::NewPendingTransactions))
+    }
+
+    fn subscribe(&mut self, subscription: Subscription) -> u64 {
+        let id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        self.subscriptions.insert(id, subscription);
+        id
+    }
+
+    fn subscribe_account_changes(&mut self, store: &Store, account_ids: Vec<u32>) -> Result<u64> {
+        if self.subscribed_accounts() + account_ids.len() > MAX_SUBSCRIBED_ACCOUNTS {
+            return Err(anyhow!(
+                "exceeded max subscribed accounts {}",
+                MAX_SUBSCRIBED_ACCOUNTS
+            ));
+        }
+        let db = store.begin_transaction();
+        let tip_hash = db.get_tip_block_hash()?;
+        let state_db =
+            StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_hash))?;
+        self.sudt_accounts.scan(&state_db.account_state_tree()?)?;
+
+        let subscription = AccountChangesSubscription::new(account_ids, &self.sudt_accounts);
+        Ok(self.subscribe(Subscription::AccountChanges(subscription)))
+    }
+
+    fn block_notifications(
+        &mut self,
+        db: &StoreTransaction,
+        block_number: u64,
+        block_hash: &H256,
+    ) -> Result<Vec<Value>> {
+        let mut notifications = Vec::new();
+        for (id, subscription) in &self.subscriptions {
+            if let Subscription::NewBlocks = subscription {
+                let new_block = Event::NewBlock(NewBlock {
+                    block_number: block_number.into(),
+                    block_hash: JsonH256::from(*block_hash).into(),
+                });
+                notifications.push(notification(*id, new_block));
+            }
+        }
+        notifications.extend(
+            self.included_transactions_notifications(db, block_number, block_hash)?,
+        );
+        notifications.extend(self.account_changes_notifications(db, block_hash)?);
+        Ok(notifications)
+    }
+
+    fn included_transactions_notifications(
+        &self,
+        db: &StoreTransaction,
+        block_number: u64,
+        block_hash: &H256,
+    ) -> Result<Vec<Value>> {
+        let ids: Vec<u64> = self
+            .subscriptions
+            .iter()
+            .filter(|(_, subscription)| {
+                matches!(subscription, Subscription::IncludedTransactions)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let block = match db.get_block(block_hash)? {
+            Some(block) => block,
+            None => return Ok(Vec::new()),
+        };
+        let mut notifications = Vec::new();
+        for (tx_index, tx) in block.transactions().into_iter().enumerate() {
+            let tx_included = Event::TxIncluded(TxIncluded {
+                tx_hash: tx.hash().into(),
+                block_number: block_number.into(),
+                block_hash: JsonH256::from(*block_hash).into(),
+                tx_index: (tx_index as u32).into(),
+            });
+            for id in &ids {
+                notifications.push(notification(*id, tx_included.clone()));
+            }
+        }
+        Ok(notifications)
+    }
+
+    fn tx_notifications(&self, tx_hash: &H256) -> Vec<Value> {
+        self.subscriptions
+            .iter()
+            .filter(|(_, subscription)| {
+                matches!(subscription, Subscription::NewPendingTransactions)
+            })
+            .map(|(id, _)| notification(*id, JsonH256::from(*tx_hash)))
+            .collect()
+    }
+
+    fn event_notifications(&self, event: &Event) -> Vec<Value> {
+        self.subscriptions
+            .iter()
+            .filter(|(_, subscription)| subscription.matches(event))
+            .map(|(id, _)| notification(*id, event))
+            .collect()
+    }
+
+    fn account_changes_notifications(
+        &mut self,
+        db: &StoreTransaction,
+        block_hash: &H256,
+    ) -> Result<Vec<Value>> {
+        if self.subscribed_accounts() == 0 {
+            return Ok(Vec::new());
+        }
+        let block = match db.get_block(block_hash)? {
+            Some(block) => block,
+            None => return Ok(Vec::new()),
+        };
+        let parent_block_hash: [u8; 32] = block.raw().parent_block_hash().unpack();
+        let prev_state_db = StateDBTransaction::from_version(
+            db,
+            StateDBVersion::from_block_hash(parent_block_hash.into()),
+        )?;
+        let prev_state = prev_state_db.account_state_tree()?;
+        let post_state_db =
+            StateDBTransaction::from_version(db, StateDBVersion::from_block_hash(*block_hash))?;
+        let post_state = post_state_db.account_state_tree()?;
+
+        if self.sudt_accounts.scan(&post_state)? {
+            for subscription in self.subscriptions.values_mut() {
+                if let Subscription::AccountChanges(subscription) = subscription {
+                    subscription.build_keys(&self.sudt_accounts);
+                }
+            }
+        }
+
+        let mut notifications = Vec::new();
+        for (id, subscription) in &self.subscriptions {
+            let subscription = match subscription {
+                Subscription::AccountChanges(subscription) => subscription,
+                _ => continue,
+            };
+            for changes in account_changes(subscription, &block, &prev_state, &post_state)? {
+                notifications.push(notification(*id, changes));
+            }
+        }
+        Ok(notifications)
+    }
+
+    async fn handle_request<R: Router + 'static>(
+        &mut self,
+        rpc: &JsonrpcServer<R>,
+        store: &Store,
+        text: String,
+    ) -> Result<Option<Value>> {
+        // each message is rate limited like a HTTP request, before it's parsed
+        if let Some(error) = self.call_checks.check(self.ip, text.as_bytes(), 0) {
+            return Ok(Some(error));
+        }
+        let request: Value = match serde_json::from_str(&text) {
+            Ok(request) => request,
+            // let the JSONRPC server report the parse error
+            Err(_) => return handle_rpc(rpc, text).await,
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some("subscribe") => {
+                let id = match params.get(0).and_then(Value::as_str) {
+                    Some("new_blocks") => Ok(self.subscribe(Subscription::NewBlocks)),
+                    Some("included_transactions") => {
+                        Ok(self.subscribe(Subscription::IncludedTransactions))
+                    }
+                    Some("new_pending_transactions") => {
+                        Ok(self.subscribe(Subscription::NewPendingTransactions))
+                    }
+                    Some("dropped_withdrawals") => {
+                        Ok(self.subscribe(Subscription::DroppedWithdrawals))
+                    }
+                    Some("finalized_withdrawals") => {
+                        Ok(self.subscribe(Subscription::FinalizedWithdrawals))
+                    }
+                    Some("challenges") => Ok(self.subscribe(Subscription::Challenges)),
+                    Some("account_changes") => params
+                        .get(1)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("missing account ids"))
+                        .and_then(|ids| Ok(serde_json::from_value::<Vec<Uint32>>(ids)?))
+                        .and_then(|ids| {
+                            let account_ids = ids.into_iter().map(Into::into).collect();
+                            self.subscribe_account_changes(store, account_ids)
+                        }),
+                    _ => Err(anyhow!("unknown subscription topic")),
+                };
+                id.map(|id| json!(Uint64::from(id)))
+            }
+            Some("unsubscribe") => params
+                .get(0)
+                .cloned()
+                .ok_or_else(|| anyhow!("missing subscription id"))
+                .and_then(|id| Ok(serde_json::from_value::<Uint64>(id)?))
+                .map(|id| json!(self.subscriptions.remove(&id.value()).is_some())),
+            _ => return handle_rpc(rpc, text).await,
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": INVALID_PARAMS_CODE, "message": err.to_string() },
+            }),
+        };
+        Ok(Some(response))
+    }
+}
+
+async fn handle_rpc<R: Router + 'static>(
+    rpc: &JsonrpcServer<R>,
+    text: String,
+) -> Result<Option<Value>> {
+    match rpc.handle(RequestKind::Bytes(text.into())).await {
+        ResponseObjects::Empty => Ok(None),
+        response => Ok(Some(serde_json::to_value(&response)?)),
+    }
+}
+
+/// Returns true if the request asks to upgrade to WebSocket
+pub fn is_upgrade_request(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(header::UPGRADE)
+        .and_then(|x| x.to_str().ok())
+        .map(|x| x.eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false)
+}
+
+/// Upgrade the request to a WebSocket connection
+#[allow(clippy::too_many_arguments)]
+pub fn upgrade<R: Router + Send + Sync + 'static>(
+    rpc: Arc<JsonrpcServer<R>>,
+    store: Store,
+    notifier: Notifier,
+    builtin_accounts: BuiltinAccounts,
+    audit_log: AuditLog,
+    call_checks: CallChecks,
+    max_message_size: usize,
+    ip: IpAddr,
+    source: String,
+    req: Request<Body>,
+) -> Result<Response<Body>> {
+    let accept_key = match req.headers().get(header::SEC_WEBSOCKET_KEY) {
+        Some(key) => derive_accept_key(key.as_bytes()),
+        None => {
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from("missing Sec-WebSocket-Key"))
+                .map_err(|e| anyhow!("WebSocket handshake error: {:?}", e))
+        }
+    };
+    tokio::spawn(async move {
+        let upgraded = match hyper::upgrade::on(req).await {
+            Ok(upgraded) => upgraded,
+            Err(err) => {
+                eprintln!("WebSocket upgrade error: {}", err);
+                return;
+            }
+        };
+        let config = WebSocketConfig {
+            max_message_size: Some(max_message_size),
+            max_frame_size: Some(max_message_size),
+            ..Default::default()
+        };
+        let ws = WebSocketStream::from_raw_socket(upgraded, Role::Server, Some(config)).await;
+        let result = serve_connection(
+            rpc,
+            store,
+            notifier,
+            builtin_accounts,
+            audit_log,
+            call_checks,
+            ip,
+            source,
+            ws,
+        )
+        .await;
+        if let Err(err) = result {
+            eprintln!("WebSocket connection error: {}", err);
+        }
+    });
+    Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(header::CONNECTION, "upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_ACCEPT, accept_key)
+        .body(Body::empty())
+        .map_err(|e| anyhow!("WebSocket handshake error: {:?}", e))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn serve_connection<R: Router + 'static>(
+    rpc: Arc<JsonrpcServer<R>>,
+    store: Store,
+    notifier: Notifier,
+    builtin_accounts: BuiltinAccounts,
+    audit_log: AuditLog,
+    call_checks: CallChecks,
+    ip: IpAddr,
+    source: String,
+    ws: WebSocketStream<Upgraded>,
+) -> Result<()> {
+    let (mut sink, mut stream) = ws.split();
+    let mut new_blocks = notifier.subscribe_blocks();
+    let mut events = notifier.subscribe_events();
+    // only receive mem-pool txs while they are subscribed
+    let mut new_txs: Option<broadcast::Receiver<H256>> = None;
+    let mut connection = Connection::new(&builtin_accounts, call_checks, ip);
+    loop {
+        match (
+            connection.subscribes_pending_transactions(),
+            new_txs.is_some(),
+        ) {
+            (true, false) => new_txs = Some(notifier.subscribe_txs()),
+            (false, true) => new_txs = None,
+            _ => {}
+        }
+        let next_tx = async {
+            match new_txs.as_mut() {
+                Some(new_txs) => new_txs.recv().await,
+                None => futures::future::pending().await,
+            }
+        };
+        tokio::select! {
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => return Err(err.into()),
+                };
+                audit_log.record(&source, text.as_bytes());
+                if let Some(response) = connection.handle_request(&rpc, &store, text).await? {
+                    sink.send(Message::Text(response.to_string())).await?;
+                }
+            }
+            new_block = new_blocks.recv() => {
+                let (block_number, block_hash) = match new_block {
+                    Ok(new_block) => new_block,
+                    // the connection is too slow, skip the missed blocks
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let notifications = {
+                    let db = store.begin_transaction();
+                    connection.block_notifications(&db, block_number, &block_hash)?
+                };
+                for notification in notifications {
+                    sink.send(Message::Text(notification.to_string())).await?;
+                }
+            }
+            tx_hash = next_tx => {
+                let tx_hash = match tx_hash {
+                    Ok(tx_hash) => tx_hash,
+                    // the connection is too slow, skip the missed txs
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                for notification in connection.tx_notifications(&tx_hash) {
+                    sink.send(Message::Text(notification.to_string())).await?;
+                }
+            }
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    // the connection is too slow, skip the missed events
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                for notification in connection.event_notifications(&event) {
+                    sink.send(Message::Text(notification.to_string())).await?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Iterate the accounts of a state
+//!
+//! Accounts are read from the state tree one at a time, the memory usage doesn't grow
+//! with the number of accounts. Only balances of the given sUDTs are read, use
+//! `sudt_account_ids` to find the sUDT accounts of a state.
+
+use gw_common::{error::Error, state::State, H256};
+use gw_traits::CodeStore;
+use gw_types::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountRecord {
+    pub id: u32,
+    pub script_hash: H256,
+    pub nonce: u32,
+    /// (sudt_id, balance), zero balances are skipped
+    pub balances: Vec<(u32, u128)>,
+}
+
+pub struct AccountIter<'a, S> {
+    state: &'a S,
+    sudt_ids: Vec<u32>,
+    next_id: u32,
+    account_count: u32,
+}
+
+impl<'a, S: State> AccountIter<'a, S> {
+    pub fn new(state: &'a S, sudt_ids: Vec<u32>) -> Result<Self, Error> {
+        let account_count = state.get_account_count()?;
+        Ok(AccountIter {
+            state,
+            sudt_ids,
+            next_id: 0,
+            account_count,
+        })
+    }
+
+    fn read_account(&self, id: u32) -> Result<AccountRecord, Error> {
+        let script_hash = self.state.get_script_hash(id)?;
+        let nonce = self.state.get_nonce(id)?;
+        let mut balances = Vec::new();
+        for sudt_id in &self.sudt_ids {
+            let balance = self.state.get_sudt_balance(*sudt_id, id)?;
+            if balance != 0 {
+                balances.push((*sudt_id, balance));
+            }
+        }
+        Ok(AccountRecord {
+            id,
+            script_hash,
+            nonce,
+            balances,
+        })
+    }
+}
+
+impl<'a, S: State> Iterator for AccountIter<'a, S> {
+    type Item = Result<AccountRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_id >= self.account_count {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        Some(self.read_account(id))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remain = (self.account_count - self.next_id) as usize;
+        (remain, Some(remain))
+    }
+}
+
+/// Ids of the accounts which script is the l2 sUDT validator
+pub fn sudt_account_ids<S: State + CodeStore>(
+    state: &S,
+    l2_sudt_validator_script_type_hash: &H256,
+) -> Result<Vec<u32>, Error> {
+    let mut sudt_ids = Vec::new();
+    for id in 0..state.get_account_count()? {
+        let script_hash = state.get_script_hash(id)?;
+        let code_hash = match state.get_script(&script_hash) {
+            Some(script) => script.code_hash(),
+            None => continue,
+        };
+        if code_hash.as_slice() == l2_sudt_validator_script_type_hash.as_slice() {
+            sudt_ids.push(id);
+        }
+    }
+    Ok(sudt_ids)
+}
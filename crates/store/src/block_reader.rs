@@ -0,0 +1,120 @@
+//! Read main chain blocks in the order of block numbers
+//!
+//! Blocks are stored by hash, reading a range of them is a series of random reads. The
+//! `BlockReader` loads the next blocks on a background task while the caller processes the
+//! current one, so the disk latency overlaps with the processing instead of adding to it.
+//!
+//! The task runs on a dedicated thread by default, callers inside an async runtime should pass
+//! the blocking pool of the runtime to `with_spawner` instead.
+
+use crate::Store;
+use gw_db::error::Error;
+use gw_types::packed::L2Block;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::{sync_channel, Receiver, TryRecvError},
+    Arc,
+};
+use std::thread;
+
+/// Default number of blocks to read ahead
+pub const DEFAULT_READ_AHEAD: usize = 32;
+
+/// Counters of a `BlockReader`
+#[derive(Debug, Default)]
+pub struct BlockReaderMetrics {
+    blocks_read: AtomicU64,
+    stalls: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockReaderStats {
+    /// blocks returned to the caller
+    pub blocks_read: u64,
+    /// times the caller had to wait because no block was read ahead
+    pub stalls: u64,
+}
+
+impl BlockReaderMetrics {
+    pub fn stats(&self) -> BlockReaderStats {
+        BlockReaderStats {
+            blocks_read: self.blocks_read.load(Ordering::Relaxed),
+            stalls: self.stalls.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Iterate main chain blocks in `[from_block, to_block]`, stops at the first missing block
+pub struct BlockReader {
+    receiver: Receiver<Result<(u64, L2Block), Error>>,
+    metrics: Arc<BlockReaderMetrics>,
+}
+
+impl BlockReader {
+    /// Read ahead on a dedicated thread
+    pub fn new(store: Store, from_block: u64, to_block: u64, read_ahead: usize) -> Self {
+        Self::with_spawner(store, from_block, to_block, read_ahead, |task| {
+            thread::spawn(task);
+        })
+    }
+
+    /// Read ahead on a task started by `spawn`, e.g. a blocking task of the tokio runtime
+    pub fn with_spawner<F>(
+        store: Store,
+        from_block: u64,
+        to_block: u64,
+        read_ahead: usize,
+        spawn: F,
+    ) -> Self
+    where
+        F: FnOnce(Box<dyn FnOnce() + Send>),
+    {
+        let (sender, receiver) = sync_channel(read_ahead);
+        spawn(Box::new(move || {
+            let db = store.begin_transaction();
+            for number in from_block..=to_block {
+                let block = match db.get_block_hash_by_number(number) {
+                    Ok(Some(block_hash)) => match db.get_block(&block_hash) {
+                        Ok(Some(block)) => Ok((number, block)),
+                        Ok(None) => break,
+                        Err(err) => Err(err),
+                    },
+                    Ok(None) => break,
+                    Err(err) => Err(err),
+                };
+                let is_err = block.is_err();
+                // the reader is dropped
+                if sender.send(block).is_err() || is_err {
+                    break;
+                }
+            }
+        }));
+        BlockReader {
+            receiver,
+            metrics: Default::default(),
+        }
+    }
+
+    pub fn metrics(&self) -> Arc<BlockReaderMetrics> {
+        Arc::clone(&self.metrics)
+    }
+}
+
+impl Iterator for BlockReader {
+    type Item = Result<(u64, L2Block), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = match self.receiver.try_recv() {
+            Ok(item) => item,
+            Err(TryRecvError::Empty) => {
+                self.metrics.stalls.fetch_add(1, Ordering::Relaxed);
+                self.receiver.recv().ok()?
+            }
+            Err(TryRecvError::Disconnected) => return None,
+        };
+        if item.is_ok() {
+            self.metrics.blocks_read.fetch_add(1, Ordering::Relaxed);
+        }
+        Some(item)
+    }
+}
@@ -38,6 +38,10 @@ impl<'db> ChainStore for ChainView<'db> {
             .to_string()
             .into())
     }
+
+    fn get_tip_block_hash(&self) -> H256 {
+        self.tip_block_hash
+    }
 }
 
 fn is_number_in_a_valid_range(tip_number: u64, number: u64) -> bool {
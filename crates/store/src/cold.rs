@@ -0,0 +1,138 @@
+//! Cold storage of block bodies
+//!
+//! A tiered store moves the blocks, txs and receipts of the main chain blocks older than
+//! `hot_blocks` into a secondary DB, which can be put on a slower and cheaper disk. The block
+//! indexes, headers info, global states and the state stay in the primary DB.
+//!
+//! Reads of the block bodies fall back to the cold DB, so callers don't know where a block is.
+//! The moves are done by `ColdStoreMover` in the background, a block is written to the cold DB
+//! before it's deleted from the primary DB, an interrupted move is redone on the next round.
+//! The moves aren't mirrored, a store mirror keeps all the block bodies.
+
+use crate::{traits::KVStore, transaction::StoreTransaction, Store};
+use gw_config::ColdStoreConfig;
+use gw_db::{
+    config::Config as DBConfig,
+    error::Error,
+    schema::{
+        COLUMNS, COLUMN_BLOCK, COLUMN_META, COLUMN_TRANSACTION, COLUMN_TRANSACTION_RECEIPT,
+        META_COLD_BLOCK_NUMBER_KEY,
+    },
+    RocksDB,
+};
+use gw_types::{
+    packed::{self, TransactionKey},
+    prelude::*,
+};
+use std::{
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Max blocks moved in a round, so a round doesn't hold a huge transaction
+const MAX_MOVE_BLOCKS: u64 = 1000;
+
+/// Open the cold DB
+pub fn open_cold_db(config: &ColdStoreConfig) -> RocksDB {
+    let config = DBConfig {
+        path: config.path.clone(),
+        ..Default::default()
+    };
+    RocksDB::open(&config, COLUMNS)
+}
+
+/// Number of the next block to move, blocks below it are in the cold DB
+pub fn get_cold_block_number(db: &StoreTransaction) -> Result<u64, Error> {
+    match db.get(COLUMN_META, META_COLD_BLOCK_NUMBER_KEY) {
+        Some(slice) => Ok(
+            packed::Uint64Reader::from_slice_should_be_ok(&slice.as_ref())
+                .to_entity()
+                .unpack(),
+        ),
+        None => Ok(0),
+    }
+}
+
+/// Move the bodies of the main chain blocks older than `hot_blocks` into the cold DB,
+/// at most `max_blocks` blocks are moved, returns the number of the moved blocks.
+/// Nothing is moved if the store isn't tiered
+pub fn move_cold_blocks(store: &Store, hot_blocks: u64, max_blocks: u64) -> Result<u64, Error> {
+    let cold = match store.cold() {
+        Some(cold) => cold,
+        None => return Ok(0),
+    };
+    let db = store.begin_transaction();
+    let tip_number: u64 = db.get_tip_block()?.raw().number().unpack();
+    // blocks in [from, to) are moved
+    let from = get_cold_block_number(&db)?;
+    let to = match tip_number.checked_sub(hot_blocks) {
+        Some(last_cold_number) => (last_cold_number + 1).min(from.saturating_add(max_blocks)),
+        None => return Ok(0),
+    };
+    if from >= to {
+        return Ok(0);
+    }
+
+    let mut batch = cold.new_write_batch();
+    for number in from..to {
+        let block_hash = db
+            .get_block_hash_by_number(number)?
+            .ok_or_else(|| Error::from(format!("missing block #{}", number)))?;
+        let block = match db.get(COLUMN_BLOCK, block_hash.as_slice()) {
+            Some(slice) => packed::L2BlockReader::from_slice_should_be_ok(&slice).to_entity(),
+            // moved by an interrupted round
+            None => continue,
+        };
+        batch.put(COLUMN_BLOCK, block_hash.as_slice(), block.as_slice())?;
+        // deleted from the primary DB only, the mirror keeps the bodies
+        db.inner.delete(COLUMN_BLOCK, block_hash.as_slice())?;
+        for index in 0..block.transactions().len() {
+            let key = TransactionKey::build_transaction_key(block_hash.pack(), index as u32);
+            for col in &[COLUMN_TRANSACTION, COLUMN_TRANSACTION_RECEIPT] {
+                if let Some(value) = db.get(*col, key.as_slice()) {
+                    batch.put(*col, key.as_slice(), &value)?;
+                    db.inner.delete(*col, key.as_slice())?;
+                }
+            }
+        }
+    }
+    let next_number: packed::Uint64 = to.pack();
+    db.inner.put(
+        COLUMN_META,
+        META_COLD_BLOCK_NUMBER_KEY,
+        next_number.as_slice(),
+    )?;
+    // the cold DB is written first, the blocks are never missing from both
+    cold.write(&batch)?;
+    db.commit()?;
+    Ok(to - from)
+}
+
+pub struct ColdStoreMover {
+    store: Store,
+    hot_blocks: u64,
+    interval: Duration,
+}
+
+impl ColdStoreMover {
+    pub fn new(store: Store, config: &ColdStoreConfig) -> Self {
+        ColdStoreMover {
+            store,
+            hot_blocks: config.hot_blocks,
+            interval: Duration::from_secs(config.move_interval_secs),
+        }
+    }
+
+    /// Start a background thread to move the old blocks periodically
+    pub fn start(self) -> JoinHandle<()> {
+        thread::spawn(move || loop {
+            match move_cold_blocks(&self.store, self.hot_blocks, MAX_MOVE_BLOCKS) {
+                // more blocks to move, start the next round without waiting
+                Ok(moved) if moved == MAX_MOVE_BLOCKS => continue,
+                Ok(_) => {}
+                Err(err) => eprintln!("Cold store move error: {}", err),
+            }
+            thread::sleep(self.interval);
+        })
+    }
+}
@@ -1,4 +1,11 @@
+pub mod account_iter;
+pub mod block_reader;
 pub mod chain_view;
+pub mod cold;
+pub mod migration;
+pub mod mirror;
+pub mod overlay_state;
+pub mod smt_gc;
 pub mod smt_store_impl;
 pub mod state_db;
 mod store_impl;
@@ -6,7 +13,7 @@ pub mod traits;
 pub mod transaction;
 mod write_batch;
 
-pub use store_impl::Store;
+pub use store_impl::{ColumnUsage, Store};
 
 #[cfg(test)]
 mod tests;
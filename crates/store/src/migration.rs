@@ -0,0 +1,124 @@
+//! Store migrations
+//!
+//! The database version is stored under `MIGRATION_VERSION_KEY` of the meta column,
+//! a database without the key is version 0. A fresh database is created with the
+//! latest layout, so the migrations only run on a database that has a chain id.
+
+use crate::{traits::KVStore, transaction::StoreTransaction, Store};
+use gw_common::H256;
+use gw_db::{
+    error::Error,
+    schema::{
+        COLUMN_META, COLUMN_TRANSACTION, COLUMN_TRANSACTION_INFO, COLUMN_TRANSACTION_RECEIPT,
+        META_CHAIN_ID_KEY, MIGRATION_VERSION_KEY,
+    },
+    IteratorMode,
+};
+use gw_types::{
+    packed::{self, TransactionKey},
+    prelude::*,
+};
+use std::convert::TryFrom;
+
+/// Version of the current database layout
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Number of txs or blocks migrated in a db transaction
+const MIGRATION_BATCH_SIZE: usize = 1000;
+
+/// Returns the database version, an error if the version isn't written by godwoken
+pub fn get_version(db: &StoreTransaction) -> Result<u32, Error> {
+    match db.get(COLUMN_META, MIGRATION_VERSION_KEY) {
+        Some(slice) => {
+            let buf = <[u8; 4]>::try_from(slice.as_ref()).map_err(|_| {
+                Error::from(format!("invalid database version {:?}", slice.as_ref()))
+            })?;
+            Ok(u32::from_le_bytes(buf))
+        }
+        None => Ok(0),
+    }
+}
+
+fn set_version(db: &StoreTransaction, version: u32) -> Result<(), Error> {
+    db.insert_raw(COLUMN_META, MIGRATION_VERSION_KEY, &version.to_le_bytes())
+}
+
+/// Migrate the database to the `CURRENT_VERSION`
+pub fn migrate(store: &Store) -> Result<(), Error> {
+    let db = store.begin_transaction();
+    let version = get_version(&db)?;
+    if version >= CURRENT_VERSION {
+        return Ok(());
+    }
+    // fresh database
+    if db.get(COLUMN_META, META_CHAIN_ID_KEY).is_none() {
+        set_version(&db, CURRENT_VERSION)?;
+        return db.commit();
+    }
+    if version < 1 {
+        migrate_transaction_keys(store)?;
+    }
+    if version < 2 {
+        migrate_account_transactions(store)?;
+    }
+    let db = store.begin_transaction();
+    set_version(&db, CURRENT_VERSION)?;
+    db.commit()
+}
+
+/// Version 1: txs and receipts were stored under `tx_hash | index`, which can't be
+/// found from the transaction info, move them to the key of the transaction info,
+/// i.e. `block_hash | index`.
+fn migrate_transaction_keys(store: &Store) -> Result<(), Error> {
+    let infos: Vec<(H256, packed::TransactionInfo)> = {
+        let db = store.begin_transaction();
+        db.get_iter(COLUMN_TRANSACTION_INFO, IteratorMode::Start)
+            .map(|(key, value)| {
+                let tx_hash = <[u8; 32]>::try_from(key.as_ref())
+                    .map_err(|_| Error::from(format!("invalid tx hash {:?}", key.as_ref())))?;
+                let info = packed::TransactionInfoReader::from_slice(&value)
+                    .map_err(|err| Error::from(format!("invalid tx info: {}", err)))?
+                    .to_entity();
+                Ok((tx_hash.into(), info))
+            })
+            .collect::<Result<_, Error>>()?
+    };
+    for chunk in infos.chunks(MIGRATION_BATCH_SIZE) {
+        let db = store.begin_transaction();
+        for (tx_hash, info) in chunk {
+            let new_key = info.key();
+            let index = <[u8; 4]>::try_from(&new_key.as_slice()[32..])
+                .map_err(|_| Error::from(format!("invalid tx key {:?}", new_key)))?;
+            let old_key =
+                TransactionKey::build_transaction_key(tx_hash.pack(), u32::from_be_bytes(index));
+            for col in &[COLUMN_TRANSACTION, COLUMN_TRANSACTION_RECEIPT] {
+                if let Some(value) = db.get(*col, old_key.as_slice()) {
+                    db.insert_raw(*col, new_key.as_slice(), &value)?;
+                    db.delete(*col, old_key.as_slice())?;
+                }
+            }
+        }
+        db.commit()?;
+    }
+    Ok(())
+}
+
+/// Version 2: index the main chain txs under their senders and receivers
+fn migrate_account_transactions(store: &Store) -> Result<(), Error> {
+    let mut number = 0;
+    loop {
+        let db = store.begin_transaction();
+        for _ in 0..MIGRATION_BATCH_SIZE {
+            let block_hash = match db.get_block_hash_by_number(number)? {
+                Some(block_hash) => block_hash,
+                None => return db.commit(),
+            };
+            let block = db
+                .get_block(&block_hash)?
+                .ok_or_else(|| Error::from(format!("can't find block {:?}", block_hash)))?;
+            db.insert_account_transactions(&block)?;
+            number += 1;
+        }
+        db.commit()?;
+    }
+}
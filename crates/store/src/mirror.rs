@@ -0,0 +1,215 @@
+//! Store mirror
+//!
+//! A mirrored store applies the writes of transactions to the primary DB and the
+//! secondary DB, write batches (e.g. SMT GC) and the moves of the cold store only go to the
+//! primary DB.
+//!
+//! `StoreMirrorChecker` compares the roots of the two DBs periodically, it's used to
+//! validate store refactors and pruning changes against production traffic. The roots are
+//! computed from the SMT data of each DB, the stored roots are copied to the mirror as is and
+//! can't catch a divergence.
+
+use crate::{
+    state_db::{StateDBTransaction, StateDBVersion},
+    traits::KVStore,
+    transaction::StoreTransaction,
+    Store,
+};
+use gw_common::{
+    smt::{Blake2bHasher, Store as SMTStore, SMT},
+    state::{build_account_field_key, GW_ACCOUNT_SCRIPT_HASH},
+    H256,
+};
+use gw_config::StoreMirrorConfig;
+use gw_db::{
+    config::Config as DBConfig,
+    error::Error,
+    schema::{COLUMNS, COLUMN_META, META_TIP_BLOCK_HASH_KEY},
+    RocksDB,
+};
+use gw_types::{packed, prelude::*};
+use std::{
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Tips of the two DBs are allowed to differ for a few rounds, the mirror is committed
+/// after the primary
+const MAX_SYNCING_ROUNDS: usize = 3;
+
+/// Open the secondary DB, it must be empty since writes are mirrored from genesis
+pub fn open_mirror_db(config: &StoreMirrorConfig) -> RocksDB {
+    match config.path {
+        Some(ref path) => {
+            let config = DBConfig {
+                path: path.clone(),
+                ..Default::default()
+            };
+            RocksDB::open(&config, COLUMNS)
+        }
+        None => RocksDB::open_tmp(COLUMNS),
+    }
+}
+
+/// Open a temporary store mirrored to the secondary DB
+pub fn open_tmp_with_mirror(config: &StoreMirrorConfig) -> Store {
+    Store::with_mirror(RocksDB::open_tmp(COLUMNS), open_mirror_db(config))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreRoots {
+    pub tip_block_hash: H256,
+    /// root computed from the account SMT of the tip, None if the SMT data is incomplete
+    pub account_root: Option<H256>,
+    pub account_count: u32,
+    /// root computed from the block SMT, None if the SMT data is incomplete
+    pub block_root: Option<H256>,
+}
+
+impl StoreRoots {
+    fn load(db: &StoreTransaction, tip_block_hash: H256) -> Result<Self, Error> {
+        let tip_number = db
+            .get_block_number(&tip_block_hash)?
+            .ok_or_else(|| Error::from(format!("missing number of tip {:?}", tip_block_hash)))?;
+        let block_key = packed::RawL2Block::compute_smt_key(tip_number).into();
+        let block_root = compute_smt_root(&db.block_smt()?, vec![(block_key, tip_block_hash)]);
+
+        let account_count = db.get_account_count()?;
+        let state_db =
+            StateDBTransaction::from_version(db, StateDBVersion::from_block_hash(tip_block_hash))?;
+        let account_smt = state_db.account_smt()?;
+        let account_leaves: Result<Vec<_>, _> = (0..account_count)
+            .map(|id| {
+                let key = build_account_field_key(id, GW_ACCOUNT_SCRIPT_HASH);
+                account_smt.get(&key).map(|value| (key, value))
+            })
+            .collect();
+        let account_root = account_leaves
+            .ok()
+            .and_then(|leaves| compute_smt_root(&account_smt, leaves));
+        Ok(StoreRoots {
+            tip_block_hash,
+            account_root,
+            account_count,
+            block_root,
+        })
+    }
+}
+
+fn load_tip_block_hash(db: &StoreTransaction) -> Result<H256, Error> {
+    match db.get(COLUMN_META, META_TIP_BLOCK_HASH_KEY) {
+        Some(slice) => Ok(
+            packed::Byte32Reader::from_slice_should_be_ok(&slice.as_ref())
+                .to_entity()
+                .unpack(),
+        ),
+        None => Err(Error::from("store isn't initialized".to_owned())),
+    }
+}
+
+/// Compute the root of a SMT by a merkle proof of `leaves`, the proof reads the branches from
+/// the DB so missing or changed branches are caught, returns None if the proof can't be built
+fn compute_smt_root<S: SMTStore<H256>>(
+    smt: &SMT<S>,
+    mut leaves: Vec<(H256, H256)>,
+) -> Option<H256> {
+    if leaves.is_empty() {
+        return Some(*smt.root());
+    }
+    leaves.sort_unstable_by_key(|(key, _value)| *key);
+    let keys = leaves.iter().map(|(key, _value)| *key).collect();
+    smt.merkle_proof(keys)
+        .and_then(|proof| proof.compile(leaves.clone()))
+        .and_then(|proof| proof.compute_root::<Blake2bHasher>(leaves))
+        .ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MirrorStatus {
+    /// roots of the two DBs are equal
+    Consistent(StoreRoots),
+    /// the DBs have different tips, the mirror may be committing
+    Syncing { primary: H256, mirror: H256 },
+    /// roots of the same tip are different
+    Diverged {
+        primary: StoreRoots,
+        mirror: StoreRoots,
+    },
+}
+
+/// Compare the roots of the primary DB and the secondary DB,
+/// returns None if the store isn't mirrored
+pub fn compare_roots(store: &Store) -> Result<Option<MirrorStatus>, Error> {
+    let mirror_store = match store.mirror() {
+        Some(mirror_store) => mirror_store,
+        None => return Ok(None),
+    };
+    // read the mirror first, it's committed after the primary
+    let mirror_db = mirror_store.begin_transaction();
+    let primary_db = store.begin_transaction();
+    let mirror_tip = load_tip_block_hash(&mirror_db)?;
+    let primary_tip = load_tip_block_hash(&primary_db)?;
+    if primary_tip != mirror_tip {
+        return Ok(Some(MirrorStatus::Syncing {
+            primary: primary_tip,
+            mirror: mirror_tip,
+        }));
+    }
+    let mirror = StoreRoots::load(&mirror_db, mirror_tip)?;
+    let primary = StoreRoots::load(&primary_db, primary_tip)?;
+    let status = if primary != mirror {
+        MirrorStatus::Diverged { primary, mirror }
+    } else {
+        MirrorStatus::Consistent(primary)
+    };
+    Ok(Some(status))
+}
+
+pub struct StoreMirrorChecker {
+    store: Store,
+    interval: Duration,
+}
+
+impl StoreMirrorChecker {
+    pub fn new(store: Store, config: &StoreMirrorConfig) -> Self {
+        StoreMirrorChecker {
+            store,
+            interval: Duration::from_secs(config.check_interval_secs),
+        }
+    }
+
+    /// Start a background thread to compare roots periodically,
+    /// `on_diverged` is called with a description of the divergence
+    pub fn start<F>(self, on_diverged: F) -> JoinHandle<()>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        thread::spawn(move || {
+            let mut syncing_rounds = 0;
+            loop {
+                thread::sleep(self.interval);
+                match compare_roots(&self.store) {
+                    Ok(Some(MirrorStatus::Consistent(_))) => syncing_rounds = 0,
+                    Ok(Some(MirrorStatus::Syncing { primary, mirror })) => {
+                        syncing_rounds += 1;
+                        if syncing_rounds >= MAX_SYNCING_ROUNDS {
+                            on_diverged(format!(
+                                "mirror tip lags for {} rounds, primary: {:?}, mirror: {:?}",
+                                syncing_rounds, primary, mirror
+                            ));
+                        }
+                    }
+                    Ok(Some(MirrorStatus::Diverged { primary, mirror })) => {
+                        syncing_rounds = 0;
+                        on_diverged(format!(
+                            "store mirror diverged, primary: {:?}, mirror: {:?}",
+                            primary, mirror
+                        ));
+                    }
+                    Ok(None) => return,
+                    Err(err) => eprintln!("Store mirror check error: {}", err),
+                }
+            }
+        })
+    }
+}
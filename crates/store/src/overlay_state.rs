@@ -0,0 +1,173 @@
+//! Overlay state
+//!
+//! `OverlayState` keeps writes in memory on top of a read-only state, the inner state
+//! is never modified. It's used to execute txs on top of the tip without touching the
+//! store, e.g. the pending state of the mem-pool, or executions with state overrides.
+
+use gw_common::{
+    blake2b::new_blake2b,
+    error::Error,
+    h256_ext::H256Ext,
+    state::{
+        build_account_field_key, build_script_hash_to_account_id_key, State, GW_ACCOUNT_SCRIPT_HASH,
+    },
+    H256,
+};
+use gw_traits::CodeStore;
+use gw_types::{bytes::Bytes, packed::Script, prelude::*};
+use std::collections::HashMap;
+
+/// Writes of an overlay state
+#[derive(Debug, Clone, Default)]
+pub struct OverlayChanges {
+    pub kv: HashMap<H256, H256>,
+    pub account_count: Option<u32>,
+    pub scripts: HashMap<H256, Script>,
+    pub data: HashMap<H256, Bytes>,
+}
+
+impl OverlayChanges {
+    pub fn is_empty(&self) -> bool {
+        self.kv.is_empty()
+            && self.account_count.is_none()
+            && self.scripts.is_empty()
+            && self.data.is_empty()
+    }
+
+    /// Apply the later `changes` on top of these changes
+    pub fn merge(&mut self, changes: OverlayChanges) {
+        self.kv.extend(changes.kv);
+        if changes.account_count.is_some() {
+            self.account_count = changes.account_count;
+        }
+        self.scripts.extend(changes.scripts);
+        self.data.extend(changes.data);
+    }
+}
+
+/// Override of an account
+#[derive(Debug, Clone, Default)]
+pub struct AccountOverride {
+    pub account_id: u32,
+    /// (sudt_id, balance)
+    pub balances: Vec<(u32, u128)>,
+    /// (key, value) of the account storage
+    pub storage: Vec<(H256, H256)>,
+    /// replace the script of the account, the backend is selected by its code hash
+    pub script: Option<Script>,
+}
+
+pub struct OverlayState<'a, S> {
+    inner: &'a S,
+    changes: OverlayChanges,
+}
+
+impl<'a, S: State + CodeStore> OverlayState<'a, S> {
+    pub fn new(inner: &'a S, changes: OverlayChanges) -> Self {
+        OverlayState { inner, changes }
+    }
+
+    pub fn changes(&self) -> &OverlayChanges {
+        &self.changes
+    }
+
+    pub fn into_changes(self) -> OverlayChanges {
+        self.changes
+    }
+
+    /// Write the override to the overlay, the account must exist
+    pub fn apply_override(&mut self, account_override: &AccountOverride) -> Result<(), Error> {
+        let id = account_override.account_id;
+        for (sudt_id, balance) in &account_override.balances {
+            self.set_sudt_balance(*sudt_id, id, *balance)?;
+        }
+        for (key, value) in &account_override.storage {
+            self.update_value(id, key, *value)?;
+        }
+        if let Some(script) = &account_override.script {
+            let script_hash: H256 = script.hash().into();
+            self.insert_script(script_hash, script.clone());
+            self.update_raw(
+                build_account_field_key(id, GW_ACCOUNT_SCRIPT_HASH),
+                script_hash,
+            )?;
+            self.update_raw(
+                build_script_hash_to_account_id_key(script_hash.as_slice()),
+                H256::from_u32(id),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, S: State + CodeStore> State for OverlayState<'a, S> {
+    fn get_raw(&self, key: &H256) -> Result<H256, Error> {
+        match self.changes.kv.get(key) {
+            Some(value) => Ok(*value),
+            None => self.inner.get_raw(key),
+        }
+    }
+
+    fn update_raw(&mut self, key: H256, value: H256) -> Result<(), Error> {
+        self.changes.kv.insert(key, value);
+        Ok(())
+    }
+
+    fn get_account_count(&self) -> Result<u32, Error> {
+        match self.changes.account_count {
+            Some(count) => Ok(count),
+            None => self.inner.get_account_count(),
+        }
+    }
+
+    fn set_account_count(&mut self, count: u32) -> Result<(), Error> {
+        self.changes.account_count = Some(count);
+        Ok(())
+    }
+
+    /// The overlay doesn't maintain a SMT, the returned root commits to the inner root
+    /// and the overlaid values, it isn't the SMT root of the merged state
+    fn calculate_root(&self) -> Result<H256, Error> {
+        let inner_root = self.inner.calculate_root()?;
+        if self.changes.kv.is_empty() {
+            return Ok(inner_root);
+        }
+        let mut kv: Vec<(&H256, &H256)> = self.changes.kv.iter().collect();
+        kv.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        let mut hasher = new_blake2b();
+        hasher.update(inner_root.as_slice());
+        for (k, v) in kv {
+            hasher.update(k.as_slice());
+            hasher.update(v.as_slice());
+        }
+        let mut root = [0u8; 32];
+        hasher.finalize(&mut root);
+        Ok(root.into())
+    }
+}
+
+impl<'a, S: State + CodeStore> CodeStore for OverlayState<'a, S> {
+    fn insert_script(&mut self, script_hash: H256, script: Script) {
+        self.changes.scripts.insert(script_hash, script);
+    }
+
+    fn get_script(&self, script_hash: &H256) -> Option<Script> {
+        self.changes
+            .scripts
+            .get(script_hash)
+            .cloned()
+            .or_else(|| self.inner.get_script(script_hash))
+    }
+
+    fn insert_data(&mut self, data_hash: H256, data: Bytes) {
+        self.changes.data.insert(data_hash, data);
+    }
+
+    fn get_data(&self, data_hash: &H256) -> Option<Bytes> {
+        self.changes
+            .data
+            .get(data_hash)
+            .cloned()
+            .or_else(|| self.inner.get_data(data_hash))
+    }
+}
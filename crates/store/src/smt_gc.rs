@@ -0,0 +1,208 @@
+//! Garbage collection of SMT nodes
+//!
+//! The state DB stores SMT nodes with a version suffix: key | block_number | tx_index,
+//! every update leaves the superseded versions of a node in the DB.
+//!
+//! We never read a state older than the pruning horizon (tip number - depth),
+//! so for each node only the latest version below the horizon is reachable,
+//! the older versions (or all versions if the latest one is a delete flag) can be deleted.
+//!
+//! The horizon is recorded before a round deletes anything, readers must not serve the states
+//! of the blocks below `get_pruning_horizon`.
+
+use crate::{
+    state_db::FLAG_DELETE_VALUE,
+    traits::KVStore,
+    transaction::{StoreTransaction, NUMBER_OF_CONFIRMATION},
+    write_batch::StoreWriteBatch,
+    Store,
+};
+use gw_config::SMTPruningConfig;
+use gw_db::{
+    error::Error,
+    schema::{
+        Col, COLUMN_ACCOUNT_SMT_BRANCH, COLUMN_ACCOUNT_SMT_LEAF, COLUMN_META,
+        META_SMT_PRUNING_HORIZON_KEY,
+    },
+    DBIterator, IteratorMode,
+};
+use gw_types::{packed, prelude::*};
+use std::{
+    mem::size_of,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Size of the version suffix: block_number(8 bytes) | tx_index(4 bytes)
+const VERSION_SUFFIX_LEN: usize = size_of::<u64>() + size_of::<u32>();
+
+#[derive(Debug, Clone)]
+pub struct SMTGCConfig {
+    /// keep all versions of the latest `pruning_depth` blocks
+    pub pruning_depth: u64,
+    /// max deletions in a write batch
+    pub batch_size: usize,
+    /// sleep between two write batches, to avoid impacting foreground writes
+    pub throttle: Duration,
+    /// interval between two GC rounds
+    pub interval: Duration,
+}
+
+impl Default for SMTGCConfig {
+    fn default() -> Self {
+        SMTGCConfig {
+            pruning_depth: NUMBER_OF_CONFIRMATION,
+            batch_size: 1000,
+            throttle: Duration::from_millis(50),
+            interval: Duration::from_secs(600),
+        }
+    }
+}
+
+impl From<&SMTPruningConfig> for SMTGCConfig {
+    fn from(config: &SMTPruningConfig) -> Self {
+        SMTGCConfig {
+            pruning_depth: config.pruning_depth,
+            interval: Duration::from_secs(config.interval_secs),
+            ..Default::default()
+        }
+    }
+}
+
+/// Block number of the pruning horizon, the states of the blocks below it may be incomplete
+pub fn get_pruning_horizon(db: &StoreTransaction) -> Result<u64, Error> {
+    match db.get(COLUMN_META, META_SMT_PRUNING_HORIZON_KEY) {
+        Some(slice) => Ok(
+            packed::Uint64Reader::from_slice_should_be_ok(&slice.as_ref())
+                .to_entity()
+                .unpack(),
+        ),
+        None => Ok(0),
+    }
+}
+
+pub struct SMTGarbageCollector {
+    store: Store,
+    config: SMTGCConfig,
+}
+
+impl SMTGarbageCollector {
+    pub fn new(store: Store, config: SMTGCConfig) -> Self {
+        SMTGarbageCollector { store, config }
+    }
+
+    /// Start a background thread to run GC periodically
+    pub fn start(self) -> JoinHandle<()> {
+        thread::spawn(move || loop {
+            thread::sleep(self.config.interval);
+            match self.run_once() {
+                Ok(deleted) => println!("SMT GC: deleted {} stale nodes", deleted),
+                Err(err) => eprintln!("SMT GC error: {}", err),
+            }
+        })
+    }
+
+    /// Run a GC round with the horizon calculated from the current tip,
+    /// return the number of deleted nodes
+    pub fn run_once(&self) -> Result<usize, Error> {
+        let tip_block = self
+            .store
+            .get_tip_block()
+            .map_err(|err| Error::from(err.to_string()))?;
+        let tip_number: u64 = tip_block.raw().number().unpack();
+        if tip_number <= self.config.pruning_depth {
+            return Ok(0);
+        }
+        self.collect(tip_number - self.config.pruning_depth)
+    }
+
+    /// Delete unreachable nodes which version is lower than the horizon block number,
+    /// return the number of deleted nodes
+    pub fn collect(&self, horizon: u64) -> Result<usize, Error> {
+        self.record_horizon(horizon)?;
+        let mut deleted = 0;
+        for col in &[COLUMN_ACCOUNT_SMT_BRANCH, COLUMN_ACCOUNT_SMT_LEAF] {
+            deleted += self.collect_column(*col, horizon)?;
+        }
+        Ok(deleted)
+    }
+
+    /// Record the horizon before deleting, so readers stop serving the states below it first
+    fn record_horizon(&self, horizon: u64) -> Result<(), Error> {
+        let db = self.store.begin_transaction();
+        if get_pruning_horizon(&db)? >= horizon {
+            return Ok(());
+        }
+        let horizon: packed::Uint64 = horizon.pack();
+        db.insert_raw(
+            COLUMN_META,
+            META_SMT_PRUNING_HORIZON_KEY,
+            horizon.as_slice(),
+        )?;
+        db.commit()
+    }
+
+    fn collect_column(&self, col: Col, horizon: u64) -> Result<usize, Error> {
+        let mut batch = self.store.new_write_batch();
+        let mut deleted = 0;
+        let mut current_key: Option<Vec<u8>> = None;
+        // versions of the current key below the horizon, sorted by version
+        let mut stale_versions: Vec<(Box<[u8]>, bool)> = Vec::new();
+
+        let iter = self.store.db().iter(col, IteratorMode::Start)?;
+        for (raw_key, value) in iter {
+            if raw_key.len() < VERSION_SUFFIX_LEN {
+                continue;
+            }
+            let (key, version) = raw_key.split_at(raw_key.len() - VERSION_SUFFIX_LEN);
+            if current_key.as_deref() != Some(key) {
+                self.delete_unreachable(col, &mut stale_versions, &mut batch, &mut deleted)?;
+                current_key = Some(key.to_vec());
+            }
+            let mut block_number = [0u8; 8];
+            block_number.copy_from_slice(&version[..8]);
+            if u64::from_be_bytes(block_number) < horizon {
+                let is_deleted = value.as_ref() == [FLAG_DELETE_VALUE];
+                stale_versions.push((raw_key, is_deleted));
+            }
+        }
+        self.delete_unreachable(col, &mut stale_versions, &mut batch, &mut deleted)?;
+        if !batch.is_empty() {
+            self.write_batch(&mut batch)?;
+        }
+        Ok(deleted)
+    }
+
+    fn delete_unreachable(
+        &self,
+        col: Col,
+        stale_versions: &mut Vec<(Box<[u8]>, bool)>,
+        batch: &mut StoreWriteBatch,
+        deleted: &mut usize,
+    ) -> Result<(), Error> {
+        // keep the latest version unless it is a delete flag
+        let keep_latest = match stale_versions.last() {
+            Some((_, is_deleted)) => !is_deleted,
+            None => return Ok(()),
+        };
+        if keep_latest {
+            stale_versions.pop();
+        }
+        for (raw_key, _) in stale_versions.drain(..) {
+            batch.delete(col, &raw_key)?;
+            *deleted += 1;
+            if batch.len() >= self.config.batch_size {
+                self.write_batch(batch)?;
+                thread::sleep(self.config.throttle);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_batch(&self, batch: &mut StoreWriteBatch) -> Result<(), Error> {
+        self.store
+            .write(batch)
+            .map_err(|err| Error::from(err.to_string()))?;
+        batch.clear()
+    }
+}
@@ -12,9 +12,10 @@ use gw_types::{
     packed::{self, AccountMerkleState},
     prelude::*,
 };
-use std::{cell::RefCell, collections::HashSet, fmt, mem::size_of_val};
+use parking_lot::Mutex;
+use std::{collections::HashSet, fmt, mem::size_of_val};
 
-const FLAG_DELETE_VALUE: u8 = 0;
+pub(crate) const FLAG_DELETE_VALUE: u8 = 0;
 
 #[derive(Debug, Clone)]
 pub struct StateDBVersion {
@@ -249,9 +250,9 @@ impl<'db> StateDBTransaction<'db> {
     }
 }
 
-/// Tracker state changes
+/// Tracker state changes, it's shared by the txs executed in parallel
 pub struct StateTracker {
-    touched_keys: Option<RefCell<HashSet<H256>>>,
+    touched_keys: Option<Mutex<HashSet<H256>>>,
 }
 
 impl Default for StateTracker {
@@ -273,14 +274,14 @@ impl StateTracker {
     }
 
     /// Return touched keys
-    pub fn touched_keys(&self) -> Option<&RefCell<HashSet<H256>>> {
+    pub fn touched_keys(&self) -> Option<&Mutex<HashSet<H256>>> {
         self.touched_keys.as_ref()
     }
 
     /// Record a key in the tracker
     pub fn touch_key(&self, key: &H256) {
         if let Some(touched_keys) = self.touched_keys.as_ref() {
-            touched_keys.borrow_mut().insert(*key);
+            touched_keys.lock().insert(*key);
         }
     }
 }
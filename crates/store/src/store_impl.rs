@@ -7,8 +7,8 @@ use gw_common::{error::Error, smt::H256};
 use gw_db::{
     schema::{
         Col, COLUMNS, COLUMN_BLOCK, COLUMN_BLOCK_GLOBAL_STATE, COLUMN_L2BLOCK_COMMITTED_INFO,
-        COLUMN_META, COLUMN_TRANSACTION, COLUMN_TRANSACTION_RECEIPT, META_CHAIN_ID_KEY,
-        META_TIP_BLOCK_HASH_KEY,
+        COLUMN_META, COLUMN_TRANSACTION, COLUMN_TRANSACTION_INFO, COLUMN_TRANSACTION_RECEIPT,
+        META_CHAIN_ID_KEY, META_TIP_BLOCK_HASH_KEY,
     },
     DBPinnableSlice, RocksDB,
 };
@@ -17,14 +17,49 @@ use gw_types::{
     prelude::*,
 };
 
+/// Disk usage of a column family
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnUsage {
+    pub column: Col,
+    /// total size of SST files
+    pub total_sst_files_size: u64,
+    /// estimated size of the live data
+    pub live_data_size: u64,
+    /// estimated bytes can be reclaimed by compaction
+    pub reclaimable_size: u64,
+}
+
 #[derive(Clone)]
 pub struct Store {
     db: RocksDB,
+    mirror: Option<RocksDB>,
+    cold: Option<RocksDB>,
 }
 
 impl<'a> Store {
     pub fn new(db: RocksDB) -> Self {
-        Store { db }
+        Store {
+            db,
+            mirror: None,
+            cold: None,
+        }
+    }
+
+    /// Mirror the writes of transactions to a secondary DB, see `crate::mirror`
+    pub fn with_mirror(db: RocksDB, mirror: RocksDB) -> Self {
+        Store {
+            db,
+            mirror: Some(mirror),
+            cold: None,
+        }
+    }
+
+    /// Read the bodies of the old blocks from the cold DB, see `crate::cold`
+    pub fn with_cold(self, cold: RocksDB) -> Self {
+        Store {
+            cold: Some(cold),
+            ..self
+        }
     }
 
     pub fn open_tmp() -> Result<Self> {
@@ -32,12 +67,42 @@ impl<'a> Store {
         Ok(Self::new(db))
     }
 
+    /// Open a store which keeps all data in memory
+    #[cfg(feature = "in-memory")]
+    pub fn open_in_memory() -> Self {
+        Self::new(RocksDB::open_in_memory(COLUMNS))
+    }
+
+    pub(crate) fn db(&self) -> &RocksDB {
+        &self.db
+    }
+
+    /// The secondary store, returns None if the store isn't mirrored
+    pub fn mirror(&self) -> Option<Store> {
+        self.mirror.clone().map(Store::new)
+    }
+
+    /// The cold DB, returns None if the block bodies aren't tiered
+    pub fn cold(&self) -> Option<&RocksDB> {
+        self.cold.as_ref()
+    }
+
     fn get(&'a self, col: Col, key: &[u8]) -> Option<DBPinnableSlice<'a>> {
         self.db
             .get_pinned(col, key)
             .expect("db operation should be ok")
     }
 
+    /// Read a column of the block bodies, falls back to the cold DB
+    fn get_block_body(&'a self, col: Col, key: &[u8]) -> Option<DBPinnableSlice<'a>> {
+        self.get(col, key).or_else(|| {
+            self.cold.as_ref().and_then(|cold| {
+                cold.get_pinned(col, key)
+                    .expect("db operation should be ok")
+            })
+        })
+    }
+
     // fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter {
     //     self.db.iter(col, mode).expect("db operation should be ok")
     // }
@@ -45,6 +110,8 @@ impl<'a> Store {
     pub fn begin_transaction(&self) -> StoreTransaction {
         StoreTransaction {
             inner: self.db.transaction(),
+            mirror: self.mirror.as_ref().map(|mirror| mirror.transaction()),
+            cold: self.cold.clone(),
         }
     }
 
@@ -62,6 +129,46 @@ impl<'a> Store {
         Ok(())
     }
 
+    /// Compact a column, or all columns if `col` is `None`
+    pub fn compact(&self, col: Option<Col>) -> Result<(), Error> {
+        let cols: Vec<Col> = match col {
+            Some(col) => vec![col],
+            None => (0..COLUMNS as Col).collect(),
+        };
+        for col in cols {
+            if let Err(err) = self.db.compact_range(col, None, None) {
+                eprintln!("Store error: {}", err);
+                return Err(Error::Store);
+            }
+        }
+        Ok(())
+    }
+
+    /// Return disk usage of all columns
+    pub fn get_usage(&self) -> Result<Vec<ColumnUsage>, Error> {
+        let get_property = |col: Col, name: &str| -> Result<u64, Error> {
+            match self.db.property_int_value(col, name) {
+                Ok(value) => Ok(value.unwrap_or(0)),
+                Err(err) => {
+                    eprintln!("Store error: {}", err);
+                    Err(Error::Store)
+                }
+            }
+        };
+        (0..COLUMNS as Col)
+            .map(|col| {
+                let total_sst_files_size = get_property(col, "rocksdb.total-sst-files-size")?;
+                let live_data_size = get_property(col, "rocksdb.estimate-live-data-size")?;
+                Ok(ColumnUsage {
+                    column: col,
+                    total_sst_files_size,
+                    live_data_size,
+                    reclaimable_size: total_sst_files_size.saturating_sub(live_data_size),
+                })
+            })
+            .collect()
+    }
+
     pub fn has_genesis(&self) -> Result<bool> {
         let db = self.begin_transaction();
         Ok(db.get_block_hash_by_number(0)?.is_some())
@@ -106,7 +213,7 @@ impl<'a> Store {
     }
 
     pub fn get_block(&self, block_hash: &H256) -> Result<Option<L2Block>, Error> {
-        match self.get(COLUMN_BLOCK, block_hash.as_slice()) {
+        match self.get_block_body(COLUMN_BLOCK, block_hash.as_slice()) {
             Some(slice) => Ok(Some(
                 packed::L2BlockReader::from_slice_should_be_ok(&slice.as_ref()).to_entity(),
             )),
@@ -127,8 +234,21 @@ impl<'a> Store {
         }
     }
 
+    /// Txs are stored under the key of the transaction info, see `StoreTransaction::insert_block`
+    fn get_transaction_key(&self, tx_hash: &H256) -> Option<packed::TransactionKey> {
+        self.get(COLUMN_TRANSACTION_INFO, tx_hash.as_slice())
+            .map(|slice| {
+                packed::TransactionInfoReader::from_slice_should_be_ok(&slice.as_ref()).to_entity()
+            })
+            .map(|info| info.key())
+    }
+
     pub fn get_transaction(&self, tx_hash: &H256) -> Result<Option<L2Transaction>, Error> {
-        match self.get(COLUMN_TRANSACTION, tx_hash.as_slice()) {
+        let tx_key = match self.get_transaction_key(tx_hash) {
+            Some(tx_key) => tx_key,
+            None => return Ok(None),
+        };
+        match self.get_block_body(COLUMN_TRANSACTION, tx_key.as_slice()) {
             Some(slice) => Ok(Some(
                 packed::L2TransactionReader::from_slice_should_be_ok(&slice.as_ref()).to_entity(),
             )),
@@ -140,7 +260,11 @@ impl<'a> Store {
         &self,
         tx_hash: &H256,
     ) -> Result<Option<packed::TxReceipt>, Error> {
-        match self.get(COLUMN_TRANSACTION_RECEIPT, tx_hash.as_slice()) {
+        let tx_key = match self.get_transaction_key(tx_hash) {
+            Some(tx_key) => tx_key,
+            None => return Ok(None),
+        };
+        match self.get_block_body(COLUMN_TRANSACTION_RECEIPT, tx_key.as_slice()) {
             Some(slice) => Ok(Some(
                 packed::TxReceiptReader::from_slice_should_be_ok(&slice.as_ref()).to_entity(),
             )),
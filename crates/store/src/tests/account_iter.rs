@@ -0,0 +1,51 @@
+use crate::{
+    account_iter::{sudt_account_ids, AccountIter},
+    state_db::{StateDBTransaction, StateDBVersion},
+    Store,
+};
+use gw_common::{state::State, H256};
+use gw_traits::CodeStore;
+use gw_types::{bytes::Bytes, packed::Script, prelude::*};
+
+fn build_script(code_hash: [u8; 32], args: &[u8]) -> Script {
+    Script::new_builder()
+        .code_hash(code_hash.pack())
+        .args(Bytes::from(args.to_vec()).pack())
+        .build()
+}
+
+#[test]
+fn iterate_accounts() {
+    let store = Store::open_tmp().unwrap();
+    let db = store.begin_transaction();
+    let state_db = StateDBTransaction::from_tx_index(&db, StateDBVersion::from_genesis(), 0, 0);
+    let mut tree = state_db.account_state_tree().unwrap();
+
+    let sudt_validator_type_hash = [1u8; 32];
+    let mut create_account = |script: Script| {
+        let script_hash: H256 = script.hash().into();
+        tree.insert_script(script_hash, script);
+        tree.create_account(script_hash).unwrap()
+    };
+    let sudt_id = create_account(build_script(sudt_validator_type_hash, &[0]));
+    let alice_id = create_account(build_script([2u8; 32], &[1]));
+    let bob_id = create_account(build_script([2u8; 32], &[2]));
+    tree.set_nonce(alice_id, 3).unwrap();
+    tree.mint_sudt(sudt_id, alice_id, 100).unwrap();
+
+    let sudt_ids = sudt_account_ids(&tree, &sudt_validator_type_hash.into()).unwrap();
+    assert_eq!(sudt_ids, vec![sudt_id]);
+
+    let iter = AccountIter::new(&tree, sudt_ids).unwrap();
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    let accounts: Vec<_> = iter.collect::<Result<_, _>>().unwrap();
+    let ids: Vec<u32> = accounts.iter().map(|account| account.id).collect();
+    assert_eq!(ids, vec![sudt_id, alice_id, bob_id]);
+    let alice = &accounts[alice_id as usize];
+    assert_eq!(alice.script_hash, tree.get_script_hash(alice_id).unwrap());
+    assert_eq!(alice.nonce, 3);
+    assert_eq!(alice.balances, vec![(sudt_id, 100)]);
+    let bob = &accounts[bob_id as usize];
+    assert_eq!(bob.nonce, 0);
+    assert!(bob.balances.is_empty());
+}
@@ -0,0 +1,71 @@
+use crate::{
+    block_reader::{BlockReader, BlockReaderStats},
+    traits::KVStore,
+    Store,
+};
+use gw_db::schema::{COLUMN_BLOCK, COLUMN_INDEX};
+use gw_types::{
+    packed::{L2Block, RawL2Block},
+    prelude::*,
+};
+
+#[test]
+fn read_blocks_by_number() {
+    let store = Store::open_tmp().unwrap();
+    let db = store.begin_transaction();
+    let mut blocks = Vec::new();
+    for number in 0..10u64 {
+        let block = L2Block::new_builder()
+            .raw(RawL2Block::new_builder().number(number.pack()).build())
+            .build();
+        db.insert_raw(COLUMN_BLOCK, &block.hash(), block.as_slice())
+            .unwrap();
+        db.insert_raw(COLUMN_INDEX, number.pack().as_slice(), &block.hash())
+            .unwrap();
+        blocks.push(block);
+    }
+    db.commit().unwrap();
+
+    let reader = BlockReader::new(store.clone(), 2, 5, 2);
+    let metrics = reader.metrics();
+    let read: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+    assert_eq!(read.len(), 4);
+    for (number, block) in read {
+        assert_eq!(block.as_bytes(), blocks[number as usize].as_bytes());
+    }
+    assert_eq!(metrics.stats().blocks_read, 4);
+
+    // stops at the first missing block
+    let reader = BlockReader::new(store, 8, 20, 2);
+    let numbers: Vec<u64> = reader.map(|block| block.unwrap().0).collect();
+    assert_eq!(numbers, vec![8, 9]);
+}
+
+#[test]
+fn read_blocks_with_spawner() {
+    let store = Store::open_tmp().unwrap();
+    let db = store.begin_transaction();
+    for number in 0..4u64 {
+        let block = L2Block::new_builder()
+            .raw(RawL2Block::new_builder().number(number.pack()).build())
+            .build();
+        db.insert_raw(COLUMN_BLOCK, &block.hash(), block.as_slice())
+            .unwrap();
+        db.insert_raw(COLUMN_INDEX, number.pack().as_slice(), &block.hash())
+            .unwrap();
+    }
+    db.commit().unwrap();
+
+    // the task runs to the end before the first read, the caller never waits
+    let reader = BlockReader::with_spawner(store, 0, 3, 4, |task| task());
+    let metrics = reader.metrics();
+    let numbers: Vec<u64> = reader.map(|block| block.unwrap().0).collect();
+    assert_eq!(numbers, vec![0, 1, 2, 3]);
+    assert_eq!(
+        metrics.stats(),
+        BlockReaderStats {
+            blocks_read: 4,
+            stalls: 0
+        }
+    );
+}
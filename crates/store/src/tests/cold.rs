@@ -0,0 +1,130 @@
+use crate::{
+    cold::{get_cold_block_number, move_cold_blocks},
+    traits::KVStore,
+    Store,
+};
+use gw_common::H256;
+use gw_db::{
+    schema::{
+        COLUMNS, COLUMN_BLOCK, COLUMN_INDEX, COLUMN_META, COLUMN_TRANSACTION,
+        COLUMN_TRANSACTION_INFO, META_TIP_BLOCK_HASH_KEY,
+    },
+    RocksDB,
+};
+use gw_types::{
+    packed::{
+        L2Block, L2Transaction, RawL2Block, RawL2Transaction, TransactionInfo, TransactionKey,
+        TxReceipt,
+    },
+    prelude::*,
+};
+
+/// Insert blocks `0..count` of one tx each, returns the blocks
+fn setup_blocks(store: &Store, count: u64) -> Vec<L2Block> {
+    let db = store.begin_transaction();
+    let mut blocks = Vec::new();
+    for number in 0..count {
+        let raw_tx = RawL2Transaction::new_builder()
+            .nonce((number as u32).pack())
+            .build();
+        let tx = L2Transaction::new_builder().raw(raw_tx).build();
+        let block = L2Block::new_builder()
+            .raw(RawL2Block::new_builder().number(number.pack()).build())
+            .transactions(vec![tx.clone()].pack())
+            .build();
+        db.insert_block(
+            block.clone(),
+            Default::default(),
+            Default::default(),
+            vec![TxReceipt::default()],
+            Vec::new(),
+        )
+        .unwrap();
+        db.insert_raw(COLUMN_INDEX, number.pack().as_slice(), &block.hash())
+            .unwrap();
+        let info = TransactionInfo::new_builder()
+            .key(TransactionKey::build_transaction_key(
+                block.hash().pack(),
+                0,
+            ))
+            .block_number(number.pack())
+            .build();
+        db.insert_raw(COLUMN_TRANSACTION_INFO, &tx.hash(), info.as_slice())
+            .unwrap();
+        db.insert_raw(COLUMN_META, META_TIP_BLOCK_HASH_KEY, &block.hash())
+            .unwrap();
+        blocks.push(block);
+    }
+    db.commit().unwrap();
+    blocks
+}
+
+#[test]
+fn move_old_blocks_to_cold_db() {
+    let store = Store::open_tmp()
+        .unwrap()
+        .with_cold(RocksDB::open_tmp(COLUMNS));
+    let blocks = setup_blocks(&store, 10);
+
+    // blocks 0..=5 are older than 4 blocks, 3 blocks are moved in a round
+    assert_eq!(move_cold_blocks(&store, 4, 3).unwrap(), 3);
+    assert_eq!(move_cold_blocks(&store, 4, 3).unwrap(), 3);
+    assert_eq!(move_cold_blocks(&store, 4, 3).unwrap(), 0);
+    let db = store.begin_transaction();
+    assert_eq!(get_cold_block_number(&db).unwrap(), 6);
+
+    let cold = Store::new(store.cold().unwrap().clone()).begin_transaction();
+    for block in &blocks {
+        let number: u64 = block.raw().number().unpack();
+        let block_hash: H256 = block.hash().into();
+        let tx = block.transactions().get(0).unwrap();
+        let tx_key = TransactionKey::build_transaction_key(block.hash().pack(), 0);
+        let is_cold = number < 6;
+        assert_eq!(db.get(COLUMN_BLOCK, &block.hash()).is_none(), is_cold);
+        assert_eq!(
+            db.get(COLUMN_TRANSACTION, tx_key.as_slice()).is_none(),
+            is_cold
+        );
+        assert_eq!(cold.get(COLUMN_BLOCK, &block.hash()).is_some(), is_cold);
+
+        // reads fall back to the cold DB
+        let read_block = db.get_block(&block_hash).unwrap().expect("block");
+        assert_eq!(read_block.as_bytes(), block.as_bytes());
+        let read_block = store.get_block(&block_hash).unwrap().expect("block");
+        assert_eq!(read_block.as_bytes(), block.as_bytes());
+        let tx_hash: H256 = tx.hash().into();
+        let read_tx = store.get_transaction(&tx_hash).unwrap().expect("tx");
+        assert_eq!(read_tx.as_bytes(), tx.as_bytes());
+        assert!(db.get_transaction_receipt(&tx_hash).unwrap().is_some());
+    }
+}
+
+#[test]
+fn move_nothing_without_cold_db() {
+    let store = Store::open_tmp().unwrap();
+    setup_blocks(&store, 10);
+    assert_eq!(move_cold_blocks(&store, 0, 100).unwrap(), 0);
+    let db = store.begin_transaction();
+    assert_eq!(get_cold_block_number(&db).unwrap(), 0);
+}
+
+#[test]
+fn mirror_keeps_moved_blocks() {
+    let store = Store::with_mirror(RocksDB::open_tmp(COLUMNS), RocksDB::open_tmp(COLUMNS))
+        .with_cold(RocksDB::open_tmp(COLUMNS));
+    let blocks = setup_blocks(&store, 10);
+    assert_eq!(move_cold_blocks(&store, 4, 100).unwrap(), 6);
+
+    // the moves only apply to the primary DB
+    let db = store.begin_transaction();
+    let mirror = store.mirror().unwrap().begin_transaction();
+    assert_eq!(get_cold_block_number(&db).unwrap(), 6);
+    assert_eq!(get_cold_block_number(&mirror).unwrap(), 0);
+    for block in &blocks {
+        let number: u64 = block.raw().number().unpack();
+        let tx_key = TransactionKey::build_transaction_key(block.hash().pack(), 0);
+        assert_eq!(db.get(COLUMN_BLOCK, &block.hash()).is_none(), number < 6);
+        assert!(mirror.get(COLUMN_BLOCK, &block.hash()).is_some());
+        assert!(mirror.get(COLUMN_TRANSACTION, tx_key.as_slice()).is_some());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    migration::{get_version, migrate, CURRENT_VERSION},
+    traits::KVStore,
+    Store,
+};
+use gw_common::H256;
+use gw_db::schema::{
+    COLUMN_BLOCK, COLUMN_INDEX, COLUMN_META, COLUMN_TRANSACTION, COLUMN_TRANSACTION_INFO,
+    COLUMN_TRANSACTION_RECEIPT, META_CHAIN_ID_KEY, MIGRATION_VERSION_KEY,
+};
+use gw_types::{
+    packed::{
+        L2Block, L2Transaction, RawL2Block, RawL2Transaction, TransactionInfo, TransactionKey,
+        TxReceipt,
+    },
+    prelude::*,
+};
+
+#[test]
+fn migrate_fresh_store() {
+    let store = Store::open_tmp().unwrap();
+    migrate(&store).unwrap();
+    assert_eq!(
+        get_version(&store.begin_transaction()).unwrap(),
+        CURRENT_VERSION
+    );
+}
+
+#[test]
+fn migrate_transaction_keys() {
+    let store = Store::open_tmp().unwrap();
+    let block_hash = [42u8; 32];
+    let tx = L2Transaction::new_builder()
+        .raw(RawL2Transaction::new_builder().nonce(3u32.pack()).build())
+        .build();
+    let tx_hash = tx.hash();
+    let receipt = TxReceipt::new_builder()
+        .tx_witness_hash(tx.witness_hash().pack())
+        .build();
+
+    // txs and receipts were stored under `tx_hash | index`
+    let db = store.begin_transaction();
+    db.setup_chain_id(H256::zero()).unwrap();
+    let old_key = TransactionKey::build_transaction_key(tx_hash.pack(), 1);
+    db.insert_raw(COLUMN_TRANSACTION, old_key.as_slice(), tx.as_slice())
+        .unwrap();
+    db.insert_raw(
+        COLUMN_TRANSACTION_RECEIPT,
+        old_key.as_slice(),
+        receipt.as_slice(),
+    )
+    .unwrap();
+    let info = TransactionInfo::new_builder()
+        .key(TransactionKey::build_transaction_key(block_hash.pack(), 1))
+        .build();
+    db.insert_raw(COLUMN_TRANSACTION_INFO, &tx_hash, info.as_slice())
+        .unwrap();
+    db.commit().unwrap();
+    assert!(store.get_transaction(&tx_hash.into()).unwrap().is_none());
+    assert_eq!(get_version(&store.begin_transaction()).unwrap(), 0);
+
+    migrate(&store).unwrap();
+    let db = store.begin_transaction();
+    assert!(db.get(COLUMN_META, META_CHAIN_ID_KEY).is_some());
+    assert_eq!(get_version(&db).unwrap(), CURRENT_VERSION);
+    assert!(db.get(COLUMN_TRANSACTION, old_key.as_slice()).is_none());
+    assert_eq!(
+        db.get_transaction(&tx_hash.into())
+            .unwrap()
+            .unwrap()
+            .as_bytes(),
+        tx.as_bytes()
+    );
+    assert_eq!(
+        store
+            .get_transaction_receipt(&tx_hash.into())
+            .unwrap()
+            .unwrap()
+            .as_bytes(),
+        receipt.as_bytes()
+    );
+}
+
+#[test]
+fn migrate_account_transactions() {
+    let store = Store::open_tmp().unwrap();
+    let build_tx = |from_id: u32, to_id: u32| {
+        let raw = RawL2Transaction::new_builder()
+            .from_id(from_id.pack())
+            .to_id(to_id.pack())
+            .build();
+        L2Transaction::new_builder().raw(raw).build()
+    };
+    let txs = vec![build_tx(2, 1), build_tx(3, 2)];
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(1u64.pack()).build())
+        .transactions(txs.clone().pack())
+        .build();
+
+    // a version 1 database, the main chain block isn't indexed
+    let db = store.begin_transaction();
+    db.setup_chain_id(H256::zero()).unwrap();
+    db.insert_raw(COLUMN_META, MIGRATION_VERSION_KEY, &1u32.to_le_bytes())
+        .unwrap();
+    for number in 0..=1u64 {
+        let block = block
+            .clone()
+            .as_builder()
+            .raw(RawL2Block::new_builder().number(number.pack()).build())
+            .build();
+        db.insert_raw(COLUMN_BLOCK, &block.hash(), block.as_slice())
+            .unwrap();
+        db.insert_raw(COLUMN_INDEX, number.pack().as_slice(), &block.hash())
+            .unwrap();
+    }
+    db.commit().unwrap();
+    let db = store.begin_transaction();
+    assert!(db.get_account_transactions(2, 0, 1, None, 10).is_empty());
+
+    migrate(&store).unwrap();
+    let db = store.begin_transaction();
+    assert_eq!(get_version(&db).unwrap(), CURRENT_VERSION);
+    let tx_hashes: Vec<H256> = txs.iter().map(|tx| tx.hash().into()).collect();
+    assert_eq!(
+        db.get_account_transactions(2, 0, 1, None, 10),
+        vec![
+            (0, 0, tx_hashes[0]),
+            (0, 1, tx_hashes[1]),
+            (1, 0, tx_hashes[0]),
+            (1, 1, tx_hashes[1]),
+        ]
+    );
+    // block range
+    assert_eq!(
+        db.get_account_transactions(3, 1, 1, None, 10),
+        vec![(1, 1, tx_hashes[1])]
+    );
+    // pagination
+    assert_eq!(
+        db.get_account_transactions(2, 0, 1, None, 3),
+        vec![
+            (0, 0, tx_hashes[0]),
+            (0, 1, tx_hashes[1]),
+            (1, 0, tx_hashes[0]),
+        ]
+    );
+    assert_eq!(
+        db.get_account_transactions(2, 0, 1, Some((1, 0)), 3),
+        vec![(1, 1, tx_hashes[1])]
+    );
+    assert!(db.get_account_transactions(4, 0, 1, None, 10).is_empty());
+}
+
+#[test]
+fn reject_invalid_version() {
+    let store = Store::open_tmp().unwrap();
+    let db = store.begin_transaction();
+    db.insert_raw(COLUMN_META, META_CHAIN_ID_KEY, &[1u8; 32])
+        .unwrap();
+    db.insert_raw(COLUMN_META, MIGRATION_VERSION_KEY, &[1u8; 3])
+        .unwrap();
+    db.commit().unwrap();
+
+    // a foreign or corrupt database is an error instead of a crash
+    assert!(get_version(&store.begin_transaction()).is_err());
+    assert!(migrate(&store).is_err());
+}
@@ -0,0 +1,130 @@
+use crate::{
+    mirror::{compare_roots, MirrorStatus},
+    state_db::{StateDBTransaction, StateDBVersion},
+    traits::KVStore,
+    Store,
+};
+use gw_common::{state::State, H256};
+use gw_db::{
+    schema::{COLUMNS, COLUMN_BLOCK_SMT_BRANCH, COLUMN_META, META_TIP_BLOCK_HASH_KEY},
+    IteratorMode, RocksDB,
+};
+use gw_types::{
+    packed::{AccountMerkleState, L2Block, RawL2Block, Script},
+    prelude::*,
+};
+
+fn open_mirrored_store() -> Store {
+    Store::with_mirror(RocksDB::open_tmp(COLUMNS), RocksDB::open_tmp(COLUMNS))
+}
+
+/// Insert a genesis block with 2 accounts, returns the genesis
+fn setup_genesis(store: &Store) -> L2Block {
+    let db = store.begin_transaction();
+    db.set_account_smt_root(H256::zero()).unwrap();
+    db.set_block_smt_root(H256::zero()).unwrap();
+    db.set_account_count(0).unwrap();
+    let genesis = {
+        let state_db =
+            StateDBTransaction::from_version(&db, StateDBVersion::from_genesis()).unwrap();
+        let mut tree = state_db.account_state_tree().unwrap();
+        for args in 0..2u8 {
+            let script = Script::new_builder().args(vec![args].pack()).build();
+            tree.create_account_from_script(script).unwrap();
+        }
+        let post_account = AccountMerkleState::new_builder()
+            .merkle_root(tree.calculate_root().unwrap().pack())
+            .count(tree.get_account_count().unwrap().pack())
+            .build();
+        let genesis = L2Block::new_builder()
+            .raw(RawL2Block::new_builder().post_account(post_account).build())
+            .build();
+        db.insert_block(
+            genesis.clone(),
+            Default::default(),
+            Default::default(),
+            Vec::new(),
+            Vec::new(),
+        )
+        .unwrap();
+        db.attach_block(genesis.clone()).unwrap();
+        tree.submit_tree().unwrap();
+        genesis
+    };
+    db.commit().unwrap();
+    genesis
+}
+
+#[test]
+fn mirror_transaction_writes() {
+    let store = open_mirrored_store();
+    let mirror = store.mirror().expect("mirror");
+
+    let store_txn = store.begin_transaction();
+    store_txn.insert_raw(1, &[1], &[1, 1]).unwrap();
+    store_txn.insert_raw(1, &[2], &[2, 2]).unwrap();
+    store_txn.delete(1, &[2]).unwrap();
+    // uncommitted writes are invisible in the mirror
+    assert!(mirror.begin_transaction().get(1, &[1]).is_none());
+    store_txn.commit().unwrap();
+
+    let mirror_txn = mirror.begin_transaction();
+    assert_eq!(
+        vec![1u8, 1].into_boxed_slice(),
+        mirror_txn.get(1, &[1]).unwrap()
+    );
+    assert!(mirror_txn.get(1, &[2]).is_none());
+
+    // write batches only go to the primary
+    let mut batch = store.new_write_batch();
+    batch.delete(1, &[1]).unwrap();
+    store.write(&batch).unwrap();
+    assert!(store.begin_transaction().get(1, &[1]).is_none());
+    assert!(mirror.begin_transaction().get(1, &[1]).is_some());
+}
+
+#[test]
+fn compare_mirror_roots() {
+    let store = Store::open_tmp().unwrap();
+    assert_eq!(compare_roots(&store).unwrap(), None);
+
+    let store = open_mirrored_store();
+    let genesis = setup_genesis(&store);
+    match compare_roots(&store).unwrap() {
+        Some(MirrorStatus::Consistent(roots)) => {
+            let account_root: H256 = genesis.raw().post_account().merkle_root().unpack();
+            assert_eq!(roots.tip_block_hash, genesis.hash().into());
+            assert_eq!(roots.account_root, Some(account_root));
+            assert_eq!(roots.account_count, 2);
+            let block_root = store.begin_transaction().get_block_smt_root().unwrap();
+            assert_eq!(roots.block_root, Some(block_root));
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+
+    // drop the block SMT branches of the primary only, the stored roots are unchanged
+    let mut batch = store.new_write_batch();
+    let db = store.begin_transaction();
+    for (key, _value) in db.get_iter(COLUMN_BLOCK_SMT_BRANCH, IteratorMode::Start) {
+        batch.delete(COLUMN_BLOCK_SMT_BRANCH, &key).unwrap();
+    }
+    store.write(&batch).unwrap();
+    match compare_roots(&store).unwrap() {
+        Some(MirrorStatus::Diverged { primary, mirror }) => {
+            assert_eq!(primary.account_root, mirror.account_root);
+            assert_ne!(primary.block_root, mirror.block_root);
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+
+    // move the tip of the primary only
+    let mut batch = store.new_write_batch();
+    batch
+        .put(COLUMN_META, META_TIP_BLOCK_HASH_KEY, &[6u8; 32])
+        .unwrap();
+    store.write(&batch).unwrap();
+    assert!(matches!(
+        compare_roots(&store).unwrap(),
+        Some(MirrorStatus::Syncing { .. })
+    ));
+}
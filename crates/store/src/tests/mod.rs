@@ -1,3 +1,10 @@
+mod account_iter;
+mod block_reader;
+mod cold;
+mod migration;
+mod mirror;
+mod overlay_state;
+mod smt_gc;
 mod state_db;
 mod transaction;
 mod transaction_clear_block_state;
@@ -0,0 +1,68 @@
+use crate::{
+    overlay_state::{AccountOverride, OverlayState},
+    state_db::{StateDBTransaction, StateDBVersion},
+    Store,
+};
+use gw_common::{state::State, H256};
+use gw_traits::CodeStore;
+use gw_types::{bytes::Bytes, packed::Script, prelude::*};
+
+fn build_script(code_hash: [u8; 32], args: &[u8]) -> Script {
+    Script::new_builder()
+        .code_hash(code_hash.pack())
+        .args(Bytes::from(args.to_vec()).pack())
+        .build()
+}
+
+#[test]
+fn overlay_writes_and_overrides() {
+    let store = Store::open_tmp().unwrap();
+    let db = store.begin_transaction();
+    let state_db = StateDBTransaction::from_tx_index(&db, StateDBVersion::from_genesis(), 0, 0);
+    let mut tree = state_db.account_state_tree().unwrap();
+    let sudt_script = build_script([1u8; 32], &[0]);
+    let sudt_id = tree.create_account(sudt_script.hash().into()).unwrap();
+    let alice_script = build_script([2u8; 32], &[1]);
+    let alice_id = tree.create_account(alice_script.hash().into()).unwrap();
+    tree.insert_script(alice_script.hash().into(), alice_script.clone());
+    tree.mint_sudt(sudt_id, alice_id, 100).unwrap();
+    let root = tree.calculate_root().unwrap();
+
+    let mut overlay = OverlayState::new(&tree, Default::default());
+    overlay.set_nonce(alice_id, 1).unwrap();
+    let key = H256::from([3u8; 32]);
+    let new_script = build_script([4u8; 32], &[1]);
+    overlay
+        .apply_override(&AccountOverride {
+            account_id: alice_id,
+            balances: vec![(sudt_id, 5)],
+            storage: vec![(key, H256::from([5u8; 32]))],
+            script: Some(new_script.clone()),
+        })
+        .unwrap();
+    assert_eq!(overlay.get_nonce(alice_id).unwrap(), 1);
+    assert_eq!(overlay.get_sudt_balance(sudt_id, alice_id).unwrap(), 5);
+    assert_eq!(
+        overlay.get_value(alice_id, &key).unwrap(),
+        H256::from([5u8; 32])
+    );
+    let new_script_hash: H256 = new_script.hash().into();
+    assert_eq!(overlay.get_script_hash(alice_id).unwrap(), new_script_hash);
+    assert_eq!(
+        overlay
+            .get_account_id_by_script_hash(&new_script_hash)
+            .unwrap(),
+        Some(alice_id)
+    );
+    assert!(overlay.get_script(&new_script_hash).is_some());
+    assert_ne!(overlay.calculate_root().unwrap(), root);
+
+    // the inner state isn't modified
+    let changes = overlay.into_changes();
+    assert!(!changes.is_empty());
+    assert_eq!(tree.get_nonce(alice_id).unwrap(), 0);
+    assert_eq!(tree.get_sudt_balance(sudt_id, alice_id).unwrap(), 100);
+    assert_eq!(tree.get_value(alice_id, &key).unwrap(), H256::zero());
+    assert!(tree.get_script(&new_script_hash).is_none());
+    assert_eq!(tree.calculate_root().unwrap(), root);
+}
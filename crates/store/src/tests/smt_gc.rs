@@ -0,0 +1,61 @@
+use crate::{
+    smt_gc::{get_pruning_horizon, SMTGCConfig, SMTGarbageCollector},
+    state_db::{StateDBTransaction, StateDBVersion},
+    traits::KVStore,
+    transaction::StoreTransaction,
+    Store,
+};
+use gw_db::schema::COLUMN_ACCOUNT_SMT_LEAF;
+
+fn get_state_db_from_mock_data(
+    db: &StoreTransaction,
+    block_number: u64,
+    tx_index: u32,
+) -> StateDBTransaction {
+    let version = StateDBVersion::from_genesis(); // just as a placeholder
+    StateDBTransaction::from_tx_index(db, version, block_number, tx_index)
+}
+
+#[test]
+fn test_collect_stale_versions() {
+    let store = Store::open_tmp().unwrap();
+    let col = COLUMN_ACCOUNT_SMT_LEAF;
+    for block_number in 1..=3u64 {
+        let db = store.begin_transaction();
+        let state_db = get_state_db_from_mock_data(&db, block_number, 0);
+        state_db
+            .insert_raw(col, &[1], &[block_number as u8])
+            .unwrap();
+        if block_number == 1 {
+            state_db.insert_raw(col, &[2], &[42]).unwrap();
+        } else if block_number == 2 {
+            state_db.delete(col, &[2]).unwrap();
+        }
+        state_db.commit().unwrap();
+    }
+
+    let gc = SMTGarbageCollector::new(store.clone(), SMTGCConfig::default());
+    // key [1]: version 1 is unreachable; key [2]: all versions are unreachable
+    assert_eq!(gc.collect(3).unwrap(), 3);
+    // nothing to collect in the second round
+    assert_eq!(gc.collect(3).unwrap(), 0);
+
+    let db = store.begin_transaction();
+    assert_eq!(get_pruning_horizon(&db).unwrap(), 3);
+    let state_db = get_state_db_from_mock_data(&db, 2, 0);
+    assert_eq!(vec![2].into_boxed_slice(), state_db.get(col, &[1]).unwrap());
+    assert!(state_db.get(col, &[2]).is_none());
+    let state_db = get_state_db_from_mock_data(&db, 3, 0);
+    assert_eq!(vec![3].into_boxed_slice(), state_db.get(col, &[1]).unwrap());
+    assert!(state_db.get(col, &[2]).is_none());
+}
+
+#[test]
+fn test_pruning_horizon_never_moves_back() {
+    let store = Store::open_tmp().unwrap();
+    let gc = SMTGarbageCollector::new(store.clone(), SMTGCConfig::default());
+    assert_eq!(get_pruning_horizon(&store.begin_transaction()).unwrap(), 0);
+    gc.collect(10).unwrap();
+    gc.collect(5).unwrap();
+    assert_eq!(get_pruning_horizon(&store.begin_transaction()).unwrap(), 10);
+}
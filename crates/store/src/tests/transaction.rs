@@ -1,5 +1,11 @@
 use crate::{traits::KVStore, Store};
+use gw_common::H256;
 use gw_db::{DBRawIterator, Direction::Forward, IteratorMode};
+use gw_types::{
+    offchain::{StorageDelta, StorageUsage, TxAccessList},
+    packed::{L2Block, RawL2Block},
+    prelude::*,
+};
 use std::collections::HashMap;
 
 #[test]
@@ -369,3 +375,91 @@ fn range_search_and_delete() {
 
     assert!(store_txn.get(1, &[6, 0]).is_some());
 }
+
+#[cfg(feature = "in-memory")]
+#[test]
+fn in_memory_store() {
+    let store = Store::open_in_memory();
+    let store_txn = store.begin_transaction();
+    store_txn.insert_raw(1, &[1], &[1, 1]).unwrap();
+    store_txn.commit().unwrap();
+
+    let store_txn = store.begin_transaction();
+    assert_eq!(
+        vec![1u8, 1].into_boxed_slice(),
+        store_txn.get(1, &[1]).unwrap()
+    );
+    // in-memory stores are independent
+    assert!(Store::open_in_memory()
+        .begin_transaction()
+        .get(1, &[1])
+        .is_none());
+}
+
+#[test]
+fn block_access_lists() {
+    let store = Store::open_tmp().unwrap();
+    let db = store.begin_transaction();
+    let block_hash: H256 = [1u8; 32].into();
+    assert!(db.get_block_access_lists(&block_hash).unwrap().is_none());
+
+    let access_lists = vec![
+        TxAccessList {
+            reads: vec![[2u8; 32].into(), [3u8; 32].into()],
+            writes: vec![[3u8; 32].into()],
+        },
+        TxAccessList::default(),
+    ];
+    db.insert_block_access_lists(&block_hash, &access_lists)
+        .unwrap();
+    db.commit().unwrap();
+    let db = store.begin_transaction();
+    assert_eq!(
+        db.get_block_access_lists(&block_hash).unwrap(),
+        Some(access_lists)
+    );
+}
+
+#[test]
+fn account_storage_usage_attach_detach() {
+    let store = Store::open_tmp().unwrap();
+    let db = store.begin_transaction();
+    db.set_block_smt_root(H256::zero()).unwrap();
+    let insert_block = |number: u64, slots: i64, data_bytes: i64| {
+        let block = L2Block::new_builder()
+            .raw(RawL2Block::new_builder().number(number.pack()).build())
+            .build();
+        db.insert_block(
+            block.clone(),
+            Default::default(),
+            Default::default(),
+            Vec::new(),
+            Vec::new(),
+        )
+        .unwrap();
+        let mut storage_usage = HashMap::new();
+        storage_usage.insert(0u32, StorageDelta { slots, data_bytes });
+        db.insert_block_storage_usage(&block.hash().into(), &storage_usage)
+            .unwrap();
+        block
+    };
+    let usage = |slots: u64, data_bytes: u64| StorageUsage { slots, data_bytes };
+
+    let genesis = insert_block(0, 0, 0);
+    db.attach_block(genesis).unwrap();
+    // block 1 frees slots used before the usage was accounted
+    let block1 = insert_block(1, -3, -30);
+    let block2 = insert_block(2, 5, 50);
+    db.attach_block(block1.clone()).unwrap();
+    assert_eq!(db.get_account_storage_usage(0).unwrap(), usage(0, 0));
+    db.attach_block(block2.clone()).unwrap();
+    assert_eq!(db.get_account_storage_usage(0).unwrap(), usage(2, 20));
+
+    // detaching undoes exactly what attaching did
+    db.detach_block(&block2).unwrap();
+    assert_eq!(db.get_account_storage_usage(0).unwrap(), usage(0, 0));
+    db.detach_block(&block1).unwrap();
+    assert_eq!(db.get_account_storage_usage(0).unwrap(), usage(0, 0));
+    db.attach_block(block2).unwrap();
+    assert_eq!(db.get_account_storage_usage(0).unwrap(), usage(5, 50));
+}
@@ -1,26 +1,36 @@
 use crate::{smt_store_impl::SMTStore, traits::KVStore};
 use gw_common::{smt::SMT, CKB_SUDT_SCRIPT_ARGS, H256};
 use gw_db::schema::{
-    Col, COLUMN_BLOCK, COLUMN_BLOCK_DEPOSITION_REQUESTS, COLUMN_BLOCK_GLOBAL_STATE,
-    COLUMN_BLOCK_SMT_BRANCH, COLUMN_BLOCK_SMT_LEAF, COLUMN_BLOCK_STATE_RECORD,
-    COLUMN_CUSTODIAN_ASSETS, COLUMN_INDEX, COLUMN_L2BLOCK_COMMITTED_INFO, COLUMN_META,
-    COLUMN_TRANSACTION, COLUMN_TRANSACTION_INFO, COLUMN_TRANSACTION_RECEIPT,
-    META_ACCOUNT_SMT_COUNT_KEY, META_ACCOUNT_SMT_ROOT_KEY, META_BLOCK_SMT_ROOT_KEY,
-    META_CHAIN_ID_KEY, META_TIP_BLOCK_HASH_KEY,
+    Col, COLUMN_ACCOUNT_STORAGE_USAGE, COLUMN_ACCOUNT_TRANSACTION, COLUMN_BLOCK,
+    COLUMN_BLOCK_ACCESS_LIST, COLUMN_BLOCK_DEPOSITION_REQUESTS, COLUMN_BLOCK_ECONOMICS,
+    COLUMN_BLOCK_GLOBAL_STATE, COLUMN_BLOCK_LOCAL_POST_STATE, COLUMN_BLOCK_SMT_BRANCH,
+    COLUMN_BLOCK_SMT_LEAF, COLUMN_BLOCK_STATE_RECORD, COLUMN_BLOCK_STORAGE_USAGE,
+    COLUMN_CUSTODIAN_ASSETS, COLUMN_INDEX, COLUMN_L2BLOCK_COMMITTED_INFO,
+    COLUMN_L2BLOCK_COMMITTED_OUTPUT_INDEX, COLUMN_META, COLUMN_ROLLUP_ACTION, COLUMN_TRANSACTION,
+    COLUMN_TRANSACTION_INFO, COLUMN_TRANSACTION_RECEIPT, META_ACCOUNT_SMT_COUNT_KEY,
+    META_ACCOUNT_SMT_ROOT_KEY, META_BLOCK_SMT_ROOT_KEY, META_CHAIN_ID_KEY, META_TIP_BLOCK_HASH_KEY,
 };
 use gw_db::{
-    error::Error, iter::DBIter, DBIterator, Direction::Forward, IteratorMode, RocksDBTransaction,
+    error::Error, iter::DBIter, DBIterator, Direction::Forward, IteratorMode, RocksDB,
+    RocksDBTransaction,
 };
 use gw_types::{
+    offchain::{StorageDelta, StorageUsage, TxAccessList},
     packed::{self, TransactionKey},
     prelude::*,
 };
-use std::{borrow::BorrowMut, collections::HashMap};
+use std::{borrow::BorrowMut, cmp::max, collections::HashMap};
 
-const NUMBER_OF_CONFIRMATION: u64 = 100;
+pub(crate) const NUMBER_OF_CONFIRMATION: u64 = 100;
+/// account_id(4 bytes) | slots(8 bytes) | data_bytes(8 bytes)
+const STORAGE_DELTA_SIZE: usize = 20;
 
 pub struct StoreTransaction {
     pub(crate) inner: RocksDBTransaction,
+    /// writes are applied to the secondary DB too if the store is mirrored
+    pub(crate) mirror: Option<RocksDBTransaction>,
+    /// bodies of the old blocks are read from the cold DB if the store is tiered
+    pub(crate) cold: Option<RocksDB>,
 }
 
 impl KVStore for StoreTransaction {
@@ -38,17 +48,43 @@ impl KVStore for StoreTransaction {
     }
 
     fn insert_raw(&self, col: Col, key: &[u8], value: &[u8]) -> Result<(), Error> {
-        self.inner.put(col, key, value)
+        self.inner.put(col, key, value)?;
+        if let Some(mirror) = self.mirror.as_ref() {
+            mirror.put(col, key, value)?;
+        }
+        Ok(())
     }
 
     fn delete(&self, col: Col, key: &[u8]) -> Result<(), Error> {
-        self.inner.delete(col, key)
+        self.inner.delete(col, key)?;
+        if let Some(mirror) = self.mirror.as_ref() {
+            mirror.delete(col, key)?;
+        }
+        Ok(())
     }
 }
 
 impl StoreTransaction {
+    /// Read a column of the block bodies, falls back to the cold DB
+    fn get_block_body(&self, col: Col, key: &[u8]) -> Option<Box<[u8]>> {
+        self.get(col, key).or_else(|| {
+            self.cold.as_ref().and_then(|cold| {
+                cold.get_pinned(col, key)
+                    .expect("db operation should be ok")
+                    .map(|v| Box::<[u8]>::from(v.as_ref()))
+            })
+        })
+    }
+
     pub fn commit(&self) -> Result<(), Error> {
-        self.inner.commit()
+        self.inner.commit()?;
+        // the primary is committed, a failure of the mirror is left to the root comparison
+        if let Some(mirror) = self.mirror.as_ref() {
+            if let Err(err) = mirror.commit() {
+                eprintln!("Store mirror commit error: {}", err);
+            }
+        }
+        Ok(())
     }
 
     pub fn setup_chain_id(&self, chain_id: H256) -> Result<(), Error> {
@@ -147,7 +183,7 @@ impl StoreTransaction {
     }
 
     pub fn get_block(&self, block_hash: &H256) -> Result<Option<packed::L2Block>, Error> {
-        match self.get(COLUMN_BLOCK, block_hash.as_slice()) {
+        match self.get_block_body(COLUMN_BLOCK, block_hash.as_slice()) {
             Some(slice) => Ok(Some(
                 packed::L2BlockReader::from_slice_should_be_ok(&slice.as_ref()).to_entity(),
             )),
@@ -160,17 +196,34 @@ impl StoreTransaction {
             let info =
                 packed::TransactionInfoReader::from_slice_should_be_ok(&slice.as_ref()).to_entity();
             let tx_key = info.key();
-            Ok(self
-                .get(COLUMN_TRANSACTION, &tx_key.as_slice())
-                .map(|slice| {
-                    packed::L2TransactionReader::from_slice_should_be_ok(&slice.as_ref())
-                        .to_entity()
-                }))
+            self.get_transaction_by_key(&tx_key)
         } else {
             Ok(None)
         }
     }
 
+    pub fn get_transaction_by_key(
+        &self,
+        key: &TransactionKey,
+    ) -> Result<Option<packed::L2Transaction>, Error> {
+        Ok(self
+            .get_block_body(COLUMN_TRANSACTION, &key.as_slice())
+            .map(|slice| {
+                packed::L2TransactionReader::from_slice_should_be_ok(&slice.as_ref()).to_entity()
+            }))
+    }
+
+    pub fn get_transaction_info(
+        &self,
+        tx_hash: &H256,
+    ) -> Result<Option<packed::TransactionInfo>, Error> {
+        Ok(self
+            .get(COLUMN_TRANSACTION_INFO, tx_hash.as_slice())
+            .map(|slice| {
+                packed::TransactionInfoReader::from_slice_should_be_ok(&slice.as_ref()).to_entity()
+            }))
+    }
+
     pub fn get_transaction_receipt(
         &self,
         tx_hash: &H256,
@@ -190,7 +243,7 @@ impl StoreTransaction {
         key: &TransactionKey,
     ) -> Result<Option<packed::TxReceipt>, Error> {
         Ok(self
-            .get(COLUMN_TRANSACTION_RECEIPT, &key.as_slice())
+            .get_block_body(COLUMN_TRANSACTION_RECEIPT, &key.as_slice())
             .map(|slice| {
                 packed::TxReceiptReader::from_slice_should_be_ok(&slice.as_ref()).to_entity()
             }))
@@ -209,6 +262,33 @@ impl StoreTransaction {
         }
     }
 
+    /// Index of the rollup cell in the outputs of the L1 transaction which committed the block
+    pub fn get_l2block_committed_output_index(
+        &self,
+        block_hash: &H256,
+    ) -> Result<Option<u32>, Error> {
+        match self.get(COLUMN_L2BLOCK_COMMITTED_OUTPUT_INDEX, block_hash.as_slice()) {
+            Some(slice) => Ok(Some(
+                packed::Uint32Reader::from_slice_should_be_ok(&slice.as_ref())
+                    .to_entity()
+                    .unpack(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    pub fn insert_l2block_committed_output_index(
+        &self,
+        block_hash: &H256,
+        output_index: u32,
+    ) -> Result<(), Error> {
+        self.insert_raw(
+            COLUMN_L2BLOCK_COMMITTED_OUTPUT_INDEX,
+            block_hash.as_slice(),
+            output_index.pack().as_slice(),
+        )
+    }
+
     pub fn get_block_deposition_requests(
         &self,
         block_hash: &H256,
@@ -236,6 +316,268 @@ impl StoreTransaction {
         }
     }
 
+    /// Fees of a block, only recorded for blocks inserted after the column was added
+    pub fn get_block_economics(
+        &self,
+        block_hash: &H256,
+    ) -> Result<Option<packed::BlockEconomics>, Error> {
+        match self.get(COLUMN_BLOCK_ECONOMICS, block_hash.as_slice()) {
+            Some(slice) => Ok(Some(
+                packed::BlockEconomicsReader::from_slice_should_be_ok(&slice.as_ref()).to_entity(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Post account state and block SMT state of a block computed by this node, unlike the
+    /// post global state they don't come from L1. stored as `account state | block state`
+    pub fn insert_block_local_post_state(
+        &self,
+        block_hash: &H256,
+        account: &packed::AccountMerkleState,
+        block: &packed::BlockMerkleState,
+    ) -> Result<(), Error> {
+        let value = [account.as_slice(), block.as_slice()].concat();
+        self.insert_raw(COLUMN_BLOCK_LOCAL_POST_STATE, block_hash.as_slice(), &value)
+    }
+
+    /// Post state of a block computed by this node, only recorded for blocks inserted after
+    /// the column was added
+    pub fn get_block_local_post_state(
+        &self,
+        block_hash: &H256,
+    ) -> Result<Option<(packed::AccountMerkleState, packed::BlockMerkleState)>, Error> {
+        match self.get(COLUMN_BLOCK_LOCAL_POST_STATE, block_hash.as_slice()) {
+            Some(slice) => {
+                let (account, block) = slice.split_at(packed::AccountMerkleState::TOTAL_SIZE);
+                Ok(Some((
+                    packed::AccountMerkleStateReader::from_slice_should_be_ok(account).to_entity(),
+                    packed::BlockMerkleStateReader::from_slice_should_be_ok(block).to_entity(),
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Access lists of the txs of a block, they are recorded by the block producer.
+    /// stored as `[reads of tx 0, writes of tx 0, reads of tx 1, ...]`
+    pub fn insert_block_access_lists(
+        &self,
+        block_hash: &H256,
+        access_lists: &[TxAccessList],
+    ) -> Result<(), Error> {
+        let items: Vec<packed::Bytes> = access_lists
+            .iter()
+            .flat_map(|access_list| vec![&access_list.reads, &access_list.writes])
+            .map(|keys| keys.pack().as_bytes().pack())
+            .collect();
+        let value = packed::BytesVec::new_builder().set(items).build();
+        self.insert_raw(
+            COLUMN_BLOCK_ACCESS_LIST,
+            block_hash.as_slice(),
+            value.as_slice(),
+        )
+    }
+
+    pub fn get_block_access_lists(
+        &self,
+        block_hash: &H256,
+    ) -> Result<Option<Vec<TxAccessList>>, Error> {
+        let slice = match self.get(COLUMN_BLOCK_ACCESS_LIST, block_hash.as_slice()) {
+            Some(slice) => slice,
+            None => return Ok(None),
+        };
+        let items = packed::BytesVecReader::from_slice_should_be_ok(&slice.as_ref());
+        let unpack_keys = |bytes: packed::BytesReader| -> Vec<H256> {
+            packed::Byte32VecReader::from_slice_should_be_ok(bytes.raw_data()).unpack()
+        };
+        let access_lists = (0..items.len() / 2)
+            .map(|i| TxAccessList {
+                reads: unpack_keys(items.get_unchecked(i * 2)),
+                writes: unpack_keys(items.get_unchecked(i * 2 + 1)),
+            })
+            .collect();
+        Ok(Some(access_lists))
+    }
+
+    /// Storage changes of the accounts called by the txs of a block, must be inserted before
+    /// the block is attached
+    pub fn insert_block_storage_usage(
+        &self,
+        block_hash: &H256,
+        storage_usage: &HashMap<u32, StorageDelta>,
+    ) -> Result<(), Error> {
+        let mut account_ids: Vec<u32> = storage_usage.keys().copied().collect();
+        account_ids.sort_unstable();
+        let mut value = Vec::with_capacity(account_ids.len() * STORAGE_DELTA_SIZE);
+        for account_id in account_ids {
+            let delta = storage_usage[&account_id];
+            value.extend_from_slice(&account_id.to_le_bytes());
+            value.extend_from_slice(&delta.slots.to_le_bytes());
+            value.extend_from_slice(&delta.data_bytes.to_le_bytes());
+        }
+        self.insert_raw(COLUMN_BLOCK_STORAGE_USAGE, block_hash.as_slice(), &value)
+    }
+
+    fn get_block_storage_usage(&self, block_hash: &H256) -> Vec<(u32, StorageDelta)> {
+        let slice = match self.get(COLUMN_BLOCK_STORAGE_USAGE, block_hash.as_slice()) {
+            Some(slice) => slice,
+            None => return Vec::new(),
+        };
+        slice
+            .chunks_exact(STORAGE_DELTA_SIZE)
+            .map(|chunk| {
+                let mut account_id = [0u8; 4];
+                account_id.copy_from_slice(&chunk[..4]);
+                let mut slots = [0u8; 8];
+                slots.copy_from_slice(&chunk[4..12]);
+                let mut data_bytes = [0u8; 8];
+                data_bytes.copy_from_slice(&chunk[12..]);
+                let delta = StorageDelta {
+                    slots: i64::from_le_bytes(slots),
+                    data_bytes: i64::from_le_bytes(data_bytes),
+                };
+                (u32::from_le_bytes(account_id), delta)
+            })
+            .collect()
+    }
+
+    /// Storage usage of an account on the main chain, only the blocks attached after the
+    /// column was added are accounted
+    pub fn get_account_storage_usage(&self, account_id: u32) -> Result<StorageUsage, Error> {
+        // the total is negative if the account frees slots used before the column was added
+        let total = self.get_account_storage_total(account_id);
+        Ok(StorageUsage {
+            slots: total.slots.max(0) as u64,
+            data_bytes: total.data_bytes.max(0) as u64,
+        })
+    }
+
+    /// Sum of the storage changes of the attached blocks
+    fn get_account_storage_total(&self, account_id: u32) -> StorageDelta {
+        match self.get(COLUMN_ACCOUNT_STORAGE_USAGE, &account_id.to_le_bytes()) {
+            Some(slice) => {
+                let mut slots = [0u8; 8];
+                slots.copy_from_slice(&slice[..8]);
+                let mut data_bytes = [0u8; 8];
+                data_bytes.copy_from_slice(&slice[8..]);
+                StorageDelta {
+                    slots: i64::from_le_bytes(slots),
+                    data_bytes: i64::from_le_bytes(data_bytes),
+                }
+            }
+            None => StorageDelta::default(),
+        }
+    }
+
+    /// Apply the storage changes of a block to the accounts, or undo them if `revert`.
+    /// The totals are signed and never clamped, so undoing is the exact inverse of applying.
+    fn update_account_storage_usage(&self, block_hash: &H256, revert: bool) -> Result<(), Error> {
+        for (account_id, delta) in self.get_block_storage_usage(block_hash) {
+            let delta = if revert { delta.revert() } else { delta };
+            let mut total = self.get_account_storage_total(account_id);
+            total.merge(delta);
+            let mut value = [0u8; 16];
+            value[..8].copy_from_slice(&total.slots.to_le_bytes());
+            value[8..].copy_from_slice(&total.data_bytes.to_le_bytes());
+            self.insert_raw(COLUMN_ACCOUNT_STORAGE_USAGE, &account_id.to_le_bytes(), &value)?;
+        }
+        Ok(())
+    }
+
+    pub fn insert_rollup_action(&self, record: &packed::RollupActionRecord) -> Result<(), Error> {
+        let key = rollup_action_key(record.l1_block_number().unpack(), &record.l1_tx_hash());
+        self.insert_raw(COLUMN_ROLLUP_ACTION, &key, record.as_slice())
+    }
+
+    /// Delete the action of a reverted L1 transaction
+    pub fn delete_rollup_action(
+        &self,
+        l1_block_number: u64,
+        l1_tx_hash: &packed::Byte32,
+    ) -> Result<(), Error> {
+        self.delete(
+            COLUMN_ROLLUP_ACTION,
+            &rollup_action_key(l1_block_number, l1_tx_hash),
+        )
+    }
+
+    /// Non-block rollup actions from the L1 block number, in the order of L1 blocks
+    pub fn get_rollup_actions(
+        &self,
+        from_l1_block_number: u64,
+        limit: usize,
+    ) -> Vec<packed::RollupActionRecord> {
+        let start_key = from_l1_block_number.to_be_bytes();
+        self.get_iter(
+            COLUMN_ROLLUP_ACTION,
+            IteratorMode::From(&start_key, Forward),
+        )
+        .take(limit)
+        .map(|(_key, value)| {
+            packed::RollupActionRecordReader::from_slice_should_be_ok(&value).to_entity()
+        })
+        .collect()
+    }
+
+    /// Index the txs of a main chain block under their senders and receivers
+    pub(crate) fn insert_account_transactions(&self, block: &packed::L2Block) -> Result<(), Error> {
+        let block_number: u64 = block.raw().number().unpack();
+        for (index, tx) in block.transactions().into_iter().enumerate() {
+            let tx_hash = tx.hash();
+            for account_id in tx_accounts(&tx) {
+                let key = account_transaction_key(account_id, block_number, index as u32);
+                self.insert_raw(COLUMN_ACCOUNT_TRANSACTION, &key, &tx_hash)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Main chain txs sent from or to the account in the block range, in the order of blocks.
+    /// Returns (block number, tx index, tx hash), starts after the `after` position if it's set
+    pub fn get_account_transactions(
+        &self,
+        account_id: u32,
+        from_block: u64,
+        to_block: u64,
+        after: Option<(u64, u32)>,
+        limit: usize,
+    ) -> Vec<(u64, u32, H256)> {
+        let mut start = (from_block, 0);
+        if let Some((block_number, tx_index)) = after {
+            let next = match tx_index.checked_add(1) {
+                Some(tx_index) => (block_number, tx_index),
+                None => (block_number.saturating_add(1), 0),
+            };
+            start = max(start, next);
+        }
+        let start_key = account_transaction_key(account_id, start.0, start.1);
+        self.get_iter(
+            COLUMN_ACCOUNT_TRANSACTION,
+            IteratorMode::From(&start_key, Forward),
+        )
+        .map(|(key, value)| {
+            let (key_account_id, block_number, tx_index) = parse_account_transaction_key(&key);
+            let mut tx_hash = [0u8; 32];
+            tx_hash.copy_from_slice(&value);
+            (key_account_id, block_number, tx_index, tx_hash.into())
+        })
+        .take_while(|(key_account_id, block_number, _, _)| {
+            *key_account_id == account_id && *block_number <= to_block
+        })
+        .take(limit)
+        .map(|(_, block_number, tx_index, tx_hash)| (block_number, tx_index, tx_hash))
+        .collect()
+    }
+
+    pub fn get_last_rollup_action(&self) -> Option<packed::RollupActionRecord> {
+        self.get_iter(COLUMN_ROLLUP_ACTION, IteratorMode::End)
+            .next()
+            .map(|(_key, value)| {
+                packed::RollupActionRecordReader::from_slice_should_be_ok(&value).to_entity()
+            })
+    }
+
     /// key: sudt_script_hash
     fn set_custodian_asset(&self, key: H256, value: u128) -> Result<(), Error> {
         self.insert_raw(
@@ -268,6 +610,8 @@ impl StoreTransaction {
         debug_assert_eq!(block.transactions().len(), tx_receipts.len());
         let block_hash = block.hash();
         self.insert_raw(COLUMN_BLOCK, &block_hash, block.as_slice())?;
+        let economics = build_block_economics(&block, &tx_receipts);
+        self.insert_raw(COLUMN_BLOCK_ECONOMICS, &block_hash, economics.as_slice())?;
         self.insert_raw(
             COLUMN_L2BLOCK_COMMITTED_INFO,
             &block_hash,
@@ -291,7 +635,7 @@ impl StoreTransaction {
             .zip(tx_receipts)
             .enumerate()
         {
-            let key = TransactionKey::build_transaction_key(tx.hash().pack(), index as u32);
+            let key = TransactionKey::build_transaction_key(block_hash.pack(), index as u32);
             self.insert_raw(COLUMN_TRANSACTION, &key.as_slice(), tx.as_slice())?;
             self.insert_raw(
                 COLUMN_TRANSACTION_RECEIPT,
@@ -394,6 +738,7 @@ impl StoreTransaction {
             let tx_hash = tx.hash();
             self.insert_raw(COLUMN_TRANSACTION_INFO, &tx_hash, info.as_slice())?;
         }
+        self.insert_account_transactions(&block)?;
 
         // update custodian assets
         let deposit_assets = self
@@ -414,6 +759,7 @@ impl StoreTransaction {
             }
         });
         self.update_custodian_assets(deposit_assets, withdrawal_assets)?;
+        self.update_account_storage_usage(&block_hash.into(), false)?;
 
         // build main chain index
         self.insert_raw(COLUMN_INDEX, raw_number.as_slice(), &block_hash)?;
@@ -434,9 +780,14 @@ impl StoreTransaction {
 
     pub fn detach_block(&self, block: &packed::L2Block) -> Result<(), Error> {
         // remove transaction info
-        for tx in block.transactions().into_iter() {
+        let block_number: u64 = block.raw().number().unpack();
+        for (index, tx) in block.transactions().into_iter().enumerate() {
             let tx_hash = tx.hash();
             self.delete(COLUMN_TRANSACTION_INFO, &tx_hash)?;
+            for account_id in tx_accounts(&tx) {
+                let key = account_transaction_key(account_id, block_number, index as u32);
+                self.delete(COLUMN_ACCOUNT_TRANSACTION, &key)?;
+            }
         }
 
         // update custodian assets
@@ -458,10 +809,12 @@ impl StoreTransaction {
             }
         });
         self.update_custodian_assets(withdrawal_assets, deposit_assets)?;
+        self.update_account_storage_usage(&block.hash().into(), true)?;
 
         let block_number = block.raw().number();
         self.delete(COLUMN_INDEX, block_number.as_slice())?;
         self.delete(COLUMN_INDEX, &block.hash())?;
+        self.delete(COLUMN_BLOCK_LOCAL_POST_STATE, &block.hash())?;
 
         // update block tree
         let mut block_smt = self.block_smt()?;
@@ -543,6 +896,49 @@ impl StoreTransaction {
     }
 }
 
+// l1_block_number(8 bytes, big endian) | l1_tx_hash(32 bytes)
+fn rollup_action_key(l1_block_number: u64, l1_tx_hash: &packed::Byte32) -> [u8; 40] {
+    let mut key = [0; 40];
+    key[..8].copy_from_slice(&l1_block_number.to_be_bytes());
+    key[8..].copy_from_slice(l1_tx_hash.as_slice());
+    key
+}
+
+// account_id(4 bytes, big endian) | block_number(8 bytes, big endian) | tx_index(4 bytes, big endian)
+fn account_transaction_key(account_id: u32, block_number: u64, tx_index: u32) -> [u8; 16] {
+    let mut key = [0; 16];
+    key[..4].copy_from_slice(&account_id.to_be_bytes());
+    key[4..12].copy_from_slice(&block_number.to_be_bytes());
+    key[12..].copy_from_slice(&tx_index.to_be_bytes());
+    key
+}
+
+fn parse_account_transaction_key(key: &[u8]) -> (u32, u64, u32) {
+    let mut account_id = [0u8; 4];
+    account_id.copy_from_slice(&key[..4]);
+    let mut block_number = [0u8; 8];
+    block_number.copy_from_slice(&key[4..12]);
+    let mut tx_index = [0u8; 4];
+    tx_index.copy_from_slice(&key[12..16]);
+    (
+        u32::from_be_bytes(account_id),
+        u64::from_be_bytes(block_number),
+        u32::from_be_bytes(tx_index),
+    )
+}
+
+// the sender and the receiver of a tx
+fn tx_accounts(tx: &packed::L2Transaction) -> Vec<u32> {
+    let raw = tx.raw();
+    let from_id: u32 = raw.from_id().unpack();
+    let to_id: u32 = raw.to_id().unpack();
+    if from_id == to_id {
+        vec![from_id]
+    } else {
+        vec![from_id, to_id]
+    }
+}
+
 struct CustodianChange {
     capacity: u64,
     sudt_script_hash: H256,
@@ -579,3 +975,29 @@ impl BlockStateRecordKey {
         &self.0[..]
     }
 }
+
+/// Sum fees in the receipts, fees are settled in CKB by the generator
+fn build_block_economics(
+    block: &packed::L2Block,
+    tx_receipts: &[packed::TxReceipt],
+) -> packed::BlockEconomics {
+    let mut used_cycles = 0u64;
+    let mut paid_fees = 0u128;
+    let mut refunded_fees = 0u128;
+    for receipt in tx_receipts {
+        let cycles: u64 = receipt.used_cycles().unpack();
+        let paid_fee: u128 = receipt.paid_fee().unpack();
+        let refunded_fee: u128 = receipt.refunded_fee().unpack();
+        used_cycles = used_cycles.saturating_add(cycles);
+        paid_fees = paid_fees.saturating_add(paid_fee);
+        refunded_fees = refunded_fees.saturating_add(refunded_fee);
+    }
+    packed::BlockEconomics::new_builder()
+        .block_producer_id(block.raw().block_producer_id())
+        .tx_count((tx_receipts.len() as u32).pack())
+        .withdrawal_count((block.withdrawals().len() as u32).pack())
+        .used_cycles(used_cycles.pack())
+        .paid_fees(paid_fees.pack())
+        .refunded_fees(refunded_fees.pack())
+        .build()
+}
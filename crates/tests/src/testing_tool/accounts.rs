@@ -0,0 +1,112 @@
+use gw_chain::chain::Chain;
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State};
+use gw_config::BlockLimitsConfig;
+use gw_store::state_db::{StateDBTransaction, StateDBVersion};
+use gw_types::{
+    packed::{
+        CellOutput, DepositionRequest, L2Transaction, RawL2Transaction, SUDTArgs, SUDTTransfer,
+        Script,
+    },
+    prelude::*,
+};
+
+use super::chain::{
+    apply_block_result, construct_block, setup_chain_with_block_limits, ALWAYS_SUCCESS_CODE_HASH,
+};
+
+/// CKB deposited by each account of `setup_accounts`
+pub const ACCOUNT_CAPACITY: u64 = 500_00000000;
+
+/// Script of the account `index` of `setup_accounts`
+pub fn account_script(index: u8) -> Script {
+    Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .args(vec![42 + index].pack())
+        .build()
+}
+
+/// Id of the account of the script at the tip of the chain
+pub fn account_id_at_tip(chain: &Chain, script: &Script) -> u32 {
+    let db = chain.store().begin_transaction();
+    let tip_block_hash = chain.store().get_tip_block_hash().unwrap();
+    let state_db =
+        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_block_hash))
+            .unwrap();
+    let tree = state_db.account_state_tree().unwrap();
+    tree.get_account_id_by_script_hash(&script.hash().into())
+        .unwrap()
+        .expect("account exists")
+}
+
+/// Setup a chain with `account_count` accounts, returns the account ids.
+/// Each account deposits `ACCOUNT_CAPACITY` in its own block
+pub fn setup_accounts(
+    block_limits: BlockLimitsConfig,
+    account_count: u8,
+) -> (Chain, CellOutput, Vec<u32>) {
+    let rollup_type_script = Script::default();
+    let mut chain =
+        setup_chain_with_block_limits(rollup_type_script.clone(), Default::default(), block_limits);
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    let scripts: Vec<Script> = (0..account_count).map(account_script).collect();
+    for script in &scripts {
+        let deposition_requests = vec![DepositionRequest::new_builder()
+            .capacity(ACCOUNT_CAPACITY.pack())
+            .script(script.clone())
+            .build()];
+        let block_result = {
+            let mem_pool = chain.mem_pool().lock();
+            construct_block(&chain, &mem_pool, deposition_requests.clone()).unwrap()
+        };
+        apply_block_result(
+            &mut chain,
+            rollup_cell.clone(),
+            block_result,
+            deposition_requests,
+        );
+    }
+    let account_ids = scripts
+        .iter()
+        .map(|script| account_id_at_tip(&chain, script))
+        .collect();
+    (chain, rollup_cell, account_ids)
+}
+
+/// Transfer of 1 CKB shannon
+pub fn build_transfer(from_id: u32, to_id: u32, nonce: u32) -> RawL2Transaction {
+    build_transfer_amount(from_id, to_id, nonce, 1)
+}
+
+/// Transfer of `amount` CKB shannons
+pub fn build_transfer_amount(
+    from_id: u32,
+    to_id: u32,
+    nonce: u32,
+    amount: u128,
+) -> RawL2Transaction {
+    let args = SUDTArgs::new_builder()
+        .set(
+            SUDTTransfer::new_builder()
+                .to(to_id.pack())
+                .amount(amount.pack())
+                .build(),
+        )
+        .build();
+    RawL2Transaction::new_builder()
+        .from_id(from_id.pack())
+        .to_id(CKB_SUDT_ACCOUNT_ID.pack())
+        .nonce(nonce.pack())
+        .args(args.as_bytes().pack())
+        .build()
+}
+
+pub fn with_gas_price(raw: RawL2Transaction, gas_price: u128) -> L2Transaction {
+    let raw = raw
+        .as_builder()
+        .gas_limit(100_000_000u64.pack())
+        .gas_price(gas_price.pack())
+        .build();
+    L2Transaction::new_builder().raw(raw).build()
+}
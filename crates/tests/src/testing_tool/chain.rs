@@ -1,7 +1,7 @@
 use gw_block_producer::produce_block::{produce_block, ProduceBlockParam, ProduceBlockResult};
 use gw_chain::chain::{Chain, L1Action, L1ActionContext, SyncEvent, SyncParam};
 use gw_common::blake2b::new_blake2b;
-use gw_config::{BackendConfig, GenesisConfig};
+use gw_config::{BackendConfig, BlockLimitsConfig, GenesisConfig};
 use gw_generator::{
     account_lock_manage::{always_success::AlwaysSuccess, AccountLockManage},
     backend_manage::BackendManage,
@@ -81,12 +81,63 @@ pub fn setup_chain(rollup_type_script: Script, rollup_config: RollupConfig) -> C
     setup_chain_with_account_lock_manage(rollup_type_script, rollup_config, account_lock_manage)
 }
 
+pub fn setup_chain_with_block_limits(
+    rollup_type_script: Script,
+    rollup_config: RollupConfig,
+    block_limits: BlockLimitsConfig,
+) -> Chain {
+    let mut account_lock_manage = AccountLockManage::default();
+    account_lock_manage.register_lock_algorithm(
+        ALWAYS_SUCCESS_CODE_HASH.clone().into(),
+        Box::new(AlwaysSuccess),
+    );
+    let backend_manage = build_backend_manage(&rollup_config);
+    setup_chain_with_config(
+        rollup_type_script,
+        rollup_config,
+        account_lock_manage,
+        backend_manage,
+        block_limits,
+    )
+}
+
 pub fn setup_chain_with_account_lock_manage(
     rollup_type_script: Script,
     rollup_config: RollupConfig,
     account_lock_manage: AccountLockManage,
 ) -> Chain {
-    let store = Store::open_tmp().unwrap();
+    let backend_manage = build_backend_manage(&rollup_config);
+    setup_chain_with_backend_manage(
+        rollup_type_script,
+        rollup_config,
+        account_lock_manage,
+        backend_manage,
+    )
+}
+
+pub fn setup_chain_with_backend_manage(
+    rollup_type_script: Script,
+    rollup_config: RollupConfig,
+    account_lock_manage: AccountLockManage,
+    backend_manage: BackendManage,
+) -> Chain {
+    setup_chain_with_config(
+        rollup_type_script,
+        rollup_config,
+        account_lock_manage,
+        backend_manage,
+        Default::default(),
+    )
+}
+
+fn setup_chain_with_config(
+    rollup_type_script: Script,
+    rollup_config: RollupConfig,
+    account_lock_manage: AccountLockManage,
+    backend_manage: BackendManage,
+    block_limits: BlockLimitsConfig,
+) -> Chain {
+    let store = Store::open_in_memory();
     let rollup_script_hash = rollup_type_script.hash();
     let genesis_config = GenesisConfig {
         timestamp: 0,
@@ -95,15 +146,20 @@ pub fn setup_chain_with_account_lock_manage(
         rollup_type_hash: rollup_script_hash.into(),
     };
     let genesis_committed_info = L2BlockCommittedInfo::default();
-    let backend_manage = build_backend_manage(&rollup_config);
     let rollup_context = RollupContext {
         rollup_script_hash: rollup_script_hash.into(),
         rollup_config: rollup_config.clone(),
+        builtin_accounts: Default::default(),
+        script_args: Default::default(),
+        deposit_limits: Default::default(),
+        withdrawal_fee: Default::default(),
     };
     let generator = Arc::new(Generator::new(
         backend_manage,
         account_lock_manage,
         rollup_context.clone(),
+        block_limits,
+        0,
     ));
     init_genesis(&store, &genesis_config, genesis_committed_info).unwrap();
     let mem_pool = MemPool::create(store.clone(), Arc::clone(&generator)).unwrap();
@@ -126,6 +182,7 @@ pub fn build_sync_tx(
         global_state,
         unused_transactions,
         unused_withdrawal_requests,
+        ..
     } = produce_block_result;
     assert!(unused_transactions.is_empty());
     assert!(unused_withdrawal_requests.is_empty());
@@ -149,21 +206,37 @@ pub fn apply_block_result(
     deposition_requests: Vec<DepositionRequest>,
 ) {
     let transaction = build_sync_tx(rollup_cell, block_result);
-    let l2block_committed_info = L2BlockCommittedInfo::default();
+    let event = sync_block(chain, transaction, deposition_requests);
+    assert_eq!(event, SyncEvent::Success);
+}
+
+/// Sync a layer1 transaction which submits a block
+pub fn sync_block(
+    chain: &mut Chain,
+    transaction: Transaction,
+    deposition_requests: Vec<DepositionRequest>,
+) -> SyncEvent {
+    try_sync_block(chain, transaction, deposition_requests).unwrap()
+}
 
+/// Sync a layer1 transaction which submits a block, returns the error of the node
+pub fn try_sync_block(
+    chain: &mut Chain,
+    transaction: Transaction,
+    deposition_requests: Vec<DepositionRequest>,
+) -> anyhow::Result<SyncEvent> {
     let update = L1Action {
         context: L1ActionContext::SubmitTxs {
             deposition_requests,
         },
         transaction,
-        l2block_committed_info,
+        l2block_committed_info: L2BlockCommittedInfo::default(),
     };
     let param = SyncParam {
         updates: vec![update],
         reverts: Default::default(),
     };
-    let event = chain.sync(param).unwrap();
-    assert_eq!(event, SyncEvent::Success);
+    chain.sync(param)
 }
 
 pub fn construct_block(
@@ -1 +1,3 @@
+pub mod accounts;
 pub mod chain;
+pub mod rpc;
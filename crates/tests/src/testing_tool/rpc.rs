@@ -0,0 +1,52 @@
+use gw_chain::{chain::Chain, sync_progress::SyncProgress};
+use gw_config::RPCServerConfig;
+use gw_rpc_server::registry::Registry;
+use jsonrpc_v2::{MapRouter, RequestKind, ResponseObjects, Server};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Build the registry of the JSONRPC servers of a chain
+pub fn build_registry(chain: &Chain, config: RPCServerConfig) -> Registry {
+    Registry::new(
+        chain.mem_pool().clone(),
+        chain.store().clone(),
+        config,
+        Default::default(),
+        Arc::new(SyncProgress::default()),
+    )
+}
+
+/// Build the JSONRPC server of a chain
+pub fn build_rpc_server(chain: &Chain, config: RPCServerConfig) -> Arc<Server<MapRouter>> {
+    let registry = build_registry(chain, config);
+    registry.build_rpc_server().expect("build rpc server")
+}
+
+/// Call a method, returns the JSONRPC response object
+pub fn call(server: &Server<MapRouter>, method: &str, params: Value) -> Value {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+    // the timers are used by the methods with a timeout
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+    let response = runtime.block_on(server.handle(RequestKind::Bytes(request.to_string().into())));
+    match response {
+        ResponseObjects::Empty => panic!("empty response of {}", method),
+        response => serde_json::to_value(&response).expect("serialize response"),
+    }
+}
+
+/// Call a method, panics if it returns an error
+pub fn call_ok(server: &Server<MapRouter>, method: &str, params: Value) -> Value {
+    let response = call(server, method, params);
+    match response.get("result") {
+        Some(result) => result.clone(),
+        None => panic!("{} returns error {}", method, response["error"]),
+    }
+}
@@ -0,0 +1,126 @@
+use crate::testing_tool::accounts::{build_transfer, setup_accounts};
+use crate::testing_tool::chain::{
+    setup_chain, META_GENERATOR_PATH, META_VALIDATOR_PATH, META_VALIDATOR_SCRIPT_TYPE_HASH,
+    SUDT_GENERATOR_PATH, SUDT_VALIDATOR_PATH,
+};
+use crate::testing_tool::rpc::{build_registry, call, call_ok};
+use gw_common::H256;
+use gw_config::BackendConfig;
+use gw_types::{
+    packed::{L2Transaction, Script},
+    prelude::*,
+};
+use serde_json::json;
+
+#[test]
+fn test_register_backend() {
+    let chain = setup_chain(Script::default(), Default::default());
+    let generator = chain.generator();
+    let backends = generator.backends();
+    assert_eq!(backends.len(), 2);
+
+    // register the sUDT binaries under a new type hash
+    let type_hash = [0xffu8; 32];
+    let config = BackendConfig {
+        validator_path: SUDT_VALIDATOR_PATH.into(),
+        generator_path: SUDT_GENERATOR_PATH.into(),
+        validator_script_type_hash: type_hash.into(),
+    };
+    generator.register_backend(config.clone()).unwrap();
+    let backends = generator.backends();
+    assert_eq!(backends.len(), 3);
+    let backend = backends.last().unwrap();
+    assert_eq!(backend.validator_script_type_hash, H256::from(type_hash));
+    assert_eq!(
+        backend.generator_path.as_deref(),
+        Some(SUDT_GENERATOR_PATH.as_ref())
+    );
+
+    // a backend which fails to load changes nothing
+    let bad_config = BackendConfig {
+        validator_path: "not-exists".into(),
+        generator_path: SUDT_GENERATOR_PATH.into(),
+        validator_script_type_hash: META_VALIDATOR_SCRIPT_TYPE_HASH.into(),
+    };
+    assert!(generator.register_backend(bad_config).is_err());
+    let meta_backend = generator
+        .backends()
+        .into_iter()
+        .find(|backend| {
+            backend.validator_script_type_hash == H256::from(META_VALIDATOR_SCRIPT_TYPE_HASH)
+        })
+        .unwrap();
+    assert_ne!(
+        meta_backend.validator_path.as_deref(),
+        Some("not-exists".as_ref())
+    );
+
+    // reloading the same binaries gives the same backends
+    generator.reload_backends(&[config]).unwrap();
+    let reloaded = generator.backends();
+    assert_eq!(reloaded.len(), 3);
+    for (backend, reloaded) in backends.iter().zip(reloaded.iter()) {
+        assert_eq!(backend.validator_hash, reloaded.validator_hash);
+        assert_eq!(backend.generator_hash, reloaded.generator_hash);
+    }
+}
+
+#[test]
+fn test_reload_backends_rpc() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 2);
+    let sudt_type_hash: [u8; 32] = chain
+        .generator()
+        .rollup_context()
+        .rollup_config
+        .l2_sudt_validator_script_type_hash()
+        .unpack();
+    let type_hash = [0xffu8; 32];
+    let registry = build_registry(&chain, Default::default()).with_backends_loader(move || {
+        Ok(vec![
+            BackendConfig {
+                validator_path: SUDT_VALIDATOR_PATH.into(),
+                generator_path: SUDT_GENERATOR_PATH.into(),
+                validator_script_type_hash: type_hash.into(),
+            },
+            // the sUDT accounts run the meta contract after the reload
+            BackendConfig {
+                validator_path: META_VALIDATOR_PATH.into(),
+                generator_path: META_GENERATOR_PATH.into(),
+                validator_script_type_hash: sudt_type_hash.into(),
+            },
+        ])
+    });
+    let admin_server = registry.build_admin_rpc_server().unwrap();
+    let rpc_server = registry.build_rpc_server().unwrap();
+
+    // the result of the sUDT backend is cached
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(account_ids[0], account_ids[1], 0))
+        .build();
+    let params = json!([format!("0x{}", hex::encode(tx.as_slice()))]);
+    let run_result = call_ok(&rpc_server, "execute_l2transaction", params.clone());
+
+    // backends are only changed on the admin listener
+    let response = call(&rpc_server, "gw_admin_reload_backends", json!([]));
+    assert!(response.get("result").is_none());
+    assert_eq!(
+        call_ok(&rpc_server, "get_backends", json!([]))
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    call_ok(&admin_server, "gw_admin_reload_backends", json!([]));
+    let backends = call_ok(&rpc_server, "get_backends", json!([]));
+    let backends = backends.as_array().unwrap();
+    assert_eq!(backends.len(), 3);
+    // the paths of the node's filesystem are not served
+    let backend = backends.last().unwrap();
+    assert!(backend.get("validator_path").is_none());
+    assert!(backend.get("generator_path").is_none());
+
+    // the same call runs on the reloaded backend instead of returning the cached result
+    let response = call(&rpc_server, "execute_l2transaction", params);
+    assert_ne!(response.get("result"), Some(&run_result));
+}
@@ -0,0 +1,90 @@
+use gw_common::{
+    builtins::CKB_SUDT_ACCOUNT_ID,
+    h256_ext::H256Ext,
+    merkle_utils::{build_sudt_balance_key, verify_sudt_balances_proof},
+    state::State,
+    H256,
+};
+use gw_store::state_db::{StateDBTransaction, StateDBVersion};
+use gw_types::{
+    packed::{CellOutput, Script},
+    prelude::*,
+};
+
+use super::deposition_withdrawal::deposite_to_chain;
+use crate::testing_tool::chain::{setup_chain, ALWAYS_SUCCESS_CODE_HASH};
+
+#[test]
+fn test_sudt_balances_proof() {
+    let rollup_type_script = Script::default();
+    let mut chain = setup_chain(rollup_type_script.clone(), Default::default());
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    let scripts: Vec<Script> = (0..3u8)
+        .map(|i| {
+            Script::new_builder()
+                .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+                .args(vec![42, i].pack())
+                .build()
+        })
+        .collect();
+    for (i, script) in scripts.iter().enumerate() {
+        let capacity = 500_00000000u64 + i as u64;
+        deposite_to_chain(
+            &mut chain,
+            rollup_cell.clone(),
+            script.clone(),
+            capacity,
+            H256::zero(),
+            0,
+        )
+        .unwrap();
+    }
+
+    let db = chain.store().begin_transaction();
+    let tip_block = chain.store().get_tip_block().unwrap();
+    let state_db = StateDBTransaction::from_version(
+        &db,
+        StateDBVersion::from_block_hash(tip_block.hash().into()),
+    )
+    .unwrap();
+    let tree = state_db.account_state_tree().unwrap();
+    let balances: Vec<(u32, u32, u128)> = scripts
+        .iter()
+        .map(|script| {
+            let account_id = tree
+                .get_account_id_by_script_hash(&script.hash().into())
+                .unwrap()
+                .expect("account exists");
+            let balance = tree
+                .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, account_id)
+                .unwrap();
+            (account_id, CKB_SUDT_ACCOUNT_ID, balance)
+        })
+        .collect();
+    let leaves: Vec<(H256, H256)> = balances
+        .iter()
+        .map(|(account_id, sudt_id, balance)| {
+            (
+                build_sudt_balance_key(*sudt_id, *account_id),
+                H256::from_u128(*balance),
+            )
+        })
+        .collect();
+    let proof = state_db
+        .account_smt()
+        .unwrap()
+        .merkle_proof(leaves.iter().map(|(key, _)| *key).collect())
+        .unwrap()
+        .compile(leaves)
+        .unwrap();
+    let account_root: [u8; 32] = tip_block.raw().post_account().merkle_root().unpack();
+    let account_root: H256 = account_root.into();
+
+    assert!(verify_sudt_balances_proof(&account_root, proof.0.clone(), &balances).unwrap());
+    // a wrong balance fails the proof
+    let mut wrong_balances = balances;
+    wrong_balances[1].2 += 1;
+    assert!(!verify_sudt_balances_proof(&account_root, proof.0, &wrong_balances).unwrap());
+}
@@ -0,0 +1,268 @@
+use gw_chain::chain::{Chain, SyncEvent};
+use gw_config::BlockLimitsConfig;
+use gw_generator::error::TransactionError;
+use gw_types::{
+    packed::{BlockInfo, CellOutput, DepositionRequest, L2Transaction, Script},
+    prelude::*,
+};
+
+use crate::testing_tool::accounts::{account_id_at_tip, build_transfer, setup_accounts};
+use crate::testing_tool::chain::{
+    apply_block_result, build_sync_tx, construct_block, setup_chain_with_block_limits, sync_block,
+    ALWAYS_SUCCESS_CODE_HASH,
+};
+
+const TX_COUNT: u32 = 3;
+
+/// Setup a chain and push `TX_COUNT` transfers of an account into the mem-pool
+fn setup_transfers(block_limits: BlockLimitsConfig) -> (Chain, CellOutput, Vec<L2Transaction>) {
+    let (chain, rollup_cell, account_ids) = setup_accounts(block_limits, 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let txs: Vec<L2Transaction> = (0..TX_COUNT)
+        .map(|nonce| {
+            let raw = build_transfer(user_id, receiver_id, nonce);
+            L2Transaction::new_builder().raw(raw).build()
+        })
+        .collect();
+    {
+        let mut mem_pool = chain.mem_pool().lock();
+        for tx in &txs {
+            mem_pool.push_transaction(tx.clone()).unwrap();
+        }
+    }
+    (chain, rollup_cell, txs)
+}
+
+fn hashes(txs: &[L2Transaction]) -> Vec<[u8; 32]> {
+    txs.iter().map(|tx| tx.hash()).collect()
+}
+
+#[test]
+fn test_max_block_cycles() {
+    // measure the cycles of the transfers
+    let (mut chain, rollup_cell, txs) = setup_transfers(Default::default());
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), TX_COUNT as usize);
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+    let cycles: Vec<u64> = txs
+        .iter()
+        .map(|tx| {
+            let receipt = chain
+                .store()
+                .get_transaction_receipt(&tx.hash().into())
+                .unwrap()
+                .expect("receipt");
+            receipt.used_cycles().unpack()
+        })
+        .collect();
+
+    // the txs use exactly the max block cycles
+    let (chain, _, txs) = setup_transfers(BlockLimitsConfig {
+        max_block_cycles: cycles[0] + cycles[1],
+        ..Default::default()
+    });
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    let packed: Vec<L2Transaction> = block_result.block.transactions().into_iter().collect();
+    assert_eq!(hashes(&packed), hashes(&txs[..2]));
+    assert_eq!(hashes(&block_result.unused_transactions), hashes(&txs[2..]));
+
+    // one cycle less, the second tx and the followings are left to the next block
+    let (chain, _, txs) = setup_transfers(BlockLimitsConfig {
+        max_block_cycles: cycles[0] + cycles[1] - 1,
+        ..Default::default()
+    });
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    let packed: Vec<L2Transaction> = block_result.block.transactions().into_iter().collect();
+    assert_eq!(hashes(&packed), hashes(&txs[..1]));
+    assert_eq!(hashes(&block_result.unused_transactions), hashes(&txs[1..]));
+
+    // not even one tx fits, an empty block is still produced
+    let (chain, _, txs) = setup_transfers(BlockLimitsConfig {
+        max_block_cycles: cycles[0] - 1,
+        ..Default::default()
+    });
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert!(block_result.block.transactions().is_empty());
+    assert_eq!(hashes(&block_result.unused_transactions), hashes(&txs));
+}
+
+#[test]
+fn test_max_tx_count() {
+    for max_tx_count in 0..=TX_COUNT {
+        let (chain, _, txs) = setup_transfers(BlockLimitsConfig {
+            max_tx_count,
+            ..Default::default()
+        });
+        let block_result = {
+            let mem_pool = chain.mem_pool().lock();
+            construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+        };
+        let max_tx_count = max_tx_count as usize;
+        let packed: Vec<L2Transaction> = block_result.block.transactions().into_iter().collect();
+        assert_eq!(hashes(&packed), hashes(&txs[..max_tx_count]));
+        assert_eq!(
+            hashes(&block_result.unused_transactions),
+            hashes(&txs[max_tx_count..])
+        );
+    }
+}
+
+#[test]
+fn test_package_by_gas_price() {
+    let gas_limit = 100_000_000u64;
+    for (max_block_gas, expected_count) in vec![(3 * gas_limit, 3), (2 * gas_limit, 2)] {
+        let (chain, _, account_ids) = setup_accounts(
+            BlockLimitsConfig {
+                max_block_gas,
+                ..Default::default()
+            },
+            3,
+        );
+        let receiver_id = account_ids[2];
+        let build_tx = |from_id: u32, nonce: u32, gas_price: u128| {
+            let raw = build_transfer(from_id, receiver_id, nonce)
+                .as_builder()
+                .gas_limit(gas_limit.pack())
+                .gas_price(gas_price.pack())
+                .build();
+            L2Transaction::new_builder().raw(raw).build()
+        };
+        // the second tx of alice pays the most, but it can't go before her first tx
+        let alice_tx0 = build_tx(account_ids[0], 0, 1);
+        let alice_tx1 = build_tx(account_ids[0], 1, 5);
+        let bob_tx0 = build_tx(account_ids[1], 0, 3);
+        let mut mem_pool = chain.mem_pool().lock();
+        for tx in vec![&alice_tx0, &alice_tx1, &bob_tx0] {
+            mem_pool.push_transaction(tx.clone()).unwrap();
+        }
+        let (txs, _) = mem_pool.package();
+        let expected = vec![bob_tx0, alice_tx0, alice_tx1];
+        assert_eq!(hashes(&txs), hashes(&expected[..expected_count]));
+    }
+}
+
+#[test]
+fn test_max_tx_cycles() {
+    let max_tx_cycles = 1000;
+    let (chain, _, account_ids) = setup_accounts(
+        BlockLimitsConfig {
+            max_tx_cycles,
+            ..Default::default()
+        },
+        2,
+    );
+    let snapshot = chain.mem_pool().lock().snapshot();
+    let build_tx = |gas_limit: u64| {
+        let raw = build_transfer(account_ids[0], account_ids[1], 0)
+            .as_builder()
+            .gas_limit(gas_limit.pack())
+            .build();
+        L2Transaction::new_builder().raw(raw).build()
+    };
+
+    // the max gas limit is still bounded by the max tx cycles
+    for gas_limit in vec![0, max_tx_cycles * 10] {
+        let err = snapshot
+            .execute_transaction(build_tx(gas_limit), &BlockInfo::default())
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TransactionError>(),
+            Some(&TransactionError::ExceededMaxCycles {
+                max_cycles: max_tx_cycles
+            })
+        );
+    }
+
+    // a lower gas limit runs out of gas first
+    let err = snapshot
+        .execute_transaction(build_tx(max_tx_cycles - 1), &BlockInfo::default())
+        .unwrap_err();
+    assert!(!matches!(
+        err.downcast_ref::<TransactionError>(),
+        Some(TransactionError::ExceededMaxCycles { .. })
+    ));
+}
+
+#[test]
+fn test_validator_ignores_local_block_limits() {
+    let rollup_type_script = Script::default();
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script.clone()).pack())
+        .build();
+    let mut producer = setup_chain_with_block_limits(
+        rollup_type_script.clone(),
+        Default::default(),
+        Default::default(),
+    );
+    // a validator with tiny limits, the blocks of the producer still exceed them
+    let mut validator = setup_chain_with_block_limits(
+        rollup_type_script,
+        Default::default(),
+        BlockLimitsConfig {
+            max_block_size: 1,
+            max_tx_count: 0,
+            max_tx_args_size: 0,
+            max_tx_cycles: 1,
+            ..Default::default()
+        },
+    );
+
+    let scripts: Vec<Script> = vec![42u8, 43u8]
+        .into_iter()
+        .map(|arg| {
+            Script::new_builder()
+                .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+                .args(vec![arg].pack())
+                .build()
+        })
+        .collect();
+    let deposition_requests: Vec<DepositionRequest> = scripts
+        .iter()
+        .map(|script| {
+            DepositionRequest::new_builder()
+                .capacity(500_00000000u64.pack())
+                .script(script.clone())
+                .build()
+        })
+        .collect();
+    let block_result = {
+        let mem_pool = producer.mem_pool().lock();
+        construct_block(&producer, &mem_pool, deposition_requests.clone()).unwrap()
+    };
+    let transaction = build_sync_tx(rollup_cell.clone(), block_result);
+    for chain in vec![&mut producer, &mut validator] {
+        let event = sync_block(chain, transaction.clone(), deposition_requests.clone());
+        assert_eq!(event, SyncEvent::Success);
+    }
+
+    let sender_id = account_id_at_tip(&producer, &scripts[0]);
+    let receiver_id = account_id_at_tip(&producer, &scripts[1]);
+    {
+        let mut mem_pool = producer.mem_pool().lock();
+        for nonce in 0..TX_COUNT {
+            let raw = build_transfer(sender_id, receiver_id, nonce);
+            let tx = L2Transaction::new_builder().raw(raw).build();
+            mem_pool.push_transaction(tx).unwrap();
+        }
+    }
+    let block_result = {
+        let mem_pool = producer.mem_pool().lock();
+        construct_block(&producer, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), TX_COUNT as usize);
+    let transaction = build_sync_tx(rollup_cell, block_result);
+    let event = sync_block(&mut validator, transaction, Vec::new());
+    assert_eq!(event, SyncEvent::Success);
+}
@@ -0,0 +1,168 @@
+use gw_block_producer::produce_block::{check_block_replay, replay_blocks};
+use gw_common::state::State;
+use gw_db::schema::COLUMN_BLOCK_DEPOSITION_REQUESTS;
+use gw_generator::generator::StateTransitionArgs;
+use gw_store::{
+    chain_view::ChainView,
+    state_db::{StateDBTransaction, StateDBVersion},
+    traits::KVStore,
+};
+use gw_types::{
+    bytes::Bytes,
+    offchain::TxAccessList,
+    packed::{DepositionRequest, DepositionRequestVec, L2Transaction},
+    prelude::*,
+};
+
+use crate::testing_tool::accounts::{
+    account_script, build_transfer, setup_accounts, ACCOUNT_CAPACITY,
+};
+use crate::testing_tool::chain::{apply_block_result, construct_block};
+
+#[test]
+fn test_check_block_replay() {
+    let (chain, _rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+
+    // a block with a tx and a deposit
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(account_ids[0], account_ids[1], 0))
+        .build();
+    chain.mem_pool().lock().push_transaction(tx).unwrap();
+    let deposition_requests = vec![DepositionRequest::new_builder()
+        .capacity(ACCOUNT_CAPACITY.pack())
+        .script(account_script(0))
+        .build()];
+    let block = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, deposition_requests.clone())
+            .unwrap()
+            .block
+    };
+    assert_eq!(block.transactions().len(), 1);
+    let db = chain.store().begin_transaction();
+    check_block_replay(&db, chain.generator(), &block, deposition_requests.clone()).unwrap();
+
+    // the replayed state diverges without the deposit
+    let err = check_block_replay(&db, chain.generator(), &block, Vec::new()).unwrap_err();
+    assert!(err.to_string().contains("diverges"), "{}", err);
+
+    // the replayed state diverges from a tampered block
+    let raw_block = block.raw();
+    let post_account = raw_block.post_account();
+    let count: u32 = post_account.count().unpack();
+    let tampered_block = block
+        .clone()
+        .as_builder()
+        .raw(
+            raw_block
+                .as_builder()
+                .post_account(post_account.as_builder().count((count + 1).pack()).build())
+                .build(),
+        )
+        .build();
+    let err = check_block_replay(&db, chain.generator(), &tampered_block, deposition_requests)
+        .unwrap_err();
+    assert!(err.to_string().contains("diverges"), "{}", err);
+}
+
+#[test]
+fn test_replay_stored_blocks() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(account_ids[0], account_ids[1], 0))
+        .build();
+    chain.mem_pool().lock().push_transaction(tx).unwrap();
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 1);
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+
+    // blocks #1 and #2 deposit, block #3 transfers, the range is cut at the tip
+    let replayed = replay_blocks(chain.store(), chain.generator(), 0, 10).unwrap();
+    assert_eq!(replayed, 3);
+    // the replay doesn't change the stored state
+    let replayed = replay_blocks(chain.store(), chain.generator(), 2, 3).unwrap();
+    assert_eq!(replayed, 2);
+
+    // the replay of block #2 diverges without its deposit
+    let db = chain.store().begin_transaction();
+    let block_hash = db.get_block_hash_by_number(2).unwrap().unwrap();
+    db.insert_raw(
+        COLUMN_BLOCK_DEPOSITION_REQUESTS,
+        block_hash.as_slice(),
+        DepositionRequestVec::default().as_slice(),
+    )
+    .unwrap();
+    db.commit().unwrap();
+    let err = replay_blocks(chain.store(), chain.generator(), 0, 10).unwrap_err();
+    assert!(err.to_string().contains("block #2"), "{}", err);
+}
+
+#[test]
+fn test_parallel_and_sequential_execution_give_the_same_state() {
+    let (chain, _rollup_cell, account_ids) = setup_accounts(Default::default(), 4);
+    // 0 -> 1 and 2 -> 3 don't conflict, 1 -> 2 conflicts with both
+    for &(from, to) in &[(0, 1), (2, 3), (1, 2)] {
+        let tx = L2Transaction::new_builder()
+            .raw(build_transfer(account_ids[from], account_ids[to], 0))
+            .build();
+        chain.mem_pool().lock().push_transaction(tx).unwrap();
+    }
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    let block = block_result.block;
+    assert_eq!(block.transactions().len(), 3);
+
+    // returns the post state and the receipts
+    let apply = |access_lists: Option<&[TxAccessList]>| {
+        let db = chain.store().begin_transaction();
+        let parent_block_hash: [u8; 32] = block.raw().parent_block_hash().unpack();
+        let state_db = StateDBTransaction::from_version(
+            &db,
+            StateDBVersion::from_block_hash(parent_block_hash.into()),
+        )
+        .unwrap();
+        let mut state = state_db.account_state_tree().unwrap();
+        let chain_view = ChainView::new(&db, parent_block_hash.into());
+        let args = StateTransitionArgs {
+            l2block: block.clone(),
+            deposition_requests: Vec::new(),
+        };
+        let generator = chain.generator();
+        let result = match access_lists {
+            Some(access_lists) => generator.apply_state_transition_parallel(
+                &chain_view,
+                &mut state,
+                args,
+                access_lists,
+            ),
+            None => generator.apply_state_transition(&chain_view, &mut state, args),
+        }
+        .unwrap();
+        let receipts: Vec<Bytes> = result
+            .receipts
+            .iter()
+            .map(|receipt| receipt.as_bytes())
+            .collect();
+        (
+            state.calculate_root().unwrap(),
+            state.get_account_count().unwrap(),
+            receipts,
+        )
+    };
+    let sequential = apply(None);
+    assert_eq!(apply(Some(&block_result.access_lists)), sequential);
+    // the access lists aren't trusted, the conflicts of wrong lists are found by the execution
+    let wrong_access_lists = vec![TxAccessList::default(); 3];
+    assert_eq!(apply(Some(&wrong_access_lists)), sequential);
+
+    // the replay executes the block in parallel with the stored access lists
+    let db = chain.store().begin_transaction();
+    db.insert_block_access_lists(&block.hash().into(), &wrong_access_lists)
+        .unwrap();
+    check_block_replay(&db, chain.generator(), &block, Vec::new()).unwrap();
+}
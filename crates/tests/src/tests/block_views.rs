@@ -0,0 +1,163 @@
+use gw_chain::chain::Chain;
+use gw_jsonrpc_types::godwoken::L2BlockVerboseView;
+use gw_types::{
+    packed::{CellOutput, DepositionRequest, L2Transaction, Script},
+    prelude::*,
+};
+use serde_json::{json, Value};
+
+use crate::testing_tool::{
+    accounts::{build_transfer, setup_accounts},
+    chain::{apply_block_result, construct_block, setup_chain},
+    rpc::{build_rpc_server, call_ok},
+};
+
+/// Setup a chain with `count` blocks after genesis, each block has a deposit
+fn setup_blocks(count: u8) -> Chain {
+    let rollup_type_script = Script::default();
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script.clone()).pack())
+        .build();
+    let mut chain = setup_chain(rollup_type_script, Default::default());
+    for i in 0..count {
+        let deposition = DepositionRequest::new_builder()
+            .capacity(100u64.pack())
+            .script(Script::new_builder().args(vec![i].pack()).build())
+            .build();
+        let block_result = {
+            let mem_pool = chain.mem_pool().lock();
+            construct_block(&chain, &mem_pool, vec![deposition.clone()]).unwrap()
+        };
+        apply_block_result(
+            &mut chain,
+            rollup_cell.clone(),
+            block_result,
+            vec![deposition],
+        );
+    }
+    chain
+}
+
+#[test]
+fn test_get_blocks() {
+    let chain = setup_blocks(2);
+    let server = build_rpc_server(&chain, Default::default());
+
+    let headers = call_ok(&server, "get_blocks", json!(["0x1", "0xa", false]));
+    let headers = headers.as_array().expect("headers");
+    // stops at the tip
+    assert_eq!(headers.len(), 2);
+    let db = chain.store().begin_transaction();
+    for (i, header) in headers.iter().enumerate() {
+        let number = i as u64 + 1;
+        let block_hash = db.get_block_hash_by_number(number).unwrap().unwrap();
+        assert_eq!(
+            header["hash"],
+            json!(format!("0x{}", hex::encode(block_hash.as_slice())))
+        );
+        assert_eq!(header["raw"]["number"], json!(format!("{:#x}", number)));
+        assert!(header.get("transactions").is_none());
+        assert!(header["l1_commitment"].is_object());
+    }
+
+    let blocks = call_ok(&server, "get_blocks", json!(["0x0", "0xa", true]));
+    let blocks = blocks.as_array().expect("blocks");
+    assert_eq!(blocks.len(), 3);
+    assert!(blocks.iter().all(|block| block["transactions"].is_array()));
+
+    // the count is capped and nothing is returned beyond the tip
+    let headers = call_ok(&server, "get_blocks", json!(["0x0", "0xffffffff", false]));
+    assert_eq!(headers.as_array().map(Vec::len), Some(3));
+    let headers = call_ok(&server, "get_blocks", json!(["0x3", "0xa", false]));
+    assert_eq!(headers, json!([]));
+}
+
+#[test]
+fn test_get_block_header() {
+    let chain = setup_blocks(1);
+    let server = build_rpc_server(&chain, Default::default());
+    let block = chain.store().get_tip_block().unwrap();
+    let block_hash = format!("0x{}", hex::encode(block.hash()));
+
+    let header = call_ok(&server, "get_block_header", json!([block_hash]));
+    assert_eq!(header["hash"], json!(block_hash));
+    assert_eq!(header["raw"]["number"], json!("0x1"));
+    assert!(header["l1_commitment"].is_object());
+    // the txs, withdrawals and proofs are left out
+    for key in &[
+        "transactions",
+        "withdrawal_requests",
+        "kv_state",
+        "block_proof",
+    ] {
+        assert!(header.get(key).is_none(), "unexpected {}", key);
+    }
+    // the same header as the header verbosity of get_block
+    let block_view = call_ok(&server, "get_block", json!([block_hash, "header"]));
+    assert_eq!(header, block_view);
+
+    let unknown_hash = format!("0x{}", hex::encode([42u8; 32]));
+    let header = call_ok(&server, "get_block_header", json!([unknown_hash]));
+    assert!(header.is_null());
+}
+
+#[test]
+fn test_get_block_verbosity() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(account_ids[0], account_ids[1], 0))
+        .build();
+    chain
+        .mem_pool()
+        .lock()
+        .push_transaction(tx.clone())
+        .unwrap();
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 1);
+    let block_hash = format!("0x{}", hex::encode(block_result.block.hash()));
+    let number: u64 = block_result.block.raw().number().unpack();
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+    let server = build_rpc_server(&chain, Default::default());
+    let tx_hash = json!(format!("0x{}", hex::encode(tx.hash())));
+
+    // the views are untagged, each must deserialize into its own variant
+    let get_block = |params: Value| {
+        let view = call_ok(&server, "get_block", params);
+        let parsed: L2BlockVerboseView = serde_json::from_value(view.clone()).expect("view");
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), view);
+        (view, parsed)
+    };
+
+    // the full block without receipts by default
+    let (block, parsed) = get_block(json!([block_hash]));
+    assert!(matches!(parsed, L2BlockVerboseView::Block(_)));
+    assert_eq!(block["hash"], json!(block_hash));
+    assert_eq!(block["transactions"][0]["hash"], tx_hash);
+    assert!(block.get("receipts").is_none());
+
+    let (header, parsed) = get_block(json!([block_hash, "header"]));
+    assert!(matches!(parsed, L2BlockVerboseView::Header(_)));
+    assert_eq!(header["hash"], json!(block_hash));
+    assert!(header.get("transactions").is_none());
+
+    let (block, parsed) = get_block(json!([block_hash, "tx_hashes"]));
+    assert!(matches!(parsed, L2BlockVerboseView::TxHashes(_)));
+    assert_eq!(block["transactions"], json!([tx_hash]));
+    assert_eq!(block["withdrawal_requests"], json!([]));
+
+    let (block, parsed) = get_block(json!([block_hash, "full"]));
+    assert!(matches!(parsed, L2BlockVerboseView::Full(_)));
+    assert_eq!(block["transactions"][0]["hash"], tx_hash);
+    assert_eq!(block["receipts"].as_array().map(Vec::len), Some(1));
+
+    // get_block_by_number returns the same views
+    let number = json!(format!("{:#x}", number));
+    for verbosity in &["header", "tx_hashes", "full"] {
+        let by_hash = call_ok(&server, "get_block", json!([block_hash, verbosity]));
+        let by_number = call_ok(&server, "get_block_by_number", json!([number, verbosity]));
+        assert_eq!(by_hash, by_number);
+    }
+}
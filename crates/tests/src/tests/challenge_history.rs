@@ -0,0 +1,122 @@
+use crate::testing_tool::chain::setup_chain;
+use gw_chain::chain::{L1Action, L1ActionContext, RevertedL1Action, SyncEvent, SyncParam};
+use gw_types::{
+    core::{RollupActionType, Status},
+    packed::{
+        CellOutput, ChallengeTarget, GlobalState, L2BlockCommittedInfo, RawTransaction, Script,
+        Transaction, VerifyTransactionWitness,
+    },
+    prelude::*,
+};
+use std::convert::TryFrom;
+
+fn build_action_tx(rollup_cell: CellOutput, global_state: &GlobalState) -> Transaction {
+    let raw = RawTransaction::new_builder()
+        .outputs(vec![rollup_cell].pack())
+        .outputs_data(vec![global_state.as_bytes()].pack())
+        .build();
+    Transaction::new_builder().raw(raw).build()
+}
+
+fn committed_info(number: u64, tx_hash: [u8; 32]) -> L2BlockCommittedInfo {
+    L2BlockCommittedInfo::new_builder()
+        .number(number.pack())
+        .transaction_hash(tx_hash.pack())
+        .build()
+}
+
+#[test]
+fn test_challenge_history() {
+    let rollup_type_script = Script::default();
+    let mut chain = setup_chain(rollup_type_script.clone(), Default::default());
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    let running_state = chain.local_state().last_global_state().clone();
+    let halting_state = running_state
+        .clone()
+        .as_builder()
+        .status(Status::Halting.into())
+        .build();
+    let target = ChallengeTarget::new_builder()
+        .block_hash([42u8; 32].pack())
+        .target_index(1u32.pack())
+        .build();
+
+    // enter challenge
+    let challenge = L1Action {
+        context: L1ActionContext::Challenge {
+            context: target.clone(),
+        },
+        transaction: build_action_tx(rollup_cell.clone(), &halting_state),
+        l2block_committed_info: committed_info(1, [1u8; 32]),
+    };
+    let param = SyncParam {
+        updates: vec![challenge],
+        reverts: Default::default(),
+    };
+    assert_eq!(chain.sync(param).unwrap(), SyncEvent::WaitChallenge);
+
+    // cancel challenge
+    let cancel = L1Action {
+        context: L1ActionContext::CancelChallenge {
+            context: VerifyTransactionWitness::default(),
+        },
+        transaction: build_action_tx(rollup_cell, &running_state),
+        l2block_committed_info: committed_info(2, [2u8; 32]),
+    };
+    let param = SyncParam {
+        updates: vec![cancel.clone()],
+        reverts: Default::default(),
+    };
+    assert_eq!(chain.sync(param).unwrap(), SyncEvent::Success);
+
+    let actions = chain.store().begin_transaction().get_rollup_actions(0, 10);
+    assert_eq!(actions.len(), 2);
+    let action_types: Vec<_> = actions
+        .iter()
+        .map(|action| RollupActionType::try_from(action.action_type()).unwrap())
+        .collect();
+    assert_eq!(
+        action_types,
+        vec![
+            RollupActionType::EnterChallenge,
+            RollupActionType::CancelChallenge
+        ]
+    );
+    assert_eq!(actions[0].challenge_target(), target);
+    assert_eq!(actions[1].challenge_target(), target);
+    assert_eq!(
+        Status::try_from(actions[0].post_status()),
+        Ok(Status::Halting)
+    );
+    assert_eq!(
+        Status::try_from(actions[1].post_status()),
+        Ok(Status::Running)
+    );
+    let l1_block_number: u64 = actions[1].l1_block_number().unpack();
+    assert_eq!(l1_block_number, 2);
+    assert_eq!(
+        chain.store().begin_transaction().get_rollup_actions(2, 10),
+        actions[1..].to_vec()
+    );
+
+    // revert the cancellation on a L1 fork
+    let L1Action {
+        transaction,
+        l2block_committed_info,
+        context,
+    } = cancel;
+    let param = SyncParam {
+        updates: Default::default(),
+        reverts: vec![RevertedL1Action {
+            prev_global_state: halting_state,
+            transaction,
+            l2block_committed_info,
+            context,
+        }],
+    };
+    assert_eq!(chain.sync(param).unwrap(), SyncEvent::Success);
+    let actions = chain.store().begin_transaction().get_rollup_actions(0, 10);
+    assert_eq!(actions.len(), 1);
+}
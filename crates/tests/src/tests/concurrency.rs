@@ -0,0 +1,133 @@
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State};
+use gw_store::state_db::{StateDBTransaction, StateDBVersion};
+use gw_types::{
+    packed::{BlockInfo, L2Transaction, RawL2Transaction, SUDTArgs, SUDTQuery, SUDTTransfer},
+    prelude::*,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::testing_tool::accounts::setup_accounts;
+use crate::testing_tool::chain::{apply_block_result, construct_block};
+
+const BLOCKS: usize = 10;
+const THREADS: usize = 4;
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+fn tip_nonce(store: &gw_store::Store, account_id: u32) -> u32 {
+    let tip_block_hash = store.get_tip_block_hash().unwrap();
+    let db = store.begin_transaction();
+    let state_db =
+        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_block_hash))
+            .unwrap();
+    let tree = state_db.account_state_tree().unwrap();
+    tree.get_nonce(account_id).unwrap()
+}
+
+fn build_tx(from_id: u32, nonce: u32, args: SUDTArgs) -> L2Transaction {
+    let raw = RawL2Transaction::new_builder()
+        .from_id(from_id.pack())
+        .to_id(CKB_SUDT_ACCOUNT_ID.pack())
+        .nonce(nonce.pack())
+        .args(args.as_bytes().pack())
+        .build();
+    L2Transaction::new_builder().raw(raw).build()
+}
+
+#[test]
+fn test_concurrent_submit_execute_query() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let (done_tx, done_rx) = mpsc::channel();
+    let spawn = |name: &'static str, f: Box<dyn Fn() + Send>| {
+        let stop = Arc::clone(&stop);
+        let done_tx = done_tx.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                f();
+            }
+            done_tx.send(name).unwrap();
+        });
+    };
+    // a single submitter, so there is at most one tx of each nonce in the pool
+    {
+        let store = chain.store().clone();
+        let mem_pool = Arc::clone(chain.mem_pool());
+        spawn(
+            "submit",
+            Box::new(move || {
+                let transfer = SUDTArgs::new_builder()
+                    .set(
+                        SUDTTransfer::new_builder()
+                            .to(receiver_id.pack())
+                            .amount(1u128.pack())
+                            .build(),
+                    )
+                    .build();
+                let tx = build_tx(user_id, tip_nonce(&store, user_id), transfer);
+                // duplicated txs are rejected
+                mem_pool.lock().push_transaction(tx).ok();
+            }),
+        );
+    }
+    for _ in 0..THREADS {
+        let store = chain.store().clone();
+        let mem_pool = Arc::clone(chain.mem_pool());
+        spawn(
+            "execute",
+            Box::new(move || {
+                let query = SUDTArgs::new_builder()
+                    .set(SUDTQuery::new_builder().account_id(user_id.pack()).build())
+                    .build();
+                let tx = build_tx(user_id, tip_nonce(&store, user_id), query);
+                let snapshot = mem_pool.lock().snapshot();
+                // the nonce may be outdated by a new block
+                snapshot.execute_transaction(tx, &BlockInfo::default()).ok();
+            }),
+        );
+    }
+    for _ in 0..THREADS {
+        let store = chain.store().clone();
+        spawn(
+            "query",
+            Box::new(move || {
+                let tip_block_hash = store.get_tip_block_hash().unwrap();
+                let db = store.begin_transaction();
+                let state_db = StateDBTransaction::from_version(
+                    &db,
+                    StateDBVersion::from_block_hash(tip_block_hash),
+                )
+                .unwrap();
+                let tree = state_db.account_state_tree().unwrap();
+                tree.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, user_id).unwrap();
+            }),
+        );
+    }
+    drop(done_tx);
+
+    // produce blocks while other threads are running
+    for _ in 0..BLOCKS {
+        thread::sleep(Duration::from_millis(100));
+        let block_result = {
+            let mem_pool = chain.mem_pool().lock();
+            construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+        };
+        apply_block_result(&mut chain, rollup_cell.clone(), block_result, Vec::new());
+    }
+
+    stop.store(true, Ordering::SeqCst);
+    for _ in 0..(1 + THREADS * 2) {
+        done_rx
+            .recv_timeout(TIMEOUT)
+            .expect("threads should exit, maybe deadlock");
+    }
+    assert!(tip_nonce(chain.store(), user_id) > 0);
+}
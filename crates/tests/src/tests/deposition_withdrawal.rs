@@ -1,21 +1,32 @@
 use anyhow::Result;
+use gw_block_producer::produce_block::{produce_block, ProduceBlockParam};
 use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State, H256};
 use gw_generator::{
+    account_lock_manage::{
+        always_success::AlwaysSuccess, secp256k1::Secp256k1Eth, AccountLockManage,
+    },
     error::{DepositionError, WithdrawalError},
     Error,
 };
+use gw_mem_pool::pool::{MemPoolEvent, WithdrawalStatus};
 use gw_store::state_db::{StateDBTransaction, StateDBVersion};
 use gw_types::{
-    packed::{CellOutput, DepositionRequest, RawWithdrawalRequest, Script, WithdrawalRequest},
+    packed::{
+        CellOutput, DepositionRequest, L2Transaction, RawL2Transaction, RawWithdrawalRequest,
+        SUDTArgs, SUDTTransfer, Script, WithdrawalRequest,
+    },
     prelude::*,
 };
 
 use crate::testing_tool::chain::{
-    apply_block_result, construct_block, setup_chain, ALWAYS_SUCCESS_CODE_HASH,
+    apply_block_result, construct_block, setup_chain, setup_chain_with_account_lock_manage,
+    ALWAYS_SUCCESS_CODE_HASH,
 };
 use gw_chain::chain::Chain;
 
-fn deposite_to_chain(
+const ETH_LOCK_CODE_HASH: [u8; 32] = [3u8; 32];
+
+pub(crate) fn deposite_to_chain(
     chain: &mut Chain,
     rollup_cell: CellOutput,
     user_script: Script,
@@ -242,3 +253,279 @@ fn test_deposit_faked_ckb() {
     let err: Error = err.downcast().unwrap();
     assert_eq!(err, Error::Deposition(DepositionError::DepositFakedCKB));
 }
+
+#[test]
+fn test_deposition_call() {
+    let rollup_type_script = Script::default();
+    let mut account_lock_manage = AccountLockManage::default();
+    account_lock_manage.register_lock_algorithm(
+        ALWAYS_SUCCESS_CODE_HASH.clone().into(),
+        Box::new(AlwaysSuccess),
+    );
+    account_lock_manage.register_lock_algorithm(ETH_LOCK_CODE_HASH.into(), Box::new(Secp256k1Eth));
+    let mut chain = setup_chain_with_account_lock_manage(
+        rollup_type_script.clone(),
+        Default::default(),
+        account_lock_manage,
+    );
+    let capacity = 500_00000000u64;
+    let alice_script = Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .args(vec![42].pack())
+        .build();
+    let bob_script = Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .args(vec![43].pack())
+        .build();
+    let carol_script = Script::new_builder()
+        .code_hash(ETH_LOCK_CODE_HASH.pack())
+        .args(vec![44u8; 20].pack())
+        .build();
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    for script in vec![
+        alice_script.clone(),
+        bob_script.clone(),
+        carol_script.clone(),
+    ] {
+        deposite_to_chain(
+            &mut chain,
+            rollup_cell.clone(),
+            script,
+            capacity,
+            H256::zero(),
+            0,
+        )
+        .unwrap();
+    }
+    let get_account = |chain: &Chain, script: &Script| {
+        let tip_block_hash = chain.store().get_tip_block_hash().unwrap();
+        let db = chain.store().begin_transaction();
+        let state_db =
+            StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_block_hash))
+                .unwrap();
+        let tree = state_db.account_state_tree().unwrap();
+        let id = tree
+            .get_account_id_by_script_hash(&script.hash().into())
+            .unwrap()
+            .expect("account exists");
+        let balance = tree.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, id).unwrap();
+        let nonce = tree.get_nonce(id).unwrap();
+        (id, balance, nonce)
+    };
+    let (alice_id, _, _) = get_account(&chain, &alice_script);
+    let (bob_id, _, _) = get_account(&chain, &bob_script);
+    let (carol_id, _, _) = get_account(&chain, &carol_script);
+
+    // a deposit with a call, returns the txs of the produced block
+    let deposit_and_call = |chain: &mut Chain, script: &Script, call: &L2Transaction| {
+        let deposition_requests = vec![DepositionRequest::new_builder()
+            .capacity(capacity.pack())
+            .sudt_script_hash(H256::zero().pack())
+            .script(script.clone())
+            .call(call.as_bytes().pack())
+            .build()];
+        let block_result = {
+            let mem_pool = chain.mem_pool().lock();
+            construct_block(chain, &mem_pool, deposition_requests.clone()).unwrap()
+        };
+        let txs: Vec<L2Transaction> = block_result.block.transactions().into_iter().collect();
+        apply_block_result(
+            chain,
+            rollup_cell.clone(),
+            block_result,
+            deposition_requests,
+        );
+        txs
+    };
+    let transfer_amount = 100_00000000u128;
+    let transfer = |from_id: u32| {
+        let args = SUDTArgs::new_builder()
+            .set(
+                SUDTTransfer::new_builder()
+                    .to(bob_id.pack())
+                    .amount(transfer_amount.pack())
+                    .build(),
+            )
+            .build();
+        let raw = RawL2Transaction::new_builder()
+            .from_id(from_id.pack())
+            .to_id(CKB_SUDT_ACCOUNT_ID.pack())
+            .nonce(0u32.pack())
+            .args(args.as_bytes().pack())
+            .build();
+        L2Transaction::new_builder().raw(raw).build()
+    };
+
+    // deposit and transfer to bob in the same block, the call is the tx of the block
+    let call = transfer(alice_id);
+    let txs = deposit_and_call(&mut chain, &alice_script, &call);
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].hash(), call.hash());
+    let tx_hash: H256 = call.hash().into();
+    let receipt = chain.store().get_transaction_receipt(&tx_hash).unwrap();
+    assert!(receipt.is_some());
+    let (_, alice_balance, alice_nonce) = get_account(&chain, &alice_script);
+    let (_, bob_balance, _) = get_account(&chain, &bob_script);
+    assert_eq!(alice_balance, capacity as u128 * 2 - transfer_amount);
+    assert_eq!(bob_balance, capacity as u128 + transfer_amount);
+    assert_eq!(alice_nonce, 1);
+
+    // replay the call, the deposit is credited but the call is dropped
+    let txs = deposit_and_call(&mut chain, &alice_script, &call);
+    assert!(txs.is_empty());
+    let (_, alice_balance, alice_nonce) = get_account(&chain, &alice_script);
+    let (_, bob_balance, _) = get_account(&chain, &bob_script);
+    assert_eq!(alice_balance, capacity as u128 * 3 - transfer_amount);
+    assert_eq!(bob_balance, capacity as u128 + transfer_amount);
+    assert_eq!(alice_nonce, 1);
+
+    // a call without the signature of the account is dropped, anyone can deposit to carol
+    let txs = deposit_and_call(&mut chain, &carol_script, &transfer(carol_id));
+    assert!(txs.is_empty());
+    let (_, carol_balance, carol_nonce) = get_account(&chain, &carol_script);
+    let (_, bob_balance, _) = get_account(&chain, &bob_script);
+    assert_eq!(carol_balance, capacity as u128 * 2);
+    assert_eq!(bob_balance, capacity as u128 + transfer_amount);
+    assert_eq!(carol_nonce, 0);
+}
+
+#[test]
+fn test_withdrawal_feasibility() {
+    let rollup_type_script = Script::default();
+    let mut chain = setup_chain(rollup_type_script.clone(), Default::default());
+    let capacity = 500_00000000u64;
+    let user_script = Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .args(vec![42].pack())
+        .build();
+    let user_script_hash = user_script.hash();
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    deposite_to_chain(
+        &mut chain,
+        rollup_cell,
+        user_script,
+        capacity,
+        H256::zero(),
+        0,
+    )
+    .unwrap();
+    let build_withdrawal = |capacity: u64, nonce: u32| {
+        let raw = RawWithdrawalRequest::new_builder()
+            .nonce(nonce.pack())
+            .capacity(capacity.pack())
+            .account_script_hash(user_script_hash.pack())
+            .sudt_script_hash(H256::zero().pack())
+            .build();
+        WithdrawalRequest::new_builder().raw(raw).build()
+    };
+    let withdraw_capacity = 200_00000000u64;
+
+    let mut mem_pool = chain.mem_pool().lock();
+    let feasibility = mem_pool
+        .withdrawal_feasibility(&build_withdrawal(withdraw_capacity, 0))
+        .unwrap();
+    assert_eq!(feasibility.status, WithdrawalStatus::NextBlock);
+    assert_eq!(feasibility.available_capacity, capacity as u128);
+
+    // overdraft
+    let feasibility = mem_pool
+        .withdrawal_feasibility(&build_withdrawal(capacity + 1, 0))
+        .unwrap();
+    assert_eq!(feasibility.status, WithdrawalStatus::Rejected);
+
+    // a block takes one withdrawal of an account, the pending one claims the custodian
+    mem_pool
+        .push_withdrawal_request(build_withdrawal(withdraw_capacity, 0))
+        .unwrap();
+    let feasibility = mem_pool
+        .withdrawal_feasibility(&build_withdrawal(withdraw_capacity, 1))
+        .unwrap();
+    assert_eq!(feasibility.status, WithdrawalStatus::Queued);
+    assert_eq!(
+        feasibility.available_capacity,
+        (capacity - withdraw_capacity) as u128
+    );
+}
+
+#[test]
+fn test_drop_invalid_withdrawals_on_new_tip() {
+    let rollup_type_script = Script::default();
+    let mut chain = setup_chain(rollup_type_script.clone(), Default::default());
+    let capacity = 500_00000000u64;
+    let user_script = Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .args(vec![42].pack())
+        .build();
+    let user_script_hash = user_script.hash();
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    deposite_to_chain(
+        &mut chain,
+        rollup_cell.clone(),
+        user_script,
+        capacity,
+        H256::zero(),
+        0,
+    )
+    .unwrap();
+    let build_withdrawal = |capacity: u64, nonce: u32| {
+        let raw = RawWithdrawalRequest::new_builder()
+            .nonce(nonce.pack())
+            .capacity(capacity.pack())
+            .account_script_hash(user_script_hash.pack())
+            .sudt_script_hash(H256::zero().pack())
+            .build();
+        WithdrawalRequest::new_builder().raw(raw).build()
+    };
+
+    // pending withdrawals are valid on the current tip
+    let dropped_withdrawal = build_withdrawal(200_00000000, 1);
+    {
+        let mut mem_pool = chain.mem_pool().lock();
+        mem_pool
+            .push_withdrawal_request(build_withdrawal(300_00000000, 0))
+            .unwrap();
+        mem_pool
+            .push_withdrawal_request(dropped_withdrawal.clone())
+            .unwrap();
+    }
+
+    // another withdrawal spends the balance in the new block
+    let block_result = {
+        let db = chain.store().begin_transaction();
+        let parent_block = chain.store().get_tip_block().unwrap();
+        let rollup_config_hash = chain.rollup_config_hash().clone().into();
+        let param = ProduceBlockParam {
+            db,
+            generator: chain.generator(),
+            block_producer_id: 0,
+            timestamp: 0,
+            txs: Vec::new(),
+            deposition_requests: Vec::new(),
+            withdrawal_requests: vec![build_withdrawal(400_00000000, 0)],
+            parent_block: &parent_block,
+            rollup_config_hash: &rollup_config_hash,
+            max_withdrawal_capacity: std::u128::MAX,
+        };
+        produce_block(param).unwrap()
+    };
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+
+    let mut mem_pool = chain.mem_pool().lock();
+    assert!(mem_pool.pending().is_empty());
+    let events = mem_pool.drain_events();
+    assert_eq!(events.len(), 1);
+    let expected_hash: H256 = dropped_withdrawal.hash().into();
+    match &events[0] {
+        MemPoolEvent::WithdrawalDropped {
+            withdrawal_hash, ..
+        } => assert_eq!(*withdrawal_hash, expected_hash),
+        event => panic!("unexpected event {:?}", event),
+    }
+    assert!(mem_pool.drain_events().is_empty());
+}
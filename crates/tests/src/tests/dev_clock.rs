@@ -0,0 +1,48 @@
+use gw_common::H256;
+use gw_config::MemPoolLimitsConfig;
+use gw_mem_pool::pool::MemPoolEvent;
+use std::time::Duration;
+
+use crate::testing_tool::accounts::{build_transfer, setup_accounts, with_gas_price};
+
+#[test]
+fn test_dev_clock() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 2);
+    let tip_block_hash: H256 = chain.store().get_tip_block_hash().unwrap();
+    let mut mem_pool = chain.mem_pool().lock();
+    assert!(mem_pool.dev_clock().is_none());
+    mem_pool.enable_dev_mode();
+    mem_pool.set_limits(MemPoolLimitsConfig {
+        tx_ttl_secs: 60,
+        ..Default::default()
+    });
+    let dev_clock = mem_pool.dev_clock().cloned().expect("dev clock");
+
+    // block timestamps follow the clock, but never go backwards
+    dev_clock.set_time(Duration::from_millis(1_000_000));
+    assert_eq!(mem_pool.next_block_timestamp(0), 1_000_000);
+    assert_eq!(mem_pool.next_block_timestamp(2_000_000), 2_000_000);
+
+    let tx = with_gas_price(build_transfer(account_ids[0], account_ids[1], 0), 1);
+    mem_pool.push_transaction(tx.clone()).unwrap();
+
+    // the tx stays in the pool until the TTL passed
+    dev_clock.increase_time(Duration::from_secs(59));
+    mem_pool.notify_new_tip(tip_block_hash).unwrap();
+    assert!(mem_pool.drain_events().is_empty());
+    assert_eq!(mem_pool.stats().txs, 1);
+
+    let now = dev_clock.increase_time(Duration::from_secs(1));
+    assert_eq!(now, Duration::from_millis(1_060_000));
+    mem_pool.notify_new_tip(tip_block_hash).unwrap();
+    let events = mem_pool.drain_events();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        MemPoolEvent::TransactionDropped { tx_hash, reason } => {
+            assert_eq!(*tx_hash, H256::from(tx.hash()));
+            assert_eq!(reason, "expired");
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+    assert_eq!(mem_pool.stats().txs, 0);
+}
@@ -0,0 +1,73 @@
+use crate::testing_tool::chain::{
+    apply_block_result, construct_block, setup_chain, ALWAYS_SUCCESS_CODE_HASH,
+};
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State, CKB_SUDT_SCRIPT_ARGS, H256};
+use gw_store::state_db::{StateDBTransaction, StateDBVersion};
+use gw_types::{
+    packed::{CellOutput, DepositionRequest, Script},
+    prelude::*,
+};
+
+#[test]
+fn test_dev_deposit() {
+    let rollup_type_script = Script::default();
+    let mut chain = setup_chain(rollup_type_script.clone(), Default::default());
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    let capacity = 500_00000000u64;
+    let user_script = Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .args(vec![42].pack())
+        .build();
+    let user_script_hash = user_script.hash();
+    let deposit = DepositionRequest::new_builder()
+        .capacity(capacity.pack())
+        .sudt_script_hash(CKB_SUDT_SCRIPT_ARGS.pack())
+        .script(user_script)
+        .build();
+
+    // synthetic deposits are only accepted in dev mode
+    {
+        let mut mem_pool = chain.mem_pool().lock();
+        assert!(mem_pool.push_dev_deposit(deposit.clone()).is_err());
+        assert!(!mem_pool.is_dev_mode());
+        mem_pool.enable_dev_mode();
+        mem_pool.push_dev_deposit(deposit.clone()).unwrap();
+    }
+
+    // the block producer takes the queued deposits and keeps them for the chain updater
+    let block_result = {
+        let mut mem_pool = chain.mem_pool().lock();
+        let dev_deposits = mem_pool.dev_deposits().unwrap().take_queued();
+        assert_eq!(dev_deposits.len(), 1);
+        assert_eq!(dev_deposits[0].as_slice(), deposit.as_slice());
+        let block_result = construct_block(&chain, &mem_pool, dev_deposits.clone()).unwrap();
+        let dev_deposits_of_block = mem_pool.dev_deposits().unwrap();
+        dev_deposits_of_block.insert_produced(block_result.block.hash().into(), dev_deposits);
+        assert!(dev_deposits_of_block.take_queued().is_empty());
+        block_result
+    };
+
+    // the chain updater applies them again when the block is synced
+    let block_hash: H256 = block_result.block.hash().into();
+    let deposition_requests = {
+        let mut mem_pool = chain.mem_pool().lock();
+        mem_pool.dev_deposits().unwrap().take_produced(&block_hash)
+    };
+    assert_eq!(deposition_requests.len(), 1);
+    apply_block_result(&mut chain, rollup_cell, block_result, deposition_requests);
+
+    let db = chain.store().begin_transaction();
+    let state_db =
+        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(block_hash)).unwrap();
+    let tree = state_db.account_state_tree().unwrap();
+    let user_id = tree
+        .get_account_id_by_script_hash(&user_script_hash.into())
+        .unwrap()
+        .expect("account exists");
+    assert_eq!(
+        tree.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, user_id).unwrap(),
+        capacity as u128
+    );
+}
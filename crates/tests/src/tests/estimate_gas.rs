@@ -0,0 +1,51 @@
+use gw_types::{
+    packed::{BlockInfo, L2Transaction},
+    prelude::*,
+};
+
+use crate::testing_tool::accounts::{build_transfer, setup_accounts};
+
+#[test]
+fn test_estimate_gas() {
+    let (chain, _rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let snapshot = chain.mem_pool().lock().snapshot();
+    let block_info = BlockInfo::default();
+    let build_tx = |gas_limit: u64| {
+        let raw = build_transfer(user_id, receiver_id, 0)
+            .as_builder()
+            .gas_limit(gas_limit.pack())
+            .build();
+        L2Transaction::new_builder().raw(raw).build()
+    };
+
+    // gas limit 0 means the max gas limit
+    let gas = snapshot
+        .estimate_gas(build_tx(0), &block_info, None)
+        .unwrap();
+    assert!(gas > 0);
+    let used_cycles = snapshot
+        .execute_transaction(build_tx(0), &block_info)
+        .unwrap()
+        .used_cycles;
+    assert_eq!(gas, used_cycles);
+
+    // the estimation is the lowest gas limit the tx succeeds with
+    assert_eq!(
+        snapshot
+            .estimate_gas(build_tx(gas * 10), &block_info, None)
+            .unwrap(),
+        gas
+    );
+    snapshot
+        .execute_transaction(build_tx(gas), &block_info)
+        .unwrap();
+    assert!(snapshot
+        .execute_transaction(build_tx(gas - 1), &block_info)
+        .is_err());
+
+    // the tx fails with its own gas limit
+    assert!(snapshot
+        .estimate_gas(build_tx(gas - 1), &block_info, None)
+        .is_err());
+}
@@ -0,0 +1,189 @@
+use gw_events::{
+    ChallengeStarted, ChallengeTargetType, Event, NewBlock, TxIncluded, WithdrawalDropped,
+    WithdrawalFinalized,
+};
+use gw_rpc_server::subscription::{challenge_started, finalized_withdrawals};
+use gw_types::{
+    core::{ChallengeTargetType as PackedChallengeTargetType, RollupActionType},
+    packed::{
+        ChallengeTarget, L2Block, RawL2Block, RawWithdrawalRequest, RollupActionRecord,
+        WithdrawalRequest, WithdrawalRequestVec,
+    },
+    prelude::*,
+};
+use serde_json::{json, Value};
+
+fn hex_hash(byte: u8) -> String {
+    format!("0x{}", hex::encode([byte; 32]))
+}
+
+fn assert_schema(event: Event, expected: Value) {
+    assert_eq!(serde_json::to_value(&event).unwrap(), expected);
+    let decoded: Event = serde_json::from_value(expected).unwrap();
+    assert_eq!(decoded, event);
+}
+
+#[test]
+fn test_new_block_schema() {
+    let event = Event::NewBlock(NewBlock {
+        block_number: 1u64.into(),
+        block_hash: [1u8; 32].into(),
+    });
+    assert_schema(
+        event,
+        json!({
+            "type": "new_block",
+            "payload": {"block_number": "0x1", "block_hash": hex_hash(1)},
+        }),
+    );
+}
+
+#[test]
+fn test_tx_included_schema() {
+    let event = Event::TxIncluded(TxIncluded {
+        tx_hash: [1u8; 32].into(),
+        block_number: 2u64.into(),
+        block_hash: [3u8; 32].into(),
+        tx_index: 4u32.into(),
+    });
+    assert_schema(
+        event,
+        json!({
+            "type": "tx_included",
+            "payload": {
+                "tx_hash": hex_hash(1),
+                "block_number": "0x2",
+                "block_hash": hex_hash(3),
+                "tx_index": "0x4",
+            },
+        }),
+    );
+}
+
+#[test]
+fn test_withdrawal_finalized_schema() {
+    let event = Event::WithdrawalFinalized(WithdrawalFinalized {
+        withdrawal_hash: [1u8; 32].into(),
+        block_number: 2u64.into(),
+        block_hash: [3u8; 32].into(),
+        account_script_hash: [4u8; 32].into(),
+        capacity: 5u64.into(),
+        sudt_script_hash: [6u8; 32].into(),
+        amount: 7u128.into(),
+    });
+    assert_schema(
+        event,
+        json!({
+            "type": "withdrawal_finalized",
+            "payload": {
+                "withdrawal_hash": hex_hash(1),
+                "block_number": "0x2",
+                "block_hash": hex_hash(3),
+                "account_script_hash": hex_hash(4),
+                "capacity": "0x5",
+                "sudt_script_hash": hex_hash(6),
+                "amount": "0x7",
+            },
+        }),
+    );
+}
+
+#[test]
+fn test_challenge_started_schema() {
+    let event = Event::ChallengeStarted(ChallengeStarted {
+        block_hash: [1u8; 32].into(),
+        target_type: ChallengeTargetType::Withdrawal,
+        target_index: 2u32.into(),
+        l1_transaction_hash: [3u8; 32].into(),
+    });
+    assert_schema(
+        event,
+        json!({
+            "type": "challenge_started",
+            "payload": {
+                "block_hash": hex_hash(1),
+                "target_type": "withdrawal",
+                "target_index": "0x2",
+                "l1_transaction_hash": hex_hash(3),
+            },
+        }),
+    );
+}
+
+#[test]
+fn test_withdrawal_dropped_schema() {
+    let event = Event::WithdrawalDropped(WithdrawalDropped {
+        withdrawal_hash: [1u8; 32].into(),
+        reason: "insufficient balance".to_string(),
+    });
+    assert_schema(
+        event,
+        json!({
+            "type": "withdrawal_dropped",
+            "payload": {"withdrawal_hash": hex_hash(1), "reason": "insufficient balance"},
+        }),
+    );
+}
+
+#[test]
+fn test_finalized_withdrawals() {
+    let withdrawal = WithdrawalRequest::new_builder()
+        .raw(
+            RawWithdrawalRequest::new_builder()
+                .capacity(100u64.pack())
+                .amount(42u128.pack())
+                .sudt_script_hash([1u8; 32].pack())
+                .account_script_hash([2u8; 32].pack())
+                .build(),
+        )
+        .build();
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(7u64.pack()).build())
+        .withdrawals(
+            WithdrawalRequestVec::new_builder()
+                .push(withdrawal.clone())
+                .build(),
+        )
+        .build();
+
+    let expected = Event::WithdrawalFinalized(WithdrawalFinalized {
+        withdrawal_hash: withdrawal.hash().into(),
+        block_number: 7u64.into(),
+        block_hash: block.hash().into(),
+        account_script_hash: [2u8; 32].into(),
+        capacity: 100u64.into(),
+        sudt_script_hash: [1u8; 32].into(),
+        amount: 42u128.into(),
+    });
+    assert_eq!(finalized_withdrawals(&block), vec![expected]);
+}
+
+#[test]
+fn test_challenge_started() {
+    let target = ChallengeTarget::new_builder()
+        .block_hash([1u8; 32].pack())
+        .target_index(2u32.pack())
+        .target_type(PackedChallengeTargetType::Transaction.into())
+        .build();
+    let record = RollupActionRecord::new_builder()
+        .action_type(RollupActionType::EnterChallenge.into())
+        .challenge_target(target)
+        .l1_block_number(3u64.pack())
+        .l1_tx_hash([4u8; 32].pack())
+        .build();
+
+    let expected = Event::ChallengeStarted(ChallengeStarted {
+        block_hash: [1u8; 32].into(),
+        target_type: ChallengeTargetType::Transaction,
+        target_index: 2u32.into(),
+        l1_transaction_hash: [4u8; 32].into(),
+    });
+    assert_eq!(challenge_started(&record), Some(expected));
+
+    // cancelling or reverting a challenge doesn't start one
+    let cancel = record
+        .as_builder()
+        .action_type(RollupActionType::CancelChallenge.into())
+        .build();
+    assert_eq!(challenge_started(&cancel), None);
+}
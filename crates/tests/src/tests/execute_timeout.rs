@@ -0,0 +1,37 @@
+use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
+use gw_types::{
+    packed::{BlockInfo, L2Transaction, RawL2Transaction, SUDTArgs, SUDTQuery},
+    prelude::*,
+};
+use std::time::{Duration, Instant};
+
+use crate::testing_tool::accounts::setup_accounts;
+
+#[test]
+fn test_execute_timeout() {
+    let (chain, _rollup_cell, account_ids) = setup_accounts(Default::default(), 1);
+    let user_id = account_ids[0];
+
+    let args = SUDTArgs::new_builder()
+        .set(SUDTQuery::new_builder().account_id(user_id.pack()).build())
+        .build();
+    let raw = RawL2Transaction::new_builder()
+        .from_id(user_id.pack())
+        .to_id(CKB_SUDT_ACCOUNT_ID.pack())
+        .args(args.as_bytes().pack())
+        .build();
+    let tx = L2Transaction::new_builder().raw(raw).build();
+    let snapshot = chain.mem_pool().lock().snapshot();
+
+    // the VM is stopped at the first syscall after the deadline
+    let err = snapshot
+        .execute_transaction_with_deadline(tx.clone(), &BlockInfo::default(), Some(Instant::now()))
+        .expect_err("timeout");
+    assert!(err.to_string().contains("timeout"), "{}", err);
+
+    // the timeout isn't cached
+    let deadline = Instant::now() + Duration::from_secs(60);
+    snapshot
+        .execute_transaction_with_deadline(tx, &BlockInfo::default(), Some(deadline))
+        .expect("execute");
+}
@@ -0,0 +1,76 @@
+use gw_chain::chain::{extract_l2_block, ExtractError};
+use gw_types::{
+    bytes::Bytes,
+    packed::{CellOutput, L2Block, RawL2Block, RawTransaction, Script, Transaction, WitnessArgs},
+    prelude::*,
+};
+
+fn build_tx(rollup_cells: Vec<CellOutput>, witnesses: Vec<Bytes>) -> Transaction {
+    let outputs_data: Vec<Bytes> = rollup_cells.iter().map(|_| Bytes::new()).collect();
+    let raw = RawTransaction::new_builder()
+        .outputs(rollup_cells.pack())
+        .outputs_data(outputs_data.pack())
+        .build();
+    Transaction::new_builder()
+        .raw(raw)
+        .witnesses(witnesses.pack())
+        .build()
+}
+
+fn rollup_witness(block: &L2Block) -> Bytes {
+    WitnessArgs::new_builder()
+        .output_type(Pack::<_>::pack(&Some(block.as_bytes())))
+        .build()
+        .as_bytes()
+}
+
+#[test]
+fn test_extract_l2_block() {
+    let rollup_type_script = Script::new_builder().args(vec![42].pack()).build();
+    let rollup_id = rollup_type_script.hash();
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(3u64.pack()).build())
+        .build();
+    let lock_witness = WitnessArgs::new_builder()
+        .lock(Pack::<_>::pack(&Some(Bytes::from(vec![1u8; 65]))))
+        .build()
+        .as_bytes();
+
+    // the action is at the index of the rollup cell
+    let tx = build_tx(vec![rollup_cell.clone()], vec![rollup_witness(&block)]);
+    assert_eq!(extract_l2_block(&tx, &rollup_id).unwrap(), block);
+
+    // the action is in another witness
+    let tx = build_tx(
+        vec![rollup_cell.clone()],
+        vec![lock_witness.clone(), rollup_witness(&block)],
+    );
+    assert_eq!(extract_l2_block(&tx, &rollup_id).unwrap(), block);
+
+    // errors carry the L1 tx hash
+    let tx = build_tx(vec![rollup_cell.clone()], vec![lock_witness.clone()]);
+    let err = extract_l2_block(&tx, &rollup_id).unwrap_err();
+    assert_eq!(err.error, ExtractError::NoRollupAction);
+    assert_eq!(err.tx_hash.0, tx.hash());
+
+    let tx = build_tx(
+        vec![rollup_cell.clone()],
+        vec![lock_witness, rollup_witness(&block), rollup_witness(&block)],
+    );
+    let err = extract_l2_block(&tx, &rollup_id).unwrap_err();
+    assert_eq!(err.error, ExtractError::MultipleRollupActions(vec![1, 2]));
+
+    let tx = build_tx(vec![CellOutput::default()], vec![rollup_witness(&block)]);
+    let err = extract_l2_block(&tx, &rollup_id).unwrap_err();
+    assert_eq!(err.error, ExtractError::NoRollupCell);
+
+    let tx = build_tx(
+        vec![rollup_cell.clone(), rollup_cell],
+        vec![rollup_witness(&block)],
+    );
+    let err = extract_l2_block(&tx, &rollup_id).unwrap_err();
+    assert_eq!(err.error, ExtractError::MultipleRollupCells(vec![0, 1]));
+}
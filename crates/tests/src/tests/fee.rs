@@ -0,0 +1,230 @@
+use gw_block_producer::produce_block::{produce_block, ProduceBlockParam};
+use gw_common::{
+    builtins::CKB_SUDT_ACCOUNT_ID,
+    state::{State, GW_LOG_TX_FAILED},
+};
+use gw_store::state_db::{StateDBTransaction, StateDBVersion};
+use gw_types::{
+    packed::{L2Transaction, RawL2Transaction, SUDTArgs, SUDTTransfer},
+    prelude::*,
+};
+
+use crate::testing_tool::accounts::{build_transfer, setup_accounts, ACCOUNT_CAPACITY};
+use crate::testing_tool::chain::{apply_block_result, construct_block};
+
+#[test]
+fn test_fee_settlement() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let get_balance = |chain: &gw_chain::chain::Chain, account_id: u32| {
+        let db = chain.store().begin_transaction();
+        let tip_block_hash = chain.store().get_tip_block_hash().unwrap();
+        let state_db =
+            StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_block_hash))
+                .unwrap();
+        let tree = state_db.account_state_tree().unwrap();
+        tree.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, account_id)
+            .unwrap()
+    };
+    // construct_block uses account 0 as the block producer
+    let block_producer_id = 0;
+    let producer_balance = get_balance(&chain, block_producer_id);
+
+    // transfer with gas
+    let gas_limit = 100_000_000u64;
+    let gas_price = 2u128;
+    let max_fee = gas_limit as u128 * gas_price;
+    let transfer_amount = 1u128;
+    let args = SUDTArgs::new_builder()
+        .set(
+            SUDTTransfer::new_builder()
+                .to(receiver_id.pack())
+                .amount(transfer_amount.pack())
+                .build(),
+        )
+        .build();
+    let raw = RawL2Transaction::new_builder()
+        .from_id(user_id.pack())
+        .to_id(CKB_SUDT_ACCOUNT_ID.pack())
+        .nonce(0u32.pack())
+        .args(args.as_bytes().pack())
+        .gas_limit(gas_limit.pack())
+        .gas_price(gas_price.pack())
+        .build();
+    let tx = L2Transaction::new_builder().raw(raw).build();
+    chain
+        .mem_pool()
+        .lock()
+        .push_transaction(tx.clone())
+        .unwrap();
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 1);
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+
+    // check receipt
+    let receipt = chain
+        .store()
+        .begin_transaction()
+        .get_transaction_receipt(&tx.hash().into())
+        .unwrap()
+        .expect("receipt");
+    let used_cycles: u64 = receipt.used_cycles().unpack();
+    let paid_fee: u128 = receipt.paid_fee().unpack();
+    let refunded_fee: u128 = receipt.refunded_fee().unpack();
+    assert!(used_cycles > 0 && used_cycles <= gas_limit);
+    assert_eq!(paid_fee, used_cycles as u128 * gas_price);
+    assert_eq!(paid_fee + refunded_fee, max_fee);
+
+    // check block economics
+    let economics = {
+        let db = chain.store().begin_transaction();
+        let tip_block_hash = db.get_tip_block_hash().unwrap();
+        db.get_block_economics(&tip_block_hash)
+            .unwrap()
+            .expect("economics")
+    };
+    let block_producer: u32 = economics.block_producer_id().unpack();
+    let tx_count: u32 = economics.tx_count().unpack();
+    let paid_fees: u128 = economics.paid_fees().unpack();
+    let refunded_fees: u128 = economics.refunded_fees().unpack();
+    assert_eq!(block_producer, block_producer_id);
+    assert_eq!(tx_count, 1);
+    assert_eq!(paid_fees, paid_fee);
+    assert_eq!(refunded_fees, refunded_fee);
+
+    // check balances
+    assert_eq!(
+        get_balance(&chain, user_id),
+        ACCOUNT_CAPACITY as u128 - transfer_amount - paid_fee
+    );
+    assert_eq!(
+        get_balance(&chain, receiver_id),
+        ACCOUNT_CAPACITY as u128 + transfer_amount
+    );
+    assert_eq!(
+        get_balance(&chain, block_producer_id),
+        producer_balance + paid_fee
+    );
+}
+
+#[test]
+fn test_insufficient_fee() {
+    let (chain, _rollup_cell, account_ids) = setup_accounts(Default::default(), 1);
+    let user_id = account_ids[0];
+
+    // the max fee exceeds the balance
+    let args = SUDTArgs::new_builder()
+        .set(
+            SUDTTransfer::new_builder()
+                .to(user_id.pack())
+                .amount(1u128.pack())
+                .build(),
+        )
+        .build();
+    let raw = RawL2Transaction::new_builder()
+        .from_id(user_id.pack())
+        .to_id(CKB_SUDT_ACCOUNT_ID.pack())
+        .nonce(0u32.pack())
+        .args(args.as_bytes().pack())
+        .gas_limit(ACCOUNT_CAPACITY.pack())
+        .gas_price(2u128.pack())
+        .build();
+    let tx = L2Transaction::new_builder().raw(raw).build();
+    let err = chain
+        .mem_pool()
+        .lock()
+        .push_transaction(tx)
+        .expect_err("insufficient fee");
+    assert!(err.to_string().contains("max fee"), "{}", err);
+}
+
+#[test]
+fn test_failed_tx_is_charged() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let get_account = |chain: &gw_chain::chain::Chain, account_id: u32| {
+        let db = chain.store().begin_transaction();
+        let tip_block_hash = chain.store().get_tip_block_hash().unwrap();
+        let state_db =
+            StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_block_hash))
+                .unwrap();
+        let tree = state_db.account_state_tree().unwrap();
+        let balance = tree
+            .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, account_id)
+            .unwrap();
+        (balance, tree.get_nonce(account_id).unwrap())
+    };
+    let (user_balance, _) = get_account(&chain, user_id);
+    let (receiver_balance, _) = get_account(&chain, receiver_id);
+
+    // the transfer exceeds the balance, so the backend fails
+    let gas_limit = 100_000_000u64;
+    let gas_price = 2u128;
+    let max_fee = gas_limit as u128 * gas_price;
+    let args = SUDTArgs::new_builder()
+        .set(
+            SUDTTransfer::new_builder()
+                .to(receiver_id.pack())
+                .amount((user_balance * 2).pack())
+                .build(),
+        )
+        .build();
+    let raw = build_transfer(user_id, receiver_id, 0)
+        .as_builder()
+        .args(args.as_bytes().pack())
+        .gas_limit(gas_limit.pack())
+        .gas_price(gas_price.pack())
+        .build();
+    let tx = L2Transaction::new_builder().raw(raw).build();
+
+    // the mem-pool rejects it, but a producer still packages it and charges the gas limit
+    assert!(chain
+        .mem_pool()
+        .lock()
+        .push_transaction(tx.clone())
+        .is_err());
+    let block_result = {
+        let db = chain.store().begin_transaction();
+        let parent_block = chain.store().get_tip_block().unwrap();
+        let rollup_config_hash = chain.rollup_config_hash().clone().into();
+        let param = ProduceBlockParam {
+            db,
+            generator: chain.generator(),
+            block_producer_id: 0,
+            timestamp: 0,
+            txs: vec![tx.clone()],
+            deposition_requests: Vec::new(),
+            withdrawal_requests: Vec::new(),
+            parent_block: &parent_block,
+            rollup_config_hash: &rollup_config_hash,
+            max_withdrawal_capacity: std::u128::MAX,
+        };
+        produce_block(param).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 1);
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+
+    let receipt = chain
+        .store()
+        .begin_transaction()
+        .get_transaction_receipt(&tx.hash().into())
+        .unwrap()
+        .expect("receipt");
+    let used_cycles: u64 = receipt.used_cycles().unpack();
+    let paid_fee: u128 = receipt.paid_fee().unpack();
+    let refunded_fee: u128 = receipt.refunded_fee().unpack();
+    assert_eq!(used_cycles, gas_limit);
+    assert_eq!(paid_fee, max_fee);
+    assert_eq!(refunded_fee, 0);
+    let logs: Vec<_> = receipt.logs().into_iter().collect();
+    assert_eq!(logs.len(), 1);
+    let service_flag: u8 = logs[0].service_flag().into();
+    assert_eq!(service_flag, GW_LOG_TX_FAILED);
+
+    // only the fee is paid and the nonce is increased
+    assert_eq!(get_account(&chain, user_id), (user_balance - max_fee, 1));
+    assert_eq!(get_account(&chain, receiver_id), (receiver_balance, 0));
+}
@@ -0,0 +1,115 @@
+use gw_common::H256;
+use gw_types::{
+    packed::{BlockInfo, L2Transaction},
+    prelude::*,
+};
+use serde_json::json;
+
+use crate::testing_tool::accounts::{build_transfer, setup_accounts};
+use crate::testing_tool::chain::{apply_block_result, construct_block};
+use crate::testing_tool::rpc::{build_rpc_server, call_ok};
+
+#[test]
+fn test_execute_at_historical_block() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let parent_block_hash: H256 = chain.store().get_tip_block_hash().unwrap();
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(user_id, receiver_id, 0))
+        .build();
+
+    // commit the tx in a block
+    chain
+        .mem_pool()
+        .lock()
+        .push_transaction(tx.clone())
+        .unwrap();
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 1);
+    let block_hash: H256 = block_result.block.hash().into();
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+
+    // the tx is replayed on the state it ran on
+    let snapshot = chain.mem_pool().lock().snapshot();
+    let block_info = BlockInfo::default();
+    snapshot
+        .clone()
+        .at_block(parent_block_hash)
+        .execute_transaction(tx.clone(), &block_info)
+        .unwrap();
+
+    // the nonce is used on the state of the block
+    assert!(snapshot
+        .at_block(block_hash)
+        .execute_transaction(tx, &block_info)
+        .is_err());
+}
+
+#[test]
+fn test_execute_at_transaction_position() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let txs: Vec<L2Transaction> = (0..3)
+        .map(|nonce| {
+            L2Transaction::new_builder()
+                .raw(build_transfer(user_id, receiver_id, nonce))
+                .build()
+        })
+        .collect();
+    for tx in &txs {
+        chain
+            .mem_pool()
+            .lock()
+            .push_transaction(tx.clone())
+            .unwrap();
+    }
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 3);
+    let block_hash: H256 = block_result.block.hash().into();
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+
+    // each tx runs after the previous txs of the block
+    let snapshot = chain.mem_pool().lock().snapshot();
+    let block_info = BlockInfo::default();
+    for (tx_index, tx) in txs.iter().enumerate() {
+        snapshot
+            .clone()
+            .at_transaction(block_hash, tx_index as u32)
+            .unwrap()
+            .execute_transaction(tx.clone(), &block_info)
+            .unwrap();
+    }
+    // the nonce of the second tx isn't used before the first tx
+    assert!(snapshot
+        .clone()
+        .at_transaction(block_hash, 0)
+        .unwrap()
+        .execute_transaction(txs[1].clone(), &block_info)
+        .is_err());
+    assert!(snapshot.at_transaction(block_hash, 3).is_err());
+
+    // the RPC executes a tx of the block at its position
+    let server = build_rpc_server(&chain, Default::default());
+    let block = json!({ "block_hash": format!("0x{}", hex::encode(block_hash.as_slice())) });
+    let serialize = |tx: &L2Transaction| format!("0x{}", hex::encode(tx.as_slice()));
+    call_ok(
+        &server,
+        "execute_l2transaction",
+        json!([serialize(&txs[1]), block]),
+    );
+    // a tx which isn't in the block is executed after the block
+    let next_tx = L2Transaction::new_builder()
+        .raw(build_transfer(user_id, receiver_id, 3))
+        .build();
+    call_ok(
+        &server,
+        "execute_l2transaction",
+        json!([serialize(&next_tx), block]),
+    );
+}
@@ -0,0 +1,99 @@
+use gw_common::H256;
+use gw_config::MemPoolLimitsConfig;
+use gw_mem_pool::pool::MemPoolEvent;
+use gw_types::{packed::L2Transaction, prelude::*};
+
+use crate::testing_tool::accounts::{build_transfer, setup_accounts, with_gas_price};
+
+#[test]
+fn test_mem_pool_limits() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 4);
+    let receiver_id = account_ids[3];
+    let mut mem_pool = chain.mem_pool().lock();
+    mem_pool.set_limits(MemPoolLimitsConfig {
+        max_txs: 3,
+        max_txs_per_account: 2,
+        ..Default::default()
+    });
+    let transfer = |from_id: u32, nonce: u32, gas_price: u128| {
+        with_gas_price(build_transfer(from_id, receiver_id, nonce), gas_price)
+    };
+
+    // per-account cap
+    mem_pool
+        .push_transaction(transfer(account_ids[0], 0, 10))
+        .unwrap();
+    let low_price_tx = transfer(account_ids[0], 1, 10);
+    mem_pool.push_transaction(low_price_tx.clone()).unwrap();
+    let err = mem_pool
+        .push_transaction(transfer(account_ids[0], 2, 10))
+        .unwrap_err();
+    assert!(err.to_string().contains("too many txs"), "{}", err);
+    mem_pool
+        .push_transaction(transfer(account_ids[1], 0, 20))
+        .unwrap();
+
+    // the pool is full, a tx paying the lowest gas price is rejected before it's executed,
+    // its nonce isn't checked
+    let err = mem_pool
+        .push_transaction(transfer(account_ids[2], 9, 5))
+        .unwrap_err();
+    assert!(err.to_string().contains("mem-pool is full"), "{}", err);
+    assert!(mem_pool.drain_events().is_empty());
+
+    // otherwise the lowest gas price tx is evicted
+    mem_pool
+        .push_transaction(transfer(account_ids[2], 0, 30))
+        .unwrap();
+    let events = mem_pool.drain_events();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        MemPoolEvent::TransactionDropped { tx_hash, .. } => {
+            assert_eq!(*tx_hash, H256::from(low_price_tx.hash()));
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+
+    let stats = mem_pool.stats();
+    assert_eq!(stats.txs, 3);
+    assert_eq!(stats.pending_accounts, 3);
+    assert_eq!(stats.rejections.get("account_limit"), Some(&1));
+    assert_eq!(stats.rejections.get("pool_full"), Some(&1));
+    let (txs, _) = mem_pool.package();
+    assert_eq!(txs.len(), 3);
+}
+
+#[test]
+fn test_replacement_is_not_evicted() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 3);
+    let receiver_id = account_ids[2];
+    let tx = with_gas_price(build_transfer(account_ids[0], receiver_id, 0), 10);
+    let other_tx = with_gas_price(build_transfer(account_ids[1], receiver_id, 0), 100);
+    let mut mem_pool = chain.mem_pool().lock();
+    mem_pool.set_limits(MemPoolLimitsConfig {
+        max_tx_bytes: tx.as_slice().len() + other_tx.as_slice().len(),
+        ..Default::default()
+    });
+    mem_pool.push_transaction(tx).unwrap();
+    mem_pool.push_transaction(other_tx.clone()).unwrap();
+
+    // the replacement is larger, the pool exceeds the max bytes with it
+    let replacement_tx: L2Transaction =
+        with_gas_price(build_transfer(account_ids[0], receiver_id, 0), 11)
+            .as_builder()
+            .signature(vec![0u8; 100].pack())
+            .build();
+    mem_pool.push_transaction(replacement_tx.clone()).unwrap();
+    let evicted: Vec<H256> = mem_pool
+        .drain_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            MemPoolEvent::TransactionDropped { tx_hash, .. } => Some(tx_hash),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(evicted, vec![H256::from(other_tx.hash())]);
+    let (txs, _) = mem_pool.package();
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].hash(), replacement_tx.hash());
+}
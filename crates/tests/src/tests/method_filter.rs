@@ -0,0 +1,99 @@
+use gw_config::RPCServerConfig;
+use gw_rpc_server::error::MAINTENANCE_MODE_CODE;
+use gw_rpc_server::method_filter::{is_disabled, MaintenanceMode, MethodFilter, RequestBody};
+use serde_json::{json, Value};
+
+use crate::testing_tool::{
+    accounts::setup_accounts,
+    rpc::{build_registry, call, call_ok},
+};
+
+const METHOD_NOT_FOUND_CODE: i64 = -32601;
+
+fn check(filter: &MethodFilter, body: Value) -> Option<Value> {
+    let body = RequestBody::parse(body.to_string().as_bytes()).expect("parse body");
+    filter.check(&body)
+}
+
+#[test]
+fn test_disabled_methods() {
+    let patterns = vec!["debug_*".to_string(), "get_block".to_string()];
+    assert!(is_disabled(&patterns, "debug_trace"));
+    assert!(is_disabled(&patterns, "get_block"));
+    assert!(!is_disabled(&patterns, "get_block_hash"));
+
+    let filter = MethodFilter::new(patterns, MaintenanceMode::default());
+    let response = check(&filter, json!({ "id": 7, "method": "debug_trace" })).unwrap();
+    assert_eq!(response["id"], json!(7));
+    assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND_CODE));
+    assert!(check(&filter, json!({ "id": 7, "method": "get_block_hash" })).is_none());
+
+    // a batch calling a disabled method is rejected as a whole
+    let batch = json!([
+        { "id": 1, "method": "ping" },
+        { "id": 2, "method": "get_block" },
+    ]);
+    let response = check(&filter, batch).unwrap();
+    assert_eq!(response["id"], Value::Null);
+    assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND_CODE));
+}
+
+#[test]
+fn test_maintenance_mode() {
+    let maintenance_mode = MaintenanceMode::new(true);
+    let filter = MethodFilter::new(Vec::new(), maintenance_mode.clone());
+    let response = check(
+        &filter,
+        json!({ "id": 1, "method": "submit_l2transaction" }),
+    )
+    .unwrap();
+    assert_eq!(response["error"]["code"], json!(MAINTENANCE_MODE_CODE));
+    assert!(check(&filter, json!({ "id": 1, "method": "get_balance" })).is_none());
+
+    maintenance_mode.set(false);
+    assert!(check(
+        &filter,
+        json!({ "id": 1, "method": "submit_l2transaction" })
+    )
+    .is_none());
+}
+
+#[test]
+fn test_request_body() {
+    let body = RequestBody::parse(br#"[{"id": 1}, {"id": 2}]"#).unwrap();
+    assert_eq!(body.calls(), 2);
+    let body = RequestBody::parse(br#"{"id": 1, "method": "ping"}"#).unwrap();
+    assert_eq!(body.calls(), 1);
+    // an unparsable body is reported by the JSONRPC server
+    assert!(RequestBody::parse(b"[{").is_none());
+}
+
+#[test]
+fn test_maintenance_mode_is_an_admin_method() {
+    let (chain, _, _) = setup_accounts(Default::default(), 1);
+    let registry = build_registry(&chain, RPCServerConfig::default());
+    let filter = MethodFilter::new(Vec::new(), registry.maintenance_mode().clone());
+    let admin_server = registry
+        .build_admin_rpc_server()
+        .expect("build admin server");
+    let rpc_server = registry.build_rpc_server().expect("build rpc server");
+
+    let response = call(&rpc_server, "gw_admin_set_maintenance_mode", json!([true]));
+    assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND_CODE));
+    assert!(check(
+        &filter,
+        json!({ "id": 1, "method": "submit_l2transaction" })
+    )
+    .is_none());
+
+    call_ok(
+        &admin_server,
+        "gw_admin_set_maintenance_mode",
+        json!([true]),
+    );
+    assert!(check(
+        &filter,
+        json!({ "id": 1, "method": "submit_l2transaction" })
+    )
+    .is_some());
+}
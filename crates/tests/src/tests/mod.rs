@@ -1,2 +1,30 @@
+mod backends;
+mod balances_proof;
+mod block_limits;
+mod block_replay;
+mod block_views;
+mod challenge_history;
+mod concurrency;
 mod deposition_withdrawal;
+mod dev_clock;
+mod dev_deposit;
+mod estimate_gas;
+mod events;
+mod execute_timeout;
+mod extract_l2_block;
+mod fee;
+mod historical_execution;
+mod mem_pool_limits;
+mod method_filter;
+mod pending_state;
+mod rate_limit;
+mod replace_by_fee;
+mod rpc_errors;
+mod signature;
+mod state_overrides;
+mod state_roots;
+mod sudt_query;
 mod sync;
+mod sync_progress;
+mod trace;
+mod transaction_receipt;
@@ -0,0 +1,124 @@
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State};
+use gw_config::MemPoolLimitsConfig;
+use gw_mem_pool::pool::MemPool;
+use gw_store::{overlay_state::OverlayState, state_db::StateDBTransaction};
+use gw_types::{packed::L2Transaction, prelude::*};
+
+use crate::testing_tool::accounts::{
+    build_transfer, build_transfer_amount, setup_accounts, with_gas_price, ACCOUNT_CAPACITY,
+};
+use crate::testing_tool::chain::{apply_block_result, construct_block};
+
+#[test]
+fn test_pending_state() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let capacity = ACCOUNT_CAPACITY as u128;
+    // (nonce, balance) of the account in the pending state
+    let get_pending = |chain: &gw_chain::chain::Chain, account_id: u32| {
+        let (version, changes) = chain.mem_pool().lock().pending_state();
+        let db = chain.store().begin_transaction();
+        let state_db = StateDBTransaction::from_version(&db, version).unwrap();
+        let tree = state_db.account_state_tree().unwrap();
+        let state = OverlayState::new(&tree, changes);
+        (
+            state.get_nonce(account_id).unwrap(),
+            state
+                .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, account_id)
+                .unwrap(),
+        )
+    };
+    assert_eq!(get_pending(&chain, user_id), (0, capacity));
+
+    // the second tx uses the nonce left by the first one
+    for nonce in 0..2 {
+        let tx = L2Transaction::new_builder()
+            .raw(build_transfer_amount(user_id, receiver_id, nonce, 1))
+            .build();
+        chain.mem_pool().lock().push_transaction(tx).unwrap();
+    }
+    assert_eq!(get_pending(&chain, user_id), (2, capacity - 2));
+    assert_eq!(get_pending(&chain, receiver_id), (0, capacity + 2));
+
+    // the nonce is verified against the pending state
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer_amount(user_id, receiver_id, 0, 1))
+        .build();
+    assert!(chain.mem_pool().lock().push_transaction(tx).is_err());
+
+    // the pending state is recomputed on the new tip
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 2);
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+    assert!(chain.mem_pool().lock().pending().is_empty());
+    assert_eq!(get_pending(&chain, user_id), (2, capacity - 2));
+    let (_version, changes) = chain.mem_pool().lock().pending_state();
+    assert!(changes.is_empty());
+}
+
+#[test]
+fn test_pending_state_of_concurrent_pushes_and_evictions() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 5);
+    let receiver_id = account_ids[4];
+    let transfer = |from_id: u32, nonce: u32, gas_price: u128| {
+        with_gas_price(build_transfer(from_id, receiver_id, nonce), gas_price)
+    };
+    // the incrementally updated pending state equals the one recomputed from scratch
+    let assert_recomputed = || {
+        let mut mem_pool = chain.mem_pool().lock();
+        let (_version, changes) = mem_pool.pending_state();
+        let tip_block_hash = chain.store().get_tip_block_hash().unwrap();
+        mem_pool.notify_new_tip(tip_block_hash).unwrap();
+        let (_version, recomputed_changes) = mem_pool.pending_state();
+        assert!(!changes.is_empty());
+        assert_eq!(changes.kv, recomputed_changes.kv);
+    };
+
+    // the txs of 4 accounts are executed concurrently without the mem-pool lock
+    let handles: Vec<_> = account_ids[..4]
+        .iter()
+        .enumerate()
+        .map(|(i, &from_id)| {
+            let mem_pool = chain.mem_pool().clone();
+            let txs: Vec<_> = (0..2)
+                .map(|nonce| transfer(from_id, nonce, 10 + i as u128))
+                .collect();
+            std::thread::spawn(move || {
+                for tx in txs {
+                    MemPool::push_transaction_with_lock(&mem_pool, tx).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(chain.mem_pool().lock().stats().txs, 8);
+    assert_recomputed();
+
+    // the evicted tx is removed from the pending state, the later txs are executed again
+    chain.mem_pool().lock().set_limits(MemPoolLimitsConfig {
+        max_txs: 8,
+        ..Default::default()
+    });
+    let tx = transfer(account_ids[0], 2, 100);
+    MemPool::push_transaction_with_lock(chain.mem_pool(), tx).unwrap();
+    let stats = chain.mem_pool().lock().stats();
+    assert_eq!(stats.txs, 8);
+    assert_eq!(get_pending_nonce(&chain, account_ids[0]), 3);
+    assert_eq!(get_pending_nonce(&chain, account_ids[1]), 1);
+    assert_recomputed();
+}
+
+fn get_pending_nonce(chain: &gw_chain::chain::Chain, account_id: u32) -> u32 {
+    let (version, changes) = chain.mem_pool().lock().pending_state();
+    let db = chain.store().begin_transaction();
+    let state_db = StateDBTransaction::from_version(&db, version).unwrap();
+    let tree = state_db.account_state_tree().unwrap();
+    OverlayState::new(&tree, changes)
+        .get_nonce(account_id)
+        .unwrap()
+}
@@ -0,0 +1,62 @@
+use gw_config::RPCServerConfig;
+use gw_rpc_server::connection::{ConnectionLimits, ConnectionMetrics, RateLimiter};
+use gw_rpc_server::server::count_calls;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::Duration;
+
+fn setup_rate_limiter(requests_per_second: u32) -> (RateLimiter, Arc<ConnectionMetrics>) {
+    let config = RPCServerConfig {
+        max_requests_per_second_per_ip: requests_per_second,
+        ..Default::default()
+    };
+    let metrics = Arc::new(ConnectionMetrics::default());
+    let rate_limiter = RateLimiter::new(&ConnectionLimits::from(&config), Arc::clone(&metrics));
+    (rate_limiter, metrics)
+}
+
+#[test]
+fn test_each_call_takes_a_token() {
+    let (rate_limiter, metrics) = setup_rate_limiter(3);
+    let alice: IpAddr = [10, 0, 0, 1].into();
+    let bob: IpAddr = [10, 0, 0, 2].into();
+
+    // a batch larger than the rate is always refused
+    assert!(!rate_limiter.check(alice, 4));
+    assert!(rate_limiter.check(alice, 2));
+    assert!(!rate_limiter.check(alice, 2));
+    assert!(rate_limiter.check(alice, 1));
+    assert!(!rate_limiter.check(alice, 1));
+    assert_eq!(metrics.stats().rate_limited_requests.value(), 7);
+
+    // clients are limited independently
+    assert!(rate_limiter.check(bob, 3));
+    assert_eq!(rate_limiter.tracked_clients(), 2);
+}
+
+#[test]
+fn test_idle_clients_are_expired() {
+    let (rate_limiter, _) = setup_rate_limiter(10);
+    for i in 0..10u8 {
+        assert!(rate_limiter.check([10, 0, 0, i].into(), 1));
+    }
+    assert_eq!(rate_limiter.tracked_clients(), 10);
+
+    // the buckets are full again, each call drops a few of them
+    sleep(Duration::from_millis(200));
+    let client: IpAddr = [10, 0, 1, 0].into();
+    for _ in 0..5 {
+        assert!(rate_limiter.check(client, 1));
+    }
+    assert_eq!(rate_limiter.tracked_clients(), 1);
+}
+
+#[test]
+fn test_count_calls() {
+    assert_eq!(count_calls(br#"{"id": 1, "method": "gw_ping"}"#), 1);
+    assert_eq!(count_calls(br#" [{"id": 1}, {"id": 2}, {"id": 3}]"#), 3);
+    assert_eq!(count_calls(b"[]"), 0);
+    // an unparsable body is reported by the JSONRPC server as a call
+    assert_eq!(count_calls(b"[{"), 1);
+}
@@ -0,0 +1,54 @@
+use gw_common::H256;
+use gw_mem_pool::pool::{MemPool, MemPoolEvent};
+use gw_types::prelude::*;
+
+use crate::testing_tool::accounts::{build_transfer, setup_accounts, with_gas_price};
+
+#[test]
+fn test_replace_by_fee() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let tx0 = with_gas_price(build_transfer(user_id, receiver_id, 0), 100);
+    let tx1 = with_gas_price(build_transfer(user_id, receiver_id, 1), 100);
+    let mut mem_pool = chain.mem_pool().lock();
+    mem_pool.push_transaction(tx0.clone()).unwrap();
+    mem_pool.push_transaction(tx1.clone()).unwrap();
+
+    // the gas price must be bumped enough
+    let err = mem_pool
+        .push_transaction(with_gas_price(build_transfer(user_id, receiver_id, 0), 105))
+        .unwrap_err();
+    assert!(err.to_string().contains("underpriced"), "{}", err);
+    let pending_txs = |mem_pool: &MemPool| -> Vec<[u8; 32]> {
+        mem_pool.pending()[&user_id]
+            .txs
+            .iter()
+            .map(|tx| tx.hash())
+            .collect()
+    };
+    assert_eq!(pending_txs(&mem_pool), vec![tx0.hash(), tx1.hash()]);
+    assert!(mem_pool.drain_events().is_empty());
+
+    // the later tx is kept on top of the replacement
+    let replacement_tx = with_gas_price(build_transfer(user_id, receiver_id, 0), 110);
+    mem_pool.push_transaction(replacement_tx.clone()).unwrap();
+    assert_eq!(
+        pending_txs(&mem_pool),
+        vec![replacement_tx.hash(), tx1.hash()]
+    );
+    let events = mem_pool.drain_events();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        MemPoolEvent::TransactionReplaced {
+            tx_hash,
+            replaced_tx_hash,
+        } => {
+            assert_eq!(*tx_hash, H256::from(replacement_tx.hash()));
+            assert_eq!(*replaced_tx_hash, H256::from(tx0.hash()));
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+    let (txs, _) = mem_pool.package();
+    let packaged: Vec<[u8; 32]> = txs.iter().map(|tx| tx.hash()).collect();
+    assert_eq!(packaged, vec![replacement_tx.hash(), tx1.hash()]);
+}
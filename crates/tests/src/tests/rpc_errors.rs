@@ -0,0 +1,75 @@
+use anyhow::anyhow;
+use gw_common::H256;
+use gw_generator::error::TransactionError;
+use gw_rpc_server::error::{
+    RpcError, BACKEND_NOT_FOUND_CODE, EXECUTION_TIMEOUT_CODE, INTERNAL_ERROR_CODE,
+    INVALID_NONCE_CODE, INVALID_PARAMS_CODE, NOT_FOUND_CODE, TX_REJECTED_CODE,
+};
+use gw_types::prelude::*;
+use serde_json::json;
+
+use crate::testing_tool::{
+    accounts::{build_transfer, setup_accounts, with_gas_price},
+    rpc::{build_rpc_server, call},
+};
+
+#[test]
+fn test_error_codes() {
+    let code = |err: anyhow::Error| RpcError::from(err).code();
+    let nonce_error = || TransactionError::Nonce {
+        expected: 1,
+        actual: 2,
+    };
+    assert_eq!(code(nonce_error().into()), INVALID_NONCE_CODE);
+    let backend_error = TransactionError::BackendNotFound {
+        script_hash: H256::zero(),
+    };
+    assert_eq!(code(backend_error.into()), BACKEND_NOT_FOUND_CODE);
+    assert_eq!(
+        code(TransactionError::Timeout.into()),
+        EXECUTION_TIMEOUT_CODE
+    );
+    assert_eq!(code(anyhow!("db error")), INTERNAL_ERROR_CODE);
+
+    // other errors of the mem-pool are rejections
+    let rejected = RpcError::rejected(anyhow!("mem-pool is full"));
+    assert_eq!(rejected.code(), TX_REJECTED_CODE);
+    assert_eq!(rejected.message(), "mem-pool is full");
+    let rejected = RpcError::rejected(nonce_error().into());
+    assert_eq!(rejected.code(), INVALID_NONCE_CODE);
+    assert_eq!(rejected.message(), "invalid nonce expected 1, actual 2");
+}
+
+#[test]
+fn test_rpc_error_codes() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 2);
+    let server = build_rpc_server(&chain, Default::default());
+    let error_code = |method: &str, params| {
+        let response = call(&server, method, params);
+        assert!(response.get("result").is_none(), "{} succeeds", method);
+        response["error"]["code"].clone()
+    };
+
+    let tx = with_gas_price(build_transfer(account_ids[0], account_ids[1], 5), 1);
+    let tx = format!("0x{}", hex::encode(tx.as_slice()));
+    assert_eq!(
+        error_code("submit_l2transaction", json!([tx])),
+        json!(INVALID_NONCE_CODE)
+    );
+    assert_eq!(
+        error_code("submit_l2transaction", json!(["0x00"])),
+        json!(INVALID_PARAMS_CODE)
+    );
+    assert_eq!(
+        error_code("get_state_roots", json!(["0x5", "0x0"])),
+        json!(INVALID_PARAMS_CODE)
+    );
+    let account_id = format!("{:#x}", account_ids[0]);
+    assert_eq!(
+        error_code(
+            "get_balance",
+            json!([account_id, "0x1", { "block_number": "0x64" }])
+        ),
+        json!(NOT_FOUND_CODE)
+    );
+}
@@ -0,0 +1,157 @@
+use gw_chain::chain::{Chain, SyncEvent};
+use gw_generator::{
+    account_lock_manage::{
+        always_success::AlwaysSuccess, secp256k1::Secp256k1Eth, AccountLockManage, LockAlgorithm,
+    },
+    backend_manage::BackendManage,
+    error::{LockAlgorithmError, TransactionError},
+    Error as GeneratorError,
+};
+use gw_types::{
+    core::ChallengeTargetType,
+    packed::{CellOutput, DepositionRequest, L2Transaction, Script, Transaction},
+    prelude::*,
+};
+
+use crate::testing_tool::accounts::{account_id_at_tip, build_transfer};
+use crate::testing_tool::chain::{
+    build_backend_manage, build_sync_tx, construct_block, setup_chain_with_backend_manage,
+    sync_block, try_sync_block, ALWAYS_SUCCESS_CODE_HASH,
+};
+
+const ETH_LOCK_CODE_HASH: [u8; 32] = [3u8; 32];
+
+/// Setup a chain which verifies the eth lock by `eth_lock_algo`, the eth lock is unknown if None
+fn setup_chain(
+    eth_lock_algo: Option<Box<dyn LockAlgorithm + Send + Sync>>,
+    backend_manage: BackendManage,
+) -> Chain {
+    let mut account_lock_manage = AccountLockManage::default();
+    account_lock_manage.register_lock_algorithm(
+        ALWAYS_SUCCESS_CODE_HASH.clone().into(),
+        Box::new(AlwaysSuccess),
+    );
+    if let Some(eth_lock_algo) = eth_lock_algo {
+        account_lock_manage.register_lock_algorithm(ETH_LOCK_CODE_HASH.into(), eth_lock_algo);
+    }
+    setup_chain_with_backend_manage(
+        Script::default(),
+        Default::default(),
+        account_lock_manage,
+        backend_manage,
+    )
+}
+
+/// The producer accepts any signature of the eth lock, it submits a block with a transfer of
+/// an eth account without signature. Returns the validator which synced the previous blocks,
+/// the layer1 transaction of the block and the block hash
+fn produce_unsigned_transfer(mut validator: Chain) -> (Chain, Transaction, [u8; 32]) {
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(Script::default()).pack())
+        .build();
+    let mut producer = setup_chain(
+        Some(Box::new(AlwaysSuccess)),
+        build_backend_manage(&Default::default()),
+    );
+
+    let alice_script = Script::new_builder()
+        .code_hash(ETH_LOCK_CODE_HASH.pack())
+        .args(vec![42u8; 20].pack())
+        .build();
+    let bob_script = Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .args(vec![43].pack())
+        .build();
+    let deposition_requests: Vec<DepositionRequest> =
+        vec![alice_script.clone(), bob_script.clone()]
+            .into_iter()
+            .map(|script| {
+                DepositionRequest::new_builder()
+                    .capacity(500_00000000u64.pack())
+                    .script(script)
+                    .build()
+            })
+            .collect();
+    let block_result = {
+        let mem_pool = producer.mem_pool().lock();
+        construct_block(&producer, &mem_pool, deposition_requests.clone()).unwrap()
+    };
+    let transaction = build_sync_tx(rollup_cell.clone(), block_result);
+    for chain in vec![&mut producer, &mut validator] {
+        let event = sync_block(chain, transaction.clone(), deposition_requests.clone());
+        assert_eq!(event, SyncEvent::Success);
+    }
+
+    // a transfer of alice without signature
+    let alice_id = account_id_at_tip(&producer, &alice_script);
+    let bob_id = account_id_at_tip(&producer, &bob_script);
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(alice_id, bob_id, 0))
+        .build();
+    producer.mem_pool().lock().push_transaction(tx).unwrap();
+    let block_result = {
+        let mem_pool = producer.mem_pool().lock();
+        construct_block(&producer, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 1);
+    let block_hash = block_result.block.hash();
+    let transaction = build_sync_tx(rollup_cell, block_result);
+    (validator, transaction, block_hash)
+}
+
+#[test]
+fn test_block_with_bad_tx_signature_is_challenged() {
+    let validator = setup_chain(
+        Some(Box::new(Secp256k1Eth)),
+        build_backend_manage(&Default::default()),
+    );
+    let (mut validator, transaction, block_hash) = produce_unsigned_transfer(validator);
+
+    // the validator challenges the tx
+    match sync_block(&mut validator, transaction, Vec::new()) {
+        SyncEvent::BadBlock(context) => {
+            let target_type: u8 = context.target.target_type().into();
+            let target_index: u32 = context.target.target_index().unpack();
+            let target_block_hash: [u8; 32] = context.target.block_hash().unpack();
+            assert_eq!(target_type, ChallengeTargetType::Transaction as u8);
+            assert_eq!(target_index, 0);
+            assert_eq!(target_block_hash, block_hash);
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+}
+
+#[test]
+fn test_unknown_lock_is_not_challenged() {
+    // the validator isn't configured with the eth lock
+    let validator = setup_chain(None, build_backend_manage(&Default::default()));
+    let (mut validator, transaction, _block_hash) = produce_unsigned_transfer(validator);
+    let tip_block_hash = validator.local_state().tip().hash();
+
+    // the block can't be verified by this node, it's an error of the node instead of a bad block
+    let err = try_sync_block(&mut validator, transaction, Vec::new()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<GeneratorError>(),
+        Some(&GeneratorError::Unlock(
+            LockAlgorithmError::UnknownAccountLock
+        ))
+    );
+    assert_eq!(validator.local_state().tip().hash(), tip_block_hash);
+}
+
+#[test]
+fn test_missing_backend_is_not_challenged() {
+    // the validator isn't configured with the sUDT backend
+    let backend_manage = BackendManage::from_config(Vec::new()).expect("backend manage");
+    let validator = setup_chain(Some(Box::new(AlwaysSuccess)), backend_manage);
+    let (mut validator, transaction, _block_hash) = produce_unsigned_transfer(validator);
+    let tip_block_hash = validator.local_state().tip().hash();
+
+    // the tx isn't failed in the block, it's an error of the node instead of a bad block
+    let err = try_sync_block(&mut validator, transaction, Vec::new()).unwrap_err();
+    match err.downcast_ref::<GeneratorError>() {
+        Some(GeneratorError::Backend(TransactionError::BackendNotFound { .. })) => {}
+        err => panic!("unexpected error {:?}", err),
+    }
+    assert_eq!(validator.local_state().tip().hash(), tip_block_hash);
+}
@@ -0,0 +1,60 @@
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State};
+use gw_store::{
+    overlay_state::AccountOverride,
+    state_db::{StateDBTransaction, StateDBVersion},
+};
+use gw_types::{
+    packed::{BlockInfo, RawL2Transaction, SUDTArgs, SUDTQuery},
+    prelude::*,
+};
+
+use crate::testing_tool::accounts::{setup_accounts, ACCOUNT_CAPACITY};
+
+#[test]
+fn test_execute_with_state_overrides() {
+    let (chain, _rollup_cell, account_ids) = setup_accounts(Default::default(), 1);
+    let user_id = account_ids[0];
+    let get_user_balance = |chain: &gw_chain::chain::Chain| {
+        let db = chain.store().begin_transaction();
+        let tip_block_hash = chain.store().get_tip_block_hash().unwrap();
+        let state_db =
+            StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(tip_block_hash))
+                .unwrap();
+        let tree = state_db.account_state_tree().unwrap();
+        tree.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, user_id).unwrap()
+    };
+    assert_eq!(get_user_balance(&chain), ACCOUNT_CAPACITY as u128);
+
+    // query the balance with an overridden balance, the nonce isn't checked
+    let args = SUDTArgs::new_builder()
+        .set(SUDTQuery::new_builder().account_id(user_id.pack()).build())
+        .build();
+    let raw_tx = RawL2Transaction::new_builder()
+        .from_id(user_id.pack())
+        .to_id(CKB_SUDT_ACCOUNT_ID.pack())
+        .nonce(7u32.pack())
+        .args(args.as_bytes().pack())
+        .build();
+    let overrides = vec![AccountOverride {
+        account_id: user_id,
+        balances: vec![(CKB_SUDT_ACCOUNT_ID, 42)],
+        ..Default::default()
+    }];
+    let snapshot = chain.mem_pool().lock().snapshot();
+    let run_result = snapshot
+        .execute_with_state_overrides(&raw_tx, &BlockInfo::default(), &overrides, None)
+        .unwrap();
+    assert_eq!(run_result.return_data, 42u128.to_le_bytes().to_vec());
+
+    // the state isn't modified
+    assert_eq!(get_user_balance(&chain), ACCOUNT_CAPACITY as u128);
+
+    // accounts must exist
+    let overrides = vec![AccountOverride {
+        account_id: 10_000,
+        ..Default::default()
+    }];
+    assert!(snapshot
+        .execute_with_state_overrides(&raw_tx, &BlockInfo::default(), &overrides, None)
+        .is_err());
+}
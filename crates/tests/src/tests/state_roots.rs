@@ -0,0 +1,84 @@
+use gw_common::H256;
+use gw_types::{
+    packed::{AccountMerkleState, CellOutput, DepositionRequest, Script},
+    prelude::*,
+};
+use serde_json::json;
+
+use crate::testing_tool::{
+    chain::{apply_block_result, construct_block, setup_chain},
+    rpc::{build_rpc_server, call, call_ok},
+};
+
+fn hex_h256(hash: &H256) -> String {
+    format!("0x{}", hex::encode(hash.as_slice()))
+}
+
+#[test]
+fn test_get_state_roots_returns_local_roots() {
+    let rollup_type_script = Script::default();
+    let rollup_cell = CellOutput::new_builder()
+        .type_(Some(rollup_type_script.clone()).pack())
+        .build();
+    let mut chain = setup_chain(rollup_type_script, Default::default());
+
+    // the global state on L1 claims a bogus account root, which isn't checked by the node
+    let deposition = DepositionRequest::new_builder()
+        .capacity(100u64.pack())
+        .script(Script::new_builder().args(vec![42].pack()).build())
+        .build();
+    let mut block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, vec![deposition.clone()]).unwrap()
+    };
+    let bogus_account = AccountMerkleState::new_builder()
+        .merkle_root([42u8; 32].pack())
+        .count(42u32.pack())
+        .build();
+    block_result.global_state = block_result
+        .global_state
+        .as_builder()
+        .account(bogus_account)
+        .build();
+    let block = block_result.block.clone();
+    apply_block_result(&mut chain, rollup_cell, block_result, vec![deposition]);
+
+    let server = build_rpc_server(&chain, Default::default());
+    let state_roots = call_ok(&server, "get_state_roots", json!(["0x0", "0x5"]));
+    let state_roots = state_roots.as_array().expect("state roots");
+    // stops at the tip
+    assert_eq!(state_roots.len(), 2);
+    let db = chain.store().begin_transaction();
+    for (number, state_root) in state_roots.iter().enumerate() {
+        let block_hash = db.get_block_hash_by_number(number as u64).unwrap().unwrap();
+        let (post_account, post_block) = db
+            .get_block_local_post_state(&block_hash)
+            .unwrap()
+            .expect("local post state");
+        assert_eq!(state_root["block_number"], json!(format!("{:#x}", number)));
+        assert_eq!(state_root["block_hash"], json!(hex_h256(&block_hash)));
+        assert_eq!(
+            state_root["account_root"],
+            json!(hex_h256(&post_account.merkle_root().unpack()))
+        );
+        assert_eq!(
+            state_root["block_root"],
+            json!(hex_h256(&post_block.merkle_root().unpack()))
+        );
+    }
+    let post_account_root: H256 = block.raw().post_account().merkle_root().unpack();
+    assert_eq!(
+        state_roots[1]["account_root"],
+        json!(hex_h256(&post_account_root))
+    );
+    assert_ne!(
+        state_roots[1]["account_root"],
+        json!(hex_h256(&[42u8; 32].into()))
+    );
+
+    // invalid ranges
+    let response = call(&server, "get_state_roots", json!(["0x5", "0x0"]));
+    assert!(response.get("error").is_some());
+    let response = call(&server, "get_state_roots", json!(["0x0", "0x3e8"]));
+    assert!(response.get("error").is_some());
+}
@@ -0,0 +1,51 @@
+use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
+use gw_types::{
+    bytes::Bytes,
+    packed::{L2Transaction, RawL2Transaction, SUDTArgs, SUDTQuery},
+    prelude::*,
+};
+
+use crate::testing_tool::accounts::{setup_accounts, ACCOUNT_CAPACITY};
+use crate::testing_tool::chain::{apply_block_result, construct_block};
+
+#[test]
+fn test_sudt_query_result_in_receipt() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 1);
+    let user_id = account_ids[0];
+
+    // query the balance in a block
+    let args = SUDTArgs::new_builder()
+        .set(SUDTQuery::new_builder().account_id(user_id.pack()).build())
+        .build();
+    let raw = RawL2Transaction::new_builder()
+        .from_id(user_id.pack())
+        .to_id(CKB_SUDT_ACCOUNT_ID.pack())
+        .nonce(0u32.pack())
+        .args(args.as_bytes().pack())
+        .build();
+    let tx = L2Transaction::new_builder().raw(raw).build();
+    chain
+        .mem_pool()
+        .lock()
+        .push_transaction(tx.clone())
+        .unwrap();
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 1);
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+
+    // the balance is recorded in the receipt
+    let receipt = chain
+        .store()
+        .begin_transaction()
+        .get_transaction_receipt(&tx.hash().into())
+        .unwrap()
+        .expect("receipt");
+    let return_data: Bytes = receipt.return_data().unpack();
+    assert_eq!(
+        return_data.as_ref(),
+        &(ACCOUNT_CAPACITY as u128).to_le_bytes()[..]
+    );
+}
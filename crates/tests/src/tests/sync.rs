@@ -53,6 +53,33 @@ fn test_sync_a_block() {
     };
     let event = chain.sync(param).unwrap();
     assert_eq!(event, SyncEvent::Success);
+
+    // the L1 commitment of the synced block is indexed
+    let db = chain.store().begin_transaction();
+    let tip_block_hash = db.get_tip_block_hash().unwrap();
+    let committed_info = db.get_l2block_committed_info(&tip_block_hash).unwrap();
+    assert_eq!(
+        committed_info.map(|info| info.as_bytes()),
+        Some(L2BlockCommittedInfo::default().as_bytes())
+    );
+    assert_eq!(
+        db.get_l2block_committed_output_index(&tip_block_hash)
+            .unwrap(),
+        Some(0)
+    );
+
+    // the roots computed by the node match the global state of an honest block
+    let global_state = db
+        .get_block_post_global_state(&tip_block_hash)
+        .unwrap()
+        .expect("global state");
+    let (account, block) = db
+        .get_block_local_post_state(&tip_block_hash)
+        .unwrap()
+        .expect("local post state");
+    assert_eq!(account.as_slice(), global_state.account().as_slice());
+    assert_eq!(block.as_slice(), global_state.block().as_slice());
+    drop(db);
     drop(chain);
 }
 
@@ -0,0 +1,31 @@
+use gw_chain::sync_progress::SyncProgress;
+
+#[test]
+fn test_sync_progress() {
+    let progress = SyncProgress::default();
+    let status = progress.status();
+    assert_eq!(status.tip_block, None);
+    assert!(!status.is_within(u64::max_value()));
+
+    // the first synced block is the start of the catch-up
+    progress.set_synced_block(100);
+    progress.set_tip_block(300);
+    let status = progress.status();
+    assert_eq!(status.start_block, 100);
+    assert_eq!(status.percent, 0);
+    assert_eq!(status.eta, None);
+    assert!(!status.is_within(10));
+
+    progress.set_synced_block(250);
+    let status = progress.status();
+    assert_eq!(status.start_block, 100);
+    assert_eq!(status.percent, 75);
+    assert!(status.eta.is_some());
+    assert!(!status.is_within(10));
+    assert!(status.is_within(50));
+
+    progress.set_synced_block(300);
+    let status = progress.status();
+    assert_eq!(status.percent, 100);
+    assert!(status.is_within(0));
+}
@@ -0,0 +1,82 @@
+use gw_generator::error::TransactionError;
+use gw_types::{
+    offchain::TraceEvent,
+    packed::{BlockInfo, L2Transaction},
+    prelude::*,
+};
+
+use crate::testing_tool::accounts::{build_transfer, build_transfer_amount, setup_accounts};
+
+const MAX_TRACE_STEPS: usize = 1000;
+
+#[test]
+fn test_trace_transaction() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let snapshot = chain.mem_pool().lock().snapshot();
+    let block_info = BlockInfo::default();
+
+    // the balance changes are traced
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(user_id, receiver_id, 0))
+        .build();
+    let (result, steps) = snapshot
+        .trace_transaction(tx, &block_info, None, MAX_TRACE_STEPS)
+        .unwrap();
+    let run_result = result.unwrap();
+    assert_eq!(run_result.trace.as_ref().map(Vec::len), Some(steps.len()));
+    assert!(steps
+        .iter()
+        .any(|step| matches!(step.event, TraceEvent::Load { .. })));
+    assert!(steps
+        .iter()
+        .any(|step| matches!(step.event, TraceEvent::Store { .. })));
+    assert!(steps.windows(2).all(|w| w[0].cycles <= w[1].cycles));
+
+    // the trace is kept if the tx fails
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer_amount(
+            user_id,
+            receiver_id,
+            0,
+            u128::max_value(),
+        ))
+        .build();
+    let (result, steps) = snapshot
+        .trace_transaction(tx, &block_info, None, MAX_TRACE_STEPS)
+        .unwrap();
+    assert!(result.is_err());
+    assert!(steps
+        .iter()
+        .any(|step| matches!(step.event, TraceEvent::Load { .. })));
+}
+
+#[test]
+fn test_trace_max_steps() {
+    let (chain, _, account_ids) = setup_accounts(Default::default(), 2);
+    let (user_id, receiver_id) = (account_ids[0], account_ids[1]);
+    let snapshot = chain.mem_pool().lock().snapshot();
+    let block_info = BlockInfo::default();
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(user_id, receiver_id, 0))
+        .build();
+    let (result, steps) = snapshot
+        .trace_transaction(tx.clone(), &block_info, None, MAX_TRACE_STEPS)
+        .unwrap();
+    result.unwrap();
+    let max_steps = steps.len() - 1;
+
+    // the tx fails once the trace is full, the recorded steps are returned
+    let (result, capped_steps) = snapshot
+        .trace_transaction(tx, &block_info, None, max_steps)
+        .unwrap();
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::ExceededMaxTraceSteps { max_steps }
+    );
+    assert_eq!(capped_steps.len(), max_steps);
+    assert!(capped_steps
+        .iter()
+        .zip(&steps)
+        .all(|(capped, step)| capped.cycles == step.cycles));
+}
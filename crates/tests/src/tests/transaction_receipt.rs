@@ -0,0 +1,58 @@
+use gw_common::H256;
+use gw_jsonrpc_types::godwoken::{AccountMerkleState, TxReceipt};
+use gw_types::{packed::L2Transaction, prelude::*};
+use serde_json::{json, Value};
+
+use crate::testing_tool::{
+    accounts::{build_transfer, setup_accounts},
+    chain::{apply_block_result, construct_block},
+    rpc::{build_rpc_server, call_ok},
+};
+
+#[test]
+fn test_get_transaction_receipt() {
+    let (mut chain, rollup_cell, account_ids) = setup_accounts(Default::default(), 2);
+    let tx = L2Transaction::new_builder()
+        .raw(build_transfer(account_ids[0], account_ids[1], 0))
+        .build();
+    chain
+        .mem_pool()
+        .lock()
+        .push_transaction(tx.clone())
+        .unwrap();
+    let block_result = {
+        let mem_pool = chain.mem_pool().lock();
+        construct_block(&chain, &mem_pool, Vec::new()).unwrap()
+    };
+    assert_eq!(block_result.block.transactions().len(), 1);
+    let post_account = block_result.block.raw().post_account();
+    apply_block_result(&mut chain, rollup_cell, block_result, Vec::new());
+
+    let server = build_rpc_server(&chain, Default::default());
+    let tx_hash = format!("0x{}", hex::encode(tx.hash()));
+    let receipt: TxReceipt = serde_json::from_value(call_ok(
+        &server,
+        "get_transaction_receipt",
+        json!([tx_hash]),
+    ))
+    .expect("receipt");
+    let stored_receipt = chain
+        .store()
+        .begin_transaction()
+        .get_transaction_receipt(&tx.hash().into())
+        .unwrap()
+        .expect("stored receipt");
+    assert_eq!(receipt, TxReceipt::from(stored_receipt));
+    let witness_hash: [u8; 32] = receipt.tx_witness_hash.into();
+    assert_eq!(witness_hash, tx.witness_hash());
+    assert!(u64::from(receipt.used_cycles) > 0);
+    // the only tx of the block leaves the post state of the block
+    assert_eq!(receipt.post_state, AccountMerkleState::from(post_account));
+
+    // an unknown tx has no receipt
+    let unknown_hash = format!("0x{}", hex::encode(H256::from([1u8; 32]).as_slice()));
+    assert_eq!(
+        call_ok(&server, "get_transaction_receipt", json!([unknown_hash])),
+        Value::Null
+    );
+}
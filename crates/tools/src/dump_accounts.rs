@@ -0,0 +1,111 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use ckb_fixed_hash::H256;
+use gw_config::Config;
+use gw_db::{config::Config as DBConfig, schema::COLUMNS, RocksDB};
+use gw_jsonrpc_types::ckb_jsonrpc_types::{Uint128, Uint32};
+use gw_store::{
+    account_iter::{sudt_account_ids, AccountIter},
+    smt_gc::get_pruning_horizon,
+    state_db::{StateDBTransaction, StateDBVersion},
+    Store,
+};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Balance {
+    sudt_id: Uint32,
+    amount: Uint128,
+}
+
+#[derive(Serialize)]
+struct Account {
+    id: Uint32,
+    script_hash: H256,
+    nonce: Uint32,
+    balances: Vec<Balance>,
+}
+
+/// Write accounts at the block as JSON lines, the tip block is used if `block_number` is None.
+///
+/// The accounts are read from the store at `store.path` of the config. The node still runs on a
+/// temporary store and never writes there, so the tool is for a later persistent store, or a
+/// store copied to that path
+pub fn dump_accounts(
+    config_path: &Path,
+    block_number: Option<u64>,
+    output_path: Option<&Path>,
+) -> Result<()> {
+    let config: Config = {
+        let content = fs::read(config_path)?;
+        toml::from_slice(&content)?
+    };
+    if !config.store.path.exists() {
+        return Err(anyhow!(
+            "store {} doesn't exist",
+            config.store.path.to_string_lossy()
+        ));
+    }
+    let db_config = DBConfig {
+        path: config.store.path.clone(),
+        ..Default::default()
+    };
+    let store = Store::new(RocksDB::open(&db_config, COLUMNS));
+    let db = store.begin_transaction();
+    let block_hash = match block_number {
+        Some(number) => {
+            let horizon = get_pruning_horizon(&db)?;
+            if number < horizon {
+                return Err(anyhow!(
+                    "state of block {} is pruned, only the states from block {} are kept",
+                    number,
+                    horizon
+                ));
+            }
+            db.get_block_hash_by_number(number)?
+                .ok_or_else(|| anyhow!("can't find block {}", number))?
+        }
+        None => db.get_tip_block_hash()?,
+    };
+    let state_db =
+        StateDBTransaction::from_version(&db, StateDBVersion::from_block_hash(block_hash))?;
+    let tree = state_db.account_state_tree()?;
+    let l2_sudt_validator_script_type_hash: [u8; 32] = config
+        .genesis
+        .rollup_config
+        .l2_sudt_validator_script_type_hash
+        .into();
+    let sudt_ids = sudt_account_ids(&tree, &l2_sudt_validator_script_type_hash.into())?;
+
+    let output: Box<dyn Write> = match output_path {
+        Some(path) => Box::new(fs::File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let mut writer = BufWriter::new(output);
+    for account in AccountIter::new(&tree, sudt_ids)? {
+        let account = account?;
+        let script_hash: [u8; 32] = account.script_hash.into();
+        let account = Account {
+            id: account.id.into(),
+            script_hash: H256(script_hash),
+            nonce: account.nonce.into(),
+            balances: account
+                .balances
+                .into_iter()
+                .map(|(sudt_id, amount)| Balance {
+                    sudt_id: sudt_id.into(),
+                    amount: amount.into(),
+                })
+                .collect(),
+        };
+        serde_json::to_writer(&mut writer, &account)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -95,6 +95,9 @@ pub fn generate_config(
     });
     let store: StoreConfig = StoreConfig {
         path: "./store.db".into(),
+        mirror: None,
+        cold: None,
+        smt_pruning: None,
     };
     let genesis_committed_info = L2BlockCommittedInfo {
         block_hash,
@@ -104,6 +107,9 @@ pub fn generate_config(
     let chain: ChainConfig = ChainConfig {
         genesis_committed_info,
         rollup_type_script,
+        block_limits: Default::default(),
+        account_creation_fee: 0,
+        ..Default::default()
     };
     let rpc_client: RPCClientConfig = RPCClientConfig {
         indexer_url,
@@ -111,6 +117,7 @@ pub fn generate_config(
     };
     let rpc_server = RPCServerConfig {
         listen: "localhost:8119".to_string(),
+        ..Default::default()
     };
     let block_producer: Option<BlockProducerConfig> = Some(BlockProducerConfig {
         account_id,
@@ -119,6 +126,7 @@ pub fn generate_config(
         rollup_cell_type_dep,
         deposit_cell_lock_dep,
         wallet_config,
+        check_block_replay: false,
     });
     let genesis: GenesisConfig = GenesisConfig {
         timestamp: genesis.timestamp,
@@ -134,6 +142,8 @@ pub fn generate_config(
         rpc_client,
         rpc_server,
         block_producer,
+        mem_pool: Default::default(),
+        alert: Default::default(),
     };
     let output_content = toml::to_string_pretty(&config).expect("serde toml to string pretty");
     fs::write(output_path, output_content.as_bytes()).map_err(|err| anyhow!("{}", err))?;
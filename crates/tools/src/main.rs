@@ -1,5 +1,6 @@
 mod deploy_genesis;
 mod deploy_scripts;
+mod dump_accounts;
 mod generate_config;
 
 use clap::{App, Arg, SubCommand};
@@ -115,6 +116,29 @@ fn main() {
                         .required(true)
                         .help("The output json file path"),
                 ),
+        )
+        .subcommand(
+            SubCommand::with_name("dump-accounts")
+                .about("Dump accounts of a block in the persistent store as JSON lines")
+                .arg(
+                    Arg::with_name("config-path")
+                        .short("c")
+                        .takes_value(true)
+                        .default_value("./config.toml")
+                        .help("The godwoken config file path"),
+                )
+                .arg(
+                    Arg::with_name("block-number")
+                        .short("b")
+                        .takes_value(true)
+                        .help("The block number, default is the tip block"),
+                )
+                .arg(
+                    Arg::with_name("output-path")
+                        .short("o")
+                        .takes_value(true)
+                        .help("The output file path, default is stdout"),
+                ),
         );
 
     let matches = app.clone().get_matches();
@@ -174,6 +198,23 @@ fn main() {
                 std::process::exit(-1);
             };
         }
+        ("dump-accounts", Some(m)) => {
+            let config_path = Path::new(m.value_of("config-path").unwrap());
+            let block_number = m
+                .value_of("block-number")
+                .map(str::parse::<u64>)
+                .transpose()
+                .unwrap_or_else(|err| {
+                    log::error!("Invalid block number: {}", err);
+                    std::process::exit(-1);
+                });
+            let output_path = m.value_of("output-path").map(Path::new);
+            if let Err(err) = dump_accounts::dump_accounts(&config_path, block_number, output_path)
+            {
+                log::error!("Dump accounts error: {}", err);
+                std::process::exit(-1);
+            };
+        }
         _ => {
             app.print_help().expect("print help");
         }
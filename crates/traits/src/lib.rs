@@ -11,4 +11,6 @@ pub trait CodeStore {
 
 pub trait ChainStore {
     fn get_block_hash_by_number(&self, number: u64) -> Result<Option<H256>, DBError>;
+    /// Hash of the tip block, it's the parent block of the executing block
+    fn get_tip_block_hash(&self) -> H256;
 }
@@ -106,6 +106,47 @@ impl TryFrom<Byte> for ChallengeTargetType {
     }
 }
 
+/// Non-block rollup action type
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[repr(u8)]
+pub enum RollupActionType {
+    EnterChallenge = 0,
+    CancelChallenge = 1,
+    Revert = 2,
+}
+
+impl Into<u8> for RollupActionType {
+    fn into(self: RollupActionType) -> u8 {
+        self as u8
+    }
+}
+
+impl TryFrom<u8> for RollupActionType {
+    type Error = u8;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RollupActionType::EnterChallenge),
+            1 => Ok(RollupActionType::CancelChallenge),
+            2 => Ok(RollupActionType::Revert),
+            n => Err(n),
+        }
+    }
+}
+
+impl Into<Byte> for RollupActionType {
+    fn into(self: RollupActionType) -> Byte {
+        (self as u8).into()
+    }
+}
+
+impl TryFrom<Byte> for RollupActionType {
+    type Error = u8;
+    fn try_from(value: Byte) -> Result<Self, Self::Error> {
+        let v: u8 = value.into();
+        v.try_into()
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DepType {
     Code = 0,
@@ -21,10 +21,13 @@ macro_rules! impl_hash {
 macro_rules! impl_witness_hash {
     ($struct:ident) => {
         impl packed::$struct {
+            /// Hash of the raw part, the signature is excluded.
+            /// It's the canonical id used by the mem-pool, the store indexes and the RPC.
             pub fn hash(&self) -> [u8; 32] {
                 self.raw().hash()
             }
 
+            /// Hash of the whole structure, includes the signature
             pub fn witness_hash(&self) -> [u8; 32] {
                 let mut hasher = new_blake2b();
                 hasher.update(self.as_slice());
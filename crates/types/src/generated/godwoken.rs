@@ -2893,6 +2893,8 @@ impl ::core::fmt::Display for RawL2Transaction {
         write!(f, ", {}: {}", "to_id", self.to_id())?;
         write!(f, ", {}: {}", "nonce", self.nonce())?;
         write!(f, ", {}: {}", "args", self.args())?;
+        write!(f, ", {}: {}", "gas_limit", self.gas_limit())?;
+        write!(f, ", {}: {}", "gas_price", self.gas_price())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -2903,14 +2905,15 @@ impl ::core::fmt::Display for RawL2Transaction {
 impl ::core::default::Default for RawL2Transaction {
     fn default() -> Self {
         let v: Vec<u8> = vec![
-            36, 0, 0, 0, 20, 0, 0, 0, 24, 0, 0, 0, 28, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0,
+            68, 0, 0, 0, 28, 0, 0, 0, 32, 0, 0, 0, 36, 0, 0, 0, 40, 0, 0, 0, 44, 0, 0, 0, 52, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         ];
         RawL2Transaction::new_unchecked(v.into())
     }
 }
 impl RawL2Transaction {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 6;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -2948,11 +2951,23 @@ impl RawL2Transaction {
     pub fn args(&self) -> Bytes {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Bytes::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn gas_limit(&self) -> Uint64 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        let end = molecule::unpack_number(&slice[24..]) as usize;
+        Uint64::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn gas_price(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[24..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[20..]) as usize;
-            Bytes::new_unchecked(self.0.slice(start..end))
+            let end = molecule::unpack_number(&slice[28..]) as usize;
+            Uint128::new_unchecked(self.0.slice(start..end))
         } else {
-            Bytes::new_unchecked(self.0.slice(start..))
+            Uint128::new_unchecked(self.0.slice(start..))
         }
     }
     pub fn as_reader<'r>(&'r self) -> RawL2TransactionReader<'r> {
@@ -2986,6 +3001,8 @@ impl molecule::prelude::Entity for RawL2Transaction {
             .to_id(self.to_id())
             .nonce(self.nonce())
             .args(self.args())
+            .gas_limit(self.gas_limit())
+            .gas_price(self.gas_price())
     }
 }
 #[derive(Clone, Copy)]
@@ -3011,6 +3028,8 @@ impl<'r> ::core::fmt::Display for RawL2TransactionReader<'r> {
         write!(f, ", {}: {}", "to_id", self.to_id())?;
         write!(f, ", {}: {}", "nonce", self.nonce())?;
         write!(f, ", {}: {}", "args", self.args())?;
+        write!(f, ", {}: {}", "gas_limit", self.gas_limit())?;
+        write!(f, ", {}: {}", "gas_price", self.gas_price())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -3019,7 +3038,7 @@ impl<'r> ::core::fmt::Display for RawL2TransactionReader<'r> {
     }
 }
 impl<'r> RawL2TransactionReader<'r> {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 6;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -3057,11 +3076,23 @@ impl<'r> RawL2TransactionReader<'r> {
     pub fn args(&self) -> BytesReader<'r> {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        BytesReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn gas_limit(&self) -> Uint64Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        let end = molecule::unpack_number(&slice[24..]) as usize;
+        Uint64Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn gas_price(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[24..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[20..]) as usize;
-            BytesReader::new_unchecked(&self.as_slice()[start..end])
+            let end = molecule::unpack_number(&slice[28..]) as usize;
+            Uint128Reader::new_unchecked(&self.as_slice()[start..end])
         } else {
-            BytesReader::new_unchecked(&self.as_slice()[start..])
+            Uint128Reader::new_unchecked(&self.as_slice()[start..])
         }
     }
 }
@@ -3120,6 +3151,8 @@ impl<'r> molecule::prelude::Reader<'r> for RawL2TransactionReader<'r> {
         Uint32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
         Uint32Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
         BytesReader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Uint64Reader::verify(&slice[offsets[4]..offsets[5]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[5]..offsets[6]], compatible)?;
         Ok(())
     }
 }
@@ -3129,9 +3162,11 @@ pub struct RawL2TransactionBuilder {
     pub(crate) to_id: Uint32,
     pub(crate) nonce: Uint32,
     pub(crate) args: Bytes,
+    pub(crate) gas_limit: Uint64,
+    pub(crate) gas_price: Uint128,
 }
 impl RawL2TransactionBuilder {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 6;
     pub fn from_id(mut self, v: Uint32) -> Self {
         self.from_id = v;
         self
@@ -3148,6 +3183,14 @@ impl RawL2TransactionBuilder {
         self.args = v;
         self
     }
+    pub fn gas_limit(mut self, v: Uint64) -> Self {
+        self.gas_limit = v;
+        self
+    }
+    pub fn gas_price(mut self, v: Uint128) -> Self {
+        self.gas_price = v;
+        self
+    }
 }
 impl molecule::prelude::Builder for RawL2TransactionBuilder {
     type Entity = RawL2Transaction;
@@ -3158,6 +3201,8 @@ impl molecule::prelude::Builder for RawL2TransactionBuilder {
             + self.to_id.as_slice().len()
             + self.nonce.as_slice().len()
             + self.args.as_slice().len()
+            + self.gas_limit.as_slice().len()
+            + self.gas_price.as_slice().len()
     }
     fn write<W: ::molecule::io::Write>(&self, writer: &mut W) -> ::molecule::io::Result<()> {
         let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
@@ -3170,6 +3215,10 @@ impl molecule::prelude::Builder for RawL2TransactionBuilder {
         total_size += self.nonce.as_slice().len();
         offsets.push(total_size);
         total_size += self.args.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.gas_limit.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.gas_price.as_slice().len();
         writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
         for offset in offsets.into_iter() {
             writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
@@ -3178,6 +3227,8 @@ impl molecule::prelude::Builder for RawL2TransactionBuilder {
         writer.write_all(self.to_id.as_slice())?;
         writer.write_all(self.nonce.as_slice())?;
         writer.write_all(self.args.as_slice())?;
+        writer.write_all(self.gas_limit.as_slice())?;
+        writer.write_all(self.gas_price.as_slice())?;
         Ok(())
     }
     fn build(&self) -> Self::Entity {
@@ -5529,6 +5580,7 @@ impl ::core::fmt::Display for DepositionRequest {
         write!(f, ", {}: {}", "amount", self.amount())?;
         write!(f, ", {}: {}", "sudt_script_hash", self.sudt_script_hash())?;
         write!(f, ", {}: {}", "script", self.script())?;
+        write!(f, ", {}: {}", "call", self.call())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -5539,17 +5591,17 @@ impl ::core::fmt::Display for DepositionRequest {
 impl ::core::default::Default for DepositionRequest {
     fn default() -> Self {
         let v: Vec<u8> = vec![
-            129, 0, 0, 0, 20, 0, 0, 0, 28, 0, 0, 0, 44, 0, 0, 0, 76, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            137, 0, 0, 0, 24, 0, 0, 0, 32, 0, 0, 0, 48, 0, 0, 0, 80, 0, 0, 0, 133, 0, 0, 0, 0, 0, 0,
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 53, 0, 0, 0, 16, 0, 0, 0,
-            48, 0, 0, 0, 49, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 53, 0, 0, 0, 16,
+            0, 0, 0, 48, 0, 0, 0, 49, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         ];
         DepositionRequest::new_unchecked(v.into())
     }
 }
 impl DepositionRequest {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 5;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -5587,11 +5639,17 @@ impl DepositionRequest {
     pub fn script(&self) -> Script {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Script::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn call(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[20..]) as usize;
-            Script::new_unchecked(self.0.slice(start..end))
+            let end = molecule::unpack_number(&slice[24..]) as usize;
+            Bytes::new_unchecked(self.0.slice(start..end))
         } else {
-            Script::new_unchecked(self.0.slice(start..))
+            Bytes::new_unchecked(self.0.slice(start..))
         }
     }
     pub fn as_reader<'r>(&'r self) -> DepositionRequestReader<'r> {
@@ -5625,6 +5683,7 @@ impl molecule::prelude::Entity for DepositionRequest {
             .amount(self.amount())
             .sudt_script_hash(self.sudt_script_hash())
             .script(self.script())
+            .call(self.call())
     }
 }
 #[derive(Clone, Copy)]
@@ -5650,6 +5709,7 @@ impl<'r> ::core::fmt::Display for DepositionRequestReader<'r> {
         write!(f, ", {}: {}", "amount", self.amount())?;
         write!(f, ", {}: {}", "sudt_script_hash", self.sudt_script_hash())?;
         write!(f, ", {}: {}", "script", self.script())?;
+        write!(f, ", {}: {}", "call", self.call())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -5658,7 +5718,7 @@ impl<'r> ::core::fmt::Display for DepositionRequestReader<'r> {
     }
 }
 impl<'r> DepositionRequestReader<'r> {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 5;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -5696,11 +5756,17 @@ impl<'r> DepositionRequestReader<'r> {
     pub fn script(&self) -> ScriptReader<'r> {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        ScriptReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn call(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[20..]) as usize;
-            ScriptReader::new_unchecked(&self.as_slice()[start..end])
+            let end = molecule::unpack_number(&slice[24..]) as usize;
+            BytesReader::new_unchecked(&self.as_slice()[start..end])
         } else {
-            ScriptReader::new_unchecked(&self.as_slice()[start..])
+            BytesReader::new_unchecked(&self.as_slice()[start..])
         }
     }
 }
@@ -5759,6 +5825,7 @@ impl<'r> molecule::prelude::Reader<'r> for DepositionRequestReader<'r> {
         Uint128Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
         Byte32Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
         ScriptReader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        BytesReader::verify(&slice[offsets[4]..offsets[5]], compatible)?;
         Ok(())
     }
 }
@@ -5768,9 +5835,10 @@ pub struct DepositionRequestBuilder {
     pub(crate) amount: Uint128,
     pub(crate) sudt_script_hash: Byte32,
     pub(crate) script: Script,
+    pub(crate) call: Bytes,
 }
 impl DepositionRequestBuilder {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 5;
     pub fn capacity(mut self, v: Uint64) -> Self {
         self.capacity = v;
         self
@@ -5787,6 +5855,10 @@ impl DepositionRequestBuilder {
         self.script = v;
         self
     }
+    pub fn call(mut self, v: Bytes) -> Self {
+        self.call = v;
+        self
+    }
 }
 impl molecule::prelude::Builder for DepositionRequestBuilder {
     type Entity = DepositionRequest;
@@ -5797,6 +5869,7 @@ impl molecule::prelude::Builder for DepositionRequestBuilder {
             + self.amount.as_slice().len()
             + self.sudt_script_hash.as_slice().len()
             + self.script.as_slice().len()
+            + self.call.as_slice().len()
     }
     fn write<W: ::molecule::io::Write>(&self, writer: &mut W) -> ::molecule::io::Result<()> {
         let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
@@ -5809,6 +5882,8 @@ impl molecule::prelude::Builder for DepositionRequestBuilder {
         total_size += self.sudt_script_hash.as_slice().len();
         offsets.push(total_size);
         total_size += self.script.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.call.as_slice().len();
         writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
         for offset in offsets.into_iter() {
             writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
@@ -5817,6 +5892,7 @@ impl molecule::prelude::Builder for DepositionRequestBuilder {
         writer.write_all(self.amount.as_slice())?;
         writer.write_all(self.sudt_script_hash.as_slice())?;
         writer.write_all(self.script.as_slice())?;
+        writer.write_all(self.call.as_slice())?;
         Ok(())
     }
     fn build(&self) -> Self::Entity {
@@ -6203,6 +6279,8 @@ impl ::core::fmt::Display for RawWithdrawalRequest {
         write!(f, ", {}: {}", "sell_capacity", self.sell_capacity())?;
         write!(f, ", {}: {}", "owner_lock_hash", self.owner_lock_hash())?;
         write!(f, ", {}: {}", "payment_lock_hash", self.payment_lock_hash())?;
+        write!(f, ", {}: {}", "fee", self.fee())?;
+        write!(f, ", {}: {}", "sudt_fee", self.sudt_fee())?;
         write!(f, " }}")
     }
 }
@@ -6215,15 +6293,16 @@ impl ::core::default::Default for RawWithdrawalRequest {
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0,
         ];
         RawWithdrawalRequest::new_unchecked(v.into())
     }
 }
 impl RawWithdrawalRequest {
-    pub const TOTAL_SIZE: usize = 180;
-    pub const FIELD_SIZES: [usize; 9] = [4, 8, 16, 32, 32, 16, 8, 32, 32];
-    pub const FIELD_COUNT: usize = 9;
+    pub const TOTAL_SIZE: usize = 204;
+    pub const FIELD_SIZES: [usize; 11] = [4, 8, 16, 32, 32, 16, 8, 32, 32, 8, 16];
+    pub const FIELD_COUNT: usize = 11;
     pub fn nonce(&self) -> Uint32 {
         Uint32::new_unchecked(self.0.slice(0..4))
     }
@@ -6251,6 +6330,12 @@ impl RawWithdrawalRequest {
     pub fn payment_lock_hash(&self) -> Byte32 {
         Byte32::new_unchecked(self.0.slice(148..180))
     }
+    pub fn fee(&self) -> Uint64 {
+        Uint64::new_unchecked(self.0.slice(180..188))
+    }
+    pub fn sudt_fee(&self) -> Uint128 {
+        Uint128::new_unchecked(self.0.slice(188..204))
+    }
     pub fn as_reader<'r>(&'r self) -> RawWithdrawalRequestReader<'r> {
         RawWithdrawalRequestReader::new_unchecked(self.as_slice())
     }
@@ -6287,6 +6372,8 @@ impl molecule::prelude::Entity for RawWithdrawalRequest {
             .sell_capacity(self.sell_capacity())
             .owner_lock_hash(self.owner_lock_hash())
             .payment_lock_hash(self.payment_lock_hash())
+            .fee(self.fee())
+            .sudt_fee(self.sudt_fee())
     }
 }
 #[derive(Clone, Copy)]
@@ -6322,13 +6409,15 @@ impl<'r> ::core::fmt::Display for RawWithdrawalRequestReader<'r> {
         write!(f, ", {}: {}", "sell_capacity", self.sell_capacity())?;
         write!(f, ", {}: {}", "owner_lock_hash", self.owner_lock_hash())?;
         write!(f, ", {}: {}", "payment_lock_hash", self.payment_lock_hash())?;
+        write!(f, ", {}: {}", "fee", self.fee())?;
+        write!(f, ", {}: {}", "sudt_fee", self.sudt_fee())?;
         write!(f, " }}")
     }
 }
 impl<'r> RawWithdrawalRequestReader<'r> {
-    pub const TOTAL_SIZE: usize = 180;
-    pub const FIELD_SIZES: [usize; 9] = [4, 8, 16, 32, 32, 16, 8, 32, 32];
-    pub const FIELD_COUNT: usize = 9;
+    pub const TOTAL_SIZE: usize = 204;
+    pub const FIELD_SIZES: [usize; 11] = [4, 8, 16, 32, 32, 16, 8, 32, 32, 8, 16];
+    pub const FIELD_COUNT: usize = 11;
     pub fn nonce(&self) -> Uint32Reader<'r> {
         Uint32Reader::new_unchecked(&self.as_slice()[0..4])
     }
@@ -6356,6 +6445,12 @@ impl<'r> RawWithdrawalRequestReader<'r> {
     pub fn payment_lock_hash(&self) -> Byte32Reader<'r> {
         Byte32Reader::new_unchecked(&self.as_slice()[148..180])
     }
+    pub fn fee(&self) -> Uint64Reader<'r> {
+        Uint64Reader::new_unchecked(&self.as_slice()[180..188])
+    }
+    pub fn sudt_fee(&self) -> Uint128Reader<'r> {
+        Uint128Reader::new_unchecked(&self.as_slice()[188..204])
+    }
 }
 impl<'r> molecule::prelude::Reader<'r> for RawWithdrawalRequestReader<'r> {
     type Entity = RawWithdrawalRequest;
@@ -6389,11 +6484,13 @@ pub struct RawWithdrawalRequestBuilder {
     pub(crate) sell_capacity: Uint64,
     pub(crate) owner_lock_hash: Byte32,
     pub(crate) payment_lock_hash: Byte32,
+    pub(crate) fee: Uint64,
+    pub(crate) sudt_fee: Uint128,
 }
 impl RawWithdrawalRequestBuilder {
-    pub const TOTAL_SIZE: usize = 180;
-    pub const FIELD_SIZES: [usize; 9] = [4, 8, 16, 32, 32, 16, 8, 32, 32];
-    pub const FIELD_COUNT: usize = 9;
+    pub const TOTAL_SIZE: usize = 204;
+    pub const FIELD_SIZES: [usize; 11] = [4, 8, 16, 32, 32, 16, 8, 32, 32, 8, 16];
+    pub const FIELD_COUNT: usize = 11;
     pub fn nonce(mut self, v: Uint32) -> Self {
         self.nonce = v;
         self
@@ -6430,6 +6527,14 @@ impl RawWithdrawalRequestBuilder {
         self.payment_lock_hash = v;
         self
     }
+    pub fn fee(mut self, v: Uint64) -> Self {
+        self.fee = v;
+        self
+    }
+    pub fn sudt_fee(mut self, v: Uint128) -> Self {
+        self.sudt_fee = v;
+        self
+    }
 }
 impl molecule::prelude::Builder for RawWithdrawalRequestBuilder {
     type Entity = RawWithdrawalRequest;
@@ -6447,6 +6552,8 @@ impl molecule::prelude::Builder for RawWithdrawalRequestBuilder {
         writer.write_all(self.sell_capacity.as_slice())?;
         writer.write_all(self.owner_lock_hash.as_slice())?;
         writer.write_all(self.payment_lock_hash.as_slice())?;
+        writer.write_all(self.fee.as_slice())?;
+        writer.write_all(self.sudt_fee.as_slice())?;
         Ok(())
     }
     fn build(&self) -> Self::Entity {
@@ -6492,7 +6599,7 @@ impl ::core::default::Default for WithdrawalRequestVec {
     }
 }
 impl WithdrawalRequestVec {
-    pub const ITEM_SIZE: usize = 245;
+    pub const ITEM_SIZE: usize = 269;
     pub fn total_size(&self) -> usize {
         molecule::NUMBER_SIZE * (self.item_count() + 1)
     }
@@ -6576,7 +6683,7 @@ impl<'r> ::core::fmt::Display for WithdrawalRequestVecReader<'r> {
     }
 }
 impl<'r> WithdrawalRequestVecReader<'r> {
-    pub const ITEM_SIZE: usize = 245;
+    pub const ITEM_SIZE: usize = 269;
     pub fn total_size(&self) -> usize {
         molecule::NUMBER_SIZE * (self.item_count() + 1)
     }
@@ -6637,7 +6744,7 @@ impl<'r> molecule::prelude::Reader<'r> for WithdrawalRequestVecReader<'r> {
 #[derive(Debug, Default)]
 pub struct WithdrawalRequestVecBuilder(pub(crate) Vec<WithdrawalRequest>);
 impl WithdrawalRequestVecBuilder {
-    pub const ITEM_SIZE: usize = 245;
+    pub const ITEM_SIZE: usize = 269;
     pub fn set(mut self, v: Vec<WithdrawalRequest>) -> Self {
         self.0 = v;
         self
@@ -6764,20 +6871,21 @@ impl ::core::default::Default for WithdrawalRequest {
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
         ];
         WithdrawalRequest::new_unchecked(v.into())
     }
 }
 impl WithdrawalRequest {
-    pub const TOTAL_SIZE: usize = 245;
-    pub const FIELD_SIZES: [usize; 2] = [180, 65];
+    pub const TOTAL_SIZE: usize = 269;
+    pub const FIELD_SIZES: [usize; 2] = [204, 65];
     pub const FIELD_COUNT: usize = 2;
     pub fn raw(&self) -> RawWithdrawalRequest {
-        RawWithdrawalRequest::new_unchecked(self.0.slice(0..180))
+        RawWithdrawalRequest::new_unchecked(self.0.slice(0..204))
     }
     pub fn signature(&self) -> Signature {
-        Signature::new_unchecked(self.0.slice(180..245))
+        Signature::new_unchecked(self.0.slice(204..269))
     }
     pub fn as_reader<'r>(&'r self) -> WithdrawalRequestReader<'r> {
         WithdrawalRequestReader::new_unchecked(self.as_slice())
@@ -6835,14 +6943,14 @@ impl<'r> ::core::fmt::Display for WithdrawalRequestReader<'r> {
     }
 }
 impl<'r> WithdrawalRequestReader<'r> {
-    pub const TOTAL_SIZE: usize = 245;
-    pub const FIELD_SIZES: [usize; 2] = [180, 65];
+    pub const TOTAL_SIZE: usize = 269;
+    pub const FIELD_SIZES: [usize; 2] = [204, 65];
     pub const FIELD_COUNT: usize = 2;
     pub fn raw(&self) -> RawWithdrawalRequestReader<'r> {
-        RawWithdrawalRequestReader::new_unchecked(&self.as_slice()[0..180])
+        RawWithdrawalRequestReader::new_unchecked(&self.as_slice()[0..204])
     }
     pub fn signature(&self) -> SignatureReader<'r> {
-        SignatureReader::new_unchecked(&self.as_slice()[180..245])
+        SignatureReader::new_unchecked(&self.as_slice()[204..269])
     }
 }
 impl<'r> molecule::prelude::Reader<'r> for WithdrawalRequestReader<'r> {
@@ -6872,8 +6980,8 @@ pub struct WithdrawalRequestBuilder {
     pub(crate) signature: Signature,
 }
 impl WithdrawalRequestBuilder {
-    pub const TOTAL_SIZE: usize = 245;
-    pub const FIELD_SIZES: [usize; 2] = [180, 65];
+    pub const TOTAL_SIZE: usize = 269;
+    pub const FIELD_SIZES: [usize; 2] = [204, 65];
     pub const FIELD_COUNT: usize = 2;
     pub fn raw(mut self, v: RawWithdrawalRequest) -> Self {
         self.raw = v;
@@ -2060,6 +2060,10 @@ impl ::core::fmt::Display for TxReceipt {
         write!(f, ", {}: {}", "post_state", self.post_state())?;
         write!(f, ", {}: {}", "read_data_hashes", self.read_data_hashes())?;
         write!(f, ", {}: {}", "logs", self.logs())?;
+        write!(f, ", {}: {}", "used_cycles", self.used_cycles())?;
+        write!(f, ", {}: {}", "paid_fee", self.paid_fee())?;
+        write!(f, ", {}: {}", "refunded_fee", self.refunded_fee())?;
+        write!(f, ", {}: {}", "return_data", self.return_data())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -2070,16 +2074,18 @@ impl ::core::fmt::Display for TxReceipt {
 impl ::core::default::Default for TxReceipt {
     fn default() -> Self {
         let v: Vec<u8> = vec![
-            96, 0, 0, 0, 20, 0, 0, 0, 52, 0, 0, 0, 88, 0, 0, 0, 92, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            156, 0, 0, 0, 36, 0, 0, 0, 68, 0, 0, 0, 104, 0, 0, 0, 108, 0, 0, 0, 112, 0, 0, 0, 120,
+            0, 0, 0, 136, 0, 0, 0, 152, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0,
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         ];
         TxReceipt::new_unchecked(v.into())
     }
 }
 impl TxReceipt {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 8;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -2117,11 +2123,35 @@ impl TxReceipt {
     pub fn logs(&self) -> LogItemVec {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        LogItemVec::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn used_cycles(&self) -> Uint64 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        let end = molecule::unpack_number(&slice[24..]) as usize;
+        Uint64::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn paid_fee(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[24..]) as usize;
+        let end = molecule::unpack_number(&slice[28..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn refunded_fee(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[28..]) as usize;
+        let end = molecule::unpack_number(&slice[32..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn return_data(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[32..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[20..]) as usize;
-            LogItemVec::new_unchecked(self.0.slice(start..end))
+            let end = molecule::unpack_number(&slice[36..]) as usize;
+            Bytes::new_unchecked(self.0.slice(start..end))
         } else {
-            LogItemVec::new_unchecked(self.0.slice(start..))
+            Bytes::new_unchecked(self.0.slice(start..))
         }
     }
     pub fn as_reader<'r>(&'r self) -> TxReceiptReader<'r> {
@@ -2155,6 +2185,10 @@ impl molecule::prelude::Entity for TxReceipt {
             .post_state(self.post_state())
             .read_data_hashes(self.read_data_hashes())
             .logs(self.logs())
+            .used_cycles(self.used_cycles())
+            .paid_fee(self.paid_fee())
+            .refunded_fee(self.refunded_fee())
+            .return_data(self.return_data())
     }
 }
 #[derive(Clone, Copy)]
@@ -2180,6 +2214,10 @@ impl<'r> ::core::fmt::Display for TxReceiptReader<'r> {
         write!(f, ", {}: {}", "post_state", self.post_state())?;
         write!(f, ", {}: {}", "read_data_hashes", self.read_data_hashes())?;
         write!(f, ", {}: {}", "logs", self.logs())?;
+        write!(f, ", {}: {}", "used_cycles", self.used_cycles())?;
+        write!(f, ", {}: {}", "paid_fee", self.paid_fee())?;
+        write!(f, ", {}: {}", "refunded_fee", self.refunded_fee())?;
+        write!(f, ", {}: {}", "return_data", self.return_data())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -2188,7 +2226,7 @@ impl<'r> ::core::fmt::Display for TxReceiptReader<'r> {
     }
 }
 impl<'r> TxReceiptReader<'r> {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 8;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -2226,11 +2264,35 @@ impl<'r> TxReceiptReader<'r> {
     pub fn logs(&self) -> LogItemVecReader<'r> {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        LogItemVecReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn used_cycles(&self) -> Uint64Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        let end = molecule::unpack_number(&slice[24..]) as usize;
+        Uint64Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn paid_fee(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[24..]) as usize;
+        let end = molecule::unpack_number(&slice[28..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn refunded_fee(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[28..]) as usize;
+        let end = molecule::unpack_number(&slice[32..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn return_data(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[32..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[20..]) as usize;
-            LogItemVecReader::new_unchecked(&self.as_slice()[start..end])
+            let end = molecule::unpack_number(&slice[36..]) as usize;
+            BytesReader::new_unchecked(&self.as_slice()[start..end])
         } else {
-            LogItemVecReader::new_unchecked(&self.as_slice()[start..])
+            BytesReader::new_unchecked(&self.as_slice()[start..])
         }
     }
 }
@@ -2289,6 +2351,10 @@ impl<'r> molecule::prelude::Reader<'r> for TxReceiptReader<'r> {
         AccountMerkleStateReader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
         Byte32VecReader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
         LogItemVecReader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Uint64Reader::verify(&slice[offsets[4]..offsets[5]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[5]..offsets[6]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[6]..offsets[7]], compatible)?;
+        BytesReader::verify(&slice[offsets[7]..offsets[8]], compatible)?;
         Ok(())
     }
 }
@@ -2298,9 +2364,13 @@ pub struct TxReceiptBuilder {
     pub(crate) post_state: AccountMerkleState,
     pub(crate) read_data_hashes: Byte32Vec,
     pub(crate) logs: LogItemVec,
+    pub(crate) used_cycles: Uint64,
+    pub(crate) paid_fee: Uint128,
+    pub(crate) refunded_fee: Uint128,
+    pub(crate) return_data: Bytes,
 }
 impl TxReceiptBuilder {
-    pub const FIELD_COUNT: usize = 4;
+    pub const FIELD_COUNT: usize = 8;
     pub fn tx_witness_hash(mut self, v: Byte32) -> Self {
         self.tx_witness_hash = v;
         self
@@ -2317,6 +2387,22 @@ impl TxReceiptBuilder {
         self.logs = v;
         self
     }
+    pub fn used_cycles(mut self, v: Uint64) -> Self {
+        self.used_cycles = v;
+        self
+    }
+    pub fn paid_fee(mut self, v: Uint128) -> Self {
+        self.paid_fee = v;
+        self
+    }
+    pub fn refunded_fee(mut self, v: Uint128) -> Self {
+        self.refunded_fee = v;
+        self
+    }
+    pub fn return_data(mut self, v: Bytes) -> Self {
+        self.return_data = v;
+        self
+    }
 }
 impl molecule::prelude::Builder for TxReceiptBuilder {
     type Entity = TxReceipt;
@@ -2327,6 +2413,10 @@ impl molecule::prelude::Builder for TxReceiptBuilder {
             + self.post_state.as_slice().len()
             + self.read_data_hashes.as_slice().len()
             + self.logs.as_slice().len()
+            + self.used_cycles.as_slice().len()
+            + self.paid_fee.as_slice().len()
+            + self.refunded_fee.as_slice().len()
+            + self.return_data.as_slice().len()
     }
     fn write<W: ::molecule::io::Write>(&self, writer: &mut W) -> ::molecule::io::Result<()> {
         let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
@@ -2339,6 +2429,14 @@ impl molecule::prelude::Builder for TxReceiptBuilder {
         total_size += self.read_data_hashes.as_slice().len();
         offsets.push(total_size);
         total_size += self.logs.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.used_cycles.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.paid_fee.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.refunded_fee.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.return_data.as_slice().len();
         writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
         for offset in offsets.into_iter() {
             writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
@@ -2347,6 +2445,10 @@ impl molecule::prelude::Builder for TxReceiptBuilder {
         writer.write_all(self.post_state.as_slice())?;
         writer.write_all(self.read_data_hashes.as_slice())?;
         writer.write_all(self.logs.as_slice())?;
+        writer.write_all(self.used_cycles.as_slice())?;
+        writer.write_all(self.paid_fee.as_slice())?;
+        writer.write_all(self.refunded_fee.as_slice())?;
+        writer.write_all(self.return_data.as_slice())?;
         Ok(())
     }
     fn build(&self) -> Self::Entity {
@@ -2719,3 +2821,472 @@ impl molecule::prelude::Builder for SMTLeafNodeBuilder {
         SMTLeafNode::new_unchecked(inner.into())
     }
 }
+#[derive(Clone)]
+pub struct BlockEconomics(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for BlockEconomics {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for BlockEconomics {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for BlockEconomics {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "block_producer_id", self.block_producer_id())?;
+        write!(f, ", {}: {}", "tx_count", self.tx_count())?;
+        write!(f, ", {}: {}", "withdrawal_count", self.withdrawal_count())?;
+        write!(f, ", {}: {}", "used_cycles", self.used_cycles())?;
+        write!(f, ", {}: {}", "paid_fees", self.paid_fees())?;
+        write!(f, ", {}: {}", "refunded_fees", self.refunded_fees())?;
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for BlockEconomics {
+    fn default() -> Self {
+        let v: Vec<u8> = vec![
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        BlockEconomics::new_unchecked(v.into())
+    }
+}
+impl BlockEconomics {
+    pub const TOTAL_SIZE: usize = 52;
+    pub const FIELD_SIZES: [usize; 6] = [4, 4, 4, 8, 16, 16];
+    pub const FIELD_COUNT: usize = 6;
+    pub fn block_producer_id(&self) -> Uint32 {
+        Uint32::new_unchecked(self.0.slice(0..4))
+    }
+    pub fn tx_count(&self) -> Uint32 {
+        Uint32::new_unchecked(self.0.slice(4..8))
+    }
+    pub fn withdrawal_count(&self) -> Uint32 {
+        Uint32::new_unchecked(self.0.slice(8..12))
+    }
+    pub fn used_cycles(&self) -> Uint64 {
+        Uint64::new_unchecked(self.0.slice(12..20))
+    }
+    pub fn paid_fees(&self) -> Uint128 {
+        Uint128::new_unchecked(self.0.slice(20..36))
+    }
+    pub fn refunded_fees(&self) -> Uint128 {
+        Uint128::new_unchecked(self.0.slice(36..52))
+    }
+    pub fn as_reader<'r>(&'r self) -> BlockEconomicsReader<'r> {
+        BlockEconomicsReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for BlockEconomics {
+    type Builder = BlockEconomicsBuilder;
+    const NAME: &'static str = "BlockEconomics";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        BlockEconomics(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        BlockEconomicsReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        BlockEconomicsReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .block_producer_id(self.block_producer_id())
+            .tx_count(self.tx_count())
+            .withdrawal_count(self.withdrawal_count())
+            .used_cycles(self.used_cycles())
+            .paid_fees(self.paid_fees())
+            .refunded_fees(self.refunded_fees())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct BlockEconomicsReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for BlockEconomicsReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for BlockEconomicsReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for BlockEconomicsReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "block_producer_id", self.block_producer_id())?;
+        write!(f, ", {}: {}", "tx_count", self.tx_count())?;
+        write!(f, ", {}: {}", "withdrawal_count", self.withdrawal_count())?;
+        write!(f, ", {}: {}", "used_cycles", self.used_cycles())?;
+        write!(f, ", {}: {}", "paid_fees", self.paid_fees())?;
+        write!(f, ", {}: {}", "refunded_fees", self.refunded_fees())?;
+        write!(f, " }}")
+    }
+}
+impl<'r> BlockEconomicsReader<'r> {
+    pub const TOTAL_SIZE: usize = 52;
+    pub const FIELD_SIZES: [usize; 6] = [4, 4, 4, 8, 16, 16];
+    pub const FIELD_COUNT: usize = 6;
+    pub fn block_producer_id(&self) -> Uint32Reader<'r> {
+        Uint32Reader::new_unchecked(&self.as_slice()[0..4])
+    }
+    pub fn tx_count(&self) -> Uint32Reader<'r> {
+        Uint32Reader::new_unchecked(&self.as_slice()[4..8])
+    }
+    pub fn withdrawal_count(&self) -> Uint32Reader<'r> {
+        Uint32Reader::new_unchecked(&self.as_slice()[8..12])
+    }
+    pub fn used_cycles(&self) -> Uint64Reader<'r> {
+        Uint64Reader::new_unchecked(&self.as_slice()[12..20])
+    }
+    pub fn paid_fees(&self) -> Uint128Reader<'r> {
+        Uint128Reader::new_unchecked(&self.as_slice()[20..36])
+    }
+    pub fn refunded_fees(&self) -> Uint128Reader<'r> {
+        Uint128Reader::new_unchecked(&self.as_slice()[36..52])
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for BlockEconomicsReader<'r> {
+    type Entity = BlockEconomics;
+    const NAME: &'static str = "BlockEconomicsReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        BlockEconomicsReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], _compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len != Self::TOTAL_SIZE {
+            return ve!(Self, TotalSizeNotMatch, Self::TOTAL_SIZE, slice_len);
+        }
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct BlockEconomicsBuilder {
+    pub(crate) block_producer_id: Uint32,
+    pub(crate) tx_count: Uint32,
+    pub(crate) withdrawal_count: Uint32,
+    pub(crate) used_cycles: Uint64,
+    pub(crate) paid_fees: Uint128,
+    pub(crate) refunded_fees: Uint128,
+}
+impl BlockEconomicsBuilder {
+    pub const TOTAL_SIZE: usize = 52;
+    pub const FIELD_SIZES: [usize; 6] = [4, 4, 4, 8, 16, 16];
+    pub const FIELD_COUNT: usize = 6;
+    pub fn block_producer_id(mut self, v: Uint32) -> Self {
+        self.block_producer_id = v;
+        self
+    }
+    pub fn tx_count(mut self, v: Uint32) -> Self {
+        self.tx_count = v;
+        self
+    }
+    pub fn withdrawal_count(mut self, v: Uint32) -> Self {
+        self.withdrawal_count = v;
+        self
+    }
+    pub fn used_cycles(mut self, v: Uint64) -> Self {
+        self.used_cycles = v;
+        self
+    }
+    pub fn paid_fees(mut self, v: Uint128) -> Self {
+        self.paid_fees = v;
+        self
+    }
+    pub fn refunded_fees(mut self, v: Uint128) -> Self {
+        self.refunded_fees = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for BlockEconomicsBuilder {
+    type Entity = BlockEconomics;
+    const NAME: &'static str = "BlockEconomicsBuilder";
+    fn expected_length(&self) -> usize {
+        Self::TOTAL_SIZE
+    }
+    fn write<W: ::molecule::io::Write>(&self, writer: &mut W) -> ::molecule::io::Result<()> {
+        writer.write_all(self.block_producer_id.as_slice())?;
+        writer.write_all(self.tx_count.as_slice())?;
+        writer.write_all(self.withdrawal_count.as_slice())?;
+        writer.write_all(self.used_cycles.as_slice())?;
+        writer.write_all(self.paid_fees.as_slice())?;
+        writer.write_all(self.refunded_fees.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        BlockEconomics::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct RollupActionRecord(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for RollupActionRecord {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for RollupActionRecord {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for RollupActionRecord {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "action_type", self.action_type())?;
+        write!(f, ", {}: {}", "prev_status", self.prev_status())?;
+        write!(f, ", {}: {}", "post_status", self.post_status())?;
+        write!(f, ", {}: {}", "challenge_target", self.challenge_target())?;
+        write!(f, ", {}: {}", "l1_block_number", self.l1_block_number())?;
+        write!(f, ", {}: {}", "l1_block_hash", self.l1_block_hash())?;
+        write!(f, ", {}: {}", "l1_tx_hash", self.l1_tx_hash())?;
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for RollupActionRecord {
+    fn default() -> Self {
+        let v: Vec<u8> = vec![
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        RollupActionRecord::new_unchecked(v.into())
+    }
+}
+impl RollupActionRecord {
+    pub const TOTAL_SIZE: usize = 112;
+    pub const FIELD_SIZES: [usize; 7] = [1, 1, 1, 37, 8, 32, 32];
+    pub const FIELD_COUNT: usize = 7;
+    pub fn action_type(&self) -> Byte {
+        Byte::new_unchecked(self.0.slice(0..1))
+    }
+    pub fn prev_status(&self) -> Byte {
+        Byte::new_unchecked(self.0.slice(1..2))
+    }
+    pub fn post_status(&self) -> Byte {
+        Byte::new_unchecked(self.0.slice(2..3))
+    }
+    pub fn challenge_target(&self) -> ChallengeTarget {
+        ChallengeTarget::new_unchecked(self.0.slice(3..40))
+    }
+    pub fn l1_block_number(&self) -> Uint64 {
+        Uint64::new_unchecked(self.0.slice(40..48))
+    }
+    pub fn l1_block_hash(&self) -> Byte32 {
+        Byte32::new_unchecked(self.0.slice(48..80))
+    }
+    pub fn l1_tx_hash(&self) -> Byte32 {
+        Byte32::new_unchecked(self.0.slice(80..112))
+    }
+    pub fn as_reader<'r>(&'r self) -> RollupActionRecordReader<'r> {
+        RollupActionRecordReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for RollupActionRecord {
+    type Builder = RollupActionRecordBuilder;
+    const NAME: &'static str = "RollupActionRecord";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        RollupActionRecord(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        RollupActionRecordReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        RollupActionRecordReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .action_type(self.action_type())
+            .prev_status(self.prev_status())
+            .post_status(self.post_status())
+            .challenge_target(self.challenge_target())
+            .l1_block_number(self.l1_block_number())
+            .l1_block_hash(self.l1_block_hash())
+            .l1_tx_hash(self.l1_tx_hash())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct RollupActionRecordReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for RollupActionRecordReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for RollupActionRecordReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for RollupActionRecordReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "action_type", self.action_type())?;
+        write!(f, ", {}: {}", "prev_status", self.prev_status())?;
+        write!(f, ", {}: {}", "post_status", self.post_status())?;
+        write!(f, ", {}: {}", "challenge_target", self.challenge_target())?;
+        write!(f, ", {}: {}", "l1_block_number", self.l1_block_number())?;
+        write!(f, ", {}: {}", "l1_block_hash", self.l1_block_hash())?;
+        write!(f, ", {}: {}", "l1_tx_hash", self.l1_tx_hash())?;
+        write!(f, " }}")
+    }
+}
+impl<'r> RollupActionRecordReader<'r> {
+    pub const TOTAL_SIZE: usize = 112;
+    pub const FIELD_SIZES: [usize; 7] = [1, 1, 1, 37, 8, 32, 32];
+    pub const FIELD_COUNT: usize = 7;
+    pub fn action_type(&self) -> ByteReader<'r> {
+        ByteReader::new_unchecked(&self.as_slice()[0..1])
+    }
+    pub fn prev_status(&self) -> ByteReader<'r> {
+        ByteReader::new_unchecked(&self.as_slice()[1..2])
+    }
+    pub fn post_status(&self) -> ByteReader<'r> {
+        ByteReader::new_unchecked(&self.as_slice()[2..3])
+    }
+    pub fn challenge_target(&self) -> ChallengeTargetReader<'r> {
+        ChallengeTargetReader::new_unchecked(&self.as_slice()[3..40])
+    }
+    pub fn l1_block_number(&self) -> Uint64Reader<'r> {
+        Uint64Reader::new_unchecked(&self.as_slice()[40..48])
+    }
+    pub fn l1_block_hash(&self) -> Byte32Reader<'r> {
+        Byte32Reader::new_unchecked(&self.as_slice()[48..80])
+    }
+    pub fn l1_tx_hash(&self) -> Byte32Reader<'r> {
+        Byte32Reader::new_unchecked(&self.as_slice()[80..112])
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for RollupActionRecordReader<'r> {
+    type Entity = RollupActionRecord;
+    const NAME: &'static str = "RollupActionRecordReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        RollupActionRecordReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], _compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len != Self::TOTAL_SIZE {
+            return ve!(Self, TotalSizeNotMatch, Self::TOTAL_SIZE, slice_len);
+        }
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct RollupActionRecordBuilder {
+    pub(crate) action_type: Byte,
+    pub(crate) prev_status: Byte,
+    pub(crate) post_status: Byte,
+    pub(crate) challenge_target: ChallengeTarget,
+    pub(crate) l1_block_number: Uint64,
+    pub(crate) l1_block_hash: Byte32,
+    pub(crate) l1_tx_hash: Byte32,
+}
+impl RollupActionRecordBuilder {
+    pub const TOTAL_SIZE: usize = 112;
+    pub const FIELD_SIZES: [usize; 7] = [1, 1, 1, 37, 8, 32, 32];
+    pub const FIELD_COUNT: usize = 7;
+    pub fn action_type(mut self, v: Byte) -> Self {
+        self.action_type = v;
+        self
+    }
+    pub fn prev_status(mut self, v: Byte) -> Self {
+        self.prev_status = v;
+        self
+    }
+    pub fn post_status(mut self, v: Byte) -> Self {
+        self.post_status = v;
+        self
+    }
+    pub fn challenge_target(mut self, v: ChallengeTarget) -> Self {
+        self.challenge_target = v;
+        self
+    }
+    pub fn l1_block_number(mut self, v: Uint64) -> Self {
+        self.l1_block_number = v;
+        self
+    }
+    pub fn l1_block_hash(mut self, v: Byte32) -> Self {
+        self.l1_block_hash = v;
+        self
+    }
+    pub fn l1_tx_hash(mut self, v: Byte32) -> Self {
+        self.l1_tx_hash = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for RollupActionRecordBuilder {
+    type Entity = RollupActionRecord;
+    const NAME: &'static str = "RollupActionRecordBuilder";
+    fn expected_length(&self) -> usize {
+        Self::TOTAL_SIZE
+    }
+    fn write<W: ::molecule::io::Write>(&self, writer: &mut W) -> ::molecule::io::Result<()> {
+        writer.write_all(self.action_type.as_slice())?;
+        writer.write_all(self.prev_status.as_slice())?;
+        writer.write_all(self.post_status.as_slice())?;
+        writer.write_all(self.challenge_target.as_slice())?;
+        writer.write_all(self.l1_block_number.as_slice())?;
+        writer.write_all(self.l1_block_hash.as_slice())?;
+        writer.write_all(self.l1_tx_hash.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        RollupActionRecord::new_unchecked(inner.into())
+    }
+}
@@ -14,4 +14,102 @@ pub struct RunResult {
     pub read_data: HashMap<H256, usize>,
     // log data
     pub logs: Vec<LogItem>,
+    // cycles used by the backend
+    pub used_cycles: u64,
+    // syscalls recorded in the tracing mode, None if the tracing is off
+    pub trace: Option<Vec<TraceStep>>,
+}
+
+/// A syscall recorded by the tracing mode of the generator
+#[derive(Debug, Clone)]
+pub enum TraceEvent {
+    Load { key: H256, value: H256 },
+    Store { key: H256, value: H256 },
+    CreateAccount { account_id: u32, script_hash: H256 },
+    Log(LogItem),
+    SetReturnData(Vec<u8>),
+    /// other syscalls by the syscall number
+    Syscall(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    /// cycles used by the backend before the syscall
+    pub cycles: u64,
+    pub event: TraceEvent,
+}
+
+/// State keys read and written by the execution of a tx, the fee settlement is excluded
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxAccessList {
+    /// sorted keys
+    pub reads: Vec<H256>,
+    /// sorted keys
+    pub writes: Vec<H256>,
+}
+
+impl TxAccessList {
+    pub fn from_run_result(run_result: &RunResult) -> Self {
+        let mut reads: Vec<H256> = run_result.read_values.keys().copied().collect();
+        reads.sort_unstable();
+        let mut writes: Vec<H256> = run_result.write_values.keys().copied().collect();
+        writes.sort_unstable();
+        TxAccessList { reads, writes }
+    }
+
+    /// Txs conflict if one writes a key the other accesses,
+    /// conflicting txs must be executed in the order of the block
+    pub fn conflicts_with(&self, other: &TxAccessList) -> bool {
+        let contains = |keys: &[H256], key: &H256| keys.binary_search(key).is_ok();
+        self.writes
+            .iter()
+            .any(|key| contains(&other.reads, key) || contains(&other.writes, key))
+            || self.reads.iter().any(|key| contains(&other.writes, key))
+    }
+}
+
+/// Storage owned by an account, a slot is used while its value is non-zero
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub slots: u64,
+    pub data_bytes: u64,
+}
+
+impl StorageUsage {
+    /// Usage after the change, saturated at zero, so it can't be undone by the reverted delta
+    pub fn apply(self, delta: StorageDelta) -> Self {
+        let apply = |value: u64, delta: i64| {
+            if delta >= 0 {
+                value.saturating_add(delta as u64)
+            } else {
+                value.saturating_sub((-i128::from(delta)) as u64)
+            }
+        };
+        StorageUsage {
+            slots: apply(self.slots, delta.slots),
+            data_bytes: apply(self.data_bytes, delta.data_bytes),
+        }
+    }
+}
+
+/// Change of the storage owned by an account
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageDelta {
+    pub slots: i64,
+    pub data_bytes: i64,
+}
+
+impl StorageDelta {
+    pub fn merge(&mut self, other: StorageDelta) {
+        self.slots += other.slots;
+        self.data_bytes += other.data_bytes;
+    }
+
+    /// The delta of undoing the change
+    pub fn revert(self) -> Self {
+        StorageDelta {
+            slots: -self.slots,
+            data_bytes: -self.data_bytes,
+        }
+    }
 }
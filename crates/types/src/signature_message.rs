@@ -5,6 +5,8 @@ use crate::packed::{RawL2Transaction, RawWithdrawalRequest};
 use crate::prelude::*;
 
 impl RawL2Transaction {
+    /// Message to sign, the rollup type script hash scopes the signature to a rollup deployment
+    /// so a tx signed for a testnet can't be replayed on the mainnet
     pub fn calc_message(
         &self,
         rollup_type_script_hash: &H256,
@@ -23,6 +25,7 @@ impl RawL2Transaction {
 }
 
 impl RawWithdrawalRequest {
+    /// Message to sign, scoped to a rollup deployment like the tx message
     pub fn calc_message(&self, rollup_type_script_hash: &H256) -> H256 {
         let mut hasher = new_blake2b();
         hasher.update(rollup_type_script_hash.as_slice());